@@ -0,0 +1,65 @@
+//! Timings for the HTML parsing hot paths, against the 90435 fixture.
+//!
+//! Run with `cargo bench`; pass a name filter to run only matching
+//! benchmarks (`cargo bench -- reviews`). Each benchmark is warmed up, then
+//! timed over enough iterations to fill roughly a second, and the mean time
+//! per iteration is reported.
+
+use novel_finder::models::ChapterTitleStorage;
+use novel_finder::scraper::novel_page::{parse_novel_from_html, parse_page};
+use novel_finder::scraper::reviews::{parse_reviews_from_html, MAX_REVIEWS};
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Rough time spent timing each benchmark.
+const TARGET: Duration = Duration::from_secs(1);
+
+fn main() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("src/scraper/testdata/novel_page_90435.html");
+    let html = std::fs::read_to_string(&path).expect("fixture is readable");
+    // `cargo bench` passes `--bench`; anything else is a name filter
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with("--"));
+
+    let benches: [(&str, &dyn Fn()); 3] = [
+        ("parse_novel_from_html", &|| {
+            let novel = parse_novel_from_html(black_box(&html), 90435, ChapterTitleStorage::All);
+            black_box(novel.unwrap());
+        }),
+        ("parse_reviews_from_html", &|| {
+            black_box(parse_reviews_from_html(black_box(&html), MAX_REVIEWS).unwrap());
+        }),
+        ("parse_page", &|| {
+            let page = parse_page(black_box(&html), 90435, MAX_REVIEWS, ChapterTitleStorage::All);
+            black_box(page.unwrap());
+        }),
+    ];
+    for (name, bench) in benches {
+        if filter.as_deref().is_some_and(|filter| !name.contains(filter)) {
+            continue;
+        }
+        let (iterations, elapsed) = time(bench);
+        println!(
+            "{:<24} {:>10.3} ms/iter ({} iterations)",
+            name,
+            elapsed.as_secs_f64() * 1_000.0 / iterations as f64,
+            iterations
+        );
+    }
+}
+
+/// Run `bench` once to warm up and estimate its cost, then enough times to
+/// take about `TARGET`. Returns the iteration count and the total time.
+fn time(bench: &dyn Fn()) -> (u32, Duration) {
+    let started = Instant::now();
+    bench();
+    let once = started.elapsed().max(Duration::from_micros(1));
+    let iterations = (TARGET.as_secs_f64() / once.as_secs_f64()).clamp(1.0, 10_000.0) as u32;
+
+    let started = Instant::now();
+    for _ in 0..iterations {
+        bench();
+    }
+    (iterations, started.elapsed())
+}
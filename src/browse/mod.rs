@@ -0,0 +1,66 @@
+//! Interactive browsing of results in the terminal.
+//!
+//! The browser lists the results next to the details of the selected one,
+//! and lets the user open novels in the web browser and mark them; marked
+//! results are saved as a results JSON file on quit. It is behind the
+//! `interactive` cargo feature and only runs on a terminal; otherwise the
+//! usual results table is printed instead.
+
+pub mod open;
+#[cfg(feature = "interactive")]
+mod tui;
+
+use crate::config::GroupBy;
+use crate::models::NovelScore;
+use crate::output;
+use anyhow::Result;
+use std::io::IsTerminal;
+use std::path::Path;
+
+/// Browse `results` interactively, saving any marked results to
+/// `marked_path` on quit and opening novels with `browser_command` when
+/// given. Falls back to the results table, grouped by `group_by`, when stdin
+/// or stdout isn't a terminal.
+pub fn browse(
+    results: &[NovelScore],
+    marked_path: &Path,
+    browser_command: Option<&[String]>,
+    group_by: GroupBy,
+) -> Result<()> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        tracing::debug!("Not running on a terminal; printing the results table");
+        output::print_results(results, false, group_by);
+        return Ok(());
+    }
+    run(results, marked_path, browser_command, group_by)
+}
+
+#[cfg(feature = "interactive")]
+fn run(
+    results: &[NovelScore],
+    marked_path: &Path,
+    browser_command: Option<&[String]>,
+    _group_by: GroupBy,
+) -> Result<()> {
+    let marked = tui::run(results, browser_command)?;
+    if !marked.is_empty() {
+        tui::save_marked(&marked, marked_path)?;
+        println!("Saved {} marked novels to {}", marked.len(), marked_path.display());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "interactive"))]
+fn run(
+    results: &[NovelScore],
+    _marked_path: &Path,
+    _browser_command: Option<&[String]>,
+    group_by: GroupBy,
+) -> Result<()> {
+    tracing::warn!(
+        "This build of novel-finder has no interactive browser; rebuild with \
+         `cargo build --features interactive`"
+    );
+    output::print_results(results, false, group_by);
+    Ok(())
+}
@@ -0,0 +1,188 @@
+//! Opening novels in the web browser.
+//!
+//! URLs open in the system's default browser, or with `[output]
+//! browser_command` when one is configured. `--open` opens the top results
+//! after a run, a short pause apart; the interactive browser opens the
+//! selected result instead.
+
+use crate::models::NovelScore;
+use anyhow::{Context, Result};
+use std::io::{BufRead, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Pause between opening URLs, so a batch of tabs doesn't land all at once.
+const OPEN_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Opening more results than this asks for confirmation first.
+const CONFIRM_ABOVE: usize = 10;
+
+/// Open a URL with `browser_command` (a program and its arguments, the URL
+/// appended), or in the default web browser without one.
+pub fn open_url(url: &str, browser_command: Option<&[String]>) -> Result<()> {
+    let mut command = match browser_command {
+        Some([program, args @ ..]) => {
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        }
+        _ if cfg!(target_os = "macos") => Command::new("open"),
+        _ if cfg!(windows) => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        _ => Command::new("xdg-open"),
+    };
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start a web browser")?;
+    Ok(())
+}
+
+/// Open the top `count` results' novels, asking first when that's more than
+/// [`CONFIRM_ABOVE`]. Nothing is opened off a terminal or under CI. Returns
+/// how many were opened.
+pub fn open_top(
+    results: &[NovelScore],
+    count: usize,
+    browser_command: Option<&[String]>,
+) -> Result<usize> {
+    if is_ci(std::env::var("CI").ok().as_deref()) || !std::io::stdout().is_terminal() {
+        tracing::info!("Not opening results in the browser: not running on a terminal");
+        return Ok(0);
+    }
+    let urls = top_urls(results, count);
+    if urls.len() > CONFIRM_ABOVE && !confirm(&format!("Open {} novels?", urls.len()))? {
+        return Ok(0);
+    }
+    Ok(open_each(&urls, OPEN_INTERVAL, |url| {
+        open_url(url, browser_command)
+    }))
+}
+
+/// The URLs of the first `count` novels among `results`, each novel once
+/// even when it scored under several profiles.
+fn top_urls(results: &[NovelScore], count: usize) -> Vec<&str> {
+    let mut urls: Vec<&str> = Vec::new();
+    for score in results {
+        if urls.len() == count {
+            break;
+        }
+        if !urls.contains(&score.novel.url.as_str()) {
+            urls.push(&score.novel.url);
+        }
+    }
+    urls
+}
+
+/// Open each URL with `open`, pausing `interval` between them. A URL that
+/// fails to open is logged and skipped. Returns how many were opened.
+fn open_each(urls: &[&str], interval: Duration, mut open: impl FnMut(&str) -> Result<()>) -> usize {
+    let mut opened = 0;
+    for (index, url) in urls.iter().enumerate() {
+        if index > 0 {
+            std::thread::sleep(interval);
+        }
+        match open(url) {
+            Ok(()) => opened += 1,
+            Err(e) => tracing::warn!("Failed to open {}: {:#}", url, e),
+        }
+    }
+    opened
+}
+
+/// Whether the `CI` environment variable says this is a CI run.
+fn is_ci(value: Option<&str>) -> bool {
+    value.is_some_and(|value| {
+        !matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "" | "0" | "false"
+        )
+    })
+}
+
+/// Ask a yes/no question on stderr, defaulting to no.
+fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("Failed to read an answer from stdin")?;
+    Ok(is_yes(&answer))
+}
+
+/// Whether an answer to a yes/no question means yes.
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, test_score};
+
+    fn score(id: u64, profile: Option<&str>) -> NovelScore {
+        let mut score = test_score(test_novel(id), 0.5);
+        score.profile = profile.map(String::from);
+        score
+    }
+
+    #[test]
+    fn test_top_urls_skips_repeat_novels() {
+        let results = [
+            score(1, Some("cozy")),
+            score(1, Some("dark")),
+            score(2, None),
+            score(3, None),
+        ];
+        assert_eq!(
+            top_urls(&results, 2),
+            [
+                "https://www.royalroad.com/fiction/1",
+                "https://www.royalroad.com/fiction/2"
+            ]
+        );
+        assert_eq!(top_urls(&results, 10).len(), 3);
+        assert!(top_urls(&results, 0).is_empty());
+    }
+
+    #[test]
+    fn test_open_each_continues_past_failures() {
+        let mut opened = Vec::new();
+        let count = open_each(&["a", "b", "c"], Duration::ZERO, |url| {
+            opened.push(url.to_string());
+            if url == "b" {
+                anyhow::bail!("no browser");
+            }
+            Ok(())
+        });
+        assert_eq!(count, 2);
+        assert_eq!(opened, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_is_ci() {
+        assert!(!is_ci(None));
+        for value in ["", "0", "false", "FALSE"] {
+            assert!(!is_ci(Some(value)), "{:?}", value);
+        }
+        for value in ["true", "1", "yes"] {
+            assert!(is_ci(Some(value)), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_is_yes() {
+        assert!(is_yes("y\n"));
+        assert!(is_yes(" YES \r\n"));
+        assert!(!is_yes("\n"));
+        assert!(!is_yes("no"));
+    }
+}
@@ -0,0 +1,415 @@
+//! The full-screen result browser.
+//!
+//! The terminal is put in raw mode with `stty` and drawn with ANSI escape
+//! codes on the alternate screen, redrawing the whole frame after each key.
+
+use super::open;
+use crate::models::NovelScore;
+use crate::output;
+use crate::persist::{self, MarkedResults};
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// The key bindings, shown in the status line.
+const HELP: &str = "↑/↓ select  PgUp/PgDn scroll  o open  m mark  q quit";
+
+/// Run the browser until the user quits, returning the marked results in
+/// their listed order. Novels open with `browser_command`, if given.
+pub fn run<'a>(
+    results: &'a [NovelScore],
+    browser_command: Option<&[String]>,
+) -> Result<Vec<&'a NovelScore>> {
+    let mut browser = Browser::new(results);
+    browser.browser_command = browser_command.map(<[String]>::to_vec);
+    let terminal = RawTerminal::enter()?;
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    let mut buffer = [0u8; 16];
+    loop {
+        let (rows, cols) = terminal.size();
+        stdout.write_all(browser.render(rows, cols).as_bytes())?;
+        stdout.flush()?;
+
+        let read = stdin.read(&mut buffer).context("Failed to read a key")?;
+        if read == 0 {
+            break;
+        }
+        let keys = parse_keys(&buffer[..read]);
+        if keys.iter().any(|key| !browser.handle(*key)) {
+            break;
+        }
+    }
+    drop(terminal);
+    Ok(browser.marked())
+}
+
+/// Save results as a results JSON file, which `browse` and `diff` can read.
+pub fn save_marked(marked: &[&NovelScore], path: &Path) -> Result<()> {
+    let json = persist::to_string_pretty(&MarkedResults { results: marked })?;
+    std::fs::write(path, json)
+        .with_context(|| format!("Failed to write marked results to {}", path.display()))
+}
+
+/// A key press the browser responds to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Open,
+    Mark,
+    Quit,
+}
+
+/// Decode the keys in one read from a raw-mode terminal. Unbound keys are
+/// skipped.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (key, length) = match rest {
+            [0x1b, b'[', b'A', ..] => (Some(Key::Up), 3),
+            [0x1b, b'[', b'B', ..] => (Some(Key::Down), 3),
+            [0x1b, b'[', b'5', b'~', ..] => (Some(Key::PageUp), 4),
+            [0x1b, b'[', b'6', b'~', ..] => (Some(Key::PageDown), 4),
+            // Other escape sequences: skip to their final byte
+            [0x1b, b'[', tail @ ..] => {
+                let end = tail.iter().position(|b| (0x40..=0x7e).contains(b));
+                (None, end.map_or(rest.len(), |end| end + 3))
+            }
+            [0x1b, ..] | [b'q', ..] | [0x03, ..] => (Some(Key::Quit), 1),
+            [b'k', ..] => (Some(Key::Up), 1),
+            [b'j', ..] => (Some(Key::Down), 1),
+            [b'u', ..] => (Some(Key::PageUp), 1),
+            [b'd', ..] => (Some(Key::PageDown), 1),
+            [b'o', ..] | [b'\r', ..] | [b'\n', ..] => (Some(Key::Open), 1),
+            [b'm', ..] | [b' ', ..] => (Some(Key::Mark), 1),
+            _ => (None, 1),
+        };
+        keys.extend(key);
+        rest = &rest[length..];
+    }
+    keys
+}
+
+/// Browser state: the selection, scroll positions, and marks.
+struct Browser<'a> {
+    results: &'a [NovelScore],
+    selected: usize,
+    /// Index of the first result shown in the list pane.
+    list_offset: usize,
+    /// Lines scrolled past in the detail pane.
+    detail_scroll: usize,
+    marked: BTreeSet<usize>,
+    /// Feedback for the last action, shown in the status line.
+    message: Option<String>,
+    /// The `[output] browser_command` to open novels with, if configured.
+    browser_command: Option<Vec<String>>,
+}
+
+impl<'a> Browser<'a> {
+    fn new(results: &'a [NovelScore]) -> Self {
+        Self {
+            results,
+            selected: 0,
+            list_offset: 0,
+            detail_scroll: 0,
+            marked: BTreeSet::new(),
+            message: None,
+            browser_command: None,
+        }
+    }
+
+    /// Apply a key press; returns false when the browser should quit.
+    fn handle(&mut self, key: Key) -> bool {
+        self.message = None;
+        match key {
+            Key::Up if self.selected > 0 => self.select(self.selected - 1),
+            Key::Down if self.selected + 1 < self.results.len() => self.select(self.selected + 1),
+            Key::Up | Key::Down => {}
+            Key::PageUp => self.detail_scroll = self.detail_scroll.saturating_sub(10),
+            Key::PageDown => self.detail_scroll += 10,
+            Key::Mark if !self.results.is_empty() => {
+                if !self.marked.remove(&self.selected) {
+                    self.marked.insert(self.selected);
+                }
+            }
+            Key::Open => {
+                if let Some(score) = self.results.get(self.selected) {
+                    let command = self.browser_command.as_deref();
+                    self.message = Some(match open::open_url(&score.novel.url, command) {
+                        Ok(()) => format!("Opened {}", score.novel.url),
+                        Err(e) => format!("{:#}", e),
+                    });
+                }
+            }
+            Key::Mark => {}
+            Key::Quit => return false,
+        }
+        true
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.detail_scroll = 0;
+    }
+
+    /// The marked results, in listed order.
+    fn marked(&self) -> Vec<&'a NovelScore> {
+        self.marked.iter().map(|&i| &self.results[i]).collect()
+    }
+
+    /// Draw a full frame for a terminal of the given size.
+    fn render(&mut self, rows: usize, cols: usize) -> String {
+        let body_rows = rows.saturating_sub(2).max(1);
+        let list_width = (cols * 2 / 5).clamp(20, 60).min(cols.saturating_sub(3));
+        let detail_width = cols.saturating_sub(list_width + 3).max(1);
+
+        // Keep the selection in view
+        if self.selected < self.list_offset {
+            self.list_offset = self.selected;
+        } else if self.selected >= self.list_offset + body_rows {
+            self.list_offset = self.selected + 1 - body_rows;
+        }
+        let detail = self.detail_lines(detail_width);
+        self.detail_scroll = self.detail_scroll.min(detail.len().saturating_sub(body_rows));
+
+        let mut frame = String::from("\x1b[H");
+        let title = format!(
+            " novel-finder: {} results, {} marked",
+            self.results.len(),
+            self.marked.len()
+        );
+        push_line(&mut frame, &format!("\x1b[1m{}\x1b[0m", fit(&title, cols)));
+        for row in 0..body_rows {
+            let index = self.list_offset + row;
+            let item = match self.results.get(index) {
+                Some(score) => {
+                    let mark = if self.marked.contains(&index) { '*' } else { ' ' };
+                    let text = format!(
+                        "{} {:>3.0}% {}",
+                        mark,
+                        score.overall_score * 100.0,
+                        score.novel.title
+                    );
+                    let text = fit(&text, list_width);
+                    if index == self.selected {
+                        format!("\x1b[7m{}\x1b[0m", text)
+                    } else {
+                        text
+                    }
+                }
+                None => fit("", list_width),
+            };
+            let detail_line = detail.get(self.detail_scroll + row).map_or("", String::as_str);
+            push_line(&mut frame, &format!("{} │ {}", item, fit(detail_line, detail_width)));
+        }
+        let status = self.message.as_deref().unwrap_or(HELP);
+        frame.push_str(&format!("\x1b[2m{}\x1b[0m\x1b[K", fit(status, cols)));
+        frame
+    }
+
+    /// The detail pane's lines for the selected result, wrapped to `width`.
+    fn detail_lines(&self, width: usize) -> Vec<String> {
+        let Some(score) = self.results.get(self.selected) else {
+            return vec!["No novels matched the criteria.".to_string()];
+        };
+        let mut lines = output::detailed_score_lines(score);
+        if !score.novel.tags.is_empty() {
+            lines.push(format!("Tags: {}", score.novel.tags.join(", ")));
+            lines.push(String::new());
+        }
+        if let Some(cover_url) = &score.novel.cover_url {
+            lines.push(format!("Cover: {}", cover_url));
+            lines.push(String::new());
+        }
+        lines.push("Description:".to_string());
+        lines.extend(score.novel.description.lines().map(String::from));
+        lines.iter().flat_map(|line| wrap(line, width)).collect()
+    }
+}
+
+/// Append one screen line, clearing whatever was there before.
+fn push_line(frame: &mut String, line: &str) {
+    frame.push_str(line);
+    frame.push_str("\x1b[K\r\n");
+}
+
+/// Truncate or pad text to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let count = text.chars().count();
+    if count > width {
+        let mut fitted: String = text.chars().take(width.saturating_sub(1)).collect();
+        fitted.push('…');
+        fitted
+    } else {
+        format!("{}{}", text, " ".repeat(width - count))
+    }
+}
+
+/// Word-wrap a line to at most `width` characters per line, breaking words
+/// longer than a line.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for word in line.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if current_len > 0 && current_len + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        while word.len() > width {
+            let rest = word.split_off(width);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+        if current_len > 0 {
+            current.push(' ');
+            current_len += 1;
+        }
+        current_len += word.len();
+        current.extend(word);
+    }
+    lines.push(current);
+    lines
+}
+
+/// The terminal in raw mode on the alternate screen; restored on drop.
+struct RawTerminal {
+    /// The terminal settings to restore, from `stty -g`.
+    saved: String,
+}
+
+impl RawTerminal {
+    fn enter() -> Result<Self> {
+        let saved = stty(&["-g"]).context("Failed to read the terminal settings")?;
+        stty(&["raw", "-echo"]).context("Failed to put the terminal in raw mode")?;
+        print!("\x1b[?1049h\x1b[?25l");
+        Ok(Self { saved })
+    }
+
+    /// The terminal size as (rows, columns), assuming 24x80 if unknown.
+    fn size(&self) -> (usize, usize) {
+        let size = stty(&["size"]).unwrap_or_default();
+        let mut parts = size.split_whitespace().map(|part| part.parse().ok());
+        match (parts.next().flatten(), parts.next().flatten()) {
+            (Some(rows), Some(cols)) => (rows, cols),
+            _ => (24, 80),
+        }
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+        let _ = stty(&[self.saved.as_str()]);
+    }
+}
+
+/// Run `stty` on the controlling terminal, returning its trimmed output.
+fn stty(args: &[&str]) -> Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        anyhow::bail!("stty {} failed ({})", args.join(" "), output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compare;
+    use crate::models::{test_novel, test_score};
+
+    fn score(id: u64) -> NovelScore {
+        let mut novel = test_novel(id);
+        novel.description = "A long description of the story.".to_string();
+        novel.tags = vec!["Fantasy".to_string()];
+        let mut score = test_score(novel, 0.87);
+        score.reasoning = "Fits the criteria".to_string();
+        score
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(parse_keys(b"\x1b[A\x1b[Bjk"), vec![Key::Up, Key::Down, Key::Down, Key::Up]);
+        assert_eq!(parse_keys(b"\x1b[5~\x1b[6~"), vec![Key::PageUp, Key::PageDown]);
+        assert_eq!(parse_keys(b"m \ro"), vec![Key::Mark, Key::Mark, Key::Open, Key::Open]);
+        assert_eq!(parse_keys(b"\x1b[1;5Cq"), vec![Key::Quit]);
+        assert_eq!(parse_keys(b"\x1b"), vec![Key::Quit]);
+        assert!(parse_keys(b"zx").is_empty());
+    }
+
+    #[test]
+    fn test_navigation_and_marks() {
+        let results = [score(1), score(2), score(3)];
+        let mut browser = Browser::new(&results);
+        assert!(browser.handle(Key::Up));
+        assert_eq!(browser.selected, 0);
+        browser.handle(Key::Down);
+        browser.handle(Key::Mark);
+        browser.handle(Key::Down);
+        browser.handle(Key::Down);
+        assert_eq!(browser.selected, 2);
+        browser.handle(Key::Mark);
+        browser.handle(Key::Mark);
+        let marked: Vec<u64> = browser.marked().iter().map(|s| s.novel.id).collect();
+        assert_eq!(marked, vec![2]);
+        assert!(!browser.handle(Key::Quit));
+    }
+
+    #[test]
+    fn test_render_shows_list_and_details() {
+        let results: Vec<NovelScore> = (1..=30).map(score).collect();
+        let mut browser = Browser::new(&results);
+        browser.handle(Key::Mark);
+        for _ in 0..25 {
+            browser.handle(Key::Down);
+        }
+        let frame = browser.render(12, 100);
+
+        assert!(frame.contains("30 results, 1 marked"), "{}", frame);
+        // The list scrolled to keep novel 26 selected, leaving novel 1 out
+        assert!(frame.contains("\x1b[7m   87% Novel 26"), "{}", frame);
+        assert!(!frame.contains("*  87% Novel 1 "), "{}", frame);
+        assert!(frame.contains("=== Novel 26 ==="), "{}", frame);
+        assert_eq!(frame.matches("\r\n").count(), 11);
+    }
+
+    #[test]
+    fn test_detail_lines_include_description_and_tags() {
+        let results = [score(1)];
+        let lines = Browser::new(&results).detail_lines(20);
+        assert!(lines.iter().any(|line| line == "Tags: Fantasy"), "{:?}", lines);
+        assert!(lines.iter().all(|line| line.chars().count() <= 20), "{:?}", lines);
+        let description = lines.iter().position(|line| line == "Description:").unwrap();
+        assert_eq!(lines[description + 1], "A long description");
+    }
+
+    #[test]
+    fn test_wrap_and_fit() {
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![""]);
+        assert_eq!(fit("abc", 5), "abc  ");
+        assert_eq!(fit("abcdef", 4), "abc…");
+    }
+
+    #[test]
+    fn test_saved_marks_can_be_browsed_again() {
+        let results = [score(1), score(2)];
+        let path =
+            std::env::temp_dir().join(format!("novel-finder-marked-{}.json", std::process::id()));
+        save_marked(&[&results[1]], &path).unwrap();
+        let saved = compare::load_run(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved.results.len(), 1);
+        assert_eq!(saved.results[0].novel.id, 2);
+    }
+}
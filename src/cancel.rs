@@ -0,0 +1,75 @@
+//! Stopping a running pipeline from outside it.
+//!
+//! A [`CancellationToken`] given to a pipeline is checked between novels;
+//! once cancelled, the run stops and returns the results so far. Clones
+//! share one flag, so any thread holding a clone can cancel. The CLI cancels
+//! its token on Ctrl-C (see [`cancel_on_interrupt`]).
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag asking a run to stop, shared between clones.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// A token that hasn't been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every run holding this token (or a clone of it) to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Cancel `token` on the first Ctrl-C; a second one exits immediately.
+/// Only one token can be installed per process; later calls are ignored.
+#[cfg(unix)]
+pub fn cancel_on_interrupt(token: &CancellationToken) {
+    use std::sync::OnceLock;
+
+    static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+
+    extern "C" fn on_interrupt(_signal: libc::c_int) {
+        let Some(token) = TOKEN.get() else {
+            return;
+        };
+        if token.is_cancelled() {
+            // Only async-signal-safe calls are allowed here
+            unsafe { libc::_exit(130) };
+        }
+        token.cancel();
+    }
+
+    if TOKEN.set(token.clone()).is_err() {
+        return;
+    }
+    let handler = on_interrupt as extern "C" fn(libc::c_int);
+    // SAFETY: the handler only touches atomics and calls `_exit`
+    unsafe { libc::signal(libc::SIGINT, handler as libc::sighandler_t) };
+}
+
+/// Platforms without Unix signals keep the default Ctrl-C behavior.
+#[cfg(not(unix))]
+pub fn cancel_on_interrupt(_token: &CancellationToken) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        std::thread::spawn(move || clone.cancel()).join().unwrap();
+        assert!(token.is_cancelled());
+    }
+}
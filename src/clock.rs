@@ -0,0 +1,181 @@
+//! The run's clock.
+//!
+//! The pipeline asks one [`RunClock`] for the time rather than calling
+//! `Instant::now()` itself: how long the run has gone on, whether its
+//! `max_time` stop condition is up, and whether a phase or novel is past its
+//! own deadline. The clock reads the time from a [`TimeSource`], which tests
+//! replace with a [`ManualTime`] they move forward by hand.
+
+use crate::models::StopCondition;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Where the current time comes from.
+pub trait TimeSource: Send + Sync {
+    /// The current time.
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock.
+#[derive(Debug, Default)]
+pub struct SystemTime;
+
+impl TimeSource for SystemTime {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to, for tests.
+#[derive(Debug)]
+pub struct ManualTime(Mutex<Instant>);
+
+impl ManualTime {
+    /// A clock stopped at the current time.
+    pub fn new() -> Self {
+        Self(Mutex::new(Instant::now()))
+    }
+
+    /// Move the clock forward by `step`.
+    pub fn advance(&self, step: Duration) {
+        *self.0.lock().unwrap() += step;
+    }
+}
+
+impl Default for ManualTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Instant {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// The time since a run started, against its time limit.
+#[derive(Clone)]
+pub struct RunClock {
+    source: Arc<dyn TimeSource>,
+    started: Instant,
+    /// The whole run's time limit, from its stop condition.
+    limit: Option<Duration>,
+}
+
+impl RunClock {
+    /// A clock reading `source`, started now, for a run stopping on
+    /// `stop_condition`.
+    pub fn new(source: Arc<dyn TimeSource>, stop_condition: &StopCondition) -> Self {
+        let started = source.now();
+        Self {
+            source,
+            started,
+            limit: stop_condition.time_limit(),
+        }
+    }
+
+    /// A clock reading the system time, started now.
+    pub fn system(stop_condition: &StopCondition) -> Self {
+        Self::new(Arc::new(SystemTime), stop_condition)
+    }
+
+    /// Start the clock again, for a new run stopping on `stop_condition`.
+    pub fn restart(&mut self, stop_condition: &StopCondition) {
+        self.started = self.source.now();
+        self.limit = stop_condition.time_limit();
+    }
+
+    /// The current time.
+    pub fn now(&self) -> Instant {
+        self.source.now()
+    }
+
+    /// The time since `earlier`, or zero if it's in the future.
+    pub fn since(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+
+    /// The time since the run started.
+    pub fn elapsed(&self) -> Duration {
+        self.since(self.started)
+    }
+
+    /// Whether the run has used up its time limit. Never, without one.
+    pub fn time_up(&self) -> bool {
+        self.limit.is_some_and(|limit| self.elapsed() >= limit)
+    }
+
+    /// The time `budget` from now, if there is a budget.
+    pub fn deadline(&self, budget: Option<Duration>) -> Option<Instant> {
+        budget.map(|budget| self.now() + budget)
+    }
+
+    /// Whether `deadline` has passed. Never, without one.
+    pub fn is_past(&self, deadline: Option<Instant>) -> bool {
+        deadline.is_some_and(|deadline| self.now() >= deadline)
+    }
+}
+
+impl std::fmt::Debug for RunClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunClock")
+            .field("elapsed", &self.elapsed())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manual_clock(stop_condition: StopCondition) -> (RunClock, Arc<ManualTime>) {
+        let time = Arc::new(ManualTime::new());
+        let clock = RunClock::new(time.clone(), &stop_condition);
+        (clock, time)
+    }
+
+    #[test]
+    fn test_time_up_only_with_a_limit() {
+        let (clock, time) = manual_clock(StopCondition::MaxTime(Duration::from_secs(60)));
+        assert!(!clock.time_up());
+        time.advance(Duration::from_secs(59));
+        assert!(!clock.time_up());
+        time.advance(Duration::from_secs(1));
+        assert!(clock.time_up());
+        assert_eq!(clock.elapsed(), Duration::from_secs(60));
+
+        let (clock, time) = manual_clock(StopCondition::MaxNovels(5));
+        time.advance(Duration::from_secs(86_400));
+        assert!(!clock.time_up());
+    }
+
+    #[test]
+    fn test_restart_resets_elapsed_time() {
+        let (mut clock, time) = manual_clock(StopCondition::MaxTime(Duration::from_secs(60)));
+        time.advance(Duration::from_secs(90));
+        assert!(clock.time_up());
+        clock.restart(&StopCondition::MaxTime(Duration::from_secs(60)));
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+        assert!(!clock.time_up());
+        clock.restart(&StopCondition::EmptyQueue);
+        time.advance(Duration::from_secs(90));
+        assert!(!clock.time_up());
+    }
+
+    #[test]
+    fn test_deadlines() {
+        let (clock, time) = manual_clock(StopCondition::EmptyQueue);
+        let deadline = clock.deadline(Some(Duration::from_secs(10)));
+        let started = clock.now();
+        assert!(!clock.is_past(deadline));
+        time.advance(Duration::from_secs(10));
+        assert!(clock.is_past(deadline));
+        assert_eq!(clock.since(started), Duration::from_secs(10));
+
+        // Without a budget there is no deadline to pass
+        assert_eq!(clock.deadline(None), None);
+        assert!(!clock.is_past(None));
+    }
+}
@@ -0,0 +1,276 @@
+//! Comparison of two runs' results.
+//!
+//! Reads results in the `--json` output format and reports which novels
+//! entered or left the top of the rankings and whose scores moved the most.
+//! Novels are matched by fiction ID (and profile, when profiles are used).
+
+use crate::models::NovelScore;
+use crate::persist::{self, SavedRun};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How many top-ranked results per profile are compared for entries and exits.
+pub const TOP_N: usize = 20;
+
+/// How many of the largest score changes are reported.
+const MAX_SCORE_CHANGES: usize = 10;
+
+/// Load a results file written with `--json`.
+pub fn load_run(path: &Path) -> Result<SavedRun> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read results file: {}", path.display()))?;
+    persist::from_str(&content)
+        .with_context(|| format!("Failed to parse results file: {}", path.display()))
+}
+
+/// A novel that entered or left the top results.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankChange {
+    pub id: u64,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// The novel's score in the run where it is in the top results.
+    pub score: f64,
+    /// The novel's score in the other run, if it was scored there at all.
+    pub other_score: Option<f64>,
+}
+
+/// A novel scored in both runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoreChange {
+    pub id: u64,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub old_score: f64,
+    pub new_score: f64,
+}
+
+impl ScoreChange {
+    /// The change in score, new minus old.
+    pub fn delta(&self) -> f64 {
+        self.new_score - self.old_score
+    }
+}
+
+/// Differences between an earlier and a later run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunDiff {
+    /// Novels in the new top results that weren't in the old top results.
+    pub entered: Vec<RankChange>,
+    /// Novels in the old top results that aren't in the new top results.
+    pub dropped: Vec<RankChange>,
+    /// The largest score changes among novels scored in both runs.
+    pub score_changes: Vec<ScoreChange>,
+    /// Whether the runs used different criteria, so scores may not be
+    /// comparable. False when either run lacks a criteria hash.
+    pub criteria_changed: bool,
+}
+
+/// Key matching a result across runs.
+type ResultKey = (Option<String>, u64);
+
+fn key(score: &NovelScore) -> ResultKey {
+    (score.profile.clone(), score.novel.id)
+}
+
+/// Compare an earlier run with a later one, warning if their criteria differ.
+pub fn diff_runs(old: &SavedRun, new: &SavedRun) -> RunDiff {
+    let old_by_key: HashMap<ResultKey, &NovelScore> =
+        old.results.iter().map(|s| (key(s), s)).collect();
+    let new_by_key: HashMap<ResultKey, &NovelScore> =
+        new.results.iter().map(|s| (key(s), s)).collect();
+    let old_top = top_keys(&old.results);
+    let new_top = top_keys(&new.results);
+
+    let rank_change = |score: &NovelScore, other: &HashMap<ResultKey, &NovelScore>| RankChange {
+        id: score.novel.id,
+        title: score.novel.title.clone(),
+        profile: score.profile.clone(),
+        score: score.overall_score,
+        other_score: other.get(&key(score)).map(|s| s.overall_score),
+    };
+    let entered = new
+        .results
+        .iter()
+        .filter(|s| new_top.contains(&key(s)) && !old_top.contains(&key(s)))
+        .map(|s| rank_change(s, &old_by_key))
+        .collect();
+    let dropped = old
+        .results
+        .iter()
+        .filter(|s| old_top.contains(&key(s)) && !new_top.contains(&key(s)))
+        .map(|s| rank_change(s, &new_by_key))
+        .collect();
+
+    let mut score_changes: Vec<ScoreChange> = new
+        .results
+        .iter()
+        .filter_map(|s| {
+            let old_score = old_by_key.get(&key(s))?;
+            Some(ScoreChange {
+                id: s.novel.id,
+                title: s.novel.title.clone(),
+                profile: s.profile.clone(),
+                old_score: old_score.overall_score,
+                new_score: s.overall_score,
+            })
+        })
+        .filter(|change| change.delta() != 0.0)
+        .collect();
+    score_changes.sort_by(|a, b| b.delta().abs().total_cmp(&a.delta().abs()));
+    score_changes.truncate(MAX_SCORE_CHANGES);
+
+    let criteria_changed = match (&old.criteria_hash, &new.criteria_hash) {
+        (Some(old_hash), Some(new_hash)) => old_hash != new_hash,
+        _ => false,
+    };
+    if criteria_changed {
+        tracing::warn!("The compared runs used different criteria; scores may not be comparable");
+    }
+
+    RunDiff {
+        entered,
+        dropped,
+        score_changes,
+        criteria_changed,
+    }
+}
+
+/// The keys of the top results of each profile. Results are ranked best
+/// first, so these are the first `TOP_N` seen per profile.
+fn top_keys(results: &[NovelScore]) -> Vec<ResultKey> {
+    let mut per_profile: HashMap<Option<&str>, usize> = HashMap::new();
+    results
+        .iter()
+        .filter(|s| {
+            let seen = per_profile.entry(s.profile.as_deref()).or_insert(0);
+            *seen += 1;
+            *seen <= TOP_N
+        })
+        .map(key)
+        .collect()
+}
+
+/// Print a comparison section.
+pub fn print_diff(diff: &RunDiff) {
+    println!("\n=== Changes since the previous run ===");
+
+    println!("\nNew in the top {}:", TOP_N);
+    print_rank_changes(&diff.entered, "previously");
+    println!("\nDropped from the top {}:", TOP_N);
+    print_rank_changes(&diff.dropped, "now");
+
+    println!("\nBiggest score changes:");
+    if diff.score_changes.is_empty() {
+        println!("  (none)");
+    }
+    for change in &diff.score_changes {
+        println!(
+            "  {:+.0} pts  {}{}  ({:.0}% -> {:.0}%)",
+            change.delta() * 100.0,
+            change.title,
+            profile_suffix(change.profile.as_deref()),
+            change.old_score * 100.0,
+            change.new_score * 100.0
+        );
+    }
+}
+
+fn print_rank_changes(changes: &[RankChange], other_label: &str) {
+    if changes.is_empty() {
+        println!("  (none)");
+    }
+    for change in changes {
+        let other = match change.other_score {
+            Some(score) => format!("{} {:.0}%", other_label, score * 100.0),
+            None => format!("{} not scored", other_label),
+        };
+        println!(
+            "  {:.0}%  {}{}  ({})",
+            change.score * 100.0,
+            change.title,
+            profile_suffix(change.profile.as_deref()),
+            other
+        );
+    }
+}
+
+fn profile_suffix(profile: Option<&str>) -> String {
+    profile.map(|p| format!(" [{}]", p)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, test_score};
+
+    fn score(id: u64, overall: f64) -> NovelScore {
+        test_score(test_novel(id), overall)
+    }
+
+    fn run(scores: Vec<NovelScore>, hash: &str) -> SavedRun {
+        SavedRun {
+            results: scores,
+            criteria_hash: Some(hash.to_string()),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_entries_and_score_changes() {
+        // 25 novels, so the last five are outside the top 20
+        let old: Vec<NovelScore> = (1..=25).map(|id| score(id, 1.0 - id as f64 / 100.0)).collect();
+        let mut new = old.clone();
+        // Novel 22 jumps to the top and novel 1 disappears entirely
+        new[21].overall_score = 0.995;
+        new.remove(0);
+        new.sort_by(|a, b| b.overall_score.total_cmp(&a.overall_score));
+
+        let diff = diff_runs(&run(old, "abc"), &run(new, "abc"));
+
+        let entered: Vec<u64> = diff.entered.iter().map(|c| c.id).collect();
+        assert_eq!(entered, vec![22]);
+        assert_eq!(diff.entered[0].other_score, Some(0.78));
+
+        let dropped: Vec<u64> = diff.dropped.iter().map(|c| c.id).collect();
+        assert_eq!(dropped, vec![1]);
+        assert_eq!(diff.dropped[0].other_score, None);
+
+        assert_eq!(diff.score_changes.len(), 1);
+        assert_eq!(diff.score_changes[0].id, 22);
+        assert!(!diff.criteria_changed);
+    }
+
+    #[test]
+    fn test_criteria_mismatch_flagged() {
+        let diff = diff_runs(&run(vec![score(1, 0.5)], "abc"), &run(vec![score(1, 0.5)], "def"));
+        assert!(diff.criteria_changed);
+
+        let unknown = SavedRun {
+            results: Vec::new(),
+            criteria_hash: None,
+            metadata: None,
+        };
+        assert!(!diff_runs(&unknown, &run(Vec::new(), "abc")).criteria_changed);
+    }
+
+    #[test]
+    fn test_profiles_are_matched_separately() {
+        let mut cozy = score(1, 0.9);
+        cozy.profile = Some("cozy".to_string());
+        let mut grim = score(1, 0.2);
+        grim.profile = Some("grim".to_string());
+
+        let old = run(vec![cozy.clone(), grim.clone()], "abc");
+        grim.overall_score = 0.4;
+        let diff = diff_runs(&old, &run(vec![cozy, grim], "abc"));
+
+        assert_eq!(diff.score_changes.len(), 1);
+        assert_eq!(diff.score_changes[0].profile.as_deref(), Some("grim"));
+        assert!(diff.entered.is_empty() && diff.dropped.is_empty());
+    }
+}
@@ -0,0 +1,427 @@
+//! Config files built on shared fragments with `include = ["base.toml"]`.
+//!
+//! Included files are loaded first, in order, and merged; the including
+//! file is merged over them. Tables merge key by key, at any depth, while
+//! any other value (a string, a number, an array, an array of tables)
+//! replaces what was there. Relative include paths are resolved against the
+//! directory of the file that names them, and may themselves include more
+//! files, as long as no file includes itself along the way.
+//!
+//! Every key remembers the file that set it, so problems found once the
+//! merged config is validated still name the file to fix.
+
+use super::describe_toml_error;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The top-level key listing the files to include.
+pub(super) const INCLUDE_KEY: &str = "include";
+
+/// A config file merged with everything it includes.
+#[derive(Debug)]
+pub(super) struct MergedConfig {
+    /// The merged tables, without `include` keys.
+    pub table: toml::Table,
+    /// Which file set each value.
+    pub origins: Origins,
+    /// Whether the file had an `include` key; without, `table` is just the
+    /// file's own.
+    pub has_includes: bool,
+}
+
+/// The file each merged value came from, by dotted key path. Arrays count
+/// as single values, since they are replaced whole.
+#[derive(Debug, Default)]
+pub(super) struct Origins(BTreeMap<String, PathBuf>);
+
+impl Origins {
+    /// The file that set `key` or the value containing it.
+    fn file_of(&self, key: &str) -> Option<&Path> {
+        let mut key = key;
+        loop {
+            if let Some(file) = self.0.get(key) {
+                return Some(file);
+            }
+            // Up one level: `a.b[0].c` -> `a.b[0]` -> `a.b` -> `a`
+            key = &key[..key.rfind(['.', '['])?];
+        }
+    }
+
+    /// Name the source file in a "key.path: message" problem, e.g.
+    /// "criteria.min_pages (in base.toml): must be ...". Other messages are
+    /// returned unchanged.
+    pub fn attribute(&self, problem: &str) -> String {
+        let Some((key, message)) = problem.split_once(": ") else {
+            return problem.to_string();
+        };
+        if key.contains(char::is_whitespace) {
+            return problem.to_string();
+        }
+        match self.file_of(key) {
+            Some(file) => format!("{} (in {}): {}", key, file.display(), message),
+            None => problem.to_string(),
+        }
+    }
+
+    /// Record every value under `value` at `key` as set by `file`, dropping
+    /// what the old value there had recorded.
+    fn replace(&mut self, key: &str, value: &toml::Value, file: &Path) {
+        let nested = format!("{}.", key);
+        self.0
+            .retain(|path, _| path != key && !path.starts_with(&nested));
+        match value {
+            toml::Value::Table(table) => {
+                for (child, value) in table {
+                    self.replace(&format!("{}.{}", key, child), value, file);
+                }
+            }
+            _ => {
+                self.0.insert(key.to_string(), file.to_path_buf());
+            }
+        }
+    }
+}
+
+/// Load the config file at `path` and everything it includes, merged.
+pub(super) fn load(path: &Path) -> Result<MergedConfig> {
+    let mut merged = MergedConfig {
+        table: toml::Table::new(),
+        origins: Origins::default(),
+        has_includes: false,
+    };
+    let mut chain = Vec::new();
+    load_into(path, &mut chain, &mut merged)?;
+    Ok(merged)
+}
+
+/// Merge the file at `path`, after the files it includes, into `merged`.
+/// `chain` holds the files including this one, to catch cycles.
+fn load_into(path: &Path, chain: &mut Vec<PathBuf>, merged: &mut MergedConfig) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let mut table: toml::Table = toml::from_str(&content)
+        .map_err(|e| describe_toml_error(&content, &e))
+        .with_context(|| format!("Invalid config file: {}", path.display()))?;
+
+    // Compare canonical paths, so one file reached by two spellings is
+    // still recognized
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if let Some(start) = chain.iter().position(|file| *file == canonical) {
+        let cycle: Vec<String> = chain[start..]
+            .iter()
+            .chain([&canonical])
+            .map(|file| file.display().to_string())
+            .collect();
+        anyhow::bail!(
+            "{}: include: cycle of includes: {}",
+            path.display(),
+            cycle.join(" -> ")
+        );
+    }
+
+    if let Some(includes) = table.remove(INCLUDE_KEY) {
+        let includes = include_paths(path, includes)?;
+        merged.has_includes = true;
+        chain.push(canonical);
+        for (index, include) in includes.into_iter().enumerate() {
+            load_into(&include, chain, merged).with_context(|| {
+                format!(
+                    "{}: include[{}]: failed to include {}",
+                    path.display(),
+                    index,
+                    include.display()
+                )
+            })?;
+        }
+        chain.pop();
+    }
+    merge(&mut merged.table, table, "", &mut merged.origins, path);
+    Ok(())
+}
+
+/// The files an `include` value names, resolved against the directory of
+/// the including file at `path`.
+fn include_paths(path: &Path, includes: toml::Value) -> Result<Vec<PathBuf>> {
+    let not_paths = || {
+        anyhow::anyhow!(
+            "{}: include: must be an array of file paths",
+            path.display()
+        )
+    };
+    let toml::Value::Array(includes) = includes else {
+        return Err(not_paths());
+    };
+    let dir = path.parent().unwrap_or(Path::new(""));
+    includes
+        .into_iter()
+        .map(|include| match include {
+            toml::Value::String(include) => Ok(dir.join(include)),
+            _ => Err(not_paths()),
+        })
+        .collect()
+}
+
+/// Deep-merge `over` into `base`: tables merge key by key, anything else
+/// replaces the value in `base`. Values taken from `over` are recorded as
+/// coming from `file`.
+fn merge(
+    base: &mut toml::Table,
+    over: toml::Table,
+    prefix: &str,
+    origins: &mut Origins,
+    file: &Path,
+) {
+    for (key, value) in over {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(over)) => {
+                merge(base, over, &path, origins, file);
+            }
+            (_, value) => {
+                origins.replace(&path, &value, file);
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str) -> toml::Table {
+        toml::from_str(text).unwrap()
+    }
+
+    fn merge_files(base: &str, over: &str) -> (toml::Table, Origins) {
+        let mut origins = Origins::default();
+        let mut merged = toml::Table::new();
+        merge(
+            &mut merged,
+            table(base),
+            "",
+            &mut origins,
+            Path::new("base.toml"),
+        );
+        merge(
+            &mut merged,
+            table(over),
+            "",
+            &mut origins,
+            Path::new("main.toml"),
+        );
+        (merged, origins)
+    }
+
+    /// A directory of config files for one test, removed first if a
+    /// previous run left it behind.
+    fn temp_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "novel-finder-include-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (file, content) in files {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_merge_nested_tables_key_by_key() {
+        let (merged, _) = merge_files(
+            "[criteria]\nmin_pages = 100\nmin_rating = 4.0\n\
+             [scraper]\nproxy = { url = \"http://a\", no_proxy = [\"x\"] }\n",
+            "[criteria]\nmin_pages = 300\nprompt = \"cozy\"\n\
+             [scraper.proxy]\nurl = \"http://b\"\n",
+        );
+        let expected = table(
+            "[criteria]\nmin_pages = 300\nmin_rating = 4.0\nprompt = \"cozy\"\n\
+             [scraper]\nproxy = { url = \"http://b\", no_proxy = [\"x\"] }\n",
+        );
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn test_merge_replaces_arrays_and_scalars_whole() {
+        let (merged, _) = merge_files(
+            "[criteria]\nrequired_tags = [\"LitRPG\", \"Magic\"]\n\
+             [[profiles]]\nname = \"cozy\"\n[[profiles]]\nname = \"grim\"\n",
+            "[criteria]\nrequired_tags = [\"Progression\"]\n\
+             [[profiles]]\nname = \"heist\"\n",
+        );
+        let expected = table(
+            "[criteria]\nrequired_tags = [\"Progression\"]\n[[profiles]]\nname = \"heist\"\n",
+        );
+        assert_eq!(merged, expected);
+
+        // An empty array still replaces, which is how a tag list is cleared
+        let (cleared, _) = merge_files(
+            "[criteria]\nrequired_tags = [\"LitRPG\"]\n",
+            "[criteria]\nrequired_tags = []\n",
+        );
+        assert_eq!(
+            cleared["criteria"]["required_tags"],
+            toml::Value::Array(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_merge_lets_values_change_type() {
+        let (merged, origins) = merge_files(
+            "[run]\nstop_condition = { type = \"max_novels\", value = 10 }\n",
+            "[run]\nstop_condition = \"empty_queue\"\n",
+        );
+        assert_eq!(
+            merged["run"]["stop_condition"].as_str(),
+            Some("empty_queue")
+        );
+        assert_eq!(
+            origins.file_of("run.stop_condition"),
+            Some(Path::new("main.toml"))
+        );
+        assert_eq!(
+            origins.file_of("run.stop_condition.value"),
+            Some(Path::new("main.toml"))
+        );
+    }
+
+    #[test]
+    fn test_origins_name_the_file_that_set_each_key() {
+        let (_, origins) = merge_files(
+            "[criteria]\nmin_pages = 100\nmin_rating = 4.0\n[[profiles]]\nname = \"cozy\"\n",
+            "[criteria]\nmin_pages = 300\n",
+        );
+        assert_eq!(
+            origins.attribute("criteria.min_rating: must be between 0.0 and 5.0"),
+            "criteria.min_rating (in base.toml): must be between 0.0 and 5.0"
+        );
+        assert_eq!(
+            origins.attribute("criteria.min_pages: must not exceed max_pages"),
+            "criteria.min_pages (in main.toml): must not exceed max_pages"
+        );
+        assert_eq!(
+            origins.attribute("profiles[0].name: needs a name"),
+            "profiles[0].name (in base.toml): needs a name"
+        );
+        // Keys no file set, and messages without a key, are left alone
+        for problem in ["eval.mode: missing", "Unknown thing: x"] {
+            assert_eq!(origins.attribute(problem), problem);
+        }
+    }
+
+    #[test]
+    fn test_load_resolves_includes_relative_to_each_file() {
+        let dir = temp_dir(
+            "relative",
+            &[
+                (
+                    "main.toml",
+                    "include = [\"shared/criteria.toml\"]\n[seeds]\nsource = \"x\"\n",
+                ),
+                (
+                    "shared/criteria.toml",
+                    "include = [\"base.toml\"]\n[criteria]\nmin_pages = 300\n",
+                ),
+                (
+                    "shared/base.toml",
+                    "[criteria]\nmin_pages = 100\nmin_rating = 4.0\n",
+                ),
+            ],
+        );
+        let merged = load(&dir.join("main.toml")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(merged.has_includes);
+        assert!(!merged.table.contains_key(INCLUDE_KEY));
+        assert_eq!(
+            merged.table["criteria"]["min_pages"].as_integer(),
+            Some(300)
+        );
+        assert_eq!(merged.table["criteria"]["min_rating"].as_float(), Some(4.0));
+        assert_eq!(merged.table["seeds"]["source"].as_str(), Some("x"));
+        assert!(merged
+            .origins
+            .file_of("criteria.min_rating")
+            .unwrap()
+            .ends_with("shared/base.toml"));
+    }
+
+    #[test]
+    fn test_later_includes_override_earlier_ones() {
+        let dir = temp_dir(
+            "order",
+            &[
+                ("main.toml", "include = [\"a.toml\", \"b.toml\"]\n"),
+                ("a.toml", "[criteria]\nmin_pages = 1\nmax_pages = 9\n"),
+                ("b.toml", "[criteria]\nmin_pages = 2\n"),
+            ],
+        );
+        let merged = load(&dir.join("main.toml")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(merged.table["criteria"]["min_pages"].as_integer(), Some(2));
+        assert_eq!(merged.table["criteria"]["max_pages"].as_integer(), Some(9));
+    }
+
+    #[test]
+    fn test_load_detects_cycles() {
+        let dir = temp_dir(
+            "cycle",
+            &[
+                ("a.toml", "include = [\"b.toml\"]\n"),
+                ("b.toml", "include = [\"./a.toml\"]\n"),
+            ],
+        );
+        let err = load(&dir.join("a.toml")).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let message = format!("{:#}", err);
+        assert!(
+            message.contains("include: cycle of includes"),
+            "{}",
+            message
+        );
+        assert!(message.contains("a.toml -> "), "{}", message);
+    }
+
+    #[test]
+    fn test_load_errors_name_the_file() {
+        let dir = temp_dir(
+            "errors",
+            &[
+                ("main.toml", "include = [\"broken.toml\"]\n"),
+                ("broken.toml", "[criteria]\nmin_pages = \n"),
+                ("not_a_list.toml", "include = \"base.toml\"\n"),
+                ("missing.toml", "include = [\"nowhere.toml\"]\n"),
+            ],
+        );
+        let error = |file: &str| format!("{:#}", load(&dir.join(file)).unwrap_err());
+        let (broken, not_a_list, missing) = (
+            error("main.toml"),
+            error("not_a_list.toml"),
+            error("missing.toml"),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            broken.contains("main.toml: include[0]: failed to include"),
+            "{}",
+            broken
+        );
+        assert!(broken.contains("broken.toml"), "{}", broken);
+        assert!(broken.contains("line 2"), "{}", broken);
+        assert!(
+            not_a_list.contains("not_a_list.toml: include: must be an array of file paths"),
+            "{}",
+            not_a_list
+        );
+        assert!(missing.contains("nowhere.toml"), "{}", missing);
+    }
+}
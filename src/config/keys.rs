@@ -0,0 +1,327 @@
+//! Detection of unknown keys in the config file.
+//!
+//! serde silently drops fields it doesn't recognize, so a typo like
+//! `min_ratting = 4.5` leaves a filter inactive without any hint. This module
+//! walks the parsed TOML against the fields each raw config struct accepts and
+//! reports the keys that match none of them, with the closest valid spellings.
+
+use super::{
+    RawConfig, RawConfigOptions, RawCriteria, RawDiscovery, RawEval, RawLlmEval, RawLocalEval,
+    RawLogging, RawOutput, RawRateLimits, RawRun, RawScraper, RawSeeds, RawStatusRule,
+    RawStopCondition, RawTagReport,
+};
+use serde::de::{self, DeserializeOwned, Visitor};
+use std::fmt;
+
+/// Maximum number of suggestions listed for an unknown key.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A key in the config file that no config option recognizes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownKey {
+    /// Full key path, e.g. "criteria.min_ratting".
+    pub path: String,
+    /// Valid keys in the same table with a similar spelling, closest first.
+    pub suggestions: Vec<&'static str>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: unknown key", self.path)?;
+        match self.suggestions.as_slice() {
+            [] => Ok(()),
+            [only] => write!(f, " (did you mean `{}`?)", only),
+            many => {
+                let quoted: Vec<String> = many.iter().map(|s| format!("`{}`", s)).collect();
+                write!(f, " (did you mean one of {}?)", quoted.join(", "))
+            }
+        }
+    }
+}
+
+/// Find every key in a parsed config file that isn't a known option.
+pub fn find_unknown_keys(table: &toml::Table) -> Vec<UnknownKey> {
+    let mut unknown = Vec::new();
+    check_table(table, Section::Root, "", &mut unknown);
+    unknown
+}
+
+/// Whether the key at `path` is `section` or one of its nested keys.
+pub fn is_within(path: &str, section: &str) -> bool {
+    path.strip_prefix(section)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// The tables of the config file, each backed by a raw config struct.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Root,
+    Config,
+    Criteria,
+    Profile,
+    StatusRule,
+    Eval,
+    LocalEval,
+    LlmEval,
+    Seeds,
+    Run,
+    StopCondition,
+    Discovery,
+    Scraper,
+    RateLimits,
+    Output,
+    TagReport,
+    Logging,
+}
+
+impl Section {
+    /// The keys accepted in this table.
+    fn fields(self) -> Vec<&'static str> {
+        match self {
+            Section::Root => field_names::<RawConfig>(),
+            Section::Config => field_names::<RawConfigOptions>(),
+            Section::Criteria => field_names::<RawCriteria>(),
+            Section::Profile => {
+                let mut fields = field_names::<RawCriteria>();
+                fields.push("name");
+                fields
+            }
+            Section::StatusRule => field_names::<RawStatusRule>(),
+            Section::Eval => field_names::<RawEval>(),
+            Section::LocalEval => field_names::<RawLocalEval>(),
+            Section::LlmEval => field_names::<RawLlmEval>(),
+            Section::Seeds => field_names::<RawSeeds>(),
+            Section::Run => field_names::<RawRun>(),
+            Section::StopCondition => field_names::<RawStopCondition>(),
+            Section::Discovery => field_names::<RawDiscovery>(),
+            Section::Scraper => field_names::<RawScraper>(),
+            Section::RateLimits => field_names::<RawRateLimits>(),
+            Section::Output => field_names::<RawOutput>(),
+            Section::TagReport => field_names::<RawTagReport>(),
+            Section::Logging => field_names::<RawLogging>(),
+        }
+    }
+
+    /// The section a nested table (or array of tables) under `key` belongs to.
+    fn child(self, key: &str) -> Option<Section> {
+        match (self, key) {
+            (Section::Root, "config") => Some(Section::Config),
+            (Section::Root, "criteria") => Some(Section::Criteria),
+            (Section::Root, "profiles") => Some(Section::Profile),
+            (Section::Root, "eval") => Some(Section::Eval),
+            (Section::Root, "seeds") => Some(Section::Seeds),
+            (Section::Root, "run") => Some(Section::Run),
+            (Section::Root, "scraper") => Some(Section::Scraper),
+            (Section::Root, "output") => Some(Section::Output),
+            (Section::Root, "logging") => Some(Section::Logging),
+            (Section::Criteria | Section::Profile, "status_policy") => Some(Section::StatusRule),
+            (Section::Eval, "local") => Some(Section::LocalEval),
+            (Section::Eval, "llm") => Some(Section::LlmEval),
+            (Section::Run, "stop_condition") => Some(Section::StopCondition),
+            (Section::Run, "discovery") => Some(Section::Discovery),
+            (Section::Scraper, "rate_limits") => Some(Section::RateLimits),
+            (Section::Output, "tag_report") => Some(Section::TagReport),
+            _ => None,
+        }
+    }
+}
+
+/// Check one table's keys, recursing into known nested tables.
+fn check_table(table: &toml::Table, section: Section, path: &str, unknown: &mut Vec<UnknownKey>) {
+    let fields = section.fields();
+    for (key, value) in table {
+        let key_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        if !fields.contains(&key.as_str()) {
+            unknown.push(UnknownKey {
+                path: key_path,
+                suggestions: suggest(key, &fields),
+            });
+            continue;
+        }
+        let Some(child) = section.child(key) else {
+            continue;
+        };
+        match value {
+            toml::Value::Table(nested) => check_table(nested, child, &key_path, unknown),
+            toml::Value::Array(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    let toml::Value::Table(nested) = item else {
+                        continue;
+                    };
+                    // Profiles are named by their name in messages, as elsewhere
+                    let item_path = match nested.get("name") {
+                        Some(toml::Value::String(name)) if child == Section::Profile => {
+                            format!("{}.{}", key_path, name)
+                        }
+                        _ => format!("{}[{}]", key_path, index),
+                    };
+                    check_table(nested, child, &item_path, unknown);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Known keys spelled similarly to `key`, closest first.
+fn suggest(key: &str, fields: &[&'static str]) -> Vec<&'static str> {
+    let max_distance = (key.chars().count() / 3).max(2);
+    let mut candidates: Vec<(usize, &'static str)> = fields
+        .iter()
+        .map(|field| (levenshtein(key, field), *field))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, field)| field)
+        .collect()
+}
+
+/// The Levenshtein edit distance between two strings.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The field names a derived `Deserialize` struct accepts.
+///
+/// serde hands the list of (renamed) field names to `deserialize_struct`, so
+/// a deserializer that stops right there recovers them without keeping a
+/// second list in sync with the struct definitions.
+fn field_names<T: DeserializeOwned>() -> Vec<&'static str> {
+    match T::deserialize(FieldNames) {
+        Err(FieldNamesError(Some(fields))) => fields.to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+/// A deserializer that only captures the field list of a struct.
+struct FieldNames;
+
+/// Carries the captured field list out of [`FieldNames`].
+#[derive(Debug)]
+struct FieldNamesError(Option<&'static [&'static str]>);
+
+impl fmt::Display for FieldNamesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "field name capture")
+    }
+}
+
+impl std::error::Error for FieldNamesError {}
+
+impl de::Error for FieldNamesError {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        FieldNamesError(None)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FieldNames {
+    type Error = FieldNamesError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(FieldNamesError(None))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(FieldNamesError(Some(fields)))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unknown_in(content: &str) -> Vec<UnknownKey> {
+        find_unknown_keys(&toml::from_str(content).unwrap())
+    }
+
+    #[test]
+    fn test_field_names_follow_renames() {
+        assert_eq!(field_names::<RawStopCondition>(), vec!["type", "value"]);
+        assert!(field_names::<RawCriteria>().contains(&"min_rating"));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("min_ratting", "min_rating"), 1);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_unknown_keys_with_suggestions() {
+        let unknown = unknown_in(
+            "[criteria]\nmin_ratting = 4.5\n\n[run]\nstop_condition = { typ = \"max_novels\" }\n",
+        );
+        assert_eq!(unknown.len(), 2);
+        assert_eq!(unknown[0].path, "criteria.min_ratting");
+        assert_eq!(unknown[0].suggestions, vec!["min_rating"]);
+        assert_eq!(
+            unknown[0].to_string(),
+            "criteria.min_ratting: unknown key (did you mean `min_rating`?)"
+        );
+        assert_eq!(unknown[1].path, "run.stop_condition.typ");
+        assert_eq!(unknown[1].suggestions, vec!["type"]);
+    }
+
+    #[test]
+    fn test_unknown_keys_in_arrays_of_tables() {
+        let unknown = unknown_in(
+            "[[profiles]]\nname = \"cozy\"\nmin_page = 10\n\n\
+             [criteria]\nstatus_policy = [{ status = \"ongoing\", max_days = 30 }]\n",
+        );
+        let paths: Vec<&str> = unknown.iter().map(|k| k.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["criteria.status_policy[0].max_days", "profiles.cozy.min_page"]
+        );
+        assert_eq!(unknown[1].suggestions, vec!["min_pages"]);
+    }
+
+    #[test]
+    fn test_discovery_entries_checked_by_index() {
+        let unknown = unknown_in(
+            "[[run.discovery]]\nkind = \"also_liked\"\nmax_per_novle = 3\n\
+             [[run.discovery]]\nkind = \"also_liked\"\nuse_cache = false\n",
+        );
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].path, "run.discovery[0].max_per_novle");
+        assert_eq!(unknown[0].suggestions, vec!["max_per_novel"]);
+    }
+
+    #[test]
+    fn test_known_keys_and_unrelated_typos() {
+        assert!(unknown_in("[eval]\nmode = \"local\"\n[eval.local]\npopularity_saturation = 5\n")
+            .is_empty());
+        let unknown = unknown_in("colour = \"blue\"\n");
+        assert_eq!(unknown[0].path, "colour");
+        assert!(unknown[0].suggestions.is_empty());
+    }
+}
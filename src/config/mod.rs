@@ -0,0 +1,3302 @@
+//! Configuration loading and types for the novel-finder application.
+//!
+//! Handles parsing the TOML configuration file that defines criteria,
+//! evaluation mode, seed sources, and run parameters.
+
+mod include;
+mod keys;
+pub mod secrets;
+pub mod validate;
+
+use crate::eval::text::DescriptionCleaner;
+use crate::models::tags::{normalize_tag, CanonicalTag};
+use crate::proxy::{parse_proxy_setting, ProxySetting};
+use crate::models::{
+    ChapterTitleStorage, Criteria, CriteriaProfile, NovelStatus, ReviewPolicy, ReviewSelection,
+    SiteId, SoftFilters, StatusRule, StopCondition,
+};
+use crate::scraper::budget::DEFAULT_MAX_REQUESTS_PER_NOVEL;
+use crate::scraper::cache::{CacheLimits, DEFAULT_CACHE_TTL};
+use crate::scraper::rate_limit::RateLimits;
+use crate::scraper::search::SearchOrder;
+use crate::util;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The evaluation mode to use for scoring novels.
+#[derive(Debug, Clone)]
+pub enum EvalMode {
+    /// Local evaluation using keyword matching and heuristics.
+    Local(LocalEvalConfig),
+    /// LLM-based evaluation using an external API.
+    Llm(LlmEvalConfig),
+    /// An evaluator registered by name with a
+    /// [`PipelineBuilder`](crate::pipeline::PipelineBuilder).
+    Custom(CustomEvalConfig),
+}
+
+impl EvalMode {
+    /// The `eval.mode` name the evaluator is registered under.
+    pub fn name(&self) -> &str {
+        match self {
+            EvalMode::Local(_) => "local",
+            EvalMode::Llm(_) => "llm",
+            EvalMode::Custom(custom) => &custom.name,
+        }
+    }
+}
+
+/// Names the evaluator and, for LLMs, the model; never the API key.
+impl std::fmt::Display for EvalMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalMode::Local(_) => write!(f, "local"),
+            EvalMode::Llm(llm) => write!(f, "llm ({})", llm.model),
+            EvalMode::Custom(custom) => write!(f, "{}", custom.name),
+        }
+    }
+}
+
+/// How seed novels are sourced.
+#[derive(Debug, Clone)]
+pub enum SeedSource {
+    /// Manually specified list of RoyalRoad URLs or IDs.
+    Manual(Vec<String>),
+    /// Scraped from RoyalRoad's advanced search.
+    Search {
+        query: String,
+        max_results: usize,
+    },
+    /// Scraped from RoyalRoad's listing of fictions with all of some tags.
+    Tag(TagSeeds),
+    /// Fiction IDs sampled at random, for serendipitous finds.
+    Random(RandomSeeds),
+    /// IDs or URLs listed one per line in a file, such as the queue left
+    /// over from an earlier run (see `--export-queue`).
+    File(PathBuf),
+    /// Novels saved with `--export-dataset`, queued as they were saved
+    /// rather than scraped again.
+    Dataset { path: PathBuf, filter: DatasetFilter },
+}
+
+/// Which entries of a dataset seed a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatasetFilter {
+    /// Every novel in the dataset.
+    All,
+    /// Only novels the run that saved them didn't evaluate, because they
+    /// failed its hard filters.
+    Unevaluated,
+}
+
+impl std::fmt::Display for DatasetFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatasetFilter::All => write!(f, "all"),
+            DatasetFilter::Unevaluated => write!(f, "unevaluated"),
+        }
+    }
+}
+
+impl std::fmt::Display for SeedSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeedSource::Manual(urls) => write!(f, "manual (URLs: {})", urls.len()),
+            SeedSource::Search { query, max_results } => {
+                write!(f, "search {:?} (up to {} results)", query, max_results)
+            }
+            SeedSource::Tag(seeds) => {
+                let names: Vec<&str> = seeds.tags.iter().map(|tag| tag.name).collect();
+                write!(
+                    f,
+                    "tag {} by {} (up to {} results)",
+                    names.join(", "),
+                    seeds.order_by,
+                    seeds.max_results
+                )
+            }
+            SeedSource::Random(random) => {
+                write!(f, "random ({} novels from IDs 1-{}", random.count, random.max_id)?;
+                if let Some(rng_seed) = random.rng_seed {
+                    write!(f, ", seed {}", rng_seed)?;
+                }
+                write!(f, ")")
+            }
+            SeedSource::File(path) => write!(f, "file {}", path.display()),
+            SeedSource::Dataset { path, filter } => {
+                write!(f, "dataset {} ({} novels)", path.display(), filter)
+            }
+        }
+    }
+}
+
+/// Settings for seeding from the fictions tagged with all of some tags.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagSeeds {
+    /// The tags every seed must have.
+    pub tags: Vec<CanonicalTag>,
+    /// The order the listing is read in, best first.
+    pub order_by: SearchOrder,
+    /// How many fictions to take from the listing.
+    pub max_results: usize,
+}
+
+/// Settings for sampling random fiction IDs as seeds.
+#[derive(Debug, Clone)]
+pub struct RandomSeeds {
+    /// How many live novels to find.
+    pub count: usize,
+    /// The highest fiction ID to sample; IDs are drawn from 1 to this.
+    pub max_id: u64,
+    /// Seed for the random generator, to make the sample reproducible.
+    pub rng_seed: Option<u64>,
+    /// Most IDs to try (one request each) before giving up on `count`, so a
+    /// sparse ID range can't keep the sampler going forever.
+    pub max_attempts: usize,
+}
+
+/// A discovery source and its budget, from one `[[run.discovery]]` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveryConfig {
+    /// Only evaluated novels whose best score (across profiles) is at least
+    /// this are used to discover more; `None` uses every evaluated novel.
+    pub min_source_score: Option<f64>,
+    /// Most novels the source queues from one evaluated novel; `None` for
+    /// no limit.
+    pub max_per_novel: Option<usize>,
+    /// Which source, with its own settings.
+    pub source: DiscoverySourceConfig,
+}
+
+impl DiscoveryConfig {
+    /// The "also liked" source with default settings, which is what the
+    /// legacy `discovery_enabled = true` means.
+    pub fn also_liked() -> Self {
+        Self {
+            min_source_score: None,
+            max_per_novel: None,
+            source: DiscoverySourceConfig::AlsoLiked(AlsoLikedConfig::default()),
+        }
+    }
+}
+
+/// The discovery sources, each with its source-specific settings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscoverySourceConfig {
+    /// RoyalRoad's "Others also liked" recommendations.
+    AlsoLiked(AlsoLikedConfig),
+    /// A source registered by kind with a
+    /// [`PipelineBuilder`](crate::pipeline::PipelineBuilder).
+    Custom(CustomDiscoveryConfig),
+}
+
+impl DiscoverySourceConfig {
+    /// The `kind` the source is registered under.
+    pub fn kind(&self) -> &str {
+        match self {
+            DiscoverySourceConfig::AlsoLiked(_) => "also_liked",
+            DiscoverySourceConfig::Custom(custom) => &custom.kind,
+        }
+    }
+}
+
+impl std::fmt::Display for DiscoverySourceConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiscoverySourceConfig::AlsoLiked(_) | DiscoverySourceConfig::Custom(_) => {
+                write!(f, "{}", self.kind())
+            }
+        }
+    }
+}
+
+/// Settings for the "also liked" discovery source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlsoLikedConfig {
+    /// Keep similar fictions API responses in `[scraper] cache_dir`, when
+    /// one is set.
+    pub use_cache: bool,
+}
+
+impl Default for AlsoLikedConfig {
+    fn default() -> Self {
+        Self { use_cache: true }
+    }
+}
+
+/// A discovery source the config doesn't know, resolved by `kind` when the
+/// pipeline is built.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomDiscoveryConfig {
+    pub kind: String,
+    /// The `[[run.discovery]]` entry as written, for the source's own keys.
+    pub settings: toml::Table,
+}
+
+/// Settings for the RoyalRoad scraper.
+#[derive(Debug, Clone)]
+pub struct ScraperConfig {
+    /// Fetch sample chapters of novels passing the hard filters to estimate
+    /// their word count.
+    pub estimate_word_count: bool,
+    /// Largest response body to read, in bytes; bigger responses fail.
+    pub max_response_bytes: u64,
+    /// Longest one request may take, from connecting to reading the whole
+    /// response.
+    pub request_timeout: Duration,
+    /// Minimum interval between page requests and between API requests.
+    pub rate_limits: RateLimits,
+    /// Cloudflare challenge pages in a row after which the run stops early,
+    /// keeping the results so far.
+    pub max_challenge_streak: usize,
+    /// Which chapter titles are kept on each scraped novel.
+    pub store_chapter_titles: ChapterTitleStorage,
+    /// Fetch pages even where robots.txt disallows them.
+    pub ignore_robots: bool,
+    /// Proxy for scraper requests.
+    pub proxy: ProxySetting,
+    /// Directory to cache API responses in between runs, if any.
+    pub cache_dir: Option<PathBuf>,
+    /// How long cached API responses are reused.
+    pub cache_ttl: Duration,
+    /// Bounds on the cache's size, kept by evicting the least recently
+    /// used responses.
+    pub cache_limits: CacheLimits,
+}
+
+impl Default for ScraperConfig {
+    fn default() -> Self {
+        Self {
+            estimate_word_count: false,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            rate_limits: RateLimits::default(),
+            max_challenge_streak: DEFAULT_MAX_CHALLENGE_STREAK,
+            store_chapter_titles: ChapterTitleStorage::default(),
+            ignore_robots: false,
+            proxy: ProxySetting::default(),
+            cache_dir: None,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            cache_limits: CacheLimits::default(),
+        }
+    }
+}
+
+/// Settings for files written after a run, and for reports.
+#[derive(Debug, Clone)]
+pub struct OutputConfig {
+    /// Minimum overall score (0.0 - 1.0) for a result to be exported or added
+    /// to the reading list.
+    pub min_score: Option<f64>,
+    /// Settings for the `--tag-report` analysis.
+    pub tag_report: TagReportConfig,
+    /// Write each result's whole novel in JSON output, rather than a summary
+    /// without the description, chapter titles, and other bulky fields.
+    pub include_full_novel: bool,
+    /// Program and arguments to open novel URLs with (the URL is appended),
+    /// in place of the default web browser.
+    pub browser_command: Option<Vec<String>>,
+    /// How the results table and HTML report split results into sections.
+    pub group_by: GroupBy,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            min_score: None,
+            tag_report: TagReportConfig::default(),
+            include_full_novel: true,
+            browser_command: None,
+            group_by: GroupBy::None,
+        }
+    }
+}
+
+/// The order queued novels are evaluated in.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Sampling {
+    /// The order they were queued in.
+    #[default]
+    Fifo,
+    /// Spread across strata of a novel statistic, so a run that stops early
+    /// has evaluated novels from across the queue rather than its head.
+    Stratified(StratifiedSampling),
+}
+
+/// Settings for stratified sampling of the seeded queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StratifiedSampling {
+    /// The statistic novels are stratified by.
+    pub by: StratifyBy,
+    /// How many strata of equal size the queue is split into.
+    pub strata: usize,
+    /// Seed for the random order within each stratum, to make the sample
+    /// reproducible.
+    pub rng_seed: Option<u64>,
+}
+
+/// The novel statistic stratified sampling spreads the sample across.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StratifyBy {
+    #[default]
+    Followers,
+    Rating,
+}
+
+impl std::fmt::Display for StratifyBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StratifyBy::Followers => write!(f, "followers"),
+            StratifyBy::Rating => write!(f, "rating"),
+        }
+    }
+}
+
+/// What results are grouped by in the results table and HTML report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    /// One list of every result.
+    #[default]
+    None,
+    /// The novel's first canonical genre.
+    Genre,
+    /// The novel's publication status.
+    Status,
+}
+
+/// Settings for the tag frequency and co-occurrence report.
+#[derive(Debug, Clone)]
+pub struct TagReportConfig {
+    /// Tags and tag pairs seen on fewer novels than this are left out.
+    pub min_count: usize,
+    /// How tags are ordered.
+    pub sort: TagSort,
+    /// Minimum overall score (0.0 - 1.0) for a novel to count towards tag
+    /// pair co-occurrence.
+    pub high_score: f64,
+    /// Most tag pairs listed.
+    pub max_pairs: usize,
+}
+
+impl Default for TagReportConfig {
+    fn default() -> Self {
+        Self {
+            min_count: 2,
+            sort: TagSort::MeanScore,
+            high_score: 0.7,
+            max_pairs: 10,
+        }
+    }
+}
+
+/// The order of tags in the tag report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagSort {
+    /// Most common tags first.
+    Count,
+    /// Tags with the highest mean score first.
+    MeanScore,
+}
+
+/// What the local evaluator's popularity sub-score is read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PopularitySignal {
+    /// The follower count, against the popularity saturation.
+    #[default]
+    Followers,
+    /// The best "Top X%" badge, falling back to followers without one.
+    Badges,
+}
+
+/// Tuning for the local evaluator, from `[eval.local]`.
+#[derive(Debug, Clone)]
+pub struct LocalEvalConfig {
+    /// Follower count at which the popularity sub-score reaches 1.0.
+    pub popularity_saturation: u64,
+    /// What the popularity sub-score is read from.
+    pub popularity_signal: PopularitySignal,
+}
+
+impl Default for LocalEvalConfig {
+    fn default() -> Self {
+        Self {
+            popularity_saturation: 20_000,
+            popularity_signal: PopularitySignal::default(),
+        }
+    }
+}
+
+/// Settings for the LLM evaluator, from `[eval.llm]`.
+#[derive(Debug, Clone)]
+pub struct LlmEvalConfig {
+    pub api_key: String,
+    pub model: String,
+    pub endpoint: String,
+    /// Fetch the first chapter and include an excerpt in the prompt.
+    pub include_first_chapter: bool,
+    /// Proxy for API requests; `None` uses the scraper's.
+    pub proxy: Option<ProxySetting>,
+    /// Directory each evaluation's prompts and API response are written to.
+    /// Set by [`AppConfig::audit_llm_to`] (`--audit-dir`), never by the
+    /// config file.
+    pub audit_dir: Option<PathBuf>,
+}
+
+/// An evaluator the config doesn't know, resolved by `eval.mode` when the
+/// pipeline is built.
+#[derive(Debug, Clone)]
+pub struct CustomEvalConfig {
+    pub name: String,
+    /// The `[eval]` table as written; the evaluator's own settings go under
+    /// `[eval.<name>]`.
+    pub settings: toml::Table,
+}
+
+/// Logging settings from the `[logging]` table.
+#[derive(Debug, Clone, Default)]
+pub struct LoggingConfig {
+    /// Log debug detail to the console.
+    pub verbose: bool,
+    /// Directory to write a per-run log file into, if any.
+    pub log_dir: Option<PathBuf>,
+    /// How log lines are formatted.
+    pub format: LogFormat,
+}
+
+/// The format of log output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Plain,
+    /// One JSON object per line, including span fields such as `novel_id`.
+    Json,
+}
+
+/// Default `max_response_bytes` for the scraper: 5 MB.
+const DEFAULT_MAX_RESPONSE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Default `request_timeout` for the scraper.
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default `max_challenge_streak` for the scraper.
+const DEFAULT_MAX_CHALLENGE_STREAK: usize = 3;
+
+/// Default `sampling_strata` for stratified sampling.
+const DEFAULT_SAMPLING_STRATA: usize = 5;
+
+/// Default `max_attempts` per requested novel for random seeds.
+const DEFAULT_ATTEMPTS_PER_RANDOM_SEED: usize = 20;
+
+/// Top-level application configuration.
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    /// User-defined evaluation criteria.
+    pub criteria: Criteria,
+    /// Named criteria profiles. When non-empty, each novel is scored against
+    /// every profile instead of `criteria`.
+    pub profiles: Vec<CriteriaProfile>,
+    /// Which evaluation mode to use.
+    pub eval_mode: EvalMode,
+    /// Look up each author's other fictions to score their track record.
+    pub author_reputation: bool,
+    /// Which reviews are passed to the evaluator, in what order.
+    pub review_policy: ReviewPolicy,
+    /// How to obtain seed novels.
+    pub seed_source: SeedSource,
+    /// When to stop the pipeline.
+    pub stop_condition: StopCondition,
+    /// Longest one novel may spend being scraped and evaluated before it's
+    /// abandoned. Checked between steps, so a step in progress can overshoot
+    /// it by up to one request timeout.
+    pub max_time_per_novel: Option<Duration>,
+    /// Longest seed gathering may take before it stops scraping seeds, so a
+    /// big search leaves the rest of a `max_time` run for evaluation.
+    pub seed_time_budget: Option<Duration>,
+    /// Most seeds gathered. Set by [`AppConfig::limit_novels`] (`--limit`),
+    /// never by the config file.
+    pub max_seeds: Option<usize>,
+    /// The order the seeded queue is evaluated in.
+    pub sampling: Sampling,
+    /// Requests one novel may make for its optional enrichments (sampled
+    /// chapters, author pages, the first chapter) before the rest are
+    /// skipped. Its own page is always fetched.
+    pub max_requests_per_novel: u64,
+    /// Sources to discover new novels from, after each novel is evaluated.
+    /// Empty when discovery is off.
+    pub discovery: Vec<DiscoveryConfig>,
+    /// Least best score an evaluated novel needs before any discovery
+    /// source is asked about it; 0.0 lets every evaluated novel through.
+    pub discovery_min_score: f64,
+    /// Most discovered novels evaluated per evaluated seed; further
+    /// discovered novels are recorded as found but not evaluated. `None` for
+    /// no limit.
+    pub max_discovery_ratio: Option<f64>,
+    /// Scraper settings.
+    pub scraper: ScraperConfig,
+    /// Settings for exports and the reading list.
+    pub output: OutputConfig,
+    /// Extra regex patterns for description lines to strip before evaluation,
+    /// in addition to the built-in boilerplate patterns.
+    pub description_strip_patterns: Vec<String>,
+    /// The site novels are found on.
+    pub site: SiteId,
+}
+
+impl AppConfig {
+    /// Bound the run to `limit` novels whatever the config says, as
+    /// `--limit` does: stop after `limit` novels are evaluated, discoveries
+    /// included, or at the configured stop condition if that comes first,
+    /// and gather at most `limit` seeds.
+    pub fn limit_novels(&mut self, limit: usize) {
+        let configured = std::mem::replace(&mut self.stop_condition, StopCondition::EmptyQueue);
+        self.stop_condition = StopCondition::Any(vec![configured, StopCondition::MaxNovels(limit)]);
+        self.max_seeds = Some(self.max_seeds.map_or(limit, |max| max.min(limit)));
+    }
+
+    /// Write every LLM evaluation's prompts and API response into `dir`, as
+    /// `--audit-dir` does. Fails unless the config uses the LLM evaluator.
+    pub fn audit_llm_to(&mut self, dir: &Path) -> Result<()> {
+        match &mut self.eval_mode {
+            EvalMode::Llm(llm) => {
+                llm.audit_dir = Some(dir.to_path_buf());
+                Ok(())
+            }
+            _ => anyhow::bail!("--audit-dir needs the LLM evaluator ([eval] mode = \"llm\")"),
+        }
+    }
+
+    /// A fingerprint of everything that affects scores: the criteria,
+    /// profiles, and evaluator. Runs with different hashes may not have
+    /// comparable scores.
+    pub fn criteria_hash(&self) -> String {
+        let mut evaluator = match &self.eval_mode {
+            EvalMode::Local(local) => match local.popularity_signal {
+                PopularitySignal::Followers => format!("local:{}", local.popularity_saturation),
+                PopularitySignal::Badges => {
+                    format!("local:{}:badges", local.popularity_saturation)
+                }
+            },
+            EvalMode::Llm(llm) => format!("llm:{}:{}", llm.model, llm.include_first_chapter),
+            EvalMode::Custom(custom) => format!(
+                "{}:{}",
+                custom.name,
+                serde_json::to_string(&custom.settings).unwrap_or_default()
+            ),
+        };
+        // Only a non-default policy is added, so existing hashes still match
+        let policy = &self.review_policy;
+        if *policy != ReviewPolicy::default() {
+            let (selection, min_chars, min_kept) =
+                (policy.selection, policy.min_chars, policy.min_kept);
+            evaluator.push_str(&format!(":reviews:{}:{}:{}", selection, min_chars, min_kept));
+        }
+        let fingerprint = serde_json::json!({
+            "criteria": self.criteria,
+            "profiles": self.profiles,
+            "evaluator": evaluator,
+        });
+        format!("{:016x}", util::fnv1a_64(fingerprint.to_string().as_bytes()))
+    }
+}
+
+/// Raw TOML structure for deserialization.
+#[derive(Debug, Deserialize)]
+struct RawConfig {
+    /// Files to load first and merge this one over; resolved by
+    /// [`load_config`], which removes the key before parsing.
+    include: Option<toml::Value>,
+    config: Option<RawConfigOptions>,
+    site: Option<String>,
+    #[serde(default)]
+    criteria: RawCriteria,
+    /// Each `[[profiles]]` entry is a criteria table plus a `name`. Kept as
+    /// raw tables so the name can be split off before deserializing the
+    /// criteria, which keeps the key named in type errors.
+    profiles: Option<Vec<toml::Table>>,
+    eval: RawEval,
+    seeds: RawSeeds,
+    run: RawRun,
+    scraper: Option<RawScraper>,
+    output: Option<RawOutput>,
+    /// Applied by [`load_logging_config`], before the rest of the config is
+    /// loaded; only validated here.
+    logging: Option<RawLogging>,
+}
+
+/// Just the `[logging]` table, read before the rest of the config so logging
+/// can start before config warnings are emitted.
+#[derive(Debug, Deserialize)]
+struct RawLoggingOnly {
+    logging: Option<RawLogging>,
+}
+
+/// Options about the config file itself, from the `[config]` table.
+#[derive(Debug, Deserialize)]
+struct RawConfigOptions {
+    /// Treat unknown keys as errors instead of warnings.
+    strict: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawCriteria {
+    prompt: Option<String>,
+    min_pages: Option<u64>,
+    max_pages: Option<u64>,
+    min_rating: Option<f64>,
+    min_words: Option<u64>,
+    max_words: Option<u64>,
+    min_pages_per_chapter: Option<f64>,
+    max_pages_per_chapter: Option<f64>,
+    allowed_statuses: Option<Vec<String>>,
+    status_policy: Option<Vec<RawStatusRule>>,
+    max_fiction_age_days: Option<u64>,
+    min_fiction_age_days: Option<u64>,
+    required_tags: Option<Vec<String>>,
+    excluded_tags: Option<Vec<String>>,
+    required_genres: Option<Vec<String>>,
+    excluded_genres: Option<Vec<String>>,
+    allow_fanfiction: Option<bool>,
+    exclude_ai_content: Option<bool>,
+    exclude_stubs: Option<bool>,
+    estimate_stub_pages: Option<bool>,
+    soft_filters: Option<bool>,
+    soft_rating_tolerance: Option<f64>,
+    soft_size_tolerance: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawStatusRule {
+    status: String,
+    max_days_since_update: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEval {
+    mode: String,
+    author_reputation: Option<bool>,
+    review_selection: Option<String>,
+    min_review_chars: Option<usize>,
+    min_reviews_kept: Option<usize>,
+    description_strip_patterns: Option<Vec<String>>,
+    local: Option<RawLocalEval>,
+    llm: Option<RawLlmEval>,
+    /// Deprecated flat spellings of the `[eval.llm]` keys, still accepted
+    /// with a warning; see [`RawEval::take_legacy_llm_keys`].
+    llm_api_key: Option<String>,
+    llm_model: Option<String>,
+    llm_endpoint: Option<String>,
+    include_first_chapter: Option<bool>,
+    proxy: Option<String>,
+}
+
+impl RawEval {
+    /// Move the deprecated flat LLM keys into `[eval.llm]`, warning about
+    /// each one. A key set both ways is an error rather than a guess.
+    fn take_legacy_llm_keys(&mut self, errors: &mut Vec<String>) -> RawLlmEval {
+        let mut llm = self.llm.take().unwrap_or_default();
+        move_legacy_key(
+            self.llm_api_key.take(),
+            &mut llm.api_key,
+            "llm_api_key",
+            "api_key",
+            errors,
+        );
+        move_legacy_key(self.llm_model.take(), &mut llm.model, "llm_model", "model", errors);
+        move_legacy_key(
+            self.llm_endpoint.take(),
+            &mut llm.endpoint,
+            "llm_endpoint",
+            "endpoint",
+            errors,
+        );
+        move_legacy_key(
+            self.include_first_chapter.take(),
+            &mut llm.include_first_chapter,
+            "include_first_chapter",
+            "include_first_chapter",
+            errors,
+        );
+        move_legacy_key(self.proxy.take(), &mut llm.proxy, "proxy", "proxy", errors);
+        llm
+    }
+}
+
+/// Move a deprecated `[eval]` key's value to its `[eval.llm]` replacement.
+fn move_legacy_key<T>(
+    legacy: Option<T>,
+    current: &mut Option<T>,
+    old: &str,
+    new: &str,
+    errors: &mut Vec<String>,
+) {
+    let Some(value) = legacy else {
+        return;
+    };
+    if current.is_some() {
+        errors.push(format!(
+            "eval.{}: also set as eval.llm.{}; remove the deprecated key",
+            old, new
+        ));
+        return;
+    }
+    tracing::warn!("eval.{} is deprecated; set {} under [eval.llm] instead", old, new);
+    *current = Some(value);
+}
+
+#[derive(Debug, Deserialize)]
+struct RawLocalEval {
+    popularity_saturation: Option<u64>,
+    popularity_signal: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawLlmEval {
+    api_key: Option<String>,
+    model: Option<String>,
+    endpoint: Option<String>,
+    include_first_chapter: Option<bool>,
+    proxy: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSeeds {
+    source: String,
+    urls: Option<Vec<String>>,
+    search_query: Option<String>,
+    search_max_results: Option<usize>,
+    tags: Option<Vec<String>>,
+    order_by: Option<String>,
+    max_results: Option<usize>,
+    count: Option<usize>,
+    max_id: Option<u64>,
+    rng_seed: Option<u64>,
+    max_attempts: Option<usize>,
+    file: Option<PathBuf>,
+    path: Option<PathBuf>,
+    filter: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRun {
+    stop_condition: RawStopCondition,
+    /// Legacy switch for the default "also liked" source; see `discovery`.
+    discovery_enabled: Option<bool>,
+    discovery: Option<Vec<RawDiscovery>>,
+    discovery_min_score: Option<f64>,
+    max_discovery_ratio: Option<f64>,
+    max_seconds_per_novel: Option<u64>,
+    max_requests_per_novel: Option<u64>,
+    seed_time_budget: Option<RawNumberOrString>,
+    sampling: Option<String>,
+    sampling_by: Option<String>,
+    sampling_strata: Option<usize>,
+    sampling_seed: Option<u64>,
+}
+
+/// One `[[run.discovery]]` entry. Source-specific keys are only accepted
+/// for their source.
+#[derive(Debug, Deserialize)]
+struct RawDiscovery {
+    kind: String,
+    max_per_novel: Option<usize>,
+    min_source_score: Option<f64>,
+    /// also_liked only.
+    use_cache: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawStopCondition {
+    #[serde(rename = "type")]
+    kind: String,
+    value: Option<RawNumberOrString>,
+}
+
+/// A config value that may be written as a bare integer or a string,
+/// such as a duration given as seconds or as "1h30m".
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged, expecting = "expected a whole number or a string")]
+enum RawNumberOrString {
+    Number(u64),
+    Text(String),
+}
+
+impl RawNumberOrString {
+    /// Interpret the value as a duration; bare integers are seconds.
+    fn to_duration(&self) -> Result<Duration> {
+        match self {
+            RawNumberOrString::Number(seconds) => Ok(Duration::from_secs(*seconds)),
+            RawNumberOrString::Text(text) => parse_duration(text),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawScraper {
+    estimate_word_count: Option<bool>,
+    max_response_bytes: Option<u64>,
+    request_timeout: Option<RawNumberOrString>,
+    max_challenge_streak: Option<usize>,
+    store_chapter_titles: Option<String>,
+    ignore_robots: Option<bool>,
+    proxy: Option<String>,
+    cache_dir: Option<PathBuf>,
+    cache_ttl: Option<RawNumberOrString>,
+    max_cache_mb: Option<u64>,
+    max_cache_entries: Option<usize>,
+    rate_limits: Option<RawRateLimits>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawRateLimits {
+    pages: Option<RawNumberOrString>,
+    api: Option<RawNumberOrString>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawOutput {
+    min_score: Option<f64>,
+    tag_report: Option<RawTagReport>,
+    include_full_novel: Option<bool>,
+    browser_command: Option<String>,
+    group_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawTagReport {
+    min_count: Option<usize>,
+    sort: Option<String>,
+    high_score: Option<f64>,
+    max_pairs: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawLogging {
+    verbose: Option<bool>,
+    /// Directory for per-run log files.
+    log_file: Option<PathBuf>,
+    format: Option<String>,
+}
+
+impl RawLogging {
+    /// Build logging settings, falling back to defaults for invalid values
+    /// (which `parse_config` reports).
+    fn build(raw: Option<RawLogging>) -> LoggingConfig {
+        raw.map(|l| LoggingConfig {
+            verbose: l.verbose.unwrap_or(false),
+            log_dir: l.log_file,
+            format: l
+                .format
+                .and_then(|f| parse_log_format(&f).ok())
+                .unwrap_or_default(),
+        })
+        .unwrap_or_default()
+    }
+}
+
+fn parse_tag_sort(s: &str) -> Result<TagSort> {
+    match s.to_lowercase().as_str() {
+        "count" => Ok(TagSort::Count),
+        "mean_score" => Ok(TagSort::MeanScore),
+        other => anyhow::bail!(
+            "Unknown tag report sort: {} (expected \"count\" or \"mean_score\")",
+            other
+        ),
+    }
+}
+
+fn parse_popularity_signal(s: &str) -> Result<PopularitySignal> {
+    match s.to_lowercase().as_str() {
+        "followers" => Ok(PopularitySignal::Followers),
+        "badges" => Ok(PopularitySignal::Badges),
+        other => anyhow::bail!(
+            "Unknown popularity signal: {} (expected \"followers\" or \"badges\")",
+            other
+        ),
+    }
+}
+
+fn parse_stratify_by(s: &str) -> Result<StratifyBy> {
+    match s.to_lowercase().as_str() {
+        "followers" => Ok(StratifyBy::Followers),
+        "rating" => Ok(StratifyBy::Rating),
+        other => anyhow::bail!(
+            "Unknown sampling statistic: {} (expected \"followers\" or \"rating\")",
+            other
+        ),
+    }
+}
+
+fn parse_group_by(s: &str) -> Result<GroupBy> {
+    match s.to_lowercase().as_str() {
+        "none" => Ok(GroupBy::None),
+        "genre" => Ok(GroupBy::Genre),
+        "status" => Ok(GroupBy::Status),
+        other => anyhow::bail!(
+            "Unknown grouping: {} (expected \"genre\", \"status\", or \"none\")",
+            other
+        ),
+    }
+}
+
+fn parse_chapter_title_storage(s: &str) -> Result<ChapterTitleStorage> {
+    match s.to_lowercase().as_str() {
+        "none" => Ok(ChapterTitleStorage::None),
+        "sample" => Ok(ChapterTitleStorage::Sample),
+        "all" => Ok(ChapterTitleStorage::All),
+        other => anyhow::bail!(
+            "Unknown chapter title storage: {} (expected \"none\", \"sample\", or \"all\")",
+            other
+        ),
+    }
+}
+
+fn parse_site(s: &str) -> Result<SiteId> {
+    SiteId::from_name(&s.to_lowercase()).with_context(|| {
+        let known: Vec<String> = SiteId::ALL.iter().map(|site| format!("\"{}\"", site)).collect();
+        format!("Unknown site: {} (expected {})", s, known.join(" or "))
+    })
+}
+
+fn parse_review_selection(s: &str) -> Result<ReviewSelection> {
+    match s.to_lowercase().as_str() {
+        "page_order" => Ok(ReviewSelection::PageOrder),
+        "most_helpful" => Ok(ReviewSelection::MostHelpful),
+        "longest" => Ok(ReviewSelection::Longest),
+        "newest" => Ok(ReviewSelection::Newest),
+        other => anyhow::bail!(
+            "Unknown review selection: {} (expected \"page_order\", \"most_helpful\", \
+             \"longest\", or \"newest\")",
+            other
+        ),
+    }
+}
+
+fn parse_dataset_filter(s: &str) -> Result<DatasetFilter> {
+    match s.to_lowercase().as_str() {
+        "all" => Ok(DatasetFilter::All),
+        "unevaluated" => Ok(DatasetFilter::Unevaluated),
+        other => anyhow::bail!(
+            "Unknown dataset filter: {} (expected \"all\" or \"unevaluated\")",
+            other
+        ),
+    }
+}
+
+fn parse_search_order(s: &str) -> Result<SearchOrder> {
+    SearchOrder::from_name(&s.to_lowercase()).with_context(|| {
+        let known: Vec<&str> = SearchOrder::ALL.iter().map(|order| order.name()).collect();
+        format!("Unknown order: {} (expected one of {})", s, known.join(", "))
+    })
+}
+
+fn parse_log_format(s: &str) -> Result<LogFormat> {
+    match s.to_lowercase().as_str() {
+        "plain" => Ok(LogFormat::Plain),
+        "json" => Ok(LogFormat::Json),
+        other => anyhow::bail!("Unknown log format: {} (expected \"plain\" or \"json\")", other),
+    }
+}
+
+/// Parse a human-friendly duration like "250ms", "90s", "30m", "2h", "1d",
+/// or "1h30m".
+///
+/// A bare number is taken as seconds. Components may be separated by spaces.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        anyhow::bail!("empty duration");
+    }
+    if let Ok(seconds) = trimmed.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut total: u64 = 0;
+    let mut rest = trimmed;
+    while !rest.is_empty() {
+        let digits_len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits_len == 0 {
+            anyhow::bail!("invalid duration \"{}\": expected a number at \"{}\"", s, rest);
+        }
+        let amount: u64 = rest[..digits_len]
+            .parse()
+            .with_context(|| format!("invalid duration \"{}\": number too large", s))?;
+        rest = rest[digits_len..].trim_start();
+
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = &rest[..unit_len];
+        let millis_per_unit = match unit {
+            "ms" | "msec" | "msecs" | "millisecond" | "milliseconds" => 1,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1_000,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60_000,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3_600_000,
+            "d" | "day" | "days" => 86_400_000,
+            "" => anyhow::bail!(
+                "invalid duration \"{}\": missing unit after {} (use ms, s, m, h, or d)",
+                s,
+                amount
+            ),
+            other => anyhow::bail!(
+                "invalid duration \"{}\": unknown unit \"{}\" (use ms, s, m, h, or d)",
+                s,
+                other
+            ),
+        };
+        total = amount
+            .checked_mul(millis_per_unit)
+            .and_then(|millis| total.checked_add(millis))
+            .with_context(|| format!("invalid duration \"{}\": too large", s))?;
+        rest = rest[unit_len..].trim_start();
+    }
+
+    Ok(Duration::from_millis(total))
+}
+
+/// Parse a status string into a `NovelStatus`.
+fn parse_status(s: &str) -> Result<NovelStatus> {
+    match s.to_lowercase().as_str() {
+        "ongoing" => Ok(NovelStatus::Ongoing),
+        "completed" => Ok(NovelStatus::Completed),
+        "hiatus" => Ok(NovelStatus::Hiatus),
+        "dropped" => Ok(NovelStatus::Dropped),
+        "stub" => Ok(NovelStatus::Stub),
+        other => anyhow::bail!("Unknown novel status: {}", other),
+    }
+}
+
+/// Rewrite user-specified tags to their canonical RoyalRoad names.
+///
+/// Tags that don't match any known RoyalRoad tag are kept as written, with a
+/// warning since they are most likely typos and will never match.
+fn normalize_criteria_tags(tags: Vec<String>, field: &str) -> Vec<String> {
+    tags.into_iter()
+        .map(|tag| match normalize_tag(&tag) {
+            Some(canonical) => canonical.name.to_string(),
+            None => {
+                tracing::warn!(
+                    "{}: '{}' is not a known RoyalRoad tag and may never match",
+                    field,
+                    tag
+                );
+                tag
+            }
+        })
+        .collect()
+}
+
+/// Warn about genre criteria that name a known tag which isn't a genre,
+/// since genre criteria are matched against the novel's genres only.
+fn warn_non_genres(genres: &[String], field: &str) {
+    for genre in genres {
+        if let Some(canonical) = normalize_tag(genre).filter(|c| !c.is_genre()) {
+            tracing::warn!(
+                "{}: '{}' is a tag, not a genre; use required_tags/excluded_tags instead",
+                field,
+                canonical
+            );
+        }
+    }
+}
+
+/// Build typed criteria from a raw criteria table.
+///
+/// `section` is the table's key path (e.g. "criteria" or "profiles.cozy"),
+/// used in error and warning messages. Tags are checked against RoyalRoad's
+/// taxonomy only when `site` is RoyalRoad. Problems are recorded in
+/// `errors`; returns `None` if any were found.
+fn build_criteria(
+    raw: RawCriteria,
+    section: &str,
+    site: SiteId,
+    errors: &mut Vec<String>,
+) -> Option<Criteria> {
+    let error_count = errors.len();
+
+    // An empty list would allow nothing, which is never what's meant, so
+    // it's an error rather than a filter that rejects every novel
+    let allowed_statuses = raw.allowed_statuses.and_then(|statuses| {
+        let field = format!("{}.allowed_statuses", section);
+        if statuses.is_empty() {
+            errors.push(format!(
+                "{}: must list at least one status; leave it out to allow every status",
+                field
+            ));
+            return None;
+        }
+        let mut allowed = Vec::new();
+        for s in &statuses {
+            match parse_status(s) {
+                Ok(status) if allowed.contains(&status) => {
+                    tracing::warn!("{}: '{}' is listed more than once", field, s);
+                }
+                Ok(status) => allowed.push(status),
+                Err(e) => errors.push(format!("{}: {}", field, e)),
+            }
+        }
+        Some(allowed)
+    });
+
+    let status_policy = raw.status_policy.map(|rules| {
+        rules
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, rule)| match parse_status(&rule.status) {
+                Ok(status) => Some(StatusRule {
+                    status,
+                    max_days_since_update: rule.max_days_since_update,
+                }),
+                Err(e) => {
+                    errors.push(format!("{}.status_policy[{}].status: {}", section, index, e));
+                    None
+                }
+            })
+            .collect::<Vec<_>>()
+    });
+
+    if allowed_statuses.is_some() && status_policy.is_some() {
+        errors.push(format!(
+            "{0}.allowed_statuses and {0}.status_policy cannot both be set; \
+             use status_policy for qualified rules",
+            section
+        ));
+    }
+
+    let royalroad = site == SiteId::RoyalRoad;
+    for (genres, field) in [
+        (&raw.required_genres, "required_genres"),
+        (&raw.excluded_genres, "excluded_genres"),
+    ] {
+        if let Some(genres) = genres.as_ref().filter(|_| royalroad) {
+            warn_non_genres(genres, &format!("{}.{}", section, field));
+        }
+    }
+
+    let soft_filters = build_soft_filters(
+        raw.soft_filters,
+        [raw.soft_rating_tolerance, raw.soft_size_tolerance],
+        section,
+        errors,
+    );
+
+    if errors.len() > error_count {
+        return None;
+    }
+
+    let normalize = |tags: Option<Vec<String>>, field: &str| {
+        let field = format!("{}.{}", section, field);
+        if royalroad {
+            tags.map(|tags| normalize_criteria_tags(tags, &field))
+        } else {
+            tags
+        }
+    };
+    Some(Criteria {
+        prompt: raw.prompt,
+        min_pages: raw.min_pages,
+        max_pages: raw.max_pages,
+        min_rating: raw.min_rating,
+        min_words: raw.min_words,
+        max_words: raw.max_words,
+        min_pages_per_chapter: raw.min_pages_per_chapter,
+        max_pages_per_chapter: raw.max_pages_per_chapter,
+        allowed_statuses,
+        status_policy,
+        max_fiction_age_days: raw.max_fiction_age_days,
+        min_fiction_age_days: raw.min_fiction_age_days,
+        required_tags: normalize(raw.required_tags, "required_tags"),
+        excluded_tags: normalize(raw.excluded_tags, "excluded_tags"),
+        required_genres: normalize(raw.required_genres, "required_genres"),
+        excluded_genres: normalize(raw.excluded_genres, "excluded_genres"),
+        allow_fanfiction: raw.allow_fanfiction,
+        exclude_ai_content: raw.exclude_ai_content,
+        exclude_stubs: raw.exclude_stubs,
+        estimate_stub_pages: raw.estimate_stub_pages,
+        soft_filters,
+    })
+}
+
+/// The soft filter tolerances from a criteria table (its rating and size
+/// tolerances, in that order), if `soft_filters` is on, using the defaults
+/// for tolerances left out.
+fn build_soft_filters(
+    enabled: Option<bool>,
+    [rating_tolerance, size_tolerance]: [Option<f64>; 2],
+    section: &str,
+    errors: &mut Vec<String>,
+) -> Option<SoftFilters> {
+    let defaults = SoftFilters::default();
+    let mut tolerance = |value: Option<f64>, field: &str, default: f64| match value {
+        Some(value) if !(value >= 0.0 && value.is_finite()) => {
+            errors.push(format!("{}.{}: must be 0 or more", section, field));
+            default
+        }
+        Some(value) => value,
+        None => default,
+    };
+    let soft_filters = SoftFilters {
+        rating_tolerance: tolerance(
+            rating_tolerance,
+            "soft_rating_tolerance",
+            defaults.rating_tolerance,
+        ),
+        size_tolerance: tolerance(
+            size_tolerance,
+            "soft_size_tolerance",
+            defaults.size_tolerance,
+        ),
+    };
+    if enabled == Some(true) {
+        return Some(soft_filters);
+    }
+    for (value, field) in [
+        (rating_tolerance, "soft_rating_tolerance"),
+        (size_tolerance, "soft_size_tolerance"),
+    ] {
+        if value.is_some() {
+            tracing::warn!(
+                "{0}.{1} has no effect without {0}.soft_filters = true",
+                section,
+                field
+            );
+        }
+    }
+    None
+}
+
+/// Load the application configuration from a TOML file at the given path,
+/// merged over any files it lists under `include`.
+pub fn load_config(path: &Path) -> Result<AppConfig> {
+    let merged = include::load(path)?;
+    if !merged.has_includes {
+        // Parse the text itself, so errors can point at lines and columns
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        return parse_config(&content)
+            .with_context(|| format!("Invalid config file: {}", path.display()));
+    }
+    let raw: RawConfig = toml::Value::Table(merged.table.clone())
+        .try_into()
+        .map_err(|e| {
+            let problem = describe_value_error("", &e);
+            let problem = merged.origins.attribute(problem.trim_start_matches(['.', ':', ' ']));
+            anyhow::anyhow!("Failed to parse config TOML: {}", problem)
+        })
+        .with_context(|| format!("Invalid config file: {}", path.display()))?;
+    build_config(raw, &merged.table, Some(&merged.origins))
+        .with_context(|| format!("Invalid config file: {}", path.display()))
+}
+
+/// Read only the `[logging]` settings from a config file.
+///
+/// Used to set up logging before the full config is loaded. Problems are
+/// left for [`load_config`] to report, so an unreadable or invalid file
+/// yields the default settings here.
+pub fn load_logging_config(path: &Path) -> LoggingConfig {
+    include::load(path)
+        .ok()
+        .and_then(|merged| toml::Value::Table(merged.table).try_into::<RawLoggingOnly>().ok())
+        .map(|raw| RawLogging::build(raw.logging))
+        .unwrap_or_default()
+}
+
+/// Parse the application configuration from TOML text.
+///
+/// Semantic problems (unknown modes, missing values, conflicting options)
+/// are collected and reported together rather than one at a time.
+pub fn parse_config(content: &str) -> Result<AppConfig> {
+    let raw: RawConfig =
+        toml::from_str(content).map_err(|e| describe_toml_error(content, &e))?;
+    let table: toml::Table = toml::from_str(content).context("Failed to parse config TOML")?;
+    build_config(raw, &table, None)
+}
+
+/// Validate a parsed config and build the [`AppConfig`]. `table` is the same
+/// config, used to find unknown keys. With `origins`, each problem names the
+/// included file that set the key.
+fn build_config(
+    mut raw: RawConfig,
+    table: &toml::Table,
+    origins: Option<&include::Origins>,
+) -> Result<AppConfig> {
+    let mut errors: Vec<String> = Vec::new();
+    if raw.include.is_some() {
+        errors.push(format!(
+            "{}: includes are only resolved when loading a config file",
+            include::INCLUDE_KEY
+        ));
+    }
+
+    // Report keys that no option recognizes, which serde would otherwise drop
+    let strict = raw.config.as_ref().and_then(|c| c.strict).unwrap_or(false);
+    let custom = custom_sections(&raw);
+    let is_custom = |path: &str| custom.iter().any(|section| keys::is_within(path, section));
+    for unknown in keys::find_unknown_keys(table) {
+        if is_custom(&unknown.path) {
+            continue;
+        }
+        let unknown = match origins {
+            Some(origins) => origins.attribute(&unknown.to_string()),
+            None => unknown.to_string(),
+        };
+        if strict {
+            errors.push(unknown);
+        } else {
+            tracing::warn!(
+                "{}; ignoring it (set strict = true under [config] to make this an error)",
+                unknown
+            );
+        }
+    }
+
+    let site = match raw.site.as_deref().map(parse_site).transpose() {
+        Ok(site) => site.unwrap_or_default(),
+        Err(e) => {
+            errors.push(format!("site: {}", e));
+            SiteId::default()
+        }
+    };
+
+    // Build criteria and profiles
+    let criteria = build_criteria(raw.criteria, "criteria", site, &mut errors);
+    let mut profiles: Vec<CriteriaProfile> = Vec::new();
+    let mut profile_names: Vec<String> = Vec::new();
+    for (index, mut table) in raw.profiles.unwrap_or_default().into_iter().enumerate() {
+        let name = match table.remove("name") {
+            Some(toml::Value::String(name)) if !name.trim().is_empty() => name,
+            _ => {
+                errors.push(format!(
+                    "profiles[{}].name: every [[profiles]] entry needs a non-empty name",
+                    index
+                ));
+                continue;
+            }
+        };
+        if profile_names.contains(&name) {
+            errors.push(format!("profiles[{}].name: Duplicate profile name: {}", index, name));
+            continue;
+        }
+        profile_names.push(name.clone());
+        let section = format!("profiles.{}", name);
+        let raw_criteria = match toml::Value::Table(table).try_into::<RawCriteria>() {
+            Ok(raw_criteria) => raw_criteria,
+            Err(e) => {
+                errors.push(describe_value_error(&section, &e));
+                continue;
+            }
+        };
+        if let Some(criteria) = build_criteria(raw_criteria, &section, site, &mut errors) {
+            profiles.push(CriteriaProfile { name, criteria });
+        }
+    }
+
+    // Build eval mode, with the settings of the chosen evaluator
+    let mut local_eval = LocalEvalConfig::default();
+    if let Some(raw_local) = raw.eval.local.take() {
+        if let Some(saturation) = raw_local.popularity_saturation {
+            if saturation == 0 {
+                errors
+                    .push("eval.local.popularity_saturation: must be greater than 0".to_string());
+            }
+            local_eval.popularity_saturation = saturation;
+        }
+        if let Some(signal) = raw_local.popularity_signal {
+            match parse_popularity_signal(&signal) {
+                Ok(signal) => local_eval.popularity_signal = signal,
+                Err(e) => errors.push(format!("eval.local.popularity_signal: {}", e)),
+            }
+        }
+    }
+    let llm = raw.eval.take_legacy_llm_keys(&mut errors);
+    let eval_mode = match raw.eval.mode.as_str() {
+        "local" => Some(EvalMode::Local(local_eval)),
+        "llm" => {
+            let api_key = require(
+                &mut errors,
+                llm.api_key,
+                "eval.llm.api_key: LLM mode requires api_key under [eval.llm]",
+            )
+            .and_then(|key| match secrets::resolve_secret(&key) {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    errors.push(format!("eval.llm.api_key: {:#}", e));
+                    None
+                }
+            });
+            let model = require(
+                &mut errors,
+                llm.model,
+                "eval.llm.model: LLM mode requires model under [eval.llm]",
+            );
+            let endpoint = require(
+                &mut errors,
+                llm.endpoint,
+                "eval.llm.endpoint: LLM mode requires endpoint under [eval.llm]",
+            );
+            let proxy = llm.proxy.and_then(|proxy| match parse_proxy_setting(&proxy) {
+                Ok(proxy) => Some(proxy),
+                Err(e) => {
+                    errors.push(format!("eval.llm.proxy: {}", e));
+                    None
+                }
+            });
+            match (api_key, model, endpoint) {
+                (Some(api_key), Some(model), Some(endpoint)) => {
+                    Some(EvalMode::Llm(LlmEvalConfig {
+                        api_key,
+                        model,
+                        endpoint,
+                        include_first_chapter: llm.include_first_chapter.unwrap_or(false),
+                        proxy,
+                        audit_dir: None,
+                    }))
+                }
+                _ => None,
+            }
+        }
+        "" => {
+            errors.push("eval.mode: must not be empty".to_string());
+            None
+        }
+        // Anything else names a registered evaluator, checked when the
+        // pipeline is built
+        other => Some(EvalMode::Custom(CustomEvalConfig {
+            name: other.to_string(),
+            settings: section_table(table, &["eval"]).cloned().unwrap_or_default(),
+        })),
+    };
+
+    // Validate extra description strip patterns early
+    let description_strip_patterns = raw.eval.description_strip_patterns.unwrap_or_default();
+    let author_reputation = raw.eval.author_reputation.unwrap_or(false);
+    let mut review_policy = ReviewPolicy::default();
+    if let Some(selection) = raw.eval.review_selection {
+        match parse_review_selection(&selection) {
+            Ok(selection) => review_policy.selection = selection,
+            Err(e) => errors.push(format!("eval.review_selection: {}", e)),
+        }
+    }
+    if let Some(min_chars) = raw.eval.min_review_chars {
+        review_policy.min_chars = min_chars;
+    }
+    if let Some(min_kept) = raw.eval.min_reviews_kept {
+        review_policy.min_kept = min_kept;
+    }
+    if let Err(e) = DescriptionCleaner::new(&description_strip_patterns) {
+        errors.push(format!("eval.description_strip_patterns: {:#}", e));
+    }
+
+    // Build seed source
+    let seed_source = match raw.seeds.source.as_str() {
+        "manual" => require(
+            &mut errors,
+            raw.seeds.urls,
+            "seeds.urls: Manual seed source requires urls",
+        )
+        .map(SeedSource::Manual),
+        "file" => require(
+            &mut errors,
+            raw.seeds.file,
+            "seeds.file: File seed source requires file",
+        )
+        .map(SeedSource::File),
+        "dataset" => {
+            let filter = match raw.seeds.filter.as_deref().map(parse_dataset_filter) {
+                None => Some(DatasetFilter::All),
+                Some(Ok(filter)) => Some(filter),
+                Some(Err(e)) => {
+                    errors.push(format!("seeds.filter: {}", e));
+                    None
+                }
+            };
+            let path = require(
+                &mut errors,
+                raw.seeds.path,
+                "seeds.path: Dataset seed source requires path",
+            );
+            path.zip(filter)
+                .map(|(path, filter)| SeedSource::Dataset { path, filter })
+        }
+        "search" => {
+            if site != SiteId::RoyalRoad {
+                errors.push(format!("seeds.source: Search seeds aren't supported on {}", site));
+            }
+            require(
+                &mut errors,
+                raw.seeds.search_query,
+                "seeds.search_query: Search seed source requires search_query",
+            )
+            .map(|query| SeedSource::Search {
+                query,
+                max_results: raw.seeds.search_max_results.unwrap_or(20),
+            })
+        }
+        "tag" => {
+            let tags = match raw.seeds.tags {
+                Some(tags) if !tags.is_empty() => {
+                    let mut canonical = Vec::new();
+                    for tag in tags {
+                        match normalize_tag(&tag) {
+                            Some(known) if !canonical.contains(&known) => canonical.push(known),
+                            Some(_) => {}
+                            None => errors.push(format!("seeds.tags: Unknown tag: {}", tag)),
+                        }
+                    }
+                    Some(canonical)
+                }
+                _ => {
+                    errors.push("seeds.tags: Tag seed source requires tags".to_string());
+                    None
+                }
+            };
+            let order_by = match raw.seeds.order_by.as_deref().map(parse_search_order) {
+                None => Some(SearchOrder::Popularity),
+                Some(Ok(order_by)) => Some(order_by),
+                Some(Err(e)) => {
+                    errors.push(format!("seeds.order_by: {}", e));
+                    None
+                }
+            };
+            let max_results = raw.seeds.max_results.unwrap_or(20);
+            if max_results == 0 {
+                errors.push("seeds.max_results: must be greater than 0".to_string());
+            }
+            if site != SiteId::RoyalRoad {
+                errors.push(format!("seeds.source: Tag seeds aren't supported on {}", site));
+            }
+            tags.zip(order_by).map(|(tags, order_by)| {
+                SeedSource::Tag(TagSeeds {
+                    tags,
+                    order_by,
+                    max_results,
+                })
+            })
+        }
+        "random" => {
+            let count = require(
+                &mut errors,
+                raw.seeds.count,
+                "seeds.count: Random seed source requires count",
+            );
+            let max_id = require(
+                &mut errors,
+                raw.seeds.max_id,
+                "seeds.max_id: Random seed source requires max_id",
+            );
+            match (count, max_id) {
+                (Some(0), _) => {
+                    errors.push("seeds.count: must be greater than 0".to_string());
+                    None
+                }
+                (_, Some(0)) => {
+                    errors.push("seeds.max_id: must be greater than 0".to_string());
+                    None
+                }
+                (Some(count), Some(max_id)) => {
+                    let max_attempts = raw
+                        .seeds
+                        .max_attempts
+                        .unwrap_or(count.saturating_mul(DEFAULT_ATTEMPTS_PER_RANDOM_SEED));
+                    if max_attempts < count {
+                        errors.push("seeds.max_attempts: must be at least count".to_string());
+                    }
+                    Some(SeedSource::Random(RandomSeeds {
+                        count,
+                        max_id,
+                        rng_seed: raw.seeds.rng_seed,
+                        max_attempts,
+                    }))
+                }
+                _ => None,
+            }
+        }
+        other => {
+            errors.push(format!("seeds.source: Unknown seed source: {}", other));
+            None
+        }
+    };
+
+    // Build stop condition
+    let stop_condition = match raw.run.stop_condition.kind.as_str() {
+        "max_novels" => match raw.run.stop_condition.value {
+            Some(RawNumberOrString::Number(n)) => Some(StopCondition::MaxNovels(n as usize)),
+            Some(RawNumberOrString::Text(text)) => {
+                errors.push(format!(
+                    "run.stop_condition.value: max_novels stop condition requires a whole \
+                     number, got \"{}\"",
+                    text
+                ));
+                None
+            }
+            None => {
+                errors.push(
+                    "run.stop_condition.value: max_novels stop condition requires a value"
+                        .to_string(),
+                );
+                None
+            }
+        },
+        "max_time" => require(
+            &mut errors,
+            raw.run.stop_condition.value,
+            "run.stop_condition.value: max_time stop condition requires a value \
+             (e.g. \"30m\" or seconds)",
+        )
+        .and_then(|value| match value.to_duration() {
+            Ok(duration) => Some(StopCondition::MaxTime(duration)),
+            Err(e) => {
+                errors.push(format!(
+                    "run.stop_condition.value: Invalid max_time stop condition value: {:#}",
+                    e
+                ));
+                None
+            }
+        }),
+        "empty_queue" => Some(StopCondition::EmptyQueue),
+        other => {
+            errors.push(format!("run.stop_condition.type: Unknown stop condition: {}", other));
+            None
+        }
+    };
+
+    // Validate logging settings, which were applied before loading
+    if let Some(format) = raw.logging.and_then(|l| l.format) {
+        if let Err(e) = parse_log_format(&format) {
+            errors.push(format!("logging.format: {}", e));
+        }
+    }
+
+    // Build scraper settings
+    let raw_scraper = raw.scraper.unwrap_or_default();
+    let mut scraper = ScraperConfig {
+        estimate_word_count: raw_scraper.estimate_word_count.unwrap_or(false),
+        max_response_bytes: raw_scraper
+            .max_response_bytes
+            .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES),
+        max_challenge_streak: raw_scraper
+            .max_challenge_streak
+            .unwrap_or(DEFAULT_MAX_CHALLENGE_STREAK),
+        ignore_robots: raw_scraper.ignore_robots.unwrap_or(false),
+        cache_dir: raw_scraper.cache_dir,
+        ..ScraperConfig::default()
+    };
+    if let Some(timeout) = raw_scraper.request_timeout {
+        match timeout.to_duration() {
+            Ok(timeout) if timeout.is_zero() => {
+                errors.push("scraper.request_timeout: must be greater than 0".to_string())
+            }
+            Ok(timeout) => scraper.request_timeout = timeout,
+            Err(e) => errors.push(format!("scraper.request_timeout: {:#}", e)),
+        }
+    }
+    if let Some(ttl) = raw_scraper.cache_ttl {
+        match ttl.to_duration() {
+            Ok(ttl) if ttl.is_zero() => {
+                errors.push("scraper.cache_ttl: must be greater than 0".to_string())
+            }
+            Ok(ttl) => scraper.cache_ttl = ttl,
+            Err(e) => errors.push(format!("scraper.cache_ttl: {:#}", e)),
+        }
+    }
+    let raw_limits = raw_scraper.rate_limits.unwrap_or_default();
+    for (key, raw, delay) in [
+        ("pages", raw_limits.pages, &mut scraper.rate_limits.pages),
+        ("api", raw_limits.api, &mut scraper.rate_limits.api),
+    ] {
+        match raw.map(|raw| raw.to_duration()) {
+            Some(Ok(parsed)) => *delay = parsed,
+            Some(Err(e)) => errors.push(format!("scraper.rate_limits.{}: {:#}", key, e)),
+            None => {}
+        }
+    }
+    match raw_scraper.max_cache_mb {
+        Some(0) => errors.push("scraper.max_cache_mb: must be greater than 0".to_string()),
+        Some(mb) => scraper.cache_limits.max_bytes = Some(mb.saturating_mul(1024 * 1024)),
+        None => {}
+    }
+    match raw_scraper.max_cache_entries {
+        Some(0) => errors.push("scraper.max_cache_entries: must be greater than 0".to_string()),
+        max => scraper.cache_limits.max_entries = max,
+    }
+    if let Some(proxy) = raw_scraper.proxy {
+        match parse_proxy_setting(&proxy) {
+            Ok(proxy) => scraper.proxy = proxy,
+            Err(e) => errors.push(format!("scraper.proxy: {}", e)),
+        }
+    }
+    if let Some(storage) = raw_scraper.store_chapter_titles {
+        match parse_chapter_title_storage(&storage) {
+            Ok(storage) => scraper.store_chapter_titles = storage,
+            Err(e) => errors.push(format!("scraper.store_chapter_titles: {}", e)),
+        }
+    }
+    // Chapter fetches need chapter URLs
+    if scraper.store_chapter_titles == ChapterTitleStorage::None {
+        if scraper.estimate_word_count {
+            errors.push(
+                "scraper.store_chapter_titles: \"none\" keeps no chapters to sample for \
+                 scraper.estimate_word_count"
+                    .to_string(),
+            );
+        }
+        if let Some(EvalMode::Llm(LlmEvalConfig {
+            include_first_chapter: true,
+            ..
+        })) = eval_mode
+        {
+            errors.push(
+                "scraper.store_chapter_titles: \"none\" keeps no first chapter for \
+                 eval.llm.include_first_chapter"
+                    .to_string(),
+            );
+        }
+    }
+    if scraper.max_response_bytes == 0 {
+        errors.push("scraper.max_response_bytes: must be greater than 0".to_string());
+    }
+    if scraper.max_challenge_streak == 0 {
+        errors.push("scraper.max_challenge_streak: must be greater than 0".to_string());
+    }
+
+    // Build output settings
+    let (min_score, raw_tag_report, include_full_novel, browser_command, group_by) =
+        match raw.output {
+            Some(output) => (
+                output.min_score,
+                output.tag_report,
+                output.include_full_novel,
+                output.browser_command,
+                output.group_by,
+            ),
+            None => (None, None, None, None, None),
+        };
+    if min_score.is_some_and(|score| !(0.0..=1.0).contains(&score)) {
+        errors.push("output.min_score: must be between 0.0 and 1.0".to_string());
+    }
+    let browser_command = browser_command
+        .map(|command| command.split_whitespace().map(String::from).collect::<Vec<_>>());
+    if browser_command.as_ref().is_some_and(Vec::is_empty) {
+        errors.push("output.browser_command: must not be empty".to_string());
+    }
+    let group_by = match group_by.as_deref().map(parse_group_by) {
+        Some(Ok(group_by)) => group_by,
+        Some(Err(e)) => {
+            errors.push(format!("output.group_by: {}", e));
+            GroupBy::None
+        }
+        None => GroupBy::None,
+    };
+    let mut tag_report = TagReportConfig::default();
+    if let Some(raw) = raw_tag_report {
+        if let Some(min_count) = raw.min_count {
+            tag_report.min_count = min_count;
+        }
+        if let Some(sort) = raw.sort {
+            match parse_tag_sort(&sort) {
+                Ok(sort) => tag_report.sort = sort,
+                Err(e) => errors.push(format!("output.tag_report.sort: {}", e)),
+            }
+        }
+        if let Some(high_score) = raw.high_score {
+            if !(0.0..=1.0).contains(&high_score) {
+                errors.push(
+                    "output.tag_report.high_score: must be between 0.0 and 1.0".to_string(),
+                );
+            }
+            tag_report.high_score = high_score;
+        }
+        if let Some(max_pairs) = raw.max_pairs {
+            tag_report.max_pairs = max_pairs;
+        }
+    }
+    let sampling = build_sampling(
+        raw.run.sampling.as_deref(),
+        raw.run.sampling_by.as_deref(),
+        raw.run.sampling_strata,
+        raw.run.sampling_seed,
+        &mut errors,
+    );
+    let max_time_per_novel = raw.run.max_seconds_per_novel.map(Duration::from_secs);
+    if max_time_per_novel.is_some_and(|limit| limit.is_zero()) {
+        errors.push("run.max_seconds_per_novel: must be greater than 0".to_string());
+    }
+    let seed_time_budget = match raw.run.seed_time_budget.map(|budget| budget.to_duration()) {
+        Some(Ok(budget)) if budget.is_zero() => {
+            errors.push("run.seed_time_budget: must be greater than 0".to_string());
+            None
+        }
+        Some(Ok(budget)) => {
+            if let Some(limit) = stop_condition.as_ref().and_then(StopCondition::time_limit) {
+                if budget >= limit {
+                    errors.push(format!(
+                        "run.seed_time_budget: must be shorter than the max_time stop \
+                         condition ({}), or nothing is left for evaluation",
+                        util::format_duration(limit)
+                    ));
+                }
+            }
+            Some(budget)
+        }
+        Some(Err(e)) => {
+            errors.push(format!("run.seed_time_budget: {:#}", e));
+            None
+        }
+        None => None,
+    };
+    let max_requests_per_novel = raw
+        .run
+        .max_requests_per_novel
+        .unwrap_or(DEFAULT_MAX_REQUESTS_PER_NOVEL);
+    let discovery = match (raw.run.discovery_enabled, raw.run.discovery) {
+        (Some(_), Some(_)) => {
+            errors.push(
+                "run.discovery_enabled: conflicts with [[run.discovery]]; list the sources \
+                 under [[run.discovery]] only"
+                    .to_string(),
+            );
+            Vec::new()
+        }
+        (Some(true), None) => vec![DiscoveryConfig::also_liked()],
+        (Some(false) | None, None) => Vec::new(),
+        (None, Some(entries)) => build_discovery(entries, table, &mut errors),
+    };
+    let discovery_min_score = raw.run.discovery_min_score.unwrap_or(0.0);
+    if !(0.0..=1.0).contains(&discovery_min_score) {
+        errors.push("run.discovery_min_score: must be between 0.0 and 1.0".to_string());
+    }
+    let max_discovery_ratio = raw.run.max_discovery_ratio;
+    if max_discovery_ratio.is_some_and(|ratio| !ratio.is_finite() || ratio < 0.0) {
+        errors.push("run.max_discovery_ratio: must be 0.0 or more".to_string());
+    }
+
+    let output = OutputConfig {
+        min_score,
+        tag_report,
+        include_full_novel: include_full_novel.unwrap_or(true),
+        browser_command,
+        group_by,
+    };
+
+    match (criteria, eval_mode, seed_source, stop_condition) {
+        (Some(criteria), Some(eval_mode), Some(seed_source), Some(stop_condition))
+            if errors.is_empty() =>
+        {
+            Ok(AppConfig {
+                criteria,
+                profiles,
+                eval_mode,
+                author_reputation,
+                review_policy,
+                seed_source,
+                stop_condition,
+                max_time_per_novel,
+                seed_time_budget,
+                max_seeds: None,
+                sampling,
+                max_requests_per_novel,
+                discovery,
+                discovery_min_score,
+                max_discovery_ratio,
+                scraper,
+                output,
+                description_strip_patterns,
+                site,
+            })
+        }
+        _ => {
+            if let Some(origins) = origins {
+                errors = errors.iter().map(|problem| origins.attribute(problem)).collect();
+            }
+            Err(validation_error(&errors))
+        }
+    }
+}
+
+/// The sampling order from the `[run]` table's `sampling*` keys, pushing any
+/// problems onto `errors`.
+fn build_sampling(
+    sampling: Option<&str>,
+    by: Option<&str>,
+    strata: Option<usize>,
+    rng_seed: Option<u64>,
+    errors: &mut Vec<String>,
+) -> Sampling {
+    let stratified = match sampling.map(str::to_lowercase).as_deref() {
+        None | Some("fifo") => false,
+        Some("stratified") => true,
+        Some(other) => {
+            errors.push(format!(
+                "run.sampling: Unknown sampling: {} (expected \"fifo\" or \"stratified\")",
+                other
+            ));
+            false
+        }
+    };
+    if !stratified {
+        let settings = [
+            ("sampling_by", by.is_some()),
+            ("sampling_strata", strata.is_some()),
+            ("sampling_seed", rng_seed.is_some()),
+        ];
+        for (key, _) in settings.iter().filter(|(_, set)| *set) {
+            tracing::warn!("run.{} has no effect without run.sampling = \"stratified\"", key);
+        }
+        return Sampling::Fifo;
+    }
+
+    let by = match by.map(parse_stratify_by) {
+        Some(Ok(by)) => by,
+        Some(Err(e)) => {
+            errors.push(format!("run.sampling_by: {}", e));
+            StratifyBy::default()
+        }
+        None => StratifyBy::default(),
+    };
+    let strata = strata.unwrap_or(DEFAULT_SAMPLING_STRATA);
+    if strata == 0 {
+        errors.push("run.sampling_strata: must be greater than 0".to_string());
+    }
+    Sampling::Stratified(StratifiedSampling {
+        by,
+        strata,
+        rng_seed,
+    })
+}
+
+/// Build the `[[run.discovery]]` entries, pushing any problems onto
+/// `errors`. Each source may be listed once. `table` is the whole config, for
+/// the entries of sources the config doesn't know.
+fn build_discovery(
+    entries: Vec<RawDiscovery>,
+    table: &toml::Table,
+    errors: &mut Vec<String>,
+) -> Vec<DiscoveryConfig> {
+    let written = section_table(table, &["run"])
+        .and_then(|run| run.get("discovery"))
+        .and_then(toml::Value::as_array);
+    let mut discovery: Vec<DiscoveryConfig> = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let key = format!("run.discovery[{}]", index);
+        let source = match entry.kind.as_str() {
+            "also_liked" => DiscoverySourceConfig::AlsoLiked(AlsoLikedConfig {
+                use_cache: entry.use_cache.unwrap_or(true),
+            }),
+            "" => {
+                errors.push(format!("{}.kind: must not be empty", key));
+                continue;
+            }
+            // Anything else names a registered source, checked when the
+            // pipeline is built
+            other => DiscoverySourceConfig::Custom(CustomDiscoveryConfig {
+                kind: other.to_string(),
+                settings: written
+                    .and_then(|entries| entries.get(index))
+                    .and_then(toml::Value::as_table)
+                    .cloned()
+                    .unwrap_or_default(),
+            }),
+        };
+        if discovery.iter().any(|d| d.source.kind() == source.kind()) {
+            errors.push(format!("{}.kind: {} is listed more than once", key, source));
+            continue;
+        }
+        if entry.max_per_novel == Some(0) {
+            errors.push(format!("{}.max_per_novel: must be greater than 0", key));
+        }
+        if entry.min_source_score.is_some_and(|score| !(0.0..=1.0).contains(&score)) {
+            errors.push(format!("{}.min_source_score: must be between 0.0 and 1.0", key));
+        }
+        discovery.push(DiscoveryConfig {
+            min_source_score: entry.min_source_score,
+            max_per_novel: entry.max_per_novel,
+            source,
+        });
+    }
+    discovery
+}
+
+/// The tables holding the settings of evaluators and discovery sources the
+/// config doesn't know, whose keys only they can check: `[eval.<mode>]` and
+/// the `[[run.discovery]]` entries of other kinds.
+fn custom_sections(raw: &RawConfig) -> Vec<String> {
+    let mut sections = Vec::new();
+    if !matches!(raw.eval.mode.as_str(), "local" | "llm") {
+        sections.push(format!("eval.{}", raw.eval.mode));
+    }
+    for (index, entry) in raw.run.discovery.iter().flatten().enumerate() {
+        if entry.kind != "also_liked" {
+            sections.push(format!("run.discovery[{}]", index));
+        }
+    }
+    sections
+}
+
+/// The table at `path` in a parsed config, if there is one.
+fn section_table<'a>(table: &'a toml::Table, path: &[&str]) -> Option<&'a toml::Table> {
+    path.iter().try_fold(table, |table, key| {
+        table.get(*key).and_then(toml::Value::as_table)
+    })
+}
+
+/// Return `value`, recording `message` as a validation error if it is missing.
+fn require<T>(errors: &mut Vec<String>, value: Option<T>, message: &str) -> Option<T> {
+    if value.is_none() {
+        errors.push(message.to_string());
+    }
+    value
+}
+
+/// Combine collected validation errors into one error listing all of them.
+fn validation_error(errors: &[String]) -> anyhow::Error {
+    match errors {
+        [only] => anyhow::anyhow!("Invalid config: {}", only),
+        _ => anyhow::anyhow!(
+            "Invalid config ({} problems):\n  - {}",
+            errors.len(),
+            errors.join("\n  - ")
+        ),
+    }
+}
+
+/// Turn a TOML deserialization error into a message naming the offending
+/// key path and its line and column.
+fn describe_toml_error(content: &str, err: &toml::de::Error) -> anyhow::Error {
+    let Some(span) = err.span() else {
+        return anyhow::anyhow!("Failed to parse config TOML: {}", err.message());
+    };
+    let (line, column) = line_column(content, span.start);
+    match key_path_at(content, span.start) {
+        Some(path) => anyhow::anyhow!(
+            "Failed to parse config TOML: {} (line {}, column {}): {}",
+            path,
+            line,
+            column,
+            err.message()
+        ),
+        None => anyhow::anyhow!(
+            "Failed to parse config TOML at line {}, column {}: {}",
+            line,
+            column,
+            err.message()
+        ),
+    }
+}
+
+/// Describe an error from deserializing an already-parsed TOML table at
+/// `section`. Such errors carry no span, but do name the key they failed on.
+fn describe_value_error(section: &str, err: &toml::de::Error) -> String {
+    let text = err.to_string();
+    match text.trim_end().rsplit_once("\nin `") {
+        Some((message, key)) => format!("{}.{}: {}", section, key.trim_end_matches('`'), message),
+        None => format!("{}: {}", section, text.trim_end()),
+    }
+}
+
+/// The 1-based line and column of a byte offset.
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..floor_char_boundary(content, offset)];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// The dotted key path (e.g. `run.stop_condition.value`) of the value at a
+/// byte offset in TOML text.
+///
+/// This is a lightweight scan rather than a full parse: it tracks table
+/// headers, `key =` assignments, inline tables, and arrays, which is enough
+/// to name the key a deserialization error points at.
+fn key_path_at(content: &str, offset: usize) -> Option<String> {
+    let offset = floor_char_boundary(content, offset);
+    let line_end = content[offset..].find('\n').map_or(content.len(), |i| offset + i);
+
+    // Find the table the offset belongs to
+    let mut table: Vec<String> = Vec::new();
+    let mut array_counts: HashMap<String, usize> = HashMap::new();
+    let mut body_start = 0;
+    let mut pos = 0;
+    for line in content[..line_end].split_inclusive('\n') {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix("[[") {
+            let name = name.split("]]").next().unwrap_or_default().trim();
+            let count = array_counts.entry(name.to_string()).or_insert(0);
+            table = vec![format!("{}[{}]", name, count)];
+            *count += 1;
+            body_start = pos + line.len();
+        } else if let Some(name) = trimmed.strip_prefix('[') {
+            let name = name.split(']').next().unwrap_or_default();
+            table = name.split('.').map(|k| k.trim().trim_matches('"').to_string()).collect();
+            body_start = pos + line.len();
+        }
+        pos += line.len();
+    }
+    if body_start > offset {
+        // The error points at a table header, e.g. a missing field
+        return (!table.is_empty()).then(|| table.join("."));
+    }
+
+    // Scan the table body up to the offset for the enclosing keys
+    let mut frames: Vec<(Option<String>, usize)> = Vec::new();
+    let mut key: Option<String> = None;
+    let mut brackets: usize = 0;
+    let mut token = String::new();
+    let mut chars = content[body_start..offset].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    if next == c && !escaped {
+                        break;
+                    }
+                    escaped = c == '"' && next == '\\' && !escaped;
+                }
+            }
+            '#' => {
+                chars.by_ref().find(|&next| next == '\n');
+                if brackets == 0 && frames.is_empty() {
+                    key = None;
+                }
+                token.clear();
+            }
+            '=' => {
+                key = Some(token.trim().trim_matches('"').to_string());
+                token.clear();
+            }
+            '{' => {
+                frames.push((key.take(), brackets));
+                brackets = 0;
+                token.clear();
+            }
+            '}' => {
+                (key, brackets) = frames.pop().unwrap_or_default();
+                token.clear();
+            }
+            '[' => {
+                brackets += 1;
+                token.clear();
+            }
+            ']' => {
+                brackets = brackets.saturating_sub(1);
+                token.clear();
+            }
+            ',' | '\n' => {
+                if brackets == 0 && (c == ',' || frames.is_empty()) {
+                    key = None;
+                }
+                token.clear();
+            }
+            _ => token.push(c),
+        }
+    }
+
+    let path: Vec<String> = table
+        .into_iter()
+        .chain(frames.into_iter().filter_map(|(k, _)| k))
+        .chain(key)
+        .collect();
+    (!path.is_empty()).then(|| path.join("."))
+}
+
+/// Round a byte offset down to the nearest character boundary.
+fn floor_char_boundary(content: &str, offset: usize) -> usize {
+    let mut offset = offset.min(content.len());
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["12345"]
+
+[run]
+stop_condition = { type = "max_novels", value = 10 }
+discovery_enabled = false
+"#;
+
+    #[test]
+    fn test_run_settings_display() {
+        let config = parse_config(BASE).unwrap();
+        assert_eq!(config.eval_mode.to_string(), "local");
+        assert_eq!(config.seed_source.to_string(), "manual (URLs: 1)");
+        assert_eq!(config.stop_condition.to_string(), "after 10 novels");
+
+        let search = BASE.replace(
+            "source = \"manual\"\nurls = [\"12345\"]",
+            "source = \"search\"\nsearch_query = \"litrpg\"\nsearch_max_results = 50",
+        );
+        let config = parse_config(&search).unwrap();
+        assert_eq!(config.seed_source.to_string(), "search \"litrpg\" (up to 50 results)");
+    }
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1_800));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7_200));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86_400));
+        assert_eq!(parse_duration("45 minutes").unwrap(), Duration::from_secs(2_700));
+        assert_eq!(parse_duration("600").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_parse_duration_combinations() {
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5_400));
+        assert_eq!(parse_duration("1h 30m 15s").unwrap(), Duration::from_secs(5_415));
+        assert_eq!(parse_duration("2d12h").unwrap(), Duration::from_secs(216_000));
+        assert_eq!(parse_duration("1s 500ms").unwrap(), Duration::from_millis(1_500));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_nonsense() {
+        let err = parse_duration("5 parsecs").unwrap_err().to_string();
+        assert!(err.contains("unknown unit \"parsecs\""), "{}", err);
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("1h30").unwrap_err().to_string().contains("missing unit"));
+        assert!(parse_duration("-5m").is_err());
+        assert!(parse_duration("99999999999999999999d").is_err());
+    }
+
+    #[test]
+    fn test_max_time_accepts_strings_and_seconds() {
+        let with_value = |value: &str| {
+            BASE.replace(
+                r#"{ type = "max_novels", value = 10 }"#,
+                &format!(r#"{{ type = "max_time", value = {} }}"#, value),
+            )
+        };
+
+        let config = parse_config(&with_value(r#""1h30m""#)).unwrap();
+        assert!(matches!(
+            config.stop_condition,
+            StopCondition::MaxTime(d) if d == Duration::from_secs(5_400)
+        ));
+
+        let config = parse_config(&with_value("1800")).unwrap();
+        assert!(matches!(
+            config.stop_condition,
+            StopCondition::MaxTime(d) if d == Duration::from_secs(1_800)
+        ));
+
+        assert!(parse_config(&with_value(r#""5 parsecs""#)).is_err());
+    }
+
+    #[test]
+    fn test_limit_novels_overrides_stop_condition() {
+        let mut config = parse_config(BASE).unwrap();
+        assert_eq!(config.max_seeds, None);
+        config.limit_novels(3);
+        assert_eq!(config.stop_condition.to_string(), "after 10 novels or after 3 novels");
+        assert_eq!(config.max_seeds, Some(3));
+        // A second, looser limit doesn't raise the seed cap
+        config.limit_novels(5);
+        assert_eq!(config.max_seeds, Some(3));
+
+        let mut config = parse_config(&BASE.replace(
+            r#"{ type = "max_novels", value = 10 }"#,
+            r#"{ type = "max_time", value = "30m" }"#,
+        ))
+        .unwrap();
+        config.limit_novels(5);
+        // The configured time limit still applies alongside the limit
+        assert_eq!(config.stop_condition.time_limit(), Some(Duration::from_secs(1_800)));
+        assert!(!config.stop_condition.ends_on_empty_queue());
+
+        let mut config = parse_config(&BASE.replace(
+            r#"{ type = "max_novels", value = 10 }"#,
+            r#"{ type = "empty_queue" }"#,
+        ))
+        .unwrap();
+        config.limit_novels(5);
+        assert!(config.stop_condition.ends_on_empty_queue());
+        assert_eq!(config.stop_condition.time_limit(), None);
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let config = parse_config(&format!(
+            r#"{}
+[[profiles]]
+name = "cozy"
+prompt = "cozy slice of life"
+required_tags = ["slice of life"]
+
+[[profiles]]
+name = "grimdark"
+min_rating = 4.2
+required_tags = ["Grimdark", "progression"]
+"#,
+            BASE
+        ))
+        .unwrap();
+
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.profiles[0].name, "cozy");
+        assert_eq!(
+            config.profiles[0].criteria.required_tags,
+            Some(vec!["Slice of Life".to_string()])
+        );
+        assert_eq!(config.profiles[1].criteria.min_rating, Some(4.2));
+        // Without a [criteria] table the base criteria are unconstrained
+        assert_eq!(config.criteria.min_rating, None);
+    }
+
+    #[test]
+    fn test_parse_site() {
+        assert_eq!(parse_config(BASE).unwrap().site, SiteId::RoyalRoad);
+
+        let with_site = |site: &str| {
+            format!(
+                "site = \"{}\"\n{}\n[criteria]\nrequired_tags = [\"slice of life\"]\n",
+                site, BASE
+            )
+        };
+        let config = parse_config(&with_site("ScribbleHub")).unwrap();
+        assert_eq!(config.site, SiteId::ScribbleHub);
+        // Tags aren't rewritten to RoyalRoad's names for other sites
+        assert_eq!(
+            config.criteria.required_tags,
+            Some(vec!["slice of life".to_string()])
+        );
+        let config = parse_config(&with_site("royalroad")).unwrap();
+        assert_eq!(
+            config.criteria.required_tags,
+            Some(vec!["Slice of Life".to_string()])
+        );
+
+        let err = parse_config(&with_site("wattpad")).unwrap_err().to_string();
+        assert!(
+            err.contains("site: Unknown site: wattpad (expected \"royalroad\" or \"scribblehub\")"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_duplicate_profile_names_rejected() {
+        let err = parse_config(&format!(
+            "{}\n[[profiles]]\nname = \"a\"\n\n[[profiles]]\nname = \"a\"\n",
+            BASE
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("Duplicate profile name: a"));
+    }
+
+    #[test]
+    fn test_profile_errors_name_the_profile() {
+        let err = parse_config(&format!(
+            r#"{}
+[[profiles]]
+name = "moody"
+allowed_statuses = ["completed"]
+status_policy = [{{ status = "ongoing" }}]
+"#,
+            BASE
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("profiles.moody.allowed_statuses"));
+    }
+
+    #[test]
+    fn test_type_errors_name_the_key_path() {
+        let err = parse_config(&BASE.replace("value = 10", "value = true"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("run.stop_condition.value"), "{}", err);
+        assert!(err.contains("line 10"), "{}", err);
+
+        let err = parse_config(&format!("{}\n[criteria]\nmin_pages = \"lots\"\n", BASE))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("criteria.min_pages"), "{}", err);
+        assert!(err.contains("expected u64"), "{}", err);
+
+        let err = parse_config(&format!(
+            "{}\n[[profiles]]\nname = \"a\"\n\n[[profiles]]\nname = \"b\"\nmin_rating = \"high\"\n",
+            BASE
+        ))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("profiles.b.min_rating"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_field_names_the_table() {
+        let err = parse_config(&BASE.replace(
+            r#"stop_condition = { type = "max_novels", value = 10 }"#,
+            "",
+        ))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("run"), "{}", err);
+        assert!(err.contains("missing field `stop_condition`"), "{}", err);
+    }
+
+    #[test]
+    fn test_key_path_in_arrays_and_inline_tables() {
+        let content = "[criteria]\n# statuses\nstatus_policy = [\n  { status = \"ongoing\" },\n  \
+                       { status = 5 },\n]\nmin_pages = 1\n";
+        let offset = content.find('5').unwrap();
+        assert_eq!(
+            key_path_at(content, offset).as_deref(),
+            Some("criteria.status_policy.status")
+        );
+        let offset = content.find('1').unwrap();
+        assert_eq!(key_path_at(content, offset).as_deref(), Some("criteria.min_pages"));
+        assert_eq!(line_column(content, offset), (7, 13));
+    }
+
+    #[test]
+    fn test_allowed_statuses_must_not_be_empty() {
+        let with_statuses = |statuses: &str| {
+            parse_config(&format!("{}\n[criteria]\nallowed_statuses = {}\n", BASE, statuses))
+        };
+        let err = with_statuses("[]").unwrap_err().to_string();
+        assert!(
+            err.contains("criteria.allowed_statuses: must list at least one status"),
+            "{}",
+            err
+        );
+
+        // Duplicates are dropped, however they're spelled
+        let config = with_statuses(r#"["completed", "Ongoing", "COMPLETED"]"#).unwrap();
+        assert_eq!(
+            config.criteria.allowed_statuses,
+            Some(vec![NovelStatus::Completed, NovelStatus::Ongoing])
+        );
+    }
+
+    #[test]
+    fn test_all_validation_errors_are_reported() {
+        let config = BASE
+            .replace(r#"mode = "local""#, r#"mode = "llm""#)
+            .replace(r#"source = "manual""#, r#"source = "carrier pigeon""#);
+        let err = parse_config(&format!(
+            "{}\n[criteria]\nallowed_statuses = [\"finished\"]\n",
+            config
+        ))
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("5 problems"), "{}", err);
+        for key in [
+            "criteria.allowed_statuses",
+            "eval.llm.api_key",
+            "eval.llm.model",
+            "eval.llm.endpoint",
+            "seeds.source",
+        ] {
+            assert!(err.contains(key), "missing {} in {}", key, err);
+        }
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    #[test]
+    fn test_keyring_reference_needs_feature() {
+        let config = BASE.replace(
+            r#"mode = "local""#,
+            r#"mode = "llm"
+[eval.llm]
+api_key = "keyring:novel-finder/anthropic"
+model = "claude"
+endpoint = "https://api.anthropic.com/v1""#,
+        );
+        let err = parse_config(&config).unwrap_err().to_string();
+        assert!(err.contains("eval.llm.api_key"), "{}", err);
+        assert!(err.contains("keyring entry novel-finder/anthropic"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_logging_config() {
+        let path = std::env::temp_dir().join(format!("novel-finder-{}.toml", std::process::id()));
+        let logging = "[logging]\nverbose = true\nlog_file = \"logs\"\nformat = \"json\"\n";
+        std::fs::write(&path, format!("{}\n{}", BASE, logging)).unwrap();
+        let logging = load_logging_config(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(logging.verbose);
+        assert_eq!(logging.log_dir, Some(PathBuf::from("logs")));
+        assert_eq!(logging.format, LogFormat::Json);
+        // Missing files fall back to the defaults; load_config reports them
+        assert!(!load_logging_config(Path::new("/nonexistent/criteria.toml")).verbose);
+    }
+
+    /// Write `files` to a fresh temp directory and load `main.toml` from it.
+    fn load_with_includes(name: &str, files: &[(&str, &str)]) -> Result<AppConfig> {
+        let dir =
+            std::env::temp_dir().join(format!("novel-finder-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            std::fs::write(dir.join(file), content).unwrap();
+        }
+        let config = load_config(&dir.join("main.toml"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_load_config_merges_includes() {
+        let base = format!(
+            "{}\n[criteria]\nmin_pages = 100\nmin_rating = 4.0\n\
+             required_tags = [\"LitRPG\", \"Magic\"]\n",
+            BASE
+        );
+        let config = load_with_includes(
+            "include-merge",
+            &[
+                ("base.toml", &base),
+                (
+                    "main.toml",
+                    "include = [\"base.toml\"]\n[criteria]\nmin_pages = 300\n\
+                     required_tags = [\"Progression\"]\n",
+                ),
+            ],
+        )
+        .unwrap();
+        let criteria = config.criteria;
+        assert_eq!(criteria.min_pages, Some(300));
+        assert_eq!(criteria.min_rating, Some(4.0));
+        assert_eq!(criteria.required_tags, Some(vec!["Progression".to_string()]));
+        assert!(matches!(config.eval_mode, EvalMode::Local(_)));
+    }
+
+    #[test]
+    fn test_load_config_names_the_included_file_in_errors() {
+        let err = load_with_includes(
+            "include-errors",
+            &[
+                ("base.toml", &BASE.replace("\"local\"", "\"psychic\"")),
+                ("main.toml", "include = [\"base.toml\"]\n[criteria]\nmin_pages = \"many\"\n"),
+            ],
+        )
+        .unwrap_err();
+        let err = format!("{:#}", err);
+        assert!(err.contains("criteria.min_pages (in "), "{}", err);
+        assert!(err.contains("main.toml): "), "{}", err);
+
+        let err = load_with_includes(
+            "include-validation",
+            &[
+                ("base.toml", &BASE.replace("\"local\"", "\"\"")),
+                ("main.toml", "include = [\"base.toml\"]\n"),
+            ],
+        )
+        .unwrap_err();
+        let err = format!("{:#}", err);
+        assert!(err.contains("eval.mode (in "), "{}", err);
+        assert!(err.contains("base.toml): must not be empty"), "{}", err);
+    }
+
+    #[test]
+    fn test_include_only_resolved_when_loading_files() {
+        let err = parse_config(&format!("include = [\"base.toml\"]\n{}", BASE))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("include: includes are only resolved"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_log_format_rejected() {
+        let err = parse_config(&format!("{}\n[logging]\nformat = \"xml\"\n", BASE))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("logging.format: Unknown log format: xml"), "{}", err);
+    }
+
+    #[test]
+    fn test_unknown_keys_warn_by_default() {
+        let config = parse_config(&format!("{}\n[criteria]\nmin_ratting = 4.5\n", BASE)).unwrap();
+        // The misspelled filter is ignored rather than applied
+        assert_eq!(config.criteria.min_rating, None);
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected_in_strict_mode() {
+        let err = parse_config(&format!(
+            "[config]\nstrict = true\n{}\n[criteria]\nmin_ratting = 4.5\n\n\
+             [scraper]\nestimate = true\n",
+            BASE
+        ))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("2 problems"), "{}", err);
+        assert!(
+            err.contains("criteria.min_ratting: unknown key (did you mean `min_rating`?)"),
+            "{}",
+            err
+        );
+        assert!(err.contains("scraper.estimate"), "{}", err);
+    }
+
+    #[test]
+    fn test_custom_modes_keep_their_settings_unchecked() {
+        let content = BASE.replace("\"local\"", "\"toy\"\n[eval.toy]\nbias = 0.2\n");
+        let content = format!(
+            "[config]\nstrict = true\n{}\n[[run.discovery]]\nkind = \"shelf\"\nshelf = 7\n\
+             [[run.discovery]]\nkind = \"also_liked\"\nshelf = 7\n",
+            content.replace("discovery_enabled = false\n", "")
+        );
+        let err = parse_config(&content).unwrap_err().to_string();
+        // Only the built-in source's keys are checked
+        assert!(err.contains("run.discovery[1].shelf: unknown key"), "{}", err);
+        assert!(!err.contains("eval.toy") && !err.contains("[0]"), "{}", err);
+
+        let config = parse_config(&content.replace("also_liked\"\nshelf = 7", "also_liked\""))
+            .unwrap();
+        let EvalMode::Custom(custom) = &config.eval_mode else {
+            panic!("expected a custom mode, got {:?}", config.eval_mode);
+        };
+        assert_eq!(config.eval_mode.name(), "toy");
+        assert_eq!(custom.settings["toy"]["bias"].as_float(), Some(0.2));
+        assert_eq!(config.discovery[0].source.kind(), "shelf");
+        let DiscoverySourceConfig::Custom(shelf) = &config.discovery[0].source else {
+            panic!(
+                "expected a custom source, got {:?}",
+                config.discovery[0].source
+            );
+        };
+        assert_eq!(shelf.settings["shelf"].as_integer(), Some(7));
+    }
+
+    #[test]
+    fn test_criteria_hash_tracks_scoring_settings() {
+        let hash = |content: &str| parse_config(content).unwrap().criteria_hash();
+        let base = hash(BASE);
+        assert_eq!(base, hash(BASE));
+        assert_ne!(base, hash(&format!("{}\n[criteria]\nmin_rating = 4.5\n", BASE)));
+        // Settings that don't affect scores leave the hash alone
+        assert_eq!(base, hash(&BASE.replace("value = 10", "value = 50")));
+        let with_selection = |value: &str| {
+            BASE.replace("[eval]", &format!("[eval]\nreview_selection = \"{}\"", value))
+        };
+        assert_eq!(base, hash(&with_selection("page_order")));
+        assert_ne!(base, hash(&with_selection("newest")));
+    }
+
+    #[test]
+    fn test_review_selection() {
+        let policy = parse_config(BASE).unwrap().review_policy;
+        assert_eq!(policy, ReviewPolicy::default());
+        assert_eq!(policy.selection, ReviewSelection::PageOrder);
+        let with_selection = |value: &str| {
+            let eval = format!("[eval]\nreview_selection = \"{}\"", value);
+            parse_config(&BASE.replace("[eval]", &eval))
+        };
+        for (value, expected) in [
+            ("most_helpful", ReviewSelection::MostHelpful),
+            ("Longest", ReviewSelection::Longest),
+            ("newest", ReviewSelection::Newest),
+        ] {
+            assert_eq!(with_selection(value).unwrap().review_policy.selection, expected);
+        }
+        let err = with_selection("best").unwrap_err().to_string();
+        assert!(err.contains("eval.review_selection: Unknown review selection: best"), "{}", err);
+    }
+
+    #[test]
+    fn test_review_length_filter() {
+        let policy = parse_config(BASE).unwrap().review_policy;
+        assert_eq!((policy.min_chars, policy.min_kept), (200, 3));
+
+        let eval = "[eval]\nmin_review_chars = 0\nmin_reviews_kept = 5";
+        let config = parse_config(&BASE.replace("[eval]", eval)).unwrap();
+        assert_eq!((config.review_policy.min_chars, config.review_policy.min_kept), (0, 5));
+        assert_ne!(config.criteria_hash(), parse_config(BASE).unwrap().criteria_hash());
+    }
+
+    #[test]
+    fn test_min_score_range() {
+        let with_min_score = |value: &str| format!("{}\n[output]\nmin_score = {}\n", BASE, value);
+        assert_eq!(parse_config(&with_min_score("0.6")).unwrap().output.min_score, Some(0.6));
+        let err = parse_config(&with_min_score("60.0")).unwrap_err().to_string();
+        assert!(err.contains("output.min_score: must be between 0.0 and 1.0"), "{}", err);
+    }
+
+    #[test]
+    fn test_include_full_novel() {
+        assert!(parse_config(BASE).unwrap().output.include_full_novel);
+        let lean = format!("{}\n[output]\ninclude_full_novel = false\n", BASE);
+        assert!(!parse_config(&lean).unwrap().output.include_full_novel);
+    }
+
+    #[test]
+    fn test_browser_command() {
+        assert_eq!(parse_config(BASE).unwrap().output.browser_command, None);
+        let with_command =
+            |value: &str| format!("{}\n[output]\nbrowser_command = {:?}\n", BASE, value);
+        let config = parse_config(&with_command("firefox --new-tab")).unwrap();
+        assert_eq!(
+            config.output.browser_command,
+            Some(vec!["firefox".to_string(), "--new-tab".to_string()])
+        );
+        let err = parse_config(&with_command("  ")).unwrap_err().to_string();
+        assert!(err.contains("output.browser_command: must not be empty"), "{}", err);
+    }
+
+    #[test]
+    fn test_group_by() {
+        assert_eq!(parse_config(BASE).unwrap().output.group_by, GroupBy::None);
+        let with_group = |value: &str| format!("{}\n[output]\ngroup_by = {:?}\n", BASE, value);
+        let group_by = |value: &str| parse_config(&with_group(value)).unwrap().output.group_by;
+        assert_eq!(group_by("genre"), GroupBy::Genre);
+        assert_eq!(group_by("Status"), GroupBy::Status);
+        assert_eq!(group_by("none"), GroupBy::None);
+        let err = parse_config(&with_group("tag")).unwrap_err().to_string();
+        assert!(err.contains("output.group_by: Unknown grouping: tag"), "{}", err);
+    }
+
+    #[test]
+    fn test_soft_filters() {
+        let with_criteria = |lines: &str| format!("{}\n[criteria]\n{}\n", BASE, lines);
+        let soft = |lines: &str| parse_config(&with_criteria(lines)).unwrap().criteria.soft_filters;
+        assert_eq!(parse_config(BASE).unwrap().criteria.soft_filters, None);
+        assert_eq!(soft("soft_filters = true"), Some(SoftFilters::default()));
+        assert_eq!(
+            soft("soft_filters = true\nsoft_rating_tolerance = 0.1\nsoft_size_tolerance = 0.25"),
+            Some(SoftFilters {
+                rating_tolerance: 0.1,
+                size_tolerance: 0.25,
+            })
+        );
+        // Tolerances alone don't turn soft filters on
+        assert_eq!(soft("soft_rating_tolerance = 0.1"), None);
+
+        let err = parse_config(&with_criteria("soft_filters = true\nsoft_size_tolerance = -0.1"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("criteria.soft_size_tolerance: must be 0 or more"), "{}", err);
+    }
+
+    #[test]
+    fn test_sampling() {
+        let with_run = |lines: &str| {
+            BASE.replace(
+                "discovery_enabled = false",
+                &format!("discovery_enabled = false\n{}", lines),
+            )
+        };
+        let sampling = |lines: &str| parse_config(&with_run(lines)).unwrap().sampling;
+        assert_eq!(parse_config(BASE).unwrap().sampling, Sampling::Fifo);
+        assert_eq!(sampling(r#"sampling = "fifo""#), Sampling::Fifo);
+        assert_eq!(
+            sampling(r#"sampling = "stratified""#),
+            Sampling::Stratified(StratifiedSampling {
+                by: StratifyBy::Followers,
+                strata: 5,
+                rng_seed: None,
+            })
+        );
+        assert_eq!(
+            sampling(
+                "sampling = \"Stratified\"\nsampling_by = \"rating\"\n\
+                 sampling_strata = 3\nsampling_seed = 7"
+            ),
+            Sampling::Stratified(StratifiedSampling {
+                by: StratifyBy::Rating,
+                strata: 3,
+                rng_seed: Some(7),
+            })
+        );
+        // The other keys alone don't turn stratified sampling on
+        assert_eq!(sampling("sampling_strata = 3"), Sampling::Fifo);
+
+        let err = |lines: &str| parse_config(&with_run(lines)).unwrap_err().to_string();
+        let e = err(r#"sampling = "random""#);
+        assert!(e.contains("run.sampling: Unknown sampling: random"), "{}", e);
+        let e = err("sampling = \"stratified\"\nsampling_by = \"pages\"");
+        assert!(e.contains("run.sampling_by: Unknown sampling statistic: pages"), "{}", e);
+        let e = err("sampling = \"stratified\"\nsampling_strata = 0");
+        assert!(e.contains("run.sampling_strata: must be greater than 0"), "{}", e);
+    }
+
+    #[test]
+    fn test_max_response_bytes() {
+        assert_eq!(parse_config(BASE).unwrap().scraper.max_response_bytes, 5 * 1024 * 1024);
+        let with_limit =
+            |value: &str| format!("{}\n[scraper]\nmax_response_bytes = {}\n", BASE, value);
+        let config = parse_config(&with_limit("1000000")).unwrap();
+        assert_eq!(config.scraper.max_response_bytes, 1_000_000);
+        let err = parse_config(&with_limit("0")).unwrap_err().to_string();
+        assert!(err.contains("scraper.max_response_bytes: must be greater than 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let config = parse_config(BASE).unwrap();
+        assert_eq!(config.scraper.request_timeout, Duration::from_secs(30));
+        assert_eq!(config.max_time_per_novel, None);
+
+        let with_timeouts = |request: &str, novel: &str| {
+            let config = BASE.replace(
+                "discovery_enabled = false",
+                &format!("discovery_enabled = false\nmax_seconds_per_novel = {}", novel),
+            );
+            parse_config(&format!("{}\n[scraper]\nrequest_timeout = {}\n", config, request))
+        };
+        let config = with_timeouts(r#""1m""#, "120").unwrap();
+        assert_eq!(config.scraper.request_timeout, Duration::from_secs(60));
+        assert_eq!(config.max_time_per_novel, Some(Duration::from_secs(120)));
+        assert_eq!(with_timeouts("45", "1").unwrap().scraper.request_timeout.as_secs(), 45);
+
+        let err = with_timeouts("0", "0").unwrap_err().to_string();
+        assert!(err.contains("scraper.request_timeout: must be greater than 0"), "{}", err);
+        assert!(err.contains("run.max_seconds_per_novel: must be greater than 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_seed_time_budget() {
+        assert_eq!(parse_config(BASE).unwrap().seed_time_budget, None);
+        let with_budget = |stop: &str, budget: &str| {
+            let config = BASE.replace(
+                "stop_condition = { type = \"max_novels\", value = 10 }",
+                &format!("stop_condition = {}\nseed_time_budget = {}", stop, budget),
+            );
+            parse_config(&config)
+        };
+        let max_time = r#"{ type = "max_time", value = "30m" }"#;
+        let config = with_budget(max_time, r#""5m""#).unwrap();
+        assert_eq!(config.seed_time_budget, Some(Duration::from_secs(300)));
+        // Without a time limit, the budget still caps seeding
+        let max_novels = r#"{ type = "max_novels", value = 10 }"#;
+        assert_eq!(with_budget(max_novels, "90").unwrap().seed_time_budget.unwrap().as_secs(), 90);
+
+        let err = with_budget(max_time, r#""30m""#).unwrap_err().to_string();
+        assert!(err.contains("run.seed_time_budget: must be shorter than the max_time"), "{}", err);
+        let err = with_budget(max_novels, "0").unwrap_err().to_string();
+        assert!(err.contains("run.seed_time_budget: must be greater than 0"), "{}", err);
+        let err = with_budget(max_novels, r#""soon""#).unwrap_err().to_string();
+        assert!(err.contains("run.seed_time_budget: "), "{}", err);
+    }
+
+    #[test]
+    fn test_response_cache_settings() {
+        let config = parse_config(BASE).unwrap();
+        assert_eq!(config.scraper.cache_dir, None);
+        assert_eq!(config.scraper.cache_ttl, Duration::from_secs(24 * 60 * 60));
+
+        let with_cache = |ttl: &str| {
+            let scraper = format!("[scraper]\ncache_dir = \"cache\"\ncache_ttl = {}\n", ttl);
+            parse_config(&format!("{}\n{}", BASE, scraper))
+        };
+        let config = with_cache(r#""6h""#).unwrap();
+        assert_eq!(config.scraper.cache_dir, Some(PathBuf::from("cache")));
+        assert_eq!(config.scraper.cache_ttl, Duration::from_secs(6 * 60 * 60));
+        let err = with_cache("0").unwrap_err().to_string();
+        assert!(err.contains("scraper.cache_ttl: must be greater than 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let limits = parse_config(BASE).unwrap().scraper.rate_limits;
+        assert_eq!(limits, RateLimits::default());
+        assert!(limits.api < limits.pages);
+
+        let with_limits = |limits: &str| {
+            parse_config(&format!("{}\n[scraper.rate_limits]\n{}\n", BASE, limits))
+        };
+        let limits = with_limits("pages = \"2s\"\napi = \"400ms\"").unwrap();
+        let limits = limits.scraper.rate_limits;
+        assert_eq!(limits.pages, Duration::from_secs(2));
+        assert_eq!(limits.api, Duration::from_millis(400));
+        assert_eq!(with_limits("api = 0").unwrap().scraper.rate_limits.api, Duration::ZERO);
+        let err = with_limits("pages = \"soon\"").unwrap_err().to_string();
+        assert!(err.contains("scraper.rate_limits.pages: invalid duration"), "{}", err);
+    }
+
+    #[test]
+    fn test_cache_limits() {
+        assert_eq!(parse_config(BASE).unwrap().scraper.cache_limits, CacheLimits::default());
+        let with_limits = |mb: u64, entries: usize| {
+            let scraper = format!(
+                "[scraper]\ncache_dir = \"cache\"\nmax_cache_mb = {}\nmax_cache_entries = {}\n",
+                mb, entries
+            );
+            parse_config(&format!("{}\n{}", BASE, scraper))
+        };
+        let limits = with_limits(50, 1000).unwrap().scraper.cache_limits;
+        assert_eq!(limits.max_bytes, Some(50 * 1024 * 1024));
+        assert_eq!(limits.max_entries, Some(1000));
+        let err = with_limits(0, 0).unwrap_err().to_string();
+        assert!(err.contains("scraper.max_cache_mb: must be greater than 0"), "{}", err);
+        assert!(err.contains("scraper.max_cache_entries: must be greater than 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_ignore_robots() {
+        assert!(!parse_config(BASE).unwrap().scraper.ignore_robots);
+        let ignoring = format!("{}\n[scraper]\nignore_robots = true\n", BASE);
+        assert!(parse_config(&ignoring).unwrap().scraper.ignore_robots);
+    }
+
+    #[test]
+    fn test_proxy_settings() {
+        assert_eq!(parse_config(BASE).unwrap().scraper.proxy, ProxySetting::FromEnv);
+        let llm = BASE.replace(
+            r#"mode = "local""#,
+            r#"mode = "llm"
+[eval.llm]
+api_key = "sk-test"
+model = "claude"
+endpoint = "https://api.anthropic.com/v1""#,
+        );
+        let with_proxies = |scraper: &str, eval: &str| {
+            let config = llm.replace("[seeds]", &format!("{}\n[seeds]", eval));
+            parse_config(&format!("{}\n[scraper]\n{}\n", config, scraper))
+        };
+        let eval_proxy = |config: &AppConfig| match &config.eval_mode {
+            EvalMode::Llm(llm) => llm.proxy.clone(),
+            EvalMode::Local(_) | EvalMode::Custom(_) => unreachable!(),
+        };
+
+        let config = with_proxies(r#"proxy = "http://proxy.lan:3128""#, "").unwrap();
+        let url = ProxySetting::Url("http://proxy.lan:3128".to_string());
+        assert_eq!(config.scraper.proxy, url);
+        assert_eq!(eval_proxy(&config), None);
+
+        let config =
+            with_proxies(r#"proxy = "http://proxy.lan:3128""#, r#"proxy = "none""#).unwrap();
+        assert_eq!(config.scraper.proxy, url);
+        assert_eq!(eval_proxy(&config), Some(ProxySetting::Direct));
+
+        let err = with_proxies(r#"proxy = "socks5://proxy.lan""#, r#"proxy = "ftp://x""#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("scraper.proxy: Unsupported proxy URL"), "{}", err);
+        assert!(err.contains("eval.llm.proxy: Unknown proxy scheme"), "{}", err);
+    }
+
+    #[test]
+    fn test_discovery_sources() {
+        let without_switch = BASE.replace("discovery_enabled = false\n", "");
+        let with_discovery =
+            |entries: &str| parse_config(&format!("{}\n{}", without_switch, entries));
+
+        // The legacy switch means the default also_liked source
+        assert!(parse_config(BASE).unwrap().discovery.is_empty());
+        assert!(parse_config(&without_switch).unwrap().discovery.is_empty());
+        let legacy = BASE.replace("discovery_enabled = false", "discovery_enabled = true");
+        assert_eq!(parse_config(&legacy).unwrap().discovery, vec![DiscoveryConfig::also_liked()]);
+
+        let config = with_discovery(
+            "[[run.discovery]]\nkind = \"also_liked\"\nmax_per_novel = 5\n\
+             min_source_score = 0.6\nuse_cache = false\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.discovery,
+            vec![DiscoveryConfig {
+                min_source_score: Some(0.6),
+                max_per_novel: Some(5),
+                source: DiscoverySourceConfig::AlsoLiked(AlsoLikedConfig { use_cache: false }),
+            }]
+        );
+
+        let err = with_discovery(
+            "[[run.discovery]]\nkind = \"also_liked\"\nmax_per_novel = 0\n\
+             min_source_score = 1.5\n\
+             [[run.discovery]]\nkind = \"also_liked\"\n\
+             [[run.discovery]]\nkind = \"\"\n",
+        )
+        .unwrap_err()
+        .to_string();
+        for problem in [
+            "run.discovery[0].max_per_novel: must be greater than 0",
+            "run.discovery[0].min_source_score: must be between 0.0 and 1.0",
+            "run.discovery[1].kind: also_liked is listed more than once",
+            "run.discovery[2].kind: must not be empty",
+        ] {
+            assert!(err.contains(problem), "missing {} in {}", problem, err);
+        }
+
+        let config = parse_config(&format!("{}discovery_min_score = 0.5\n", BASE)).unwrap();
+        assert_eq!(config.discovery_min_score, 0.5);
+        assert_eq!(parse_config(BASE).unwrap().discovery_min_score, 0.0);
+        let err = parse_config(&format!("{}discovery_min_score = -0.1\n", BASE))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("run.discovery_min_score: must be between 0.0 and 1.0"), "{}", err);
+
+        let config = parse_config(&format!("{}max_discovery_ratio = 3.0\n", BASE)).unwrap();
+        assert_eq!(config.max_discovery_ratio, Some(3.0));
+        assert_eq!(parse_config(BASE).unwrap().max_discovery_ratio, None);
+        for bad in ["-1.0", "nan"] {
+            let err = parse_config(&format!("{}max_discovery_ratio = {}\n", BASE, bad))
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("run.max_discovery_ratio: must be 0.0 or more"), "{}", err);
+        }
+
+        // The switch and the list can't both be given
+        let err = parse_config(&format!("{}\n[[run.discovery]]\nkind = \"also_liked\"\n", BASE))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("run.discovery_enabled: conflicts with [[run.discovery]]"), "{}", err);
+    }
+
+    const LLM_TABLE: &str = r#"mode = "llm"
+[eval.llm]
+api_key = "sk-test"
+model = "claude"
+endpoint = "https://api.anthropic.com/v1"
+include_first_chapter = true
+proxy = "none""#;
+
+    const LLM_FLAT: &str = r#"mode = "llm"
+llm_api_key = "sk-test"
+llm_model = "claude"
+llm_endpoint = "https://api.anthropic.com/v1"
+include_first_chapter = true
+proxy = "none""#;
+
+    #[test]
+    fn test_llm_settings_table() {
+        let config = parse_config(&BASE.replace(r#"mode = "local""#, LLM_TABLE)).unwrap();
+        let EvalMode::Llm(llm) = &config.eval_mode else {
+            panic!("expected LLM mode, got {}", config.eval_mode);
+        };
+        assert_eq!(llm.api_key, "sk-test");
+        assert_eq!(llm.model, "claude");
+        assert_eq!(llm.endpoint, "https://api.anthropic.com/v1");
+        assert!(llm.include_first_chapter);
+        assert_eq!(llm.proxy, Some(ProxySetting::Direct));
+        assert_eq!(config.eval_mode.to_string(), "llm (claude)");
+    }
+
+    #[test]
+    fn test_flat_llm_keys_still_accepted() {
+        let table = parse_config(&BASE.replace(r#"mode = "local""#, LLM_TABLE)).unwrap();
+        let flat = parse_config(&BASE.replace(r#"mode = "local""#, LLM_FLAT)).unwrap();
+        let EvalMode::Llm(llm) = &flat.eval_mode else {
+            panic!("expected LLM mode, got {}", flat.eval_mode);
+        };
+        assert_eq!(llm.api_key, "sk-test");
+        assert!(llm.include_first_chapter);
+        assert_eq!(llm.proxy, Some(ProxySetting::Direct));
+        // Moving to the new layout doesn't change which runs are comparable
+        assert_eq!(flat.criteria_hash(), table.criteria_hash());
+
+        // Old and new spellings of one key can mix, but not overlap
+        let mixed = BASE.replace(
+            r#"mode = "local""#,
+            "mode = \"llm\"\nllm_api_key = \"sk-test\"\n[eval.llm]\nmodel = \"claude\"\n\
+             endpoint = \"https://api.anthropic.com/v1\"",
+        );
+        assert!(parse_config(&mixed).is_ok());
+        let both = mixed.replace("[eval.llm]", "[eval.llm]\napi_key = \"sk-other\"");
+        let err = parse_config(&both).unwrap_err().to_string();
+        assert!(
+            err.contains("eval.llm_api_key: also set as eval.llm.api_key"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_local_settings_table() {
+        let config = parse_config(&BASE.replace(
+            r#"mode = "local""#,
+            "mode = \"local\"\n[eval.local]\npopularity_saturation = 500",
+        ))
+        .unwrap();
+        let EvalMode::Local(local) = &config.eval_mode else {
+            panic!("expected local mode, got {}", config.eval_mode);
+        };
+        assert_eq!(local.popularity_saturation, 500);
+        assert_eq!(local.popularity_signal, PopularitySignal::Followers);
+    }
+
+    #[test]
+    fn test_popularity_signal() {
+        let with_signal = |signal: &str| {
+            BASE.replace(
+                r#"mode = "local""#,
+                &format!("mode = \"local\"\n[eval.local]\npopularity_signal = \"{}\"", signal),
+            )
+        };
+        let config = parse_config(&with_signal("badges")).unwrap();
+        let EvalMode::Local(local) = &config.eval_mode else {
+            panic!("expected local mode, got {}", config.eval_mode);
+        };
+        assert_eq!(local.popularity_signal, PopularitySignal::Badges);
+        // Scores read from badges aren't comparable with follower-based ones
+        let default = parse_config(BASE).unwrap();
+        assert_ne!(config.criteria_hash(), default.criteria_hash());
+
+        let err = parse_config(&with_signal("views")).unwrap_err().to_string();
+        assert!(err.contains("eval.local.popularity_signal: Unknown"), "{}", err);
+    }
+
+    #[test]
+    fn test_store_chapter_titles() {
+        let storage = |config: &str| parse_config(config).map(|c| c.scraper.store_chapter_titles);
+        assert_eq!(storage(BASE).unwrap(), ChapterTitleStorage::Sample);
+        let with_storage = |value: &str, extra: &str| {
+            format!("{}\n[scraper]\nstore_chapter_titles = \"{}\"\n{}", BASE, value, extra)
+        };
+        assert_eq!(storage(&with_storage("all", "")).unwrap(), ChapterTitleStorage::All);
+        assert_eq!(storage(&with_storage("None", "")).unwrap(), ChapterTitleStorage::None);
+
+        let err = storage(&with_storage("first", "")).unwrap_err().to_string();
+        assert!(err.contains("scraper.store_chapter_titles: Unknown"), "{}", err);
+        let err = storage(&with_storage("none", "estimate_word_count = true\n"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("scraper.estimate_word_count"), "{}", err);
+    }
+
+    #[test]
+    fn test_max_challenge_streak() {
+        assert_eq!(parse_config(BASE).unwrap().scraper.max_challenge_streak, 3);
+        let with_streak =
+            |value: &str| format!("{}\n[scraper]\nmax_challenge_streak = {}\n", BASE, value);
+        assert_eq!(parse_config(&with_streak("5")).unwrap().scraper.max_challenge_streak, 5);
+        let err = parse_config(&with_streak("0")).unwrap_err().to_string();
+        assert!(err.contains("scraper.max_challenge_streak: must be greater than 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_file_seed_source() {
+        let with_seeds = |seeds: &str| {
+            parse_config(&BASE.replace("source = \"manual\"\nurls = [\"12345\"]", seeds))
+        };
+        let config = with_seeds("source = \"file\"\nfile = \"queue.txt\"").unwrap();
+        assert!(
+            matches!(&config.seed_source, SeedSource::File(path) if path == Path::new("queue.txt"))
+        );
+        assert_eq!(config.seed_source.to_string(), "file queue.txt");
+        let err = with_seeds("source = \"file\"").unwrap_err().to_string();
+        assert!(err.contains("seeds.file: File seed source requires file"), "{}", err);
+    }
+
+    #[test]
+    fn test_dataset_seed_source() {
+        let with_seeds = |seeds: &str| {
+            parse_config(&BASE.replace("source = \"manual\"\nurls = [\"12345\"]", seeds))
+        };
+        let config = with_seeds("source = \"dataset\"\npath = \"novels.json\"").unwrap();
+        match &config.seed_source {
+            SeedSource::Dataset { path, filter } => {
+                assert_eq!(path, Path::new("novels.json"));
+                assert_eq!(*filter, DatasetFilter::All);
+            }
+            other => panic!("unexpected seed source: {:?}", other),
+        }
+        let config = with_seeds(
+            "source = \"dataset\"\npath = \"novels.jsonl\"\nfilter = \"unevaluated\"",
+        )
+        .unwrap();
+        assert_eq!(
+            config.seed_source.to_string(),
+            "dataset novels.jsonl (unevaluated novels)"
+        );
+
+        let err = with_seeds("source = \"dataset\"\nfilter = \"recent\"").unwrap_err().to_string();
+        assert!(err.contains("seeds.path: Dataset seed source requires path"), "{}", err);
+        assert!(err.contains("seeds.filter: Unknown dataset filter: recent"), "{}", err);
+    }
+
+    #[test]
+    fn test_tag_seed_source() {
+        let with_seeds = |seeds: &str| {
+            parse_config(&BASE.replace("source = \"manual\"\nurls = [\"12345\"]", seeds))
+        };
+        let config = with_seeds(
+            "source = \"tag\"\ntags = [\"litrpg\", \"kingdom building\", \"LitRPG\"]\n\
+             max_results = 40",
+        )
+        .unwrap();
+        match &config.seed_source {
+            SeedSource::Tag(seeds) => {
+                let slugs: Vec<&str> = seeds.tags.iter().map(|tag| tag.slug).collect();
+                assert_eq!(slugs, ["litrpg", "kingdom_building"]);
+                assert_eq!(seeds.order_by, SearchOrder::Popularity);
+                assert_eq!(seeds.max_results, 40);
+            }
+            other => panic!("unexpected seed source: {:?}", other),
+        }
+        let config =
+            with_seeds("source = \"tag\"\ntags = [\"isekai\"]\norder_by = \"Rating\"").unwrap();
+        assert_eq!(
+            config.seed_source.to_string(),
+            "tag Portal Fantasy / Isekai by rating (up to 20 results)"
+        );
+
+        let err = with_seeds(
+            "source = \"tag\"\ntags = [\"litrpg\", \"lit rpgg\"]\norder_by = \"newest\"\n\
+             max_results = 0",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("seeds.tags: Unknown tag: lit rpgg"), "{}", err);
+        assert!(err.contains("seeds.order_by: Unknown order: newest"), "{}", err);
+        assert!(err.contains("seeds.max_results: must be greater than 0"), "{}", err);
+        let err = with_seeds("source = \"tag\"\ntags = []").unwrap_err().to_string();
+        assert!(err.contains("seeds.tags: Tag seed source requires tags"), "{}", err);
+        let scribblehub = format!("site = \"scribblehub\"\n{}", BASE).replace(
+            "source = \"manual\"\nurls = [\"12345\"]",
+            "source = \"tag\"\ntags = [\"magic\"]",
+        );
+        let err = parse_config(&scribblehub).unwrap_err().to_string();
+        assert!(err.contains("seeds.source: Tag seeds aren't supported on scribblehub"), "{}", err);
+    }
+
+    #[test]
+    fn test_random_seed_source() {
+        let with_seeds = |seeds: &str| {
+            BASE.replace("source = \"manual\"\nurls = [\"12345\"]", seeds)
+        };
+        let config =
+            parse_config(&with_seeds("source = \"random\"\ncount = 5\nmax_id = 120000")).unwrap();
+        match config.seed_source {
+            SeedSource::Random(seeds) => {
+                assert_eq!((seeds.count, seeds.max_id, seeds.rng_seed), (5, 120_000, None));
+                assert_eq!(seeds.max_attempts, 100);
+            }
+            other => panic!("unexpected seed source: {:?}", other),
+        }
+
+        let err = parse_config(&with_seeds("source = \"random\"\ncount = 5\nmax_attempts = 2"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("seeds.max_id: Random seed source requires max_id"), "{}", err);
+        let err = parse_config(&with_seeds("source = \"random\"\ncount = 0\nmax_id = 10"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("seeds.count: must be greater than 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_tag_report_settings() {
+        let config = parse_config(&format!(
+            "{}\n[output.tag_report]\nmin_count = 3\nsort = \"count\"\nhigh_score = 0.8\n",
+            BASE
+        ))
+        .unwrap();
+        let tag_report = config.output.tag_report;
+        assert_eq!((tag_report.min_count, tag_report.sort), (3, TagSort::Count));
+        assert_eq!((tag_report.high_score, tag_report.max_pairs), (0.8, 10));
+
+        let err = parse_config(&format!("{}\n[output.tag_report]\nsort = \"alpha\"\n", BASE))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("output.tag_report.sort: Unknown tag report sort: alpha"), "{}", err);
+    }
+}
@@ -0,0 +1,301 @@
+//! Secrets stored in the operating system keyring.
+//!
+//! A config value like `"keyring:novel-finder/anthropic"` refers to a keyring
+//! entry instead of holding the secret itself, so the config file can be
+//! synced between machines safely. Keyring access is behind the `keyring`
+//! cargo feature and goes through the platform's own tool: `secret-tool`
+//! (libsecret) on Linux and other Unixes, `security` on macOS.
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::io::{BufRead, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Prefix marking a config value as a keyring reference.
+pub const KEYRING_PREFIX: &str = "keyring:";
+
+/// Keyring service used when a reference names only an account.
+const DEFAULT_SERVICE: &str = "novel-finder";
+
+/// A keyring entry, identified by service and account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyringEntry {
+    /// The service the secret belongs to (e.g., "novel-finder").
+    pub service: String,
+    /// The account name within the service (e.g., "anthropic").
+    pub account: String,
+}
+
+impl KeyringEntry {
+    /// Parse an entry name like "novel-finder/anthropic". A name without a
+    /// slash is an account under the default "novel-finder" service.
+    pub fn parse(name: &str) -> Result<Self> {
+        let (service, account) = match name.trim().split_once('/') {
+            Some((service, account)) => (service.trim(), account.trim()),
+            None => (DEFAULT_SERVICE, name.trim()),
+        };
+        if service.is_empty() || account.is_empty() {
+            anyhow::bail!(
+                "Invalid keyring entry \"{}\"; expected \"service/account\" or \"account\"",
+                name
+            );
+        }
+        Ok(Self {
+            service: service.to_string(),
+            account: account.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for KeyringEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.service, self.account)
+    }
+}
+
+/// Resolve a config value that may be a keyring reference.
+///
+/// Plain values are returned unchanged; `keyring:<entry>` values are looked
+/// up in the OS keyring.
+pub fn resolve_secret(value: &str) -> Result<String> {
+    match value.strip_prefix(KEYRING_PREFIX) {
+        Some(name) => lookup(&KeyringEntry::parse(name)?),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Look up a secret in the OS keyring.
+#[cfg(feature = "keyring")]
+pub fn lookup(entry: &KeyringEntry) -> Result<String> {
+    match backend::lookup(entry)? {
+        Some(secret) => Ok(secret),
+        None => anyhow::bail!(
+            "No keyring entry {0}; store it with `novel-finder secret set {0}`",
+            entry
+        ),
+    }
+}
+
+/// Look up a secret in the OS keyring.
+#[cfg(not(feature = "keyring"))]
+pub fn lookup(entry: &KeyringEntry) -> Result<String> {
+    anyhow::bail!(
+        "Config refers to keyring entry {}, but this build of novel-finder has no keyring \
+         support; rebuild with `cargo build --features keyring` or put the value in the config",
+        entry
+    )
+}
+
+/// Store a secret in the OS keyring, replacing any existing value.
+#[cfg(feature = "keyring")]
+pub fn store(entry: &KeyringEntry, secret: &str) -> Result<()> {
+    backend::store(entry, secret)
+}
+
+/// Store a secret in the OS keyring, replacing any existing value.
+#[cfg(not(feature = "keyring"))]
+pub fn store(_entry: &KeyringEntry, _secret: &str) -> Result<()> {
+    anyhow::bail!(
+        "This build of novel-finder has no keyring support; rebuild with \
+         `cargo build --features keyring`"
+    )
+}
+
+/// Read a secret from stdin, prompting and hiding the input on a terminal.
+pub fn read_secret(prompt: &str) -> Result<String> {
+    let stdin = std::io::stdin();
+    let interactive = stdin.is_terminal();
+    if interactive {
+        eprint!("{}", prompt);
+        std::io::stderr().flush()?;
+        set_echo(false);
+    }
+    let mut line = String::new();
+    let read = stdin.lock().read_line(&mut line);
+    if interactive {
+        set_echo(true);
+        eprintln!();
+    }
+    read.context("Failed to read secret from stdin")?;
+
+    let secret = line.trim_end_matches(['\r', '\n']).to_string();
+    if secret.is_empty() {
+        anyhow::bail!("No secret given");
+    }
+    Ok(secret)
+}
+
+/// Turn terminal echo on or off with `stty`. Best effort: if it fails, the
+/// secret is merely visible while typing.
+fn set_echo(on: bool) {
+    let _ = Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(Stdio::inherit())
+        .status();
+}
+
+/// libsecret's `secret-tool`, which talks to GNOME Keyring or KWallet.
+#[cfg(all(feature = "keyring", unix, not(target_os = "macos")))]
+mod backend {
+    use super::KeyringEntry;
+    use anyhow::{Context, Result};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    pub fn lookup(entry: &KeyringEntry) -> Result<Option<String>> {
+        let output = Command::new("secret-tool")
+            .args(["lookup", "service", &entry.service, "account", &entry.account])
+            .output()
+            .context("Failed to run secret-tool; is libsecret installed?")?;
+        // secret-tool exits unsuccessfully with no output for a missing entry
+        let secret = String::from_utf8(output.stdout).context("Keyring secret is not UTF-8")?;
+        Ok((output.status.success() && !secret.is_empty()).then_some(secret))
+    }
+
+    pub fn store(entry: &KeyringEntry, secret: &str) -> Result<()> {
+        let mut child = Command::new("secret-tool")
+            .args(["store", "--label", &format!("novel-finder: {}", entry)])
+            .args(["service", &entry.service, "account", &entry.account])
+            .stdin(Stdio::piped())
+            .spawn()
+            .context("Failed to run secret-tool; is libsecret installed?")?;
+        child
+            .stdin
+            .take()
+            .context("secret-tool stdin unavailable")?
+            .write_all(secret.as_bytes())?;
+        let status = child.wait()?;
+        if !status.success() {
+            anyhow::bail!("secret-tool failed to store {} ({})", entry, status);
+        }
+        Ok(())
+    }
+}
+
+/// The macOS login keychain via the `security` tool.
+#[cfg(all(feature = "keyring", target_os = "macos"))]
+mod backend {
+    use super::KeyringEntry;
+    use anyhow::{Context, Result};
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    /// Exit status `security` uses when an item is not in the keychain.
+    const ITEM_NOT_FOUND: i32 = 44;
+
+    pub fn lookup(entry: &KeyringEntry) -> Result<Option<String>> {
+        let output = Command::new("security")
+            .args(["find-generic-password", "-s", &entry.service, "-a", &entry.account, "-w"])
+            .output()
+            .context("Failed to run security")?;
+        if output.status.code() == Some(ITEM_NOT_FOUND) {
+            return Ok(None);
+        }
+        if !output.status.success() {
+            anyhow::bail!("security failed to read {} ({})", entry, output.status);
+        }
+        let secret = String::from_utf8(output.stdout).context("Keyring secret is not UTF-8")?;
+        Ok(Some(secret.trim_end_matches('\n').to_string()))
+    }
+
+    /// Runs `security` interactively and writes the command to its stdin,
+    /// so the secret never appears in its arguments, where other users
+    /// could read it with `ps`.
+    pub fn store(entry: &KeyringEntry, secret: &str) -> Result<()> {
+        if secret.contains(['\n', '\r']) {
+            anyhow::bail!("A keyring secret can't contain line breaks");
+        }
+        let mut child = Command::new("security")
+            .arg("-i")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .context("Failed to run security")?;
+        let args = [
+            "add-generic-password",
+            "-U",
+            "-s",
+            &entry.service,
+            "-a",
+            &entry.account,
+            "-w",
+            secret,
+        ];
+        child
+            .stdin
+            .take()
+            .context("security stdin unavailable")?
+            .write_all(command_line(&args).as_bytes())?;
+        let status = child.wait()?;
+        // `security -i` carries on past a failed command, so check the
+        // keychain holds the secret now
+        if !status.success() || lookup(entry)?.as_deref() != Some(secret) {
+            anyhow::bail!("security failed to store {} ({})", entry, status);
+        }
+        Ok(())
+    }
+
+    /// One line of input for `security -i`, each argument double-quoted.
+    pub(super) fn command_line(args: &[&str]) -> String {
+        let quoted: Vec<String> = args
+            .iter()
+            .map(|arg| format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect();
+        format!("{}\n", quoted.join(" "))
+    }
+}
+
+/// Platforms without a supported keyring tool.
+#[cfg(all(feature = "keyring", not(unix)))]
+mod backend {
+    use super::KeyringEntry;
+    use anyhow::Result;
+
+    pub fn lookup(_entry: &KeyringEntry) -> Result<Option<String>> {
+        anyhow::bail!("Keyring support is not available on this platform")
+    }
+
+    pub fn store(_entry: &KeyringEntry, _secret: &str) -> Result<()> {
+        anyhow::bail!("Keyring support is not available on this platform")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entry() {
+        let entry = KeyringEntry::parse("novel-finder/anthropic").unwrap();
+        assert_eq!(entry.service, "novel-finder");
+        assert_eq!(entry.account, "anthropic");
+
+        // A bare account uses the default service
+        assert_eq!(KeyringEntry::parse("openai").unwrap().to_string(), "novel-finder/openai");
+
+        assert!(KeyringEntry::parse("novel-finder/").is_err());
+        assert!(KeyringEntry::parse("").is_err());
+    }
+
+    #[test]
+    fn test_plain_values_pass_through() {
+        assert_eq!(resolve_secret("sk-test").unwrap(), "sk-test");
+    }
+
+    #[cfg(all(feature = "keyring", target_os = "macos"))]
+    #[test]
+    fn test_security_command_line() {
+        assert_eq!(
+            backend::command_line(&["-w", r#"sk "a\b" c"#]),
+            "\"-w\" \"sk \\\"a\\\\b\\\" c\"\n"
+        );
+    }
+
+    #[cfg(not(feature = "keyring"))]
+    #[test]
+    fn test_keyring_reference_without_feature() {
+        let err = resolve_secret("keyring:novel-finder/anthropic").unwrap_err().to_string();
+        assert!(err.contains("novel-finder/anthropic"), "{}", err);
+        assert!(err.contains("--features keyring"), "{}", err);
+    }
+}
@@ -0,0 +1,559 @@
+//! Checks for settings that contradict each other.
+//!
+//! Each setting can be valid on its own while the combination is
+//! self-defeating: a tag both required and excluded, or a page minimum above
+//! the maximum, silently rejects every novel and wastes a whole run. These
+//! checks run on the built [`AppConfig`] at startup and with `check-config`.
+//! Contradictions that guarantee no novel passes are errors; combinations
+//! that are merely suspicious are warnings. [`validate_names`] also reports
+//! an `eval.mode` or discovery `kind` nothing is registered under, which is
+//! most likely a typo.
+
+use super::AppConfig;
+use crate::models::tags::tag_key;
+use crate::models::{Criteria, NovelStatus};
+use crate::registry::Registry;
+use std::fmt;
+
+/// How serious a conflict is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// No novel can pass, or the run can't start at all.
+    Error,
+    /// Likely a mistake, but novels can still pass.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A conflict between config settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigWarning {
+    pub severity: Severity,
+    /// Full paths of the keys involved, e.g. "criteria.min_pages".
+    pub keys: Vec<String>,
+    /// What's wrong, naming the keys.
+    pub message: String,
+}
+
+impl ConfigWarning {
+    fn new(severity: Severity, keys: [&str; 2], message: String) -> Self {
+        ConfigWarning {
+            severity,
+            keys: keys.iter().map(|key| key.to_string()).collect(),
+            message,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Find conflicting settings in a config, errors first.
+///
+/// With profiles, each profile's criteria are checked and `criteria` is
+/// skipped, since novels are only scored against the profiles.
+pub fn validate_config(config: &AppConfig) -> Vec<ConfigWarning> {
+    let mut warnings = Vec::new();
+    if config.profiles.is_empty() {
+        check_criteria(&config.criteria, "criteria", &mut warnings);
+    }
+    for profile in &config.profiles {
+        let section = format!("profiles.{}", profile.name);
+        check_criteria(&profile.criteria, &section, &mut warnings);
+    }
+
+    if !config.discovery.is_empty() && config.max_discovery_ratio == Some(0.0) {
+        warnings.push(ConfigWarning::new(
+            Severity::Warning,
+            ["run.discovery", "run.max_discovery_ratio"],
+            "run.max_discovery_ratio is 0, so novels found by run.discovery are never \
+             evaluated"
+                .to_string(),
+        ));
+    }
+
+    warnings.sort_by_key(|warning| !warning.is_error());
+    warnings
+}
+
+/// Find the names in a config that `registry` has nothing registered
+/// under: its `eval.mode` and each `[[run.discovery]]` kind. Each is an
+/// error, since building the pipeline would fail on it.
+pub fn validate_names(config: &AppConfig, registry: &Registry) -> Vec<ConfigWarning> {
+    let mut errors = Vec::new();
+    if let Err(err) = registry.evaluator(config.eval_mode.name()) {
+        errors.push(("eval.mode", err));
+    }
+    for entry in &config.discovery {
+        if let Err(err) = registry.discovery(entry.source.kind()) {
+            errors.push(("run.discovery.kind", err));
+        }
+    }
+    errors
+        .into_iter()
+        .map(|(key, err)| ConfigWarning {
+            severity: Severity::Error,
+            keys: vec![key.to_string()],
+            message: err.to_string(),
+        })
+        .collect()
+}
+
+/// Check one set of criteria; `section` is its key path.
+fn check_criteria(criteria: &Criteria, section: &str, warnings: &mut Vec<ConfigWarning>) {
+    check_overlap(
+        section,
+        ("required_tags", &criteria.required_tags),
+        ("excluded_tags", &criteria.excluded_tags),
+        warnings,
+    );
+    check_overlap(
+        section,
+        ("required_genres", &criteria.required_genres),
+        ("excluded_genres", &criteria.excluded_genres),
+        warnings,
+    );
+    // Genres are also tags, so excluding one as a tag excludes the genre
+    check_overlap(
+        section,
+        ("required_genres", &criteria.required_genres),
+        ("excluded_tags", &criteria.excluded_tags),
+        warnings,
+    );
+
+    check_range(
+        section,
+        "pages",
+        criteria.min_pages,
+        criteria.max_pages,
+        warnings,
+    );
+    check_range(
+        section,
+        "words",
+        criteria.min_words,
+        criteria.max_words,
+        warnings,
+    );
+    check_range(
+        section,
+        "pages_per_chapter",
+        criteria.min_pages_per_chapter,
+        criteria.max_pages_per_chapter,
+        warnings,
+    );
+    check_range(
+        section,
+        "fiction_age_days",
+        criteria.min_fiction_age_days,
+        criteria.max_fiction_age_days,
+        warnings,
+    );
+
+    check_statuses(criteria, section, warnings);
+}
+
+/// A required tag or genre that's also excluded rejects every novel.
+fn check_overlap(
+    section: &str,
+    (required_key, required): (&str, &Option<Vec<String>>),
+    (excluded_key, excluded): (&str, &Option<Vec<String>>),
+    warnings: &mut Vec<ConfigWarning>,
+) {
+    let excluded: Vec<String> = excluded.iter().flatten().map(|tag| tag_key(tag)).collect();
+    for tag in required.iter().flatten() {
+        if excluded.contains(&tag_key(tag)) {
+            let required_key = format!("{}.{}", section, required_key);
+            let excluded_key = format!("{}.{}", section, excluded_key);
+            warnings.push(ConfigWarning::new(
+                Severity::Error,
+                [&required_key, &excluded_key],
+                format!(
+                    "{} and {} both list '{}', so no novel can pass",
+                    required_key, excluded_key, tag
+                ),
+            ));
+        }
+    }
+}
+
+/// A minimum above its maximum rejects every novel.
+fn check_range<T: PartialOrd + fmt::Display>(
+    section: &str,
+    name: &str,
+    min: Option<T>,
+    max: Option<T>,
+    warnings: &mut Vec<ConfigWarning>,
+) {
+    let (Some(min), Some(max)) = (min, max) else {
+        return;
+    };
+    if min > max {
+        let min_key = format!("{}.min_{}", section, name);
+        let max_key = format!("{}.max_{}", section, name);
+        warnings.push(ConfigWarning::new(
+            Severity::Error,
+            [&min_key, &max_key],
+            format!(
+                "{} ({}) exceeds {} ({}), so no novel can pass",
+                min_key, min, max_key, max
+            ),
+        ));
+    }
+}
+
+/// Statuses that can't pass the other criteria, and status qualifiers that
+/// rarely can.
+fn check_statuses(criteria: &Criteria, section: &str, warnings: &mut Vec<ConfigWarning>) {
+    let excludes_stubs = criteria.exclude_stubs == Some(true);
+    let stubs_key = format!("{}.exclude_stubs", section);
+
+    // Configs can't list no statuses, but criteria built in code can
+    if criteria.allowed_statuses.as_ref().is_some_and(Vec::is_empty) {
+        let status_key = format!("{}.allowed_statuses", section);
+        warnings.push(ConfigWarning {
+            severity: Severity::Error,
+            message: format!("{} is empty, so no novel can pass", status_key),
+            keys: vec![status_key],
+        });
+    }
+
+    // Only stubs allowed, but stubs excluded
+    let only_stubs = |statuses: &[NovelStatus]| {
+        !statuses.is_empty() && statuses.iter().all(|s| *s == NovelStatus::Stub)
+    };
+    if excludes_stubs {
+        let status_key = if criteria.allowed_statuses.as_deref().is_some_and(only_stubs) {
+            Some("allowed_statuses")
+        } else if criteria.status_policy.as_ref().is_some_and(|rules| {
+            let statuses: Vec<NovelStatus> = rules.iter().map(|rule| rule.status.clone()).collect();
+            only_stubs(&statuses)
+        }) {
+            Some("status_policy")
+        } else {
+            None
+        };
+        if let Some(status_key) = status_key {
+            let status_key = format!("{}.{}", section, status_key);
+            warnings.push(ConfigWarning::new(
+                Severity::Error,
+                [&status_key, &stubs_key],
+                format!(
+                    "{} only allows stubs but {} rejects them, so no novel can pass",
+                    status_key, stubs_key
+                ),
+            ));
+        }
+    }
+
+    // Completed novels stop updating, so a recency limit on them passes
+    // only those finished in the last few days
+    for (index, rule) in criteria.status_policy.iter().flatten().enumerate() {
+        if rule.status != NovelStatus::Completed {
+            continue;
+        }
+        if let Some(max_days) = rule.max_days_since_update {
+            let rule_key = format!("{}.status_policy[{}]", section, index);
+            warnings.push(ConfigWarning::new(
+                Severity::Warning,
+                [
+                    &format!("{}.status", rule_key),
+                    &format!("{}.max_days_since_update", rule_key),
+                ],
+                format!(
+                    "{0}.status is \"completed\" but {0}.max_days_since_update is {1}, \
+                     so only novels completed in the last {1} days pass",
+                    rule_key, max_days
+                ),
+            ));
+        }
+    }
+
+    if excludes_stubs && criteria.estimate_stub_pages == Some(true) {
+        let estimate_key = format!("{}.estimate_stub_pages", section);
+        warnings.push(ConfigWarning::new(
+            Severity::Warning,
+            [&estimate_key, &stubs_key],
+            format!(
+                "{} has no effect because {} rejects stubs",
+                estimate_key, stubs_key
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_config;
+
+    /// Validate a config with the given `[criteria]` lines and extra tables.
+    fn validate(criteria: &str, extra: &str) -> Vec<ConfigWarning> {
+        let config = parse_config(&format!(
+            "[eval]\nmode = \"local\"\n[seeds]\nsource = \"manual\"\nurls = [\"1\"]\n\
+             [run]\nstop_condition = {{ type = \"empty_queue\" }}\n{}\n\
+             [criteria]\n{}\n",
+            extra, criteria
+        ))
+        .unwrap();
+        validate_config(&config)
+    }
+
+    /// Check the names in a config with the given `[eval]` and extra lines.
+    fn names(eval: &str, extra: &str) -> Vec<ConfigWarning> {
+        let config = parse_config(&format!(
+            "[eval]\n{}\n[seeds]\nsource = \"manual\"\nurls = [\"1\"]\n\
+             [run]\nstop_condition = {{ type = \"empty_queue\" }}\n{}\n",
+            eval, extra
+        ))
+        .unwrap();
+        validate_names(&config, &Registry::default())
+    }
+
+    fn messages(warnings: &[ConfigWarning]) -> Vec<String> {
+        warnings.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_consistent_config_has_no_warnings() {
+        let warnings = validate(
+            "min_pages = 100\nmax_pages = 2000\nrequired_tags = [\"Magic\"]\n\
+             excluded_tags = [\"Harem\"]\nallowed_statuses = [\"completed\", \"ongoing\"]\n\
+             exclude_stubs = true",
+            "",
+        );
+        assert_eq!(warnings, Vec::new());
+    }
+
+    #[test]
+    fn test_tag_required_and_excluded() {
+        let warnings = validate(
+            "required_tags = [\"LitRPG\", \"Magic\"]\nexcluded_tags = [\"litrpg\"]",
+            "",
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_error());
+        assert_eq!(
+            warnings[0].keys,
+            ["criteria.required_tags", "criteria.excluded_tags"]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "criteria.required_tags and criteria.excluded_tags both list 'LitRPG', \
+             so no novel can pass"
+        );
+    }
+
+    #[test]
+    fn test_genre_required_and_excluded() {
+        let warnings = validate(
+            "required_genres = [\"Fantasy\"]\nexcluded_genres = [\"Fantasy\"]",
+            "",
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_error());
+        assert_eq!(
+            warnings[0].keys,
+            ["criteria.required_genres", "criteria.excluded_genres"]
+        );
+    }
+
+    #[test]
+    fn test_genre_required_and_excluded_as_tag() {
+        let warnings = validate(
+            "required_genres = [\"Sci-fi\"]\nexcluded_tags = [\"Science Fiction\"]",
+            "",
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_error());
+        assert_eq!(
+            warnings[0].keys,
+            ["criteria.required_genres", "criteria.excluded_tags"]
+        );
+    }
+
+    #[test]
+    fn test_minimum_above_maximum() {
+        let warnings = validate(
+            "min_pages = 500\nmax_pages = 100\nmin_words = 9\nmax_words = 10\n\
+             min_pages_per_chapter = 8.5\nmax_pages_per_chapter = 2.0\n\
+             min_fiction_age_days = 30\nmax_fiction_age_days = 7",
+            "",
+        );
+        assert!(warnings.iter().all(ConfigWarning::is_error));
+        assert_eq!(
+            messages(&warnings),
+            [
+                "criteria.min_pages (500) exceeds criteria.max_pages (100), \
+                 so no novel can pass",
+                "criteria.min_pages_per_chapter (8.5) exceeds \
+                 criteria.max_pages_per_chapter (2), so no novel can pass",
+                "criteria.min_fiction_age_days (30) exceeds \
+                 criteria.max_fiction_age_days (7), so no novel can pass",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_equal_minimum_and_maximum_pass() {
+        assert_eq!(validate("min_pages = 100\nmax_pages = 100", ""), Vec::new());
+    }
+
+    #[test]
+    fn test_empty_allowed_statuses_in_code() {
+        let mut config = parse_config(
+            "[eval]\nmode = \"local\"\n[seeds]\nsource = \"manual\"\nurls = [\"1\"]\n\
+             [run]\nstop_condition = { type = \"empty_queue\" }\n",
+        )
+        .unwrap();
+        config.criteria.allowed_statuses = Some(Vec::new());
+        let warnings = validate_config(&config);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_error());
+        assert_eq!(warnings[0].keys, ["criteria.allowed_statuses"]);
+        assert_eq!(
+            warnings[0].to_string(),
+            "criteria.allowed_statuses is empty, so no novel can pass"
+        );
+    }
+
+    #[test]
+    fn test_only_stubs_allowed_but_excluded() {
+        let warnings = validate("allowed_statuses = [\"stub\"]\nexclude_stubs = true", "");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_error());
+        assert_eq!(
+            warnings[0].keys,
+            ["criteria.allowed_statuses", "criteria.exclude_stubs"]
+        );
+
+        let warnings = validate(
+            "exclude_stubs = true\n\
+             status_policy = [{ status = \"stub\" }, { status = \"stub\" }]",
+            "",
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].keys,
+            ["criteria.status_policy", "criteria.exclude_stubs"]
+        );
+
+        // Another status can still pass
+        assert_eq!(
+            validate(
+                "allowed_statuses = [\"stub\", \"ongoing\"]\nexclude_stubs = true",
+                ""
+            ),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_recency_limit_on_completed_rule() {
+        let warnings = validate(
+            "status_policy = [\n\
+             { status = \"ongoing\", max_days_since_update = 30 },\n\
+             { status = \"completed\", max_days_since_update = 14 },\n]",
+            "",
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(
+            warnings[0].keys,
+            [
+                "criteria.status_policy[1].status",
+                "criteria.status_policy[1].max_days_since_update"
+            ]
+        );
+        assert!(
+            warnings[0].to_string().contains("last 14 days"),
+            "{}",
+            warnings[0]
+        );
+    }
+
+    #[test]
+    fn test_stub_estimate_with_stubs_excluded() {
+        let warnings = validate("exclude_stubs = true\nestimate_stub_pages = true", "");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(
+            warnings[0].keys,
+            ["criteria.estimate_stub_pages", "criteria.exclude_stubs"]
+        );
+    }
+
+    #[test]
+    fn test_discovery_never_evaluated() {
+        let warnings = validate(
+            "",
+            "max_discovery_ratio = 0.0\n[[run.discovery]]\nkind = \"also_liked\"",
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Warning);
+        assert_eq!(
+            warnings[0].keys,
+            ["run.discovery", "run.max_discovery_ratio"]
+        );
+    }
+
+    #[test]
+    fn test_profiles_checked_instead_of_criteria() {
+        let warnings = validate(
+            "min_pages = 500\nmax_pages = 100",
+            "[[profiles]]\nname = \"cozy\"\nexclude_stubs = true\n\
+             estimate_stub_pages = true\n\
+             [[profiles]]\nname = \"epic\"\nmin_words = 10\nmax_words = 1",
+        );
+        // Errors come first
+        assert_eq!(
+            messages(&warnings),
+            [
+                "profiles.epic.min_words (10) exceeds profiles.epic.max_words (1), \
+                 so no novel can pass",
+                "profiles.cozy.estimate_stub_pages has no effect because \
+                 profiles.cozy.exclude_stubs rejects stubs",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_misspelled_eval_mode() {
+        let warnings = names("mode = \"lcoal\"", "");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].severity, Severity::Error);
+        assert_eq!(warnings[0].keys, ["eval.mode"]);
+        assert_eq!(
+            warnings[0].message,
+            "eval.mode: Unknown eval mode: lcoal (known modes: llm, local)"
+        );
+    }
+
+    #[test]
+    fn test_unknown_discovery_kind() {
+        let warnings = names(
+            "mode = \"local\"",
+            "[[run.discovery]]\nkind = \"also_liked\"\n[[run.discovery]]\nkind = \"also_likes\"",
+        );
+        assert_eq!(
+            messages(&warnings),
+            ["run.discovery.kind: Unknown discovery source: also_likes \
+              (known sources: also_liked)"]
+        );
+    }
+}
@@ -1,51 +1,116 @@
 //! "Others Also Liked" discovery source.
 //!
-//! Scrapes the "also liked" section from a novel's RoyalRoad page
+//! Scrapes the "also liked" section from a novel's page on its site
 //! to discover related novels, then applies lightweight pre-filtering
 //! before adding them to the processing queue.
 
+use crate::config::AlsoLikedConfig;
 use crate::discovery::DiscoverySource;
-use crate::models::{Criteria, Novel};
-use crate::scraper::RoyalRoadClient;
+use crate::models::tags::tag_key;
+use crate::models::{ChapterTitleStorage, Criteria, Novel};
+use crate::scraper::cache::ResponseCache;
+use crate::scraper::novel_page::scrape_also_liked_from;
+use crate::scraper::HttpFetch;
+use crate::site::{scrape_novel, Site};
 use anyhow::Result;
 use std::sync::Arc;
 
-/// Discovers new novels via RoyalRoad's "Others Also Liked" recommendations.
+/// Discovers new novels via a site's recommendations: RoyalRoad's "Others
+/// Also Liked" or ScribbleHub's "Similar Series".
 ///
 /// For each evaluated novel, this source scrapes the recommendation sidebar
 /// and performs lightweight filtering to avoid adding obviously irrelevant
 /// novels to the queue.
 pub struct AlsoLikedDiscovery {
+    /// The site recommendations are scraped from.
+    site: Arc<dyn Site>,
     /// Shared HTTP client for making requests.
-    #[allow(dead_code)]
-    client: Arc<RoyalRoadClient>,
+    client: Arc<dyn HttpFetch>,
     /// Criteria used for lightweight pre-filtering of discovered novels.
-    #[allow(dead_code)]
     criteria: Criteria,
+    /// Which chapter titles to keep on discovered novels.
+    chapters: ChapterTitleStorage,
+    /// Where similar fictions API responses are kept between runs, if
+    /// anywhere.
+    cache: Option<ResponseCache>,
+    /// Most novels discovered from one novel; `None` for no limit.
+    max_per_novel: Option<usize>,
 }
 
 impl AlsoLikedDiscovery {
-    /// Create a new "also liked" discovery source.
-    pub fn new(client: Arc<RoyalRoadClient>, criteria: Criteria) -> Self {
-        Self { client, criteria }
+    /// Create a new "also liked" discovery source with its `settings`,
+    /// keeping API responses in `cache` unless the settings turn that off.
+    /// Recommendations stop being fetched once `max_per_novel` of one
+    /// novel's have passed the pre-filter.
+    pub fn new(
+        site: Arc<dyn Site>,
+        client: Arc<dyn HttpFetch>,
+        criteria: Criteria,
+        chapters: ChapterTitleStorage,
+        cache: Option<ResponseCache>,
+        settings: AlsoLikedConfig,
+        max_per_novel: Option<usize>,
+    ) -> Self {
+        Self {
+            site,
+            client,
+            criteria,
+            chapters,
+            cache: cache.filter(|_| settings.use_cache),
+            max_per_novel,
+        }
+    }
+
+    /// Cheap checks on a discovered novel: status, rating, and excluded
+    /// tags. The pipeline applies the full hard filters later.
+    fn passes_prefilter(&self, novel: &Novel) -> bool {
+        let criteria = &self.criteria;
+        if let Some(statuses) = &criteria.allowed_statuses {
+            if !statuses.is_empty() && !statuses.contains(&novel.status) {
+                return false;
+            }
+        }
+        if criteria.min_rating.is_some_and(|min| novel.rating < min) {
+            return false;
+        }
+        let excluded = criteria.excluded_tags.iter().flatten();
+        let excluded: Vec<String> = excluded.map(|tag| tag_key(tag)).collect();
+        !novel.tags.iter().any(|tag| excluded.contains(&tag_key(tag)))
     }
 }
 
 impl DiscoverySource for AlsoLikedDiscovery {
-    fn discover(&self, novel: &Novel) -> Result<Vec<Novel>> {
-        let _ = novel;
-
-        // TODO: Implement "also liked" discovery
-        //
-        // Steps:
-        // 1. Use scraper::novel_page::scrape_also_liked() to get related novel IDs
-        // 2. For each discovered ID, scrape and parse the novel using scrape_novel or parse_novel_from_html
-        // 3. Apply lightweight pre-filtering:
-        //    - Check if status is in allowed_statuses
-        //    - Check if rating meets min_rating
-        //    - Check for excluded tags
-        // 4. Return novels that pass the pre-filter
-
-        todo!("Implement 'also liked' discovery with pre-filtering")
+    fn discover(&self, novel: &Novel, is_seen: &dyn Fn(u64) -> bool) -> Result<Vec<Novel>> {
+        let also_liked = scrape_also_liked_from(
+            self.site.as_ref(),
+            self.client.as_ref(),
+            novel,
+            self.cache.as_ref(),
+        )?;
+        tracing::debug!(
+            "Found {} recommendations for '{}' via the {}",
+            also_liked.fictions.len(),
+            novel.title,
+            also_liked.source
+        );
+        let mut discovered = Vec::new();
+        for id in also_liked.ids().into_iter().filter(|id| !is_seen(*id)) {
+            if self.max_per_novel.is_some_and(|max| discovered.len() >= max) {
+                break;
+            }
+            match scrape_novel(self.site.as_ref(), self.client.as_ref(), id, self.chapters) {
+                Ok(candidate) if self.passes_prefilter(&candidate) => discovered.push(candidate),
+                Ok(candidate) => {
+                    tracing::debug!("Discovered novel '{}' failed pre-filter", candidate.title);
+                }
+                Err(e) => tracing::warn!("Skipping discovered novel {}: {:#}", id, e),
+            }
+        }
+        tracing::debug!(
+            "Discovered {} novels from '{}'",
+            discovered.len(),
+            novel.title
+        );
+        Ok(discovered)
     }
 }
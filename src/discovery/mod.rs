@@ -1,9 +1,11 @@
 //! Discovery module for finding new novels to evaluate.
 //!
 //! Defines the `DiscoverySource` trait and provides implementations
-//! for discovering related novels through RoyalRoad's recommendation features.
+//! for discovering related novels through RoyalRoad's recommendation features,
+//! plus random sampling of fiction IDs for exploration.
 
 pub mod also_liked;
+pub mod random;
 
 use crate::models::Novel;
 use anyhow::Result;
@@ -18,5 +20,7 @@ pub trait DiscoverySource: Send + Sync {
     ///
     /// Returns a list of novel stubs (may have partial metadata)
     /// that should be added to the processing queue for full evaluation.
-    fn discover(&self, novel: &Novel) -> Result<Vec<Novel>>;
+    /// Novels for which `is_seen` returns true are already queued or
+    /// processed, and are skipped without fetching them again.
+    fn discover(&self, novel: &Novel, is_seen: &dyn Fn(u64) -> bool) -> Result<Vec<Novel>>;
 }
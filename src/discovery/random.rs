@@ -0,0 +1,149 @@
+//! Random exploration: seed novels sampled from the fiction ID space.
+//!
+//! Many IDs belong to deleted or hidden fictions, so IDs are tried until
+//! enough live novels are found. Every try is one request, and the number of
+//! tries is capped, so a sparse ID range ends the search rather than spinning.
+
+use crate::config::RandomSeeds;
+use crate::models::{ChapterTitleStorage, Novel};
+use crate::scraper::{is_not_found, HttpFetch};
+use crate::site::{scrape_novel, Site};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+/// Fetch up to `seeds.count` live novels on `site` with uniformly sampled
+/// IDs.
+///
+/// Each ID is tried at most once. Missing fictions are skipped quietly and
+/// other failures with a warning; either way they count as an attempt.
+/// `chapters` picks the chapter titles kept on each novel.
+pub fn sample_novels(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    seeds: &RandomSeeds,
+    chapters: ChapterTitleStorage,
+) -> Vec<Novel> {
+    let mut rng = match seeds.rng_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut tried = HashSet::new();
+    let mut novels = Vec::new();
+    while novels.len() < seeds.count
+        && tried.len() < seeds.max_attempts
+        && (tried.len() as u64) < seeds.max_id
+    {
+        let id = rng.gen_range(1..=seeds.max_id);
+        if !tried.insert(id) {
+            continue;
+        }
+        match scrape_novel(site, client, id, chapters) {
+            Ok(novel) => novels.push(novel),
+            Err(e) if is_not_found(&e) => tracing::debug!("No fiction {}; skipping", id),
+            Err(e) => tracing::warn!("Skipping random fiction {}: {:#}", id, e),
+        }
+    }
+
+    if novels.len() < seeds.count {
+        tracing::warn!(
+            "Found {} of {} random novels after trying {} IDs",
+            novels.len(),
+            seeds.count,
+            tried.len()
+        );
+    } else {
+        tracing::info!("Found {} random novels after trying {} IDs", novels.len(), tried.len());
+    }
+    novels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::FakeClient;
+    use crate::site::RoyalRoad;
+    use std::path::PathBuf;
+
+    /// A client where only `live` IDs exist; every other ID is a 404.
+    fn fake_client(live: &[u64]) -> FakeClient {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/scraper/testdata/novel_page_90435.html");
+        let html = std::fs::read_to_string(path).unwrap();
+        live.iter().fold(FakeClient::new(), |client, id| {
+            client.with_page(&format!("https://www.royalroad.com/fiction/{}", id), &html)
+        })
+    }
+
+    fn seeds(count: usize, max_id: u64, max_attempts: usize) -> RandomSeeds {
+        RandomSeeds {
+            count,
+            max_id,
+            rng_seed: Some(42),
+            max_attempts,
+        }
+    }
+
+    #[test]
+    fn test_skips_missing_ids_until_count_reached() {
+        let client = fake_client(&[3, 7, 9]);
+        let novels = sample_novels(
+            &RoyalRoad,
+            &client,
+            &seeds(2, 10, 100),
+            ChapterTitleStorage::All,
+        );
+        assert_eq!(novels.len(), 2);
+        assert!(novels.iter().all(|n| [3, 7, 9].contains(&n.id)));
+
+        // Each ID is requested at most once
+        let requests = client.requests();
+        let unique: HashSet<&String> = requests.iter().collect();
+        assert_eq!(unique.len(), requests.len());
+    }
+
+    #[test]
+    fn test_stops_when_id_space_is_exhausted() {
+        let client = fake_client(&[3]);
+        let novels = sample_novels(
+            &RoyalRoad,
+            &client,
+            &seeds(5, 10, 100),
+            ChapterTitleStorage::All,
+        );
+        assert_eq!(novels.len(), 1);
+        assert_eq!(client.requests().len(), 10);
+    }
+
+    #[test]
+    fn test_attempt_cap_bounds_requests() {
+        let client = fake_client(&[]);
+        let novels = sample_novels(
+            &RoyalRoad,
+            &client,
+            &seeds(5, 1_000_000, 8),
+            ChapterTitleStorage::All,
+        );
+        assert!(novels.is_empty());
+        assert_eq!(client.requests().len(), 8);
+    }
+
+    #[test]
+    fn test_seeded_sampling_is_reproducible() {
+        let first = fake_client(&[]);
+        let second = fake_client(&[]);
+        sample_novels(
+            &RoyalRoad,
+            &first,
+            &seeds(3, 1_000, 5),
+            ChapterTitleStorage::All,
+        );
+        sample_novels(
+            &RoyalRoad,
+            &second,
+            &seeds(3, 1_000, 5),
+            ChapterTitleStorage::All,
+        );
+        assert_eq!(first.requests(), second.requests());
+    }
+}
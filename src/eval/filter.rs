@@ -2,95 +2,822 @@
 //!
 //! Applies strict pass/fail checks based on metadata thresholds.
 //! Used as a pre-step by both Local and LLM evaluators to skip
-//! novels that cannot possibly match the criteria.
+//! novels that cannot possibly match the criteria. Every failed check is
+//! reported as a [`FilterReason`], so rejections can be explained.
+//!
+//! With soft filters on, a novel that misses only numeric limits, each by
+//! no more than its tolerance, is evaluated anyway: [`near_miss_penalty`]
+//! gives the multiplier its score is scaled by. Exact-match filters (tags,
+//! genres, statuses, and the like) are never softened.
+
+use crate::models::tags::tag_key;
+use crate::models::{AiContentKind, Criteria, Novel, NovelStatus, SoftFilters, StatusRule};
+use serde::Serialize;
+use std::fmt;
+
+/// Pages per chapter assumed when a stub has no content chapters to measure.
+const DEFAULT_PAGES_PER_CHAPTER: f64 = 10.0;
+
+/// Score penalty for missing a filter by its whole tolerance; smaller
+/// misses are penalized in proportion.
+const MAX_NEAR_MISS_PENALTY: f64 = 0.3;
+
+/// Why a novel failed a hard filter.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum FilterReason {
+    /// Stubs are excluded.
+    Stub,
+    /// Fewer pages than `min_pages`.
+    TooFewPages { pages: u64, min: u64 },
+    /// More pages than `max_pages`.
+    TooManyPages { pages: u64, max: u64 },
+    /// Fewer estimated words than `min_words`.
+    TooFewWords { words: u64, min: u64 },
+    /// More estimated words than `max_words`.
+    TooManyWords { words: u64, max: u64 },
+    /// Shorter chapters than `min_pages_per_chapter`.
+    ChaptersTooShort { pages_per_chapter: f64, min: f64 },
+    /// Longer chapters than `max_pages_per_chapter`.
+    ChaptersTooLong { pages_per_chapter: f64, max: f64 },
+    /// Rated below `min_rating`.
+    RatingTooLow { rating: f64, min: f64 },
+    /// A status not in `allowed_statuses`.
+    StatusNotAllowed { status: NovelStatus },
+    /// No `status_policy` rule matches the status and last update.
+    NoStatusRuleMatches {
+        status: NovelStatus,
+        days_since_update: Option<i64>,
+    },
+    /// Started longer ago than `max_fiction_age_days`.
+    TooOld { age_days: i64, max: u64 },
+    /// Started more recently than `min_fiction_age_days`.
+    TooNew { age_days: i64, min: u64 },
+    /// Fan fiction isn't allowed.
+    FanFiction,
+    /// AI content is excluded.
+    AiContent { kind: AiContentKind },
+    /// A tag in `required_tags` is missing.
+    MissingTag { tag: String },
+    /// A tag in `excluded_tags` is present.
+    ExcludedTag { tag: String },
+    /// A genre in `required_genres` is missing.
+    MissingGenre { genre: String },
+    /// A genre in `excluded_genres` is present.
+    ExcludedGenre { genre: String },
+}
+
+impl fmt::Display for FilterReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterReason::Stub => write!(f, "fiction is a stub"),
+            FilterReason::TooFewPages { pages, min } => write!(f, "{} pages < min {}", pages, min),
+            FilterReason::TooManyPages { pages, max } => {
+                write!(f, "{} pages > max {}", pages, max)
+            }
+            FilterReason::TooFewWords { words, min } => {
+                write!(f, "~{} words < min {}", words, min)
+            }
+            FilterReason::TooManyWords { words, max } => {
+                write!(f, "~{} words > max {}", words, max)
+            }
+            FilterReason::ChaptersTooShort {
+                pages_per_chapter,
+                min,
+            } => write!(f, "{:.1} pages per chapter < min {:.1}", pages_per_chapter, min),
+            FilterReason::ChaptersTooLong {
+                pages_per_chapter,
+                max,
+            } => write!(f, "{:.1} pages per chapter > max {:.1}", pages_per_chapter, max),
+            FilterReason::RatingTooLow { rating, min } => {
+                write!(f, "rating {:.2} < min {:.2}", rating, min)
+            }
+            FilterReason::StatusNotAllowed { status } => {
+                write!(f, "status {} not in allowed list", status)
+            }
+            FilterReason::NoStatusRuleMatches {
+                status,
+                days_since_update,
+            } => {
+                write!(f, "status {} (updated ", status)?;
+                match days_since_update {
+                    Some(days) => write!(f, "{} days ago", days)?,
+                    None => write!(f, "at an unknown date")?,
+                }
+                write!(f, ") matches no status policy rule")
+            }
+            FilterReason::TooOld { age_days, max } => {
+                write!(f, "started {} days ago > max {}", age_days, max)
+            }
+            FilterReason::TooNew { age_days, min } => {
+                write!(f, "started {} days ago < min {}", age_days, min)
+            }
+            FilterReason::FanFiction => write!(f, "fan fiction not allowed"),
+            FilterReason::AiContent { kind } => write!(f, "labelled {}", kind),
+            FilterReason::MissingTag { tag } => write!(f, "missing required tag '{}'", tag),
+            FilterReason::ExcludedTag { tag } => write!(f, "has excluded tag '{}'", tag),
+            FilterReason::MissingGenre { genre } => {
+                write!(f, "missing required genre '{}'", genre)
+            }
+            FilterReason::ExcludedGenre { genre } => write!(f, "has excluded genre '{}'", genre),
+        }
+    }
+}
 
-use crate::models::{Criteria, Novel};
+impl FilterReason {
+    /// The criteria setting that rejected the novel, e.g. "min_rating".
+    pub fn setting(&self) -> &'static str {
+        match self {
+            FilterReason::Stub => "exclude_stubs",
+            FilterReason::TooFewPages { .. } => "min_pages",
+            FilterReason::TooManyPages { .. } => "max_pages",
+            FilterReason::TooFewWords { .. } => "min_words",
+            FilterReason::TooManyWords { .. } => "max_words",
+            FilterReason::ChaptersTooShort { .. } => "min_pages_per_chapter",
+            FilterReason::ChaptersTooLong { .. } => "max_pages_per_chapter",
+            FilterReason::RatingTooLow { .. } => "min_rating",
+            FilterReason::StatusNotAllowed { .. } => "allowed_statuses",
+            FilterReason::NoStatusRuleMatches { .. } => "status_policy",
+            FilterReason::TooOld { .. } => "max_fiction_age_days",
+            FilterReason::TooNew { .. } => "min_fiction_age_days",
+            FilterReason::FanFiction => "allow_fanfiction",
+            FilterReason::AiContent { .. } => "exclude_ai_content",
+            FilterReason::MissingTag { .. } => "required_tags",
+            FilterReason::ExcludedTag { .. } => "excluded_tags",
+            FilterReason::MissingGenre { .. } => "required_genres",
+            FilterReason::ExcludedGenre { .. } => "excluded_genres",
+        }
+    }
+
+    /// The novel's value and the limit it failed, for numeric filters.
+    pub fn measurement(&self) -> Option<(f64, f64)> {
+        match *self {
+            FilterReason::TooFewPages { pages, min } => Some((pages as f64, min as f64)),
+            FilterReason::TooManyPages { pages, max } => Some((pages as f64, max as f64)),
+            FilterReason::TooFewWords { words, min } => Some((words as f64, min as f64)),
+            FilterReason::TooManyWords { words, max } => Some((words as f64, max as f64)),
+            FilterReason::ChaptersTooShort {
+                pages_per_chapter,
+                min,
+            } => Some((pages_per_chapter, min)),
+            FilterReason::ChaptersTooLong {
+                pages_per_chapter,
+                max,
+            } => Some((pages_per_chapter, max)),
+            FilterReason::RatingTooLow { rating, min } => Some((rating, min)),
+            FilterReason::TooOld { age_days, max } => Some((age_days as f64, max as f64)),
+            FilterReason::TooNew { age_days, min } => Some((age_days as f64, min as f64)),
+            _ => None,
+        }
+    }
+}
 
 /// Check whether a novel passes all hard filters defined in the criteria.
 ///
 /// Returns `true` if the novel meets all specified thresholds.
 /// A filter that is `None` in the criteria is treated as "no constraint".
 pub fn passes_hard_filters(novel: &Novel, criteria: &Criteria) -> bool {
-    // Check minimum pages
-    if let Some(min_pages) = criteria.min_pages {
-        if novel.pages < min_pages {
-            tracing::debug!(
-                "Novel '{}' rejected: {} pages < min {}",
-                novel.title,
-                novel.pages,
-                min_pages
-            );
-            return false;
+    hard_filter(novel, criteria).is_empty()
+}
+
+/// Every hard filter a novel fails, logging each at debug level. Empty if
+/// the novel passes.
+pub fn hard_filter(novel: &Novel, criteria: &Criteria) -> Vec<FilterReason> {
+    let reasons = filter_reasons(novel, criteria);
+    for reason in &reasons {
+        tracing::debug!("Novel '{}' rejected: {}", novel.title, reason);
+    }
+    reasons
+}
+
+/// The multiplier for the score of a novel that failed the filters in
+/// `reasons`, if soft filters let it through: 1.0 when it failed none, and
+/// less the further it missed by. `None` if it's rejected, because soft
+/// filters are off, a filter can't be softened, or a miss is beyond its
+/// tolerance.
+pub fn near_miss_penalty(reasons: &[FilterReason], soft: Option<&SoftFilters>) -> Option<f64> {
+    if reasons.is_empty() {
+        return Some(1.0);
+    }
+    let soft = soft?;
+    reasons.iter().try_fold(1.0, |multiplier, reason| {
+        let miss = near_miss(reason, soft)?;
+        Some(multiplier * (1.0 - MAX_NEAR_MISS_PENALTY * miss))
+    })
+}
+
+/// How much of its tolerance a failed filter used (0.0 - 1.0), or `None`
+/// if it can't be softened or missed by more than the tolerance. Only
+/// numeric filters can: the rating within a number of points, the rest
+/// within a fraction of their limit.
+fn near_miss(reason: &FilterReason, soft: &SoftFilters) -> Option<f64> {
+    let (value, limit) = reason.measurement()?;
+    let tolerance = match reason {
+        FilterReason::RatingTooLow { .. } => soft.rating_tolerance,
+        _ => soft.size_tolerance * limit.abs(),
+    };
+    let miss = (value - limit).abs();
+    (miss <= tolerance).then(|| miss / tolerance)
+}
+
+/// Every hard filter a novel fails.
+fn filter_reasons(novel: &Novel, criteria: &Criteria) -> Vec<FilterReason> {
+    let mut reasons = Vec::new();
+
+    // Check stub exclusion
+    if criteria.exclude_stubs == Some(true) && novel.status == NovelStatus::Stub {
+        reasons.push(FilterReason::Stub);
+    }
+
+    // Check page limits (only on sites that give a page count)
+    if novel.site.reports_pages() {
+        let pages = effective_pages(novel, criteria);
+        if let Some(min) = criteria.min_pages.filter(|&min| pages < min) {
+            reasons.push(FilterReason::TooFewPages { pages, min });
+        }
+        if let Some(max) = criteria.max_pages.filter(|&max| pages > max) {
+            reasons.push(FilterReason::TooManyPages { pages, max });
         }
     }
 
-    // Check maximum pages
-    if let Some(max_pages) = criteria.max_pages {
-        if novel.pages > max_pages {
-            tracing::debug!(
-                "Novel '{}' rejected: {} pages > max {}",
-                novel.title,
-                novel.pages,
-                max_pages
-            );
-            return false;
+    // Check word count limits (only once a word count estimate exists)
+    if let Some(words) = novel.word_count_estimate {
+        if let Some(min) = criteria.min_words.filter(|&min| words < min) {
+            reasons.push(FilterReason::TooFewWords { words, min });
+        }
+        if let Some(max) = criteria.max_words.filter(|&max| words > max) {
+            reasons.push(FilterReason::TooManyWords { words, max });
         }
     }
 
-    // Check minimum rating
-    if let Some(min_rating) = criteria.min_rating {
-        if novel.rating < min_rating {
-            tracing::debug!(
-                "Novel '{}' rejected: rating {:.2} < min {:.2}",
-                novel.title,
-                novel.rating,
-                min_rating
-            );
-            return false;
+    // Check chapter length limits
+    if criteria.min_pages_per_chapter.is_some() || criteria.max_pages_per_chapter.is_some() {
+        match novel.pages_per_chapter() {
+            Some(ratio) => {
+                if let Some(min) = criteria.min_pages_per_chapter.filter(|&min| ratio < min) {
+                    reasons.push(FilterReason::ChaptersTooShort {
+                        pages_per_chapter: ratio,
+                        min,
+                    });
+                }
+                if let Some(max) = criteria.max_pages_per_chapter.filter(|&max| ratio > max) {
+                    reasons.push(FilterReason::ChaptersTooLong {
+                        pages_per_chapter: ratio,
+                        max,
+                    });
+                }
+            }
+            None => tracing::debug!(
+                "Novel '{}': skipping pages-per-chapter check (stub, no chapters, or no pages)",
+                novel.title
+            ),
         }
     }
 
+    // Check minimum rating
+    if let Some(min) = criteria.min_rating.filter(|&min| novel.rating < min) {
+        reasons.push(FilterReason::RatingTooLow {
+            rating: novel.rating,
+            min,
+        });
+    }
+
     // Check allowed statuses
     if let Some(ref allowed) = criteria.allowed_statuses {
-        if !allowed.is_empty() && !allowed.contains(&novel.status) {
-            tracing::debug!(
-                "Novel '{}' rejected: status {:?} not in allowed list",
-                novel.title,
-                novel.status
-            );
-            return false;
-        }
-    }
-
-    // Check required tags
-    if let Some(ref required) = criteria.required_tags {
-        for tag in required {
-            let tag_lower = tag.to_lowercase();
-            if !novel.tags.iter().any(|t| t.to_lowercase() == tag_lower) {
-                tracing::debug!(
-                    "Novel '{}' rejected: missing required tag '{}'",
-                    novel.title,
-                    tag
-                );
-                return false;
-            }
+        if !allowed.contains(&novel.status) {
+            reasons.push(FilterReason::StatusNotAllowed {
+                status: novel.status.clone(),
+            });
         }
     }
 
-    // Check excluded tags
-    if let Some(ref excluded) = criteria.excluded_tags {
-        for tag in excluded {
-            let tag_lower = tag.to_lowercase();
-            if novel.tags.iter().any(|t| t.to_lowercase() == tag_lower) {
-                tracing::debug!(
-                    "Novel '{}' rejected: has excluded tag '{}'",
-                    novel.title,
-                    tag
-                );
-                return false;
-            }
+    // Check status policy
+    if let Some(ref rules) = criteria.status_policy {
+        if !rules.iter().any(|rule| matches_status_rule(novel, rule)) {
+            reasons.push(FilterReason::NoStatusRuleMatches {
+                status: novel.status.clone(),
+                days_since_update: novel.days_since_update(),
+            });
+        }
+    }
+
+    // Check fiction age (only once the first chapter date is known)
+    if let Some(age_days) = novel.fiction_age_days() {
+        if let Some(max) = criteria.max_fiction_age_days.filter(|&max| age_days > max as i64) {
+            reasons.push(FilterReason::TooOld { age_days, max });
+        }
+        if let Some(min) = criteria.min_fiction_age_days.filter(|&min| age_days < min as i64) {
+            reasons.push(FilterReason::TooNew { age_days, min });
+        }
+    }
+
+    // Check fan fiction
+    if criteria.allow_fanfiction == Some(false) && novel.is_fanfiction {
+        reasons.push(FilterReason::FanFiction);
+    }
+
+    // Check AI content
+    if criteria.exclude_ai_content == Some(true) {
+        if let Some(kind) = novel.ai_content {
+            reasons.push(FilterReason::AiContent { kind });
+        }
+    }
+
+    let novel_tags: Vec<String> = novel.tags.iter().map(|t| tag_key(t)).collect();
+
+    // Check required and excluded tags
+    for tag in criteria.required_tags.iter().flatten() {
+        if !novel_tags.contains(&tag_key(tag)) {
+            reasons.push(FilterReason::MissingTag { tag: tag.clone() });
+        }
+    }
+    for tag in criteria.excluded_tags.iter().flatten() {
+        if novel_tags.contains(&tag_key(tag)) {
+            reasons.push(FilterReason::ExcludedTag { tag: tag.clone() });
+        }
+    }
+
+    let novel_genres: Vec<String> = novel.genres.iter().map(|g| tag_key(g)).collect();
+
+    // Check required and excluded genres
+    for genre in criteria.required_genres.iter().flatten() {
+        if !novel_genres.contains(&tag_key(genre)) {
+            reasons.push(FilterReason::MissingGenre {
+                genre: genre.clone(),
+            });
+        }
+    }
+    for genre in criteria.excluded_genres.iter().flatten() {
+        if novel_genres.contains(&tag_key(genre)) {
+            reasons.push(FilterReason::ExcludedGenre {
+                genre: genre.clone(),
+            });
+        }
+    }
+
+    reasons
+}
+
+/// Check whether a novel satisfies a single status policy rule.
+///
+/// A rule with `max_days_since_update` only matches when the latest chapter
+/// date is known and recent enough.
+fn matches_status_rule(novel: &Novel, rule: &StatusRule) -> bool {
+    if novel.status != rule.status {
+        return false;
+    }
+    match rule.max_days_since_update {
+        Some(max_days) => novel
+            .days_since_update()
+            .is_some_and(|days| days <= max_days as i64),
+        None => true,
+    }
+}
+
+/// The page count used for page-limit checks.
+///
+/// Normally the scraped page count. For stubs with `estimate_stub_pages` set,
+/// the remaining pages per content chapter is extrapolated to the estimated
+/// full chapter count, since most of the story's pages have been removed.
+fn effective_pages(novel: &Novel, criteria: &Criteria) -> u64 {
+    if novel.status != NovelStatus::Stub || criteria.estimate_stub_pages != Some(true) {
+        return novel.pages;
+    }
+
+    let content_chapters = novel.content_chapter_count();
+    let pages_per_chapter = if content_chapters > 0 && novel.pages > 0 {
+        novel.pages as f64 / content_chapters as f64
+    } else {
+        DEFAULT_PAGES_PER_CHAPTER
+    };
+    let estimate = (novel.estimated_full_chapter_count() as f64 * pages_per_chapter).round() as u64;
+
+    tracing::debug!(
+        "Novel '{}' is a stub: estimating {} pages from {} chapters (scraped {} pages)",
+        novel.title,
+        estimate,
+        novel.estimated_full_chapter_count(),
+        novel.pages
+    );
+    estimate.max(novel.pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, AiContentKind, SiteId};
+    use crate::models::timestamp::Timestamp;
+    use crate::util::today_days;
+
+    fn stub_novel() -> Novel {
+        let mut novel = test_novel(1);
+        novel.status = NovelStatus::Stub;
+        novel.pages = 60;
+        novel.chapter_titles = vec![
+            "1 - Rabbit".to_string(),
+            "2 - Burrow".to_string(),
+            "Stub Announcement".to_string(),
+            "99 - Return".to_string(),
+            "100 - The End".to_string(),
+        ];
+        novel.chapter_count = 5;
+        novel
+    }
+
+    #[test]
+    fn test_hard_filter_reports_every_failure() {
+        let criteria = Criteria {
+            min_pages: Some(200),
+            min_rating: Some(4.5),
+            allowed_statuses: Some(vec![NovelStatus::Completed]),
+            required_tags: Some(vec!["Magic".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            hard_filter(&test_novel(1), &criteria),
+            vec![
+                FilterReason::TooFewPages { pages: 100, min: 200 },
+                FilterReason::RatingTooLow {
+                    rating: 4.0,
+                    min: 4.5
+                },
+                FilterReason::StatusNotAllowed {
+                    status: NovelStatus::Ongoing
+                },
+                FilterReason::MissingTag {
+                    tag: "Magic".to_string()
+                },
+            ]
+        );
+        assert!(hard_filter(&test_novel(1), &Criteria::default()).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_status_only_fails_a_status_filter() {
+        let mut novel = test_novel(1);
+        novel.status = NovelStatus::Unknown("ON BREAK".to_string());
+        assert!(hard_filter(&novel, &Criteria::default()).is_empty());
+
+        let criteria = Criteria {
+            allowed_statuses: Some(vec![NovelStatus::Ongoing, NovelStatus::Completed]),
+            ..Default::default()
+        };
+        assert_eq!(
+            hard_filter(&novel, &criteria),
+            vec![FilterReason::StatusNotAllowed {
+                status: NovelStatus::Unknown("ON BREAK".to_string())
+            }]
+        );
+    }
+
+    #[test]
+    fn test_filter_reason_text() {
+        let stale = FilterReason::NoStatusRuleMatches {
+            status: NovelStatus::Ongoing,
+            days_since_update: Some(45),
+        };
+        assert_eq!(
+            stale.to_string(),
+            "status Ongoing (updated 45 days ago) matches no status policy rule"
+        );
+        let ai = FilterReason::AiContent {
+            kind: AiContentKind::Assisted,
+        };
+        assert_eq!(ai.to_string(), "labelled AI-assisted");
+    }
+
+    #[test]
+    fn test_word_count_limits_apply_once_estimated() {
+        let criteria = Criteria {
+            min_words: Some(100_000),
+            max_words: Some(500_000),
+            ..Default::default()
+        };
+        let mut novel = test_novel(1);
+        assert!(passes_hard_filters(&novel, &criteria));
+
+        novel.word_count_estimate = Some(80_000);
+        assert!(!passes_hard_filters(&novel, &criteria));
+
+        novel.word_count_estimate = Some(250_000);
+        assert!(passes_hard_filters(&novel, &criteria));
+
+        novel.word_count_estimate = Some(900_000);
+        assert!(!passes_hard_filters(&novel, &criteria));
+    }
+
+    #[test]
+    fn test_pages_per_chapter_limits() {
+        let criteria = Criteria {
+            min_pages_per_chapter: Some(5.0),
+            max_pages_per_chapter: Some(20.0),
+            ..Default::default()
+        };
+        let with_ratio = |pages, chapters| {
+            let mut novel = test_novel(1);
+            novel.pages = pages;
+            novel.chapter_count = chapters;
+            novel
+        };
+
+        assert!(!passes_hard_filters(&with_ratio(300, 100), &criteria));
+        assert!(passes_hard_filters(&with_ratio(1000, 100), &criteria));
+        assert!(!passes_hard_filters(&with_ratio(2500, 100), &criteria));
+        // The check is skipped when there is no meaningful ratio
+        assert!(passes_hard_filters(&with_ratio(300, 0), &criteria));
+        assert!(passes_hard_filters(&stub_novel(), &criteria));
+    }
+
+    #[test]
+    fn test_page_limits_skip_sites_without_pages() {
+        let criteria = Criteria {
+            min_pages: Some(500),
+            min_pages_per_chapter: Some(5.0),
+            ..Default::default()
+        };
+        let mut novel = test_novel(1);
+        novel.pages = 0;
+        assert_eq!(hard_filter(&novel, &criteria).len(), 2);
+
+        novel.site = SiteId::ScribbleHub;
+        assert!(passes_hard_filters(&novel, &criteria));
+    }
+
+    fn updated_days_ago(status: NovelStatus, days: i64) -> Novel {
+        let mut novel = test_novel(1);
+        novel.status = status;
+        novel.last_chapter_date = Some(Timestamp::from_days(today_days() - days));
+        novel
+    }
+
+    fn completed_or_fresh_ongoing() -> Criteria {
+        Criteria {
+            status_policy: Some(vec![
+                StatusRule {
+                    status: NovelStatus::Completed,
+                    max_days_since_update: None,
+                },
+                StatusRule {
+                    status: NovelStatus::Ongoing,
+                    max_days_since_update: Some(30),
+                },
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_status_policy_rejects_stale_ongoing() {
+        let criteria = completed_or_fresh_ongoing();
+
+        assert!(passes_hard_filters(&updated_days_ago(NovelStatus::Ongoing, 5), &criteria));
+        assert!(!passes_hard_filters(&updated_days_ago(NovelStatus::Ongoing, 45), &criteria));
+        // An unknown update date can't satisfy the recency qualifier
+        assert!(!passes_hard_filters(&test_novel(1), &criteria));
+    }
+
+    #[test]
+    fn test_status_policy_matches_any_rule() {
+        let criteria = completed_or_fresh_ongoing();
+
+        assert!(passes_hard_filters(&updated_days_ago(NovelStatus::Completed, 900), &criteria));
+        assert!(!passes_hard_filters(&updated_days_ago(NovelStatus::Hiatus, 1), &criteria));
+    }
+
+    fn started_days_ago(days: i64) -> Novel {
+        let mut novel = test_novel(1);
+        novel.first_chapter_date = Some(Timestamp::from_days(today_days() - days));
+        novel
+    }
+
+    #[test]
+    fn test_max_fiction_age() {
+        let criteria = Criteria {
+            max_fiction_age_days: Some(60),
+            ..Default::default()
+        };
+        assert!(passes_hard_filters(&started_days_ago(10), &criteria));
+        assert!(!passes_hard_filters(&started_days_ago(400), &criteria));
+        assert!(passes_hard_filters(&test_novel(1), &criteria));
+    }
+
+    #[test]
+    fn test_min_fiction_age() {
+        let criteria = Criteria {
+            min_fiction_age_days: Some(365),
+            ..Default::default()
+        };
+        assert!(!passes_hard_filters(&started_days_ago(10), &criteria));
+        assert!(passes_hard_filters(&started_days_ago(400), &criteria));
+    }
+
+    #[test]
+    fn test_tags_match_across_spellings() {
+        let mut novel = test_novel(1);
+        novel.tags = vec!["Sci-fi".to_string(), "LitRPG".to_string()];
+
+        let required = Criteria {
+            required_tags: Some(vec!["Science Fiction".to_string(), "litrpg".to_string()]),
+            ..Default::default()
+        };
+        assert!(passes_hard_filters(&novel, &required));
+
+        let excluded = Criteria {
+            excluded_tags: Some(vec!["sci_fi".to_string()]),
+            ..Default::default()
+        };
+        assert!(!passes_hard_filters(&novel, &excluded));
+    }
+
+    #[test]
+    fn test_genre_criteria_ignore_descriptor_tags() {
+        let mut novel = test_novel(1);
+        novel.genres = vec!["Fantasy".to_string()];
+        novel.tags = vec!["Fantasy".to_string(), "Male Lead".to_string()];
+
+        let required = Criteria {
+            required_genres: Some(vec!["fantasy".to_string()]),
+            ..Default::default()
+        };
+        assert!(passes_hard_filters(&novel, &required));
+
+        // A descriptor tag is not a genre, even though it is in `tags`
+        let required_descriptor = Criteria {
+            required_genres: Some(vec!["Male Lead".to_string()]),
+            ..Default::default()
+        };
+        assert!(!passes_hard_filters(&novel, &required_descriptor));
+
+        let excluded = Criteria {
+            excluded_genres: Some(vec!["Fantasy".to_string()]),
+            ..Default::default()
+        };
+        assert!(!passes_hard_filters(&novel, &excluded));
+
+        let excluded_other = Criteria {
+            excluded_genres: Some(vec!["Sci-fi".to_string(), "Male Lead".to_string()]),
+            ..Default::default()
+        };
+        assert!(passes_hard_filters(&novel, &excluded_other));
+    }
+
+    #[test]
+    fn test_exclude_stubs() {
+        let criteria = Criteria {
+            exclude_stubs: Some(true),
+            ..Default::default()
+        };
+        assert!(!passes_hard_filters(&stub_novel(), &criteria));
+        assert!(passes_hard_filters(&test_novel(2), &criteria));
+        assert!(passes_hard_filters(&stub_novel(), &Criteria::default()));
+    }
+
+    #[test]
+    fn test_allow_fanfiction() {
+        let mut fanfic = test_novel(1);
+        fanfic.is_fanfiction = true;
+        let original = test_novel(2);
+
+        let mut criteria = Criteria::default();
+        assert!(passes_hard_filters(&fanfic, &criteria));
+
+        criteria.allow_fanfiction = Some(false);
+        assert!(!passes_hard_filters(&fanfic, &criteria));
+        assert!(passes_hard_filters(&original, &criteria));
+
+        criteria.allow_fanfiction = Some(true);
+        assert!(passes_hard_filters(&fanfic, &criteria));
+    }
+
+    #[test]
+    fn test_exclude_ai_content() {
+        let mut assisted = test_novel(1);
+        assisted.ai_content = Some(AiContentKind::Assisted);
+        let mut generated = test_novel(2);
+        generated.ai_content = Some(AiContentKind::Generated);
+        let human = test_novel(3);
+
+        let criteria = Criteria {
+            exclude_ai_content: Some(true),
+            ..Default::default()
+        };
+        assert!(!passes_hard_filters(&assisted, &criteria));
+        assert!(!passes_hard_filters(&generated, &criteria));
+        assert!(passes_hard_filters(&human, &criteria));
+
+        assert!(passes_hard_filters(&generated, &Criteria::default()));
+    }
+
+    #[test]
+    fn test_stub_min_pages_uses_estimate_when_enabled() {
+        let mut criteria = Criteria {
+            min_pages: Some(500),
+            ..Default::default()
+        };
+        assert!(!passes_hard_filters(&stub_novel(), &criteria));
+
+        // 60 pages over 4 content chapters, extrapolated to 100 chapters = 1500 pages
+        criteria.estimate_stub_pages = Some(true);
+        assert_eq!(effective_pages(&stub_novel(), &criteria), 1500);
+        assert!(passes_hard_filters(&stub_novel(), &criteria));
+    }
+
+    #[test]
+    fn test_near_miss_penalty_in_proportion_to_miss() {
+        let soft = SoftFilters::default();
+        let penalty = |reasons: &[FilterReason]| near_miss_penalty(reasons, Some(&soft));
+        let rating = |rating: f64| FilterReason::RatingTooLow { rating, min: 4.5 };
+        let pages = |pages: u64| FilterReason::TooFewPages { pages, min: 1000 };
+
+        assert_eq!(penalty(&[]), Some(1.0));
+        assert_eq!(near_miss_penalty(&[], None), Some(1.0));
+        // Half the 0.2 rating tolerance costs half the full penalty
+        let half = penalty(&[rating(4.4)]).unwrap();
+        assert!((half - (1.0 - MAX_NEAR_MISS_PENALTY / 2.0)).abs() < 1e-9, "{}", half);
+        // A miss of the whole tolerance costs the full penalty; more rejects
+        let full = penalty(&[pages(850)]).unwrap();
+        assert!((full - (1.0 - MAX_NEAR_MISS_PENALTY)).abs() < 1e-9, "{}", full);
+        assert_eq!(penalty(&[pages(849)]), None);
+        assert_eq!(penalty(&[rating(4.29)]), None);
+        // Size tolerances are a fraction of the limit, above or below it
+        let too_long = FilterReason::TooManyPages { pages: 1100, max: 1000 };
+        let penalty_long = penalty(std::slice::from_ref(&too_long)).unwrap();
+        assert!((penalty_long - (1.0 - MAX_NEAR_MISS_PENALTY * 2.0 / 3.0)).abs() < 1e-9);
+        // Several near misses compound, and one miss too many rejects them all
+        let both = penalty(&[rating(4.4), too_long.clone()]).unwrap();
+        assert!((both - half * penalty_long).abs() < 1e-9);
+        assert_eq!(penalty(&[rating(4.4), too_long.clone(), pages(10)]), None);
+
+        // Without soft filters, or with no tolerance, any miss rejects
+        assert_eq!(near_miss_penalty(&[rating(4.49)], None), None);
+        let strict = SoftFilters {
+            rating_tolerance: 0.0,
+            size_tolerance: 0.0,
+        };
+        assert_eq!(near_miss_penalty(&[rating(4.49)], Some(&strict)), None);
+    }
+
+    #[test]
+    fn test_exact_match_filters_never_softened() {
+        let lenient = SoftFilters {
+            rating_tolerance: 5.0,
+            size_tolerance: 100.0,
+        };
+        let exact = [
+            FilterReason::Stub,
+            FilterReason::StatusNotAllowed {
+                status: NovelStatus::Hiatus,
+            },
+            FilterReason::NoStatusRuleMatches {
+                status: NovelStatus::Dropped,
+                days_since_update: Some(400),
+            },
+            FilterReason::FanFiction,
+            FilterReason::AiContent {
+                kind: AiContentKind::Generated,
+            },
+            FilterReason::MissingTag {
+                tag: "Magic".to_string(),
+            },
+            FilterReason::ExcludedTag {
+                tag: "Gore".to_string(),
+            },
+            FilterReason::MissingGenre {
+                genre: "Fantasy".to_string(),
+            },
+            FilterReason::ExcludedGenre {
+                genre: "Horror".to_string(),
+            },
+        ];
+        for reason in &exact {
+            let penalty = near_miss_penalty(std::slice::from_ref(reason), Some(&lenient));
+            assert_eq!(penalty, None, "{}", reason);
         }
+
+        // A novel missing the rating by a hair still fails for its tag
+        let criteria = Criteria {
+            min_rating: Some(4.1),
+            excluded_tags: Some(vec!["Grimdark".to_string()]),
+            soft_filters: Some(SoftFilters::default()),
+            ..Default::default()
+        };
+        let mut novel = test_novel(1);
+        let reasons = hard_filter(&novel, &criteria);
+        assert!(near_miss_penalty(&reasons, criteria.soft_filters.as_ref()).is_some());
+        novel.tags.push("Grimdark".to_string());
+        let reasons = hard_filter(&novel, &criteria);
+        assert_eq!(near_miss_penalty(&reasons, criteria.soft_filters.as_ref()), None);
     }
 
-    true
+    #[test]
+    fn test_estimate_ignored_for_non_stubs() {
+        let criteria = Criteria {
+            min_pages: Some(500),
+            estimate_stub_pages: Some(true),
+            ..Default::default()
+        };
+        let mut novel = stub_novel();
+        novel.status = NovelStatus::Ongoing;
+        assert!(!passes_hard_filters(&novel, &criteria));
+    }
 }
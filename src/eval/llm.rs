@@ -3,11 +3,78 @@
 //! Uses an external LLM API (e.g., Anthropic, OpenAI) to evaluate
 //! how well a novel matches natural language criteria. Provides richer
 //! semantic understanding than keyword matching.
+//!
+//! With an audit directory set (`--audit-dir`), each evaluation's rendered
+//! prompts and the raw API response are written there, named by the
+//! novel's title and ID, with the API key redacted wherever it appears.
+
+use crate::config::LlmEvalConfig;
+use crate::eval::filter::{hard_filter, FilterReason};
+use crate::eval::{self, Evaluator};
+use crate::models::{
+    sample_chapter_titles, sub_scores, Criteria, Evidence, EvidenceSource, Novel, NovelScore,
+    NovelStatus, Review,
+};
+use crate::proxy::{ProxiedAgent, ProxySetting};
+use crate::util;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Anthropic API version header value.
+const API_VERSION: &str = "2023-06-01";
+
+/// Maximum tokens the model may use for its answer.
+const MAX_RESPONSE_TOKENS: u32 = 1024;
+
+/// Number of chapter titles sampled from each of the start, middle, and end.
+const CHAPTER_TITLES_PER_SECTION: usize = 5;
+
+/// Maximum number of reviews included in the prompt.
+const MAX_PROMPT_REVIEWS: usize = 5;
+
+/// Maximum characters kept from each review in the prompt.
+const MAX_REVIEW_CHARS: usize = 1500;
 
-use crate::eval::filter::passes_hard_filters;
-use crate::eval::Evaluator;
-use crate::models::{Criteria, Novel, NovelScore, Review};
-use anyhow::Result;
+/// Approximate character budget for the whole user prompt.
+const MAX_PROMPT_CHARS: usize = 24_000;
+
+/// The first chapter excerpt is not shrunk below this many characters
+/// while there are still reviews that could be dropped instead.
+const MIN_EXCERPT_CHARS: usize = 1_000;
+
+/// Characters of formatting added around each review's text.
+const REVIEW_OVERHEAD_CHARS: usize = 40;
+
+/// Most evidence quotes asked for, and kept, per novel.
+const MAX_QUOTES: usize = 5;
+
+/// System prompt explaining the scoring task and the response format.
+pub(crate) fn system_prompt() -> String {
+    format!(
+        "You are an expert webnovel critic helping a reader decide \
+whether a RoyalRoad fiction matches what they are looking for. Score how well the novel \
+matches the reader's criteria using its metadata, description, chapter titles, and reviews. \
+Respond with only a JSON object of the form \
+{{\"overall_score\": <0.0-1.0>, \"sub_scores\": {{\"<criteria dimension>\": <0.0-1.0>}}, \
+\"confidence\": <0.0-1.0>, \"reasoning\": \"<two or three sentences>\", \
+\"evidence\": [{{\"source\": \"description\" | \"review\" | \"chapter_title\" | \"metadata\", \
+\"author\": \"<the review's author, for reviews>\", \"quote\": \"<short exact quote>\", \
+\"contribution\": <how much it added to overall_score, 0.0-1.0>}}]}}. \
+Give up to {} evidence quotes supporting the score. \
+Set confidence by how much the material gives you to go on: low for a short description \
+and few reviews, high when the score is well supported. \
+Name criteria dimensions in snake_case. \
+When a first chapter excerpt is provided, also include a \"{}\" sub-score \
+judging the readability of its writing.",
+        MAX_QUOTES,
+        sub_scores::PROSE_QUALITY
+    )
+}
 
 /// An evaluator that uses an LLM API for semantic evaluation.
 ///
@@ -15,62 +82,570 @@ use anyhow::Result;
 /// then sends them to an LLM to get nuanced scoring and reasoning.
 pub struct LlmEvaluator {
     /// API key for authentication.
-    #[allow(dead_code)]
     api_key: String,
     /// Model identifier (e.g., "claude-sonnet-4-5-20250929").
-    #[allow(dead_code)]
     model: String,
     /// API endpoint URL.
-    #[allow(dead_code)]
     endpoint: String,
+    /// HTTP agents used for API calls, by the proxy each goes through.
+    agent: ProxiedAgent,
+    /// Directory to write each evaluation's prompts and response to.
+    audit_dir: Option<PathBuf>,
+    /// Evaluations audited so far by novel ID, so a novel scored against
+    /// several profiles gets a set of files for each.
+    audited: Mutex<HashMap<u64, usize>>,
+}
+
+/// The JSON object the model is asked to return.
+#[derive(Debug, Deserialize)]
+struct LlmVerdict {
+    overall_score: f64,
+    #[serde(default)]
+    sub_scores: HashMap<String, f64>,
+    /// How well supported the score is; models that leave it out get no
+    /// score range.
+    #[serde(default)]
+    confidence: Option<f64>,
+    reasoning: String,
+    #[serde(default)]
+    evidence: Vec<LlmQuote>,
+}
+
+/// A quote the model gives in support of its score.
+#[derive(Debug, Deserialize)]
+struct LlmQuote {
+    source: String,
+    #[serde(default)]
+    author: Option<String>,
+    quote: String,
+    #[serde(default)]
+    contribution: f64,
+}
+
+impl LlmQuote {
+    /// The quote as evidence. Sources the model made up count as metadata.
+    fn into_evidence(self) -> Evidence {
+        let source = match self.source.as_str() {
+            "description" => EvidenceSource::Description,
+            "review" => EvidenceSource::Review(self.author.unwrap_or_default()),
+            "chapter_title" => EvidenceSource::ChapterTitle,
+            _ => EvidenceSource::Metadata,
+        };
+        let contribution = if self.contribution.is_nan() { 0.0 } else { self.contribution };
+        Evidence::new(source, &self.quote, contribution.clamp(0.0, 1.0))
+    }
 }
 
 impl LlmEvaluator {
-    /// Create a new LLM evaluator with the given API configuration.
-    pub fn new(api_key: String, model: String, endpoint: String) -> Self {
-        Self {
+    /// Create a new LLM evaluator from its settings, sending requests
+    /// through the configured proxy or else `default_proxy` (the scraper's).
+    /// Fails if the proxy is invalid.
+    pub fn new(settings: LlmEvalConfig, default_proxy: &ProxySetting) -> Result<Self> {
+        let LlmEvalConfig {
+            api_key,
+            model,
+            endpoint,
+            proxy,
+            audit_dir,
+            ..
+        } = settings;
+        let agent = ProxiedAgent::new(proxy.as_ref().unwrap_or(default_proxy), || {
+            ureq::AgentBuilder::new()
+                .timeout_read(Duration::from_secs(120))
+                .timeout_write(Duration::from_secs(30))
+        })
+        .context("Failed to set up the LLM evaluator's proxy")?;
+
+        Ok(Self {
             api_key,
             model,
             endpoint,
+            agent,
+            audit_dir,
+            audited: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// The messages API URL.
+    fn url(&self) -> String {
+        format!("{}/messages", self.endpoint.trim_end_matches('/'))
+    }
+
+    /// Send the prompt to the messages API and return the response body.
+    fn send(&self, user_prompt: &str) -> Result<String> {
+        let url = self.url();
+        let body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": MAX_RESPONSE_TOKENS,
+            "system": system_prompt(),
+            "messages": [{ "role": "user", "content": user_prompt }],
+        });
+
+        let response = self
+            .agent
+            .agent(&url)
+            .post(&url)
+            .set("x-api-key", &self.api_key)
+            .set("anthropic-version", API_VERSION)
+            .set("content-type", "application/json")
+            .send_string(&body.to_string())
+            .context("LLM API request failed")?;
+        Ok(response.into_string()?)
+    }
+
+    /// The path prefix for the audit files of an evaluation of `novel`, if
+    /// auditing: its safe file name, with a count after the first
+    /// evaluation of the same novel.
+    fn audit_path(&self, novel: &Novel) -> Option<PathBuf> {
+        let dir = self.audit_dir.as_ref()?;
+        let mut audited = self.audited.lock().unwrap();
+        let count = audited.entry(novel.id).or_insert(0);
+        *count += 1;
+        let name = util::safe_filename(&novel.title, novel.id);
+        Some(match *count {
+            1 => dir.join(name),
+            count => dir.join(format!("{}-{}", name, count)),
+        })
+    }
+
+    /// Write `content` to the audit file `path` with `extension`, with the
+    /// API key redacted. A failure is logged rather than failing the
+    /// evaluation.
+    fn write_audit(&self, path: &std::path::Path, extension: &str, content: &str) {
+        let path = path.with_extension(extension);
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, util::redact(content, &self.api_key)));
+        if let Err(e) = written {
+            tracing::warn!("Failed to write audit file {}: {}", path.display(), e);
         }
     }
 }
 
+/// The model's text reply from a messages API response body.
+fn reply_text(response: &str) -> Result<String> {
+    let json: serde_json::Value =
+        serde_json::from_str(response).context("failed to parse LLM API response")?;
+
+    json["content"]
+        .as_array()
+        .and_then(|blocks| blocks.iter().find_map(|b| b["text"].as_str()))
+        .map(String::from)
+        .context("LLM API response contained no text content")
+}
+
+/// The system and user prompts as one document, for reading.
+pub fn format_prompts(system: &str, user: &str) -> String {
+    format!("## System prompt\n{}\n\n## User prompt\n{}", system, user)
+}
+
 impl Evaluator for LlmEvaluator {
+    #[tracing::instrument(skip_all, fields(novel_id = novel.id, phase = "evaluate"))]
     fn evaluate(
         &self,
         novel: &Novel,
         reviews: &[Review],
         criteria: &Criteria,
     ) -> Result<NovelScore> {
-        let _ = (novel, reviews, criteria);
-
-        // TODO: Implement LLM-based evaluation
-        //
-        // The approach:
-        // 1. Construct a system prompt explaining the scoring task
-        // 2. Build a user prompt containing:
-        //    - The user's criteria (natural language prompt + filters)
-        //    - The novel's description
-        //    - A selection of reviews (truncated to fit context window)
-        //    - Chapter titles as structural signal
-        //    - Metadata (rating, pages, status, tags)
-        // 3. Ask the LLM to return a JSON object with:
-        //    - overall_score (0.0 - 1.0)
-        //    - sub_scores (map of criteria dimension to score)
-        //    - reasoning (string explanation)
-        // 4. Parse the LLM response and construct a NovelScore
-        //
-        // API call will use reqwest to POST to self.endpoint with:
-        // - Authorization header using self.api_key
-        // - Model set to self.model
-        // - Structured output format to ensure valid JSON response
-
-        todo!("Implement LLM-based evaluation via API call")
-    }
-
-    fn pre_filter(&self, novel: &Novel, criteria: &Criteria) -> bool {
+        let prompt = build_user_prompt(novel, reviews, criteria);
+        let audit = self.audit_path(novel);
+        if let Some(path) = &audit {
+            let header = format!("Model: {}\nEndpoint: {}\n\n", self.model, self.url());
+            let prompts = format_prompts(&system_prompt(), &prompt);
+            self.write_audit(path, "prompt.md", &(header + &prompts));
+        }
+        let response = self.send(&prompt)?;
+        if let Some(path) = &audit {
+            self.write_audit(path, "response.json", &response);
+        }
+        let verdict = parse_verdict(&reply_text(&response)?)?;
+
+        let score = NovelScore {
+            novel: novel.clone(),
+            overall_score: verdict.overall_score.clamp(0.0, 1.0),
+            score_low: None,
+            score_high: None,
+            evidence: verdict
+                .evidence
+                .into_iter()
+                .filter(|quote| !quote.quote.trim().is_empty())
+                .take(MAX_QUOTES)
+                .map(LlmQuote::into_evidence)
+                .collect(),
+            sub_scores: verdict
+                .sub_scores
+                .into_iter()
+                .map(|(k, v)| (sub_scores::normalize(&k), v.clamp(0.0, 1.0)))
+                .collect(),
+            reasoning: verdict.reasoning,
+            profile: None,
+            near_misses: Vec::new(),
+        };
+        Ok(match verdict.confidence {
+            Some(confidence) => score.with_half_width(eval::half_width(confidence)),
+            None => score,
+        })
+    }
+
+    fn pre_filter(&self, novel: &Novel, criteria: &Criteria) -> Vec<FilterReason> {
         // Use the same hard filters as local mode to avoid wasting API calls
-        passes_hard_filters(novel, criteria)
+        hard_filter(novel, criteria)
+    }
+}
+
+/// Render the user prompt describing the criteria and the novel.
+///
+/// Chapter titles are sampled from the start, middle, and end of the list
+/// rather than included in full, since long serials have thousands.
+pub(crate) fn build_user_prompt(novel: &Novel, reviews: &[Review], criteria: &Criteria) -> String {
+    let mut prompt = String::new();
+
+    prompt.push_str("## Reader criteria\n");
+    match &criteria.prompt {
+        Some(text) => writeln!(prompt, "{}", text).unwrap(),
+        None => prompt.push_str("(no description given; judge general quality)\n"),
+    }
+    if let Some(tags) = &criteria.required_tags {
+        writeln!(prompt, "Required tags: {}", tags.join(", ")).unwrap();
+    }
+    if let Some(tags) = &criteria.excluded_tags {
+        writeln!(prompt, "Excluded tags: {}", tags.join(", ")).unwrap();
+    }
+
+    prompt.push_str("\n## Novel\n");
+    writeln!(prompt, "Title: {}", novel.title).unwrap();
+    writeln!(prompt, "Author: {}", novel.author).unwrap();
+    // A page count of 0 from a site without them would read as an empty novel
+    let pages = novel
+        .reported_pages()
+        .map(|pages| format!(" | Pages: {}", pages))
+        .unwrap_or_default();
+    writeln!(
+        prompt,
+        "Rating: {:.2} / 5{} | Chapters: {} | Status: {}",
+        novel.rating, pages, novel.chapter_count, novel.status
+    )
+    .unwrap();
+    writeln!(prompt, "Followers: {} | Favorites: {}", novel.followers, novel.favorites).unwrap();
+    writeln!(prompt, "Tags: {}", novel.tags.join(", ")).unwrap();
+    if let Some(reputation) = &novel.author_reputation {
+        match reputation.best_rating {
+            Some(rating) => writeln!(
+                prompt,
+                "Author's other fictions: {} (best rating {:.2}, {} followers in total)",
+                reputation.other_fictions, rating, reputation.total_followers
+            )
+            .unwrap(),
+            None => {
+                prompt.push_str("Author's other fictions: none rated (likely a first fiction)\n")
+            }
+        }
+    }
+    if novel.status == NovelStatus::Stub {
+        writeln!(
+            prompt,
+            "Note: this fiction is a STUB. Most chapters have been removed from RoyalRoad \
+             (usually moved to Kindle Unlimited), so the page and chapter counts understate \
+             the story, which originally ran to about {} chapters.",
+            novel.estimated_full_chapter_count()
+        )
+        .unwrap();
+    }
+
+    prompt.push_str("\n## Description\n");
+    writeln!(prompt, "{}", novel.description.trim()).unwrap();
+
+    let content_titles: Vec<String> = novel.content_chapter_titles().cloned().collect();
+    let titles = sample_chapter_titles(&content_titles, CHAPTER_TITLES_PER_SECTION);
+    if !titles.is_empty() {
+        writeln!(
+            prompt,
+            "\n## Chapter titles (sample of {} of {})",
+            titles.len(),
+            novel.content_chapter_count().max(content_titles.len() as u64)
+        )
+        .unwrap();
+        for title in titles {
+            writeln!(prompt, "- {}", title).unwrap();
+        }
+    }
+
+    let excerpt = novel.first_chapter_excerpt.as_deref().unwrap_or("").trim();
+    let review_texts: Vec<String> = reviews
+        .iter()
+        .take(MAX_PROMPT_REVIEWS)
+        .map(|r| r.text.chars().take(MAX_REVIEW_CHARS).collect())
+        .collect();
+    let review_lens: Vec<usize> = review_texts.iter().map(|t| t.chars().count()).collect();
+    let (excerpt_chars, reviews_kept) = allocate_budget(
+        MAX_PROMPT_CHARS.saturating_sub(prompt.chars().count()),
+        excerpt.chars().count(),
+        &review_lens,
+    );
+
+    if excerpt_chars > 0 {
+        prompt.push_str("\n## First chapter excerpt\n");
+        let text: String = excerpt.chars().take(excerpt_chars).collect();
+        writeln!(prompt, "{}", text).unwrap();
+    }
+
+    if reviews_kept > 0 {
+        prompt.push_str("\n## Reviews\n");
+        for (review, text) in reviews.iter().zip(&review_texts).take(reviews_kept) {
+            writeln!(prompt, "- {} ({:.1} stars): {}", review.author, review.rating, text).unwrap();
+        }
+    }
+
+    prompt
+}
+
+/// Split the remaining prompt budget between the chapter excerpt and reviews.
+///
+/// The excerpt is shrunk first, down to `MIN_EXCERPT_CHARS`; only then are
+/// reviews dropped from the end. Returns the number of excerpt characters to
+/// keep and the number of reviews to keep.
+pub(crate) fn allocate_budget(
+    available: usize,
+    excerpt_len: usize,
+    review_lens: &[usize],
+) -> (usize, usize) {
+    let review_cost = |kept: usize| -> usize {
+        review_lens[..kept]
+            .iter()
+            .map(|len| len + REVIEW_OVERHEAD_CHARS)
+            .sum()
+    };
+    let min_excerpt = excerpt_len.min(MIN_EXCERPT_CHARS);
+
+    let mut kept = review_lens.len();
+    while kept > 0 && review_cost(kept) + min_excerpt > available {
+        kept -= 1;
+    }
+    let excerpt_chars = excerpt_len.min(available.saturating_sub(review_cost(kept)));
+    (excerpt_chars, kept)
+}
+
+/// Parse the model's reply into a verdict.
+///
+/// Tolerates prose or code fences around the JSON object.
+fn parse_verdict(reply: &str) -> Result<LlmVerdict> {
+    let start = reply.find('{').context("LLM reply contained no JSON object")?;
+    let end = reply.rfind('}').context("LLM reply contained no JSON object")?;
+    serde_json::from_str(&reply[start..=end]).context("failed to parse LLM verdict JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, SiteId};
+
+    fn criteria() -> Criteria {
+        Criteria {
+            prompt: Some("dungeon diving".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_prompt_samples_chapter_titles() {
+        let mut novel = test_novel(1);
+        novel.chapter_titles = (1..=300).map(|i| format!("Floor {}", i)).collect();
+        novel.chapter_count = 300;
+
+        let prompt = build_user_prompt(&novel, &[], &criteria());
+
+        assert!(prompt.contains("## Chapter titles (sample of 15 of 300)"));
+        assert!(prompt.contains("- Floor 1\n"));
+        assert!(prompt.contains("- Floor 300\n"));
+        assert!(!prompt.contains("- Floor 100\n"));
+    }
+
+    #[test]
+    fn test_prompt_leaves_out_unreported_pages() {
+        let mut novel = test_novel(1);
+        let prompt = build_user_prompt(&novel, &[], &criteria());
+        assert!(prompt.contains(&format!("| Pages: {} |", novel.pages)), "{}", prompt);
+
+        novel.site = SiteId::ScribbleHub;
+        novel.pages = 0;
+        let prompt = build_user_prompt(&novel, &[], &criteria());
+        assert!(!prompt.contains("Pages:"), "{}", prompt);
+    }
+
+    #[test]
+    fn test_prompt_includes_short_chapter_list_whole() {
+        let mut novel = test_novel(1);
+        novel.chapter_titles = vec!["Prologue".to_string(), "Floor 1".to_string()];
+        novel.chapter_count = 2;
+
+        let prompt = build_user_prompt(&novel, &[], &criteria());
+
+        assert!(prompt.contains("## Chapter titles (sample of 2 of 2)"));
+        assert!(prompt.contains("- Prologue\n"));
+    }
+
+    #[test]
+    fn test_prompt_counts_chapters_beyond_stored_sample() {
+        let mut novel = test_novel(1);
+        novel.chapter_titles = (1..=30).map(|i| format!("Floor {}", i)).collect();
+        novel.chapter_count = 2_000;
+
+        let prompt = build_user_prompt(&novel, &[], &criteria());
+        assert!(prompt.contains("## Chapter titles (sample of 15 of 2000)"));
+
+        novel.chapter_titles.clear();
+        let prompt = build_user_prompt(&novel, &[], &criteria());
+        assert!(!prompt.contains("## Chapter titles"));
+    }
+
+    #[test]
+    fn test_prompt_includes_first_chapter_excerpt() {
+        let mut novel = test_novel(1);
+        novel.first_chapter_excerpt = Some("The kettle whistled.".to_string());
+        let reviews = vec![Review {
+            author: "reader".to_string(),
+            rating: 4.5,
+            text: "Great prose.".to_string(),
+            posted_date: None,
+            helpful_votes: None,
+        }];
+
+        let prompt = build_user_prompt(&novel, &reviews, &criteria());
+
+        assert!(prompt.contains("## First chapter excerpt\nThe kettle whistled.\n"));
+        assert!(prompt.contains("- reader (4.5 stars): Great prose."));
+        assert!(!build_user_prompt(&test_novel(1), &[], &criteria()).contains("First chapter"));
+    }
+
+    #[test]
+    fn test_allocate_budget_keeps_everything_that_fits() {
+        assert_eq!(allocate_budget(10_000, 5_000, &[1_000, 1_000]), (5_000, 2));
+        assert_eq!(allocate_budget(10_000, 0, &[1_000]), (0, 1));
+    }
+
+    #[test]
+    fn test_allocate_budget_shrinks_excerpt_before_dropping_reviews() {
+        // Two reviews cost 2 * 1040; the excerpt gets the rest
+        assert_eq!(allocate_budget(5_000, 8_000, &[1_000, 1_000]), (2_920, 2));
+        // Once the excerpt is at its minimum, reviews are dropped from the end
+        assert_eq!(allocate_budget(2_500, 8_000, &[1_000, 1_000]), (1_460, 1));
+        // A tiny budget truncates the excerpt below its minimum
+        assert_eq!(allocate_budget(500, 8_000, &[1_000]), (500, 0));
+    }
+
+    #[test]
+    fn test_parse_verdict_tolerates_code_fences() {
+        let reply = "```json\n{\"overall_score\": 0.8, \"sub_scores\": {\"premise\": 0.9}, \"reasoning\": \"Fits.\"}\n```";
+        let verdict = parse_verdict(reply).unwrap();
+        assert!((verdict.overall_score - 0.8).abs() < 1e-9);
+        assert_eq!(verdict.sub_scores["premise"], 0.9);
+        assert_eq!(verdict.reasoning, "Fits.");
+        assert_eq!(verdict.confidence, None);
+    }
+
+    #[test]
+    fn test_parse_verdict_confidence() {
+        let reply = r#"{"overall_score": 0.5, "confidence": 0.25, "reasoning": "Thin."}"#;
+        assert_eq!(parse_verdict(reply).unwrap().confidence, Some(0.25));
+    }
+
+    #[test]
+    fn test_quotes_become_evidence() {
+        let reply = r#"{"overall_score": 0.7, "reasoning": "Fits.", "evidence": [
+            {"source": "description", "quote": "The dungeon  opens.", "contribution": 0.3},
+            {"source": "review", "author": "Reader", "quote": "Great magic.", "contribution": 2},
+            {"source": "vibes", "quote": "Tagged LitRPG"}
+        ]}"#;
+        let evidence: Vec<Evidence> = parse_verdict(reply)
+            .unwrap()
+            .evidence
+            .into_iter()
+            .map(LlmQuote::into_evidence)
+            .collect();
+        assert_eq!(
+            evidence,
+            [
+                Evidence::new(EvidenceSource::Description, "The dungeon opens.", 0.3),
+                Evidence::new(EvidenceSource::Review("Reader".to_string()), "Great magic.", 1.0),
+                Evidence::new(EvidenceSource::Metadata, "Tagged LitRPG", 0.0),
+            ]
+        );
+    }
+
+    /// Answer every request on a local port with a messages API response
+    /// whose reply quotes the request's `x-api-key` header back, returning
+    /// the base URL.
+    fn serve_echoing_key() -> String {
+        use std::io::{BufRead, BufReader, Read, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let (mut key, mut length) = (String::new(), 0);
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    let (name, value) = header.split_once(':').unwrap_or_default();
+                    match name.to_ascii_lowercase().as_str() {
+                        "x-api-key" => key = value.trim().to_string(),
+                        "content-length" => length = value.trim().parse().unwrap(),
+                        _ => {}
+                    }
+                    header.clear();
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let reply = format!(
+                    r#"{{"overall_score": 0.7, "reasoning": "Sent with key {}."}}"#,
+                    key
+                );
+                let response = serde_json::json!({ "content": [{ "type": "text", "text": reply }] })
+                    .to_string();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                );
+            }
+        });
+        base
+    }
+
+    #[test]
+    fn test_audit_files_record_prompts_and_response() {
+        let dir = std::env::temp_dir().join(format!("novel-finder-audit-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let settings = LlmEvalConfig {
+            api_key: "sk-test-secret".to_string(),
+            model: "test-model".to_string(),
+            endpoint: serve_echoing_key(),
+            include_first_chapter: false,
+            proxy: None,
+            audit_dir: Some(dir.clone()),
+        };
+        let evaluator = LlmEvaluator::new(settings, &ProxySetting::Direct).unwrap();
+        let novel = test_novel(42);
+        let score = evaluator.evaluate(&novel, &[], &criteria()).unwrap();
+        assert!((score.overall_score - 0.7).abs() < 1e-9);
+
+        let name = util::safe_filename(&novel.title, novel.id);
+        let read = |file: String| std::fs::read_to_string(dir.join(file)).unwrap();
+        let prompt = read(format!("{}.prompt.md", name));
+        assert!(prompt.starts_with("Model: test-model\n"), "{}", prompt);
+        assert!(prompt.contains(&format!("## System prompt\n{}", system_prompt())));
+        let user_prompt = build_user_prompt(&novel, &[], &criteria());
+        assert!(prompt.ends_with(&format!("## User prompt\n{}", user_prompt)));
+        let response = read(format!("{}.response.json", name));
+        assert!(response.contains("Sent with key [redacted]."), "{}", response);
+        assert!(!response.contains("sk-test-secret"));
+
+        // Evaluating the novel again, as for another profile, keeps both
+        evaluator.evaluate(&novel, &[], &criteria()).unwrap();
+        assert!(dir.join(format!("{}-2.prompt.md", name)).exists());
+        assert!(dir.join(format!("{}-2.response.json", name)).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -3,50 +3,786 @@
 //! Scores novels using keyword matching against descriptions and reviews,
 //! plus metadata alignment with criteria. No external API calls required.
 
-use crate::eval::filter::passes_hard_filters;
-use crate::eval::Evaluator;
-use crate::models::{Criteria, Novel, NovelScore, Review};
+use crate::config::{LocalEvalConfig, PopularitySignal};
+use crate::eval::filter::{hard_filter, FilterReason};
+use crate::eval::{self, Evaluator};
+use crate::models::{
+    sub_scores, AuthorReputation, Criteria, Evidence, EvidenceSource, Novel, NovelScore,
+    NovelStatus, Review,
+};
+use crate::util;
 use anyhow::Result;
+use std::collections::{BTreeMap, HashSet};
+
+/// Words ignored when extracting keywords from the criteria prompt.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "and", "any", "are", "but", "can", "does", "for", "from", "game",
+    "get", "gets", "has", "have", "into", "its", "like", "looking", "main", "more", "novel",
+    "novels", "off", "one", "only", "really", "some", "starts", "story", "stories", "that",
+    "the", "their", "then", "there", "they", "this", "very", "want", "where", "which", "while",
+    "who", "with", "would", "you",
+];
+
+/// Relative weight of each sub-score in the overall score.
+const DESCRIPTION_WEIGHT: f64 = 0.35;
+const REVIEWS_WEIGHT: f64 = 0.2;
+const TAGS_WEIGHT: f64 = 0.15;
+const CHAPTER_TITLES_WEIGHT: f64 = 0.1;
+const RATING_WEIGHT: f64 = 0.2;
+const CHAPTER_LENGTH_WEIGHT: f64 = 0.1;
+const STICKINESS_WEIGHT: f64 = 0.1;
+const ENTHUSIASM_WEIGHT: f64 = 0.05;
+const POPULARITY_WEIGHT: f64 = 0.1;
+const AUTHOR_REPUTATION_WEIGHT: f64 = 0.1;
+
+/// Author reputation score for a first fiction, between a poor and a
+/// strong track record.
+const FIRST_FICTION_REPUTATION: f64 = 0.5;
+
+/// Followers per 1,000 average views that earn full stickiness credit.
+const STICKINESS_SATURATION: f64 = 1000.0;
+/// Favorites per follower that earns full enthusiasm credit.
+const ENTHUSIASM_SATURATION: f64 = 0.4;
+
+/// Pages per chapter at or below which chapters count as filler-short.
+const SHORT_PAGES_PER_CHAPTER: f64 = 3.0;
+/// Pages per chapter at or above which chapters get full chapter-length credit.
+const FULL_PAGES_PER_CHAPTER: f64 = 8.0;
+
+/// Reviews at which the reviews count as plenty of evidence.
+const FULL_EVIDENCE_REVIEWS: usize = 10;
+/// Description words at which the description counts as plenty of evidence.
+const FULL_EVIDENCE_DESCRIPTION_WORDS: usize = 150;
+/// Share of the confidence in a score that comes from the reviews; the rest
+/// comes from the description.
+const REVIEWS_EVIDENCE_SHARE: f64 = 0.7;
 
 /// An evaluator that uses local heuristics and keyword matching.
 ///
 /// This evaluator works entirely offline and scores novels based on:
 /// - Keyword overlap between the user's prompt and the novel's description/reviews
+/// - Keyword overlap with chapter titles (weighted lower than the description)
 /// - Metadata alignment (rating closeness to maximum, page count, etc.)
+/// - Chapter length, penalizing ultra-short chapters
+/// - Reader retention ratios (followers per view, favorites per follower)
+/// - Popularity, on a log scale that saturates at a configurable follower count
+/// - The author's track record from their other fictions, when looked up
 /// - Tag relevance
-pub struct LocalEvaluator;
+pub struct LocalEvaluator {
+    /// Tuning for the heuristic sub-scores.
+    config: LocalEvalConfig,
+}
 
 impl LocalEvaluator {
-    /// Create a new local evaluator.
-    pub fn new() -> Self {
-        Self
+    /// Create a new local evaluator with the given tuning.
+    pub fn new(config: LocalEvalConfig) -> Self {
+        Self { config }
     }
 }
 
 impl Evaluator for LocalEvaluator {
+    #[tracing::instrument(skip_all, fields(novel_id = novel.id, phase = "evaluate"))]
     fn evaluate(
         &self,
         novel: &Novel,
         reviews: &[Review],
         criteria: &Criteria,
     ) -> Result<NovelScore> {
-        let _ = (novel, reviews, criteria);
+        let keywords = criteria
+            .prompt
+            .as_deref()
+            .map(extract_keywords)
+            .unwrap_or_default();
+
+        let mut sub_scores: BTreeMap<String, f64> = BTreeMap::new();
+        let mut weighted: Vec<(f64, f64)> = Vec::new();
+        let mut notes: Vec<String> = Vec::new();
+        let mut evidence: Vec<Evidence> = Vec::new();
+
+        if !keywords.is_empty() {
+            let description_matches = matched_keywords(&keywords, &novel.description);
+            let review_text = reviews
+                .iter()
+                .map(|r| r.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let review_matches = matched_keywords(&keywords, &review_text);
+            let tag_matches = matched_keywords(&keywords, &novel.tags.join(" "));
+            let content_titles: Vec<&str> =
+                novel.content_chapter_titles().map(String::as_str).collect();
+            let title_matches = matched_keywords(&keywords, &content_titles.join("\n"));
+
+            let fraction = |matches: &[&str]| matches.len() as f64 / keywords.len() as f64;
+
+            let description_passages = sentences(&novel.description)
+                .map(|sentence| (EvidenceSource::Description, sentence.to_string()))
+                .collect();
+            let review_passages = reviews
+                .iter()
+                .flat_map(|review| {
+                    sentences(&review.text).map(|sentence| {
+                        (EvidenceSource::Review(review.author.clone()), sentence.to_string())
+                    })
+                })
+                .collect();
+            let tag_passages = novel
+                .tags
+                .iter()
+                .map(|tag| (EvidenceSource::Metadata, format!("Tag: {}", tag)))
+                .collect();
+            let title_passages = content_titles
+                .iter()
+                .map(|title| (EvidenceSource::ChapterTitle, title.to_string()))
+                .collect();
+
+            for (key, place, matches, weight, passages) in [
+                (
+                    sub_scores::KEYWORD_DESCRIPTION,
+                    "description",
+                    &description_matches,
+                    DESCRIPTION_WEIGHT,
+                    description_passages,
+                ),
+                (
+                    sub_scores::KEYWORD_REVIEWS,
+                    "reviews",
+                    &review_matches,
+                    REVIEWS_WEIGHT,
+                    review_passages,
+                ),
+                (sub_scores::TAGS, "tags", &tag_matches, TAGS_WEIGHT, tag_passages),
+                (
+                    sub_scores::CHAPTER_TITLES,
+                    "chapter titles",
+                    &title_matches,
+                    CHAPTER_TITLES_WEIGHT,
+                    title_passages,
+                ),
+            ] {
+                // Without stored titles there is nothing to match against,
+                // which says nothing about the novel
+                if key == sub_scores::CHAPTER_TITLES && novel.chapter_titles.is_empty() {
+                    continue;
+                }
+                let score = fraction(matches);
+                sub_scores.insert(key.to_string(), score);
+                weighted.push((score, weight));
+                notes.push(describe_matches(place, matches, keywords.len()));
+                evidence.extend(keyword_evidence(&keywords, passages, weight));
+            }
+        }
+
+        let rating_score = (novel.rating / 5.0).clamp(0.0, 1.0);
+        sub_scores.insert(sub_scores::RATING.to_string(), rating_score);
+        weighted.push((rating_score, RATING_WEIGHT));
+        notes.push(format!("rating {:.2}", novel.rating));
+
+        match novel.pages_per_chapter() {
+            Some(ratio) => {
+                let length_score = chapter_length_score(ratio);
+                sub_scores.insert(sub_scores::CHAPTER_LENGTH.to_string(), length_score);
+                weighted.push((length_score, CHAPTER_LENGTH_WEIGHT));
+                notes.push(format!("{:.1} pages per chapter", ratio));
+            }
+            None => tracing::debug!(
+                "Novel '{}': no chapter length score (stub or no chapters)",
+                novel.title
+            ),
+        }
+
+        let badge = match self.config.popularity_signal {
+            PopularitySignal::Badges => novel.best_badge(),
+            PopularitySignal::Followers => None,
+        };
+        let popularity = match badge {
+            Some(badge) => badge_score(badge.top_percent),
+            None => popularity_score(novel.followers, self.config.popularity_saturation),
+        };
+        sub_scores.insert(sub_scores::POPULARITY.to_string(), popularity);
+        weighted.push((popularity, POPULARITY_WEIGHT));
+        notes.push(match badge {
+            Some(badge) => format!("popularity {:.2} \u{2014} {}", popularity, badge),
+            None => format!(
+                "popularity {:.2} \u{2014} {} followers",
+                popularity,
+                util::format_thousands(novel.followers)
+            ),
+        });
+
+        if let Some(reputation) = &novel.author_reputation {
+            let score = author_reputation_score(reputation, self.config.popularity_saturation);
+            sub_scores.insert(sub_scores::AUTHOR_REPUTATION.to_string(), score);
+            weighted.push((score, AUTHOR_REPUTATION_WEIGHT));
+            notes.push(match reputation.best_rating {
+                Some(rating) => format!(
+                    "author's best other fiction rated {:.2} ({} other fictions, {} followers)",
+                    rating,
+                    reputation.other_fictions,
+                    util::format_thousands(reputation.total_followers)
+                ),
+                None => "first fiction by this author".to_string(),
+            });
+        }
+
+        if let Some(ratio) = novel.followers_per_1k_views() {
+            let score = stickiness_score(ratio);
+            sub_scores.insert(sub_scores::STICKINESS.to_string(), score);
+            weighted.push((score, STICKINESS_WEIGHT));
+            notes.push(format!("{:.0} followers per 1k views", ratio));
+        }
+
+        if let Some(ratio) = novel.favorites_per_follower() {
+            let score = enthusiasm_score(ratio);
+            sub_scores.insert(sub_scores::ENTHUSIASM.to_string(), score);
+            weighted.push((score, ENTHUSIASM_WEIGHT));
+            notes.push(format!("{:.2} favorites per follower", ratio));
+        }
+
+        if novel.status == NovelStatus::Stub {
+            notes.insert(
+                0,
+                "STUB: most chapters have been removed from RoyalRoad".to_string(),
+            );
+        }
+
+        let total_weight: f64 = weighted.iter().map(|(_, w)| w).sum();
+        let overall_score = weighted.iter().map(|(s, w)| s * w).sum::<f64>() / total_weight;
+        for item in &mut evidence {
+            item.contribution /= total_weight;
+        }
+
+        let confidence =
+            evidence_confidence(reviews.len(), novel.description.split_whitespace().count());
+        Ok(NovelScore {
+            novel: novel.clone(),
+            overall_score,
+            score_low: None,
+            score_high: None,
+            evidence,
+            sub_scores,
+            reasoning: capitalize(&notes.join("; ")),
+            profile: None,
+            near_misses: Vec::new(),
+        }
+        .with_half_width(eval::half_width(confidence)))
+    }
+
+    fn pre_filter(&self, novel: &Novel, criteria: &Criteria) -> Vec<FilterReason> {
+        hard_filter(novel, criteria)
+    }
+}
+
+/// How much a score can be trusted (0.0 - 1.0) given how much there was to
+/// go on: few reviews and a short description make for a shaky score.
+fn evidence_confidence(review_count: usize, description_words: usize) -> f64 {
+    let fraction = |count: usize, full: usize| (count as f64 / full as f64).min(1.0);
+    REVIEWS_EVIDENCE_SHARE * fraction(review_count, FULL_EVIDENCE_REVIEWS)
+        + (1.0 - REVIEWS_EVIDENCE_SHARE)
+            * fraction(description_words, FULL_EVIDENCE_DESCRIPTION_WORDS)
+}
+
+/// Score average chapter length, ramping from 0 for filler-short chapters
+/// to 1 for full-length ones.
+fn chapter_length_score(pages_per_chapter: f64) -> f64 {
+    ((pages_per_chapter - SHORT_PAGES_PER_CHAPTER)
+        / (FULL_PAGES_PER_CHAPTER - SHORT_PAGES_PER_CHAPTER))
+        .clamp(0.0, 1.0)
+}
+
+/// Score popularity as `log10(followers + 1)` relative to the saturation
+/// follower count, so a 100k-follower juggernaut doesn't drown other signals.
+fn popularity_score(followers: u64, saturation: u64) -> f64 {
+    ((followers as f64 + 1.0).log10() / (saturation as f64 + 1.0).log10()).clamp(0.0, 1.0)
+}
+
+/// Score a "Top X%" badge linearly, from 0 for the bottom of the site to 1
+/// for its very top.
+fn badge_score(top_percent: f64) -> f64 {
+    (1.0 - top_percent / 100.0).clamp(0.0, 1.0)
+}
+
+/// Score an author's track record from the best rating and total followers
+/// of their other fictions. Authors without rated prior work get a neutral score.
+fn author_reputation_score(reputation: &AuthorReputation, saturation: u64) -> f64 {
+    match reputation.best_rating {
+        Some(rating) => {
+            0.6 * (rating / 5.0).clamp(0.0, 1.0)
+                + 0.4 * popularity_score(reputation.total_followers, saturation)
+        }
+        None => FIRST_FICTION_REPUTATION,
+    }
+}
+
+/// Score followers per 1,000 average views on a log scale, so a few
+/// extreme ratios don't dominate.
+fn stickiness_score(followers_per_1k_views: f64) -> f64 {
+    ((1.0 + followers_per_1k_views.max(0.0)).log10() / (1.0 + STICKINESS_SATURATION).log10())
+        .clamp(0.0, 1.0)
+}
+
+/// Score favorites per follower linearly up to the saturation point.
+fn enthusiasm_score(favorites_per_follower: f64) -> f64 {
+    (favorites_per_follower / ENTHUSIASM_SATURATION).clamp(0.0, 1.0)
+}
+
+/// Extract distinct, lowercase keywords from a natural language prompt.
+///
+/// Drops short words and common filler words so that only content-bearing
+/// terms are matched against the novel's text.
+pub(crate) fn extract_keywords(prompt: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tokenize(prompt)
+        .filter(|word| word.len() >= 3 && !STOPWORDS.contains(&word.as_str()))
+        .filter(|word| seen.insert(word.clone()))
+        .collect()
+}
+
+/// Return the keywords that occur in the given text.
+///
+/// A keyword matches a word that starts with it, so "dungeon" also matches
+/// "dungeons" and "magic" matches "magical".
+fn matched_keywords<'a>(keywords: &'a [String], text: &str) -> Vec<&'a str> {
+    let words: HashSet<String> = tokenize(text).collect();
+    keywords
+        .iter()
+        .filter(|keyword| words.iter().any(|word| word.starts_with(keyword.as_str())))
+        .map(String::as_str)
+        .collect()
+}
+
+/// Quote the passages that matched keywords, crediting each keyword to the
+/// first passage it occurs in. `weight` is the weight of the sub-score the
+/// passages feed, so the contributions add up to its share of the weighted
+/// sum (still to be divided by the total weight).
+fn keyword_evidence(
+    keywords: &[String],
+    passages: Vec<(EvidenceSource, String)>,
+    weight: f64,
+) -> Vec<Evidence> {
+    let mut credited: HashSet<&str> = HashSet::new();
+    let mut evidence = Vec::new();
+    for (source, passage) in passages {
+        let new_matches = matched_keywords(keywords, &passage)
+            .into_iter()
+            .filter(|keyword| credited.insert(keyword))
+            .count();
+        if new_matches > 0 {
+            let contribution = weight * new_matches as f64 / keywords.len() as f64;
+            evidence.push(Evidence::new(source, &passage, contribution));
+        }
+    }
+    evidence
+}
+
+/// Split text into sentences, for quoting.
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    text.split_inclusive(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|sentence| !sentence.is_empty())
+}
+
+/// Split text into lowercase alphanumeric words.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Describe a keyword match count in `place` for the reasoning string.
+fn describe_matches(place: &str, matches: &[&str], total: usize) -> String {
+    if matches.is_empty() {
+        format!("0/{} keywords in {}", total, place)
+    } else {
+        format!(
+            "{}/{} keywords in {} ({})",
+            matches.len(),
+            total,
+            place,
+            matches.join(", ")
+        )
+    }
+}
+
+/// Uppercase the first character of a string.
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_novel;
+
+    fn criteria(prompt: &str) -> Criteria {
+        Criteria {
+            prompt: Some(prompt.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_extract_keywords_drops_filler() {
+        let keywords =
+            extract_keywords("I want a story about a dungeon and a tournament, a DUNGEON!");
+        assert_eq!(keywords, vec!["dungeon", "tournament"]);
+    }
+
+    #[test]
+    fn test_chapter_titles_sub_score() {
+        let mut novel = test_novel(1);
+        novel.chapter_titles = vec![
+            "Floor 23: The Broodmother".to_string(),
+            "Into the Dungeons".to_string(),
+            "Rest Day".to_string(),
+        ];
+
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&novel, &[], &criteria("dungeon diving with a tournament arc"))
+            .unwrap();
+
+        // "dungeon" matches "Dungeons"; "diving", "tournament", "arc" don't appear
+        assert!((score.sub_scores["chapter_titles"] - 0.25).abs() < 1e-9);
+        assert!(score.reasoning.contains("1/4 keywords in chapter titles (dungeon)"));
+    }
+
+    #[test]
+    fn test_evidence_quotes_matching_sentences() {
+        let mut novel = test_novel(1);
+        novel.description = "A quiet village life. Then the dungeon opens!\n\
+                             Magic returns to the world, and the dungeon grows. The end."
+            .to_string();
+        let review = Review {
+            author: "Reader".to_string(),
+            rating: 5.0,
+            text: "Slow start. The magic system is great.".to_string(),
+            posted_date: None,
+            helpful_votes: None,
+        };
 
-        // TODO: Implement local scoring logic
-        // 1. Extract keywords from the criteria prompt (if any)
-        // 2. Count keyword matches in novel description
-        // 3. Count keyword matches across review texts
-        // 4. Score metadata alignment:
-        //    - Rating proximity to 5.0
-        //    - Follower/favorite counts as popularity signal
-        //    - Chapter count as a maturity signal
-        // 5. Combine sub-scores into overall score with weights
-        // 6. Generate human-readable reasoning string
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&novel, &[review], &criteria("dungeon magic crafting"))
+            .unwrap();
 
-        todo!("Implement local keyword-based scoring")
+        // Each keyword is credited to the first sentence it appears in
+        let quoted: Vec<(&EvidenceSource, &str)> = score
+            .evidence
+            .iter()
+            .map(|item| (&item.source, item.snippet.as_str()))
+            .collect();
+        let reader = EvidenceSource::Review("Reader".to_string());
+        assert_eq!(
+            quoted,
+            [
+                (&EvidenceSource::Description, "Then the dungeon opens!"),
+                (
+                    &EvidenceSource::Description,
+                    "Magic returns to the world, and the dungeon grows.",
+                ),
+                (&reader, "The magic system is great."),
+            ]
+        );
+
+        // One keyword each, weighted by the sub-score they feed
+        let per_weight: Vec<f64> = [DESCRIPTION_WEIGHT, DESCRIPTION_WEIGHT, REVIEWS_WEIGHT]
+            .iter()
+            .zip(&score.evidence)
+            .map(|(weight, item)| item.contribution / weight)
+            .collect();
+        assert!(per_weight.windows(2).all(|pair| (pair[0] - pair[1]).abs() < 1e-9));
+        assert!(score.evidence.iter().all(|item| item.contribution < score.overall_score));
+    }
+
+    #[test]
+    fn test_no_evidence_without_matches() {
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&test_novel(1), &[], &criteria("dungeon"))
+            .unwrap();
+        assert!(score.evidence.is_empty());
     }
 
-    fn pre_filter(&self, novel: &Novel, criteria: &Criteria) -> bool {
-        passes_hard_filters(novel, criteria)
+    #[test]
+    fn test_description_outweighs_chapter_titles() {
+        let prompt = criteria("dungeon tournament");
+
+        let mut in_description = test_novel(1);
+        in_description.description =
+            "A dungeon tournament decides the fate of the city.".to_string();
+
+        let mut in_titles = test_novel(2);
+        in_titles.chapter_titles = vec!["The Dungeon Tournament".to_string()];
+
+        let evaluator = LocalEvaluator::new(LocalEvalConfig::default());
+        let description_score = evaluator.evaluate(&in_description, &[], &prompt).unwrap();
+        let titles_score = evaluator.evaluate(&in_titles, &[], &prompt).unwrap();
+
+        assert!(description_score.overall_score > titles_score.overall_score);
+        let no_match_score = evaluator.evaluate(&test_novel(3), &[], &prompt).unwrap();
+        assert!(titles_score.overall_score > no_match_score.overall_score);
+    }
+
+    #[test]
+    fn test_stub_noted_and_notices_ignored() {
+        let mut novel = test_novel(1);
+        novel.status = NovelStatus::Stub;
+        novel.chapter_titles = vec![
+            "1 - Rabbit".to_string(),
+            "Stub Announcement".to_string(),
+        ];
+
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&novel, &[], &criteria("stub announcement rabbit"))
+            .unwrap();
+
+        assert!(score.reasoning.starts_with("STUB: most chapters have been removed"));
+        // Only "rabbit" matches; the stub notice doesn't count as content
+        assert!(score.reasoning.contains("1/3 keywords in chapter titles (rabbit)"));
+    }
+
+    #[test]
+    fn test_unstored_chapter_titles_not_scored() {
+        let mut novel = test_novel(1);
+        novel.description = "A dungeon story".to_string();
+        novel.chapter_count = 2_000;
+
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&novel, &[], &criteria("dungeon"))
+            .unwrap();
+        assert_eq!(score.sub_scores[sub_scores::KEYWORD_DESCRIPTION], 1.0);
+        assert!(!score.sub_scores.contains_key("chapter_titles"));
+    }
+
+    #[test]
+    fn test_no_prompt_scores_on_metadata_only() {
+        let mut novel = test_novel(1);
+        novel.rating = 4.5;
+        novel.chapter_count = 0;
+        novel.followers = 20_000;
+        let no_prompt = Criteria::default();
+
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&novel, &[], &no_prompt)
+            .unwrap();
+
+        // Rating 0.9 (weight 0.2), saturated popularity 1.0 (weight 0.1), and
+        // no favorites for an enthusiasm of 0.0 (weight 0.05)
+        assert!((score.overall_score - 0.8).abs() < 1e-9);
+        assert!(!score.sub_scores.contains_key("chapter_titles"));
+        assert!(!score.sub_scores.contains_key("chapter_length"));
+    }
+
+    #[test]
+    fn test_retention_outranks_raw_traffic() {
+        let mut small_loyal = test_novel(1);
+        small_loyal.followers = 800;
+        small_loyal.favorites = 300;
+        small_loyal.average_views = Some(1_600);
+
+        let mut big_leaky = test_novel(2);
+        big_leaky.followers = 5_000;
+        big_leaky.favorites = 200;
+        big_leaky.average_views = Some(100_000);
+
+        let evaluator = LocalEvaluator::new(LocalEvalConfig::default());
+        let criteria = Criteria::default();
+        let loyal = evaluator.evaluate(&small_loyal, &[], &criteria).unwrap();
+        let leaky = evaluator.evaluate(&big_leaky, &[], &criteria).unwrap();
+
+        assert!(loyal.sub_scores["stickiness"] > leaky.sub_scores["stickiness"]);
+        assert!(loyal.sub_scores["enthusiasm"] > leaky.sub_scores["enthusiasm"]);
+        assert!(loyal.overall_score > leaky.overall_score);
+        assert!(loyal.reasoning.contains("500 followers per 1k views"));
+    }
+
+    #[test]
+    fn test_retention_ratios_guard_zero() {
+        let mut brand_new = test_novel(1);
+        brand_new.average_views = Some(0);
+        assert_eq!(brand_new.followers_per_1k_views(), None);
+        assert_eq!(brand_new.favorites_per_follower(), None);
+
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&brand_new, &[], &Criteria::default())
+            .unwrap();
+        assert!(!score.sub_scores.contains_key("stickiness"));
+        assert!(!score.sub_scores.contains_key("enthusiasm"));
+    }
+
+    #[test]
+    fn test_retention_scores_are_clamped() {
+        assert_eq!(stickiness_score(0.0), 0.0);
+        assert_eq!(stickiness_score(50_000.0), 1.0);
+        assert!(stickiness_score(100.0) < stickiness_score(500.0));
+        assert_eq!(enthusiasm_score(3.0), 1.0);
+        assert!((enthusiasm_score(0.2) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_popularity_score_is_monotonic_and_clamped() {
+        let saturation = LocalEvalConfig::default().popularity_saturation;
+        let followers = [0, 1, 10, 100, 1_000, 6_475, 20_000, 100_000, 1_000_000];
+        let scores: Vec<f64> = followers
+            .iter()
+            .map(|&f| popularity_score(f, saturation))
+            .collect();
+
+        assert_eq!(scores[0], 0.0);
+        assert!(scores.windows(2).take(6).all(|w| w[0] < w[1]));
+        assert_eq!(scores[6], 1.0);
+        assert_eq!(scores[7], 1.0);
+        assert_eq!(scores[8], 1.0);
+        // A lower saturation reaches full credit sooner
+        assert_eq!(popularity_score(6_475, 5_000), 1.0);
+    }
+
+    #[test]
+    fn test_evidence_confidence() {
+        assert_eq!(evidence_confidence(0, 0), 0.0);
+        assert_eq!(evidence_confidence(10, 150), 1.0);
+        assert_eq!(evidence_confidence(40, 900), 1.0);
+        assert!((evidence_confidence(10, 0) - REVIEWS_EVIDENCE_SHARE).abs() < 1e-9);
+        // Reviews count for more than the description
+        assert!(evidence_confidence(5, 0) > evidence_confidence(0, 75));
+        assert!(evidence_confidence(2, 40) < evidence_confidence(3, 40));
+    }
+
+    #[test]
+    fn test_score_range_narrows_with_evidence() {
+        let evaluator = LocalEvaluator::new(LocalEvalConfig::default());
+        let mut novel = test_novel(1);
+        novel.description = "word ".repeat(FULL_EVIDENCE_DESCRIPTION_WORDS);
+        let review = Review {
+            author: "reader".to_string(),
+            rating: 4.5,
+            text: "Great progression.".to_string(),
+            posted_date: None,
+            helpful_votes: None,
+        };
+        let reviews = vec![review; FULL_EVIDENCE_REVIEWS];
+
+        let shaky = evaluator.evaluate(&test_novel(1), &[], &Criteria::default()).unwrap();
+        let solid = evaluator.evaluate(&novel, &reviews, &Criteria::default()).unwrap();
+        assert!(shaky.half_width().unwrap() > solid.half_width().unwrap());
+        for score in [&shaky, &solid] {
+            let (low, high) = (score.score_low.unwrap(), score.score_high.unwrap());
+            assert!(0.0 <= low && low <= score.overall_score, "{:?}", score);
+            assert!(score.overall_score <= high && high <= 1.0, "{:?}", score);
+        }
+    }
+
+    #[test]
+    fn test_popularity_reasoning() {
+        let mut novel = test_novel(1);
+        novel.followers = 6_475;
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&novel, &[], &Criteria::default())
+            .unwrap();
+        assert!(score.reasoning.contains("popularity 0.89 \u{2014} 6,475 followers"));
+    }
+
+    #[test]
+    fn test_author_reputation_favors_proven_authors() {
+        let mut proven = test_novel(1);
+        proven.author_reputation = Some(AuthorReputation {
+            other_fictions: 2,
+            best_rating: Some(4.7),
+            total_followers: 10_000,
+        });
+        let mut first_timer = test_novel(2);
+        first_timer.author_reputation = Some(AuthorReputation {
+            other_fictions: 0,
+            best_rating: None,
+            total_followers: 0,
+        });
+
+        let evaluator = LocalEvaluator::new(LocalEvalConfig::default());
+        let criteria = Criteria::default();
+        let proven_score = evaluator.evaluate(&proven, &[], &criteria).unwrap();
+        let first_score = evaluator.evaluate(&first_timer, &[], &criteria).unwrap();
+
+        assert!(proven_score.sub_scores["author_reputation"] > 0.8);
+        assert_eq!(first_score.sub_scores["author_reputation"], FIRST_FICTION_REPUTATION);
+        assert!(proven_score.overall_score > first_score.overall_score);
+        assert!(first_score.reasoning.contains("first fiction by this author"));
+        // Without a lookup, there is no reputation sub-score at all
+        let unknown = evaluator.evaluate(&test_novel(3), &[], &criteria).unwrap();
+        assert!(!unknown.sub_scores.contains_key("author_reputation"));
+    }
+
+    #[test]
+    fn test_popularity_from_badges() {
+        use crate::models::Badge;
+
+        let mut ranked = test_novel(1);
+        ranked.followers = 150;
+        ranked.badges = vec![
+            Badge {
+                metric: "Views".to_string(),
+                top_percent: 10.0,
+            },
+            Badge {
+                metric: "Followers".to_string(),
+                top_percent: 2.0,
+            },
+        ];
+        let mut crowded = test_novel(2);
+        crowded.followers = 15_000;
+
+        let popularity = |config: &LocalEvalConfig, novel: &Novel| {
+            let score = LocalEvaluator::new(config.clone())
+                .evaluate(novel, &[], &Criteria::default())
+                .unwrap();
+            (score.sub_scores["popularity"], score.reasoning)
+        };
+
+        // By default the raw follower count decides
+        let followers = LocalEvalConfig::default();
+        assert!(popularity(&followers, &crowded).0 > popularity(&followers, &ranked).0);
+
+        // Read from badges, the top 2% ranking wins over the bigger count
+        let badges = LocalEvalConfig {
+            popularity_signal: PopularitySignal::Badges,
+            ..LocalEvalConfig::default()
+        };
+        let (ranked_score, reasoning) = popularity(&badges, &ranked);
+        assert!((ranked_score - 0.98).abs() < 1e-9);
+        assert!(
+            reasoning.contains("popularity 0.98 \u{2014} Top 2% in Followers"),
+            "{}",
+            reasoning
+        );
+        assert!(ranked_score > popularity(&badges, &crowded).0);
+        // Without a badge, followers still count
+        assert_eq!(popularity(&badges, &crowded).0, popularity(&followers, &crowded).0);
+    }
+
+    #[test]
+    fn test_chapter_length_score() {
+        assert_eq!(chapter_length_score(1.5), 0.0);
+        assert_eq!(chapter_length_score(3.0), 0.0);
+        assert!((chapter_length_score(5.5) - 0.5).abs() < 1e-9);
+        assert_eq!(chapter_length_score(8.0), 1.0);
+        assert_eq!(chapter_length_score(40.0), 1.0);
+    }
+
+    #[test]
+    fn test_chapter_length_sub_score_skipped_for_stubs() {
+        let mut short = test_novel(1);
+        short.pages = 200;
+        short.chapter_count = 100;
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&short, &[], &Criteria::default())
+            .unwrap();
+        assert_eq!(score.sub_scores["chapter_length"], 0.0);
+        assert!(score.reasoning.contains("2.0 pages per chapter"));
+
+        short.status = NovelStatus::Stub;
+        let score = LocalEvaluator::new(LocalEvalConfig::default())
+            .evaluate(&short, &[], &Criteria::default())
+            .unwrap();
+        assert!(!score.sub_scores.contains_key("chapter_length"));
     }
 }
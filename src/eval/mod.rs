@@ -6,10 +6,27 @@
 pub mod filter;
 pub mod llm;
 pub mod local;
+pub mod text;
 
+use crate::eval::filter::FilterReason;
 use crate::models::{Criteria, Novel, NovelScore, Review};
 use anyhow::Result;
 
+/// Half the width of the score range when the score rests on plenty of
+/// evidence.
+const MIN_HALF_WIDTH: f64 = 0.03;
+
+/// Half the width of the score range when the score rests on next to no
+/// evidence.
+const MAX_HALF_WIDTH: f64 = 0.25;
+
+/// Half the width of the score range for a score backed by `confidence`
+/// (0.0 - 1.0): the more confidence, the narrower the range.
+pub fn half_width(confidence: f64) -> f64 {
+    let confidence = if confidence.is_nan() { 0.0 } else { confidence.clamp(0.0, 1.0) };
+    MAX_HALF_WIDTH - (MAX_HALF_WIDTH - MIN_HALF_WIDTH) * confidence
+}
+
 /// Trait for evaluating how well a novel matches user criteria.
 ///
 /// Implementations can use different strategies (local heuristics, LLM calls, etc.)
@@ -28,7 +45,22 @@ pub trait Evaluator: Send + Sync {
 
     /// Quick pre-filter check to determine if a novel is worth fully evaluating.
     ///
-    /// Returns `true` if the novel passes basic checks (hard filters like
-    /// page count, status, rating thresholds) and should proceed to full evaluation.
-    fn pre_filter(&self, novel: &Novel, criteria: &Criteria) -> bool;
+    /// Returns every basic check (hard filters like page count, status,
+    /// rating thresholds) the novel fails; it should proceed to full
+    /// evaluation only if there are none.
+    fn pre_filter(&self, novel: &Novel, criteria: &Criteria) -> Vec<FilterReason>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_width_narrows_with_confidence() {
+        assert_eq!(half_width(0.0), MAX_HALF_WIDTH);
+        assert_eq!(half_width(1.0), MIN_HALF_WIDTH);
+        assert!(half_width(0.5) < half_width(0.25));
+        assert_eq!(half_width(2.0), MIN_HALF_WIDTH);
+        assert_eq!(half_width(f64::NAN), MAX_HALF_WIDTH);
+    }
 }
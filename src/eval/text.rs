@@ -0,0 +1,136 @@
+//! Text cleaning helpers shared by the evaluators.
+//!
+//! Descriptions on RoyalRoad often end with author-note boilerplate (update
+//! schedules, Patreon/Discord plugs, cover credits) that carries no signal
+//! about the story itself. These helpers strip such lines before the text is
+//! used for keyword matching or sent to an LLM.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Default line patterns treated as author-note boilerplate.
+///
+/// Each pattern is matched case-insensitively against a single line of the
+/// description; matching lines are removed entirely.
+pub const DEFAULT_STRIP_PATTERNS: &[&str] = &[
+    // Update schedules: "Updates Tuesdays and Thursdays", "New chapters daily"
+    r"\b(updates?|releases?|new chapters?|chapters? (are )?posted)\b.*\b(daily|weekly|every|(mon|tues|wednes|thurs|fri|satur|sun)days?)\b",
+    r"^\s*(update|release) schedule\b",
+    // Support platforms
+    r"\b(patreon|ko-?fi|subscribestar|buy me a coffee)\b",
+    // Discord invitations and links
+    r"\bdiscord\.(gg|com)\b",
+    r"\bjoin\b.*\bdiscord\b",
+    r"\bdiscord\b.*\b(server|link|community)\b",
+    // Cover and artwork credits
+    r"^\s*(the\s+)?(cover|cover art|cover artwork|artwork)\s*(is\s+)?(by|from|credit|credits|:)",
+    // Store links for published volumes
+    r"\b(amazon|audible|kindle)\b.*\blink\b",
+    r"\bavailable (now )?on (amazon|audible|kindle)\b",
+];
+
+/// Strips author-note boilerplate lines from novel descriptions.
+#[derive(Debug, Clone)]
+pub struct DescriptionCleaner {
+    /// Compiled line patterns; a line matching any of them is removed.
+    patterns: Vec<Regex>,
+}
+
+impl DescriptionCleaner {
+    /// Build a cleaner from the default patterns plus any user-supplied extras.
+    pub fn new(extra_patterns: &[String]) -> Result<Self> {
+        let patterns = DEFAULT_STRIP_PATTERNS
+            .iter()
+            .copied()
+            .chain(extra_patterns.iter().map(String::as_str))
+            .map(compile_pattern)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { patterns })
+    }
+
+    /// Return the description with all boilerplate lines removed.
+    ///
+    /// Lines that don't match any pattern are kept verbatim and in order.
+    pub fn clean(&self, description: &str) -> String {
+        description
+            .lines()
+            .filter(|line| !self.patterns.iter().any(|re| re.is_match(line)))
+            .collect::<Vec<&str>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    }
+}
+
+impl Default for DescriptionCleaner {
+    fn default() -> Self {
+        Self::new(&[]).expect("default strip patterns are valid")
+    }
+}
+
+/// Compile a line pattern as a case-insensitive regex.
+fn compile_pattern(pattern: &str) -> Result<Regex> {
+    Regex::new(&format!("(?i){}", pattern))
+        .with_context(|| format!("Invalid description strip pattern: {}", pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_strips_trailing_author_notes() {
+        let blurb = "When the last mage academy falls, Ilsa is the only student left.\n\
+                     Now she has to teach herself magic from a library that bites back.\n\
+                     Updates Tuesdays and Thursdays!\n\
+                     Cover art by @inkwell_arts\n\
+                     Join my Discord for art and memes: discord.gg/abc123\n\
+                     Support me on Patreon for 10 advance chapters.";
+
+        let cleaned = DescriptionCleaner::default().clean(blurb);
+
+        assert_eq!(
+            cleaned,
+            "When the last mage academy falls, Ilsa is the only student left.\n\
+             Now she has to teach herself magic from a library that bites back."
+        );
+    }
+
+    #[test]
+    fn test_clean_leaves_story_text_untouched() {
+        let blurb = "The Duke sows discord among the noble houses.\n\
+                     Every night the tower bell rings thirteen times.\n\
+                     A cover story is all that keeps the spy alive.\n\
+                     She releases the bound spirit on the first day of winter.";
+
+        let cleaned = DescriptionCleaner::default().clean(blurb);
+
+        assert_eq!(cleaned, blurb);
+    }
+
+    #[test]
+    fn test_clean_handles_schedules_and_store_links() {
+        let blurb = "A dungeon core learns to love its adventurers.\n\
+                     New chapters daily at 8pm EST.\n\
+                     Book 1 Amazon Link\n\
+                     Book 2 now available on Kindle Unlimited!\n\
+                     Ko-fi: ko-fi.com/dungeonwriter";
+
+        let cleaned = DescriptionCleaner::default().clean(blurb);
+
+        assert_eq!(cleaned, "A dungeon core learns to love its adventurers.");
+    }
+
+    #[test]
+    fn test_clean_applies_extra_patterns() {
+        let cleaner = DescriptionCleaner::new(&["^sponsored by".to_string()]).unwrap();
+        let cleaned = cleaner.clean("A quiet story.\nSponsored by Tea Co.");
+        assert_eq!(cleaned, "A quiet story.");
+    }
+
+    #[test]
+    fn test_invalid_extra_pattern_is_an_error() {
+        assert!(DescriptionCleaner::new(&["(unclosed".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+//! Export of every novel a run scraped, for `rescore` and for sharing.
+//!
+//! As a [`PipelineObserver`], the writer gets each novel once its page (and
+//! reviews, if it got that far) has been scraped, whether it then failed the
+//! filters or was evaluated.
+//! A `.jsonl` path gets JSON Lines, flushed after every novel, so a crash
+//! loses at most the novel being written. Any other path gets one JSON
+//! document, rewritten every [`CHECKPOINT_NOVELS`] novels and when the run
+//! ends.
+
+use crate::models::timestamp::Timestamp;
+use crate::eval::filter::FilterReason;
+use crate::models::{Novel, Review};
+use crate::observer::PipelineObserver;
+use crate::persist::{self, DatasetEntry, DatasetFile, DatasetHeader};
+use crate::stats::RunStats;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// How many novels a JSON dataset takes between rewrites.
+pub const CHECKPOINT_NOVELS: usize = 25;
+
+/// Where the novels go until they're on disk.
+enum Sink {
+    /// JSON Lines, written as they come.
+    Lines(BufWriter<File>),
+    /// One JSON document, rewritten at checkpoints.
+    Document {
+        file: DatasetFile,
+        /// Entries added since the last rewrite.
+        unsaved: usize,
+    },
+}
+
+/// A dataset file being written during a run.
+pub struct DatasetWriter {
+    path: PathBuf,
+    sink: Sink,
+    /// Novels added so far.
+    written: usize,
+    /// The scrape time recorded for every novel the run adds, for
+    /// deterministic runs. Without one, novels get the time they're added.
+    scraped_at: Option<Timestamp>,
+}
+
+impl DatasetWriter {
+    /// Start a dataset at `path`, replacing any file already there.
+    pub fn create(path: &Path) -> Result<Self> {
+        let is_lines = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"));
+        let sink = if is_lines {
+            let file = File::create(path)
+                .with_context(|| format!("Failed to create dataset file {}", path.display()))?;
+            Sink::Lines(BufWriter::new(file))
+        } else {
+            Sink::Document {
+                file: DatasetFile::default(),
+                unsaved: 0,
+            }
+        };
+        let mut writer = DatasetWriter {
+            path: path.to_path_buf(),
+            sink,
+            written: 0,
+            scraped_at: None,
+        };
+        match &mut writer.sink {
+            Sink::Lines(lines) => {
+                let header = persist::to_string(&DatasetHeader {})?;
+                writeln!(lines, "{}", header)
+                    .and_then(|()| lines.flush())
+                    .with_context(|| write_error(path))?;
+            }
+            Sink::Document { .. } => writer.save()?,
+        }
+        Ok(writer)
+    }
+
+    /// Record `scraped_at`, if given, as the scrape time of every novel the
+    /// run adds.
+    pub fn with_scraped_at(mut self, scraped_at: Option<Timestamp>) -> Self {
+        self.scraped_at = scraped_at;
+        self
+    }
+
+    /// Add a scraped novel with the reviews fetched for it, and whether
+    /// the run is evaluating it.
+    pub fn write(
+        &mut self,
+        novel: &Novel,
+        reviews: Option<&[Review]>,
+        scraped_at: Timestamp,
+        evaluated: bool,
+    ) -> Result<()> {
+        let entry = DatasetEntry {
+            novel: Novel {
+                reviews: None,
+                ..novel.clone()
+            },
+            reviews: reviews.map(<[Review]>::to_vec),
+            scraped_at: Some(scraped_at),
+            evaluated,
+        };
+        self.written += 1;
+        match &mut self.sink {
+            Sink::Lines(lines) => {
+                let line = serde_json::to_string(&entry)?;
+                writeln!(lines, "{}", line)
+                    .and_then(|()| lines.flush())
+                    .with_context(|| write_error(&self.path))
+            }
+            Sink::Document { file, unsaved } => {
+                file.novels.push(entry);
+                *unsaved += 1;
+                if *unsaved >= CHECKPOINT_NOVELS {
+                    self.save()?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Write out anything not yet on disk.
+    pub fn finish(&mut self) -> Result<()> {
+        match &mut self.sink {
+            Sink::Lines(lines) => lines.flush().with_context(|| write_error(&self.path)),
+            Sink::Document { unsaved: 0, .. } => Ok(()),
+            Sink::Document { .. } => self.save(),
+        }
+    }
+
+    /// How many novels have been added.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Rewrite a JSON document in full. Write then rename, so a crash
+    /// mid-write leaves the previous checkpoint in place.
+    fn save(&mut self) -> Result<()> {
+        let Sink::Document { file, unsaved } = &mut self.sink else {
+            return Ok(());
+        };
+        let json = persist::to_string(file)?;
+        let partial = self.path.with_extension("partial");
+        std::fs::write(&partial, json)
+            .and_then(|()| std::fs::rename(&partial, &self.path))
+            .with_context(|| write_error(&self.path))?;
+        *unsaved = 0;
+        Ok(())
+    }
+}
+
+/// Rejected novels go in with whatever reviews came with their page;
+/// the rest once their reviews are fetched.
+impl PipelineObserver for DatasetWriter {
+    fn on_novel_scraped(&mut self, novel: &Novel, reviews: &[Review]) -> Result<()> {
+        let scraped_at = self.scraped_at.unwrap_or_else(Timestamp::now);
+        self.write(novel, Some(reviews), scraped_at, true)
+    }
+
+    fn on_prefilter_rejected(&mut self, novel: &Novel, _reasons: &[FilterReason]) -> Result<()> {
+        let scraped_at = self.scraped_at.unwrap_or_else(Timestamp::now);
+        self.write(novel, novel.reviews.as_deref(), scraped_at, false)
+    }
+
+    fn on_finished(&mut self, _stats: &RunStats) -> Result<()> {
+        self.finish()?;
+        tracing::info!("Wrote {} scraped novels to the dataset", self.written);
+        Ok(())
+    }
+}
+
+fn write_error(path: &Path) -> String {
+    format!("Failed to write dataset file {}", path.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_novel;
+    use crate::rescore::load_dataset;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("novel-finder-{}-{}", std::process::id(), name))
+    }
+
+    fn review(text: &str) -> Review {
+        Review {
+            author: "Reader".to_string(),
+            rating: 4.5,
+            text: text.to_string(),
+            posted_date: None,
+            helpful_votes: Some(3),
+        }
+    }
+
+    /// Write two novels, one with reviews, and read them back for `rescore`.
+    fn round_trip(name: &str) {
+        let path = temp_path(name);
+        let scraped_at: Timestamp = "2025-01-07T10:09:50Z".parse().unwrap();
+        let mut writer = DatasetWriter::create(&path).unwrap();
+        let mut reviewed = test_novel(2);
+        reviewed.reviews = Some(vec![review("Kept out of the novel")]);
+        writer
+            .write(&test_novel(1), None, scraped_at, false)
+            .unwrap();
+        writer
+            .write(&reviewed, Some(&[review("Great")]), scraped_at, true)
+            .unwrap();
+        writer.finish().unwrap();
+        assert_eq!(writer.written(), 2);
+
+        let dataset = load_dataset(&path).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(content.starts_with(r#"{"format_version":2"#), "{}", content);
+        let ids: Vec<u64> = dataset.novels.iter().map(|novel| novel.id).collect();
+        assert_eq!(ids, [1, 2]);
+        assert_eq!(dataset.novels[1].title, reviewed.title);
+        assert_eq!(dataset.reviews.len(), 1);
+        assert_eq!(dataset.reviews[&2][0].text, "Great");
+        assert_eq!(dataset.reviews[&2][0].helpful_votes, Some(3));
+        assert!(content.contains(r#""scraped_at":"2025-01-07T10:09:50Z""#));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        round_trip("dataset.json");
+    }
+
+    #[test]
+    fn test_json_lines_round_trip() {
+        round_trip("dataset.jsonl");
+    }
+
+    #[test]
+    fn test_json_saved_at_checkpoints() {
+        let path = temp_path("checkpoints.json");
+        let scraped_at = Timestamp::now();
+        let mut writer = DatasetWriter::create(&path).unwrap();
+        let saved = || load_dataset(&path).unwrap().novels.len();
+        assert_eq!(saved(), 0);
+        for id in 0..CHECKPOINT_NOVELS as u64 + 1 {
+            writer
+                .write(&test_novel(id), None, scraped_at, true)
+                .unwrap();
+        }
+        assert_eq!(saved(), CHECKPOINT_NOVELS);
+        writer.finish().unwrap();
+        assert_eq!(saved(), CHECKPOINT_NOVELS + 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_json_lines_written_as_they_come() {
+        let path = temp_path("streamed.jsonl");
+        let mut writer = DatasetWriter::create(&path).unwrap();
+        writer
+            .write(&test_novel(1), None, Timestamp::now(), true)
+            .unwrap();
+        // Readable without finishing, as after a crash
+        assert_eq!(load_dataset(&path).unwrap().novels.len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
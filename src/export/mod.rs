@@ -0,0 +1,238 @@
+//! Export of results to files other tools can import.
+//!
+//! Exports are CSV files limited to results scoring at least `min_score`.
+//! Each format maps results onto its own columns; quoting is shared. The
+//! Markdown reading list uses the same selection of results. On request, a
+//! CSV starts with `#`-prefixed comment lines describing the run. The dataset
+//! export is separate: it holds every novel scraped, not results.
+
+pub mod dataset;
+pub mod reading_list;
+
+use crate::models::NovelScore;
+use crate::stats::RunMetadata;
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// A file format results can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// The Goodreads library import CSV, which StoryGraph also accepts.
+    Goodreads,
+}
+
+/// The header row of a Goodreads library export, which is also what its
+/// importer reads.
+const GOODREADS_HEADER: [&str; 24] = [
+    "Book Id",
+    "Title",
+    "Author",
+    "Author l-f",
+    "Additional Authors",
+    "ISBN",
+    "ISBN13",
+    "My Rating",
+    "Average Rating",
+    "Publisher",
+    "Binding",
+    "Number of Pages",
+    "Year Published",
+    "Original Publication Year",
+    "Date Read",
+    "Date Added",
+    "Bookshelves",
+    "Bookshelves with positions",
+    "Exclusive Shelf",
+    "My Review",
+    "Spoiler",
+    "Private Notes",
+    "Read Count",
+    "Owned Copies",
+];
+
+/// The shelf exported novels are put on.
+const GOODREADS_SHELF: &str = "to-read";
+
+/// Write the results scoring at least `min_score` to `path`, preceded by
+/// comment lines describing the run when `metadata` is given. Returns how
+/// many novels were exported.
+pub fn export(
+    results: &[NovelScore],
+    format: ExportFormat,
+    min_score: Option<f64>,
+    metadata: Option<&RunMetadata>,
+    path: &Path,
+) -> Result<usize> {
+    let selected = exportable(results, min_score);
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create export file {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    metadata
+        .map_or(Ok(()), |metadata| write_metadata_comments(&mut writer, metadata))
+        .and_then(|()| match format {
+            ExportFormat::Goodreads => write_goodreads(&mut writer, &selected),
+        })
+        .and_then(|_| writer.flush())
+    .with_context(|| format!("Failed to write export file {}", path.display()))?;
+    Ok(selected.len())
+}
+
+/// The results to export: those scoring at least `min_score`, each novel
+/// once (at its first, best-ranked appearance when profiles are used).
+fn exportable(results: &[NovelScore], min_score: Option<f64>) -> Vec<&NovelScore> {
+    let mut seen = HashSet::new();
+    results
+        .iter()
+        .filter(|score| min_score.is_none_or(|min| score.overall_score >= min))
+        .filter(|score| seen.insert(score.novel.id))
+        .collect()
+}
+
+/// Write results as a Goodreads import CSV: the novels go on the "to-read"
+/// shelf unrated, with their RoyalRoad URL in the private notes.
+fn write_goodreads<W: Write>(writer: &mut W, results: &[&NovelScore]) -> std::io::Result<()> {
+    write_row(writer, &GOODREADS_HEADER)?;
+    for score in results {
+        let novel = &score.novel;
+        // Left blank on sites that give no page count
+        let pages = novel.reported_pages().map(|pages| pages.to_string()).unwrap_or_default();
+        let mut row = [""; GOODREADS_HEADER.len()];
+        row[1] = &novel.title;
+        row[2] = &novel.author;
+        row[11] = &pages;
+        row[16] = GOODREADS_SHELF;
+        row[18] = GOODREADS_SHELF;
+        row[21] = &novel.url;
+        write_row(writer, &row)?;
+    }
+    Ok(())
+}
+
+/// Write the run's metadata as `# name: value` lines.
+fn write_metadata_comments<W: Write>(
+    writer: &mut W,
+    metadata: &RunMetadata,
+) -> std::io::Result<()> {
+    for (name, value) in metadata.fields() {
+        // A line break would end the comment early
+        writeln!(writer, "# {}: {}", name, value.replace(['\r', '\n'], " "))?;
+    }
+    Ok(())
+}
+
+/// Write one CSV record.
+pub(crate) fn write_row<W: Write>(writer: &mut W, fields: &[&str]) -> std::io::Result<()> {
+    let quoted: Vec<Cow<str>> = fields.iter().map(|field| quote(field)).collect();
+    writeln!(writer, "{}", quoted.join(","))
+}
+
+/// Quote a CSV field if it contains a delimiter, quote, or line break
+/// (RFC 4180), doubling any embedded quotes.
+fn quote(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, test_score, SiteId};
+    use crate::stats::test_run_metadata;
+    use std::path::PathBuf;
+
+    fn testdata_path(filename: &str) -> PathBuf {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src");
+        path.push("export");
+        path.push("testdata");
+        path.push(filename);
+        path
+    }
+
+    fn score(id: u64, title: &str, overall: f64, profile: Option<&str>) -> NovelScore {
+        let mut novel = test_novel(id);
+        novel.title = title.to_string();
+        let mut score = test_score(novel, overall);
+        score.profile = profile.map(String::from);
+        score
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("Mother of Learning"), "Mother of Learning");
+        assert_eq!(quote("Blood, Sweat"), "\"Blood, Sweat\"");
+        assert_eq!(quote("The \"Hero\""), "\"The \"\"Hero\"\"\"");
+        assert_eq!(quote("Two\nLines"), "\"Two\nLines\"");
+    }
+
+    #[test]
+    fn test_goodreads_golden_file() {
+        let mut quoted = score(2, "Blood, Sweat & \"Tears\"", 0.8, None);
+        quoted.novel.author = "O'Brien, K.".to_string();
+        let results = [score(1, "Mother of Learning", 0.9, None), quoted];
+        let selected: Vec<&NovelScore> = results.iter().collect();
+
+        let mut output = Vec::new();
+        write_goodreads(&mut output, &selected).unwrap();
+        let expected = std::fs::read_to_string(testdata_path("goodreads.csv")).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_goodreads_leaves_out_unreported_pages() {
+        let mut result = score(1, "Scribbled", 0.9, None);
+        result.novel.site = SiteId::ScribbleHub;
+        result.novel.pages = 0;
+        let mut output = Vec::new();
+        write_goodreads(&mut output, &[&result]).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let row = output.lines().nth(1).unwrap();
+        assert_eq!(row.split(',').nth(11), Some(""), "{}", row);
+    }
+
+    #[test]
+    fn test_metadata_comments_precede_the_header() {
+        let mut metadata = test_run_metadata("0123456789abcdef", 1_722_546_183);
+        metadata.seeds = "search \"two\nlines\"".to_string();
+        let path = std::env::temp_dir().join(format!(
+            "novel-finder-export-metadata-{}.csv",
+            std::process::id()
+        ));
+        let results = [score(1, "Mother of Learning", 0.9, None)];
+        export(&results, ExportFormat::Goodreads, None, Some(&metadata), &path).unwrap();
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "# novel-finder: 0.1.0");
+        assert!(lines.contains(&"# finished_at: 2024-08-01T21:03:03Z"));
+        assert!(lines.contains(&"# criteria_hash: 0123456789abcdef"));
+        assert!(lines.contains(&"# seeds: search \"two lines\""));
+        assert!(lines.iter().any(|line| line.starts_with("# criteria: {")));
+        let header = lines.iter().position(|line| !line.starts_with('#')).unwrap();
+        assert_eq!(lines[header], GOODREADS_HEADER.join(","));
+        assert_eq!(lines.len(), header + 2);
+    }
+
+    #[test]
+    fn test_exportable_applies_min_score_and_dedupes() {
+        let results = [
+            score(1, "A", 0.9, Some("cozy")),
+            score(2, "B", 0.4, Some("cozy")),
+            score(1, "A", 0.7, Some("grim")),
+            score(3, "C", 0.6, Some("grim")),
+        ];
+        let ids = |min_score| -> Vec<u64> {
+            exportable(&results, min_score).iter().map(|s| s.novel.id).collect()
+        };
+        assert_eq!(ids(Some(0.6)), vec![1, 3]);
+        assert_eq!(ids(None), vec![1, 2, 3]);
+    }
+}
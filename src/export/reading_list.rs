@@ -0,0 +1,208 @@
+//! A cumulative Markdown "to read" checklist.
+//!
+//! Each run appends its new finds as unchecked items. Novels already listed
+//! (checked off or not) are recognized by their fiction URL and skipped, and
+//! the rest of the file is never rewritten.
+
+use super::exportable;
+use crate::models::{NovelKey, NovelScore, SiteId};
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// The first lines of a newly created reading list.
+const HEADER: &str = "# To read\n\nNovels found by novel-finder.\n\n";
+
+/// Append results scoring at least `min_score` to the reading list at
+/// `path`, creating it if needed. Returns how many novels were added.
+pub fn update(results: &[NovelScore], min_score: Option<f64>, path: &Path) -> Result<usize> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Failed to read reading list {}", path.display()))
+        }
+    };
+    let listed: HashSet<NovelKey> = existing
+        .as_deref()
+        .unwrap_or_default()
+        .lines()
+        .flat_map(fiction_keys)
+        .collect();
+
+    let mut addition = String::new();
+    match &existing {
+        None => addition.push_str(HEADER),
+        // Start on a fresh line if the file doesn't end with one
+        Some(content) if !content.is_empty() && !content.ends_with('\n') => addition.push('\n'),
+        Some(_) => {}
+    }
+    let new_items: Vec<&NovelScore> = exportable(results, min_score)
+        .into_iter()
+        .filter(|score| !listed.contains(&score.novel.key()))
+        .collect();
+    for score in &new_items {
+        addition.push_str(&list_item(score));
+        addition.push('\n');
+    }
+    if existing.is_some() && new_items.is_empty() {
+        return Ok(0);
+    }
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(addition.as_bytes()))
+        .with_context(|| format!("Failed to write reading list {}", path.display()))?;
+    Ok(new_items.len())
+}
+
+/// An unchecked list item, e.g.
+/// "- [ ] [Mother of Learning](https://...) — 87%, 540 pages, Ongoing",
+/// followed by a link to the cover image when there is one. Novels from
+/// sites without page counts give their chapter count instead.
+fn list_item(score: &NovelScore) -> String {
+    let novel = &score.novel;
+    let length = if novel.site.reports_pages() {
+        format!("{} pages", novel.pages)
+    } else {
+        format!("{} chapters", novel.chapter_count)
+    };
+    let mut item = format!(
+        "- [ ] [{}]({}) \u{2014} {:.0}%, {}, {}",
+        escape_link_text(&novel.title),
+        novel.url,
+        score.overall_score * 100.0,
+        length,
+        novel.status
+    );
+    if let Some(cover_url) = &novel.cover_url {
+        item.push_str(&format!(" \u{b7} [cover]({})", cover_url));
+    }
+    item
+}
+
+/// Escape characters that would end or break a Markdown link's text.
+fn escape_link_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Where the fiction ID is in each site's fiction URLs: right after these.
+const FICTION_PATHS: [(&str, SiteId); 2] = [
+    ("royalroad.com/fiction/", SiteId::RoyalRoad),
+    ("scribblehub.com/series/", SiteId::ScribbleHub),
+];
+
+/// The novels whose fiction URLs are in a line, so an entry is recognized
+/// whether or not its URL includes the title slug.
+fn fiction_keys(line: &str) -> Vec<NovelKey> {
+    let mut found: Vec<(usize, NovelKey)> = FICTION_PATHS
+        .iter()
+        .flat_map(|&(path, site)| {
+            line.match_indices(path).filter_map(move |(start, marker)| {
+                let rest = &line[start + marker.len()..];
+                let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                let id = rest[..end].parse().ok()?;
+                Some((start, NovelKey::new(site, id)))
+            })
+        })
+        .collect();
+    found.sort_by_key(|(start, _)| *start);
+    found.into_iter().map(|(_, key)| key).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, test_score};
+    use std::path::PathBuf;
+
+    fn score(id: u64, overall: f64) -> NovelScore {
+        test_score(test_novel(id), overall)
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("novel-finder-{}-{}.md", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_list_item() {
+        let mut result = score(1, 0.87);
+        result.novel.title = "Lord of [Mysteries]".to_string();
+        result.novel.pages = 540;
+        assert_eq!(
+            list_item(&result),
+            "- [ ] [Lord of \\[Mysteries\\]](https://www.royalroad.com/fiction/1) \u{2014} 87%, \
+             540 pages, Ongoing"
+        );
+
+        let cover = "https://www.royalroadcdn.com/public/covers-large/1.jpg";
+        result.novel.cover_url = Some(cover.to_string());
+        assert!(list_item(&result).ends_with(&format!("Ongoing \u{b7} [cover]({})", cover)));
+
+        result.novel.site = SiteId::ScribbleHub;
+        assert!(list_item(&result).contains("87%, 10 chapters, Ongoing"));
+    }
+
+    #[test]
+    fn test_fiction_keys() {
+        let royalroad = |id| NovelKey::new(SiteId::RoyalRoad, id);
+        let checked = "- [x] [A](https://www.royalroad.com/fiction/90435/slug)";
+        assert_eq!(fiction_keys(checked), vec![royalroad(90435)]);
+        let two = "see royalroad.com/fiction/12 and royalroad.com/fiction/3";
+        assert_eq!(fiction_keys(two), vec![royalroad(12), royalroad(3)]);
+        let mixed = "scribblehub.com/series/12/slug/ and royalroad.com/fiction/12";
+        assert_eq!(
+            fiction_keys(mixed),
+            vec![NovelKey::new(SiteId::ScribbleHub, 12), royalroad(12)]
+        );
+        assert!(fiction_keys("- [ ] Something else").is_empty());
+    }
+
+    #[test]
+    fn test_update_creates_file_and_is_idempotent() {
+        let path = temp_path("reading-list-new");
+        let _ = std::fs::remove_file(&path);
+        let results = [score(1, 0.9), score(2, 0.4)];
+
+        assert_eq!(update(&results, Some(0.5), &path).unwrap(), 1);
+        let first = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(update(&results, Some(0.5), &path).unwrap(), 0);
+        let second = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(first.starts_with(HEADER));
+        assert!(first.contains("(https://www.royalroad.com/fiction/1)"));
+        assert!(!first.contains("fiction/2"));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_update_preserves_existing_entries() {
+        let path = temp_path("reading-list-existing");
+        let existing = "# My list\n\n- [x] [Done](https://www.royalroad.com/fiction/1/done)\nnotes";
+        std::fs::write(&path, existing).unwrap();
+        let results = [score(1, 0.9), score(2, 0.8)];
+
+        assert_eq!(update(&results, None, &path).unwrap(), 1);
+        let first = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(update(&results, None, &path).unwrap(), 0);
+        let second = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let expected_item = format!("\n{}\n", list_item(&results[1]));
+        assert_eq!(first, format!("{}{}", existing, expected_item));
+        assert_eq!(first, second);
+    }
+}
@@ -0,0 +1,283 @@
+//! Suggested next steps after a run.
+//!
+//! Each rule looks at the run's [`RunStats`] for one sign of a run that
+//! could have gone better, like a filter rejecting most candidates or the
+//! queue running out early, and words a suggestion for the config. Rules are
+//! tried in order of how much they usually matter and only the first few
+//! that apply are shown.
+
+use crate::stats::{FilterTally, Phase, RunStats};
+
+/// Most suggestions shown after one run.
+pub const MAX_HINTS: usize = 3;
+
+/// Fewest candidates before one filter's share of them means anything.
+const MIN_CANDIDATES: usize = 10;
+
+/// Discovery calls that found nothing new before it looks exhausted.
+const MIN_FRUITLESS_DISCOVERIES: usize = 3;
+
+/// Up to [`MAX_HINTS`] suggestions for the next run, most important first.
+pub fn suggestions(stats: &RunStats) -> Vec<String> {
+    let rules: [fn(&RunStats) -> Option<String>; 6] = [
+        dominant_filter,
+        queue_ran_dry,
+        discovery_exhausted,
+        challenged,
+        over_budget,
+        timed_out,
+    ];
+    rules
+        .iter()
+        .filter_map(|rule| rule(stats))
+        .take(MAX_HINTS)
+        .collect()
+}
+
+/// A filter that rejected at least half of the novels considered.
+fn dominant_filter(stats: &RunStats) -> Option<String> {
+    let candidates = stats.novels_evaluated + stats.novels_rejected;
+    if candidates < MIN_CANDIDATES {
+        return None;
+    }
+    let (setting, tally) = stats
+        .rejections
+        .iter()
+        .max_by_key(|(setting, tally)| (tally.novels, std::cmp::Reverse(*setting)))?;
+    if tally.novels * 2 < candidates {
+        return None;
+    }
+    let (filter, consider) = match (tally.limit, relaxed_limit(setting, tally)) {
+        (Some(limit), Some(relaxed)) => (
+            format!("{} {}", setting, limit),
+            format!("consider {}", relaxed),
+        ),
+        _ => (setting.to_string(), "consider relaxing it".to_string()),
+    };
+    Some(format!(
+        "{} rejected {} of {} candidates — {}",
+        filter, tally.novels, candidates, consider
+    ))
+}
+
+/// A limit that would have let about half the novels `setting` rejected
+/// through: the median of their values, rounded away from the limit to its
+/// precision (tenths unless the limit is whole).
+fn relaxed_limit(setting: &str, tally: &FilterTally) -> Option<f64> {
+    let limit = tally.limit?;
+    let mut values = tally.values.clone();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let median = values[values.len() / 2];
+    let scale = if limit.fract() == 0.0 { 1.0 } else { 10.0 };
+    Some(if setting.starts_with("min_") {
+        (median * scale).floor() / scale
+    } else {
+        (median * scale).ceil() / scale
+    })
+}
+
+/// The queue emptied before the stop condition was reached.
+fn queue_ran_dry(stats: &RunStats) -> Option<String> {
+    if !stats.queue_ran_dry {
+        return None;
+    }
+    Some(if stats.phases.contains_key(&Phase::Discovery) {
+        "The queue ran out before the stop condition — lower [run] discovery_min_score \
+         or add seeds"
+            .to_string()
+    } else {
+        "The queue ran out before the stop condition — set [run] discovery_enabled = true \
+         or add seeds"
+            .to_string()
+    })
+}
+
+/// Discovery ran several times without finding a novel not already seen.
+fn discovery_exhausted(stats: &RunStats) -> Option<String> {
+    let discoveries = stats.phases.get(&Phase::Discovery)?.count;
+    if discoveries < MIN_FRUITLESS_DISCOVERIES || stats.novels_discovered > 0 {
+        return None;
+    }
+    Some(format!(
+        "Discovery ran {} times but every recommendation was already seen — try \
+         different seeds",
+        discoveries
+    ))
+}
+
+/// Novels skipped over Cloudflare challenge pages.
+fn challenged(stats: &RunStats) -> Option<String> {
+    if stats.novels_challenged == 0 {
+        return None;
+    }
+    Some(format!(
+        "{} novels were skipped over Cloudflare challenges — raise \
+         [scraper.rate_limits] pages to slow down",
+        stats.novels_challenged
+    ))
+}
+
+/// Novels that skipped enrichments for running out of requests.
+fn over_budget(stats: &RunStats) -> Option<String> {
+    if stats.novels_over_budget.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{} novels skipped enrichments over their request budget — raise [run] \
+         max_requests_per_novel",
+        stats.novels_over_budget.len()
+    ))
+}
+
+/// Novels abandoned past the per-novel time limit.
+fn timed_out(stats: &RunStats) -> Option<String> {
+    if stats.novels_timed_out.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{} novels were abandoned over the time limit — raise [run] max_seconds_per_novel",
+        stats.novels_timed_out.len()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::filter::FilterReason;
+    use std::time::Duration;
+
+    fn rejected(stats: &mut RunStats, ratings: &[f64]) {
+        for &rating in ratings {
+            stats.record_rejection(&[FilterReason::RatingTooLow { rating, min: 4.7 }]);
+        }
+    }
+
+    #[test]
+    fn test_dominant_filter_suggests_a_relaxed_limit() {
+        let mut stats = RunStats {
+            novels_evaluated: 4,
+            ..RunStats::default()
+        };
+        rejected(&mut stats, &[4.1, 4.3, 4.56, 4.6, 4.65, 4.69]);
+        stats.record_rejection(&[FilterReason::FanFiction]);
+        assert_eq!(
+            dominant_filter(&stats).unwrap(),
+            "min_rating 4.7 rejected 6 of 11 candidates — consider 4.6"
+        );
+
+        // Under half of the candidates isn't dominant
+        stats.novels_evaluated = 20;
+        assert_eq!(dominant_filter(&stats), None);
+        // Nor is anything among too few candidates
+        let mut few = RunStats::default();
+        rejected(&mut few, &[4.0, 4.0]);
+        assert_eq!(dominant_filter(&few), None);
+    }
+
+    #[test]
+    fn test_dominant_filter_without_a_single_limit() {
+        let mut stats = RunStats::default();
+        for _ in 0..10 {
+            stats.record_rejection(&[FilterReason::FanFiction]);
+        }
+        assert_eq!(
+            dominant_filter(&stats).unwrap(),
+            "allow_fanfiction rejected 10 of 10 candidates — consider relaxing it"
+        );
+
+        // Profiles with different limits have no one limit to relax
+        let mut stats = RunStats::default();
+        for max in [500, 800].repeat(5) {
+            stats.record_rejection(&[FilterReason::TooManyPages { pages: 900, max }]);
+        }
+        assert_eq!(
+            dominant_filter(&stats).unwrap(),
+            "max_pages rejected 10 of 10 candidates — consider relaxing it"
+        );
+    }
+
+    #[test]
+    fn test_relaxed_limit_rounds_outward() {
+        let tally = |limit: f64, values: &[f64]| FilterTally {
+            novels: values.len(),
+            sole_rejections: 0,
+            limit: Some(limit),
+            values: values.to_vec(),
+        };
+        assert_eq!(
+            relaxed_limit("max_pages", &tally(500.0, &[612.0, 740.2, 900.0])),
+            Some(741.0)
+        );
+        assert_eq!(
+            relaxed_limit("min_words", &tally(1e5, &[5e4, 8.2e4])),
+            Some(82000.0)
+        );
+        assert_eq!(relaxed_limit("min_rating", &tally(4.5, &[4.47])), Some(4.4));
+        assert_eq!(relaxed_limit("min_rating", &tally(4.5, &[])), None);
+    }
+
+    #[test]
+    fn test_queue_ran_dry() {
+        let mut stats = RunStats::default();
+        assert_eq!(queue_ran_dry(&stats), None);
+        stats.queue_ran_dry = true;
+        assert!(queue_ran_dry(&stats)
+            .unwrap()
+            .contains("discovery_enabled = true"));
+        stats.record(Phase::Discovery, Duration::ZERO, None);
+        assert!(queue_ran_dry(&stats)
+            .unwrap()
+            .contains("discovery_min_score"));
+    }
+
+    #[test]
+    fn test_discovery_exhausted() {
+        let mut stats = RunStats::default();
+        for _ in 0..MIN_FRUITLESS_DISCOVERIES {
+            assert_eq!(discovery_exhausted(&stats), None);
+            stats.record(Phase::Discovery, Duration::ZERO, None);
+        }
+        assert!(discovery_exhausted(&stats)
+            .unwrap()
+            .contains("already seen"));
+        stats.novels_discovered = 1;
+        assert_eq!(discovery_exhausted(&stats), None);
+    }
+
+    #[test]
+    fn test_challenged_over_budget_and_timed_out() {
+        let mut stats = RunStats::default();
+        assert_eq!(challenged(&stats), None);
+        assert_eq!(over_budget(&stats), None);
+        assert_eq!(timed_out(&stats), None);
+
+        stats.novels_challenged = 2;
+        stats.novels_over_budget = vec![1, 2, 3];
+        stats.novels_timed_out = vec![4];
+        assert!(challenged(&stats).unwrap().starts_with("2 novels"));
+        assert!(over_budget(&stats)
+            .unwrap()
+            .contains("max_requests_per_novel"));
+        assert!(timed_out(&stats).unwrap().contains("max_seconds_per_novel"));
+    }
+
+    #[test]
+    fn test_suggestions_keep_the_first_few() {
+        assert!(suggestions(&RunStats::default()).is_empty());
+
+        let stats = RunStats {
+            queue_ran_dry: true,
+            novels_challenged: 1,
+            novels_over_budget: vec![1],
+            novels_timed_out: vec![2],
+            ..RunStats::default()
+        };
+        let hints = suggestions(&stats);
+        assert_eq!(hints.len(), MAX_HINTS);
+        assert!(hints[0].contains("queue ran out"), "{:?}", hints);
+        assert!(hints[2].contains("max_requests_per_novel"), "{:?}", hints);
+    }
+}
@@ -0,0 +1,35 @@
+//! novel-finder: find the perfect webnovel on RoyalRoad.
+//!
+//! Evaluates novels against user-defined criteria using configurable
+//! evaluation strategies (local heuristics or LLM-based analysis) and discovers
+//! related novels through RoyalRoad's recommendation system. The
+//! `novel-finder` binary is the command-line front end.
+
+pub mod browse;
+pub mod cancel;
+pub mod clock;
+pub mod compare;
+pub mod config;
+pub mod discovery;
+pub mod eval;
+pub mod export;
+pub mod hints;
+pub mod logging;
+pub mod models;
+pub mod observer;
+pub mod output;
+pub mod persist;
+pub mod prompt;
+pub mod proxy;
+pub mod pipeline;
+pub mod queue;
+pub mod rank;
+pub mod registry;
+pub mod rejects;
+pub mod rescore;
+pub mod sampling;
+pub mod scraper;
+pub mod site;
+pub mod stats;
+pub mod util;
+pub mod watch;
@@ -0,0 +1,273 @@
+//! Logging setup: console output plus an optional per-run log file.
+//!
+//! Logs are either plain human-readable lines or one JSON object per line.
+//! The pipeline, scrapers, and evaluators run inside spans carrying
+//! `novel_id` and `phase` fields; JSON lines include them so logs can be
+//! filtered per novel, while plain lines leave them out to stay readable.
+
+use crate::config::{LogFormat, LoggingConfig};
+use crate::util;
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::filter::filter_fn;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry};
+
+/// How much is logged to the console.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Warnings and errors only, always to stderr.
+    Quiet,
+    /// Info level.
+    Normal,
+    /// Debug level.
+    Verbose,
+}
+
+/// A boxed layer over the registry.
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Start logging to the console, and to a new log file if a log directory is
+/// configured.
+///
+/// The console logs at the level `verbosity` sets, to stdout or, with
+/// `stderr` or when quiet, to stderr. The log file always gets full debug
+/// detail.
+/// Returns the path of the log file.
+pub fn init(
+    settings: &LoggingConfig,
+    verbosity: Verbosity,
+    format: LogFormat,
+    stderr: bool,
+) -> Result<Option<PathBuf>> {
+    let console_level = match verbosity {
+        Verbosity::Quiet => Level::WARN,
+        Verbosity::Normal => Level::INFO,
+        Verbosity::Verbose => Level::DEBUG,
+    };
+    let console = if stderr || verbosity == Verbosity::Quiet {
+        output_layer(format, std::io::stderr, true, console_level)
+    } else {
+        output_layer(format, std::io::stdout, true, console_level)
+    };
+    let mut layers = vec![console];
+
+    let log_path = match &settings.log_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create log directory {}", dir.display()))?;
+            let path = dir.join(log_file_name(util::now_seconds()));
+            let file = File::create(&path)
+                .with_context(|| format!("Failed to create log file {}", path.display()))?;
+            layers.push(output_layer(format, Mutex::new(file), false, Level::DEBUG));
+            Some(path)
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry().with(layers).init();
+    Ok(log_path)
+}
+
+/// A layer writing events up to `max_level` to `writer` in `format`.
+fn output_layer<W>(format: LogFormat, writer: W, ansi: bool, max_level: Level) -> BoxedLayer
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    match format {
+        // Plain output ignores spans entirely so lines look as they always
+        // have, without a span-context prefix
+        LogFormat::Plain => tracing_subscriber::fmt::layer()
+            .with_writer(writer)
+            .with_ansi(ansi)
+            .with_filter(filter_fn(move |meta| {
+                meta.is_event() && *meta.level() <= max_level
+            }))
+            .boxed(),
+        LogFormat::Json => JsonLayer { writer }
+            .with_filter(filter_fn(move |meta: &Metadata<'_>| *meta.level() <= max_level))
+            .boxed(),
+    }
+}
+
+/// The name of the log file for a run started at `seconds` since the epoch,
+/// e.g. "novel-finder-20240801-210303.log" (UTC).
+fn log_file_name(seconds: u64) -> String {
+    format!("novel-finder-{}.log", util::format_compact_timestamp(seconds))
+}
+
+/// Writes each event as one JSON object per line, with the fields of the
+/// spans it occurred in merged (innermost last) under `span`.
+struct JsonLayer<W> {
+    writer: W,
+}
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let mut fields = JsonFields::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<JsonFields>() {
+                values.record(fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let mut span_fields = Map::new();
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                if let Some(recorded) = span.extensions().get::<JsonFields>() {
+                    span_fields.extend(recorded.0.clone());
+                }
+            }
+        }
+
+        let meta = event.metadata();
+        let mut line = Map::new();
+        line.insert("timestamp".into(), util::format_rfc3339(SystemTime::now()).into());
+        line.insert("level".into(), meta.level().as_str().into());
+        line.insert("target".into(), meta.target().into());
+        if let Some(message) = fields.0.remove("message") {
+            line.insert("message".into(), message);
+        }
+        if !fields.0.is_empty() {
+            line.insert("fields".into(), Value::Object(fields.0));
+        }
+        if !span_fields.is_empty() {
+            line.insert("span".into(), Value::Object(span_fields));
+        }
+
+        let mut text = Value::Object(line).to_string();
+        text.push('\n');
+        // Logging must never fail the program, so write errors are dropped
+        let _ = self.writer.make_writer().write_all(text.as_bytes());
+    }
+}
+
+/// Field values recorded from a span or event, as JSON.
+#[derive(Debug, Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// A writer collecting output in memory.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Log a message inside novel and phase spans, returning the output.
+    fn capture(format: LogFormat) -> String {
+        let buffer = Buffer::default();
+        let layer = output_layer(format, buffer.clone(), false, Level::INFO);
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let _novel = tracing::info_span!("novel", novel_id = 90435_u64).entered();
+            let _phase = tracing::info_span!("phase", phase = "scrape").entered();
+            tracing::info!(pages = 391, "Scraped novel");
+            tracing::debug!("Not shown at info level");
+        });
+        let bytes = buffer.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_json_lines_carry_span_fields() {
+        let output = capture(LogFormat::Json);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 1);
+
+        let line: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["message"], "Scraped novel");
+        assert_eq!(line["fields"]["pages"], 391);
+        assert_eq!(line["span"]["novel_id"], 90435);
+        assert_eq!(line["span"]["phase"], "scrape");
+        assert!(line["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+
+    #[test]
+    fn test_plain_lines_omit_spans() {
+        let output = capture(LogFormat::Plain);
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.contains("INFO"), "{}", output);
+        assert!(output.contains("Scraped novel pages=391"), "{}", output);
+        assert!(!output.contains("novel_id"), "{}", output);
+    }
+
+    #[test]
+    fn test_log_file_name() {
+        assert_eq!(log_file_name(1_722_546_183), "novel-finder-20240801-210303.log");
+    }
+}
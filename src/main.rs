@@ -1,57 +1,823 @@
 //! novel-finder: A CLI tool to find the perfect webnovel on RoyalRoad.
 //!
-//! This tool evaluates novels against user-defined criteria using configurable
-//! evaluation strategies (local heuristics or LLM-based analysis) and discovers
-//! related novels through RoyalRoad's recommendation system.
-
-mod config;
-mod discovery;
-mod eval;
-mod models;
-mod output;
-mod pipeline;
-mod queue;
-mod scraper;
-
-use anyhow::Result;
-use clap::Parser;
+//! The command-line front end; the search itself lives in the library crate.
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use novel_finder::cancel::CancellationToken;
+use novel_finder::config::secrets::{self, KeyringEntry};
+use novel_finder::config::GroupBy;
+use novel_finder::config::validate::{validate_config, validate_names, ConfigWarning};
+use novel_finder::logging::{self, Verbosity};
+use novel_finder::models::timestamp::Timestamp;
+use novel_finder::models::NovelScore;
+use novel_finder::registry::Registry;
+use novel_finder::scraper::cache::ResponseCache;
+use novel_finder::stats::RunMetadata;
+use novel_finder::{
+    browse, cancel, compare, config, export, hints, output, persist, pipeline, prompt, queue,
+    rank, rescore, util, watch,
+};
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// The exit statuses, for `--help`.
+const EXIT_STATUS_HELP: &str = "\
+Exit status:
+  0  At least one novel scored at least min_score (any result, without min_score)
+  1  The run failed with an error
+  2  The run completed but no novel matched";
 
 /// Find the perfect webnovel on RoyalRoad.
 #[derive(Parser, Debug)]
-#[command(name = "novel-finder", version, about)]
+#[command(
+    name = "novel-finder",
+    version,
+    about,
+    subcommand_negates_reqs = true,
+    after_help = EXIT_STATUS_HELP
+)]
 struct Cli {
     /// Path to the configuration TOML file.
-    #[arg(short, long)]
-    config: PathBuf,
+    #[arg(short, long, required = true)]
+    config: Option<PathBuf>,
 
     /// Enable verbose/debug logging output.
     #[arg(short, long, default_value_t = false)]
     verbose: bool,
+
+    /// Print only the results: logs go to stderr and only warnings and
+    /// errors are logged. Useful in scripts, with the exit status.
+    #[arg(short, long, default_value_t = false, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Don't suggest config changes after the run statistics.
+    #[arg(long, default_value_t = false)]
+    no_hints: bool,
+
+    /// Print results and run statistics as JSON instead of tables. Console
+    /// logs go to stderr so stdout stays valid JSON.
+    #[arg(long, default_value_t = false)]
+    json: bool,
+
+    /// Compare the results with a previous run's `--json` output and print
+    /// what changed.
+    #[arg(long, value_name = "PREVIOUS_RESULTS")]
+    compare: Option<PathBuf>,
+
+    /// Also export the results scoring at least `min_score` (under [output]) in
+    /// this format; requires --output.
+    #[arg(long, value_enum, requires = "output")]
+    export: Option<export::ExportFormat>,
+
+    /// File to write the export to.
+    #[arg(long, requires = "export")]
+    output: Option<PathBuf>,
+
+    /// Start the export with `#`-prefixed lines describing the run: version,
+    /// criteria, evaluator, seeds, and so on. Not every importer skips them.
+    #[arg(long, default_value_t = false, requires = "export")]
+    export_metadata: bool,
+
+    /// Append results scoring at least `min_score` (under [output]) to this
+    /// Markdown checklist, skipping novels already on it.
+    #[arg(long, value_name = "PATH")]
+    reading_list: Option<PathBuf>,
+
+    /// Write the results into this Atom feed, keeping entries from earlier
+    /// runs.
+    #[arg(long, value_name = "PATH")]
+    feed: Option<PathBuf>,
+
+    /// Write the results as a self-contained HTML page that sorts and
+    /// filters in the browser.
+    #[arg(long, value_name = "PATH")]
+    html: Option<PathBuf>,
+
+    /// Open the top N novels in the web browser after printing the results,
+    /// asking first when N is over 10. In the interactive browser, press `o`
+    /// instead.
+    #[arg(long, value_name = "N", conflicts_with_all = ["interactive", "watch"])]
+    open: Option<usize>,
+
+    /// Download the results' cover images into this directory, skipping
+    /// covers already there.
+    #[arg(long, value_name = "DIR", conflicts_with = "watch")]
+    download_covers: Option<PathBuf>,
+
+    /// Write the novels still queued when the run stops to this file, one
+    /// ID per line, for a later run with `[seeds] source = "file"`.
+    #[arg(long, value_name = "PATH", conflicts_with = "watch")]
+    export_queue: Option<PathBuf>,
+
+    /// Write every novel the run scrapes, with its reviews, to this dataset
+    /// file as the run goes, for `rescore`: JSON Lines for a `.jsonl` path,
+    /// else JSON.
+    #[arg(long, value_name = "PATH", conflicts_with = "watch")]
+    export_dataset: Option<PathBuf>,
+
+    /// Write every novel the hard filters rejected, with the reasons, to
+    /// this file as the run goes: JSON for a `.json` path, else CSV.
+    #[arg(long, value_name = "PATH", conflicts_with = "watch")]
+    rejects: Option<PathBuf>,
+
+    /// After the results, show the most recent novels the hard filters
+    /// rejected and why.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json", "watch"])]
+    show_rejects: bool,
+
+    /// Make the run reproducible: random choices use a fixed seed, timings
+    /// are left out of the stats, and the metadata carries a fixed finish
+    /// time. Time-based limits ([run] max_time, max_seconds_per_novel) are
+    /// refused.
+    #[arg(long, default_value_t = false, conflicts_with = "watch")]
+    deterministic: bool,
+
+    /// With --deterministic, the seed for random seed sampling and stratified
+    /// sampling of the queue (default: the configured rng_seed or
+    /// sampling_seed, or 0).
+    #[arg(long, value_name = "N", requires = "deterministic")]
+    seed: Option<u64>,
+
+    /// With --deterministic, the finish time written into the run metadata
+    /// (default: when the newest cached response was fetched).
+    #[arg(long, value_name = "RFC3339", requires = "deterministic")]
+    timestamp: Option<Timestamp>,
+
+    /// Report how often each tag appears, its mean score, and which tags
+    /// appear together on high-scoring novels (see [output.tag_report]).
+    #[arg(long, default_value_t = false)]
+    tag_report: bool,
+
+    /// Browse the results in a full-screen terminal UI instead of printing
+    /// the results table (needs the `interactive` feature).
+    #[arg(short, long, default_value_t = false, conflicts_with = "json")]
+    interactive: bool,
+
+    /// File the interactive browser saves marked results to on quit.
+    #[arg(long, value_name = "PATH", default_value = "marked.json")]
+    marked: PathBuf,
+
+    /// Re-run the search every interval (e.g. "1d" or "6h"), reporting only
+    /// matches no earlier run reported. Stop with Ctrl-C.
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        value_parser = config::parse_duration,
+        conflicts_with_all = ["interactive", "compare"]
+    )]
+    watch: Option<Duration>,
+
+    /// With --watch, run a single iteration and exit.
+    #[arg(long, default_value_t = false, requires = "watch")]
+    once: bool,
+
+    /// With --watch, remember the novels already reported in this file, so
+    /// they aren't reported again after a restart.
+    #[arg(long, value_name = "PATH", requires = "watch")]
+    watch_history: Option<PathBuf>,
+
+    /// Keep the run small whatever the config says: stop after N novels,
+    /// discoveries included (or sooner, at the configured stop condition),
+    /// and gather at most N seeds, so a big search isn't scraped in full.
+    /// For quick runs while trying out config changes.
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+
+    /// In LLM mode, write each evaluation's prompts and the raw API response
+    /// into this directory, named by novel, with the API key redacted. For
+    /// checking exactly what was sent when a score looks wrong.
+    #[arg(long, value_name = "DIR")]
+    audit_dir: Option<PathBuf>,
+
+    /// Log output format; overrides `format` under [logging].
+    #[arg(long, value_enum)]
+    log_format: Option<config::LogFormat>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Commands other than running a search.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage secrets stored in the OS keyring.
+    Secret {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+    /// Compare two results files written with `--json`, without running a
+    /// search.
+    Diff {
+        /// The earlier results.
+        old: PathBuf,
+        /// The later results.
+        new: PathBuf,
+    },
+    /// Browse a results file written with `--json` in the interactive
+    /// browser, without running a search.
+    Browse {
+        /// The results file.
+        results: PathBuf,
+    },
+    /// Filter and score a saved dataset of novels against the config's
+    /// criteria instead of searching, printing the results as a search
+    /// would. Local evaluation makes no network requests.
+    Rescore {
+        /// A JSON array of novels, each with optional `reviews`.
+        dataset: PathBuf,
+    },
+    /// Scrape and score specific novels, printing them ranked against each
+    /// other with a detailed breakdown of each, without discovery or a stop
+    /// condition. As with a search, exits with 2 if none passes the hard
+    /// filters (and reaches min_score, if set).
+    Rank {
+        /// RoyalRoad fiction URLs or IDs. Without any, they're read from
+        /// stdin, one per line.
+        novels: Vec<String>,
+    },
+    /// Print the prompts the LLM evaluator would send for a novel, without
+    /// calling its API, for trying out criteria wording.
+    Prompt {
+        /// A RoyalRoad fiction URL or ID.
+        novel: String,
+        /// Take the novel from this dataset (written with
+        /// `--export-dataset`) instead of scraping it.
+        #[arg(long, value_name = "PATH")]
+        dataset: Option<PathBuf>,
+        /// The criteria profile to render the prompt for; needed when the
+        /// config has profiles.
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+    },
+    /// Check the config file for errors and for settings that contradict
+    /// each other, without running a search.
+    CheckConfig,
+    /// Inspect or clean up the response cache in the config's
+    /// `[scraper] cache_dir`.
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+/// Response cache operations.
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// Show the entries, size, and ages of each kind of cached response.
+    Stats,
+    /// Delete cached responses written longer ago than a duration.
+    Prune {
+        /// How old an entry must be to go, like "30d" or "12h".
+        #[arg(long, value_parser = config::parse_duration)]
+        older_than: Duration,
+    },
+    /// Delete every cached response.
+    Clear,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Keyring secret operations.
+#[derive(Subcommand, Debug)]
+enum SecretAction {
+    /// Store a secret (read from stdin) under a keyring entry such as
+    /// "novel-finder/anthropic", for use as `api_key = "keyring:<name>"`
+    /// under `[eval.llm]`.
+    Set {
+        /// The keyring entry, as "service/account" or just "account".
+        name: String,
+    },
+}
+
+fn main() -> Result<ExitCode> {
+    let mut cli = Cli::parse();
+
+    // Rescoring a dataset or ranking novels stands in for the search; the
+    // other commands run on their own
+    let batch = match cli.command.take() {
+        Some(Command::Rescore { dataset }) => Some(Batch::Dataset(dataset)),
+        Some(Command::Rank { novels }) => Some(Batch::Novels(novels)),
+        Some(command) => return run_command(command, &cli),
+        None => None,
+    };
+    let config_path = cli.config.clone().context("--config is required")?;
 
-    // Initialize logging
-    let log_level = if cli.verbose { "debug" } else { "info" };
-    tracing_subscriber::fmt()
-        .with_env_filter(log_level)
-        .init();
+    // Initialize logging; command-line flags override the config file
+    let logging_config = config::load_logging_config(&config_path);
+    let log_path = logging::init(
+        &logging_config,
+        verbosity(cli.quiet, cli.verbose || logging_config.verbose),
+        cli.log_format.unwrap_or(logging_config.format),
+        cli.json,
+    )?;
 
     tracing::info!("novel-finder starting up");
-    tracing::debug!("Config path: {}", cli.config.display());
+    if let Some(log_path) = log_path {
+        tracing::info!("Writing log to {}", log_path.display());
+    }
+    tracing::debug!("Config path: {}", config_path.display());
 
     // Load configuration
-    let app_config = config::load_config(&cli.config)?;
+    let mut app_config = config::load_config(&config_path)?;
     tracing::info!("Configuration loaded successfully");
+    if let Some(limit) = cli.limit {
+        app_config.limit_novels(limit);
+        tracing::info!("Limiting the run to {} novels (--limit)", limit);
+    }
+    if let Some(dir) = &cli.audit_dir {
+        app_config.audit_llm_to(dir)?;
+        tracing::info!("Writing LLM prompts and responses to {}", dir.display());
+    }
+    check_conflicts(&app_config)?;
+    let min_score = app_config.output.min_score;
+    let tag_settings = app_config.output.tag_report.clone();
+    let full_novel = app_config.output.include_full_novel;
+    let browser_command = app_config.output.browser_command.clone();
+    let browser_command = browser_command.as_deref();
+    let group_by = app_config.output.group_by;
+
+    if let Some(batch) = &batch {
+        let scraping_flags = [
+            ("--watch", cli.watch.is_some()),
+            ("--deterministic", cli.deterministic),
+            ("--download-covers", cli.download_covers.is_some()),
+            ("--export-queue", cli.export_queue.is_some()),
+            ("--export-dataset", cli.export_dataset.is_some()),
+            ("--rejects", cli.rejects.is_some()),
+            ("--show-rejects", cli.show_rejects),
+            ("--limit", cli.limit.is_some()),
+        ];
+        if let Some((flag, _)) = scraping_flags.iter().find(|(_, set)| *set) {
+            anyhow::bail!("{} can't be used with {}", flag, batch.command());
+        }
+    }
+
+    // Ctrl-C stops a run after the novel under way, keeping the results so
+    // far; a second Ctrl-C quits at once
+    let cancel = CancellationToken::new();
 
-    // Build and run the pipeline
-    let mut pipeline = pipeline::Pipeline::new(app_config)?;
-    let results = pipeline.run()?;
+    if let Some(interval) = cli.watch {
+        let mut history = watch::WatchHistory::load(cli.watch_history.as_deref())?;
+        let mut status = output::EXIT_NO_MATCHES;
+        cancel::cancel_on_interrupt(&cancel);
+        watch::run(&app_config, interval, cli.once, &mut history, &cancel, |iteration| {
+            status = output::exit_status(&iteration.new_matches, min_score);
+            write_result_files(
+                &cli,
+                iteration.results,
+                &iteration.metadata,
+                min_score,
+                group_by,
+            )?;
+            let tag_report = cli
+                .tag_report
+                .then(|| output::analysis::tag_report(iteration.results, &tag_settings));
+            if cli.json {
+                output::print_json(
+                    &iteration.new_matches,
+                    full_novel,
+                    iteration.stats,
+                    &iteration.metadata,
+                    None,
+                    tag_report.as_ref(),
+                )
+            } else {
+                if !cli.quiet {
+                    println!(
+                        "\n=== Watch iteration {}: {} new matches ===",
+                        iteration.number,
+                        iteration.new_matches.len()
+                    );
+                }
+                if !iteration.new_matches.is_empty() {
+                    output::print_results(&iteration.new_matches, cli.quiet, group_by);
+                }
+                if let Some(report) = &tag_report {
+                    output::analysis::print_tag_report(report);
+                }
+                if !cli.quiet {
+                    output::print_run_stats(iteration.stats);
+                }
+                Ok(())
+            }
+        })?;
+        // A continuous watch ends with Ctrl-C, which is a normal stop
+        return Ok(if cli.once { ExitCode::from(status) } else { ExitCode::SUCCESS });
+    }
+
+    // Read the previous results up front so a bad path fails before the run
+    let previous = cli.compare.as_deref().map(compare::load_run).transpose()?;
+
+    // Build and run the pipeline, or score the batch instead
+    let mut pipeline = None;
+    let (results, stats, metadata) = match &batch {
+        Some(Batch::Dataset(path)) => {
+            let rescored = rescore::rescore(&app_config, path)?;
+            (rescored.results, rescored.stats, rescored.metadata)
+        }
+        Some(Batch::Novels(novels)) => {
+            let novels = if novels.is_empty() { read_novels_from_stdin()? } else { novels.clone() };
+            let ranked = rank::rank(&app_config, &novels)?;
+            (ranked.results, ranked.stats, ranked.metadata)
+        }
+        None => {
+            let mut run = pipeline::Pipeline::new(app_config)?;
+            cancel::cancel_on_interrupt(&cancel);
+            run.cancel_with(cancel.clone());
+            if cli.deterministic {
+                run.make_deterministic(&pipeline::Deterministic {
+                    seed: cli.seed,
+                    timestamp: cli.timestamp,
+                })?;
+            }
+            if let Some(path) = &cli.rejects {
+                run.log_rejects_to(path)?;
+            }
+            if let Some(path) = &cli.export_dataset {
+                run.export_dataset_to(path)?;
+            }
+            let results = run.run()?;
+            let metadata = run.run_metadata();
+            let stats = run.stats().clone();
+            pipeline = Some(run);
+            (results, stats, metadata)
+        }
+    };
+
+    write_result_files(&cli, &results, &metadata, min_score, group_by)?;
+    if let Some(pipeline) = &pipeline {
+        if let Some(dir) = &cli.download_covers {
+            let count = pipeline.download_covers(&results, dir)?;
+            tracing::info!("Downloaded {} covers to {}", count, dir.display());
+        }
+        if let Some(path) = &cli.export_queue {
+            let count = pipeline.export_queue(&results, path)?;
+            tracing::info!("Wrote {} queued novels to {}", count, path.display());
+        }
+    }
+
+    let comparison = previous.map(|previous| {
+        let current = persist::SavedRun {
+            results: results.clone(),
+            criteria_hash: Some(metadata.criteria_hash.clone()),
+            metadata: Some(metadata.clone()),
+        };
+        compare::diff_runs(&previous, &current)
+    });
+    let tag_report = cli
+        .tag_report
+        .then(|| output::analysis::tag_report(&results, &tag_settings));
 
     // Output results
-    output::print_results(&results);
+    if cli.json {
+        output::print_json(
+            &results,
+            full_novel,
+            &stats,
+            &metadata,
+            comparison.as_ref(),
+            tag_report.as_ref(),
+        )?;
+    } else {
+        if cli.interactive {
+            browse::browse(&results, &cli.marked, browser_command, group_by)?;
+        } else {
+            output::print_results(&results, cli.quiet, group_by);
+            if !cli.quiet {
+                output::print_score_histogram(&results);
+            }
+            if matches!(batch, Some(Batch::Novels(_))) && !cli.quiet {
+                for score in &results {
+                    println!();
+                    output::print_detailed_score(score);
+                }
+            }
+        }
+        if let Some(pipeline) = pipeline.as_ref().filter(|_| cli.show_rejects) {
+            output::print_rejects(pipeline.rejects());
+        }
+        if let Some(diff) = &comparison {
+            compare::print_diff(diff);
+        }
+        if let Some(report) = &tag_report {
+            output::analysis::print_tag_report(report);
+        }
+        if !cli.quiet {
+            output::print_run_stats(&stats);
+            if !cli.no_hints {
+                output::print_hints(&hints::suggestions(&stats));
+            }
+        }
+    }
+    if let Some(count) = cli.open {
+        let opened = browse::open::open_top(&results, count, browser_command)?;
+        tracing::info!("Opened {} novels in the browser", opened);
+    }
+
+    Ok(ExitCode::from(output::exit_status(&results, min_score)))
+}
+
+/// The console log verbosity. Quiet wins over verbose logging set in the
+/// config file; the flags themselves conflict.
+fn verbosity(quiet: bool, verbose: bool) -> Verbosity {
+    if quiet {
+        Verbosity::Quiet
+    } else if verbose {
+        Verbosity::Verbose
+    } else {
+        Verbosity::Normal
+    }
+}
+
+/// Write the export, reading list, feed, and HTML report files requested on
+/// the command line. The HTML report is grouped by `group_by`.
+fn write_result_files(
+    cli: &Cli,
+    results: &[NovelScore],
+    metadata: &RunMetadata,
+    min_score: Option<f64>,
+    group_by: GroupBy,
+) -> Result<()> {
+    if let (Some(format), Some(path)) = (cli.export, &cli.output) {
+        let metadata = cli.export_metadata.then_some(metadata);
+        let count = export::export(results, format, min_score, metadata, path)?;
+        tracing::info!("Exported {} novels to {}", count, path.display());
+    }
+    if let Some(path) = &cli.reading_list {
+        let count = export::reading_list::update(results, min_score, path)?;
+        tracing::info!("Added {} novels to reading list {}", count, path.display());
+    }
+    if let Some(path) = &cli.feed {
+        let count = output::write_atom_feed(results, path, metadata)?;
+        tracing::info!("Added {} entries to feed {}", count, path.display());
+    }
+    if let Some(path) = &cli.html {
+        output::write_html_report(results, path, metadata, group_by)?;
+        tracing::info!("Wrote HTML report of {} results to {}", results.len(), path.display());
+    }
+    Ok(())
+}
 
+/// Print a comparison of two runs, as a section or as JSON.
+fn print_diff(diff: &compare::RunDiff, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(diff)?);
+    } else {
+        compare::print_diff(diff);
+    }
     Ok(())
 }
+
+/// A batch of novels scored in place of a search.
+enum Batch {
+    /// `rescore`: the novels of a saved dataset.
+    Dataset(PathBuf),
+    /// `rank`: novels named on the command line.
+    Novels(Vec<String>),
+}
+
+impl Batch {
+    /// The command that asked for the batch.
+    fn command(&self) -> &'static str {
+        match self {
+            Batch::Dataset(_) => "rescore",
+            Batch::Novels(_) => "rank",
+        }
+    }
+}
+
+/// Read the novels to rank from stdin: one ID or URL per line, ignoring
+/// blank lines and `#` comments.
+fn read_novels_from_stdin() -> Result<Vec<String>> {
+    let input = std::io::read_to_string(std::io::stdin()).context("Failed to read stdin")?;
+    let novels = queue::parse_seeds(&input);
+    if novels.is_empty() {
+        anyhow::bail!("No novels to rank: pass IDs or URLs, or pipe them in one per line");
+    }
+    Ok(novels)
+}
+
+/// Run a command other than a search, `rescore`, or `rank`.
+fn run_command(command: Command, cli: &Cli) -> Result<ExitCode> {
+    let format = cli.log_format.unwrap_or_default();
+    let verbosity = verbosity(cli.quiet, cli.verbose);
+    logging::init(&config::LoggingConfig::default(), verbosity, format, cli.json)?;
+    match command {
+        Command::Secret { action } => run_secret_command(action)?,
+        Command::Diff { old, new } => {
+            let diff = compare::diff_runs(&compare::load_run(&old)?, &compare::load_run(&new)?);
+            print_diff(&diff, cli.json)?;
+        }
+        Command::Browse { results } => {
+            let results = compare::load_run(&results)?.results;
+            browse::browse(&results, &cli.marked, None, GroupBy::None)?;
+        }
+        Command::Cache { action } => run_cache_command(action, cli)?,
+        Command::Prompt {
+            novel,
+            dataset,
+            profile,
+        } => {
+            let path = cli.config.as_deref().context("--config is required")?;
+            let app_config = config::load_config(path)?;
+            let dataset = dataset.as_deref().map(rescore::load_dataset).transpose()?;
+            let client = pipeline::build_client(&app_config)?;
+            let (novel, reviews) =
+                prompt::load_novel(&app_config, &client, &novel, dataset.as_ref())?;
+            let prompts =
+                prompt::render_prompts(&app_config, &novel, &reviews, profile.as_deref())?;
+            println!("{}", prompts);
+        }
+        Command::CheckConfig => {
+            let path = cli.config.as_deref().context("--config is required")?;
+            let warnings = config_problems(&config::load_config(path)?);
+            for warning in &warnings {
+                println!("{}: {}", warning.severity, warning);
+            }
+            let errors = warnings.iter().filter(|w| w.is_error()).count();
+            if errors > 0 {
+                anyhow::bail!("{} config error(s) would stop or spoil a run", errors);
+            }
+            if warnings.is_empty() {
+                println!("{} looks good", path.display());
+            }
+        }
+        Command::Rescore { .. } | Command::Rank { .. } => {
+            unreachable!("{:?} runs in place of a search", command)
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Conflicting settings and names nothing is registered under, errors
+/// first. The CLI only has the built-in evaluators and discovery sources.
+fn config_problems(config: &config::AppConfig) -> Vec<ConfigWarning> {
+    let mut warnings = validate_names(config, &Registry::default());
+    warnings.extend(validate_config(config));
+    warnings.sort_by_key(|warning| !warning.is_error());
+    warnings
+}
+
+/// Log suspicious combinations of settings, and fail on contradictions
+/// that would leave a run with nothing to find or on unknown names.
+fn check_conflicts(config: &config::AppConfig) -> Result<()> {
+    let mut errors = Vec::new();
+    for warning in config_problems(config) {
+        if warning.is_error() {
+            errors.push(warning.to_string());
+        } else {
+            tracing::warn!("{}", warning);
+        }
+    }
+    if !errors.is_empty() {
+        anyhow::bail!("Invalid config settings:\n  {}", errors.join("\n  "));
+    }
+    Ok(())
+}
+
+/// Run a `cache` subcommand on the configured cache directory.
+fn run_cache_command(action: CacheAction, cli: &Cli) -> Result<()> {
+    let path = cli.config.as_deref().context("--config is required")?;
+    let scraper = config::load_config(path)?.scraper;
+    let dir = scraper
+        .cache_dir
+        .with_context(|| format!("{} sets no [scraper] cache_dir", path.display()))?;
+    let cache = ResponseCache::new(dir, scraper.cache_ttl);
+    match action {
+        CacheAction::Stats => output::print_cache_stats(cache.dir(), &cache.stats()?),
+        CacheAction::Prune { older_than } => {
+            let deleted = cache.prune(older_than)?;
+            println!(
+                "Deleted {} cached responses older than {}",
+                deleted,
+                util::format_duration(older_than)
+            );
+        }
+        CacheAction::Clear => {
+            let deleted = cache.clear()?;
+            println!("Deleted {} cached responses", deleted);
+        }
+    }
+    Ok(())
+}
+
+/// Run a `secret` subcommand.
+fn run_secret_command(action: SecretAction) -> Result<()> {
+    match action {
+        SecretAction::Set { name } => {
+            let entry = KeyringEntry::parse(&name)?;
+            let secret = secrets::read_secret(&format!("Secret for {}: ", entry))?;
+            secrets::store(&entry, &secret)?;
+            println!("Stored {}; reference it as \"keyring:{}\"", entry, entry);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_flag() {
+        let cli = Cli::try_parse_from(["novel-finder", "-c", "c.toml", "--quiet"]).unwrap();
+        assert_eq!(verbosity(cli.quiet, true), Verbosity::Quiet);
+        assert!(Cli::try_parse_from(["novel-finder", "-c", "c.toml", "-q", "-v"]).is_err());
+    }
+
+    #[test]
+    fn test_deterministic_flags() {
+        let cli = Cli::try_parse_from([
+            "novel-finder",
+            "-c",
+            "c.toml",
+            "--deterministic",
+            "--seed",
+            "7",
+            "--timestamp",
+            "2025-01-07T10:09:50Z",
+        ])
+        .unwrap();
+        assert_eq!(cli.seed, Some(7));
+        assert_eq!(cli.timestamp.map(|t| t.to_string()).as_deref(), Some("2025-01-07T10:09:50Z"));
+        assert!(Cli::try_parse_from(["novel-finder", "-c", "c.toml", "--seed", "7"]).is_err());
+        assert!(Cli::try_parse_from(["novel-finder", "-c", "c.toml", "--timestamp", "x"]).is_err());
+    }
+
+    #[test]
+    fn test_rescore_command() {
+        let args = ["novel-finder", "-c", "c.toml", "--json", "rescore", "novels.json"];
+        let cli = Cli::try_parse_from(args).unwrap();
+        assert!(cli.json);
+        match cli.command {
+            Some(Command::Rescore { dataset }) => assert_eq!(dataset, PathBuf::from("novels.json")),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_prompt_command() {
+        let args = ["novel-finder", "-c", "c.toml", "prompt", "90435", "--profile", "cozy"];
+        match Cli::try_parse_from(args).unwrap().command {
+            Some(Command::Prompt {
+                novel,
+                dataset,
+                profile,
+            }) => {
+                assert_eq!(novel, "90435");
+                assert_eq!(dataset, None);
+                assert_eq!(profile.as_deref(), Some("cozy"));
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_config_command() {
+        let cli = Cli::try_parse_from(["novel-finder", "-c", "c.toml", "check-config"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::CheckConfig)));
+    }
+
+    #[test]
+    fn test_rank_command() {
+        let args = ["novel-finder", "-c", "c.toml", "rank", "90435", "89877"];
+        match Cli::try_parse_from(args).unwrap().command {
+            Some(Command::Rank { novels }) => assert_eq!(novels, ["90435", "89877"]),
+            other => panic!("{:?}", other),
+        }
+        let stdin = Cli::try_parse_from(["novel-finder", "-c", "c.toml", "rank"]).unwrap();
+        assert!(matches!(stdin.command, Some(Command::Rank { novels }) if novels.is_empty()));
+    }
+
+    #[test]
+    fn test_cache_command() {
+        let args = ["novel-finder", "-c", "c.toml", "cache", "prune", "--older-than", "30d"];
+        match Cli::try_parse_from(args).unwrap().command {
+            Some(Command::Cache {
+                action: CacheAction::Prune { older_than },
+            }) => assert_eq!(older_than, Duration::from_secs(30 * 24 * 60 * 60)),
+            other => panic!("{:?}", other),
+        }
+        let bad = ["novel-finder", "-c", "c.toml", "cache", "prune", "--older-than", "soon"];
+        assert!(Cli::try_parse_from(bad).is_err());
+    }
+
+    #[test]
+    fn test_rejects_flags() {
+        let cli = Cli::try_parse_from([
+            "novel-finder",
+            "-c",
+            "c.toml",
+            "--rejects",
+            "rejects.csv",
+            "--show-rejects",
+        ])
+        .unwrap();
+        assert_eq!(cli.rejects, Some(PathBuf::from("rejects.csv")));
+        assert!(cli.show_rejects);
+        assert!(
+            Cli::try_parse_from(["novel-finder", "-c", "c.toml", "--show-rejects", "--json"])
+                .is_err()
+        );
+    }
+}
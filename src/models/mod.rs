@@ -0,0 +1,1164 @@
+//! Core data models for the novel-finder application.
+
+pub mod sub_scores;
+pub mod tags;
+pub mod timestamp;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+use timestamp::Timestamp;
+
+/// The publication status of a novel on RoyalRoad.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NovelStatus {
+    Ongoing,
+    Completed,
+    Hiatus,
+    Dropped,
+    Stub,
+    /// A status label the scraper doesn't recognize, as shown on the page
+    /// (empty if the page had none).
+    Unknown(String),
+}
+
+impl std::fmt::Display for NovelStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NovelStatus::Ongoing => write!(f, "Ongoing"),
+            NovelStatus::Completed => write!(f, "Completed"),
+            NovelStatus::Hiatus => write!(f, "Hiatus"),
+            NovelStatus::Dropped => write!(f, "Dropped"),
+            NovelStatus::Stub => write!(f, "Stub"),
+            NovelStatus::Unknown(label) if label.is_empty() => write!(f, "Unknown"),
+            NovelStatus::Unknown(label) => write!(f, "Unknown ({})", label),
+        }
+    }
+}
+
+/// How AI was involved in writing a novel, per RoyalRoad's content labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AiContentKind {
+    /// Written with AI assistance.
+    Assisted,
+    /// Generated by AI.
+    Generated,
+}
+
+impl std::fmt::Display for AiContentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AiContentKind::Assisted => write!(f, "AI-assisted"),
+            AiContentKind::Generated => write!(f, "AI-generated"),
+        }
+    }
+}
+
+/// A "Top X% in <metric>" badge from a fiction's page: where it ranks among
+/// all fictions on one measure, which compares across ages better than raw
+/// counts do.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Badge {
+    /// What the fiction is ranked by, as shown (e.g. "Followers").
+    pub metric: String,
+    /// The percentile from the top, e.g. 2.0 for the top 2%.
+    pub top_percent: f64,
+}
+
+impl std::fmt::Display for Badge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Top {}% in {}", self.top_percent, self.metric)
+    }
+}
+
+/// The fiction sites novels are found on.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SiteId {
+    #[default]
+    RoyalRoad,
+    ScribbleHub,
+}
+
+impl SiteId {
+    /// Every site, in the order they're listed in messages.
+    pub const ALL: [SiteId; 2] = [SiteId::RoyalRoad, SiteId::ScribbleHub];
+
+    /// The `site` config value naming it.
+    pub fn name(self) -> &'static str {
+        match self {
+            SiteId::RoyalRoad => "royalroad",
+            SiteId::ScribbleHub => "scribblehub",
+        }
+    }
+
+    /// The site named `name` in a config, if there is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|site| site.name() == name)
+    }
+
+    /// Whether the site gives a page count. Page limits can't be checked
+    /// on novels from sites that don't.
+    pub fn reports_pages(self) -> bool {
+        match self {
+            SiteId::RoyalRoad => true,
+            SiteId::ScribbleHub => false,
+        }
+    }
+}
+
+impl std::fmt::Display for SiteId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A novel's identity across sites: fiction IDs are only unique within one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NovelKey {
+    /// The site the fiction is on.
+    pub site: SiteId,
+    /// The fiction ID on that site.
+    pub id: u64,
+}
+
+impl NovelKey {
+    /// The fiction with `id` on `site`.
+    pub fn new(site: SiteId, id: u64) -> Self {
+        Self { site, id }
+    }
+}
+
+impl std::fmt::Display for NovelKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.site, self.id)
+    }
+}
+
+/// A novel from one of the fiction sites with all scraped metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Novel {
+    /// The site the novel is on. Novels saved before there was a choice of
+    /// site are RoyalRoad's.
+    #[serde(default)]
+    pub site: SiteId,
+    /// The fiction ID, unique within `site`.
+    pub id: u64,
+    /// Title of the novel.
+    pub title: String,
+    /// Author name.
+    pub author: String,
+    /// The author's RoyalRoad profile ID, if linked from the novel page.
+    /// Authors on other sites have none, as only RoyalRoad's are looked up.
+    pub author_id: Option<u64>,
+    /// The author's track record from their other fictions, if looked up.
+    pub author_reputation: Option<AuthorReputation>,
+    /// Full URL to the novel page, canonicalized to include the current
+    /// title slug when known.
+    pub url: String,
+    /// The title slug from the novel's URL (e.g. "bunny-girl-evolution"),
+    /// if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    /// URL of the novel's cover image, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover_url: Option<String>,
+    /// Novel description/blurb.
+    pub description: String,
+    /// Total page count.
+    pub pages: u64,
+    /// Overall rating (0.0 - 5.0).
+    pub rating: f64,
+    /// Current publication status.
+    pub status: NovelStatus,
+    /// Genres of the novel (e.g., Fantasy, Sci-fi), a subset of `tags`.
+    pub genres: Vec<String>,
+    /// All tags associated with the novel, including its genres.
+    pub tags: Vec<String>,
+    /// Total number of chapters, whichever titles are stored.
+    pub chapter_count: u64,
+    /// Chapter titles, all or a sample depending on `ChapterTitleStorage`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapter_titles: Vec<String>,
+    /// Chapter URLs (relative on RoyalRoad, absolute elsewhere), aligned with
+    /// `chapter_titles`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapter_urls: Vec<String>,
+    /// Publish time of the first chapter, if known.
+    pub first_chapter_date: Option<Timestamp>,
+    /// Publish time of the most recent chapter, if known.
+    pub last_chapter_date: Option<Timestamp>,
+    /// Number of followers.
+    pub followers: u64,
+    /// Number of favorites.
+    pub favorites: u64,
+    /// Total views across all chapters, if shown on the page.
+    pub total_views: Option<u64>,
+    /// Average views per chapter, if shown on the page.
+    pub average_views: Option<u64>,
+    /// Whether RoyalRoad labels the novel as fan fiction rather than original.
+    pub is_fanfiction: bool,
+    /// AI involvement label, if RoyalRoad marks the novel as AI content.
+    pub ai_content: Option<AiContentKind>,
+    /// "Top X%" popularity badges shown on the page, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub badges: Vec<Badge>,
+    /// Estimated total word count from sampled chapters, if deep scraping ran.
+    pub word_count_estimate: Option<u64>,
+    /// Length-capped text of the first chapter, if it was fetched for evaluation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_chapter_excerpt: Option<String>,
+    /// Reviews parsed from the same page fetch as the metadata, kept until
+    /// evaluation so the page isn't fetched again. `None` if not scraped.
+    #[serde(skip)]
+    pub reviews: Option<Vec<Review>>,
+    /// Fiction IDs from the page's "Others also liked" sidebar, for
+    /// discovery when the similar-fictions API fails. `None` if not scraped
+    /// or the page had no sidebar.
+    #[serde(skip)]
+    pub also_liked: Option<Vec<u64>>,
+    /// Whether the page showed reviews but none could be parsed, so
+    /// `reviews` is empty for a broken parser rather than a lack of reviews.
+    #[serde(skip)]
+    pub reviews_unparsed: bool,
+}
+
+/// An author's track record across their other fictions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorReputation {
+    /// Number of other fictions the author has published.
+    pub other_fictions: usize,
+    /// Best rating among the author's other rated fictions.
+    pub best_rating: Option<f64>,
+    /// Total followers across the author's other fictions.
+    pub total_followers: u64,
+}
+
+/// Which chapters (titles with their URLs) are kept on each novel.
+///
+/// Long serials have thousands of chapters, and every stored title is carried
+/// through the queue, the results, and the JSON output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChapterTitleStorage {
+    /// No chapters.
+    None,
+    /// The first, middle, and last `CHAPTER_SAMPLE_PER_SECTION` chapters.
+    #[default]
+    Sample,
+    /// Every chapter.
+    All,
+}
+
+/// Chapters kept from each section of the list under
+/// `ChapterTitleStorage::Sample`.
+pub const CHAPTER_SAMPLE_PER_SECTION: usize = 10;
+
+impl Novel {
+    /// The badge ranking the novel highest, if it has any.
+    pub fn best_badge(&self) -> Option<&Badge> {
+        self.badges
+            .iter()
+            .min_by(|a, b| a.top_percent.total_cmp(&b.top_percent))
+    }
+
+    /// The novel's identity across sites.
+    pub fn key(&self) -> NovelKey {
+        NovelKey::new(self.site, self.id)
+    }
+
+    /// Chapter titles that are actual story content, excluding stub notices.
+    /// Only stubs have notices; other novels keep every title.
+    pub fn content_chapter_titles(&self) -> impl Iterator<Item = &String> {
+        self.chapter_titles.iter().filter(|t| !self.is_notice(t))
+    }
+
+    /// Number of chapters that are story content rather than stub notices.
+    ///
+    /// Exact when every title is stored. With a sample, only the notices in
+    /// the sample are left out.
+    pub fn content_chapter_count(&self) -> u64 {
+        let notices = self.chapter_titles.iter().filter(|t| self.is_notice(t)).count();
+        self.chapter_count.saturating_sub(notices as u64)
+    }
+
+    /// Whether `title` is a stub notice in this novel. The pattern also
+    /// matches real chapters ("The Announcement", "Book 2"), so it's only
+    /// trusted on stubs.
+    fn is_notice(&self, title: &str) -> bool {
+        self.status == NovelStatus::Stub && is_stub_notice(title)
+    }
+
+    /// Drop the chapters `storage` doesn't keep, keeping titles and URLs
+    /// aligned. `chapter_count` is unchanged.
+    pub fn trim_chapters(&mut self, storage: ChapterTitleStorage) {
+        match storage {
+            ChapterTitleStorage::All => {}
+            ChapterTitleStorage::None => {
+                self.chapter_titles = Vec::new();
+                self.chapter_urls = Vec::new();
+            }
+            ChapterTitleStorage::Sample => {
+                let len = self.chapter_titles.len();
+                let indices = sample_indices(len, CHAPTER_SAMPLE_PER_SECTION);
+                if indices.len() < len {
+                    let urls = std::mem::take(&mut self.chapter_urls);
+                    let titles = std::mem::take(&mut self.chapter_titles);
+                    self.chapter_titles = indices.iter().map(|&i| titles[i].clone()).collect();
+                    self.chapter_urls =
+                        indices.iter().filter_map(|&i| urls.get(i).cloned()).collect();
+                }
+            }
+        }
+    }
+
+    /// Estimate the number of chapters the full story has.
+    ///
+    /// For stubs, the remaining chapter list is missing most of the story, but
+    /// the highest chapter number left in a title ("170 - In the Dungeon") still
+    /// reveals how long it was before chapters were removed.
+    pub fn estimated_full_chapter_count(&self) -> u64 {
+        let content_count = self.content_chapter_count();
+        let highest_number = self
+            .content_chapter_titles()
+            .filter_map(|t| leading_chapter_number(t))
+            .max()
+            .unwrap_or(0);
+        content_count.max(highest_number)
+    }
+
+    /// The page count, on sites that give one.
+    pub fn reported_pages(&self) -> Option<u64> {
+        self.site.reports_pages().then_some(self.pages)
+    }
+
+    /// Average pages per chapter, a proxy for chapter length.
+    ///
+    /// Returns `None` when there are no chapters or no page count, or for
+    /// stubs, whose remaining pages and chapters no longer reflect the story.
+    pub fn pages_per_chapter(&self) -> Option<f64> {
+        let no_pages = !self.site.reports_pages();
+        if self.chapter_count == 0 || no_pages || self.status == NovelStatus::Stub {
+            return None;
+        }
+        Some(self.pages as f64 / self.chapter_count as f64)
+    }
+
+    /// Followers per 1,000 average chapter views, a measure of how well the
+    /// story keeps the readers it gets. `None` without view counts.
+    pub fn followers_per_1k_views(&self) -> Option<f64> {
+        match self.average_views {
+            Some(views) if views > 0 => Some(self.followers as f64 * 1000.0 / views as f64),
+            _ => None,
+        }
+    }
+
+    /// Favorites per follower, a measure of reader enthusiasm.
+    /// `None` for fictions without followers.
+    pub fn favorites_per_follower(&self) -> Option<f64> {
+        if self.followers == 0 {
+            return None;
+        }
+        Some(self.favorites as f64 / self.followers as f64)
+    }
+
+    /// Days since the most recent chapter was published, if known.
+    pub fn days_since_update(&self) -> Option<i64> {
+        self.last_chapter_date.map(|date| date.days_ago())
+    }
+
+    /// Days since the first chapter was published, if known.
+    pub fn fiction_age_days(&self) -> Option<i64> {
+        self.first_chapter_date.map(|date| date.days_ago())
+    }
+}
+
+/// Check whether a chapter title is a stub notice rather than story content.
+///
+/// Stubbed fictions typically keep a few placeholder chapters such as
+/// "Stub Announcement", "NOT A CHAPTER - ..." or per-book store links.
+pub fn is_stub_notice(title: &str) -> bool {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let re = PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?i)\bstub\b|\bnot a chapter\b|\bannouncement\b|\bkindle\b|\bamazon\b|^[^\d]*\bbook \d+\s*$",
+        )
+        .expect("valid regex")
+    });
+    re.is_match(title)
+}
+
+/// Parse the number at the start of a chapter title like "170 - In the Dungeon".
+fn leading_chapter_number(title: &str) -> Option<u64> {
+    let trimmed = title.trim_start();
+    let trimmed = trimmed
+        .strip_prefix("Chapter")
+        .or_else(|| trimmed.strip_prefix("chapter"))
+        .unwrap_or(trimmed)
+        .trim_start();
+    let digits: String = trimmed.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// A user review of a novel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Review {
+    /// Review author username.
+    pub author: String,
+    /// Rating given by the reviewer (0.0 - 5.0).
+    pub rating: f64,
+    /// Full text of the review.
+    pub text: String,
+    /// When the review was posted, if the page gave a readable time.
+    pub posted_date: Option<Timestamp>,
+    /// How many readers marked the review helpful, when the page shows it.
+    #[serde(default)]
+    pub helpful_votes: Option<u64>,
+}
+
+/// Which of a novel's reviews evaluators see first, and so keep when they
+/// can only use some of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReviewSelection {
+    /// The order the novel's page shows them in.
+    #[default]
+    PageOrder,
+    /// Most helpful votes first; reviews without a count come last.
+    MostHelpful,
+    /// Longest text first.
+    Longest,
+    /// Most recently posted first; reviews without a time come last.
+    Newest,
+}
+
+impl ReviewSelection {
+    /// Put `reviews` in this selection's order. The sort is stable, so ties
+    /// keep their page order.
+    pub fn apply<R: Borrow<Review>>(self, reviews: &mut [R]) {
+        match self {
+            ReviewSelection::PageOrder => {}
+            ReviewSelection::MostHelpful => {
+                reviews.sort_by_key(|review| std::cmp::Reverse(review.borrow().helpful_votes))
+            }
+            ReviewSelection::Longest => {
+                reviews.sort_by_key(|review| std::cmp::Reverse(review_chars(review.borrow())))
+            }
+            ReviewSelection::Newest => {
+                reviews.sort_by_key(|review| std::cmp::Reverse(review.borrow().posted_date))
+            }
+        }
+    }
+}
+
+/// Default `ReviewPolicy::min_chars`.
+pub const DEFAULT_MIN_REVIEW_CHARS: usize = 200;
+
+/// Default `ReviewPolicy::min_kept`.
+pub const DEFAULT_MIN_REVIEWS_KEPT: usize = 3;
+
+/// Which of a novel's reviews are passed to the evaluator, and in what
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReviewPolicy {
+    /// The order reviews are passed in.
+    pub selection: ReviewSelection,
+    /// Reviews shorter than this, in characters, are left out.
+    pub min_chars: usize,
+    /// Fewest reviews to pass on. When leaving out short reviews would pass
+    /// fewer, the longest of them make up the difference.
+    pub min_kept: usize,
+}
+
+impl Default for ReviewPolicy {
+    fn default() -> Self {
+        Self {
+            selection: ReviewSelection::default(),
+            min_chars: DEFAULT_MIN_REVIEW_CHARS,
+            min_kept: DEFAULT_MIN_REVIEWS_KEPT,
+        }
+    }
+}
+
+/// The reviews `policy` passes to the evaluator, in its selection's order.
+pub fn select_reviews<'a>(reviews: &'a [Review], policy: &ReviewPolicy) -> Vec<&'a Review> {
+    let mut ordered: Vec<&Review> = reviews.iter().collect();
+    policy.selection.apply(&mut ordered);
+
+    let is_long = |review: &Review| review_chars(review) >= policy.min_chars;
+    let long = ordered.iter().filter(|review| is_long(review)).count();
+    // Make up a shortfall with the longest short reviews, earlier ones first
+    // on a tie
+    let mut short: Vec<usize> = (0..ordered.len())
+        .filter(|&index| !is_long(ordered[index]))
+        .collect();
+    short.sort_by_key(|&index| std::cmp::Reverse(review_chars(ordered[index])));
+    short.truncate(policy.min_kept.saturating_sub(long));
+
+    ordered
+        .into_iter()
+        .enumerate()
+        .filter(|(index, review)| is_long(review) || short.contains(index))
+        .map(|(_, review)| review)
+        .collect()
+}
+
+fn review_chars(review: &Review) -> usize {
+    review.text.chars().count()
+}
+
+impl std::fmt::Display for ReviewSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewSelection::PageOrder => write!(f, "page_order"),
+            ReviewSelection::MostHelpful => write!(f, "most_helpful"),
+            ReviewSelection::Longest => write!(f, "longest"),
+            ReviewSelection::Newest => write!(f, "newest"),
+        }
+    }
+}
+
+/// User-defined criteria for evaluating novels.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Criteria {
+    /// A natural language description of what the user is looking for.
+    pub prompt: Option<String>,
+    /// Minimum number of pages required.
+    pub min_pages: Option<u64>,
+    /// Maximum number of pages allowed.
+    pub max_pages: Option<u64>,
+    /// Minimum overall rating required.
+    pub min_rating: Option<f64>,
+    /// Allowed publication statuses, never empty; `None` allows every status.
+    pub allowed_statuses: Option<Vec<NovelStatus>>,
+    /// Maximum days since the first chapter was published.
+    pub max_fiction_age_days: Option<u64>,
+    /// Minimum days since the first chapter was published.
+    pub min_fiction_age_days: Option<u64>,
+    /// Status rules with qualifiers; a novel passes if it matches any rule.
+    /// Mutually exclusive with `allowed_statuses`.
+    pub status_policy: Option<Vec<StatusRule>>,
+    /// Minimum estimated word count (checked only when an estimate exists).
+    pub min_words: Option<u64>,
+    /// Maximum estimated word count (checked only when an estimate exists).
+    pub max_words: Option<u64>,
+    /// Minimum average pages per chapter.
+    pub min_pages_per_chapter: Option<f64>,
+    /// Maximum average pages per chapter.
+    pub max_pages_per_chapter: Option<f64>,
+    /// Tags that must be present on the novel.
+    pub required_tags: Option<Vec<String>>,
+    /// Tags that must NOT be present on the novel.
+    pub excluded_tags: Option<Vec<String>>,
+    /// Genres that must be present on the novel (matched against genres only).
+    pub required_genres: Option<Vec<String>>,
+    /// Genres that must NOT be present on the novel.
+    pub excluded_genres: Option<Vec<String>>,
+    /// Whether fan fiction is allowed (`None` means no constraint).
+    pub allow_fanfiction: Option<bool>,
+    /// Reject novels labelled as AI-assisted or AI-generated.
+    pub exclude_ai_content: Option<bool>,
+    /// Reject stub fictions whose chapters have mostly been removed.
+    pub exclude_stubs: Option<bool>,
+    /// For stubs, check page limits against an estimate of the full story's
+    /// length instead of the remaining page count.
+    pub estimate_stub_pages: Option<bool>,
+    /// Evaluate novels that miss numeric filters by a little, with a score
+    /// penalty, instead of rejecting them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub soft_filters: Option<SoftFilters>,
+}
+
+/// How far a novel may miss the numeric filters and still be evaluated,
+/// when soft filters are on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SoftFilters {
+    /// Rating points a novel may fall below `min_rating`.
+    pub rating_tolerance: f64,
+    /// Fraction of the limit a novel may miss the page, word, chapter
+    /// length, and age limits by (0.15 for 15%).
+    pub size_tolerance: f64,
+}
+
+impl Default for SoftFilters {
+    fn default() -> Self {
+        Self {
+            rating_tolerance: 0.2,
+            size_tolerance: 0.15,
+        }
+    }
+}
+
+/// A named set of criteria, for scoring one crawl against several reading moods.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriteriaProfile {
+    /// The profile's name (e.g., "cozy").
+    pub name: String,
+    /// The criteria novels are scored against for this profile.
+    pub criteria: Criteria,
+}
+
+/// One entry of a status policy: a status that is allowed, optionally only
+/// when further qualifiers hold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusRule {
+    /// The publication status this rule applies to.
+    pub status: NovelStatus,
+    /// Only match if the latest chapter is at most this many days old.
+    pub max_days_since_update: Option<u64>,
+}
+
+/// The result of evaluating a novel against the criteria.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NovelScore {
+    /// The novel that was scored.
+    pub novel: Novel,
+    /// Overall score (0.0 - 1.0).
+    pub overall_score: f64,
+    /// Lower end of the range the score plausibly falls in, given how much
+    /// evidence it rests on. Absent from older results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_low: Option<f64>,
+    /// Upper end of the plausible range.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_high: Option<f64>,
+    /// The passages the score rests on, so it can be explained without
+    /// rereading the novel.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<Evidence>,
+    /// Breakdown of scores by criteria dimension, keyed by the names in
+    /// [`sub_scores`].
+    #[serde(deserialize_with = "sub_scores::deserialize")]
+    pub sub_scores: BTreeMap<String, f64>,
+    /// Human-readable reasoning for the score.
+    pub reasoning: String,
+    /// The criteria profile the novel was scored against, if profiles are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// The filters the novel only nearly passed, when soft filters let it
+    /// through with a penalty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub near_misses: Vec<String>,
+}
+
+impl NovelScore {
+    /// Set the score range to `half_width` either side of the overall
+    /// score, kept within 0.0 - 1.0.
+    pub fn with_half_width(mut self, half_width: f64) -> Self {
+        self.score_low = Some((self.overall_score - half_width).clamp(0.0, 1.0));
+        self.score_high = Some((self.overall_score + half_width).clamp(0.0, 1.0));
+        self
+    }
+
+    /// Half the width of the score range, if the score has one.
+    pub fn half_width(&self) -> Option<f64> {
+        Some((self.score_high? - self.score_low?) / 2.0)
+    }
+}
+
+/// Longest evidence snippet kept, in characters; longer ones are cut short.
+pub const MAX_SNIPPET_CHARS: usize = 200;
+
+/// A passage a score rests on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evidence {
+    /// Where the passage comes from.
+    pub source: EvidenceSource,
+    /// The passage, at most [`MAX_SNIPPET_CHARS`] long.
+    pub snippet: String,
+    /// How much it added to the overall score (0.0 - 1.0).
+    pub contribution: f64,
+}
+
+impl Evidence {
+    /// Evidence from `source`, with the snippet's whitespace collapsed and
+    /// its length capped.
+    pub fn new(source: EvidenceSource, snippet: &str, contribution: f64) -> Self {
+        let snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+        let snippet = if snippet.chars().count() > MAX_SNIPPET_CHARS {
+            let kept: String = snippet.chars().take(MAX_SNIPPET_CHARS - 1).collect();
+            format!("{}\u{2026}", kept)
+        } else {
+            snippet
+        };
+        Self {
+            source,
+            snippet,
+            contribution,
+        }
+    }
+}
+
+/// Where a piece of evidence comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceSource {
+    /// The novel's description.
+    Description,
+    /// A review, by the named author.
+    Review(String),
+    /// A chapter title.
+    ChapterTitle,
+    /// The novel's metadata, such as its tags.
+    Metadata,
+}
+
+impl std::fmt::Display for EvidenceSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvidenceSource::Description => write!(f, "description"),
+            EvidenceSource::Review(author) => write!(f, "review by {}", author),
+            EvidenceSource::ChapterTitle => write!(f, "chapter title"),
+            EvidenceSource::Metadata => write!(f, "metadata"),
+        }
+    }
+}
+
+/// Condition that determines when the pipeline should stop processing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StopCondition {
+    /// Stop after evaluating this many novels.
+    MaxNovels(usize),
+    /// Stop after this much time has elapsed.
+    MaxTime(Duration),
+    /// Stop when the queue is empty.
+    EmptyQueue,
+    /// Stop at whichever of these conditions is met first.
+    Any(Vec<StopCondition>),
+}
+
+impl StopCondition {
+    /// The shortest time limit among the conditions, if any sets one.
+    pub fn time_limit(&self) -> Option<Duration> {
+        match self {
+            StopCondition::MaxTime(limit) => Some(*limit),
+            StopCondition::Any(conditions) => {
+                conditions.iter().filter_map(StopCondition::time_limit).min()
+            }
+            StopCondition::MaxNovels(_) | StopCondition::EmptyQueue => None,
+        }
+    }
+
+    /// Whether the run is meant to go on until the queue is empty, so an
+    /// empty queue is an expected end rather than running dry.
+    pub fn ends_on_empty_queue(&self) -> bool {
+        match self {
+            StopCondition::EmptyQueue => true,
+            StopCondition::Any(conditions) => {
+                conditions.iter().any(StopCondition::ends_on_empty_queue)
+            }
+            StopCondition::MaxNovels(_) | StopCondition::MaxTime(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for StopCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StopCondition::MaxNovels(count) => write!(f, "after {} novels", count),
+            StopCondition::MaxTime(limit) => {
+                write!(f, "after {}", crate::util::format_duration(*limit))
+            }
+            StopCondition::EmptyQueue => write!(f, "when the queue is empty"),
+            StopCondition::Any(conditions) => {
+                let conditions: Vec<String> =
+                    conditions.iter().map(|condition| condition.to_string()).collect();
+                write!(f, "{}", conditions.join(" or "))
+            }
+        }
+    }
+}
+
+/// Total ordering used to rank scored novels, best first.
+///
+/// Sorts by overall score descending, breaking ties by rating descending,
+/// then followers descending, then fiction ID ascending so that runs over
+/// the same data always produce the same order. NaN scores sort last.
+pub fn rank_order(a: &NovelScore, b: &NovelScore) -> Ordering {
+    match (a.overall_score.is_nan(), b.overall_score.is_nan()) {
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        _ => {}
+    }
+
+    b.overall_score
+        .total_cmp(&a.overall_score)
+        .then_with(|| b.novel.rating.total_cmp(&a.novel.rating))
+        .then_with(|| b.novel.followers.cmp(&a.novel.followers))
+        .then_with(|| a.novel.id.cmp(&b.novel.id))
+}
+
+/// Pick a bounded, representative sample of chapter titles.
+///
+/// Returns the first, middle, and last `per_section` titles. Lists short
+/// enough that the sections would overlap are returned in full.
+pub fn sample_chapter_titles(titles: &[String], per_section: usize) -> Vec<&str> {
+    sample_indices(titles.len(), per_section)
+        .into_iter()
+        .map(|i| titles[i].as_str())
+        .collect()
+}
+
+/// The indices of the first, middle, and last `per_section` items of a list
+/// of `len`, or every index if the sections would overlap.
+fn sample_indices(len: usize, per_section: usize) -> Vec<usize> {
+    if len <= per_section * 3 {
+        return (0..len).collect();
+    }
+
+    let middle_start = len / 2 - per_section / 2;
+    let last_start = len - per_section;
+
+    (0..per_section)
+        .chain(middle_start..middle_start + per_section)
+        .chain(last_start..len)
+        .collect()
+}
+
+/// Build a minimal novel for unit tests.
+#[cfg(test)]
+pub(crate) fn test_novel(id: u64) -> Novel {
+    Novel {
+        site: SiteId::RoyalRoad,
+        id,
+        title: format!("Novel {}", id),
+        author: "Author".to_string(),
+        author_id: None,
+        author_reputation: None,
+        url: format!("https://www.royalroad.com/fiction/{}", id),
+        slug: None,
+        cover_url: None,
+        description: String::new(),
+        pages: 100,
+        rating: 4.0,
+        status: NovelStatus::Ongoing,
+        genres: Vec::new(),
+        tags: Vec::new(),
+        chapter_count: 10,
+        chapter_titles: Vec::new(),
+        chapter_urls: Vec::new(),
+        first_chapter_date: None,
+        last_chapter_date: None,
+        followers: 0,
+        favorites: 0,
+        total_views: None,
+        average_views: None,
+        is_fanfiction: false,
+        ai_content: None,
+        badges: Vec::new(),
+        word_count_estimate: None,
+        first_chapter_excerpt: None,
+        reviews: None,
+        also_liked: None,
+        reviews_unparsed: false,
+    }
+}
+
+/// Score a novel for unit tests, with nothing set but the overall score.
+#[cfg(test)]
+pub(crate) fn test_score(novel: Novel, overall: f64) -> NovelScore {
+    NovelScore {
+        novel,
+        overall_score: overall,
+        score_low: None,
+        score_high: None,
+        evidence: Vec::new(),
+        sub_scores: BTreeMap::new(),
+        reasoning: String::new(),
+        profile: None,
+        near_misses: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(id: u64, overall: f64, rating: f64, followers: u64) -> NovelScore {
+        let novel = Novel {
+            rating,
+            followers,
+            ..test_novel(id)
+        };
+        test_score(novel, overall)
+    }
+
+    fn ranked_ids(mut scores: Vec<NovelScore>) -> Vec<u64> {
+        scores.sort_by(rank_order);
+        scores.iter().map(|s| s.novel.id).collect()
+    }
+
+    #[test]
+    fn test_evidence_snippets_are_capped() {
+        let short = Evidence::new(EvidenceSource::Description, "  A  dungeon\nopens. ", 0.1);
+        assert_eq!(short.snippet, "A dungeon opens.");
+
+        let long = Evidence::new(EvidenceSource::ChapterTitle, &"\u{e9}".repeat(500), 0.1);
+        assert_eq!(long.snippet.chars().count(), MAX_SNIPPET_CHARS);
+        assert!(long.snippet.ends_with('\u{2026}'));
+        let exact = "x".repeat(MAX_SNIPPET_CHARS);
+        assert_eq!(Evidence::new(EvidenceSource::Metadata, &exact, 0.0).snippet, exact);
+    }
+
+    #[test]
+    fn test_rank_order_by_score() {
+        let ids = ranked_ids(vec![
+            score(1, 0.2, 4.0, 10),
+            score(2, 0.9, 4.0, 10),
+            score(3, 0.5, 4.0, 10),
+        ]);
+        assert_eq!(ids, vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_rank_order_ties_break_by_rating_then_followers_then_id() {
+        let ids = ranked_ids(vec![
+            score(4, 0.5, 4.0, 100),
+            score(3, 0.5, 4.5, 10),
+            score(2, 0.5, 4.0, 500),
+            score(1, 0.5, 4.0, 100),
+        ]);
+        assert_eq!(ids, vec![3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn test_rank_order_is_independent_of_input_order() {
+        let a = ranked_ids(vec![
+            score(1, 0.5, 4.0, 10),
+            score(2, 0.5, 4.0, 10),
+            score(3, 0.7, 3.0, 1),
+        ]);
+        let b = ranked_ids(vec![
+            score(2, 0.5, 4.0, 10),
+            score(3, 0.7, 3.0, 1),
+            score(1, 0.5, 4.0, 10),
+        ]);
+        assert_eq!(a, b);
+        assert_eq!(a, vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_rank_order_nan_sorts_last() {
+        let ids = ranked_ids(vec![
+            score(1, f64::NAN, 5.0, 1000),
+            score(2, 0.1, 3.0, 1),
+            score(3, f64::NAN, 4.0, 10),
+            score(4, 0.8, 3.0, 1),
+        ]);
+        assert_eq!(ids, vec![4, 2, 1, 3]);
+    }
+
+    fn titles(count: usize) -> Vec<String> {
+        (1..=count).map(|i| format!("Chapter {}", i)).collect()
+    }
+
+    #[test]
+    fn test_sample_chapter_titles_short_list_returned_whole() {
+        let short = titles(4);
+        assert_eq!(sample_chapter_titles(&short, 5).len(), 4);
+
+        let exact = titles(15);
+        assert_eq!(sample_chapter_titles(&exact, 5).len(), 15);
+
+        assert!(sample_chapter_titles(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_sample_chapter_titles_long_list() {
+        let long = titles(2000);
+        let sample = sample_chapter_titles(&long, 5);
+
+        assert_eq!(sample.len(), 15);
+        assert_eq!(&sample[..5], &["Chapter 1", "Chapter 2", "Chapter 3", "Chapter 4", "Chapter 5"]);
+        assert_eq!(sample[5], "Chapter 999");
+        assert_eq!(sample[9], "Chapter 1003");
+        assert_eq!(sample[14], "Chapter 2000");
+    }
+
+    #[test]
+    fn test_sample_chapter_titles_just_over_threshold() {
+        let list = titles(16);
+        let sample = sample_chapter_titles(&list, 5);
+
+        assert_eq!(sample.len(), 15);
+        // Sections never overlap
+        let mut unique = sample.clone();
+        unique.dedup();
+        assert_eq!(unique.len(), 15);
+        assert_eq!(sample[14], "Chapter 16");
+    }
+
+    #[test]
+    fn test_is_stub_notice() {
+        assert!(is_stub_notice("Stub Announcement"));
+        assert!(is_stub_notice("NOT A CHAPTER - B3 Edited .epub and .pdf available for FREE on Patreon!"));
+        assert!(is_stub_notice("Bunny Girl Evolution: Book 2"));
+        assert!(is_stub_notice("Book 1 is now on Kindle Unlimited"));
+        assert!(!is_stub_notice("1 - Rabbit"));
+        assert!(!is_stub_notice("157 - Rabbit vs Elf 1"));
+        assert!(!is_stub_notice("Chapter 12 - The Book of Names"));
+    }
+
+    #[test]
+    fn test_estimated_full_chapter_count() {
+        let mut novel = test_novel(1);
+        novel.status = NovelStatus::Stub;
+        novel.chapter_titles = vec![
+            "1 - Rabbit".to_string(),
+            "Stub Announcement".to_string(),
+            "169 - Preliminary Dungeon Competition Team".to_string(),
+            "170 - In the Dungeon Together".to_string(),
+            "Bunny Girl Evolution: Book 4".to_string(),
+        ];
+        novel.chapter_count = 5;
+        assert_eq!(novel.content_chapter_titles().count(), 3);
+        assert_eq!(novel.estimated_full_chapter_count(), 170);
+
+        novel.chapter_titles = vec!["Prologue".to_string(), "The Beginning".to_string()];
+        novel.chapter_count = 2;
+        assert_eq!(novel.estimated_full_chapter_count(), 2);
+    }
+
+    #[test]
+    fn test_notices_only_filtered_on_stubs() {
+        let mut novel = test_novel(1);
+        novel.chapter_titles = vec![
+            "1 - The Announcement".to_string(),
+            "Book 2".to_string(),
+            "3 - Lost on the Amazon".to_string(),
+        ];
+        novel.chapter_count = 3;
+        for status in [NovelStatus::Ongoing, NovelStatus::Completed] {
+            novel.status = status;
+            assert_eq!(novel.content_chapter_titles().count(), 3);
+            assert_eq!(novel.content_chapter_count(), 3);
+        }
+        novel.status = NovelStatus::Stub;
+        assert_eq!(novel.content_chapter_count(), 0);
+    }
+
+    fn long_serial(chapters: usize) -> Novel {
+        let mut novel = test_novel(1);
+        novel.chapter_titles = (1..=chapters).map(|i| format!("{} - Chapter", i)).collect();
+        novel.chapter_urls = (1..=chapters).map(|i| format!("/chapter/{}", i)).collect();
+        novel.chapter_count = chapters as u64;
+        novel
+    }
+
+    #[test]
+    fn test_trim_chapters_sample_keeps_sections_aligned() {
+        let mut novel = long_serial(2_000);
+        novel.trim_chapters(ChapterTitleStorage::Sample);
+        assert_eq!(novel.chapter_titles.len(), 3 * CHAPTER_SAMPLE_PER_SECTION);
+        assert_eq!(novel.chapter_count, 2_000);
+        assert_eq!(novel.chapter_titles[0], "1 - Chapter");
+        assert_eq!(novel.chapter_titles[10], "996 - Chapter");
+        assert_eq!(novel.chapter_titles[29], "2000 - Chapter");
+        for (title, url) in novel.chapter_titles.iter().zip(&novel.chapter_urls) {
+            assert_eq!(title.replace(" - Chapter", ""), url.replace("/chapter/", ""));
+        }
+        assert_eq!(novel.estimated_full_chapter_count(), 2_000);
+
+        // Short lists are kept whole
+        let mut short = long_serial(25);
+        short.trim_chapters(ChapterTitleStorage::Sample);
+        assert_eq!(short.chapter_titles.len(), 25);
+    }
+
+    #[test]
+    fn test_trim_chapters_none_and_all() {
+        let mut none = long_serial(50);
+        none.trim_chapters(ChapterTitleStorage::None);
+        assert!(none.chapter_titles.is_empty() && none.chapter_urls.is_empty());
+        assert_eq!(none.content_chapter_count(), 50);
+        let json = serde_json::to_value(&none).unwrap();
+        assert!(json.get("chapter_titles").is_none());
+
+        let mut all = long_serial(50);
+        all.trim_chapters(ChapterTitleStorage::All);
+        assert_eq!(all.chapter_titles.len(), 50);
+    }
+
+    #[test]
+    fn test_unknown_status_display_and_serde() {
+        let unknown = NovelStatus::Unknown("ON BREAK".to_string());
+        assert_eq!(unknown.to_string(), "Unknown (ON BREAK)");
+        assert_eq!(NovelStatus::Unknown(String::new()).to_string(), "Unknown");
+
+        let json = serde_json::to_string(&unknown).unwrap();
+        assert_eq!(json, r#"{"Unknown":"ON BREAK"}"#);
+        assert_eq!(serde_json::from_str::<NovelStatus>(&json).unwrap(), unknown);
+        // Known statuses are stored as before
+        assert_eq!(serde_json::to_string(&NovelStatus::Hiatus).unwrap(), r#""Hiatus""#);
+    }
+
+    fn review(author: &str, date: &str, text: &str, votes: Option<u64>) -> Review {
+        Review {
+            author: author.to_string(),
+            rating: 4.0,
+            text: text.to_string(),
+            posted_date: Timestamp::parse(date),
+            helpful_votes: votes,
+        }
+    }
+
+    #[test]
+    fn test_review_selection_orders() {
+        let reviews = vec![
+            review("a", "2024-03-01T00:00:00", "Short.", Some(2)),
+            review("b", "2024-05-01T00:00:00", "A much longer review.", None),
+            review("c", "2023-12-01T00:00:00", "Middling length.", Some(40)),
+            review("d", "2024-05-01T00:00:00", "Also short", Some(2)),
+        ];
+        let order = |selection: ReviewSelection| {
+            let mut reviews = reviews.clone();
+            selection.apply(&mut reviews);
+            reviews.into_iter().map(|r| r.author).collect::<Vec<_>>()
+        };
+        assert_eq!(order(ReviewSelection::PageOrder), ["a", "b", "c", "d"]);
+        // Ties keep their page order; reviews without a count come last
+        assert_eq!(order(ReviewSelection::MostHelpful), ["c", "a", "d", "b"]);
+        assert_eq!(order(ReviewSelection::Longest), ["b", "c", "d", "a"]);
+        assert_eq!(order(ReviewSelection::Newest), ["b", "d", "a", "c"]);
+    }
+
+    #[test]
+    fn test_select_reviews_drops_short_reviews() {
+        let reviews = vec![
+            review("a", "2024-01-01", "Great story 10/10", Some(9)),
+            review("b", "2024-01-02", &"Substantive. ".repeat(20), Some(1)),
+            review("c", "2024-01-03", "Fine.", None),
+            review("d", "2024-01-04", &"Also thoughtful. ".repeat(15), Some(5)),
+            review("e", "2024-01-05", "Loved the magic system!", None),
+        ];
+        let authors = |policy: ReviewPolicy| -> Vec<&str> {
+            let selected = select_reviews(&reviews, &policy);
+            selected.into_iter().map(|r| r.author.as_str()).collect()
+        };
+        let policy = |min_chars, min_kept| ReviewPolicy {
+            min_chars,
+            min_kept,
+            ..ReviewPolicy::default()
+        };
+
+        assert_eq!(authors(policy(200, 0)), ["b", "d"]);
+        assert_eq!(authors(policy(200, 2)), ["b", "d"]);
+        // Below the floor, the longest short reviews make it up, in order
+        assert_eq!(authors(policy(200, 3)), ["b", "d", "e"]);
+        assert_eq!(authors(policy(200, 4)), ["a", "b", "d", "e"]);
+        assert_eq!(authors(policy(200, 10)), ["a", "b", "c", "d", "e"]);
+        assert_eq!(authors(policy(1000, 1)), ["b"]);
+        assert_eq!(authors(policy(0, 0)).len(), 5);
+        // Filtering applies after ordering
+        let helpful = ReviewPolicy {
+            selection: ReviewSelection::MostHelpful,
+            ..policy(200, 3)
+        };
+        assert_eq!(authors(helpful), ["d", "b", "e"]);
+        assert!(select_reviews(&[], &ReviewPolicy::default()).is_empty());
+    }
+}
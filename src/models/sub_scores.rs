@@ -0,0 +1,169 @@
+//! Canonical sub-score keys.
+//!
+//! Each evaluator breaks its overall score down by criteria dimension, keyed
+//! by name in `NovelScore::sub_scores`. The local evaluator only uses the
+//! keys defined here, and the LLM evaluator asks the model to use them
+//! where they fit, so scripts reading results can rely on the names. The
+//! model may still invent its own dimensions; those are kept as they are.
+//!
+//! Keys that have been renamed are mapped to their current name when saved
+//! scores are read, so older results files and caches still load.
+
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+
+/// Fraction of the prompt's keywords found in the description.
+pub const KEYWORD_DESCRIPTION: &str = "keyword_description";
+/// Fraction of the prompt's keywords found in the reviews.
+pub const KEYWORD_REVIEWS: &str = "keyword_reviews";
+/// Fraction of the prompt's keywords found among the tags.
+pub const TAGS: &str = "tags";
+/// Fraction of the prompt's keywords found in the chapter titles.
+pub const CHAPTER_TITLES: &str = "chapter_titles";
+/// The average rating, out of 5.
+pub const RATING: &str = "rating";
+/// How close the pages per chapter are to a comfortable length.
+pub const CHAPTER_LENGTH: &str = "chapter_length";
+/// Followers, against the configured popularity saturation, or the best
+/// "Top X%" badge when popularity is read from badges.
+pub const POPULARITY: &str = "popularity";
+/// How well the author's other fictions did.
+pub const AUTHOR_REPUTATION: &str = "author_reputation";
+/// Followers per thousand views: how many readers stay.
+pub const STICKINESS: &str = "stickiness";
+/// Favorites per follower: how much readers like it.
+pub const ENTHUSIASM: &str = "enthusiasm";
+/// How readable the first chapter's writing is, as judged by an LLM.
+pub const PROSE_QUALITY: &str = "prose_quality";
+
+/// Every canonical key.
+pub const ALL: &[&str] = &[
+    KEYWORD_DESCRIPTION,
+    KEYWORD_REVIEWS,
+    TAGS,
+    CHAPTER_TITLES,
+    RATING,
+    CHAPTER_LENGTH,
+    POPULARITY,
+    AUTHOR_REPUTATION,
+    STICKINESS,
+    ENTHUSIASM,
+    PROSE_QUALITY,
+];
+
+/// Old key names and the keys that replaced them.
+const RENAMED: &[(&str, &str)] = &[
+    ("description", KEYWORD_DESCRIPTION),
+    ("reviews", KEYWORD_REVIEWS),
+];
+
+/// Whether `key` is one of the canonical keys.
+pub fn is_canonical(key: &str) -> bool {
+    ALL.contains(&key)
+}
+
+/// The current name for `key`: its replacement if it was renamed, else
+/// itself.
+pub fn canonical(key: &str) -> &str {
+    RENAMED
+        .iter()
+        .find(|(old, _)| *old == key)
+        .map_or(key, |(_, new)| new)
+}
+
+/// The key to store a sub-score named `name` under: lowercase snake case,
+/// with renamed keys mapped to their current names. For names an LLM picks.
+pub fn normalize(name: &str) -> String {
+    let snake = name
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+        .to_lowercase();
+    canonical(&snake).to_string()
+}
+
+/// Deserialize sub-scores, mapping renamed keys to their current names. A
+/// score saved under the current name wins over one under an old name.
+pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, f64>, D::Error> {
+    let saved = BTreeMap::<String, f64>::deserialize(deserializer)?;
+    let mut sub_scores = BTreeMap::new();
+    for (key, score) in &saved {
+        let current = canonical(key);
+        if current != key && saved.contains_key(current) {
+            continue;
+        }
+        sub_scores.insert(current.to_string(), *score);
+    }
+    Ok(sub_scores)
+}
+
+/// The sub-scores of a result in display order, logging any key that isn't
+/// canonical (such as a dimension an LLM made up) at debug level.
+pub fn for_display(sub_scores: &BTreeMap<String, f64>) -> impl Iterator<Item = (&str, f64)> {
+    sub_scores.iter().map(|(key, score)| {
+        if !is_canonical(key) {
+            tracing::debug!("Showing non-canonical sub-score key {:?} as is", key);
+        }
+        (key.as_str(), *score)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Saved {
+        #[serde(deserialize_with = "deserialize")]
+        sub_scores: BTreeMap<String, f64>,
+    }
+
+    fn load(json: &str) -> BTreeMap<String, f64> {
+        serde_json::from_str::<Saved>(json).unwrap().sub_scores
+    }
+
+    #[test]
+    fn test_old_names_load_as_current() {
+        let loaded =
+            load(r#"{"sub_scores": {"description": 0.5, "reviews": 0.25, "premise": 1.0}}"#);
+        assert_eq!(
+            loaded,
+            BTreeMap::from([
+                (KEYWORD_DESCRIPTION.to_string(), 0.5),
+                (KEYWORD_REVIEWS.to_string(), 0.25),
+                ("premise".to_string(), 1.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_current_name_wins_over_old() {
+        let loaded = load(r#"{"sub_scores": {"description": 0.5, "keyword_description": 0.75}}"#);
+        assert_eq!(
+            loaded,
+            BTreeMap::from([(KEYWORD_DESCRIPTION.to_string(), 0.75)])
+        );
+    }
+
+    #[test]
+    fn test_canonical_keys() {
+        assert!(ALL
+            .iter()
+            .all(|key| is_canonical(key) && canonical(key) == *key));
+        assert!(RENAMED
+            .iter()
+            .all(|(old, new)| !is_canonical(old) && is_canonical(new)));
+        assert_eq!(canonical("premise"), "premise");
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(" Prose Quality "), PROSE_QUALITY);
+        assert_eq!(normalize("world-building"), "world_building");
+        assert_eq!(normalize("Description"), KEYWORD_DESCRIPTION);
+        assert_eq!(normalize(RATING), RATING);
+    }
+}
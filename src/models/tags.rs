@@ -0,0 +1,209 @@
+//! Canonical RoyalRoad tag taxonomy.
+//!
+//! RoyalRoad has a fixed set of genres, tags, and content warnings, but they
+//! show up under several spellings: display names on novel pages ("Sci-fi"),
+//! search slugs ("sci_fi"), and whatever users type ("Science Fiction").
+//! This module maps all of those onto one canonical tag so matching works
+//! regardless of spelling.
+
+use std::fmt;
+
+/// A tag from RoyalRoad's fixed taxonomy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CanonicalTag {
+    /// The slug RoyalRoad uses in search URLs (e.g., "sci_fi").
+    pub slug: &'static str,
+    /// The display name shown on novel pages (e.g., "Sci-fi").
+    pub name: &'static str,
+    /// Which part of the taxonomy the tag belongs to.
+    pub kind: TagKind,
+}
+
+/// The part of RoyalRoad's taxonomy a tag belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TagKind {
+    /// A top-level genre such as Fantasy or Sci-fi.
+    Genre,
+    /// A descriptive tag such as Male Lead or Dungeon Crawler.
+    Tag,
+    /// A content warning such as Profanity.
+    Warning,
+}
+
+impl CanonicalTag {
+    /// Whether the tag is a genre rather than a descriptor or warning.
+    pub fn is_genre(&self) -> bool {
+        self.kind == TagKind::Genre
+    }
+}
+
+impl fmt::Display for CanonicalTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A taxonomy entry as `(slug, display name, extra aliases)`.
+type Entry = (&'static str, &'static str, &'static [&'static str]);
+
+/// RoyalRoad's genres.
+const GENRES: &[Entry] = &[
+    ("action", "Action", &[]),
+    ("adventure", "Adventure", &[]),
+    ("comedy", "Comedy", &["humor", "humour"]),
+    ("contemporary", "Contemporary", &[]),
+    ("drama", "Drama", &[]),
+    ("fantasy", "Fantasy", &[]),
+    ("historical", "Historical", &["history"]),
+    ("horror", "Horror", &[]),
+    ("mystery", "Mystery", &[]),
+    ("psychological", "Psychological", &[]),
+    ("romance", "Romance", &[]),
+    ("satire", "Satire", &[]),
+    ("sci_fi", "Sci-fi", &["science fiction"]),
+    ("one_shot", "Short Story", &["one shot"]),
+    ("tragedy", "Tragedy", &[]),
+];
+
+/// RoyalRoad's descriptive tags.
+const TAGS: &[Entry] = &[
+    ("anti-hero_lead", "Anti-Hero Lead", &["antihero"]),
+    ("artificial_intelligence", "Artificial Intelligence", &["ai"]),
+    ("attractive_lead", "Attractive Lead", &[]),
+    ("cyberpunk", "Cyberpunk", &[]),
+    ("dungeon", "Dungeon Crawler", &["dungeon"]),
+    ("dystopia", "Dystopia", &["dystopian"]),
+    ("female_lead", "Female Lead", &["female protagonist"]),
+    ("first_contact", "First Contact", &[]),
+    ("gamelit", "GameLit", &[]),
+    ("gender_bender", "Gender Bender", &[]),
+    ("genetically_engineered", "Genetically Engineered", &[]),
+    ("grimdark", "Grimdark", &[]),
+    ("hard_sci-fi", "Hard Sci-fi", &["hard science fiction"]),
+    ("harem", "Harem", &[]),
+    ("high_fantasy", "High Fantasy", &[]),
+    ("kingdom_building", "Kingdom Building", &[]),
+    ("litrpg", "LitRPG", &[]),
+    ("low_fantasy", "Low Fantasy", &[]),
+    ("magic", "Magic", &[]),
+    ("male_lead", "Male Lead", &["male protagonist"]),
+    ("martial_arts", "Martial Arts", &[]),
+    ("mecha", "Mecha", &[]),
+    ("multiple_lead", "Multiple Lead Characters", &["multiple leads", "multiple lead"]),
+    ("mythos", "Mythos", &[]),
+    ("non-human_lead", "Non-Human Lead", &["nonhuman lead"]),
+    ("summoned_hero", "Portal Fantasy / Isekai", &["isekai", "portal fantasy"]),
+    ("post_apocalyptic", "Post Apocalyptic", &[]),
+    ("progression", "Progression", &["progression fantasy"]),
+    ("reader_interactive", "Reader Interactive", &[]),
+    ("reincarnation", "Reincarnation", &[]),
+    ("ruling_class", "Ruling Class", &[]),
+    ("school_life", "School Life", &["magic school"]),
+    ("secret_identity", "Secret Identity", &[]),
+    ("slice_of_life", "Slice of Life", &[]),
+    ("soft_sci-fi", "Soft Sci-fi", &["soft science fiction"]),
+    ("space_opera", "Space Opera", &[]),
+    ("sports", "Sports", &[]),
+    ("steampunk", "Steampunk", &[]),
+    ("strategy", "Strategy", &[]),
+    ("strong_lead", "Strong Lead", &[]),
+    ("super_heroes", "Super Heroes", &["superhero", "superheroes"]),
+    ("supernatural", "Supernatural", &[]),
+    ("technologically_engineered", "Technologically Engineered", &[]),
+    ("loop", "Time Loop", &[]),
+    ("time_travel", "Time Travel", &[]),
+    ("urban_fantasy", "Urban Fantasy", &[]),
+    ("villainous_lead", "Villainous Lead", &["villain lead"]),
+    ("virtual_reality", "Virtual Reality", &["vr"]),
+    ("war_and_military", "War and Military", &["military"]),
+    ("wuxia", "Wuxia", &[]),
+    ("xianxia", "Xianxia", &["cultivation"]),
+];
+
+/// RoyalRoad's content warnings.
+const WARNINGS: &[Entry] = &[
+    ("profanity", "Profanity", &[]),
+    ("sexuality", "Sexual Content", &[]),
+    ("graphic_violence", "Graphic Violence", &["gore"]),
+    ("sensitive", "Sensitive Content", &[]),
+];
+
+/// Map any known spelling of a RoyalRoad tag onto its canonical tag.
+///
+/// Matching ignores case, spaces, and punctuation, so "Sci-fi", "sci_fi",
+/// and "SCIFI" all resolve to the same tag. Returns `None` for unknown tags.
+pub fn normalize_tag(tag: &str) -> Option<CanonicalTag> {
+    let key = fold(tag);
+    if key.is_empty() {
+        return None;
+    }
+    [
+        (GENRES, TagKind::Genre),
+        (TAGS, TagKind::Tag),
+        (WARNINGS, TagKind::Warning),
+    ]
+    .into_iter()
+    .flat_map(|(entries, kind)| entries.iter().map(move |entry| (entry, kind)))
+    .find(|((slug, name, aliases), _)| {
+        fold(slug) == key || fold(name) == key || aliases.iter().any(|a| fold(a) == key)
+    })
+    .map(|(&(slug, name, _), kind)| CanonicalTag { slug, name, kind })
+}
+
+/// A key for comparing tags: the canonical slug for known tags, otherwise
+/// the folded spelling.
+pub fn tag_key(tag: &str) -> String {
+    match normalize_tag(tag) {
+        Some(canonical) => canonical.slug.to_string(),
+        None => fold(tag),
+    }
+}
+
+/// Lowercase a tag and drop everything but letters and digits.
+fn fold(tag: &str) -> String {
+    tag.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_alias_spellings() {
+        for spelling in ["Sci-fi", "sci_fi", "Science Fiction", "SCIFI", "scifi"] {
+            assert_eq!(normalize_tag(spelling).unwrap().slug, "sci_fi", "{}", spelling);
+        }
+        for spelling in ["LitRPG", "litrpg", "Lit RPG"] {
+            assert_eq!(normalize_tag(spelling).unwrap().name, "LitRPG", "{}", spelling);
+        }
+        for spelling in ["Portal Fantasy / Isekai", "isekai", "summoned_hero"] {
+            assert_eq!(normalize_tag(spelling).unwrap().slug, "summoned_hero", "{}", spelling);
+        }
+        assert_eq!(normalize_tag("anti-hero lead").unwrap().name, "Anti-Hero Lead");
+        assert_eq!(normalize_tag("Dungeon").unwrap().name, "Dungeon Crawler");
+    }
+
+    #[test]
+    fn test_tag_kinds() {
+        assert!(normalize_tag("Sci-fi").unwrap().is_genre());
+        assert!(normalize_tag("Fantasy").unwrap().is_genre());
+        assert_eq!(normalize_tag("Male Lead").unwrap().kind, TagKind::Tag);
+        assert_eq!(normalize_tag("Profanity").unwrap().kind, TagKind::Warning);
+    }
+
+    #[test]
+    fn test_normalize_unknown_tag() {
+        assert_eq!(normalize_tag("Fantsy"), None);
+        assert_eq!(normalize_tag(""), None);
+        assert_eq!(normalize_tag(" - "), None);
+    }
+
+    #[test]
+    fn test_tag_key() {
+        assert_eq!(tag_key("Science Fiction"), tag_key("Sci-fi"));
+        assert_eq!(tag_key("Made Up Tag"), "madeuptag");
+    }
+}
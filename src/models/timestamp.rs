@@ -0,0 +1,285 @@
+//! UTC timestamps for the dates RoyalRoad attaches to chapters and reviews.
+//!
+//! RoyalRoad writes most times as RFC 3339 ("2024-08-01T21:03:03Z"), but
+//! review times come .NET style, with seven fraction digits and an optional
+//! offset ("2025-01-07T10:09:50.0000000+00:00"). Both are read, as are bare
+//! dates; times without an offset are taken as UTC. RFC 3339 is written.
+
+use crate::util;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds in one day.
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A point in time, in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    /// Whole seconds since the Unix epoch.
+    seconds: i64,
+    /// Nanoseconds past `seconds`.
+    nanos: u32,
+}
+
+impl Timestamp {
+    /// Midnight UTC at the start of the given day, counted since 1970-01-01.
+    pub fn from_days(days: i64) -> Self {
+        Self {
+            seconds: days * SECONDS_PER_DAY,
+            nanos: 0,
+        }
+    }
+
+    /// The given system time.
+    pub fn from_system_time(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => Self {
+                seconds: since.as_secs() as i64,
+                nanos: since.subsec_nanos(),
+            },
+            Err(e) => {
+                let before = e.duration();
+                let (seconds, nanos) = (-(before.as_secs() as i64), before.subsec_nanos());
+                match nanos {
+                    0 => Self { seconds, nanos },
+                    _ => Self {
+                        seconds: seconds - 1,
+                        nanos: 1_000_000_000 - nanos,
+                    },
+                }
+            }
+        }
+    }
+
+    /// The current time.
+    pub fn now() -> Self {
+        Self::from_system_time(SystemTime::now())
+    }
+
+    /// Parse an RFC 3339 timestamp, RoyalRoad's seven-fraction-digit form of
+    /// one (with or without an offset), or a bare `YYYY-MM-DD` date.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let days = util::parse_date_days(s)?;
+        let rest = s.get(10..)?;
+        if rest.is_empty() {
+            return Some(Self::from_days(days));
+        }
+
+        let rest = rest.strip_prefix(['T', 't', ' '])?;
+        let (time, rest) = rest.split_at_checked(8)?;
+        let mut fields = time.split(':').map(|field| {
+            let valid = field.len() == 2 && field.bytes().all(|b| b.is_ascii_digit());
+            valid.then(|| field.parse::<i64>().ok()).flatten()
+        });
+        let (hour, minute, second) = (fields.next()??, fields.next()??, fields.next()??);
+        // A leap second is read as the second before it
+        if hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+
+        let (nanos, offset) = match rest.strip_prefix('.') {
+            Some(fraction) => {
+                let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+                if digits == 0 {
+                    return None;
+                }
+                // Digits past nanoseconds are dropped
+                let kept = &fraction[..digits.min(9)];
+                let nanos = kept.parse::<u32>().ok()? * 10u32.pow(9 - kept.len() as u32);
+                (nanos, &fraction[digits..])
+            }
+            None => (0, rest),
+        };
+        let offset_seconds = match offset {
+            "" | "Z" | "z" => 0,
+            _ => {
+                let sign = match offset.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return None,
+                };
+                let (hours, minutes) = offset[1..].split_once(':')?;
+                if hours.len() != 2 || minutes.len() != 2 {
+                    return None;
+                }
+                let (hours, minutes) = (hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?);
+                if hours > 23 || minutes > 59 {
+                    return None;
+                }
+                sign * (hours * 3600 + minutes * 60)
+            }
+        };
+
+        let time_of_day = hour * 3600 + minute * 60 + second.min(59);
+        Some(Self {
+            seconds: days * SECONDS_PER_DAY + time_of_day - offset_seconds,
+            nanos,
+        })
+    }
+
+    /// The day this falls on, counted since 1970-01-01.
+    pub fn days(&self) -> i64 {
+        self.seconds.div_euclid(SECONDS_PER_DAY)
+    }
+
+    /// Whole days between this and today.
+    pub fn days_ago(&self) -> i64 {
+        util::today_days() - self.days()
+    }
+
+    /// The date part, as `YYYY-MM-DD`.
+    pub fn date(&self) -> String {
+        util::format_date_days(self.days())
+    }
+}
+
+/// Formats as RFC 3339 in UTC, with a fraction only when there is one.
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time_of_day = self.seconds.rem_euclid(SECONDS_PER_DAY);
+        write!(
+            f,
+            "{}T{:02}:{:02}:{:02}",
+            self.date(),
+            time_of_day / 3600,
+            time_of_day % 3600 / 60,
+            time_of_day % 60
+        )?;
+        if self.nanos > 0 {
+            let fraction = format!("{:09}", self.nanos);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        write!(f, "Z")
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Timestamp::parse(s).ok_or_else(|| anyhow::anyhow!("invalid timestamp: {:?}", s))
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Timestamp::parse(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp: {:?}", s)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> String {
+        Timestamp::parse(s)
+            .map(|t| t.to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_parse_royalroad_seven_digit_fractions() {
+        assert_eq!(parse("2025-01-07T10:09:50.0000000"), "2025-01-07T10:09:50Z");
+        assert_eq!(
+            parse("2024-08-01T21:03:03.0000000+00:00"),
+            "2024-08-01T21:03:03Z"
+        );
+        assert_eq!(
+            parse("2024-08-01T21:03:03.1234567"),
+            "2024-08-01T21:03:03.1234567Z"
+        );
+        assert_eq!(
+            parse("2024-08-01T21:03:03.5000000-02:30"),
+            "2024-08-01T23:33:03.5Z"
+        );
+        assert_eq!(
+            parse("2024-08-01T21:03:03.1234567891"),
+            "2024-08-01T21:03:03.123456789Z"
+        );
+    }
+
+    #[test]
+    fn test_parse_rfc3339_and_dates() {
+        assert_eq!(parse("2024-08-01T21:03:03Z"), "2024-08-01T21:03:03Z");
+        assert_eq!(parse(" 2024-08-01t21:03:03z "), "2024-08-01T21:03:03Z");
+        assert_eq!(parse("2024-08-01 21:03:03+01:00"), "2024-08-01T20:03:03Z");
+        // The offset can move the time across midnight
+        assert_eq!(parse("2024-01-01T00:30:00+01:00"), "2023-12-31T23:30:00Z");
+        assert_eq!(parse("2016-12-31T23:59:60Z"), "2016-12-31T23:59:59Z");
+        assert_eq!(parse("2024-08-01"), "2024-08-01T00:00:00Z");
+        assert_eq!(parse("1969-12-31T12:00:00Z"), "1969-12-31T12:00:00Z");
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        for s in [
+            "",
+            "yesterday",
+            "2024-13-01",
+            "2024-08-01T",
+            "2024-08-01T21:03",
+            "2024-08-01T24:00:00Z",
+            "2024-08-01T21:3:03Z",
+            "2024-08-01T21:03:03.",
+            "2024-08-01T21:03:03.5X",
+            "2024-08-01T21:03:03+1:00",
+            "2024-08-01T21:03:03 UTC",
+            "2024-08-01X21:03:03",
+        ] {
+            assert_eq!(Timestamp::parse(s), None, "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_days_and_ordering() {
+        let t = Timestamp::parse("2024-08-01T21:03:03Z").unwrap();
+        assert_eq!(t.days(), util::days_from_civil(2024, 8, 1));
+        assert_eq!(t.date(), "2024-08-01");
+        assert_eq!(Timestamp::parse("1969-12-31T23:00:00Z").unwrap().days(), -1);
+        assert!(Timestamp::parse("2024-08-01T21:03:03.1Z").unwrap() > t);
+        assert_eq!(
+            Timestamp::from_days(t.days()),
+            Timestamp::parse("2024-08-01").unwrap()
+        );
+        assert_eq!(Timestamp::from_days(util::today_days() - 3).days_ago(), 3);
+    }
+
+    #[test]
+    fn test_from_system_time() {
+        use std::time::Duration;
+        let after = UNIX_EPOCH + Duration::from_millis(1_722_546_183_250);
+        assert_eq!(
+            Timestamp::from_system_time(after).to_string(),
+            "2024-08-01T21:03:03.25Z"
+        );
+        let before = UNIX_EPOCH - Duration::from_millis(1_500);
+        assert_eq!(
+            Timestamp::from_system_time(before).to_string(),
+            "1969-12-31T23:59:58.5Z"
+        );
+    }
+
+    #[test]
+    fn test_serde_reads_both_forms_and_writes_rfc3339() {
+        let t: Timestamp = serde_json::from_str(r#""2025-01-07T10:09:50.0000000""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&t).unwrap(),
+            r#""2025-01-07T10:09:50Z""#
+        );
+        let t: Timestamp = serde_json::from_str(r#""2025-01-07T10:09:50Z""#).unwrap();
+        assert_eq!(t, Timestamp::parse("2025-01-07T10:09:50.0000000").unwrap());
+        let err = serde_json::from_str::<Timestamp>(r#""soon""#).unwrap_err();
+        assert!(err.to_string().contains("invalid timestamp"), "{}", err);
+    }
+}
@@ -0,0 +1,51 @@
+//! Hooks for watching a pipeline run as it goes.
+//!
+//! A [`PipelineObserver`] registered with
+//! [`Pipeline::add_observer`](crate::pipeline::Pipeline::add_observer) is
+//! told about each step of [`Pipeline::run`](crate::pipeline::Pipeline::run):
+//! seeding, every novel scraped, rejected, or evaluated, discovery, and the
+//! end of the run. Outputs written while the run goes, like the
+//! `--export-dataset` file, are observers themselves, so embedding the
+//! pipeline in another program needs no changes to it.
+
+use crate::eval::filter::FilterReason;
+use crate::models::{Novel, NovelScore, Review};
+use crate::stats::RunStats;
+use anyhow::Result;
+
+/// Receives the events of a pipeline run. Every method does nothing by
+/// default, so an observer implements only the events it cares about.
+///
+/// An error from any method stops the run and is returned from `run`.
+pub trait PipelineObserver {
+    /// The queue has been seeded with `count` novels.
+    fn on_seeded(&mut self, _count: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// A novel passed the hard filters and has been scraped in full, with
+    /// its reviews; it's about to be evaluated.
+    fn on_novel_scraped(&mut self, _novel: &Novel, _reviews: &[Review]) -> Result<()> {
+        Ok(())
+    }
+
+    /// A novel failed the hard filters for every profile, for `reasons`.
+    fn on_prefilter_rejected(&mut self, _novel: &Novel, _reasons: &[FilterReason]) -> Result<()> {
+        Ok(())
+    }
+
+    /// A novel has been scored, once per profile it passed.
+    fn on_evaluated(&mut self, _score: &NovelScore) -> Result<()> {
+        Ok(())
+    }
+
+    /// Discovery from `novel` has added `added` new novels to the queue.
+    fn on_discovery(&mut self, _novel: &Novel, _added: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// The run is over; `stats` are final.
+    fn on_finished(&mut self, _stats: &RunStats) -> Result<()> {
+        Ok(())
+    }
+}
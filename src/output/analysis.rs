@@ -0,0 +1,231 @@
+//! Tag analysis across the evaluated novels.
+//!
+//! Shows which tags go with high scores under the current criteria: how
+//! many novels carry each tag and their mean score, and which tag pairs
+//! occur together most among the high-scoring novels.
+
+use crate::config::{TagReportConfig, TagSort};
+use crate::models::NovelScore;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use tabled::{Table, Tabled};
+
+/// Tag statistics over one run's results.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagReport {
+    /// Per-tag statistics, in the configured order.
+    pub tags: Vec<TagStat>,
+    /// Tag pairs by co-occurrence among high-scoring novels, most common first.
+    pub pairs: Vec<TagPair>,
+    /// The score a novel needed to count towards `pairs`.
+    pub high_score: f64,
+}
+
+/// How a tag fared across the evaluated novels.
+#[derive(Debug, Clone, PartialEq, Serialize, Tabled)]
+pub struct TagStat {
+    #[tabled(rename = "Tag")]
+    pub tag: String,
+    /// Novels carrying the tag.
+    #[tabled(rename = "Novels")]
+    pub count: usize,
+    /// Mean overall score of those novels.
+    #[tabled(rename = "Mean score", display_with = "percent")]
+    pub mean_score: f64,
+}
+
+/// Two tags found together on high-scoring novels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagPair {
+    /// The tags, in alphabetical order.
+    pub tags: [String; 2],
+    /// High-scoring novels carrying both.
+    pub count: usize,
+}
+
+/// Compute the tag report for a run's results.
+///
+/// Each novel counts once, with its best score when it was scored against
+/// several profiles. Tags and pairs seen on fewer than `min_count` novels are
+/// left out.
+pub fn tag_report(results: &[NovelScore], settings: &TagReportConfig) -> TagReport {
+    // Keyed in ID order, so the score sums below always add up the same way
+    let mut best: BTreeMap<u64, &NovelScore> = BTreeMap::new();
+    for score in results {
+        let entry = best.entry(score.novel.id).or_insert(score);
+        if score.overall_score > entry.overall_score {
+            *entry = score;
+        }
+    }
+
+    let mut totals: BTreeMap<&str, (usize, f64)> = BTreeMap::new();
+    let mut pair_counts: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for score in best.values() {
+        let mut tags: Vec<&str> = score.novel.tags.iter().map(String::as_str).collect();
+        tags.sort_unstable();
+        tags.dedup();
+        for tag in &tags {
+            let total = totals.entry(tag).or_default();
+            total.0 += 1;
+            total.1 += score.overall_score;
+        }
+        if score.overall_score >= settings.high_score {
+            for (i, first) in tags.iter().enumerate() {
+                for second in &tags[i + 1..] {
+                    *pair_counts.entry((first, second)).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    let mut tags: Vec<TagStat> = totals
+        .into_iter()
+        .filter(|(_, (count, _))| *count >= settings.min_count)
+        .map(|(tag, (count, sum))| TagStat {
+            tag: tag.to_string(),
+            count,
+            mean_score: sum / count as f64,
+        })
+        .collect();
+    // Ties keep the alphabetical order from the map
+    match settings.sort {
+        TagSort::Count => tags.sort_by_key(|t| Reverse(t.count)),
+        TagSort::MeanScore => tags.sort_by(|a, b| b.mean_score.total_cmp(&a.mean_score)),
+    }
+
+    let mut pairs: Vec<TagPair> = pair_counts
+        .into_iter()
+        .filter(|(_, count)| *count >= settings.min_count)
+        .map(|((first, second), count)| TagPair {
+            tags: [first.to_string(), second.to_string()],
+            count,
+        })
+        .collect();
+    pairs.sort_by_key(|p| Reverse(p.count));
+    pairs.truncate(settings.max_pairs);
+
+    TagReport {
+        tags,
+        pairs,
+        high_score: settings.high_score,
+    }
+}
+
+/// A row in the tag pair table.
+#[derive(Tabled)]
+struct PairRow {
+    #[tabled(rename = "Tags")]
+    tags: String,
+    #[tabled(rename = "Novels")]
+    count: usize,
+}
+
+/// Print the tag report as tables.
+pub fn print_tag_report(report: &TagReport) {
+    println!("\n=== Tags ===");
+    if report.tags.is_empty() {
+        println!("No tag was seen often enough to report.");
+    } else {
+        println!("{}", Table::new(&report.tags));
+    }
+
+    println!(
+        "\n=== Tag pairs among novels scoring at least {:.0}% ===",
+        report.high_score * 100.0
+    );
+    if report.pairs.is_empty() {
+        println!("No tag pair was seen often enough to report.");
+    } else {
+        let rows = report.pairs.iter().map(|pair| PairRow {
+            tags: pair.tags.join(" + "),
+            count: pair.count,
+        });
+        println!("{}", Table::new(rows));
+    }
+}
+
+fn percent(score: &f64) -> String {
+    format!("{:.0}%", score * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, test_score};
+
+    fn score(id: u64, overall: f64, tags: &[&str]) -> NovelScore {
+        let mut novel = test_novel(id);
+        novel.tags = tags.iter().map(|t| t.to_string()).collect();
+        test_score(novel, overall)
+    }
+
+    fn results() -> Vec<NovelScore> {
+        vec![
+            score(1, 0.9, &["Fantasy", "Magic", "Progression"]),
+            score(2, 0.8, &["Fantasy", "Magic"]),
+            score(3, 0.4, &["Fantasy", "Romance"]),
+            score(4, 0.2, &["Romance", "Tragedy"]),
+            score(5, 0.6, &["Tragedy"]),
+        ]
+    }
+
+    #[test]
+    fn test_tag_counts_and_means() {
+        let report = tag_report(&results(), &TagReportConfig::default());
+        let tags: Vec<(&str, usize)> =
+            report.tags.iter().map(|t| (t.tag.as_str(), t.count)).collect();
+        // Progression is seen once, below the default minimum of two
+        assert_eq!(tags, vec![("Magic", 2), ("Fantasy", 3), ("Tragedy", 2), ("Romance", 2)]);
+        assert!((report.tags[0].mean_score - 0.85).abs() < 1e-9);
+        assert!((report.tags[1].mean_score - 0.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sort_by_count() {
+        let settings = TagReportConfig {
+            sort: TagSort::Count,
+            min_count: 1,
+            ..Default::default()
+        };
+        let report = tag_report(&results(), &settings);
+        let tags: Vec<&str> = report.tags.iter().map(|t| t.tag.as_str()).collect();
+        assert_eq!(tags, vec!["Fantasy", "Magic", "Romance", "Tragedy", "Progression"]);
+    }
+
+    #[test]
+    fn test_pairs_among_high_scorers() {
+        let report = tag_report(&results(), &TagReportConfig::default());
+        assert_eq!(
+            report.pairs,
+            vec![TagPair {
+                tags: ["Fantasy".to_string(), "Magic".to_string()],
+                count: 2,
+            }]
+        );
+
+        let settings = TagReportConfig {
+            min_count: 1,
+            max_pairs: 2,
+            ..Default::default()
+        };
+        let pairs = tag_report(&results(), &settings).pairs;
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].count, 2);
+    }
+
+    #[test]
+    fn test_novels_count_once_across_profiles() {
+        let mut cozy = score(1, 0.9, &["Fantasy"]);
+        cozy.profile = Some("cozy".to_string());
+        let mut grim = score(1, 0.3, &["Fantasy"]);
+        grim.profile = Some("grim".to_string());
+        let settings = TagReportConfig {
+            min_count: 1,
+            ..Default::default()
+        };
+        let report = tag_report(&[grim, cozy], &settings);
+        assert_eq!(report.tags[0].count, 1);
+        assert_eq!(report.tags[0].mean_score, 0.9);
+    }
+}
@@ -0,0 +1,294 @@
+//! Atom feed output, for following scheduled runs in a feed reader.
+//!
+//! Each result becomes an entry whose ID combines the fiction ID with the
+//! criteria hash, so a novel found again by later runs with the same criteria
+//! keeps its entry instead of showing up as new. Entries already in the feed
+//! file are carried over, newest first, up to [`MAX_ENTRIES`].
+
+use crate::models::NovelScore;
+use crate::stats::RunMetadata;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Most entries kept in the feed, counting those from earlier runs.
+const MAX_ENTRIES: usize = 200;
+
+/// The feed's own ID, the same for every run so readers see one feed.
+const FEED_ID: &str = "urn:novel-finder:feed";
+
+/// Write results as an Atom feed at `path`, keeping entries from the feed
+/// already there. Returns how many new entries were added. The run's finish
+/// time becomes the feed's and new entries' `updated`.
+pub fn write_atom_feed(results: &[NovelScore], path: &Path, run: &RunMetadata) -> Result<usize> {
+    let previous = match std::fs::read_to_string(path) {
+        Ok(content) => previous_entries(&content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read feed {}", path.display()))
+        }
+    };
+    let mut seen: HashSet<String> = previous.iter().map(|entry| entry.id.clone()).collect();
+
+    let updated = run.finished_at.to_string();
+    let mut new_entries = Vec::new();
+    for score in results {
+        let id = entry_id(score.novel.id, &run.criteria_hash);
+        if seen.insert(id.clone()) {
+            new_entries.push(FeedEntry {
+                xml: entry_xml(score, &id, &updated),
+                id,
+            });
+        }
+    }
+    let added = new_entries.len();
+
+    let entries = new_entries.into_iter().chain(previous).take(MAX_ENTRIES);
+    let mut feed = String::new();
+    feed.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    feed.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(feed, "  <id>{}</id>", FEED_ID);
+    feed.push_str("  <title>novel-finder results</title>\n");
+    let _ = writeln!(feed, "  <updated>{}</updated>", updated);
+    feed.push_str("  <author><name>novel-finder</name></author>\n");
+    feed.push_str("  <generator>novel-finder</generator>\n");
+    for entry in entries {
+        let _ = writeln!(feed, "  {}", entry.xml);
+    }
+    feed.push_str("</feed>\n");
+
+    std::fs::write(path, feed)
+        .with_context(|| format!("Failed to write feed {}", path.display()))?;
+    Ok(added)
+}
+
+/// One `<entry>` element, kept as XML so entries from earlier runs are
+/// carried over exactly as written.
+#[derive(Debug)]
+struct FeedEntry {
+    id: String,
+    xml: String,
+}
+
+/// The stable ID of a novel's entry under the given criteria.
+fn entry_id(novel_id: u64, criteria_hash: &str) -> String {
+    format!("urn:novel-finder:{}:{}", criteria_hash, novel_id)
+}
+
+/// Render one result as an `<entry>` element on a single line.
+fn entry_xml(score: &NovelScore, id: &str, updated: &str) -> String {
+    let novel = &score.novel;
+    let mut summary = format!("Score: {:.0}%", score.overall_score * 100.0);
+    if let Some(profile) = &score.profile {
+        let _ = write!(summary, " ({})", profile);
+    }
+    if !novel.tags.is_empty() {
+        let _ = write!(summary, "\nTags: {}", novel.tags.join(", "));
+    }
+    if !score.reasoning.is_empty() {
+        let _ = write!(summary, "\n\n{}", score.reasoning);
+    }
+
+    let mut xml = String::from("<entry>");
+    let _ = write!(xml, "<id>{}</id>", escape(id));
+    let _ = write!(xml, "<title>{}</title>", escape(&novel.title));
+    let _ = write!(xml, "<link rel=\"alternate\" href=\"{}\"/>", escape(&novel.url));
+    if let Some(cover_url) = &novel.cover_url {
+        let _ = write!(xml, "<link rel=\"enclosure\" href=\"{}\"/>", escape(cover_url));
+    }
+    let _ = write!(xml, "<updated>{}</updated>", updated);
+    let _ = write!(xml, "<author><name>{}</name></author>", escape(&novel.author));
+    for tag in &novel.tags {
+        let _ = write!(xml, "<category term=\"{}\"/>", escape(tag));
+    }
+    let _ = write!(xml, "<summary type=\"text\">{}</summary>", escape(&summary));
+    xml.push_str("</entry>");
+    xml
+}
+
+/// The entries of a feed written by [`write_atom_feed`], in order.
+/// Anything that isn't a complete entry with an ID is dropped.
+fn previous_entries(content: &str) -> Vec<FeedEntry> {
+    let mut entries = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<entry>") {
+        let Some(length) = rest[start..].find("</entry>") else {
+            break;
+        };
+        let xml = &rest[start..start + length + "</entry>".len()];
+        rest = &rest[start + xml.len()..];
+        let id = xml
+            .split_once("<id>")
+            .and_then(|(_, after)| after.split_once("</id>"))
+            .map(|(id, _)| id.to_string());
+        if let Some(id) = id {
+            entries.push(FeedEntry {
+                id,
+                xml: xml.to_string(),
+            });
+        }
+    }
+    entries
+}
+
+/// Escape text for XML content and attribute values.
+pub(super) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, test_score};
+    use crate::stats::test_run_metadata;
+    use regex::Regex;
+    use std::path::PathBuf;
+
+    fn score(id: u64, title: &str) -> NovelScore {
+        let mut novel = test_novel(id);
+        novel.title = title.to_string();
+        novel.tags = vec!["Fantasy".to_string(), "Magic".to_string()];
+        let mut score = test_score(novel, 0.87);
+        score.reasoning = "Strong <magic> & great prose".to_string();
+        score
+    }
+
+    fn run_at(seconds: u64) -> RunMetadata {
+        test_run_metadata("0123456789abcdef", seconds)
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("novel-finder-{}-{}.xml", name, std::process::id()))
+    }
+
+    /// Check the feed is well-formed XML with the elements RFC 4287 requires
+    /// of a feed and its entries: exactly one `id`, `title`, and `updated`
+    /// each, RFC 3339 timestamps, and an author for every entry.
+    fn assert_valid_atom(xml: &str) {
+        let body = xml
+            .strip_prefix("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n")
+            .expect("XML declaration");
+        let tag = Regex::new(r#"<(/?)([A-Za-z][\w:.-]*)((?:\s+[\w:.-]+="[^"<]*")*)\s*(/?)>"#)
+            .unwrap();
+        let entity = Regex::new(r"&(amp|lt|gt|quot|apos);").unwrap();
+        let mut stack: Vec<&str> = Vec::new();
+        let mut last_end = 0;
+        for captures in tag.captures_iter(body) {
+            let whole = captures.get(0).unwrap();
+            let text = &body[last_end..whole.start()];
+            assert!(!text.contains('<'), "stray markup before {:?}", whole.as_str());
+            assert_eq!(
+                entity.replace_all(text, "").matches('&').count(),
+                0,
+                "unescaped & in {:?}",
+                text
+            );
+            last_end = whole.end();
+            let name = captures.get(2).unwrap().as_str();
+            if &captures[1] == "/" {
+                assert_eq!(stack.pop(), Some(name), "mismatched </{}>", name);
+            } else if &captures[4] != "/" {
+                if stack.is_empty() {
+                    assert_eq!(name, "feed", "root element");
+                }
+                stack.push(name);
+            }
+        }
+        assert!(stack.is_empty(), "unclosed elements: {:?}", stack);
+        assert!(body[last_end..].trim().is_empty());
+        assert!(body.starts_with("<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+
+        let timestamp = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z$").unwrap();
+        let (head, _) = body.split_once("<entry>").unwrap_or((body, ""));
+        let entries = previous_entries(body);
+        let mut elements = vec![head];
+        elements.extend(entries.iter().map(|entry| entry.xml.as_str()));
+        for element in elements {
+            for name in ["id", "title", "updated"] {
+                assert_eq!(element.matches(&format!("<{}>", name)).count(), 1, "{}", element);
+            }
+            assert!(element.contains("<author><name>"), "{}", element);
+            let updated = element.split_once("<updated>").unwrap().1;
+            let updated = updated.split_once("</updated>").unwrap().0;
+            assert!(timestamp.is_match(updated), "{}", updated);
+        }
+    }
+
+    #[test]
+    fn test_feed_is_valid_atom() {
+        let path = temp_path("feed-valid");
+        let _ = std::fs::remove_file(&path);
+        let mut results = [score(1, "Mother of Learning"), score(2, "Tom & Jerry's \"Quest\"")];
+        results[0].novel.cover_url = Some("https://example.com/cover.jpg?a=1&b=2".to_string());
+
+        assert_eq!(write_atom_feed(&results, &path, &run_at(1_722_546_183)).unwrap(), 2);
+        let xml = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_valid_atom(&xml);
+        assert!(xml.contains("<updated>2024-08-01T21:03:03Z</updated>"), "{}", xml);
+        assert!(xml.contains("<title>Tom &amp; Jerry&apos;s &quot;Quest&quot;</title>"));
+        assert!(xml.contains("<id>urn:novel-finder:0123456789abcdef:1</id>"));
+        assert!(xml.contains("Score: 87%\nTags: Fantasy, Magic\n\nStrong &lt;magic&gt;"));
+        assert_eq!(xml.matches("rel=\"enclosure\"").count(), 1);
+        assert!(xml.contains(
+            "<link rel=\"enclosure\" href=\"https://example.com/cover.jpg?a=1&amp;b=2\"/>"
+        ));
+    }
+
+    #[test]
+    fn test_feed_merges_previous_entries() {
+        let path = temp_path("feed-merge");
+        let _ = std::fs::remove_file(&path);
+        write_atom_feed(&[score(1, "First")], &path, &run_at(1_000)).unwrap();
+
+        // A later run finds novel 1 again plus a new novel 2
+        let added =
+            write_atom_feed(&[score(2, "Second"), score(1, "First")], &path, &run_at(2_000))
+                .unwrap();
+        let xml = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(added, 1);
+        assert_valid_atom(&xml);
+        let ids: Vec<String> = previous_entries(&xml).into_iter().map(|e| e.id).collect();
+        assert_eq!(
+            ids,
+            vec!["urn:novel-finder:0123456789abcdef:2", "urn:novel-finder:0123456789abcdef:1"]
+        );
+        // The feed is updated, but the re-found novel keeps its original entry
+        let (head, entries) = xml.split_once("<entry>").unwrap();
+        assert!(head.contains("<updated>1970-01-01T00:33:20Z</updated>"), "{}", head);
+        let first = entries.split("<entry>").last().unwrap();
+        assert!(first.contains("<title>First</title>"), "{}", first);
+        assert!(first.contains("<updated>1970-01-01T00:16:40Z</updated>"), "{}", first);
+    }
+
+    #[test]
+    fn test_feed_caps_entries() {
+        let path = temp_path("feed-cap");
+        let _ = std::fs::remove_file(&path);
+        let first: Vec<NovelScore> = (1..=MAX_ENTRIES as u64).map(|id| score(id, "Old")).collect();
+        write_atom_feed(&first, &path, &run_at(1_000)).unwrap();
+        write_atom_feed(&[score(9_999, "New")], &path, &run_at(2_000)).unwrap();
+        let xml = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let entries = previous_entries(&xml);
+        assert_eq!(entries.len(), MAX_ENTRIES);
+        assert!(entries[0].id.ends_with(":9999"));
+        assert!(!xml.contains(&format!("abcdef:{}<", MAX_ENTRIES)));
+    }
+}
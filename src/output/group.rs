@@ -0,0 +1,167 @@
+//! Grouping of results into sections by genre or status.
+//!
+//! A long list mixes very different novels; grouping splits it into one
+//! section per genre or status, so a cozy slice-of-life and a grimdark
+//! progression story aren't ranked against each other. Groups are ordered
+//! by their best score, and results keep their order within each group.
+
+use crate::config::GroupBy;
+use crate::models::tags::normalize_tag;
+use crate::models::{Novel, NovelScore};
+
+/// Name of the group for novels without a recognized genre.
+const NO_GENRE: &str = "No genre";
+
+/// The results sharing a genre or status.
+#[derive(Debug)]
+pub struct ResultGroup<'a> {
+    /// The genre or status name.
+    pub name: String,
+    /// The group's results, in their original order.
+    pub results: Vec<&'a NovelScore>,
+}
+
+impl ResultGroup<'_> {
+    /// The mean overall score of the group's results.
+    pub fn mean_score(&self) -> f64 {
+        let total: f64 = self.results.iter().map(|score| score.overall_score).sum();
+        total / self.results.len() as f64
+    }
+
+    /// The highest overall score in the group.
+    pub fn best_score(&self) -> f64 {
+        self.results
+            .iter()
+            .map(|score| score.overall_score)
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// The group's name and statistics, as a section heading
+    /// ("Fantasy: 3 results, mean score 71%").
+    pub fn heading(&self) -> String {
+        format!(
+            "{}: {} result{}, mean score {:.0}%",
+            self.name,
+            self.results.len(),
+            if self.results.len() == 1 { "" } else { "s" },
+            self.mean_score() * 100.0
+        )
+    }
+}
+
+/// Split `results` into groups by `by`, ordered by each group's best score
+/// (ties keep the order groups first appear in). Order within a group is
+/// preserved. With `GroupBy::None`, every result is in one group.
+pub fn group_results<'a>(
+    results: impl IntoIterator<Item = &'a NovelScore>,
+    by: GroupBy,
+) -> Vec<ResultGroup<'a>> {
+    let mut groups: Vec<ResultGroup<'a>> = Vec::new();
+    for score in results {
+        let name = group_name(&score.novel, by);
+        match groups.iter_mut().find(|group| group.name == name) {
+            Some(group) => group.results.push(score),
+            None => groups.push(ResultGroup {
+                name,
+                results: vec![score],
+            }),
+        }
+    }
+    groups.sort_by(|a, b| b.best_score().total_cmp(&a.best_score()));
+    groups
+}
+
+/// The name of the group `novel` belongs in.
+fn group_name(novel: &Novel, by: GroupBy) -> String {
+    match by {
+        GroupBy::None => "All results".to_string(),
+        GroupBy::Genre => novel
+            .genres
+            .iter()
+            .chain(&novel.tags)
+            .filter_map(|tag| normalize_tag(tag))
+            .find(|tag| tag.is_genre())
+            .map_or_else(|| NO_GENRE.to_string(), |tag| tag.name.to_string()),
+        GroupBy::Status => novel.status.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, test_score, NovelStatus};
+
+    fn score(id: u64, overall_score: f64, genres: &[&str], status: NovelStatus) -> NovelScore {
+        let mut novel = test_novel(id);
+        novel.genres = genres.iter().map(|genre| genre.to_string()).collect();
+        novel.tags = novel.genres.clone();
+        novel.status = status;
+        test_score(novel, overall_score)
+    }
+
+    fn summary(groups: &[ResultGroup]) -> Vec<(String, Vec<u64>)> {
+        groups
+            .iter()
+            .map(|group| {
+                let ids = group.results.iter().map(|score| score.novel.id).collect();
+                (group.name.clone(), ids)
+            })
+            .collect()
+    }
+
+    fn results() -> Vec<NovelScore> {
+        vec![
+            score(1, 0.9, &["Fantasy", "Action"], NovelStatus::Ongoing),
+            score(2, 0.8, &["comedy"], NovelStatus::Completed),
+            score(3, 0.7, &["science fiction"], NovelStatus::Ongoing),
+            score(4, 0.6, &["Fantasy"], NovelStatus::Hiatus),
+            score(5, 0.5, &[], NovelStatus::Completed),
+            score(6, 0.4, &["Sci-fi", "Fantasy"], NovelStatus::Completed),
+        ]
+    }
+
+    #[test]
+    fn test_group_by_genre() {
+        let results = results();
+        let groups = group_results(&results, GroupBy::Genre);
+        // Aliases group under the canonical genre; the first genre decides
+        assert_eq!(
+            summary(&groups),
+            [
+                ("Fantasy".to_string(), vec![1, 4]),
+                ("Comedy".to_string(), vec![2]),
+                ("Sci-fi".to_string(), vec![3, 6]),
+                ("No genre".to_string(), vec![5]),
+            ]
+        );
+        assert!((groups[0].mean_score() - 0.75).abs() < 1e-9);
+        assert_eq!(groups[0].best_score(), 0.9);
+        assert_eq!(groups[0].heading(), "Fantasy: 2 results, mean score 75%");
+        assert_eq!(groups[1].heading(), "Comedy: 1 result, mean score 80%");
+    }
+
+    #[test]
+    fn test_groups_ordered_by_best_score() {
+        let mut results = results();
+        results.reverse();
+        let groups = group_results(&results, GroupBy::Status);
+        // Order within a group is left as given
+        assert_eq!(
+            summary(&groups),
+            [
+                ("Ongoing".to_string(), vec![3, 1]),
+                ("Completed".to_string(), vec![6, 5, 2]),
+                ("Hiatus".to_string(), vec![4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_grouping_keeps_one_group() {
+        let results = results();
+        let groups = group_results(&results, GroupBy::None);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].results.len(), results.len());
+        assert!(group_results(&[], GroupBy::Genre).is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+//! A self-contained HTML report, for browsing results in any web browser.
+//!
+//! The report is a single file with its CSS and JavaScript inline: a table
+//! of results that sorts by any column and filters by text, where clicking a
+//! row expands its reasoning, sub-scores, and description. A header lists
+//! the run's metadata, criteria included. When results are grouped, each
+//! group gets its own section and table, under a heading with the group's
+//! statistics. Every piece of
+//! scraped or generated text is escaped, so a title or reasoning containing
+//! markup shows as text rather than running as part of the page.
+
+use super::feed::escape;
+use super::format_pages;
+use super::group::group_results;
+use crate::config::GroupBy;
+use crate::models::{sub_scores, NovelScore};
+use crate::stats::RunMetadata;
+use anyhow::{Context, Result};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Styles for the report, inlined into its `<head>`.
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+input#filter { width: 100%; max-width: 30em; padding: 0.4em; margin-bottom: 1em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { padding: 0.4em 0.6em; text-align: left; vertical-align: top; }
+th { cursor: pointer; user-select: none; border-bottom: 2px solid #888; }
+th[aria-sort=ascending]::after { content: ' \\25B2'; }
+th[aria-sort=descending]::after { content: ' \\25BC'; }
+tbody.result { border-bottom: 1px solid #ddd; }
+tr.summary { cursor: pointer; }
+tr.summary:hover { background: #f3f3f3; }
+tr.details td { background: #fafafa; }
+tr.details p, tr.details ul { white-space: pre-wrap; margin: 0.4em 0; }
+img.cover { float: right; max-width: 10em; margin: 0 0 1em 1em; }
+details.run { margin-bottom: 1em; }
+details.run dl { display: grid; grid-template-columns: max-content auto; gap: 0.2em 1em; }
+details.run dd { margin: 0; font-family: monospace; overflow-wrap: anywhere; }
+.number { text-align: right; }
+";
+
+/// Sorting, filtering, and row expansion, inlined at the end of `<body>`.
+/// Each result is its own `<tbody>` holding a summary row and a hidden
+/// details row, so sorting moves both together. Each group's table sorts on
+/// its own; the filter applies to all of them.
+const SCRIPT: &str = "\
+const tables = Array.from(document.querySelectorAll('table.results'));
+tables.forEach(table => {
+  table.querySelectorAll('th').forEach((th, column) => {
+    th.addEventListener('click', () => {
+      const ascending = th.getAttribute('aria-sort') !== 'ascending';
+      table.querySelectorAll('th').forEach(other => other.removeAttribute('aria-sort'));
+      th.setAttribute('aria-sort', ascending ? 'ascending' : 'descending');
+      const key = tbody => {
+        const cell = tbody.rows[0].cells[column];
+        const value = cell.dataset.sort ?? cell.textContent.trim().toLowerCase();
+        return th.dataset.type === 'number' ? parseFloat(value) : value;
+      };
+      const sorted = Array.from(table.tBodies).sort((a, b) => {
+        const [x, y] = [key(a), key(b)];
+        const order = x < y ? -1 : x > y ? 1 : 0;
+        return ascending ? order : -order;
+      });
+      sorted.forEach(tbody => table.appendChild(tbody));
+    });
+  });
+  table.addEventListener('click', event => {
+    const row = event.target.closest('tr.summary');
+    if (row && !event.target.closest('a')) {
+      row.nextElementSibling.hidden = !row.nextElementSibling.hidden;
+    }
+  });
+});
+document.getElementById('filter').addEventListener('input', event => {
+  const query = event.target.value.trim().toLowerCase();
+  tables.flatMap(table => Array.from(table.tBodies)).forEach(tbody => {
+    tbody.hidden = query !== '' && !tbody.textContent.toLowerCase().includes(query);
+  });
+});
+";
+
+/// The table's columns: heading, and whether it sorts as a number.
+const COLUMNS: [(&str, bool); 9] = [
+    ("Rank", true),
+    ("Title", false),
+    ("Score", true),
+    ("Rating", true),
+    ("Pages", true),
+    ("Status", false),
+    ("Tags", false),
+    ("Followers", true),
+    ("Profile", false),
+];
+
+/// Write results as a self-contained HTML report at `path`, in a section
+/// per group unless `group_by` is `GroupBy::None`.
+pub fn write_html_report(
+    results: &[NovelScore],
+    path: &Path,
+    run: &RunMetadata,
+    group_by: GroupBy,
+) -> Result<()> {
+    std::fs::write(path, html_report(results, run, group_by))
+        .with_context(|| format!("Failed to write HTML report {}", path.display()))
+}
+
+/// Render the whole report.
+fn html_report(results: &[NovelScore], run: &RunMetadata, group_by: GroupBy) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    html.push_str("<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str("<title>novel-finder results</title>\n");
+    let _ = writeln!(html, "<style>\n{}</style>", STYLE);
+    html.push_str("</head>\n<body>\n<h1>novel-finder results</h1>\n");
+    let _ = writeln!(
+        html,
+        "<p>{} results from a run finished {}. Click a heading to sort, or a row for \
+         details.</p>",
+        results.len(),
+        escape(&run.finished_at.to_string())
+    );
+    html.push_str("<details class=\"run\"><summary>Run details</summary><dl>");
+    for (name, value) in run.fields() {
+        let _ = write!(html, "<dt>{}</dt><dd>{}</dd>", name, escape(&value));
+    }
+    html.push_str("</dl></details>\n");
+    html.push_str(
+        "<input id=\"filter\" type=\"search\" placeholder=\"Filter by any text\" \
+         aria-label=\"Filter results\">\n",
+    );
+    if group_by == GroupBy::None {
+        html.push_str(&results_table(&results.iter().collect::<Vec<_>>()));
+    } else {
+        for group in group_results(results, group_by) {
+            let _ = writeln!(html, "<h2>{}</h2>", escape(&group.heading()));
+            html.push_str(&results_table(&group.results));
+        }
+    }
+    let _ = writeln!(html, "<script>\n{}</script>", SCRIPT);
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Render a table of results, ranked in the order given.
+fn results_table(results: &[&NovelScore]) -> String {
+    let mut html = String::from("<table class=\"results\">\n<thead><tr>");
+    for (heading, numeric) in COLUMNS {
+        let kind = if numeric { "number" } else { "text" };
+        let _ = write!(html, "<th data-type=\"{}\">{}</th>", kind, heading);
+    }
+    html.push_str("</tr></thead>\n");
+    for (index, score) in results.iter().enumerate() {
+        html.push_str(&result_rows(index + 1, score));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+/// Render one result as a `<tbody>` with its summary and details rows.
+fn result_rows(rank: usize, score: &NovelScore) -> String {
+    let novel = &score.novel;
+    let mut html = String::from("<tbody class=\"result\">\n<tr class=\"summary\">");
+    let _ = write!(html, "<td class=\"number\">{}</td>", rank);
+    match web_url(&novel.url) {
+        Some(url) => {
+            let _ = write!(
+                html,
+                "<td><a href=\"{}\">{}</a></td>",
+                escape(url),
+                escape(&novel.title)
+            );
+        }
+        None => {
+            let _ = write!(html, "<td>{}</td>", escape(&novel.title));
+        }
+    }
+    let _ = write!(
+        html,
+        "<td class=\"number\" data-sort=\"{}\">{:.0}%</td>",
+        score.overall_score,
+        score.overall_score * 100.0
+    );
+    let _ = write!(html, "<td class=\"number\">{:.2}</td>", novel.rating);
+    let _ = write!(
+        html,
+        "<td class=\"number\" data-sort=\"{}\">{}</td>",
+        novel.reported_pages().unwrap_or(0),
+        format_pages(novel)
+    );
+    let _ = write!(html, "<td>{}</td>", escape(&novel.status.to_string()));
+    let _ = write!(html, "<td>{}</td>", escape(&novel.tags.join(", ")));
+    let _ = write!(html, "<td class=\"number\">{}</td>", novel.followers);
+    let _ = write!(
+        html,
+        "<td>{}</td>",
+        escape(score.profile.as_deref().unwrap_or_default())
+    );
+    html.push_str("</tr>\n");
+
+    let _ = write!(
+        html,
+        "<tr class=\"details\" hidden><td colspan=\"{}\">",
+        COLUMNS.len()
+    );
+    if let Some(cover_url) = novel.cover_url.as_deref().and_then(web_url) {
+        let _ = write!(
+            html,
+            "<img class=\"cover\" src=\"{}\" alt=\"Cover of {}\" loading=\"lazy\">",
+            escape(cover_url),
+            escape(&novel.title)
+        );
+    }
+    let _ = write!(
+        html,
+        "<p><strong>Author:</strong> {}</p>",
+        escape(&novel.author)
+    );
+    let _ = write!(
+        html,
+        "<p><strong>Reasoning:</strong> {}</p>",
+        escape(&score.reasoning)
+    );
+    if !score.sub_scores.is_empty() {
+        html.push_str("<ul>");
+        for (criterion, sub_score) in sub_scores::for_display(&score.sub_scores) {
+            let _ = write!(
+                html,
+                "<li>{}: {:.0}%</li>",
+                escape(criterion),
+                sub_score * 100.0
+            );
+        }
+        html.push_str("</ul>");
+    }
+    if !score.evidence.is_empty() {
+        html.push_str("<p><strong>Evidence:</strong></p><ul class=\"evidence\">");
+        for item in &score.evidence {
+            let _ = write!(
+                html,
+                "<li><em>{}</em> (+{:.0}%): &ldquo;{}&rdquo;</li>",
+                escape(&item.source.to_string()),
+                item.contribution * 100.0,
+                escape(&item.snippet)
+            );
+        }
+        html.push_str("</ul>");
+    }
+    let _ = write!(
+        html,
+        "<p><strong>Description:</strong>\n{}</p>",
+        escape(&novel.description)
+    );
+    html.push_str("</td></tr>\n</tbody>\n");
+    html
+}
+
+/// The URL, if it's a web URL that is safe to link to. Anything else, such
+/// as a `javascript:` URL, isn't linked.
+fn web_url(url: &str) -> Option<&str> {
+    let scheme = url.split_once("://")?.0;
+    (scheme.eq_ignore_ascii_case("https") || scheme.eq_ignore_ascii_case("http")).then_some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Evidence, EvidenceSource};
+    use crate::models::{test_novel, test_score};
+    use crate::stats::test_run_metadata;
+    use std::collections::BTreeMap;
+
+    fn score(id: u64, title: &str, reasoning: &str) -> NovelScore {
+        let mut novel = test_novel(id);
+        novel.title = title.to_string();
+        novel.tags = vec!["Fantasy".to_string(), "Magic".to_string()];
+        let mut score = test_score(novel, 0.87);
+        score.sub_scores = BTreeMap::from([("prose".to_string(), 0.9)]);
+        score.reasoning = reasoning.to_string();
+        score
+    }
+
+    fn run() -> RunMetadata {
+        test_run_metadata("0123456789abcdef", 1_722_546_183)
+    }
+
+    #[test]
+    fn test_report_lists_every_result() {
+        let results = [
+            score(1, "Mother of Learning", "Tight time loop"),
+            score(2, "Beware of Chicken", "Cozy"),
+        ];
+        let html = html_report(&results, &run(), GroupBy::None);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.ends_with("</html>\n"));
+        assert_eq!(html.matches("<tbody class=\"result\">").count(), 2);
+        assert!(html.contains(
+            "<td><a href=\"https://www.royalroad.com/fiction/1\">Mother of Learning</a></td>"
+        ));
+        assert!(html.contains("<td class=\"number\" data-sort=\"0.87\">87%</td>"));
+        assert!(html.contains("<td>Fantasy, Magic</td>"));
+        assert!(html.contains("<li>prose: 90%</li>"));
+        assert!(html.contains("from a run finished 2024-08-01T21:03:03Z."));
+        assert!(html.contains("<dt>criteria_hash</dt><dd>0123456789abcdef</dd>"));
+        assert!(html.contains("<dt>total_requests</dt><dd>12</dd>"));
+        // Everything is inline
+        assert!(!html.contains("<link"));
+        assert!(!html.contains("<script src"));
+    }
+
+    #[test]
+    fn test_report_escapes_scraped_and_generated_text() {
+        let mut result = score(
+            1,
+            "Tom & Jerry's <b>\"Quest\"</b>",
+            "Great</p><script>alert('reasoning')</script>",
+        );
+        result.novel.description = "<script>alert(1)</script>\nSecond line".to_string();
+        result.novel.author = "<img src=x onerror=alert(2)>".to_string();
+        result.novel.tags = vec!["</td><script>".to_string()];
+        result.profile = Some("<i>cozy</i>".to_string());
+        result.evidence = vec![
+            Evidence::new(EvidenceSource::Description, "<script>alert(3)</script>", 0.1),
+            Evidence::new(EvidenceSource::Review("<u>x</u>".to_string()), "A & B", 0.05),
+        ];
+        let mut run = run();
+        run.criteria.prompt = Some("</dd><script>alert('prompt')</script>".to_string());
+        let html = html_report(&[result], &run, GroupBy::None);
+
+        // The only script element is the report's own
+        assert_eq!(html.matches("<script>").count(), 1);
+        assert!(!html.contains("<img src=x"));
+        assert!(!html.contains("<b>"));
+        assert!(!html.contains("<i>"));
+        assert!(html
+            .contains("Great&lt;/p&gt;&lt;script&gt;alert(&apos;reasoning&apos;)&lt;/script&gt;"));
+        assert!(html.contains("Tom &amp; Jerry&apos;s &lt;b&gt;&quot;Quest&quot;&lt;/b&gt;"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;\nSecond line"));
+        assert!(html.contains("<td>&lt;/td&gt;&lt;script&gt;</td>"));
+        assert!(html.contains(
+            "<li><em>description</em> (+10%): &ldquo;&lt;script&gt;alert(3)&lt;/script&gt;&rdquo;"
+        ));
+        assert!(html.contains("<em>review by &lt;u&gt;x&lt;/u&gt;</em> (+5%): &ldquo;A &amp; B"));
+    }
+
+    #[test]
+    fn test_report_links_only_web_urls() {
+        let mut linked = score(1, "Linked", "");
+        linked.novel.cover_url = Some("https://cdn.example/1.jpg?a=1&b=2".to_string());
+        let mut unlinked = score(2, "Unlinked", "");
+        unlinked.novel.url = "javascript:alert(1)".to_string();
+        unlinked.novel.cover_url = Some("javascript:alert(2)".to_string());
+        let html = html_report(&[linked, unlinked], &run(), GroupBy::None);
+
+        let cover = "<img class=\"cover\" src=\"https://cdn.example/1.jpg?a=1&amp;b=2\"";
+        assert!(html.contains(cover));
+        assert!(html.contains("<td>Unlinked</td>"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn test_report_sections_per_group() {
+        let mut cozy = score(1, "Beware of Chicken", "Cozy");
+        cozy.novel.genres = vec!["Comedy".to_string()];
+        cozy.overall_score = 0.6;
+        let mut epic = score(2, "Mother of Learning", "Tight time loop");
+        epic.novel.genres = vec!["Fantasy".to_string()];
+        let mut other = score(3, "Super Supportive", "Kind");
+        other.novel.genres = vec!["fantasy".to_string()];
+        other.overall_score = 0.75;
+        let html = html_report(&[cozy, epic, other], &run(), GroupBy::Genre);
+
+        // One table per group, the group with the best result first
+        assert_eq!(html.matches("<table class=\"results\">").count(), 2);
+        let fantasy = html.find("<h2>Fantasy: 2 results, mean score 81%</h2>").unwrap();
+        let comedy = html.find("<h2>Comedy: 1 result, mean score 60%</h2>").unwrap();
+        assert!(fantasy < comedy);
+        assert!(html.find("Mother of Learning").unwrap() < html.find("Super Supportive").unwrap());
+
+        let ungrouped = html_report(&[score(1, "Solo", "")], &run(), GroupBy::None);
+        assert!(!ungrouped.contains("<h2>"));
+        assert_eq!(ungrouped.matches("<table class=\"results\">").count(), 1);
+    }
+
+    #[test]
+    fn test_write_html_report() {
+        let path =
+            std::env::temp_dir().join(format!("novel-finder-report-{}.html", std::process::id()));
+        let results = [score(1, "Mother of Learning", "Tight time loop")];
+        write_html_report(&results, &path, &run(), GroupBy::None).unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(html, html_report(&results, &run(), GroupBy::None));
+    }
+}
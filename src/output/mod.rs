@@ -0,0 +1,723 @@
+//! Result formatting and table output.
+//!
+//! Formats the scored novel results as a readable table using the `tabled` crate,
+//! and writes them as JSON, as an Atom feed, or as an HTML report.
+
+pub mod analysis;
+mod feed;
+mod group;
+mod html;
+pub mod summary;
+
+pub use feed::write_atom_feed;
+pub use html::write_html_report;
+
+use crate::compare::RunDiff;
+use crate::config::GroupBy;
+use crate::models::timestamp::Timestamp;
+use crate::models::{sub_scores, Novel, NovelScore};
+use crate::persist;
+use crate::rejects::RejectLog;
+use crate::scraper::cache::{KindStats, AGE_BUCKETS};
+use analysis::TagReport;
+use summary::ResultsView;
+use crate::stats::{RunMetadata, RunStats, ScoreDistribution};
+use crate::util;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+use tabled::{Table, Tabled};
+
+/// A row in the output table, derived from a `NovelScore`.
+#[derive(Tabled)]
+struct ResultRow {
+    /// Rank position (1-based).
+    #[tabled(rename = "Rank")]
+    rank: usize,
+    /// Novel title.
+    #[tabled(rename = "Title")]
+    title: String,
+    /// Overall score formatted as percentage.
+    #[tabled(rename = "Score")]
+    score: String,
+    /// Novel rating on RoyalRoad.
+    #[tabled(rename = "Rating")]
+    rating: String,
+    /// Page count, "n/a" on sites that don't give one.
+    #[tabled(rename = "Pages")]
+    pages: String,
+    /// Publication status.
+    #[tabled(rename = "Status")]
+    status: String,
+    /// Brief reasoning summary.
+    #[tabled(rename = "Reasoning")]
+    reasoning: String,
+}
+
+/// A row in the timing table, for one pipeline phase.
+#[derive(Tabled)]
+struct TimingRow {
+    #[tabled(rename = "Phase")]
+    phase: String,
+    #[tabled(rename = "Calls")]
+    count: usize,
+    #[tabled(rename = "Total")]
+    total: String,
+    #[tabled(rename = "Average")]
+    average: String,
+    #[tabled(rename = "Slowest")]
+    max: String,
+}
+
+/// A row in the cache table, for one kind of cached response.
+#[derive(Tabled)]
+struct CacheRow {
+    #[tabled(rename = "Kind")]
+    kind: String,
+    #[tabled(rename = "Entries")]
+    entries: usize,
+    #[tabled(rename = "Size")]
+    size: String,
+    #[tabled(rename = "Ages")]
+    ages: String,
+    #[tabled(rename = "Stale")]
+    stale: usize,
+}
+
+/// A row in the rejects table, for one novel the hard filters rejected.
+#[derive(Tabled)]
+struct RejectRow {
+    #[tabled(rename = "ID")]
+    id: u64,
+    #[tabled(rename = "Title")]
+    title: String,
+    #[tabled(rename = "Reasons")]
+    reasons: String,
+}
+
+/// The JSON output document: results plus run statistics.
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    results: ResultsView<'a>,
+    stats: &'a RunStats,
+    /// What produced the results.
+    metadata: &'a RunMetadata,
+    /// Fingerprint of the scoring settings, so later runs can tell whether
+    /// their scores are comparable with these. Also in `metadata`; kept
+    /// here for readers of older files' layout.
+    criteria_hash: &'a str,
+    /// Changes since a previous run, when one was given with `--compare`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comparison: Option<&'a RunDiff>,
+    /// Tag statistics, when requested with `--tag-report`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag_report: Option<&'a TagReport>,
+}
+
+/// Format scored results as a table and print to stdout.
+///
+/// Results should be pre-sorted by score descending. Results scored against
+/// criteria profiles are printed as one table per profile, each split into
+/// a sub-table per group unless `group_by` is `GroupBy::None`. With `quiet`,
+/// nothing is printed when there are no results.
+pub fn print_results(results: &[NovelScore], quiet: bool, group_by: GroupBy) {
+    if results.is_empty() {
+        if !quiet {
+            println!("No novels matched the criteria.");
+        }
+        return;
+    }
+
+    for (profile, group) in group_by_profile(results) {
+        if let Some(name) = profile {
+            println!("\n=== Profile: {} ===", name);
+        }
+        if group_by == GroupBy::None {
+            print_table(&group);
+            continue;
+        }
+        for result_group in group::group_results(group, group_by) {
+            println!("\n--- {} ---", result_group.heading());
+            print_table(&result_group.results);
+        }
+    }
+}
+
+/// Exit status when the run completed but no novel matched.
+pub const EXIT_NO_MATCHES: u8 = 2;
+
+/// The exit status of a completed run: 0 if any result scored at least
+/// `min_score` (or if there are any results, without a threshold), else
+/// [`EXIT_NO_MATCHES`]. Errors exit with 1 when `main` returns them.
+pub fn exit_status(results: &[NovelScore], min_score: Option<f64>) -> u8 {
+    let matched = results
+        .iter()
+        .any(|score| min_score.is_none_or(|min| score.overall_score >= min));
+    if matched {
+        0
+    } else {
+        EXIT_NO_MATCHES
+    }
+}
+
+/// Print results, run statistics, and run metadata to stdout as one JSON
+/// document. Without `full_novel`, each result's novel is written as a
+/// `NovelSummary`.
+pub fn print_json(
+    results: &[NovelScore],
+    full_novel: bool,
+    stats: &RunStats,
+    metadata: &RunMetadata,
+    comparison: Option<&RunDiff>,
+    tag_report: Option<&TagReport>,
+) -> Result<()> {
+    let document = json_document(results, full_novel, stats, metadata, comparison, tag_report)?;
+    println!("{}", document);
+    Ok(())
+}
+
+/// The JSON document [`print_json`] prints.
+pub fn json_document(
+    results: &[NovelScore],
+    full_novel: bool,
+    stats: &RunStats,
+    metadata: &RunMetadata,
+    comparison: Option<&RunDiff>,
+    tag_report: Option<&TagReport>,
+) -> Result<String> {
+    let output = JsonOutput {
+        results: ResultsView {
+            results,
+            full_novel,
+        },
+        stats,
+        metadata,
+        criteria_hash: &metadata.criteria_hash,
+        comparison,
+        tag_report,
+    };
+    persist::to_string_pretty(&output)
+}
+
+/// Print a breakdown of where the run's time went.
+pub fn print_run_stats(stats: &RunStats) {
+    println!("\n=== Timing ===");
+    let rows: Vec<TimingRow> = stats
+        .phases
+        .iter()
+        .map(|(phase, timing)| TimingRow {
+            phase: phase.to_string(),
+            count: timing.count,
+            total: util::format_duration(timing.total),
+            average: util::format_duration(timing.average()),
+            max: match timing.max_novel_id {
+                Some(id) => format!("{} (novel {})", util::format_duration(timing.max), id),
+                None => util::format_duration(timing.max),
+            },
+        })
+        .collect();
+    println!("{}", Table::new(rows));
+
+    let per_novel = stats
+        .average_per_novel()
+        .map(|d| format!(", {} per novel", util::format_duration(d)))
+        .unwrap_or_default();
+    println!(
+        "Total run time: {} for {} novels{}",
+        util::format_duration(stats.total),
+        stats.novels_evaluated,
+        per_novel
+    );
+    if !stats.novels_timed_out.is_empty() {
+        let ids: Vec<String> = stats.novels_timed_out.iter().map(u64::to_string).collect();
+        println!(
+            "Abandoned {} novels over the per-novel time limit: {}",
+            ids.len(),
+            ids.join(", ")
+        );
+    }
+    if !stats.novels_over_budget.is_empty() {
+        let ids: Vec<String> = stats.novels_over_budget.iter().map(u64::to_string).collect();
+        println!(
+            "Skipped enrichments for {} novels over the per-novel request budget: {}",
+            ids.len(),
+            ids.join(", ")
+        );
+    }
+    if !stats.novels_reviews_unparsed.is_empty() {
+        let ids: Vec<String> = stats.novels_reviews_unparsed.iter().map(u64::to_string).collect();
+        println!(
+            "Parsed no reviews for {} novels whose pages show some (the review markup may have \
+             changed): {}",
+            ids.len(),
+            ids.join(", ")
+        );
+    }
+    if stats.cancelled {
+        println!("The run was cancelled; these are the results so far");
+    }
+    if !stats.found_not_evaluated.is_empty() {
+        println!(
+            "Found {} novels by discovery but left them unevaluated (max_discovery_ratio)",
+            stats.found_not_evaluated.len()
+        );
+    }
+    if stats.novels_near_miss > 0 {
+        println!(
+            "Evaluated {} novels that only nearly passed the filters (soft_filters)",
+            stats.novels_near_miss
+        );
+    }
+    if stats.discovery_skipped_low_score > 0 {
+        println!(
+            "Skipped discovery from {} novels scoring below discovery_min_score",
+            stats.discovery_skipped_low_score
+        );
+    }
+
+    let limiting = stats.most_limiting_filters();
+    if !limiting.is_empty() {
+        println!("\nMost limiting filters (novels only that filter rejected):");
+        for (setting, tally) in limiting.iter().take(MOST_LIMITING_SHOWN) {
+            println!(
+                "  {}: {} novel{} ({} rejected in all)",
+                setting,
+                tally.sole_rejections,
+                if tally.sole_rejections == 1 { "" } else { "s" },
+                tally.novels
+            );
+        }
+    }
+
+    if let Some(sampling) = &stats.sampling {
+        println!("\nSampled the queue across strata by {}:", sampling.by);
+        let decimals = if sampling.by == "rating" { 2 } else { 0 };
+        for stratum in &sampling.strata {
+            println!(
+                "  {:.*} to {:.*}: evaluated {} of {}",
+                decimals, stratum.low, decimals, stratum.high, stratum.evaluated, stratum.queued
+            );
+        }
+    }
+}
+
+/// Filters listed under "Most limiting filters" in the run statistics.
+const MOST_LIMITING_SHOWN: usize = 5;
+
+/// Print suggested config changes for the next run, if there are any.
+pub fn print_hints(hints: &[String]) {
+    if hints.is_empty() {
+        return;
+    }
+    println!("\nNext steps:");
+    for hint in hints {
+        println!("  - {}", hint);
+    }
+}
+
+/// Print the entries, size, and ages of each kind of response cached in
+/// `dir`.
+pub fn print_cache_stats(dir: &Path, stats: &[KindStats]) {
+    if stats.is_empty() {
+        println!("No responses are cached in {}.", dir.display());
+        return;
+    }
+    let labels = AGE_BUCKETS.iter().map(|(label, _)| *label).chain(["older"]);
+    let rows: Vec<CacheRow> = stats
+        .iter()
+        .map(|kind| CacheRow {
+            kind: kind.kind.clone(),
+            entries: kind.entries,
+            size: util::format_bytes(kind.bytes),
+            ages: labels
+                .clone()
+                .zip(kind.ages)
+                .filter(|(_, count)| *count > 0)
+                .map(|(label, count)| format!("{}: {}", label, count))
+                .collect::<Vec<_>>()
+                .join(", "),
+            stale: kind.stale,
+        })
+        .collect();
+    println!("=== Response cache: {} ===", dir.display());
+    println!("{}", Table::new(rows));
+    let entries: usize = stats.iter().map(|kind| kind.entries).sum();
+    let bytes: u64 = stats.iter().map(|kind| kind.bytes).sum();
+    println!("Total: {} entries, {}", entries, util::format_bytes(bytes));
+}
+
+/// Print the most recent novels the hard filters rejected, and why.
+pub fn print_rejects(rejects: &RejectLog) {
+    let rows: Vec<RejectRow> = rejects
+        .recent()
+        .map(|rejection| RejectRow {
+            id: rejection.id,
+            title: rejection.title.clone(),
+            reasons: rejection.reasons_text(),
+        })
+        .collect();
+    if rows.is_empty() {
+        println!("\nNo novels were rejected by the hard filters.");
+        return;
+    }
+    println!(
+        "\n=== Rejected ({} most recent of {}) ===",
+        rows.len(),
+        rejects.total()
+    );
+    println!("{}", Table::new(rows));
+}
+
+/// Widest histogram bar, in characters.
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// Print a histogram of the result scores with min/median/mean/max.
+pub fn print_score_histogram(results: &[NovelScore]) {
+    let scores = results.iter().map(|s| s.overall_score);
+    if let Some(dist) = ScoreDistribution::from_scores(scores) {
+        println!("\n=== Score distribution ===");
+        for line in histogram_lines(&dist) {
+            println!("{}", line);
+        }
+    }
+}
+
+/// The lines of a score histogram, bars scaled to the fullest bucket.
+fn histogram_lines(dist: &ScoreDistribution) -> Vec<String> {
+    let widest = dist.buckets.iter().copied().max().unwrap_or(0).max(1);
+    let mut lines: Vec<String> = dist
+        .buckets
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            // Round up so a non-empty bucket always shows
+            let width = (count * HISTOGRAM_WIDTH).div_ceil(widest);
+            let range = format!("{}-{}%", i * 10, (i + 1) * 10);
+            format!("{:>8} | {:<w$} {}", range, "#".repeat(width), count, w = HISTOGRAM_WIDTH)
+        })
+        .collect();
+    lines.push(format!(
+        "min {:.0}%, median {:.0}%, mean {:.0}%, max {:.0}%",
+        dist.min * 100.0,
+        dist.median * 100.0,
+        dist.mean * 100.0,
+        dist.max * 100.0
+    ));
+    lines
+}
+
+/// Split results into groups by profile, in order of each profile's first
+/// appearance. Order within a group is preserved.
+fn group_by_profile(results: &[NovelScore]) -> Vec<(Option<&str>, Vec<&NovelScore>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&NovelScore>)> = Vec::new();
+    for score in results {
+        let profile = score.profile.as_deref();
+        match groups.iter_mut().find(|(p, _)| *p == profile) {
+            Some((_, group)) => group.push(score),
+            None => groups.push((profile, vec![score])),
+        }
+    }
+    groups
+}
+
+/// Print one results table.
+fn print_table(results: &[&NovelScore]) {
+    let rows: Vec<ResultRow> = results
+        .iter()
+        .enumerate()
+        .map(|(i, score)| {
+            // Truncate reasoning to a reasonable length for the table
+            let reasoning = if score.reasoning.len() > 80 {
+                format!("{}...", &score.reasoning[..77])
+            } else {
+                score.reasoning.clone()
+            };
+
+            ResultRow {
+                rank: i + 1,
+                title: if score.near_misses.is_empty() {
+                    score.novel.title.clone()
+                } else {
+                    format!("{} (near miss)", score.novel.title)
+                },
+                score: format_score(score),
+                rating: format!("{:.2}", score.novel.rating),
+                pages: format_pages(&score.novel),
+                status: score.novel.status.to_string(),
+                reasoning,
+            }
+        })
+        .collect();
+
+    let table = Table::new(rows).to_string();
+    println!("\n{}\n", table);
+    println!("Total novels evaluated: {}", results.len());
+}
+
+/// A score as a percentage, with its range as "±" points when it has one
+/// ("72% ±9").
+fn format_score(score: &NovelScore) -> String {
+    let percent = format!("{:.0}%", score.overall_score * 100.0);
+    match score.half_width() {
+        Some(half_width) => format!("{} \u{b1}{:.0}", percent, half_width * 100.0),
+        None => percent,
+    }
+}
+
+/// A novel's page count, or "n/a" on sites that don't give one.
+pub(crate) fn format_pages(novel: &Novel) -> String {
+    match novel.reported_pages() {
+        Some(pages) => pages.to_string(),
+        None => "n/a".to_string(),
+    }
+}
+
+/// Print a detailed breakdown for a single novel score.
+pub fn print_detailed_score(score: &NovelScore) {
+    for line in detailed_score_lines(score) {
+        println!("{}", line);
+    }
+}
+
+/// The lines of a detailed breakdown of a novel score.
+pub fn detailed_score_lines(score: &NovelScore) -> Vec<String> {
+    let novel = &score.novel;
+    let mut lines = vec![format!("=== {} ===", novel.title)];
+    if let Some(profile) = &score.profile {
+        lines.push(format!("Profile: {}", profile));
+    }
+    lines.push(format!("URL: {}", novel.url));
+    lines.push(format!("Author: {}", novel.author));
+    lines.push(format!(
+        "Rating: {:.2} | Pages: {} | Status: {}",
+        novel.rating,
+        format_pages(novel),
+        novel.status
+    ));
+    if let Some(date) = novel.first_chapter_date {
+        lines.push(format!("Started: {}", describe_date(date)));
+    }
+    if let Some(date) = novel.last_chapter_date {
+        lines.push(format!("Last updated: {}", describe_date(date)));
+    }
+    if let Some(words) = novel.word_count_estimate {
+        lines.push(format!("Words (estimated): ~{}", words));
+    }
+    if let Some(ratio) = novel.followers_per_1k_views() {
+        lines.push(format!("Followers per 1k average views: {:.0}", ratio));
+    }
+    if let Some(ratio) = novel.favorites_per_follower() {
+        lines.push(format!("Favorites per follower: {:.2}", ratio));
+    }
+    if !novel.badges.is_empty() {
+        let badges: Vec<String> = novel.badges.iter().map(|badge| badge.to_string()).collect();
+        lines.push(format!("Badges: {}", badges.join(", ")));
+    }
+    lines.push(format!("Fan fiction: {}", if novel.is_fanfiction { "yes" } else { "no" }));
+    lines.push(match novel.ai_content {
+        Some(kind) => format!("AI content: {}", kind),
+        None => "AI content: none labelled".to_string(),
+    });
+    lines.push(match (score.score_low, score.score_high) {
+        (Some(low), Some(high)) => format!(
+            "Overall Score: {:.0}% (range {:.0}%-{:.0}%)",
+            score.overall_score * 100.0,
+            low * 100.0,
+            high * 100.0
+        ),
+        _ => format!("Overall Score: {:.0}%", score.overall_score * 100.0),
+    });
+    if !score.near_misses.is_empty() {
+        lines.push(format!("Near miss: {}", score.near_misses.join("; ")));
+    }
+    lines.push(String::new());
+    lines.push("Sub-scores:".to_string());
+    for (criterion, sub_score) in sub_scores::for_display(&score.sub_scores) {
+        lines.push(format!("  {}: {:.0}%", criterion, sub_score * 100.0));
+    }
+    if !score.evidence.is_empty() {
+        lines.push(String::new());
+        lines.push("Evidence:".to_string());
+        for item in &score.evidence {
+            lines.push(format!(
+                "  [{}] +{:.0}%: \"{}\"",
+                item.source,
+                item.contribution * 100.0,
+                item.snippet
+            ));
+        }
+    }
+    lines.push(String::new());
+    lines.push(format!("Reasoning: {}", score.reasoning));
+    lines.push(String::new());
+    lines
+}
+
+/// Render a time as `YYYY-MM-DD (N days ago)`.
+fn describe_date(date: Timestamp) -> String {
+    format!("{} ({} days ago)", date.date(), date.days_ago())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persist::SavedRun;
+    use crate::models::{test_novel, test_score, Evidence, EvidenceSource, SiteId};
+    use crate::stats::{test_run_metadata, Phase};
+    use std::time::Duration;
+
+    fn score(id: u64, profile: Option<&str>) -> NovelScore {
+        let mut score = test_score(test_novel(id), 0.5);
+        score.profile = profile.map(String::from);
+        score
+    }
+
+    #[test]
+    fn test_group_by_profile() {
+        let results = vec![
+            score(1, Some("cozy")),
+            score(2, Some("grimdark")),
+            score(3, Some("cozy")),
+        ];
+        let groups = group_by_profile(&results);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, Some("cozy"));
+        let cozy_ids: Vec<u64> = groups[0].1.iter().map(|s| s.novel.id).collect();
+        assert_eq!(cozy_ids, vec![1, 3]);
+        assert_eq!(groups[1].0, Some("grimdark"));
+    }
+
+    #[test]
+    fn test_group_without_profiles() {
+        let results = vec![score(1, None), score(2, None)];
+        let groups = group_by_profile(&results);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, None);
+        assert_eq!(groups[0].1.len(), 2);
+    }
+
+    #[test]
+    fn test_format_score_shows_range() {
+        let point = NovelScore {
+            overall_score: 0.72,
+            ..score(1, None)
+        };
+        assert_eq!(format_score(&point), "72%");
+        let ranged = point.with_half_width(0.09);
+        assert_eq!(format_score(&ranged), "72% \u{b1}9");
+        let lines = detailed_score_lines(&ranged);
+        assert!(lines.contains(&"Overall Score: 72% (range 63%-81%)".to_string()), "{:?}", lines);
+    }
+
+    #[test]
+    fn test_pages_are_na_on_sites_without_page_counts() {
+        let mut result = score(1, None);
+        assert_eq!(format_pages(&result.novel), result.novel.pages.to_string());
+        result.novel.site = SiteId::ScribbleHub;
+        result.novel.pages = 0;
+        assert_eq!(format_pages(&result.novel), "n/a");
+        let lines = detailed_score_lines(&result);
+        assert!(lines.iter().any(|line| line.contains("| Pages: n/a |")), "{:?}", lines);
+    }
+
+    #[test]
+    fn test_detailed_score_lists_evidence() {
+        let mut quoted = score(1, None);
+        assert!(!detailed_score_lines(&quoted).contains(&"Evidence:".to_string()));
+        quoted.evidence = vec![Evidence::new(
+            EvidenceSource::Review("Reader".to_string()),
+            "The magic system is great.",
+            0.061,
+        )];
+        let lines = detailed_score_lines(&quoted);
+        assert!(lines.contains(&"Evidence:".to_string()), "{:?}", lines);
+        assert!(
+            lines.contains(&"  [review by Reader] +6%: \"The magic system is great.\"".to_string()),
+            "{:?}",
+            lines
+        );
+    }
+
+    #[test]
+    fn test_json_output_includes_stats() {
+        let results = vec![score(1, None)];
+        let mut stats = RunStats {
+            novels_evaluated: 1,
+            ..Default::default()
+        };
+        stats.record(Phase::Evaluate, Duration::from_millis(250), Some(1));
+        let mut metadata = test_run_metadata("0123456789abcdef", 1_722_546_183);
+        metadata.criteria.prompt = Some("Slow-burn cultivation".to_string());
+        metadata.criteria.min_pages = Some(300);
+
+        let text = persist::to_string_pretty(&JsonOutput {
+            results: ResultsView {
+                results: &results,
+                full_novel: true,
+            },
+            stats: &stats,
+            metadata: &metadata,
+            criteria_hash: &metadata.criteria_hash,
+            comparison: None,
+            tag_report: None,
+        })
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["format_version"], persist::FORMAT_VERSION);
+        assert_eq!(json["results"][0]["novel"]["id"], 1);
+        assert_eq!(json["stats"]["novels_evaluated"], 1);
+        assert_eq!(json["stats"]["phases"]["evaluate"]["count"], 1);
+        assert!(json.get("comparison").is_none());
+        assert!(json.get("tag_report").is_none());
+
+        // The output can be read back for `--compare`
+        let saved: SavedRun = persist::from_str(&text).unwrap();
+        assert_eq!(saved.results[0].novel.id, 1);
+        assert_eq!(saved.criteria_hash.as_deref(), Some("0123456789abcdef"));
+        assert_eq!(json["metadata"]["finished_at"], "2024-08-01T21:03:03Z");
+        assert_eq!(json["metadata"]["total_requests"], 12);
+        let read = saved.metadata.expect("metadata");
+        assert_eq!(read.finished_at, metadata.finished_at);
+        assert_eq!(read.criteria_hash, "0123456789abcdef");
+        assert_eq!(read.criteria.prompt.as_deref(), Some("Slow-burn cultivation"));
+        assert_eq!(read.criteria.min_pages, Some(300));
+        assert_eq!(read.duration_secs, 3.5);
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&metadata).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_histogram_bars_scale_to_widest_bucket() {
+        let dist = ScoreDistribution::from_scores([0.05, 0.55, 0.56, 0.57, 0.58, 1.0]).unwrap();
+        let lines = histogram_lines(&dist);
+        assert_eq!(lines.len(), 11);
+        let bar = |line: &str| line.matches('#').count();
+        assert_eq!(bar(&lines[5]), HISTOGRAM_WIDTH);
+        assert_eq!(bar(&lines[0]), HISTOGRAM_WIDTH / 4);
+        assert_eq!(bar(&lines[1]), 0);
+        assert!(lines[9].starts_with(" 90-100% |"));
+        assert!(lines[9].ends_with(" 1"));
+        assert_eq!(lines[10], "min 5%, median 56%, mean 55%, max 100%");
+    }
+
+    #[test]
+    fn test_exit_status_follows_min_score() {
+        let scored = |overall: f64| NovelScore {
+            overall_score: overall,
+            score_low: None,
+            score_high: None,
+            evidence: Vec::new(),
+            ..score(1, None)
+        };
+        let results = vec![scored(0.8), scored(0.4)];
+        assert_eq!(exit_status(&results, Some(0.6)), 0);
+        assert_eq!(exit_status(&results, Some(0.8)), 0);
+        assert_eq!(exit_status(&results, Some(0.9)), EXIT_NO_MATCHES);
+        assert_eq!(exit_status(&results, None), 0);
+        assert_eq!(exit_status(&[], None), EXIT_NO_MATCHES);
+        assert_eq!(exit_status(&[scored(f64::NAN)], Some(0.1)), EXIT_NO_MATCHES);
+    }
+}
@@ -0,0 +1,274 @@
+//! The lean form of results in JSON output.
+//!
+//! Every result embeds its whole novel, description and chapter titles
+//! included, which makes results files for long runs large. With
+//! `[output] include_full_novel = false`, results are written with a
+//! `NovelSummary` in place of the novel. Results files in either form can be
+//! read back for `--compare` and `browse`.
+
+use crate::models::{sub_scores, Evidence, Novel, NovelScore, NovelStatus, SiteId};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// The parts of a novel kept in lean results output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NovelSummary {
+    #[serde(default)]
+    pub site: SiteId,
+    pub id: u64,
+    pub title: String,
+    pub author: String,
+    pub url: String,
+    pub rating: f64,
+    pub pages: u64,
+    pub status: NovelStatus,
+    pub tags: Vec<String>,
+    pub followers: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover_url: Option<String>,
+}
+
+impl From<&Novel> for NovelSummary {
+    fn from(novel: &Novel) -> Self {
+        Self {
+            site: novel.site,
+            id: novel.id,
+            title: novel.title.clone(),
+            author: novel.author.clone(),
+            url: novel.url.clone(),
+            rating: novel.rating,
+            pages: novel.pages,
+            status: novel.status.clone(),
+            tags: novel.tags.clone(),
+            followers: novel.followers,
+            cover_url: novel.cover_url.clone(),
+        }
+    }
+}
+
+impl From<NovelSummary> for Novel {
+    /// A novel with the summary's fields and everything else empty.
+    fn from(summary: NovelSummary) -> Self {
+        Novel {
+            site: summary.site,
+            id: summary.id,
+            title: summary.title,
+            author: summary.author,
+            author_id: None,
+            author_reputation: None,
+            url: summary.url,
+            slug: None,
+            cover_url: summary.cover_url,
+            description: String::new(),
+            pages: summary.pages,
+            rating: summary.rating,
+            status: summary.status,
+            genres: Vec::new(),
+            tags: summary.tags,
+            chapter_count: 0,
+            chapter_titles: Vec::new(),
+            chapter_urls: Vec::new(),
+            first_chapter_date: None,
+            last_chapter_date: None,
+            followers: summary.followers,
+            favorites: 0,
+            total_views: None,
+            average_views: None,
+            is_fanfiction: false,
+            ai_content: None,
+            badges: Vec::new(),
+            word_count_estimate: None,
+            first_chapter_excerpt: None,
+            reviews: None,
+            also_liked: None,
+            reviews_unparsed: false,
+        }
+    }
+}
+
+/// Results as written to JSON: each with its full novel, or with a
+/// `NovelSummary` when `full_novel` is false.
+pub(crate) struct ResultsView<'a> {
+    pub results: &'a [NovelScore],
+    pub full_novel: bool,
+}
+
+/// A result with its novel summarized.
+#[derive(Serialize)]
+struct SummaryScore<'a> {
+    novel: NovelSummary,
+    overall_score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score_low: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    score_high: Option<f64>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    evidence: &'a [Evidence],
+    sub_scores: &'a BTreeMap<String, f64>,
+    reasoning: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<&'a str>,
+}
+
+impl Serialize for ResultsView<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.full_novel {
+            return self.results.serialize(serializer);
+        }
+        let mut seq = serializer.serialize_seq(Some(self.results.len()))?;
+        for score in self.results {
+            seq.serialize_element(&SummaryScore {
+                novel: NovelSummary::from(&score.novel),
+                overall_score: score.overall_score,
+                score_low: score.score_low,
+                score_high: score.score_high,
+                evidence: &score.evidence,
+                sub_scores: &score.sub_scores,
+                reasoning: &score.reasoning,
+                profile: score.profile.as_deref(),
+            })?;
+        }
+        seq.end()
+    }
+}
+
+/// A novel read from a results file, in either form.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedNovel {
+    Full(Box<Novel>),
+    Summary(NovelSummary),
+}
+
+/// A result read from a results file.
+#[derive(Deserialize)]
+struct SavedScore {
+    novel: SavedNovel,
+    overall_score: f64,
+    #[serde(default)]
+    score_low: Option<f64>,
+    #[serde(default)]
+    score_high: Option<f64>,
+    #[serde(default)]
+    evidence: Vec<Evidence>,
+    #[serde(default, deserialize_with = "sub_scores::deserialize")]
+    sub_scores: BTreeMap<String, f64>,
+    #[serde(default)]
+    reasoning: String,
+    #[serde(default)]
+    profile: Option<String>,
+}
+
+/// Read results written with full novels or with summaries. Summarized
+/// novels come back with only the summary's fields filled in.
+pub(crate) fn deserialize_results<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<NovelScore>, D::Error> {
+    let saved = Vec::<SavedScore>::deserialize(deserializer)?;
+    Ok(saved
+        .into_iter()
+        .map(|score| NovelScore {
+            novel: match score.novel {
+                SavedNovel::Full(novel) => *novel,
+                SavedNovel::Summary(summary) => summary.into(),
+            },
+            overall_score: score.overall_score,
+            score_low: score.score_low,
+            score_high: score.score_high,
+            evidence: score.evidence,
+            sub_scores: score.sub_scores,
+            reasoning: score.reasoning,
+            profile: score.profile,
+            near_misses: Vec::new(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compare::diff_runs;
+    use crate::models::{test_novel, test_score};
+    use crate::persist::SavedRun;
+    use serde_json::json;
+
+    fn score(id: u64, overall: f64) -> NovelScore {
+        let mut novel = test_novel(id);
+        novel.description = "A long blurb that lean output leaves out.".to_string();
+        novel.chapter_titles = vec!["1 - Rabbit".to_string()];
+        novel.tags = vec!["Fantasy".to_string()];
+        let mut score = test_score(novel, overall);
+        score.sub_scores = BTreeMap::from([("rating".to_string(), 0.8)]);
+        score.reasoning = "Fits.".to_string();
+        score.profile = Some("cozy".to_string());
+        score
+    }
+
+    fn saved(results: &[NovelScore], full_novel: bool) -> SavedRun {
+        let view = ResultsView {
+            results,
+            full_novel,
+        };
+        let json = json!({ "results": view, "criteria_hash": "abc" });
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_summary_leaves_out_bulky_fields() {
+        let results = [score(1, 0.9)];
+        let view = ResultsView {
+            results: &results,
+            full_novel: false,
+        };
+        let json = serde_json::to_value(&view).unwrap();
+        let novel = json[0]["novel"].as_object().unwrap();
+        let mut keys: Vec<&str> = novel.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            [
+                "author", "followers", "id", "pages", "rating", "site", "status", "tags", "title",
+                "url"
+            ]
+        );
+        assert_eq!(json[0]["overall_score"], 0.9);
+        assert_eq!(json[0]["sub_scores"]["rating"], 0.8);
+        assert_eq!(json[0]["profile"], "cozy");
+
+        let full = ResultsView {
+            results: &results,
+            full_novel: true,
+        };
+        let json = serde_json::to_value(&full).unwrap();
+        assert_eq!(json, serde_json::to_value(&results).unwrap());
+    }
+
+    #[test]
+    fn test_both_forms_read_back() {
+        let results = [score(1, 0.9), score(2, 0.5)];
+        let full = saved(&results, true);
+        let lean = saved(&results, false);
+        assert_eq!(full.results[0].novel.description, results[0].novel.description);
+        assert!(lean.results[0].novel.description.is_empty());
+        for run in [&full, &lean] {
+            let ids: Vec<u64> = run.results.iter().map(|s| s.novel.id).collect();
+            assert_eq!(ids, vec![1, 2]);
+            assert_eq!(run.results[1].overall_score, 0.5);
+            assert_eq!(run.results[0].profile.as_deref(), Some("cozy"));
+            assert_eq!(run.results[0].novel.tags, vec!["Fantasy"]);
+        }
+    }
+
+    #[test]
+    fn test_lean_results_drive_comparison() {
+        let old = saved(&[score(1, 0.9), score(2, 0.5)], false);
+        let new = saved(&[score(2, 0.8), score(3, 0.7)], true);
+        let diff = diff_runs(&old, &new);
+        assert_eq!(diff.score_changes.len(), 1);
+        assert_eq!(diff.score_changes[0].id, 2);
+        assert_eq!(diff.entered[0].id, 3);
+        assert_eq!(diff.dropped[0].id, 1);
+        assert_eq!(diff.dropped[0].title, "Novel 1");
+    }
+}
@@ -0,0 +1,323 @@
+//! Documents novel-finder writes and later reads back, possibly from another
+//! version: results files (`--json` output and marked results from `browse`),
+//! the watch history, and datasets of scraped novels (`--export-dataset`, read
+//! by `rescore`).
+//!
+//! Every document carries a `format_version`. The compatibility policy:
+//!
+//! - Documents from a newer format are refused with a message to upgrade,
+//!   rather than read with fields silently dropped.
+//! - Documents from an older format are migrated to the current one before
+//!   they are deserialized. Documents from before versioning have no
+//!   `format_version` and count as version 0.
+//! - Fields added within a format version must be optional or have serde
+//!   defaults, so documents written before them still read.
+
+use crate::models::timestamp::Timestamp;
+use crate::models::{Novel, NovelKey, NovelScore, Review, SiteId};
+use crate::stats::RunMetadata;
+use anyhow::{bail, Context, Result};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+/// The format version this build writes, and the newest it reads.
+///
+/// Version 2 keys the watch history by site as well as fiction ID.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// A document as written: the format version, then the contents' fields.
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    format_version: u32,
+    #[serde(flatten)]
+    contents: &'a T,
+}
+
+/// Serialize a document with the current format version.
+pub fn to_string<T: Serialize>(contents: &T) -> Result<String> {
+    Ok(serde_json::to_string(&versioned(contents))?)
+}
+
+/// Serialize a document with the current format version, indented.
+pub fn to_string_pretty<T: Serialize>(contents: &T) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&versioned(contents))?)
+}
+
+fn versioned<T>(contents: &T) -> Versioned<'_, T> {
+    Versioned {
+        format_version: FORMAT_VERSION,
+        contents,
+    }
+}
+
+/// Parse a document written by this or an earlier version, migrating it to
+/// the current format first.
+pub fn from_str<T: DeserializeOwned>(content: &str) -> Result<T> {
+    let mut document: Value = serde_json::from_str(content)?;
+    let version = match document.get("format_version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .context("format_version is not a version number")?,
+    };
+    if version > FORMAT_VERSION {
+        bail!(
+            "written by a newer novel-finder (format version {}, this version reads up to {}); \
+             upgrade novel-finder to read it",
+            version,
+            FORMAT_VERSION
+        );
+    }
+    migrate(&mut document, version);
+    if let Some(fields) = document.as_object_mut() {
+        fields.remove("format_version");
+    }
+    Ok(serde_json::from_value(document)?)
+}
+
+/// Bring a document from format `version` up to the current format.
+fn migrate(document: &mut Value, version: u32) {
+    if version == 0 {
+        // Novels in unversioned results files may predate fields that have
+        // no serde default
+        let results = document.get_mut("results").and_then(Value::as_array_mut);
+        for result in results.into_iter().flatten() {
+            let novel = result.get_mut("novel").and_then(Value::as_object_mut);
+            let Some(novel) = novel.filter(|novel| novel.contains_key("description")) else {
+                continue;
+            };
+            for (field, default) in [("genres", json!([])), ("is_fanfiction", json!(false))] {
+                novel.entry(field).or_insert(default);
+            }
+        }
+    }
+    if version < 2 {
+        // The watch history held bare fiction IDs, all RoyalRoad's
+        let reported = document.get_mut("reported").and_then(Value::as_array_mut);
+        for id in reported.into_iter().flatten() {
+            if id.is_u64() {
+                *id = json!({ "site": SiteId::RoyalRoad, "id": id });
+            }
+        }
+    }
+}
+
+/// The parts of a results file needed to read it back: `--json` output or
+/// marked results.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SavedRun {
+    /// Scored results, ranked best first. Novels written as summaries come
+    /// back with only the summary's fields.
+    #[serde(deserialize_with = "crate::output::summary::deserialize_results")]
+    pub results: Vec<NovelScore>,
+    /// Hash of the criteria that produced the results, if recorded.
+    #[serde(default)]
+    pub criteria_hash: Option<String>,
+    /// What produced the results, if recorded.
+    #[serde(default)]
+    pub metadata: Option<RunMetadata>,
+}
+
+/// Results marked in the browser, written as a results file.
+#[derive(Serialize)]
+pub struct MarkedResults<'a> {
+    pub results: &'a [&'a NovelScore],
+}
+
+/// The watch history file: the novels already reported.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HistoryFile {
+    pub reported: BTreeSet<NovelKey>,
+}
+
+/// One scraped novel in a dataset, with the reviews fetched for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetEntry {
+    #[serde(flatten)]
+    pub novel: Novel,
+    /// The reviews, if any were fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviews: Option<Vec<Review>>,
+    /// When the novel was scraped, if recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scraped_at: Option<Timestamp>,
+    /// Whether the run that saved the novel went on to evaluate it, rather
+    /// than rejecting it with its hard filters.
+    #[serde(default)]
+    pub evaluated: bool,
+}
+
+/// A dataset written as one JSON document.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DatasetFile {
+    pub novels: Vec<DatasetEntry>,
+}
+
+/// The first line of a dataset written as JSON Lines, before one
+/// [`DatasetEntry`] per line. It only carries the format version.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DatasetHeader {}
+
+/// Parse a dataset in any of the forms it is written in: a [`DatasetFile`]
+/// document, JSON Lines starting with a [`DatasetHeader`], or a bare array
+/// of entries from before datasets were versioned.
+///
+/// A JSON Lines dataset cut off mid-line, as by a crash while it was being
+/// written, reads up to its last complete entry.
+pub fn dataset_from_str(content: &str) -> Result<Vec<DatasetEntry>> {
+    let content = content.trim_start();
+    if content.starts_with('[') {
+        return Ok(serde_json::from_str(content)?);
+    }
+    // A whole document parses as one value; JSON Lines has trailing lines
+    if serde_json::from_str::<IgnoredAny>(content).is_ok() {
+        return Ok(from_str::<DatasetFile>(content)?.novels);
+    }
+    let mut lines = content.lines().enumerate();
+    let (_, header) = lines.next().context("empty dataset")?;
+    from_str::<DatasetHeader>(header).context("line 1 is not a dataset header")?;
+    let complete = content.ends_with('\n');
+    let mut entries = Vec::new();
+    let mut lines = lines.filter(|(_, line)| !line.trim().is_empty()).peekable();
+    while let Some((index, line)) = lines.next() {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) if lines.peek().is_none() && !complete => {
+                tracing::warn!("Ignoring incomplete last dataset line {}: {}", index + 1, e);
+            }
+            Err(e) => bail!("line {}: {}", index + 1, e),
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> String {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/persist/testdata");
+        path.push(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_writes_current_version_first() {
+        let history = HistoryFile {
+            reported: BTreeSet::from([
+                NovelKey::new(SiteId::ScribbleHub, 1),
+                NovelKey::new(SiteId::RoyalRoad, 3),
+            ]),
+        };
+        let json = to_string(&history).unwrap();
+        assert_eq!(
+            json,
+            r#"{"format_version":2,"reported":[{"site":"royalroad","id":3},"#.to_string()
+                + r#"{"site":"scribblehub","id":1}]}"#
+        );
+        let read: HistoryFile = from_str(&json).unwrap();
+        assert_eq!(read.reported, history.reported);
+    }
+
+    #[test]
+    fn test_reads_unversioned_history() {
+        let history: HistoryFile = from_str(&fixture("history_v0.json")).unwrap();
+        let royal_road = |id| NovelKey::new(SiteId::RoyalRoad, id);
+        assert_eq!(history.reported, BTreeSet::from([royal_road(90435), royal_road(89877)]));
+    }
+
+    #[test]
+    fn test_reads_history_of_bare_ids_as_royal_road() {
+        let history: HistoryFile = from_str(r#"{"format_version":1,"reported":[7]}"#).unwrap();
+        assert_eq!(history.reported, BTreeSet::from([NovelKey::new(SiteId::RoyalRoad, 7)]));
+    }
+
+    #[test]
+    fn test_reads_unversioned_results() {
+        // Written before novels had genres, a fan fiction flag, chapter URLs,
+        // or slugs, and before sub-scores were renamed
+        let run: SavedRun = from_str(&fixture("results_v0.json")).unwrap();
+        assert_eq!(run.results.len(), 2);
+        assert_eq!(run.criteria_hash, None);
+        let novel = &run.results[0].novel;
+        assert_eq!((novel.id, novel.title.as_str()), (90435, "Bunny Girl Evolution"));
+        assert!(novel.genres.is_empty() && !novel.is_fanfiction);
+        assert_eq!(novel.chapter_titles.len(), 2);
+        assert!(novel.chapter_urls.is_empty() && novel.slug.is_none());
+        assert_eq!(run.results[1].overall_score, 0.4);
+        let sub_scores: Vec<&str> = run.results[0].sub_scores.keys().map(String::as_str).collect();
+        assert_eq!(sub_scores, ["keyword_description", "rating"]);
+        assert_eq!((run.results[0].score_low, run.results[0].score_high), (None, None));
+    }
+
+    fn dataset_entry(id: u64) -> DatasetEntry {
+        DatasetEntry {
+            novel: crate::models::test_novel(id),
+            reviews: None,
+            scraped_at: "2025-01-07T10:09:50Z".parse().ok(),
+            evaluated: id.is_multiple_of(2),
+        }
+    }
+
+    #[test]
+    fn test_dataset_forms() {
+        let file = DatasetFile {
+            novels: vec![dataset_entry(1), dataset_entry(2)],
+        };
+        let document = to_string(&file).unwrap();
+        let first = r#"{"format_version":2,"novels":[{"site":"royalroad","id":1,"#;
+        assert!(document.starts_with(first));
+        let ids = |entries: Vec<DatasetEntry>| -> Vec<u64> {
+            entries.iter().map(|entry| entry.novel.id).collect()
+        };
+        assert_eq!(ids(dataset_from_str(&document).unwrap()), [1, 2]);
+
+        let lines = format!(
+            "{}\n{}\n{}\n",
+            to_string(&DatasetHeader {}).unwrap(),
+            serde_json::to_string(&dataset_entry(1)).unwrap(),
+            serde_json::to_string(&dataset_entry(2)).unwrap()
+        );
+        let read = dataset_from_str(&lines).unwrap();
+        assert_eq!(read[1].scraped_at, file.novels[1].scraped_at);
+        assert_eq!((read[0].evaluated, read[1].evaluated), (false, true));
+        assert_eq!(ids(read), [1, 2]);
+
+        let unversioned = serde_json::to_string(&file.novels).unwrap();
+        assert_eq!(ids(dataset_from_str(&unversioned).unwrap()), [1, 2]);
+    }
+
+    #[test]
+    fn test_dataset_lines_cut_off_mid_entry() {
+        let lines = format!(
+            "{}\n{}\n",
+            to_string(&DatasetHeader {}).unwrap(),
+            serde_json::to_string(&dataset_entry(1)).unwrap()
+        );
+        let cut_off = format!("{}{{\"id\":2,\"tit", lines);
+        assert_eq!(dataset_from_str(&cut_off).unwrap().len(), 1);
+
+        // A bad line that isn't the last is an error
+        let corrupt = format!("{}not json\n{}", lines, lines.lines().nth(1).unwrap());
+        let err = dataset_from_str(&corrupt).unwrap_err().to_string();
+        assert!(err.starts_with("line 3:"), "{}", err);
+
+        let newer = r#"{"format_version":3}"#.to_string() + "\n" + &lines;
+        assert!(dataset_from_str(&newer).is_err());
+    }
+
+    #[test]
+    fn test_refuses_newer_version() {
+        let newer = r#"{"format_version":3,"reported":[1]}"#;
+        let err = from_str::<HistoryFile>(newer).unwrap_err().to_string();
+        assert!(err.contains("newer novel-finder (format version 3"), "{}", err);
+
+        let bad = r#"{"format_version":"one","reported":[1]}"#;
+        assert!(from_str::<HistoryFile>(bad).is_err());
+    }
+}
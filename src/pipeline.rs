@@ -3,165 +3,817 @@
 //! Ties together seed gathering, the processing queue, evaluation,
 //! discovery, and result collection into a single processing flow.
 
-use crate::config::{AppConfig, EvalMode, SeedSource};
-use crate::discovery::also_liked::AlsoLikedDiscovery;
+use crate::cancel::CancellationToken;
+use crate::clock::RunClock;
+use crate::config::{
+    AppConfig, DatasetFilter, DiscoveryConfig, EvalMode, LlmEvalConfig, RandomSeeds, Sampling,
+    SeedSource,
+};
 use crate::discovery::DiscoverySource;
-use crate::eval::llm::LlmEvaluator;
-use crate::eval::local::LocalEvaluator;
+use crate::eval::filter::{near_miss_penalty, FilterReason};
+use crate::eval::text::DescriptionCleaner;
 use crate::eval::Evaluator;
-use crate::models::{NovelScore, StopCondition};
+use crate::export::dataset::DatasetWriter;
+use crate::models::{
+    rank_order, select_reviews, Criteria, Novel, NovelKey, NovelScore, Review, StopCondition,
+};
+use crate::models::timestamp::Timestamp;
+use crate::observer::PipelineObserver;
 use crate::queue::NovelQueue;
-use crate::scraper::RoyalRoadClient;
-use anyhow::Result;
+use crate::registry::{DiscoveryContext, Registry};
+use crate::rejects::{RejectLog, Rejection};
+use crate::scraper::author::AuthorCache;
+use crate::scraper::budget::{over_budget_step, BudgetGuard};
+use crate::scraper::cache::ResponseCache;
+use crate::scraper::covers;
+use crate::scraper::reviews::{is_parse_failure, MAX_REVIEWS};
+use crate::scraper::search::SearchQuery;
+use crate::scraper::{is_challenged, HttpFetch, RoyalRoadClient};
+use crate::site::Site;
+use crate::stats::{FoundNovel, Phase, RunMetadata, RunStats, ScoreDistribution};
+use crate::util;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Instant;
+use tracing::span::EnteredSpan;
+
+/// Maximum characters of the first chapter kept for evaluation.
+pub(crate) const FIRST_CHAPTER_EXCERPT_CHARS: usize = 8_000;
 
 /// The main processing pipeline that orchestrates the full novel-finding flow.
 pub struct Pipeline {
     /// Application configuration.
     config: AppConfig,
-    /// Shared HTTP client for RoyalRoad scraping.
-    client: Arc<RoyalRoadClient>,
+    /// Shared HTTP client for scraping.
+    client: Arc<dyn HttpFetch>,
+    /// The site novels are scraped from.
+    site: Arc<dyn Site>,
     /// The evaluator to use for scoring novels.
     evaluator: Box<dyn Evaluator>,
-    /// Optional discovery source for finding related novels.
-    discovery: Option<Box<dyn DiscoverySource>>,
+    /// Sources for finding novels related to each evaluated one.
+    discovery: Vec<Discoverer>,
     /// The processing queue.
     queue: NovelQueue,
+    /// Strips author-note boilerplate from descriptions before evaluation.
+    cleaner: DescriptionCleaner,
+    /// Author fiction lists fetched so far this run.
+    author_cache: AuthorCache,
+    /// Timing and counts for the current run.
+    stats: RunStats,
+    /// The stratum of each seed, with stratified sampling, as an index into
+    /// the run stats' strata.
+    seed_strata: HashMap<NovelKey, usize>,
+    /// The finish time written into run metadata, for deterministic runs.
+    pinned_time: Option<Timestamp>,
+    /// Novels that failed the hard filters.
+    rejects: RejectLog,
+    /// Told about each step of the run, in the order they were added.
+    observers: Vec<Box<dyn PipelineObserver>>,
+    /// Stops the run early, with the results so far, once cancelled.
+    cancel: CancellationToken,
+    /// The time since the run started, against its time limit.
+    clock: RunClock,
 }
 
-impl Pipeline {
-    /// Build a new pipeline from the given configuration.
-    pub fn new(config: AppConfig) -> Result<Self> {
-        let client = Arc::new(RoyalRoadClient::new(Duration::from_millis(1000))?);
-
-        // Build the evaluator based on config
-        let evaluator: Box<dyn Evaluator> = match &config.eval_mode {
-            EvalMode::Local => Box::new(LocalEvaluator::new()),
-            EvalMode::Llm {
-                api_key,
-                model,
-                endpoint,
-            } => Box::new(LlmEvaluator::new(
-                api_key.clone(),
-                model.clone(),
-                endpoint.clone(),
-            )),
-        };
+/// Settings for a deterministic run (`--deterministic`), whose output is
+/// the same every time it runs over the same pages.
+#[derive(Debug, Clone, Default)]
+pub struct Deterministic {
+    /// Seed for random seed sampling and stratified sampling of the queue.
+    /// Without one, the configured `rng_seed` or `sampling_seed` is used,
+    /// or 0.
+    pub seed: Option<u64>,
+    /// The finish time written into run metadata. Without one, the time the
+    /// newest cached response was fetched is used.
+    pub timestamp: Option<Timestamp>,
+}
 
-        // Build discovery source if enabled
-        let discovery: Option<Box<dyn DiscoverySource>> = if config.discovery_enabled {
-            Some(Box::new(AlsoLikedDiscovery::new(
-                Arc::clone(&client),
-                config.criteria.clone(),
-            )))
-        } else {
-            None
+/// Build the evaluator the config asks for, from the built-in modes.
+pub fn build_evaluator(config: &AppConfig) -> Result<Box<dyn Evaluator>> {
+    Registry::default().build_evaluator(config)
+}
+
+/// Build the rate-limited RoyalRoad client the config's scraper settings
+/// ask for.
+pub fn build_client(config: &AppConfig) -> Result<RoyalRoadClient> {
+    RoyalRoadClient::new(
+        config.scraper.rate_limits,
+        config.scraper.request_timeout,
+        config.scraper.max_response_bytes,
+        config.scraper.ignore_robots,
+        &config.scraper.proxy,
+    )
+}
+
+/// Builds a [`Pipeline`] with evaluators and discovery sources beyond the
+/// built-in ones, which configs can then name.
+#[derive(Default)]
+pub struct PipelineBuilder {
+    registry: Registry,
+    client: Option<Arc<dyn HttpFetch>>,
+}
+
+impl PipelineBuilder {
+    /// Make `eval.mode = "<name>"` build its evaluator with `factory`,
+    /// which gets the `[eval]` table as written. The evaluator's own settings
+    /// go under `[eval.<name>]`, which the config leaves unchecked.
+    pub fn register_evaluator<F>(mut self, name: &str, factory: F) -> Self
+    where
+        F: Fn(&toml::Table, &AppConfig) -> Result<Box<dyn Evaluator>> + 'static,
+    {
+        self.registry.register_evaluator(name, Box::new(factory));
+        self
+    }
+
+    /// Make `[[run.discovery]]` entries with `kind = "<kind>"` build their
+    /// source with `factory`, which gets the entry as written.
+    pub fn register_discovery<F>(mut self, kind: &str, factory: F) -> Self
+    where
+        F: Fn(&toml::Table, &DiscoveryContext) -> Result<Box<dyn DiscoverySource>> + 'static,
+    {
+        self.registry.register_discovery(kind, Box::new(factory));
+        self
+    }
+
+    /// Fetch pages through `client`, such as a fake client serving canned
+    /// pages in tests, instead of one built from the scraper settings.
+    pub fn client(mut self, client: Arc<dyn HttpFetch>) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Build the pipeline for `config`. Fails if it names an evaluator or
+    /// discovery source that isn't registered.
+    pub fn build(self, config: AppConfig) -> Result<Pipeline> {
+        let client = match self.client {
+            Some(client) => client,
+            None => Arc::new(build_client(&config)?),
         };
+        let site = config.site.site();
+        let cleaner = DescriptionCleaner::new(&config.description_strip_patterns)?;
+        let evaluator = self.registry.build_evaluator(&config)?;
+        let clock = RunClock::system(&config.stop_condition);
+
+        // Build the configured discovery sources, each with its own settings
+        let discovery = config
+            .discovery
+            .iter()
+            .map(|entry| {
+                let source = self.registry.build_discovery(&config, entry, &client, &site)?;
+                Ok(Discoverer::new(entry, source))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        Ok(Self {
+        Ok(Pipeline {
             config,
             client,
+            site,
             evaluator,
             discovery,
             queue: NovelQueue::new(),
+            cleaner,
+            author_cache: AuthorCache::new(),
+            stats: RunStats::default(),
+            seed_strata: HashMap::new(),
+            pinned_time: None,
+            rejects: RejectLog::default(),
+            observers: Vec::new(),
+            cancel: CancellationToken::new(),
+            clock,
         })
     }
+}
+
+impl Pipeline {
+    /// Build a new pipeline from the given configuration.
+    pub fn new(config: AppConfig) -> Result<Self> {
+        Self::builder().build(config)
+    }
+
+    /// Build a pipeline that fetches pages through `client`, such as a fake
+    /// client serving canned pages in tests.
+    pub fn with_client(config: AppConfig, client: Arc<dyn HttpFetch>) -> Result<Self> {
+        Self::builder().client(client).build(config)
+    }
+
+    /// Start building a pipeline with evaluators or discovery sources of
+    /// its own.
+    pub fn builder() -> PipelineBuilder {
+        PipelineBuilder::default()
+    }
+
+    /// Make runs deterministic: random choices use a fixed seed, timings are
+    /// left out of the stats, and the run metadata carries a fixed finish
+    /// time. Fails if the config stops anything on the clock.
+    pub fn make_deterministic(&mut self, settings: &Deterministic) -> Result<()> {
+        if self.config.stop_condition.time_limit().is_some() {
+            anyhow::bail!(
+                "--deterministic can't be used with a max_time stop condition; \
+                 use max_novels or empty_queue"
+            );
+        }
+        if self.config.max_time_per_novel.is_some() {
+            anyhow::bail!("--deterministic can't be used with [run] max_seconds_per_novel");
+        }
+        if self.config.seed_time_budget.is_some() {
+            anyhow::bail!("--deterministic can't be used with [run] seed_time_budget");
+        }
+        if let SeedSource::Random(seeds) = &mut self.config.seed_source {
+            seeds.rng_seed = Some(settings.seed.or(seeds.rng_seed).unwrap_or(0));
+        }
+        if let Sampling::Stratified(sampling) = &mut self.config.sampling {
+            sampling.rng_seed = Some(settings.seed.or(sampling.rng_seed).unwrap_or(0));
+        }
+        let timestamp = match settings.timestamp {
+            Some(timestamp) => timestamp,
+            None => self
+                .config
+                .scraper
+                .cache_dir
+                .as_ref()
+                .and_then(|dir| {
+                    ResponseCache::new(dir, self.config.scraper.cache_ttl).newest_fetch_time()
+                })
+                .map(Timestamp::from_system_time)
+                .context(
+                    "--deterministic needs --timestamp when there are no cached responses \
+                     to take the time from",
+                )?,
+        };
+        self.pinned_time = Some(timestamp);
+        Ok(())
+    }
+
+    /// Write every novel the run scrapes, with its reviews, to `path` (JSON
+    /// Lines for a `.jsonl` path, else one JSON document) as the run goes.
+    /// In a deterministic run, call [`make_deterministic`](Self::make_deterministic)
+    /// first, so the novels are stamped with its fixed time.
+    pub fn export_dataset_to(&mut self, path: &Path) -> Result<()> {
+        let dataset = DatasetWriter::create(path)?.with_scraped_at(self.pinned_time);
+        self.add_observer(Box::new(dataset));
+        Ok(())
+    }
+
+    /// Stop runs early once `token` is cancelled. The run finishes the novel
+    /// under way and returns the results so far, with
+    /// [`RunStats::cancelled`] set.
+    pub fn cancel_with(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    /// Tell `observer` about each step of every run from now on.
+    pub fn add_observer(&mut self, observer: Box<dyn PipelineObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Write every novel the hard filters reject to `path` (CSV, or JSON
+    /// for a `.json` path) as the run goes.
+    pub fn log_rejects_to(&mut self, path: &Path) -> Result<()> {
+        self.rejects = RejectLog::to_file(path)?;
+        Ok(())
+    }
 
     /// Run the full pipeline and return scored results.
     pub fn run(&mut self) -> Result<Vec<NovelScore>> {
         tracing::info!("Starting novel-finder pipeline");
+        self.clock.restart(&self.config.stop_condition);
+        self.stats = RunStats::default();
+        self.seed_strata.clear();
 
         // Step 1: Gather seed novels
+        let started = self.clock.now();
         self.gather_seeds()?;
+        self.stats
+            .record(Phase::SeedGathering, self.clock.since(started), None);
         tracing::info!("Seeded queue with {} novels", self.queue.len());
+        let seeded = self.queue.len();
+        if let Sampling::Stratified(sampling) = &self.config.sampling {
+            let (stats, seed_strata) = crate::sampling::sample_queue(&mut self.queue, sampling);
+            tracing::info!(
+                "Sampling the queue across {} strata by {}",
+                stats.strata.len(),
+                stats.by
+            );
+            self.stats.sampling = Some(stats);
+            self.seed_strata = seed_strata;
+        }
+        self.notify(|observer| observer.on_seeded(seeded))?;
+        let seed_keys: HashSet<NovelKey> = self.queue.queued().map(Novel::key).collect();
 
         // Step 2: Process queue until stop condition
         let mut results: Vec<NovelScore> = Vec::new();
-        let start_time = Instant::now();
-
-        while let Some(novel) = self.queue.pop() {
-            // Check stop condition
-            if self.should_stop(&results, start_time) {
+        let mut evaluated = 0;
+        let (mut seeds_evaluated, mut discovered_evaluated) = (0, 0);
+        'novels: while let Some(mut novel) = self.queue.pop() {
+            // Check stop condition, leaving the novel queued for --export-queue
+            if self.should_stop(evaluated) {
                 tracing::info!("Stop condition reached, finishing pipeline");
+                self.queue.push_front(novel);
+                break;
+            }
+            if self.cancel.is_cancelled() {
+                tracing::warn!(
+                    "Run cancelled, finishing with the {} results so far",
+                    results.len()
+                );
+                self.stats.cancelled = true;
+                self.queue.push_front(novel);
+                break;
+            }
+            let streak = self.client.challenge_streak();
+            if streak >= self.config.scraper.max_challenge_streak {
+                tracing::error!(
+                    "The last {} responses were Cloudflare challenge pages; stopping early \
+                     with the {} results so far",
+                    streak,
+                    results.len()
+                );
+                self.queue.push_front(novel);
                 break;
             }
 
+            let _novel_span = tracing::info_span!("novel", novel_id = novel.id).entered();
             tracing::info!("Processing novel: {} (ID: {})", novel.title, novel.id);
 
-            // Pre-filter check
-            if !self.evaluator.pre_filter(&novel, &self.config.criteria) {
+            // Each step checks the deadline before it starts rather than
+            // being interrupted, so one under way can overshoot it by up to
+            // a request timeout
+            let deadline = self.clock.deadline(self.config.max_time_per_novel);
+            let clock = self.clock.clone();
+            let past_deadline = || clock.is_past(deadline);
+            // Optional enrichments the request budget can't cover are skipped
+            let client = Arc::clone(&self.client);
+            let budget = BudgetGuard::new(client.as_ref(), self.config.max_requests_per_novel);
+            let mut skipped: Vec<&'static str> = Vec::new();
+
+            // Pre-filter check (a novel passing any profile's filters continues)
+            let phase = enter_phase("filter");
+            if let Some(reasons) = self.rejection_reasons(&novel) {
                 tracing::info!("Novel '{}' failed pre-filter, skipping", novel.title);
+                self.reject(&novel, reasons)?;
                 continue;
             }
 
-            // Scrape reviews for evaluation
-            let reviews =
-                crate::scraper::reviews::scrape_reviews(&self.client, novel.id, 10)?;
+            // Keep discovered novels to their configured share of the run
+            let is_seed = seed_keys.contains(&novel.key());
+            if !is_seed
+                && discovery_ratio_reached(
+                    self.config.max_discovery_ratio,
+                    seeds_evaluated,
+                    discovered_evaluated,
+                )
+            {
+                tracing::info!(
+                    "Not evaluating discovered novel '{}': max_discovery_ratio reached",
+                    novel.title
+                );
+                self.stats.found_not_evaluated.push(FoundNovel::from(&novel));
+                continue;
+            }
 
-            // Evaluate
-            let score =
-                self.evaluator
-                    .evaluate(&novel, &reviews, &self.config.criteria)?;
-            tracing::info!(
-                "Novel '{}' scored {:.2}",
-                novel.title,
-                score.overall_score
+            drop(phase);
+
+            // Optionally estimate the word count from sample chapters, then
+            // re-check the filters now that word-count limits can apply.
+            // Novels seeded from a dataset may already carry this and the
+            // other enrichments, which aren't fetched again.
+            let phase = enter_phase("enrich");
+            if self.config.scraper.estimate_word_count && novel.word_count_estimate.is_none() {
+                let started = self.clock.now();
+                let estimate = crate::scraper::chapter::estimate_word_count(
+                    self.site.as_ref(),
+                    self.client.as_ref(),
+                    &novel,
+                    &budget,
+                );
+                self.stats
+                    .record(Phase::NovelScrape, self.clock.since(started), Some(novel.id));
+                match estimate {
+                    Ok(words) => {
+                        tracing::debug!("Novel '{}' estimated at ~{} words", novel.title, words);
+                        novel.word_count_estimate = Some(words);
+                    }
+                    Err(e) => enrichment_failed(&novel, "Word count estimation", &e, &mut skipped),
+                }
+
+                if let Some(reasons) = self.rejection_reasons(&novel) {
+                    tracing::info!("Novel '{}' failed word count filter, skipping", novel.title);
+                    self.reject(&novel, reasons)?;
+                    continue;
+                }
+            }
+
+            if past_deadline() {
+                self.abandon(&novel);
+                continue;
+            }
+
+            // Look up the author's track record from their other fictions
+            if self.config.author_reputation && novel.author_reputation.is_none() {
+                if let Some(author_id) = novel.author_id {
+                    let started = self.clock.now();
+                    let reputation = self.author_cache.reputation(
+                        self.client.as_ref(),
+                        author_id,
+                        novel.id,
+                        &budget,
+                    );
+                    self.stats
+                        .record(Phase::NovelScrape, self.clock.since(started), Some(novel.id));
+                    match reputation {
+                        Ok(reputation) => novel.author_reputation = Some(reputation),
+                        Err(e) => enrichment_failed(&novel, "Author lookup", &e, &mut skipped),
+                    }
+                }
+            }
+
+            if past_deadline() {
+                self.abandon(&novel);
+                continue;
+            }
+
+            // Fetch the first chapter for evaluators that read the prose
+            let reads_prose = matches!(
+                self.config.eval_mode,
+                EvalMode::Llm(LlmEvalConfig {
+                    include_first_chapter: true,
+                    ..
+                })
             );
-            results.push(score);
+            if reads_prose && novel.first_chapter_excerpt.is_none() {
+                let started = self.clock.now();
+                let excerpt = crate::scraper::chapter::scrape_first_chapter_excerpt(
+                    self.site.as_ref(),
+                    self.client.as_ref(),
+                    &novel,
+                    FIRST_CHAPTER_EXCERPT_CHARS,
+                    &budget,
+                );
+                self.stats
+                    .record(Phase::NovelScrape, self.clock.since(started), Some(novel.id));
+                match excerpt {
+                    Ok(excerpt) => novel.first_chapter_excerpt = Some(excerpt),
+                    Err(e) => {
+                        enrichment_failed(&novel, "Fetching first chapter", &e, &mut skipped)
+                    }
+                }
+            }
+            if !skipped.is_empty() {
+                self.stats.novels_over_budget.push(novel.id);
+            }
+
+            if past_deadline() {
+                self.abandon(&novel);
+                continue;
+            }
+
+            // Reviews come from the same page fetch as the metadata; only
+            // novels that were queued without them need another request
+            if novel.reviews_unparsed {
+                self.stats.novels_reviews_unparsed.push(novel.id);
+            }
+            let reviews = match novel.reviews.take() {
+                Some(reviews) => Ok(reviews),
+                None => {
+                    let started = self.clock.now();
+                    let reviews = crate::site::scrape_reviews(
+                        self.site.as_ref(),
+                        self.client.as_ref(),
+                        novel.id,
+                        MAX_REVIEWS,
+                    );
+                    self.stats
+                        .record(Phase::ReviewScrape, self.clock.since(started), Some(novel.id));
+                    reviews
+                }
+            };
+            let reviews = match reviews {
+                Ok(reviews) => reviews,
+                // Leave challenges to the streak check rather than failing
+                // the whole run
+                Err(e) if is_challenged(&e) => {
+                    tracing::warn!("Skipping novel '{}': {:#}", novel.title, e);
+                    self.stats.novels_challenged += 1;
+                    continue;
+                }
+                // Evaluate without reviews rather than skipping the novel,
+                // but count it so a markup change doesn't go unnoticed
+                Err(e) if is_parse_failure(&e) => {
+                    tracing::warn!("Reviews of novel '{}': {}", novel.title, e);
+                    self.stats.novels_reviews_unparsed.push(novel.id);
+                    Vec::new()
+                }
+                Err(e) => return Err(e),
+            };
+            self.notify(|observer| observer.on_novel_scraped(&novel, &reviews))?;
+
+            // Short reviews are left out, and evaluators that can't use
+            // every review keep the first ones
+            let reviews: Vec<Review> = select_reviews(&reviews, &self.config.review_policy)
+                .into_iter()
+                .cloned()
+                .collect();
+
+            drop(phase);
+
+            // Evaluate against a cleaned copy, keeping the original for display.
+            // With profiles, the novel is scored once per profile it passes.
+            let phase = enter_phase("evaluate");
+            let eval_novel = Novel {
+                description: self.cleaner.clean(&novel.description),
+                ..novel.clone()
+            };
+            let mut scores = Vec::new();
+            let mut eval_times = Vec::new();
+            let passing = self.passing_profiles(&novel);
+            let near_miss = !passing.is_empty()
+                && passing.iter().all(|passed| !passed.near_misses.is_empty());
+            for passed in passing {
+                if past_deadline() {
+                    self.abandon(&novel);
+                    continue 'novels;
+                }
+                let profile = passed.name;
+                let started = self.clock.now();
+                let mut score = self.evaluator.evaluate(&eval_novel, &reviews, passed.criteria)?;
+                eval_times.push(self.clock.since(started));
+                score.novel = novel.clone();
+                score.profile = profile.map(String::from);
+                penalize_near_misses(&mut score, &passed.near_misses, passed.penalty);
+                if !skipped.is_empty() {
+                    let _ = write!(
+                        score.reasoning,
+                        "\n\nOver the request budget, skipped: {}.",
+                        skipped.join(", ")
+                    );
+                }
+                tracing::info!(
+                    "Novel '{}' scored {:.2}{}",
+                    novel.title,
+                    score.overall_score,
+                    profile.map(|p| format!(" for profile '{}'", p)).unwrap_or_default()
+                );
+                scores.push(score);
+            }
+            for score in &scores {
+                self.notify(|observer| observer.on_evaluated(score))?;
+            }
+            let best_score = scores
+                .iter()
+                .map(|score| score.overall_score)
+                .filter(|score| !score.is_nan())
+                .reduce(f64::max);
+            results.extend(scores);
+            for elapsed in eval_times {
+                self.stats.record(Phase::Evaluate, elapsed, Some(novel.id));
+            }
+            evaluated += 1;
+            if near_miss {
+                self.stats.novels_near_miss += 1;
+            }
+            if let (Some(sampling), Some(&stratum)) =
+                (&mut self.stats.sampling, self.seed_strata.get(&novel.key()))
+            {
+                sampling.strata[stratum].evaluated += 1;
+            }
+            if is_seed {
+                seeds_evaluated += 1;
+            } else {
+                discovered_evaluated += 1;
+            }
+            drop(phase);
 
-            // Discover related novels
-            if let Some(ref discovery) = self.discovery {
-                match discovery.discover(&novel) {
+            // Discover related novels, from novels that scored well enough
+            let _phase = enter_phase("discover");
+            let min_score = self.config.discovery_min_score;
+            if !self.discovery.is_empty() && !best_score.is_some_and(|score| score >= min_score) {
+                tracing::debug!(
+                    "Novel '{}' scored below {:.2}, not using it for discovery",
+                    novel.title,
+                    min_score
+                );
+                self.stats.discovery_skipped_low_score += 1;
+                continue;
+            }
+            let mut added = 0;
+            for discoverer in &self.discovery {
+                if !discoverer.wants_source(best_score) {
+                    tracing::debug!(
+                        "Novel '{}' scored too low for {} discovery",
+                        novel.title,
+                        discoverer.name
+                    );
+                    continue;
+                }
+                let started = self.clock.now();
+                let discovered = discoverer
+                    .source
+                    .discover(&novel, &|id| {
+                        self.queue.has_seen(NovelKey::new(novel.site, id))
+                    });
+                self.stats
+                    .record(Phase::Discovery, self.clock.since(started), Some(novel.id));
+                match discovered {
                     Ok(discovered) => {
                         for discovered_novel in discovered {
-                            self.queue.push(discovered_novel);
+                            if self.queue.push(discovered_novel) {
+                                added += 1;
+                            }
                         }
                     }
                     Err(e) => {
                         tracing::warn!(
-                            "Discovery failed for novel '{}': {}",
+                            "{} discovery failed for novel '{}': {}",
+                            discoverer.name,
                             novel.title,
                             e
                         );
                     }
                 }
             }
+            self.stats.novels_discovered += added;
+            self.notify(|observer| observer.on_discovery(&novel, added))?;
         }
+        // Every way of stopping early puts the current novel back
+        self.stats.queue_ran_dry = self.queue.is_empty()
+            && !self.config.stop_condition.ends_on_empty_queue();
 
-        // Sort results by score descending
-        results.sort_by(|a, b| {
-            b.overall_score
-                .partial_cmp(&a.overall_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
+        // Sort results by score descending with deterministic tie-breaking
+        for score in results.iter().filter(|s| s.overall_score.is_nan()) {
+            tracing::warn!(
+                "Novel '{}' (ID: {}) has a NaN score, ranking it last",
+                score.novel.title,
+                score.novel.id
+            );
+        }
+        results.sort_by(rank_order);
 
-        tracing::info!("Pipeline complete. {} novels evaluated.", results.len());
+        self.stats.novels_evaluated = evaluated;
+        self.stats.score_distribution =
+            ScoreDistribution::from_scores(results.iter().map(|s| s.overall_score));
+        self.stats.total = self.clock.elapsed();
+        if self.pinned_time.is_some() {
+            self.stats.clear_timings();
+        }
+        self.rejects.finish()?;
+        for observer in &mut self.observers {
+            observer.on_finished(&self.stats)?;
+        }
+        tracing::info!("Pipeline complete. {} novels evaluated.", evaluated);
         Ok(results)
     }
 
-    /// Gather seed novels and add them to the queue.
+    /// Give up on a novel that ran past its deadline.
+    fn abandon(&mut self, novel: &Novel) {
+        tracing::warn!(
+            "Abandoning novel '{}': it took longer than [run] max_seconds_per_novel",
+            novel.title
+        );
+        self.stats.novels_timed_out.push(novel.id);
+    }
+
+    /// Record a novel the hard filters rejected.
+    fn reject(&mut self, novel: &Novel, reasons: Vec<FilterReason>) -> Result<()> {
+        self.notify(|observer| observer.on_prefilter_rejected(novel, &reasons))?;
+        self.stats.record_rejection(&reasons);
+        self.rejects.record(Rejection {
+            id: novel.id,
+            title: novel.title.clone(),
+            url: novel.url.clone(),
+            reasons,
+        })
+    }
+
+    /// Pass an event to every observer, stopping at the first error.
+    fn notify(
+        &mut self,
+        mut event: impl FnMut(&mut dyn PipelineObserver) -> Result<()>,
+    ) -> Result<()> {
+        self.observers.iter_mut().try_for_each(|observer| event(observer.as_mut()))
+    }
+
+    /// Novels the hard filters rejected.
+    pub fn rejects(&self) -> &RejectLog {
+        &self.rejects
+    }
+
+    /// Timing and counts for the most recent run.
+    pub fn stats(&self) -> &RunStats {
+        &self.stats
+    }
+
+    /// What produced the most recent run's results, stamped with the
+    /// current time (or the pinned time of a deterministic run).
+    pub fn run_metadata(&self) -> RunMetadata {
+        RunMetadata {
+            finished_at: self.pinned_time.unwrap_or_else(Timestamp::now),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            criteria: self.config.criteria.clone(),
+            profiles: self.config.profiles.clone(),
+            criteria_hash: self.config.criteria_hash(),
+            eval_mode: self.config.eval_mode.to_string(),
+            seeds: self.config.seed_source.to_string(),
+            stop_condition: self.config.stop_condition.to_string(),
+            total_requests: self.client.request_count(),
+            duration_secs: self.stats.total.as_secs_f64(),
+        }
+    }
+
+    /// Download the covers of the results' novels into `dir`, through the
+    /// same rate-limited client as the run. Returns how many were fetched.
+    pub fn download_covers(&self, results: &[NovelScore], dir: &Path) -> Result<usize> {
+        let novels = results.iter().map(|score| &score.novel);
+        covers::download_covers(self.client.as_ref(), novels, dir)
+    }
+
+    /// Write the novels still queued when the run stopped to a seeds file at
+    /// `path`, leaving out any among `results` (already evaluated). Returns
+    /// how many were written.
+    pub fn export_queue(&self, results: &[NovelScore], path: &Path) -> Result<usize> {
+        let evaluated: HashSet<NovelKey> = results.iter().map(|score| score.novel.key()).collect();
+        let remaining: Vec<&Novel> = self
+            .queue
+            .queued()
+            .filter(|novel| !evaluated.contains(&novel.key()))
+            .collect();
+        crate::queue::write_seeds_file(path, remaining.iter().copied())?;
+        Ok(remaining.len())
+    }
+
+    /// Gather seed novels and add them to the queue, up to the seed limit.
+    /// Seeds scraped from URLs or search results stop once the seed time
+    /// budget is spent.
     fn gather_seeds(&mut self) -> Result<()> {
+        let chapters = self.config.scraper.store_chapter_titles;
+        let deadline = self.clock.deadline(self.config.seed_time_budget);
         match &self.config.seed_source {
             SeedSource::Manual(urls) => {
-                for url in urls {
-                    let novel_id = parse_novel_id(url)?;
-                    let novel =
-                        crate::scraper::novel_page::scrape_novel(&self.client, novel_id)?;
+                let urls = urls.clone();
+                self.queue_seed_urls(&urls, deadline)?;
+            }
+            SeedSource::File(path) => {
+                let urls = crate::queue::read_seeds_file(path)?;
+                tracing::info!("Read {} seeds from {}", urls.len(), path.display());
+                self.queue_seed_urls(&urls, deadline)?;
+            }
+            SeedSource::Dataset { path, filter } => {
+                // Saved novels are queued as they are, reviews and all, so
+                // only what they lack is fetched
+                let entries = crate::rescore::read_dataset(path)?;
+                let total = entries.len();
+                let limit = self.seed_cap(total);
+                let mut queued = 0;
+                for entry in entries {
+                    if queued == limit {
+                        break;
+                    }
+                    if *filter == DatasetFilter::Unevaluated && entry.evaluated {
+                        continue;
+                    }
+                    let mut novel = entry.novel;
+                    novel.reviews = entry.reviews;
                     self.queue.push(novel);
+                    queued += 1;
                 }
+                tracing::info!(
+                    "Queued {} of {} novels from dataset {}",
+                    queued,
+                    total,
+                    path.display()
+                );
             }
             SeedSource::Search { query, max_results } => {
-                let results = crate::scraper::search::search_novels(
-                    &self.client,
-                    query,
-                    *max_results,
-                )?;
-                for result in results {
-                    let novel = crate::scraper::novel_page::scrape_novel(
-                        &self.client,
-                        result.id,
-                    )?;
+                let query = SearchQuery {
+                    title: Some(query.clone()),
+                    ..SearchQuery::default()
+                };
+                let max_results = self.seed_cap(*max_results);
+                self.queue_search(&query, max_results, deadline)?;
+            }
+            SeedSource::Tag(seeds) => {
+                let query = SearchQuery {
+                    title: None,
+                    tags: seeds.tags.clone(),
+                    order_by: Some(seeds.order_by),
+                };
+                let max_results = self.seed_cap(seeds.max_results);
+                self.queue_search(&query, max_results, deadline)?;
+            }
+            SeedSource::Random(seeds) => {
+                let seeds = RandomSeeds {
+                    count: self.seed_cap(seeds.count),
+                    ..seeds.clone()
+                };
+                let novels = crate::discovery::random::sample_novels(
+                    self.site.as_ref(),
+                    self.client.as_ref(),
+                    &seeds,
+                    chapters,
+                );
+                for novel in novels {
                     self.queue.push(novel);
                 }
             }
@@ -169,38 +821,994 @@ impl Pipeline {
         Ok(())
     }
 
-    /// Check whether the stop condition has been met.
-    fn should_stop(&self, results: &[NovelScore], start_time: Instant) -> bool {
-        match &self.config.stop_condition {
-            StopCondition::MaxNovels(max) => results.len() >= *max,
-            StopCondition::MaxTime(duration) => start_time.elapsed() >= *duration,
-            StopCondition::EmptyQueue => false, // Queue emptiness is handled by the while-let
+    /// Scrape and queue up to `max_results` novels the site lists for
+    /// `query`, until `deadline`.
+    fn queue_search(
+        &mut self,
+        query: &SearchQuery,
+        max_results: usize,
+        deadline: Option<Instant>,
+    ) -> Result<()> {
+        let results = crate::scraper::search::search_novels(
+            self.site.as_ref(),
+            self.client.as_ref(),
+            query,
+            max_results,
+        )?;
+        tracing::info!("Search for {} found {} seeds", query, results.len());
+        let total = results.len();
+        for (index, result) in results.into_iter().enumerate() {
+            if self.cancel.is_cancelled() || self.seeding_time_up(deadline, total - index) {
+                break;
+            }
+            let started = self.clock.now();
+            let novel = crate::site::scrape_novel(
+                self.site.as_ref(),
+                self.client.as_ref(),
+                result.id,
+                self.config.scraper.store_chapter_titles,
+            )?;
+            self.stats
+                .record(Phase::NovelScrape, self.clock.since(started), Some(result.id));
+            self.queue.push(novel);
+        }
+        Ok(())
+    }
+
+    /// Scrape and queue the novels with the given IDs or URLs, until
+    /// `deadline`.
+    fn queue_seed_urls(&mut self, urls: &[String], deadline: Option<Instant>) -> Result<()> {
+        let ids = self.seed_ids(urls)?;
+        for (index, &novel_id) in ids.iter().enumerate() {
+            if self.cancel.is_cancelled() || self.seeding_time_up(deadline, ids.len() - index) {
+                break;
+            }
+            let started = self.clock.now();
+            let novel = crate::site::scrape_novel(
+                self.site.as_ref(),
+                self.client.as_ref(),
+                novel_id,
+                self.config.scraper.store_chapter_titles,
+            )?;
+            self.stats
+                .record(Phase::NovelScrape, self.clock.since(started), Some(novel_id));
+            self.queue.push(novel);
         }
+        Ok(())
+    }
+
+    /// The novel IDs of the given seed IDs or URLs, parsed before any is
+    /// scraped so a bad entry fails the run up front. Duplicates (the same
+    /// novel by ID and by URL, say) are dropped with a warning, as are seeds
+    /// past the seed limit.
+    fn seed_ids(&self, urls: &[String]) -> Result<Vec<u64>> {
+        let mut seen = HashSet::new();
+        let mut ids = Vec::new();
+        for url in urls {
+            let id = parse_novel_id(self.site.as_ref(), url)?;
+            if seen.insert(id) {
+                ids.push(id);
+            }
+        }
+        let duplicates = urls.len() - ids.len();
+        if duplicates > 0 {
+            tracing::warn!(
+                "{} duplicate seed{} ignored",
+                duplicates,
+                if duplicates == 1 { "" } else { "s" }
+            );
+        }
+        ids.truncate(self.seed_cap(ids.len()));
+        Ok(ids)
+    }
+
+    /// `wanted` seeds, or fewer if that's over the seed limit.
+    fn seed_cap(&self, wanted: usize) -> usize {
+        self.config.max_seeds.map_or(wanted, |max| wanted.min(max))
+    }
+
+    /// Whether seed gathering is out of time, with `remaining` seeds not yet
+    /// scraped: past its `deadline`, or past the whole run's time limit.
+    fn seeding_time_up(&self, deadline: Option<Instant>, remaining: usize) -> bool {
+        if self.clock.is_past(deadline) {
+            tracing::warn!(
+                "Seed time budget spent after {}; skipped the remaining {} seeds",
+                util::format_duration(self.clock.elapsed()),
+                remaining
+            );
+            true
+        } else if self.clock.time_up() {
+            tracing::warn!(
+                "Run time limit reached while gathering seeds; skipped the remaining {} seeds",
+                remaining
+            );
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The criteria sets to evaluate against, with the profile name (`None`
+    /// when no profiles are configured).
+    fn profiles(&self) -> Vec<(Option<&str>, &Criteria)> {
+        if self.config.profiles.is_empty() {
+            vec![(None, &self.config.criteria)]
+        } else {
+            self.config
+                .profiles
+                .iter()
+                .map(|p| (Some(p.name.as_str()), &p.criteria))
+                .collect()
+        }
+    }
+
+    /// The criteria sets a novel passes the hard filters for, or nearly
+    /// passes with soft filters on.
+    fn passing_profiles(&self, novel: &Novel) -> Vec<PassedProfile<'_>> {
+        self.profiles()
+            .into_iter()
+            .filter_map(|(name, criteria)| {
+                let failed = self.evaluator.pre_filter(novel, criteria);
+                let penalty = near_miss_penalty(&failed, criteria.soft_filters.as_ref())?;
+                Some(PassedProfile {
+                    name,
+                    criteria,
+                    near_misses: failed,
+                    penalty,
+                })
+            })
+            .collect()
+    }
+
+    /// Every hard filter a novel fails, across all criteria sets, if it
+    /// passes none of them; `None` if it passes any.
+    fn rejection_reasons(&self, novel: &Novel) -> Option<Vec<FilterReason>> {
+        let mut reasons = Vec::new();
+        for (_, criteria) in self.profiles() {
+            let failed = self.evaluator.pre_filter(novel, criteria);
+            if near_miss_penalty(&failed, criteria.soft_filters.as_ref()).is_some() {
+                return None;
+            }
+            for reason in failed {
+                if !reasons.contains(&reason) {
+                    reasons.push(reason);
+                }
+            }
+        }
+        Some(reasons)
+    }
+
+    /// Check whether the stop condition has been met. A `max_time` limit
+    /// counts from the start of the run, seed gathering included.
+    fn should_stop(&self, evaluated: usize) -> bool {
+        condition_met(&self.config.stop_condition, evaluated, &self.clock)
     }
 }
 
-/// Extract a RoyalRoad fiction ID from a URL or raw ID string.
-fn parse_novel_id(url_or_id: &str) -> Result<u64> {
+/// A criteria set a novel passed the hard filters for.
+struct PassedProfile<'a> {
+    /// The profile name, `None` when no profiles are configured.
+    name: Option<&'a str>,
+    criteria: &'a Criteria,
+    /// The filters the novel only nearly passed, with soft filters on.
+    near_misses: Vec<FilterReason>,
+    /// What the novel's score is multiplied by for its near misses.
+    penalty: f64,
+}
+
+/// Scale down the score of a novel soft filters let through by `penalty`,
+/// marking the filters it only nearly passed.
+fn penalize_near_misses(score: &mut NovelScore, near_misses: &[FilterReason], penalty: f64) {
+    if near_misses.is_empty() {
+        return;
+    }
+    score.overall_score *= penalty;
+    score.score_low = score.score_low.map(|low| low * penalty);
+    score.score_high = score.score_high.map(|high| high * penalty);
+    score.near_misses = near_misses.iter().map(ToString::to_string).collect();
+    let _ = write!(
+        score.reasoning,
+        "\n\nNear miss, score \u{d7}{:.2}: {}.",
+        penalty,
+        score.near_misses.join("; ")
+    );
+}
+
+/// Whether `condition` is met after `evaluated` novels, by `clock`.
+fn condition_met(condition: &StopCondition, evaluated: usize, clock: &RunClock) -> bool {
+    match condition {
+        StopCondition::MaxNovels(max) => evaluated >= *max,
+        StopCondition::MaxTime(_) => clock.time_up(),
+        StopCondition::EmptyQueue => false, // Queue emptiness is handled by the while-let
+        StopCondition::Any(conditions) => conditions
+            .iter()
+            .any(|condition| condition_met(condition, evaluated, clock)),
+    }
+}
+
+/// A discovery source, with the pipeline-side limits on when it's used.
+struct Discoverer {
+    /// The source's config name, for logs.
+    name: String,
+    /// Least best score a novel needs to be used to discover more.
+    min_source_score: Option<f64>,
+    source: Box<dyn DiscoverySource>,
+}
+
+impl Discoverer {
+    fn new(config: &DiscoveryConfig, source: Box<dyn DiscoverySource>) -> Self {
+        Self {
+            name: config.source.to_string(),
+            min_source_score: config.min_source_score,
+            source,
+        }
+    }
+
+    /// Whether a novel whose best score was `best_score` (`None` when it
+    /// wasn't scored) should be used to discover more from this source.
+    fn wants_source(&self, best_score: Option<f64>) -> bool {
+        match self.min_source_score {
+            Some(min) => best_score.is_some_and(|score| score >= min),
+            None => true,
+        }
+    }
+}
+
+/// Whether evaluating one more discovered novel would take discovered
+/// novels past `max_ratio` per evaluated seed.
+fn discovery_ratio_reached(
+    max_ratio: Option<f64>,
+    seeds_evaluated: usize,
+    discovered_evaluated: usize,
+) -> bool {
+    max_ratio
+        .is_some_and(|ratio| (discovered_evaluated + 1) as f64 > ratio * seeds_evaluated as f64)
+}
+
+/// Enter a span marking the pipeline phase, for filtering log output.
+fn enter_phase(phase: &'static str) -> EnteredSpan {
+    tracing::info_span!("phase", phase).entered()
+}
+
+/// Log an optional enrichment step's failure, noting it in `skipped` if
+/// the request budget is why.
+fn enrichment_failed(
+    novel: &Novel,
+    step: &str,
+    err: &anyhow::Error,
+    skipped: &mut Vec<&'static str>,
+) {
+    match over_budget_step(err) {
+        Some(over) => {
+            tracing::info!("Novel '{}' {:#}", novel.title, err);
+            skipped.push(over);
+        }
+        None => tracing::warn!("{} failed for novel '{}': {}", step, novel.title, err),
+    }
+}
+
+/// Extract a fiction ID on `site` from a URL or raw ID string.
+pub(crate) fn parse_novel_id(site: &dyn Site, url_or_id: &str) -> Result<u64> {
     // Try parsing as a plain number first
-    if let Ok(id) = url_or_id.parse::<u64>() {
+    if let Ok(id) = url_or_id.trim().parse::<u64>() {
         return Ok(id);
     }
 
-    // Try extracting from a RoyalRoad URL like:
+    // Try extracting from a fiction or chapter URL, like RoyalRoad's
     // https://www.royalroad.com/fiction/12345/some-title
-    let parts: Vec<&str> = url_or_id.split('/').collect();
-    for (i, part) in parts.iter().enumerate() {
-        if *part == "fiction" {
-            if let Some(id_str) = parts.get(i + 1) {
-                if let Ok(id) = id_str.parse::<u64>() {
-                    return Ok(id);
-                }
-            }
-        }
+    if let Some(id) = site.fiction_id(url_or_id) {
+        return Ok(id);
     }
 
     anyhow::bail!(
-        "Could not extract novel ID from: {}. Expected a numeric ID or RoyalRoad URL.",
-        url_or_id
+        "Could not extract novel ID from: {}. Expected a numeric ID or {} URL.",
+        url_or_id,
+        site.id()
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualTime;
+    use crate::config::parse_config;
+    use crate::models::{test_novel, test_score, SiteId};
+    use crate::scraper::FakeClient;
+    use crate::site::RoyalRoad;
+    use crate::util::random_string;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    /// Cases per property test; the generator is seeded, so failures repeat.
+    const CASES: usize = 500;
+
+    /// Pick one of `options` at random.
+    fn pick<'a>(rng: &mut impl Rng, options: &[&'a str]) -> &'a str {
+        options[rng.gen_range(0..options.len())]
+    }
+
+    #[test]
+    fn test_parse_novel_id_from_generated_urls() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(152);
+        for _ in 0..CASES {
+            let id: u64 = rng.gen_range(1..10_000_000);
+            let url = format!(
+                "{}{}{}/fiction/{}{}{}{}{}{}{}",
+                pick(&mut rng, &["", " ", "\t"]),
+                pick(&mut rng, &["https://", "http://", ""]),
+                pick(&mut rng, &["www.royalroad.com", "royalroad.com"]),
+                id,
+                pick(&mut rng, &["", "/some-title", "/a-9-b"]),
+                pick(&mut rng, &["", "/chapter/456", "/chapter/456/the-start"]),
+                pick(&mut rng, &["", "/"]),
+                pick(&mut rng, &["", "?page=2", "?a=1&fiction=3"]),
+                pick(&mut rng, &["", "#reviews", "#/fiction/9"]),
+                pick(&mut rng, &["", " ", "\n"]),
+            );
+            assert_eq!(parse_novel_id(&RoyalRoad, &url).unwrap(), id, "{:?}", url);
+        }
+    }
+
+    #[test]
+    fn test_parse_novel_id_never_panics() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(152);
+        let alphabet = ['f', 'i', 'c', 't', 'o', 'n', '/', '1', '2', '?', '#', ' ', '-', '\u{e9}'];
+        for _ in 0..CASES {
+            let input = random_string(&mut rng, &alphabet, 30);
+            // An ID, when one comes back, is taken from the input itself
+            if let Ok(id) = parse_novel_id(&RoyalRoad, &input) {
+                assert!(input.contains(&id.to_string()), "{:?} -> {}", input, id);
+            }
+        }
+        let listing = "https://www.royalroad.com/fictions/best-rated";
+        assert!(parse_novel_id(&RoyalRoad, listing).is_err());
+        assert!(parse_novel_id(&RoyalRoad, "99999999999999999999999").is_err());
+    }
+
+    /// A discovery source that records the novels it's asked about, and
+    /// finds `finds` (those not already seen) from each.
+    struct FakeSource {
+        asked: Arc<Mutex<Vec<u64>>>,
+        finds: Vec<Novel>,
+    }
+
+    impl DiscoverySource for FakeSource {
+        fn discover(&self, novel: &Novel, is_seen: &dyn Fn(u64) -> bool) -> Result<Vec<Novel>> {
+            self.asked.lock().unwrap().push(novel.id);
+            Ok(self.finds.iter().filter(|n| !is_seen(n.id)).cloned().collect())
+        }
+    }
+
+    /// A discoverer with `min_source_score` and the novels it was asked about.
+    fn fake_discoverer(
+        min_source_score: Option<f64>,
+        finds: &[u64],
+    ) -> (Discoverer, Arc<Mutex<Vec<u64>>>) {
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let finds = finds
+            .iter()
+            .map(|id| Novel {
+                reviews: Some(Vec::new()),
+                ..test_novel(*id)
+            })
+            .collect();
+        let config = DiscoveryConfig {
+            min_source_score,
+            ..DiscoveryConfig::also_liked()
+        };
+        let source = FakeSource {
+            asked: Arc::clone(&asked),
+            finds,
+        };
+        (Discoverer::new(&config, Box::new(source)), asked)
+    }
+
+    /// Scores each novel by its ID, and lets every novel through the filters.
+    struct FakeEvaluator {
+        scores: HashMap<u64, f64>,
+    }
+
+    impl Evaluator for FakeEvaluator {
+        fn evaluate(
+            &self,
+            novel: &Novel,
+            _reviews: &[Review],
+            _criteria: &Criteria,
+        ) -> Result<NovelScore> {
+            Ok(test_score(novel.clone(), self.scores[&novel.id]))
+        }
+
+        fn pre_filter(&self, _novel: &Novel, _criteria: &Criteria) -> Vec<FilterReason> {
+            Vec::new()
+        }
+    }
+
+    /// A pipeline seeded with novel 90435 from its fixture page, running
+    /// until the queue is empty, with `run_settings` added under `[run]`.
+    fn fixture_pipeline(run_settings: &str) -> Pipeline {
+        let page = include_str!("scraper/testdata/novel_page_90435.html");
+        let client = FakeClient::new().with_page("https://www.royalroad.com/fiction/90435", page);
+        let config = parse_config(&format!(
+            r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["90435"]
+
+[run]
+stop_condition = {{ type = "empty_queue" }}
+{}
+"#,
+            run_settings
+        ))
+        .unwrap();
+        assert!(config.discovery.is_empty());
+        Pipeline::with_client(config, Arc::new(client)).unwrap()
+    }
+
+    /// Serves `inner`'s pages, moving `time` forward by `step` per request.
+    struct SlowClient {
+        inner: FakeClient,
+        time: Arc<ManualTime>,
+        step: Duration,
+    }
+
+    impl HttpFetch for SlowClient {
+        fn fetch(&self, url: &str) -> Result<String> {
+            self.time.advance(self.step);
+            self.inner.fetch(url)
+        }
+
+        fn request_count(&self) -> u64 {
+            self.inner.request_count()
+        }
+    }
+
+    /// A pipeline seeded with four copies of novel 90435's page, where every
+    /// request takes ten seconds on a manual clock, with `run_settings`
+    /// under `[run]`. Returns the pipeline and the results of its run.
+    fn slow_seed_run(run_settings: &str) -> (Pipeline, Vec<NovelScore>) {
+        let page = include_str!("scraper/testdata/novel_page_90435.html");
+        let mut inner = FakeClient::new();
+        for id in 1..=4 {
+            inner = inner.with_page(&format!("https://www.royalroad.com/fiction/{}", id), page);
+        }
+        let time = Arc::new(ManualTime::new());
+        let client = SlowClient {
+            inner,
+            time: Arc::clone(&time),
+            step: Duration::from_secs(10),
+        };
+        let config = parse_config(&format!(
+            r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["1", "2", "3", "4"]
+
+[run]
+{}
+"#,
+            run_settings
+        ))
+        .unwrap();
+        let mut pipeline = Pipeline::with_client(config, Arc::new(client)).unwrap();
+        pipeline.clock = RunClock::new(time, &pipeline.config.stop_condition);
+        let results = pipeline.run().unwrap();
+        (pipeline, results)
+    }
+
+    #[test]
+    fn test_seed_time_budget_stops_seeding() {
+        // Seeds take 10s each: the third starts at 20s, inside the budget,
+        // and the fourth is skipped
+        let (pipeline, results) = slow_seed_run(
+            "stop_condition = { type = \"empty_queue\" }\nseed_time_budget = \"25s\"",
+        );
+        assert_eq!(pipeline.client.request_count(), 3);
+        assert_eq!(results.len(), 3);
+        assert_eq!(pipeline.stats().total, Duration::from_secs(30));
+
+        // The rest of the run's time is left for evaluation
+        let (_, results) = slow_seed_run(
+            "stop_condition = { type = \"max_time\", value = \"60s\" }\nseed_time_budget = 15",
+        );
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_limit_caps_seeds_and_novels() {
+        let page = include_str!("scraper/testdata/novel_page_90435.html");
+        let mut client = FakeClient::new();
+        for id in 1..=4 {
+            client = client.with_page(&format!("https://www.royalroad.com/fiction/{}", id), page);
+        }
+        let mut config = parse_config(
+            r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["1", "2", "3", "4"]
+
+[run]
+stop_condition = { type = "empty_queue" }
+"#,
+        )
+        .unwrap();
+        config.limit_novels(2);
+        let mut pipeline = Pipeline::with_client(config, Arc::new(client)).unwrap();
+        let results = pipeline.run().unwrap();
+
+        // Seeds past the limit aren't scraped, and the run stops at the limit
+        assert_eq!(pipeline.client.request_count(), 2);
+        assert_eq!(results.len(), 2);
+        assert!(!pipeline.stats().queue_ran_dry);
+    }
+
+    #[test]
+    fn test_duplicate_seeds_scraped_once() {
+        let page = include_str!("scraper/testdata/novel_page_90435.html");
+        let client = || {
+            Arc::new(
+                FakeClient::new()
+                    .with_page("https://www.royalroad.com/fiction/1", page)
+                    .with_page("https://www.royalroad.com/fiction/2", page),
+            )
+        };
+        let config = |urls: &str| {
+            parse_config(&format!(
+                "[eval]\nmode = \"local\"\n[seeds]\nsource = \"manual\"\nurls = {}\n\
+                 [run]\nstop_condition = {{ type = \"empty_queue\" }}\n",
+                urls
+            ))
+            .unwrap()
+        };
+
+        let urls = r#"["1", "https://www.royalroad.com/fiction/1/some-title", "2", " 2 "]"#;
+        let mut pipeline = Pipeline::with_client(config(urls), client()).unwrap();
+        let SeedSource::Manual(seeds) = &pipeline.config.seed_source else {
+            panic!("expected manual seeds");
+        };
+        assert_eq!(pipeline.seed_ids(seeds).unwrap(), [1, 2]);
+        let results = pipeline.run().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(pipeline.client.request_count(), 2);
+
+        // Every seed is parsed before any is scraped
+        let mut pipeline =
+            Pipeline::with_client(config(r#"["1", "not a seed"]"#), client()).unwrap();
+        assert!(pipeline.run().is_err());
+        assert_eq!(pipeline.client.request_count(), 0);
+    }
+
+    #[test]
+    fn test_unparsable_reviews_are_counted() {
+        let page = include_str!("scraper/testdata/novel_page_90435.html")
+            .replace(r#"<div class="review""#, r#"<div class="critique""#)
+            .replace("reviews-container", "critiques");
+        let client = FakeClient::new().with_page("https://www.royalroad.com/fiction/90435", &page);
+        let config = parse_config(
+            r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["90435"]
+
+[run]
+stop_condition = { type = "empty_queue" }
+discovery_enabled = false
+"#,
+        )
+        .unwrap();
+        let mut pipeline = Pipeline::with_client(config, Arc::new(client)).unwrap();
+        let results = pipeline.run().unwrap();
+        // Evaluated without reviews, not skipped
+        assert_eq!(results.len(), 1);
+        assert_eq!(pipeline.stats().novels_reviews_unparsed, [90435]);
+    }
+
+    #[test]
+    fn test_stratified_sampling_evaluates_across_strata() {
+        // Six seeds queued with the fewest followers first
+        let page = include_str!("scraper/testdata/novel_page_90435.html");
+        let mut client = FakeClient::new();
+        for id in 1..=6 {
+            let followers = format!(">{}<", id * 100);
+            client = client.with_page(
+                &format!("https://www.royalroad.com/fiction/{}", id),
+                &page.replace(">6,475<", &followers),
+            );
+        }
+        let config = parse_config(
+            r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["1", "2", "3", "4", "5", "6"]
+
+[run]
+stop_condition = { type = "max_novels", value = 3 }
+discovery_enabled = false
+sampling = "stratified"
+sampling_strata = 3
+sampling_seed = 4
+"#,
+        )
+        .unwrap();
+        let mut pipeline = Pipeline::with_client(config, Arc::new(client)).unwrap();
+        let results = pipeline.run().unwrap();
+
+        let mut ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids.iter().map(|id| (id - 1) / 2).collect::<Vec<_>>(), [0, 1, 2]);
+        let sampling = pipeline.stats().sampling.as_ref().unwrap();
+        assert_eq!(sampling.by, "followers");
+        let strata: Vec<(f64, f64, usize, usize)> = sampling
+            .strata
+            .iter()
+            .map(|stratum| (stratum.low, stratum.high, stratum.queued, stratum.evaluated))
+            .collect();
+        assert_eq!(
+            strata,
+            [(100.0, 200.0, 2, 1), (300.0, 400.0, 2, 1), (500.0, 600.0, 2, 1)]
+        );
+    }
+
+    #[test]
+    fn test_soft_filters_evaluate_near_misses_with_penalty() {
+        // The fixture novel is rated 4.398
+        let run = |criteria: &str| {
+            let mut pipeline = fixture_pipeline(&format!("\n[criteria]\n{}", criteria));
+            let results = pipeline.run().unwrap();
+            (pipeline, results)
+        };
+        let (_, plain) = run("");
+        let (_, results) = run("min_rating = 4.5");
+        assert!(results.is_empty());
+
+        let (pipeline, results) = run("min_rating = 4.5\nsoft_filters = true");
+        assert_eq!(results.len(), 1);
+        let expected = plain[0].overall_score * (1.0 - 0.3 * (4.5 - 4.39845) / 0.2);
+        assert!((results[0].overall_score - expected).abs() < 1e-3);
+        assert_eq!(results[0].near_misses, ["rating 4.40 < min 4.50"]);
+        assert!(results[0].reasoning.contains("Near miss"));
+        assert_eq!(pipeline.stats().novels_near_miss, 1);
+        assert_eq!(pipeline.stats().novels_rejected, 0);
+
+        // Beyond the tolerance, the novel is still rejected
+        let (pipeline, results) = run("min_rating = 4.7\nsoft_filters = true");
+        assert!(results.is_empty());
+        assert_eq!(pipeline.stats().novels_rejected, 1);
+    }
+
+    #[test]
+    fn test_max_time_counts_seed_gathering() {
+        let (pipeline, results) =
+            slow_seed_run("stop_condition = { type = \"max_time\", value = \"30s\" }");
+        // Seeding used the whole run, so nothing was evaluated
+        assert_eq!(pipeline.client.request_count(), 3);
+        assert!(results.is_empty());
+        assert_eq!(pipeline.queue.len(), 3);
+    }
+
+    #[test]
+    fn test_enrichments_skipped_over_request_budget() {
+        // Sampling needs 3 requests and the author's fiction list 1. The
+        // fake serves neither, so the estimate stops at its first sample,
+        // but failed requests count against the budget all the same
+        let run = |max_requests: u64| {
+            let setting = format!("max_requests_per_novel = {}", max_requests);
+            let mut pipeline = fixture_pipeline(&setting);
+            pipeline.config.scraper.estimate_word_count = true;
+            pipeline.config.author_reputation = true;
+            let results = pipeline.run().unwrap();
+            assert_eq!(results.len(), 1);
+            (pipeline, results[0].reasoning.clone())
+        };
+
+        let (pipeline, reasoning) = run(20);
+        assert_eq!(pipeline.client.request_count(), 3);
+        assert!(pipeline.stats().novels_over_budget.is_empty());
+        assert!(!reasoning.contains("request budget"), "{}", reasoning);
+
+        let (pipeline, reasoning) = run(1);
+        assert_eq!(pipeline.client.request_count(), 2);
+        assert_eq!(pipeline.stats().novels_over_budget, [90435]);
+        assert!(
+            reasoning.ends_with("Over the request budget, skipped: word count sampling."),
+            "{}",
+            reasoning
+        );
+
+        let (pipeline, reasoning) = run(0);
+        assert_eq!(pipeline.client.request_count(), 1);
+        assert!(
+            reasoning.ends_with("skipped: word count sampling, author lookup."),
+            "{}",
+            reasoning
+        );
+    }
+
+    #[test]
+    fn test_registered_evaluator_and_source_run_from_config() {
+        let page = include_str!("scraper/testdata/novel_page_90435.html");
+        let client = FakeClient::new().with_page("https://www.royalroad.com/fiction/90435", page);
+        let config = parse_config(
+            r#"
+[eval]
+mode = "toy"
+
+[eval.toy]
+score = 0.7
+
+[seeds]
+source = "manual"
+urls = ["90435"]
+
+[run]
+stop_condition = { type = "empty_queue" }
+
+[[run.discovery]]
+kind = "shelf"
+finds = [1, 2]
+"#,
+        )
+        .unwrap();
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let shelf_asked = Arc::clone(&asked);
+        let mut pipeline = Pipeline::builder()
+            .register_evaluator("toy", |settings, _config| {
+                let score = settings["toy"]["score"].as_float().context("no score")?;
+                let scores = [90435, 1, 2].map(|id| (id, score));
+                Ok(Box::new(FakeEvaluator {
+                    scores: HashMap::from(scores),
+                }))
+            })
+            .register_discovery("shelf", move |settings, context| {
+                assert_eq!(context.entry.source.kind(), "shelf");
+                let finds = settings["finds"].as_array().context("no finds")?;
+                let finds = finds
+                    .iter()
+                    .filter_map(toml::Value::as_integer)
+                    .map(|id| Novel {
+                        reviews: Some(Vec::new()),
+                        ..test_novel(id as u64)
+                    })
+                    .collect();
+                Ok(Box::new(FakeSource {
+                    asked: Arc::clone(&shelf_asked),
+                    finds,
+                }))
+            })
+            .client(Arc::new(client))
+            .build(config.clone())
+            .unwrap();
+        let results = pipeline.run().unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|score| score.overall_score == 0.7));
+        assert_eq!(*asked.lock().unwrap(), vec![90435, 1, 2]);
+
+        // Without the registrations the config names nothing known
+        let err = Pipeline::with_client(config.clone(), Arc::new(FakeClient::new()))
+            .err()
+            .expect("an unregistered mode was accepted")
+            .to_string();
+        assert_eq!(err, "eval.mode: Unknown eval mode: toy (known modes: llm, local)");
+        let err = Pipeline::builder()
+            .register_evaluator("toy", |_, _| {
+                Ok(Box::new(FakeEvaluator {
+                    scores: HashMap::new(),
+                }))
+            })
+            .client(Arc::new(FakeClient::new()))
+            .build(config)
+            .err()
+            .expect("an unregistered source was accepted")
+            .to_string();
+        assert!(err.contains("Unknown discovery source: shelf"), "{}", err);
+    }
+
+    #[test]
+    fn test_scribblehub_run() {
+        let seed = include_str!("site/testdata/scribblehub_series_299262.html");
+        let found = include_str!("site/testdata/scribblehub_series_187402.html");
+        let client = FakeClient::new()
+            .with_page("https://www.scribblehub.com/series/299262/", seed)
+            .with_page("https://www.scribblehub.com/series/187402/", found);
+        let config = parse_config(
+            r#"
+site = "scribblehub"
+
+[criteria]
+min_pages = 500
+
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["https://www.scribblehub.com/series/299262/the-tower-climbers-apprentice/"]
+
+[run]
+stop_condition = { type = "empty_queue" }
+
+[[run.discovery]]
+kind = "also_liked"
+"#,
+        )
+        .unwrap();
+        let client = Arc::new(client);
+        let mut pipeline = Pipeline::with_client(config, client.clone()).unwrap();
+        let results = pipeline.run().unwrap();
+
+        // Page limits don't apply, and the similar series come from the page
+        let mut urls: Vec<&str> = results.iter().map(|score| score.novel.url.as_str()).collect();
+        urls.sort_unstable();
+        assert_eq!(
+            urls,
+            [
+                "https://www.scribblehub.com/series/187402/floor-zero/",
+                "https://www.scribblehub.com/series/299262/the-tower-climbers-apprentice/"
+            ]
+        );
+        assert!(results.iter().all(|score| score.novel.site == SiteId::ScribbleHub));
+        assert!(client.requests().iter().all(|url| url.contains("/series/")));
+    }
+
+    #[test]
+    fn test_discovery_skipped_for_low_scores() {
+        let mut pipeline = fixture_pipeline("discovery_min_score = 0.5");
+        pipeline.evaluator = Box::new(FakeEvaluator {
+            scores: HashMap::from([(90435, 0.9), (1, 0.2), (2, 0.8), (3, 0.5)]),
+        });
+        let (discoverer, asked) = fake_discoverer(None, &[1, 2, 3]);
+        pipeline.discovery = vec![discoverer];
+        let results = pipeline.run().unwrap();
+
+        // Every novel was evaluated, but only those scoring at least 0.5
+        // were used for discovery
+        assert_eq!(results.len(), 4);
+        assert_eq!(*asked.lock().unwrap(), vec![90435, 2, 3]);
+        assert_eq!(pipeline.stats().discovery_skipped_low_score, 1);
+
+        // The default lets every evaluated novel through
+        let mut pipeline = fixture_pipeline("");
+        pipeline.evaluator = Box::new(FakeEvaluator {
+            scores: HashMap::from([(90435, 0.0), (1, 0.0)]),
+        });
+        let (discoverer, asked) = fake_discoverer(None, &[1]);
+        pipeline.discovery = vec![discoverer];
+        pipeline.run().unwrap();
+        assert_eq!(*asked.lock().unwrap(), vec![90435, 1]);
+        assert_eq!(pipeline.stats().discovery_skipped_low_score, 0);
+    }
+
+    #[test]
+    fn test_discovered_novels_capped_per_seed() {
+        let mut pipeline = fixture_pipeline("max_discovery_ratio = 2.0");
+        pipeline.evaluator = Box::new(FakeEvaluator {
+            scores: HashMap::from([(90435, 0.9), (1, 0.8), (2, 0.7), (3, 0.6), (4, 0.5)]),
+        });
+        let (discoverer, asked) = fake_discoverer(None, &[1, 2, 3, 4]);
+        pipeline.discovery = vec![discoverer];
+        let results = pipeline.run().unwrap();
+
+        // One seed allows two discovered novels; the rest were found only
+        let ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+        assert_eq!(ids, vec![90435, 1, 2]);
+        assert_eq!(*asked.lock().unwrap(), vec![90435, 1, 2]);
+        let found: Vec<u64> = pipeline.stats().found_not_evaluated.iter().map(|f| f.id).collect();
+        assert_eq!(found, vec![3, 4]);
+        assert_eq!(
+            pipeline.stats().found_not_evaluated[0].url,
+            "https://www.royalroad.com/fiction/3"
+        );
+    }
+
+    #[test]
+    fn test_discovery_ratio_reached() {
+        assert!(!discovery_ratio_reached(None, 0, 100));
+        assert!(!discovery_ratio_reached(Some(3.0), 1, 2));
+        assert!(discovery_ratio_reached(Some(3.0), 1, 3));
+        assert!(!discovery_ratio_reached(Some(1.5), 2, 2));
+        assert!(discovery_ratio_reached(Some(1.5), 2, 3));
+        // No seeds evaluated yet, or a ratio of 0, lets no discovered novel in
+        assert!(discovery_ratio_reached(Some(3.0), 0, 0));
+        assert!(discovery_ratio_reached(Some(0.0), 5, 0));
+    }
+
+    #[test]
+    fn test_discovery_sources_gated_by_source_score() {
+        let mut pipeline = fixture_pipeline("");
+        let (any_score, any_asked) = fake_discoverer(None, &[1, 2]);
+        let (some_score, some_asked) = fake_discoverer(Some(0.0), &[3]);
+        let (perfect_score, perfect_asked) = fake_discoverer(Some(1.0), &[4]);
+        pipeline.discovery = vec![any_score, some_score, perfect_score];
+        let results = pipeline.run().unwrap();
+
+        // Every source saw the seed but the strict one; novels found by one
+        // source were used as sources in turn
+        assert!(results.iter().all(|score| score.overall_score < 1.0));
+        let mut ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 2, 3, 90435]);
+        assert_eq!(*any_asked.lock().unwrap(), vec![90435, 1, 2, 3]);
+        assert_eq!(*some_asked.lock().unwrap(), vec![90435, 1, 2, 3]);
+        assert!(perfect_asked.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_discoverer_wants_source() {
+        let (any_score, _) = fake_discoverer(None, &[]);
+        assert!(any_score.wants_source(None));
+        assert!(any_score.wants_source(Some(0.0)));
+        let (min_half, _) = fake_discoverer(Some(0.5), &[]);
+        assert!(min_half.wants_source(Some(0.5)));
+        assert!(min_half.wants_source(Some(0.9)));
+        assert!(!min_half.wants_source(Some(0.49)));
+        // Novels that weren't scored have no score to clear the bar with
+        assert!(!min_half.wants_source(None));
+    }
+
+    #[test]
+    fn test_make_deterministic() {
+        let pinned = Timestamp::from_days(20_000);
+        let mut pipeline = fixture_pipeline("");
+        // Without a cache there is no fetch time to fall back on
+        assert!(pipeline.make_deterministic(&Deterministic::default()).is_err());
+        pipeline
+            .make_deterministic(&Deterministic {
+                timestamp: Some(pinned),
+                ..Default::default()
+            })
+            .unwrap();
+        pipeline.run().unwrap();
+        assert_eq!(pipeline.run_metadata().finished_at, pinned);
+        assert_eq!(pipeline.stats().total, Duration::ZERO);
+        assert_eq!(pipeline.stats().phases[&Phase::SeedGathering].count, 1);
+
+        pipeline.config.seed_source = SeedSource::Random(crate::config::RandomSeeds {
+            count: 1,
+            max_id: 10,
+            rng_seed: Some(3),
+            max_attempts: 1,
+        });
+        let mut seeded = |seed| {
+            let settings = Deterministic {
+                seed,
+                timestamp: Some(pinned),
+            };
+            pipeline.make_deterministic(&settings).unwrap();
+            match &pipeline.config.seed_source {
+                SeedSource::Random(seeds) => seeds.rng_seed,
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(seeded(None), Some(3));
+        assert_eq!(seeded(Some(7)), Some(7));
+
+        let mut timed = fixture_pipeline("max_seconds_per_novel = 60");
+        let settings = Deterministic {
+            timestamp: Some(pinned),
+            ..Default::default()
+        };
+        assert!(timed.make_deterministic(&settings).is_err());
+        let mut budgeted = fixture_pipeline("seed_time_budget = \"1m\"");
+        let err = budgeted.make_deterministic(&settings).unwrap_err().to_string();
+        assert!(err.contains("seed_time_budget"), "{}", err);
+    }
+}
@@ -0,0 +1,198 @@
+//! Rendering the LLM evaluator's prompts for a novel without calling its API.
+//!
+//! The `prompt` command prints the system and user prompts the LLM evaluator
+//! would send for one novel, for iterating on criteria wording and prompt
+//! templates without paying for API calls. The novel is taken from a saved
+//! dataset when one is given, making no requests at all, or else scraped
+//! the way `rank` does. Its description is cleaned and its reviews selected
+//! as in a run.
+
+use crate::config::{AppConfig, EvalMode, LlmEvalConfig};
+use crate::eval::llm::{build_user_prompt, format_prompts, system_prompt};
+use crate::eval::text::DescriptionCleaner;
+use crate::models::{select_reviews, Criteria, Novel, Review};
+use crate::pipeline::{parse_novel_id, FIRST_CHAPTER_EXCERPT_CHARS};
+use crate::rescore::Dataset;
+use crate::scraper::budget::BudgetGuard;
+use crate::scraper::chapter::scrape_first_chapter_excerpt;
+use crate::scraper::reviews::MAX_REVIEWS;
+use crate::scraper::HttpFetch;
+use crate::site::scrape_novel_with_reviews;
+use anyhow::Result;
+
+/// Find the novel with the given ID or URL in `dataset`, or scrape it
+/// through `client` without one, with its reviews. A scraped novel gets a
+/// first chapter excerpt if the LLM evaluator is set to include one.
+pub fn load_novel(
+    config: &AppConfig,
+    client: &dyn HttpFetch,
+    url_or_id: &str,
+    dataset: Option<&Dataset>,
+) -> Result<(Novel, Vec<Review>)> {
+    let site = config.site.site();
+    let id = parse_novel_id(site.as_ref(), url_or_id)?;
+    if let Some(dataset) = dataset {
+        let Some(novel) = dataset.novels.iter().find(|novel| novel.id == id) else {
+            anyhow::bail!("Novel {} is not in the dataset", id);
+        };
+        let reviews = dataset.reviews.get(&id).cloned().unwrap_or_default();
+        return Ok((novel.clone(), reviews));
+    }
+
+    let chapters = config.scraper.store_chapter_titles;
+    let (mut novel, reviews) =
+        scrape_novel_with_reviews(site.as_ref(), client, id, MAX_REVIEWS, chapters)?;
+    let reads_prose = matches!(
+        config.eval_mode,
+        EvalMode::Llm(LlmEvalConfig {
+            include_first_chapter: true,
+            ..
+        })
+    );
+    if reads_prose {
+        let budget = BudgetGuard::unlimited(client);
+        match scrape_first_chapter_excerpt(
+            site.as_ref(),
+            client,
+            &novel,
+            FIRST_CHAPTER_EXCERPT_CHARS,
+            &budget,
+        ) {
+            Ok(excerpt) => novel.first_chapter_excerpt = Some(excerpt),
+            Err(e) => tracing::warn!("Fetching first chapter failed: {:#}", e),
+        }
+    }
+    Ok((novel, reviews))
+}
+
+/// The system and user prompts for `novel` against the config's criteria,
+/// or the named profile's. A config with profiles needs one named.
+pub fn render_prompts(
+    config: &AppConfig,
+    novel: &Novel,
+    reviews: &[Review],
+    profile: Option<&str>,
+) -> Result<String> {
+    let criteria = profile_criteria(config, profile)?;
+    let cleaner = DescriptionCleaner::new(&config.description_strip_patterns)?;
+    let novel = Novel {
+        description: cleaner.clean(&novel.description),
+        ..novel.clone()
+    };
+    let reviews: Vec<Review> = select_reviews(reviews, &config.review_policy)
+        .into_iter()
+        .cloned()
+        .collect();
+    let user_prompt = build_user_prompt(&novel, &reviews, criteria);
+    Ok(format_prompts(&system_prompt(), &user_prompt))
+}
+
+/// The criteria of the profile named `profile`, or the config's criteria
+/// without profiles.
+fn profile_criteria<'a>(config: &'a AppConfig, profile: Option<&str>) -> Result<&'a Criteria> {
+    let names = || {
+        let names: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+        names.join(", ")
+    };
+    match profile {
+        None if config.profiles.is_empty() => Ok(&config.criteria),
+        None => anyhow::bail!("Choose a profile with --profile (profiles: {})", names()),
+        Some(name) if config.profiles.is_empty() => {
+            anyhow::bail!("Unknown profile: {} (the config has no profiles)", name)
+        }
+        Some(name) => match config.profiles.iter().find(|p| p.name == name) {
+            Some(profile) => Ok(&profile.criteria),
+            None => anyhow::bail!("Unknown profile: {} (profiles: {})", name, names()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_config;
+    use crate::models::test_novel;
+    use crate::scraper::FakeClient;
+
+    const CONFIG: &str = r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["1"]
+
+[run]
+stop_condition = { type = "empty_queue" }
+
+[criteria]
+prompt = "a bunny that evolves"
+"#;
+
+    #[test]
+    fn test_renders_prompts_for_a_scraped_novel() {
+        let page = include_str!("scraper/testdata/novel_page_90435.html");
+        let client = FakeClient::new().with_page("https://www.royalroad.com/fiction/90435", page);
+        let config = parse_config(CONFIG).unwrap();
+        let (novel, reviews) = load_novel(&config, &client, "90435", None).unwrap();
+        assert_eq!(client.request_count(), 1);
+
+        let prompts = render_prompts(&config, &novel, &reviews, None).unwrap();
+        assert!(prompts.starts_with(&format!("## System prompt\n{}\n", system_prompt())));
+        assert!(prompts.contains("## User prompt\n## Reader criteria\na bunny that evolves\n"));
+        assert!(prompts.contains("Bunny Girl Evolution"), "{}", prompts);
+    }
+
+    #[test]
+    fn test_dataset_novel_needs_no_requests() {
+        let client = FakeClient::new();
+        let config = parse_config(CONFIG).unwrap();
+        let dataset = Dataset {
+            novels: vec![test_novel(7)],
+            ..Default::default()
+        };
+        let url = "https://www.royalroad.com/fiction/7/novel-7";
+        let (novel, reviews) = load_novel(&config, &client, url, Some(&dataset)).unwrap();
+        assert_eq!(novel.id, 7);
+        assert!(reviews.is_empty());
+        assert_eq!(client.request_count(), 0);
+
+        let err = load_novel(&config, &client, "8", Some(&dataset)).unwrap_err();
+        assert_eq!(err.to_string(), "Novel 8 is not in the dataset");
+    }
+
+    #[test]
+    fn test_profile_choice() {
+        let config = parse_config(CONFIG).unwrap();
+        let novel = test_novel(1);
+        let err = render_prompts(&config, &novel, &[], Some("cozy")).unwrap_err();
+        assert!(
+            err.to_string().contains("the config has no profiles"),
+            "{}",
+            err
+        );
+
+        let with_profiles = format!(
+            "{}\n[[profiles]]\nname = \"cozy\"\nprompt = \"low stakes\"\n\
+             [[profiles]]\nname = \"grim\"\nprompt = \"high stakes\"\n",
+            CONFIG
+        );
+        let config = parse_config(&with_profiles).unwrap();
+        let prompts = render_prompts(&config, &novel, &[], Some("grim")).unwrap();
+        assert!(
+            prompts.contains("## Reader criteria\nhigh stakes\n"),
+            "{}",
+            prompts
+        );
+        let err = render_prompts(&config, &novel, &[], None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Choose a profile with --profile (profiles: cozy, grim)"
+        );
+        let err = render_prompts(&config, &novel, &[], Some("warm")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown profile: warm (profiles: cozy, grim)"
+        );
+    }
+}
@@ -2,19 +2,29 @@
 //!
 //! Maintains a queue of novels to be evaluated, ensuring that each novel
 //! is only processed once and providing basic priority ordering.
+//!
+//! What's left in the queue when a run stops can be written out as a seeds
+//! file: one fiction ID per line, with the title as a `#` comment. The
+//! `file` seed source reads the same format, so a later run picks up where
+//! this one stopped.
 
-use crate::models::Novel;
+use crate::models::{Novel, NovelKey};
+use anyhow::{Context, Result};
 use std::collections::{HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::path::Path;
 
 /// A queue for managing novels awaiting evaluation.
 ///
-/// Provides deduplication via a set of seen novel IDs and a FIFO queue
-/// for processing order. Can be extended with priority-based ordering.
+/// Provides deduplication via a set of seen novels, by their site and ID,
+/// and a FIFO queue for processing order. Can be extended with
+/// priority-based ordering.
+#[derive(Default)]
 pub struct NovelQueue {
     /// The queue of novels waiting to be processed.
     queue: VecDeque<Novel>,
-    /// Set of novel IDs that have already been seen (queued or processed).
-    seen: HashSet<u64>,
+    /// Novels that have already been seen (queued or processed).
+    seen: HashSet<NovelKey>,
 }
 
 impl NovelQueue {
@@ -30,15 +40,22 @@ impl NovelQueue {
     ///
     /// Returns `true` if the novel was added, `false` if it was a duplicate.
     pub fn push(&mut self, novel: Novel) -> bool {
-        if self.seen.contains(&novel.id) {
-            tracing::debug!("Skipping duplicate novel: {} (ID: {})", novel.title, novel.id);
+        if self.seen.contains(&novel.key()) {
+            tracing::debug!("Skipping duplicate novel: {} ({})", novel.title, novel.key());
             return false;
         }
-        self.seen.insert(novel.id);
+        self.seen.insert(novel.key());
         self.queue.push_back(novel);
         true
     }
 
+    /// Put a novel taken with [`pop`](Self::pop) back at the front, as the
+    /// next one out.
+    pub fn push_front(&mut self, novel: Novel) {
+        self.seen.insert(novel.key());
+        self.queue.push_front(novel);
+    }
+
     /// Remove and return the next novel from the queue.
     pub fn pop(&mut self) -> Option<Novel> {
         self.queue.pop_front()
@@ -54,8 +71,141 @@ impl NovelQueue {
         self.queue.len()
     }
 
-    /// Check whether a novel ID has already been seen.
-    pub fn has_seen(&self, novel_id: u64) -> bool {
-        self.seen.contains(&novel_id)
+    /// The IDs of the novels waiting in the queue, in order.
+    pub fn queued_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.queued().map(|novel| novel.id)
+    }
+
+    /// The novels waiting in the queue, in order.
+    pub fn queued(&self) -> impl Iterator<Item = &Novel> + '_ {
+        self.queue.iter()
+    }
+
+    /// Reorder the waiting novels: those at the positions in `order` come
+    /// first, in that order, followed by the rest in their current order.
+    /// Positions out of range or listed twice are ignored.
+    pub fn reorder(&mut self, order: &[usize]) {
+        let mut novels: Vec<Option<Novel>> = self.queue.drain(..).map(Some).collect();
+        let mut reordered = VecDeque::with_capacity(novels.len());
+        for &position in order {
+            if let Some(novel) = novels.get_mut(position).and_then(Option::take) {
+                reordered.push_back(novel);
+            }
+        }
+        reordered.extend(novels.into_iter().flatten());
+        self.queue = reordered;
+    }
+
+    /// Check whether a novel has already been seen.
+    pub fn has_seen(&self, key: NovelKey) -> bool {
+        self.seen.contains(&key)
+    }
+}
+
+/// Write `novels` to a seeds file at `path`, one ID per line with its title
+/// as a comment.
+pub fn write_seeds_file<'a>(
+    path: &Path,
+    novels: impl IntoIterator<Item = &'a Novel>,
+) -> Result<()> {
+    let mut content = String::from("# Novels left in the queue, for [seeds] source = \"file\"\n");
+    for novel in novels {
+        // Titles are kept to one line so they stay inside the comment
+        let title = novel.title.split_whitespace().collect::<Vec<_>>().join(" ");
+        let _ = if title.is_empty() {
+            writeln!(content, "{}", novel.id)
+        } else {
+            writeln!(content, "{}  # {}", novel.id, title)
+        };
+    }
+    std::fs::write(path, content)
+        .with_context(|| format!("Failed to write seeds file: {}", path.display()))
+}
+
+/// Read the seeds in a seeds file: one fiction ID or URL per line. Blank
+/// lines and everything after a `#` are ignored.
+pub fn read_seeds_file(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read seeds file: {}", path.display()))?;
+    Ok(parse_seeds(&content))
+}
+
+/// The seeds listed in a seeds file's text.
+pub fn parse_seeds(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, SiteId};
+
+    #[test]
+    fn test_parse_seeds() {
+        let content = "# header\n\n90435  # Bunny Girl Evolution\n  \
+                       https://www.royalroad.com/fiction/89877/cursed\n# 1\n115399#x\n";
+        assert_eq!(
+            parse_seeds(content),
+            ["90435", "https://www.royalroad.com/fiction/89877/cursed", "115399"]
+        );
+    }
+
+    #[test]
+    fn test_seeds_file_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "novel-finder-seeds-{}.txt",
+            std::process::id()
+        ));
+        let mut titled = test_novel(90435);
+        titled.title = "Bunny Girl\nEvolution # 2".to_string();
+        let mut untitled = test_novel(89877);
+        untitled.title = String::new();
+        write_seeds_file(&path, [&titled, &untitled]).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let seeds = read_seeds_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(content.contains("90435  # Bunny Girl Evolution # 2\n"), "{}", content);
+        assert_eq!(seeds, ["90435", "89877"]);
+    }
+
+    #[test]
+    fn test_push_front_comes_out_next() {
+        let mut queue = NovelQueue::new();
+        queue.push(test_novel(1));
+        queue.push(test_novel(2));
+        let first = queue.pop().unwrap();
+        queue.push_front(first);
+        assert_eq!(queue.queued_ids().collect::<Vec<_>>(), [1, 2]);
+        assert!(!queue.push(test_novel(1)));
+    }
+
+    #[test]
+    fn test_reorder() {
+        let mut queue = NovelQueue::new();
+        for id in 1..=5 {
+            queue.push(test_novel(id));
+        }
+        queue.reorder(&[3, 0, 3, 9]);
+        assert_eq!(queue.queued_ids().collect::<Vec<_>>(), [4, 1, 2, 3, 5]);
+        assert!(!queue.push(test_novel(4)));
+    }
+
+    #[test]
+    fn test_same_id_on_another_site_is_not_a_duplicate() {
+        let mut queue = NovelQueue::new();
+        assert!(queue.push(test_novel(1)));
+        let mut other = test_novel(1);
+        other.site = SiteId::ScribbleHub;
+        assert!(!queue.has_seen(other.key()));
+        assert!(queue.push(other));
+        assert!(queue.has_seen(NovelKey::new(SiteId::RoyalRoad, 1)));
+        assert!(!queue.push(test_novel(1)));
+        assert_eq!(queue.len(), 2);
     }
 }
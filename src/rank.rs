@@ -0,0 +1,158 @@
+//! Ranking a handful of specific novels against each other.
+//!
+//! The `rank` command takes RoyalRoad URLs or fiction IDs, scrapes each
+//! novel's page (which carries its reviews too), and scores the batch the
+//! way `rescore` scores a dataset: no discovery, no stop condition, just the
+//! novels given, ranked best first. Novels that can't be scraped are skipped
+//! with a warning.
+
+use crate::config::AppConfig;
+use crate::pipeline::{build_client, parse_novel_id};
+use crate::rescore::{batch_metadata, score_batch, Dataset, Rescored};
+use crate::scraper::reviews::MAX_REVIEWS;
+use crate::scraper::HttpFetch;
+use crate::site::scrape_novel_with_reviews;
+use crate::stats::{Phase, RunStats};
+use anyhow::Result;
+use std::time::Instant;
+
+/// Scrape and rank the novels with the given IDs or URLs through a client
+/// built from the config's scraper settings.
+pub fn rank(config: &AppConfig, novels: &[String]) -> Result<Rescored> {
+    rank_with_client(config, &build_client(config)?, novels)
+}
+
+/// Scrape and rank the novels with the given IDs or URLs through `client`.
+/// Fails before any request if one isn't an ID or fiction URL.
+pub fn rank_with_client(
+    config: &AppConfig,
+    client: &dyn HttpFetch,
+    novels: &[String],
+) -> Result<Rescored> {
+    let started = Instant::now();
+    let site = config.site.site();
+    let mut ids: Vec<u64> = Vec::new();
+    for url_or_id in novels {
+        let id = parse_novel_id(site.as_ref(), url_or_id)?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    tracing::info!("Ranking {} novels", ids.len());
+
+    let mut stats = RunStats::default();
+    let mut dataset = Dataset::default();
+    for id in ids {
+        let scraping = Instant::now();
+        let chapters = config.scraper.store_chapter_titles;
+        let scraped = scrape_novel_with_reviews(site.as_ref(), client, id, MAX_REVIEWS, chapters);
+        stats.record(Phase::NovelScrape, scraping.elapsed(), Some(id));
+        match scraped {
+            Ok((novel, reviews)) => {
+                dataset.reviews.insert(id, reviews);
+                dataset.novels.push(novel);
+            }
+            Err(e) => tracing::warn!("Skipping novel {}: {:#}", id, e),
+        }
+    }
+
+    let results = score_batch(config, &dataset, &mut stats)?;
+    stats.total = started.elapsed();
+    tracing::info!(
+        "Ranking complete. {} of {} novels passed the filters.",
+        stats.novels_evaluated,
+        dataset.novels.len()
+    );
+
+    let seeds = format!("{} novels given to rank", novels.len());
+    let metadata = batch_metadata(
+        config,
+        seeds,
+        "every novel given",
+        client.request_count(),
+        &stats,
+    );
+    Ok(Rescored {
+        results,
+        stats,
+        metadata,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_config;
+    use crate::scraper::FakeClient;
+
+    const CONFIG: &str = r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["1"]
+
+[run]
+stop_condition = { type = "empty_queue" }
+
+[criteria]
+max_pages = 1000
+"#;
+
+    fn fixture(name: &str) -> String {
+        let path = format!(
+            "{}/src/scraper/testdata/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    fn client() -> FakeClient {
+        ["90435", "89877", "115399"]
+            .iter()
+            .fold(FakeClient::new(), |client, id| {
+                client.with_page(
+                    &format!("https://www.royalroad.com/fiction/{}", id),
+                    &fixture(&format!("novel_page_{}.html", id)),
+                )
+            })
+    }
+
+    #[test]
+    fn test_ranks_the_novels_given() {
+        let client = client();
+        let novels: Vec<String> = [
+            "115399",
+            "https://www.royalroad.com/fiction/90435/bunny-girl-evolution",
+            "89877",
+            "404",
+            "90435",
+        ]
+        .map(String::from)
+        .to_vec();
+        let ranked = rank_with_client(&parse_config(CONFIG).unwrap(), &client, &novels).unwrap();
+
+        // 89877 is too long and 404 has no page; 90435 is scraped once
+        let mut ids: Vec<u64> = ranked.results.iter().map(|s| s.novel.id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, [90435, 115399]);
+        assert!(ranked.results[0].overall_score >= ranked.results[1].overall_score);
+        assert_eq!(client.requests().len(), 4);
+        assert_eq!(ranked.metadata.total_requests, 4);
+        assert_eq!(ranked.stats.novels_evaluated, 2);
+        assert_eq!(ranked.stats.phases[&Phase::NovelScrape].count, 4);
+    }
+
+    #[test]
+    fn test_bad_id_fails_before_scraping() {
+        let client = client();
+        let novels = ["90435".to_string(), "not a novel".to_string()];
+        let err = rank_with_client(&parse_config(CONFIG).unwrap(), &client, &novels)
+            .err()
+            .expect("a bad ID was accepted");
+        assert!(err.to_string().contains("not a novel"), "{}", err);
+        assert!(client.requests().is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+//! Evaluators and discovery sources looked up by name.
+//!
+//! A config's `eval.mode` and each `[[run.discovery]]` entry's `kind` name
+//! a factory in a [`Registry`], which builds the evaluator or source when the
+//! pipeline is built. The built-in modes (`local`, `llm`) and sources
+//! (`also_liked`) are registered in every registry; programs using the
+//! library add their own through
+//! [`PipelineBuilder`](crate::pipeline::PipelineBuilder), and configs then
+//! name them like any other. A factory gets the table its settings were
+//! written in, since the config only checks the keys of built-in ones.
+
+use crate::config::{AppConfig, DiscoveryConfig, DiscoverySourceConfig, EvalMode};
+use crate::discovery::also_liked::AlsoLikedDiscovery;
+use crate::discovery::DiscoverySource;
+use crate::eval::llm::LlmEvaluator;
+use crate::eval::local::LocalEvaluator;
+use crate::eval::Evaluator;
+use crate::scraper::cache::ResponseCache;
+use crate::scraper::HttpFetch;
+use crate::site::Site;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Builds an evaluator from the `[eval]` table as written (empty for the
+/// built-in modes, whose settings the config has already checked) and the
+/// rest of the config.
+pub type EvaluatorFactory = Box<dyn Fn(&toml::Table, &AppConfig) -> Result<Box<dyn Evaluator>>>;
+
+/// Builds a discovery source from its `[[run.discovery]]` entry as written
+/// (empty for the built-in sources) and what else a source may need.
+pub type DiscoveryFactory =
+    Box<dyn Fn(&toml::Table, &DiscoveryContext) -> Result<Box<dyn DiscoverySource>>>;
+
+/// What a discovery source is built with besides its own settings.
+pub struct DiscoveryContext<'a> {
+    pub config: &'a AppConfig,
+    /// The entry being built, with its budget.
+    pub entry: &'a DiscoveryConfig,
+    /// The client the pipeline fetches pages through.
+    pub client: &'a Arc<dyn HttpFetch>,
+    /// The site the pipeline scrapes.
+    pub site: &'a Arc<dyn Site>,
+}
+
+/// Evaluator and discovery source factories by name.
+pub struct Registry {
+    evaluators: BTreeMap<String, EvaluatorFactory>,
+    discovery: BTreeMap<String, DiscoveryFactory>,
+}
+
+impl Registry {
+    /// Register `factory` as the evaluator for `eval.mode = "<name>"`,
+    /// replacing any registered under that name, built-in ones included.
+    pub fn register_evaluator(&mut self, name: &str, factory: EvaluatorFactory) {
+        self.evaluators.insert(name.to_string(), factory);
+    }
+
+    /// Register `factory` as the source for `[[run.discovery]]` entries of
+    /// `kind`, replacing any registered under that kind.
+    pub fn register_discovery(&mut self, kind: &str, factory: DiscoveryFactory) {
+        self.discovery.insert(kind.to_string(), factory);
+    }
+
+    /// The evaluator factory registered as `name`.
+    pub(crate) fn evaluator(&self, name: &str) -> Result<&EvaluatorFactory> {
+        match self.evaluators.get(name) {
+            Some(factory) => Ok(factory),
+            None => anyhow::bail!(
+                "eval.mode: Unknown eval mode: {} (known modes: {})",
+                name,
+                known(&self.evaluators)
+            ),
+        }
+    }
+
+    /// The discovery source factory registered as `kind`.
+    pub(crate) fn discovery(&self, kind: &str) -> Result<&DiscoveryFactory> {
+        match self.discovery.get(kind) {
+            Some(factory) => Ok(factory),
+            None => anyhow::bail!(
+                "run.discovery.kind: Unknown discovery source: {} (known sources: {})",
+                kind,
+                known(&self.discovery)
+            ),
+        }
+    }
+
+    /// Build the evaluator the config's `eval.mode` names.
+    pub fn build_evaluator(&self, config: &AppConfig) -> Result<Box<dyn Evaluator>> {
+        let factory = self.evaluator(config.eval_mode.name())?;
+        let settings = match &config.eval_mode {
+            EvalMode::Custom(custom) => custom.settings.clone(),
+            EvalMode::Local(_) | EvalMode::Llm(_) => toml::Table::new(),
+        };
+        factory(&settings, config)
+    }
+
+    /// Build the source one `[[run.discovery]]` entry names.
+    pub fn build_discovery(
+        &self,
+        config: &AppConfig,
+        entry: &DiscoveryConfig,
+        client: &Arc<dyn HttpFetch>,
+        site: &Arc<dyn Site>,
+    ) -> Result<Box<dyn DiscoverySource>> {
+        let factory = self.discovery(entry.source.kind())?;
+        let settings = match &entry.source {
+            DiscoverySourceConfig::Custom(custom) => custom.settings.clone(),
+            DiscoverySourceConfig::AlsoLiked(_) => toml::Table::new(),
+        };
+        let context = DiscoveryContext {
+            config,
+            entry,
+            client,
+            site,
+        };
+        factory(&settings, &context)
+    }
+}
+
+/// A registry of the built-in evaluators and discovery sources.
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self {
+            evaluators: BTreeMap::new(),
+            discovery: BTreeMap::new(),
+        };
+        registry.register_evaluator("local", Box::new(build_local));
+        registry.register_evaluator("llm", Box::new(build_llm));
+        registry.register_discovery("also_liked", Box::new(build_also_liked));
+        registry
+    }
+}
+
+/// The registered names, for error messages.
+fn known<T>(factories: &BTreeMap<String, T>) -> String {
+    factories
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn build_local(_settings: &toml::Table, config: &AppConfig) -> Result<Box<dyn Evaluator>> {
+    let EvalMode::Local(local) = &config.eval_mode else {
+        anyhow::bail!("eval.mode: the local evaluator needs [eval.local] settings");
+    };
+    Ok(Box::new(LocalEvaluator::new(local.clone())))
+}
+
+fn build_llm(_settings: &toml::Table, config: &AppConfig) -> Result<Box<dyn Evaluator>> {
+    let EvalMode::Llm(llm) = &config.eval_mode else {
+        anyhow::bail!("eval.mode: the LLM evaluator needs [eval.llm] settings");
+    };
+    Ok(Box::new(LlmEvaluator::new(
+        llm.clone(),
+        &config.scraper.proxy,
+    )?))
+}
+
+fn build_also_liked(
+    _settings: &toml::Table,
+    context: &DiscoveryContext,
+) -> Result<Box<dyn DiscoverySource>> {
+    let DiscoverySourceConfig::AlsoLiked(settings) = &context.entry.source else {
+        anyhow::bail!("run.discovery.kind: also_liked needs its own settings");
+    };
+    let config = context.config;
+    let cache = config.scraper.cache_dir.as_ref().map(|dir| {
+        ResponseCache::new(dir, config.scraper.cache_ttl).with_limits(config.scraper.cache_limits)
+    });
+    Ok(Box::new(AlsoLikedDiscovery::new(
+        Arc::clone(context.site),
+        Arc::clone(context.client),
+        config.criteria.clone(),
+        config.scraper.store_chapter_titles,
+        cache,
+        settings.clone(),
+        context.entry.max_per_novel,
+    )))
+}
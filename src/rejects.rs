@@ -0,0 +1,265 @@
+//! Novels rejected by the hard filters, and why.
+//!
+//! The pipeline records every novel that fails the pre-filter along with
+//! the [`FilterReason`]s it failed. Only the most recent rejections are kept
+//! in memory, for the `--show-rejects` table; with `--rejects <path>`, each
+//! one is also written to the file as it happens, so a long run can reject
+//! thousands of novels without holding them all.
+
+use crate::eval::filter::FilterReason;
+use crate::export::write_row;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// How many rejections are kept in memory.
+pub const RECENT_REJECTIONS: usize = 20;
+
+/// The header row of a rejects CSV.
+const CSV_HEADER: [&str; 4] = ["id", "title", "url", "reasons"];
+
+/// A novel that failed the hard filters.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rejection {
+    pub id: u64,
+    pub title: String,
+    pub url: String,
+    /// Every filter the novel failed, for every criteria profile.
+    pub reasons: Vec<FilterReason>,
+}
+
+impl Rejection {
+    /// The reasons as one line of text.
+    pub fn reasons_text(&self) -> String {
+        let reasons: Vec<String> = self.reasons.iter().map(ToString::to_string).collect();
+        reasons.join("; ")
+    }
+}
+
+/// The format of a rejects file, chosen by its extension: `.json` for a
+/// JSON array, anything else for CSV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RejectFormat {
+    Csv,
+    Json,
+}
+
+impl RejectFormat {
+    fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => RejectFormat::Json,
+            _ => RejectFormat::Csv,
+        }
+    }
+}
+
+/// A rejects file being written.
+struct RejectFile {
+    path: PathBuf,
+    format: RejectFormat,
+    writer: BufWriter<File>,
+    /// Rejections written so far.
+    written: usize,
+}
+
+impl RejectFile {
+    fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create rejects file {}", path.display()))?;
+        let mut rejects = RejectFile {
+            path: path.to_path_buf(),
+            format: RejectFormat::for_path(path),
+            writer: BufWriter::new(file),
+            written: 0,
+        };
+        let started = match rejects.format {
+            RejectFormat::Csv => write_row(&mut rejects.writer, &CSV_HEADER),
+            RejectFormat::Json => write!(rejects.writer, "["),
+        };
+        started.with_context(|| rejects.write_error())?;
+        Ok(rejects)
+    }
+
+    fn write(&mut self, rejection: &Rejection) -> Result<()> {
+        let written = match self.format {
+            RejectFormat::Csv => write_row(
+                &mut self.writer,
+                &[
+                    &rejection.id.to_string(),
+                    &rejection.title,
+                    &rejection.url,
+                    &rejection.reasons_text(),
+                ],
+            ),
+            RejectFormat::Json => {
+                let separator = if self.written == 0 { "" } else { "," };
+                let json = serde_json::to_string(rejection)?;
+                write!(self.writer, "{}\n  {}", separator, json)
+            }
+        };
+        written.with_context(|| self.write_error())?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Close the JSON array, if any, and flush.
+    fn finish(&mut self) -> Result<()> {
+        let finished = match self.format {
+            RejectFormat::Csv => Ok(()),
+            RejectFormat::Json if self.written == 0 => writeln!(self.writer, "]"),
+            RejectFormat::Json => write!(self.writer, "\n]\n"),
+        };
+        finished
+            .and_then(|()| self.writer.flush())
+            .with_context(|| self.write_error())
+    }
+
+    fn write_error(&self) -> String {
+        format!("Failed to write rejects file {}", self.path.display())
+    }
+}
+
+/// The rejections of a run: a count, the most recent few, and optionally a
+/// file every one is written to.
+#[derive(Default)]
+pub struct RejectLog {
+    recent: VecDeque<Rejection>,
+    total: usize,
+    file: Option<RejectFile>,
+}
+
+impl RejectLog {
+    /// A log that also writes every rejection to `path`, replacing any file
+    /// already there.
+    pub fn to_file(path: &Path) -> Result<Self> {
+        Ok(RejectLog {
+            file: Some(RejectFile::create(path)?),
+            ..RejectLog::default()
+        })
+    }
+
+    /// Record a rejection, writing it to the file if there is one.
+    pub fn record(&mut self, rejection: Rejection) -> Result<()> {
+        if let Some(file) = &mut self.file {
+            file.write(&rejection)?;
+        }
+        self.total += 1;
+        if self.recent.len() == RECENT_REJECTIONS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(rejection);
+        Ok(())
+    }
+
+    /// Complete and flush the file, if there is one.
+    pub fn finish(&mut self) -> Result<()> {
+        match &mut self.file {
+            Some(file) => file.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// How many novels were rejected.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The most recent rejections, oldest first.
+    pub fn recent(&self) -> impl Iterator<Item = &Rejection> {
+        self.recent.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NovelStatus;
+
+    fn rejection(id: u64, title: &str) -> Rejection {
+        Rejection {
+            id,
+            title: title.to_string(),
+            url: format!("https://www.royalroad.com/fiction/{}", id),
+            reasons: vec![
+                FilterReason::TooFewPages {
+                    pages: 50,
+                    min: 100,
+                },
+                FilterReason::StatusNotAllowed {
+                    status: NovelStatus::Hiatus,
+                },
+            ],
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("novel-finder-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_keeps_only_recent_rejections() {
+        let mut log = RejectLog::default();
+        for id in 0..RECENT_REJECTIONS as u64 + 5 {
+            log.record(rejection(id, "Novel")).unwrap();
+        }
+        assert_eq!(log.total(), RECENT_REJECTIONS + 5);
+        let ids: Vec<u64> = log.recent().map(|r| r.id).collect();
+        assert_eq!(ids, (5..RECENT_REJECTIONS as u64 + 5).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_writes_csv() {
+        let path = temp_path("rejects.csv");
+        let mut log = RejectLog::to_file(&path).unwrap();
+        log.record(rejection(1, "Plain")).unwrap();
+        log.record(rejection(2, "Commas, Quotes \"and\" All"))
+            .unwrap();
+        log.finish().unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            csv,
+            "id,title,url,reasons\n\
+             1,Plain,https://www.royalroad.com/fiction/1,\
+             50 pages < min 100; status Hiatus not in allowed list\n\
+             2,\"Commas, Quotes \"\"and\"\" All\",https://www.royalroad.com/fiction/2,\
+             50 pages < min 100; status Hiatus not in allowed list\n"
+        );
+    }
+
+    #[test]
+    fn test_writes_json() {
+        let path = temp_path("rejects.json");
+        let mut log = RejectLog::to_file(&path).unwrap();
+        log.record(rejection(1, "One")).unwrap();
+        log.record(rejection(2, "Two")).unwrap();
+        log.finish().unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[1]["title"], "Two");
+        assert_eq!(
+            json[0]["reasons"][0],
+            serde_json::json!({"reason": "too_few_pages", "pages": 50, "min": 100})
+        );
+        assert_eq!(json[0]["reasons"][1]["reason"], "status_not_allowed");
+    }
+
+    #[test]
+    fn test_empty_json_is_an_array() {
+        let path = temp_path("empty-rejects.json");
+        let mut log = RejectLog::to_file(&path).unwrap();
+        log.finish().unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(json, serde_json::json!([]));
+    }
+}
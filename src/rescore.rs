@@ -0,0 +1,344 @@
+//! Rescoring a saved dataset of novels without scraping.
+//!
+//! A dataset holds novels as they were scraped, each with its reviews if
+//! they were fetched, as written by `--export-dataset` (see
+//! [`persist::DatasetEntry`]). Rescoring runs the hard filters and the
+//! configured evaluator over it, so criteria can be tried out instantly: the
+//! local evaluator makes no network requests at all, and the LLM evaluator
+//! only calls its API. The same batch scoring ranks novels named on the
+//! command line (see [`crate::rank`]).
+
+use crate::config::AppConfig;
+use crate::eval::text::DescriptionCleaner;
+use crate::eval::Evaluator;
+use crate::models::timestamp::Timestamp;
+use crate::models::{rank_order, select_reviews, Criteria, Novel, NovelScore, Review};
+use crate::persist::{self, DatasetEntry};
+use crate::pipeline::build_evaluator;
+use crate::stats::{Phase, RunMetadata, RunStats, ScoreDistribution};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Instant;
+
+/// Novels to rescore, with their reviews kept apart.
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    pub novels: Vec<Novel>,
+    /// Reviews by novel ID. Novels saved without reviews have no entry.
+    pub reviews: HashMap<u64, Vec<Review>>,
+}
+
+impl Dataset {
+    /// Split the reviews out of saved entries.
+    pub fn from_entries(entries: Vec<DatasetEntry>) -> Self {
+        let mut dataset = Dataset::default();
+        for entry in entries {
+            if let Some(reviews) = entry.reviews {
+                dataset.reviews.insert(entry.novel.id, reviews);
+            }
+            dataset.novels.push(entry.novel);
+        }
+        dataset
+    }
+}
+
+/// Read the entries of a dataset file in any form `--export-dataset` writes.
+pub fn read_dataset(path: &Path) -> Result<Vec<DatasetEntry>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read dataset file: {}", path.display()))?;
+    persist::dataset_from_str(&content)
+        .with_context(|| format!("Failed to parse dataset file: {}", path.display()))
+}
+
+/// Load a dataset file for rescoring.
+pub fn load_dataset(path: &Path) -> Result<Dataset> {
+    Ok(Dataset::from_entries(read_dataset(path)?))
+}
+
+/// Score the novels that pass the hard filters, ranked best first. Novels
+/// with no entry in `reviews` are scored without reviews.
+pub fn evaluate_dataset(
+    novels: &[Novel],
+    reviews: &HashMap<u64, Vec<Review>>,
+    evaluator: &dyn Evaluator,
+    criteria: &Criteria,
+) -> Result<Vec<NovelScore>> {
+    let mut results = Vec::new();
+    for novel in novels {
+        if !evaluator.pre_filter(novel, criteria).is_empty() {
+            tracing::debug!("Novel '{}' failed pre-filter, skipping", novel.title);
+            continue;
+        }
+        let novel_reviews = reviews.get(&novel.id).map_or(&[][..], Vec::as_slice);
+        let mut score = evaluator
+            .evaluate(novel, novel_reviews, criteria)
+            .with_context(|| format!("Failed to evaluate novel '{}'", novel.title))?;
+        score.novel = novel.clone();
+        results.push(score);
+    }
+    results.sort_by(rank_order);
+    Ok(results)
+}
+
+/// A rescored dataset, ready for the usual outputs.
+pub struct Rescored {
+    pub results: Vec<NovelScore>,
+    pub stats: RunStats,
+    pub metadata: RunMetadata,
+}
+
+/// Rescore the dataset at `path` against the config's criteria (once per
+/// profile, with profiles), cleaning descriptions and selecting reviews the
+/// way a run does.
+pub fn rescore(config: &AppConfig, path: &Path) -> Result<Rescored> {
+    let started = Instant::now();
+    let dataset = load_dataset(path)?;
+    tracing::info!(
+        "Rescoring {} novels from {}",
+        dataset.novels.len(),
+        path.display()
+    );
+
+    let mut stats = RunStats::default();
+    let results = score_batch(config, &dataset, &mut stats)?;
+    stats.total = started.elapsed();
+    tracing::info!(
+        "Rescoring complete. {} of {} novels passed the filters.",
+        stats.novels_evaluated,
+        dataset.novels.len()
+    );
+
+    let metadata = batch_metadata(
+        config,
+        format!("dataset {}", path.display()),
+        "whole dataset",
+        0,
+        &stats,
+    );
+    Ok(Rescored {
+        results,
+        stats,
+        metadata,
+    })
+}
+
+/// Score every novel of `dataset` that passes the hard filters, against
+/// the config's criteria (once per profile, with profiles), ranked best
+/// first. Descriptions are cleaned and reviews selected the way a run does;
+/// the results show the novels as given. Fills in the evaluation counts and
+/// timings of `stats`.
+pub fn score_batch(
+    config: &AppConfig,
+    dataset: &Dataset,
+    stats: &mut RunStats,
+) -> Result<Vec<NovelScore>> {
+    let evaluator = build_evaluator(config)?;
+    let cleaner = DescriptionCleaner::new(&config.description_strip_patterns)?;
+    let eval_novels: Vec<Novel> = dataset
+        .novels
+        .iter()
+        .map(|novel| Novel {
+            description: cleaner.clean(&novel.description),
+            ..novel.clone()
+        })
+        .collect();
+    let reviews: HashMap<u64, Vec<Review>> = dataset
+        .reviews
+        .iter()
+        .map(|(&id, reviews)| {
+            let selected = select_reviews(reviews, &config.review_policy);
+            (id, selected.into_iter().cloned().collect())
+        })
+        .collect();
+
+    let profiles: Vec<(Option<&str>, &Criteria)> = if config.profiles.is_empty() {
+        vec![(None, &config.criteria)]
+    } else {
+        config
+            .profiles
+            .iter()
+            .map(|p| (Some(p.name.as_str()), &p.criteria))
+            .collect()
+    };
+    let originals: HashMap<u64, &Novel> = dataset
+        .novels
+        .iter()
+        .map(|novel| (novel.id, novel))
+        .collect();
+    let mut results = Vec::new();
+    for (profile, criteria) in profiles {
+        let evaluating = Instant::now();
+        let scores = evaluate_dataset(&eval_novels, &reviews, evaluator.as_ref(), criteria)?;
+        stats.record(Phase::Evaluate, evaluating.elapsed(), None);
+        for mut score in scores {
+            // Show the description as given, not the cleaned copy
+            score.novel = originals[&score.novel.id].clone();
+            score.profile = profile.map(String::from);
+            results.push(score);
+        }
+    }
+    results.sort_by(rank_order);
+
+    let evaluated: HashSet<u64> = results.iter().map(|score| score.novel.id).collect();
+    stats.novels_evaluated = evaluated.len();
+    stats.score_distribution =
+        ScoreDistribution::from_scores(results.iter().map(|s| s.overall_score));
+    Ok(results)
+}
+
+/// The run metadata of a batch scored outside a pipeline run.
+pub fn batch_metadata(
+    config: &AppConfig,
+    seeds: String,
+    stop_condition: &str,
+    total_requests: u64,
+    stats: &RunStats,
+) -> RunMetadata {
+    RunMetadata {
+        finished_at: Timestamp::now(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        criteria: config.criteria.clone(),
+        profiles: config.profiles.clone(),
+        criteria_hash: config.criteria_hash(),
+        eval_mode: config.eval_mode.to_string(),
+        seeds,
+        stop_condition: stop_condition.to_string(),
+        total_requests,
+        duration_secs: stats.total.as_secs_f64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_config;
+    use crate::config::LocalEvalConfig;
+    use crate::eval::local::LocalEvaluator;
+    use crate::models::test_novel;
+
+    const CONFIG: &str = r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["https://www.royalroad.com/fiction/1"]
+
+[run]
+stop_condition = { type = "empty_queue" }
+
+[criteria]
+prompt = "magic academy"
+min_pages = 200
+"#;
+
+    fn novel(id: u64, pages: u64, description: &str) -> Novel {
+        Novel {
+            pages,
+            description: description.to_string(),
+            ..test_novel(id)
+        }
+    }
+
+    fn review(text: &str) -> Review {
+        Review {
+            author: "Reader".to_string(),
+            rating: 5.0,
+            text: text.to_string(),
+            posted_date: None,
+            helpful_votes: None,
+        }
+    }
+
+    fn local_evaluator() -> LocalEvaluator {
+        LocalEvaluator::new(LocalEvalConfig::default())
+    }
+
+    #[test]
+    fn test_evaluate_dataset_filters_and_ranks() {
+        let criteria = Criteria {
+            prompt: Some("magic academy".to_string()),
+            min_pages: Some(200),
+            ..Default::default()
+        };
+        let novels = vec![
+            novel(1, 500, "A quiet farming story."),
+            novel(2, 50, "A magic academy story, far too short."),
+            novel(3, 500, "A magic academy story."),
+        ];
+        let results =
+            evaluate_dataset(&novels, &HashMap::new(), &local_evaluator(), &criteria).unwrap();
+        let ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+        assert_eq!(results[0].novel.description, "A magic academy story.");
+    }
+
+    #[test]
+    fn test_evaluate_dataset_uses_reviews() {
+        let criteria = Criteria {
+            prompt: Some("magic academy".to_string()),
+            ..Default::default()
+        };
+        let novels = vec![novel(1, 500, "A story."), novel(2, 500, "A story.")];
+        let reviews = HashMap::from([(2, vec![review("The magic academy arc is great.")])]);
+        let results = evaluate_dataset(&novels, &reviews, &local_evaluator(), &criteria).unwrap();
+        assert_eq!(results[0].novel.id, 2);
+        assert!(results[0].overall_score > results[1].overall_score);
+    }
+
+    #[test]
+    fn test_rescore_dataset_file() {
+        let entry = |novel, reviews| DatasetEntry {
+            novel,
+            reviews,
+            scraped_at: None,
+            evaluated: false,
+        };
+        let entries = vec![
+            entry(novel(1, 500, "A farming story."), None),
+            entry(novel(2, 50, "Short."), None),
+            entry(
+                novel(3, 500, "A magic academy story."),
+                Some(vec![review("Best magic academy novel.")]),
+            ),
+        ];
+        let path =
+            std::env::temp_dir().join(format!("novel-finder-dataset-{}.json", std::process::id()));
+        let file = persist::DatasetFile { novels: entries };
+        std::fs::write(&path, persist::to_string(&file).unwrap()).unwrap();
+
+        let dataset = load_dataset(&path).unwrap();
+        assert_eq!(dataset.novels.len(), 3);
+        assert!(dataset.novels.iter().all(|novel| novel.reviews.is_none()));
+        assert_eq!(dataset.reviews.keys().collect::<Vec<_>>(), vec![&3]);
+
+        let rescored = rescore(&parse_config(CONFIG).unwrap(), &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let ids: Vec<u64> = rescored
+            .results
+            .iter()
+            .map(|score| score.novel.id)
+            .collect();
+        assert_eq!(ids, vec![3, 1]);
+        assert_eq!(rescored.stats.novels_evaluated, 2);
+        assert_eq!(rescored.metadata.total_requests, 0);
+        assert!(rescored.metadata.seeds.starts_with("dataset "));
+    }
+
+    #[test]
+    fn test_load_dataset_rejects_other_json() {
+        let path = std::env::temp_dir().join(format!(
+            "novel-finder-not-a-dataset-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, r#"{"results": []}"#).unwrap();
+        let err = load_dataset(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(
+            err.to_string().contains("Failed to parse dataset file"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,171 @@
+//! Stratified sampling of the seeded queue.
+//!
+//! A run that stops after a few novels evaluates the head of its queue, and
+//! seeds from a search ordered by popularity are all alike there. With
+//! `sampling = "stratified"` the seeded queue is split into strata of equal
+//! size by followers or rating, then reordered to take one novel from each
+//! stratum in turn, at random within a stratum. However early the run
+//! stops, the novels it evaluated span the whole range.
+
+use crate::config::{StratifiedSampling, StratifyBy};
+use crate::models::{Novel, NovelKey};
+use crate::queue::NovelQueue;
+use crate::stats::{SamplingStats, StratumStats};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+/// Reorder `queue` to sample it across strata as `settings` say. Returns
+/// the strata for the run stats, and each queued novel's stratum as an
+/// index into them.
+pub fn sample_queue(
+    queue: &mut NovelQueue,
+    settings: &StratifiedSampling,
+) -> (SamplingStats, HashMap<NovelKey, usize>) {
+    let (keys, values): (Vec<NovelKey>, Vec<f64>) = queue
+        .queued()
+        .map(|novel| (novel.key(), stratum_value(novel, settings.by)))
+        .unzip();
+    let strata = stratify(&values, settings.strata);
+
+    let mut stratum_of = HashMap::new();
+    let stats = strata
+        .iter()
+        .enumerate()
+        .map(|(index, members)| {
+            for &position in members {
+                stratum_of.insert(keys[position], index);
+            }
+            StratumStats {
+                low: values[members[0]],
+                high: values[members[members.len() - 1]],
+                queued: members.len(),
+                evaluated: 0,
+            }
+        })
+        .collect();
+
+    let mut rng = match settings.rng_seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    queue.reorder(&interleave(strata, &mut rng));
+    let stats = SamplingStats {
+        by: settings.by.to_string(),
+        strata: stats,
+    };
+    (stats, stratum_of)
+}
+
+/// The statistic `novel` is stratified by.
+fn stratum_value(novel: &Novel, by: StratifyBy) -> f64 {
+    match by {
+        StratifyBy::Followers => novel.followers as f64,
+        StratifyBy::Rating => novel.rating,
+    }
+}
+
+/// Split the positions of `values` into `strata` strata of as near equal
+/// size as possible, from the lowest values up. There are fewer strata when
+/// there are fewer values, so none is empty.
+fn stratify(values: &[f64], strata: usize) -> Vec<Vec<usize>> {
+    let mut positions: Vec<usize> = (0..values.len()).collect();
+    positions.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let strata = strata.min(positions.len());
+    let len = positions.len();
+    (0..strata)
+        .map(|index| positions[index * len / strata..(index + 1) * len / strata].to_vec())
+        .collect()
+}
+
+/// An order taking one position from each stratum in turn, shuffling each
+/// stratum first.
+fn interleave(mut strata: Vec<Vec<usize>>, rng: &mut impl Rng) -> Vec<usize> {
+    for stratum in &mut strata {
+        stratum.shuffle(rng);
+    }
+    let longest = strata.iter().map(Vec::len).max().unwrap_or(0);
+    (0..longest)
+        .flat_map(|round| {
+            strata
+                .iter()
+                .filter_map(move |stratum| stratum.get(round).copied())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_novel;
+
+    /// A queue of 100 novels ordered by followers, most first, the way a
+    /// popularity search lists them, with followers falling off steeply.
+    fn popular_first_queue() -> NovelQueue {
+        let mut queue = NovelQueue::new();
+        for id in (1..=100u64).rev() {
+            let mut novel = test_novel(id);
+            novel.followers = id * id * id;
+            novel.rating = 3.0 + id as f64 / 50.0;
+            queue.push(novel);
+        }
+        queue
+    }
+
+    fn settings(by: StratifyBy, rng_seed: u64) -> StratifiedSampling {
+        StratifiedSampling {
+            by,
+            strata: 5,
+            rng_seed: Some(rng_seed),
+        }
+    }
+
+    #[test]
+    fn test_stratify() {
+        let strata = stratify(&[5.0, 1.0, 4.0, 2.0, 3.0, 0.0, 6.0], 3);
+        assert_eq!(strata, [vec![5, 1], vec![3, 4], vec![2, 0, 6]]);
+        assert_eq!(stratify(&[2.0, 1.0], 5), [vec![1], vec![0]]);
+        assert!(stratify(&[], 5).is_empty());
+    }
+
+    #[test]
+    fn test_sample_spreads_the_queue_head_across_strata() {
+        let mut queue = popular_first_queue();
+        let (stats, stratum_of) = sample_queue(&mut queue, &settings(StratifyBy::Followers, 1));
+        assert_eq!(stats.by, "followers");
+        assert_eq!(stats.strata.len(), 5);
+        assert!(stats.strata.iter().all(|stratum| stratum.queued == 20));
+        assert_eq!((stats.strata[0].low, stats.strata[0].high), (1.0, 8000.0));
+        assert_eq!(stats.strata[4].high, 1_000_000.0);
+
+        // Every run of five from the front holds one novel from each stratum,
+        // where the unsampled queue's first twenty all came from the top one
+        assert_eq!(queue.len(), 100);
+        let head: Vec<usize> = queue
+            .queued()
+            .map(|novel| stratum_of[&novel.key()])
+            .collect();
+        for round in head.chunks(5) {
+            let mut strata = round.to_vec();
+            strata.sort_unstable();
+            assert_eq!(strata, [0, 1, 2, 3, 4]);
+        }
+    }
+
+    #[test]
+    fn test_sample_is_reproducible_with_a_seed() {
+        let order = |by, seed| {
+            let mut queue = popular_first_queue();
+            sample_queue(&mut queue, &settings(by, seed));
+            queue.queued_ids().collect::<Vec<_>>()
+        };
+        assert_eq!(order(StratifyBy::Rating, 7), order(StratifyBy::Rating, 7));
+        assert_ne!(order(StratifyBy::Rating, 7), order(StratifyBy::Rating, 8));
+
+        let mut queue = popular_first_queue();
+        let (stats, _) = sample_queue(&mut queue, &settings(StratifyBy::Rating, 7));
+        assert_eq!(stats.by, "rating");
+        assert_eq!((stats.strata[0].low, stats.strata[4].high), (3.02, 5.0));
+    }
+}
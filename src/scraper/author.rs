@@ -0,0 +1,210 @@
+//! Scrape an author's fiction list from their RoyalRoad profile.
+//!
+//! Used to judge an author's track record from their other works.
+
+use crate::models::AuthorReputation;
+use crate::scraper::budget::BudgetGuard;
+use crate::scraper::HttpFetch;
+use crate::site::royalroad;
+use anyhow::{Context, Result};
+use scraper::{Html, Selector};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// One fiction listed on an author's profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorFiction {
+    /// The RoyalRoad fiction ID.
+    pub id: u64,
+    /// Title of the fiction.
+    pub title: String,
+    /// Number of followers.
+    pub followers: u64,
+    /// Overall rating, if the fiction has been rated.
+    pub rating: Option<f64>,
+}
+
+/// Scrape the list of fictions an author has published.
+///
+/// # Arguments
+/// * `client` - The HTTP client to use for requests.
+/// * `author_id` - The author's RoyalRoad profile ID.
+#[tracing::instrument(skip(client), fields(phase = "scrape"))]
+pub fn scrape_author_fictions(
+    client: &dyn HttpFetch,
+    author_id: u64,
+) -> Result<Vec<AuthorFiction>> {
+    let html = client.fetch(&royalroad::author_fictions_url(author_id))?;
+    parse_author_fictions(&html)
+}
+
+/// Parse the fiction list from the raw HTML of an author's fictions page.
+pub(crate) fn parse_author_fictions(html: &str) -> Result<Vec<AuthorFiction>> {
+    let document = Html::parse_document(html);
+    let item_selector = Selector::parse("div.fiction-list-item").expect("valid selector");
+    let title_selector = Selector::parse("h2.fiction-title a").expect("valid selector");
+    let stat_selector = Selector::parse("div.stats span").expect("valid selector");
+
+    let mut fictions = Vec::new();
+    for item in document.select(&item_selector) {
+        let link = item
+            .select(&title_selector)
+            .next()
+            .context("fiction entry without a title link")?;
+        let href = link.value().attr("href").unwrap_or_default();
+        let Some(id) = parse_fiction_id(href) else {
+            tracing::debug!("Skipping author fiction with unexpected link: {}", href);
+            continue;
+        };
+
+        let mut followers = 0;
+        let mut rating = None;
+        for stat in item.select(&stat_selector) {
+            if let Some(title) = stat.value().attr("title") {
+                rating = title.trim().parse().ok();
+                continue;
+            }
+            let text = stat.text().collect::<String>();
+            if text.trim().ends_with("Followers") {
+                let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+                followers = digits.parse().unwrap_or(0);
+            }
+        }
+
+        fictions.push(AuthorFiction {
+            id,
+            title: link.text().collect::<String>().trim().to_string(),
+            followers,
+            rating,
+        });
+    }
+
+    Ok(fictions)
+}
+
+/// Summarize an author's track record from their fictions other than `novel_id`.
+pub fn reputation_excluding(fictions: &[AuthorFiction], novel_id: u64) -> AuthorReputation {
+    let others: Vec<&AuthorFiction> = fictions.iter().filter(|f| f.id != novel_id).collect();
+    AuthorReputation {
+        other_fictions: others.len(),
+        best_rating: others
+            .iter()
+            .filter_map(|f| f.rating)
+            .max_by(|a, b| a.total_cmp(b)),
+        total_followers: others.iter().map(|f| f.followers).sum(),
+    }
+}
+
+/// Per-run cache of author fiction lists, so each author is fetched once.
+#[derive(Debug, Default)]
+pub struct AuthorCache {
+    fictions: HashMap<u64, Vec<AuthorFiction>>,
+}
+
+impl AuthorCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The reputation of a novel's author from their other fictions,
+    /// fetching the author's fiction list on first use if `budget` covers
+    /// it, and failing with
+    /// [`OverBudget`](crate::scraper::budget::OverBudget) if not.
+    pub fn reputation(
+        &mut self,
+        client: &dyn HttpFetch,
+        author_id: u64,
+        novel_id: u64,
+        budget: &BudgetGuard,
+    ) -> Result<AuthorReputation> {
+        let fictions = match self.fictions.entry(author_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                budget.check("author lookup", 1)?;
+                entry.insert(scrape_author_fictions(client, author_id)?)
+            }
+        };
+        Ok(reputation_excluding(fictions, novel_id))
+    }
+}
+
+/// Extract the fiction ID from a link like "/fiction/90435/bunny-girl-evolution".
+fn parse_fiction_id(href: &str) -> Option<u64> {
+    let mut parts = href.split('/').skip_while(|p| *p != "fiction");
+    parts.next()?;
+    parts.next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::FakeClient;
+    use std::path::PathBuf;
+
+    fn testdata_path(filename: &str) -> PathBuf {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src");
+        path.push("scraper");
+        path.push("testdata");
+        path.push(filename);
+        path
+    }
+
+    fn fake_client() -> FakeClient {
+        let html = std::fs::read_to_string(testdata_path("author_fictions_512699.html")).unwrap();
+        FakeClient::new().with_page("https://www.royalroad.com/profile/512699/fictions", &html)
+    }
+
+    #[test]
+    fn test_parse_author_fictions() {
+        let html = std::fs::read_to_string(testdata_path("author_fictions_512699.html")).unwrap();
+        let fictions = parse_author_fictions(&html).unwrap();
+
+        assert_eq!(fictions.len(), 3);
+        assert_eq!(fictions[0].id, 90435);
+        assert_eq!(fictions[0].title, "Bunny Girl Evolution");
+        assert_eq!(fictions[1].followers, 10_212);
+        assert_eq!(fictions[1].rating, Some(4.71));
+        // Unrated fictions have no rating
+        assert_eq!(fictions[2].rating, None);
+    }
+
+    #[test]
+    fn test_reputation_excludes_current_novel() {
+        let client = fake_client();
+        let fictions = scrape_author_fictions(&client, 512699).unwrap();
+        let reputation = reputation_excluding(&fictions, 90435);
+
+        assert_eq!(reputation.other_fictions, 2);
+        assert_eq!(reputation.best_rating, Some(4.71));
+        assert_eq!(reputation.total_followers, 10_270);
+    }
+
+    #[test]
+    fn test_author_cache_fetches_each_author_once() {
+        let client = fake_client();
+        let mut cache = AuthorCache::new();
+
+        let first = cache.reputation(&client, 512699, 90435, &BudgetGuard::new(&client, 1));
+        // A cached author costs nothing, so an empty budget still covers it
+        let second = cache.reputation(&client, 512699, 71234, &BudgetGuard::new(&client, 0));
+
+        assert_eq!(client.requests().len(), 1);
+        assert_eq!(first.unwrap().best_rating, Some(4.71));
+        // From the other novel's point of view, 90435 is the best other work
+        assert_eq!(second.unwrap().best_rating, Some(4.63));
+
+        let err = cache
+            .reputation(&client, 1, 1, &BudgetGuard::new(&client, 0))
+            .unwrap_err();
+        assert_eq!(crate::scraper::budget::over_budget_step(&err), Some("author lookup"));
+        assert_eq!(client.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_fiction_id() {
+        assert_eq!(parse_fiction_id("/fiction/90435/bunny-girl-evolution"), Some(90435));
+        assert_eq!(parse_fiction_id("/profile/512699"), None);
+    }
+}
@@ -0,0 +1,118 @@
+//! Per-novel request budgets.
+//!
+//! Enrichment steps each add requests: sampled chapters for a word count,
+//! the author's fiction list, the first chapter. A [`BudgetGuard`] measures
+//! what a novel has cost so far from the client's request counter, and the
+//! optional steps ask it before fetching. A step the budget can't cover fails
+//! with [`OverBudget`], which callers skip past; the novel's own page is
+//! always fetched.
+
+use crate::scraper::HttpFetch;
+use std::fmt;
+
+/// Requests one novel may make beyond its page, unless configured otherwise.
+pub const DEFAULT_MAX_REQUESTS_PER_NOVEL: u64 = 20;
+
+/// The requests one novel has left, measured from the client's counter
+/// since the guard was made.
+pub struct BudgetGuard<'a> {
+    client: &'a dyn HttpFetch,
+    start: u64,
+    limit: u64,
+}
+
+impl<'a> BudgetGuard<'a> {
+    /// Start a budget of `limit` requests through `client`.
+    pub fn new(client: &'a dyn HttpFetch, limit: u64) -> Self {
+        Self {
+            client,
+            start: client.request_count(),
+            limit,
+        }
+    }
+
+    /// A budget that never runs out, for callers that don't limit requests.
+    pub fn unlimited(client: &'a dyn HttpFetch) -> Self {
+        Self::new(client, u64::MAX)
+    }
+
+    /// Requests made since the budget started.
+    pub fn spent(&self) -> u64 {
+        self.client.request_count().saturating_sub(self.start)
+    }
+
+    /// Whether `requests` more fit in the budget.
+    pub fn allows(&self, requests: u64) -> bool {
+        self.spent().saturating_add(requests) <= self.limit
+    }
+
+    /// Fail with [`OverBudget`] for `step` unless `requests` more fit.
+    pub fn check(&self, step: &'static str, requests: u64) -> anyhow::Result<()> {
+        if self.allows(requests) {
+            return Ok(());
+        }
+        Err(OverBudget {
+            step,
+            spent: self.spent(),
+            limit: self.limit,
+        }
+        .into())
+    }
+}
+
+/// The error for an optional step skipped because the novel's request
+/// budget can't cover it.
+#[derive(Debug)]
+pub struct OverBudget {
+    /// What was skipped, e.g. "author lookup".
+    pub step: &'static str,
+    /// Requests the novel had made.
+    pub spent: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for OverBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "skipped {}: {} of {} requests for this novel used; raise [run] \
+             max_requests_per_novel to allow more",
+            self.step, self.spent, self.limit
+        )
+    }
+}
+
+impl std::error::Error for OverBudget {}
+
+/// The step an error skipped for being over budget, if it was.
+pub fn over_budget_step(err: &anyhow::Error) -> Option<&'static str> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<OverBudget>())
+        .map(|over| over.step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::FakeClient;
+
+    #[test]
+    fn test_counts_requests_since_start() {
+        let client = FakeClient::new().with_page("https://x/1", "one");
+        client.fetch("https://x/1").unwrap();
+        let budget = BudgetGuard::new(&client, 2);
+        assert_eq!(budget.spent(), 0);
+        assert!(budget.allows(2));
+        assert!(!budget.allows(3));
+
+        client.fetch("https://x/1").unwrap();
+        assert!(client.fetch("https://x/missing").is_err());
+        assert_eq!(budget.spent(), 2);
+        assert!(budget.check("page", 0).is_ok());
+        let err = budget.check("author lookup", 1).unwrap_err();
+        assert_eq!(over_budget_step(&err), Some("author lookup"));
+        assert!(err.to_string().contains("2 of 2 requests"), "{}", err);
+        assert_eq!(over_budget_step(&anyhow::anyhow!("other")), None);
+        assert!(BudgetGuard::unlimited(&client).allows(u64::MAX));
+    }
+}
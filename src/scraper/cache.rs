@@ -0,0 +1,819 @@
+//! On-disk cache of API responses, so repeated runs reuse them.
+//!
+//! Responses are stored raw, one file per kind and key, under the
+//! configured `[scraper] cache_dir`. A response is fresh for `cache_ttl`
+//! after it was written. A stale response cached with validators (an ETag
+//! or Last-Modified date) can be revalidated with a conditional request;
+//! if it's unchanged, [`ResponseCache::refresh`] makes it fresh again.
+//!
+//! An index file beside the entries records when each was written and how
+//! recently it was used, so lookups don't stat entry files, and so a cache
+//! bounded by `max_cache_mb` or `max_cache_entries` can evict the least
+//! recently used entries when a write takes it over a bound. The index is
+//! checked against the entry files when first loaded, so entries written by
+//! other runs or deleted behind its back are picked up. Eviction is best
+//! effort: a failure is logged and never fails the write.
+//!
+//! The `cache` command reports on and deletes entries. It deletes only in a
+//! directory carrying the [`CACHE_MARKER`] written with the first entry, and
+//! only entry files, so a mistyped `cache_dir` can't cost anything else.
+//! Files that vanish mid-walk, removed by a concurrent run or prune, are
+//! skipped.
+
+use super::Validators;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long cached responses stay fresh unless configured otherwise.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The file marking a directory as a response cache, following the Cache
+/// Directory Tagging convention so backup tools skip it too.
+pub const CACHE_MARKER: &str = "CACHEDIR.TAG";
+
+const CACHE_MARKER_CONTENT: &str = "Signature: 8a477f597d28d172789f06886806bc55\n\
+    # This file marks a novel-finder response cache.\n";
+
+/// The file in the cache's root directory indexing its entries.
+pub const CACHE_INDEX: &str = "index.json";
+
+/// Bounds on a cache's size, kept by evicting the least recently used
+/// entries after each write. Bounds left unset don't apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheLimits {
+    /// Total size of the entries in bytes.
+    pub max_bytes: Option<u64>,
+    pub max_entries: Option<usize>,
+}
+
+impl CacheLimits {
+    /// Whether `entries` entries taking `bytes` bytes are over a bound.
+    fn exceeded(&self, entries: usize, bytes: u64) -> bool {
+        self.max_entries.is_some_and(|max| entries > max)
+            || self.max_bytes.is_some_and(|max| bytes > max)
+    }
+}
+
+/// Upper bounds of the entry age ranges counted by [`ResponseCache::stats`];
+/// entries older than the last are counted in a final range.
+pub const AGE_BUCKETS: [(&str, Duration); 3] = [
+    ("< 1 day", Duration::from_secs(24 * 60 * 60)),
+    ("1-7 days", Duration::from_secs(7 * 24 * 60 * 60)),
+    ("7-30 days", Duration::from_secs(30 * 24 * 60 * 60)),
+];
+
+/// Entry counts, sizes, and ages for one kind of cached response.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KindStats {
+    /// The kind, which is also its subdirectory's name.
+    pub kind: String,
+    pub entries: usize,
+    /// Total size of the entries in bytes.
+    pub bytes: u64,
+    /// Entries per age range: one per [`AGE_BUCKETS`] entry, then older.
+    pub ages: [usize; AGE_BUCKETS.len() + 1],
+    /// Entries older than the cache TTL, which runs no longer use.
+    pub stale: usize,
+}
+
+/// One entry file found in the cache.
+struct Entry {
+    kind: String,
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+    age: Duration,
+}
+
+/// The cache's index: what each entry is, by "kind/key".
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    /// Advances on every read or write, ordering entries by last use.
+    clock: u64,
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    /// When the entry was written or last revalidated, in seconds since
+    /// the Unix epoch.
+    written: u64,
+    /// The index clock at the entry's last read or write; 0 if it was
+    /// found on disk rather than used through the index.
+    used: u64,
+    bytes: u64,
+    /// What the server sent to identify the response's version.
+    #[serde(default, skip_serializing_if = "Validators::is_empty")]
+    validators: Validators,
+}
+
+/// A cached response that has gone stale, and how to revalidate it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleEntry {
+    pub body: String,
+    pub validators: Validators,
+}
+
+/// The index shared by a cache's clones, loaded on first use and saved
+/// after each write and when the last clone is dropped.
+#[derive(Debug)]
+struct IndexState {
+    path: PathBuf,
+    index: Option<Index>,
+    /// Whether reads have been recorded since the index was last saved.
+    dirty: bool,
+}
+
+impl IndexState {
+    fn save(&mut self) -> Result<()> {
+        let Some(index) = &self.index else {
+            return Ok(());
+        };
+        let json = serde_json::to_string(index)?;
+        let partial = self.path.with_extension("partial");
+        std::fs::write(&partial, json)
+            .and_then(|()| std::fs::rename(&partial, &self.path))
+            .with_context(|| format!("Failed to write cache index {}", self.path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+impl Drop for IndexState {
+    fn drop(&mut self) {
+        if self.dirty {
+            if let Err(e) = self.save() {
+                tracing::debug!("{:#}", e);
+            }
+        }
+    }
+}
+
+/// A directory of cached responses.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    /// Root directory; each kind of response gets a subdirectory.
+    dir: PathBuf,
+    /// How long a cached response stays fresh.
+    ttl: Duration,
+    limits: CacheLimits,
+    index: Arc<Mutex<IndexState>>,
+}
+
+impl ResponseCache {
+    /// A cache rooted at `dir` whose entries stay fresh for `ttl`, with no
+    /// bounds on its size. The directory is created on first write.
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        let dir = dir.into();
+        let index = IndexState {
+            path: dir.join(CACHE_INDEX),
+            index: None,
+            dirty: false,
+        };
+        Self {
+            dir,
+            ttl,
+            limits: CacheLimits::default(),
+            index: Arc::new(Mutex::new(index)),
+        }
+    }
+
+    /// Keep the cache within `limits` from now on.
+    pub fn with_limits(mut self, limits: CacheLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// The cached response of `kind` for `key`, if there is a fresh one.
+    pub fn get(&self, kind: &str, key: u64) -> Option<String> {
+        let id = format!("{}/{}", kind, key);
+        let mut state = self.lock_index();
+        let index = state.index.as_mut()?;
+        if !self.is_fresh(index.entries.get(&id)?) {
+            tracing::debug!("Cached {} is stale", id);
+            return None;
+        }
+        let Ok(body) = std::fs::read_to_string(self.path(kind, key)) else {
+            // Deleted since the index was loaded
+            index.entries.remove(&id);
+            state.dirty = true;
+            return None;
+        };
+        index.clock += 1;
+        let clock = index.clock;
+        if let Some(entry) = index.entries.get_mut(&id) {
+            entry.used = clock;
+        }
+        state.dirty = true;
+        Some(body)
+    }
+
+    /// The cached response of `kind` for `key` if it's stale but has
+    /// validators to revalidate it with.
+    pub fn stale(&self, kind: &str, key: u64) -> Option<StaleEntry> {
+        let id = format!("{}/{}", kind, key);
+        let validators = {
+            let state = self.lock_index();
+            let entry = state.index.as_ref()?.entries.get(&id)?;
+            if entry.validators.is_empty() || self.is_fresh(entry) {
+                return None;
+            }
+            entry.validators.clone()
+        };
+        let body = std::fs::read_to_string(self.path(kind, key)).ok()?;
+        Some(StaleEntry { body, validators })
+    }
+
+    /// Make the cached response of `kind` for `key` fresh again, after the
+    /// server said it's unchanged.
+    pub fn refresh(&self, kind: &str, key: u64) -> Result<()> {
+        let path = self.path(kind, key);
+        let now = SystemTime::now();
+        // The file's time is what `stats` and `prune` go by
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(now))
+            .with_context(|| format!("Failed to refresh cache entry {}", path.display()))?;
+        let id = format!("{}/{}", kind, key);
+        let mut state = self.lock_index();
+        if let Some(index) = state.index.as_mut() {
+            index.clock += 1;
+            let clock = index.clock;
+            if let Some(entry) = index.entries.get_mut(&id) {
+                entry.written = unix_seconds(now);
+                entry.used = clock;
+            }
+        }
+        state.save()
+    }
+
+    /// Store a response of `kind` for `key`, replacing any cached one, then
+    /// evict entries if that took the cache over its limits.
+    pub fn put(&self, kind: &str, key: u64, body: &str) -> Result<()> {
+        self.put_with_validators(kind, key, body, &Validators::default())
+    }
+
+    /// Store a response like [`put`](Self::put), with the validators that
+    /// came with it.
+    pub fn put_with_validators(
+        &self,
+        kind: &str,
+        key: u64,
+        body: &str,
+        validators: &Validators,
+    ) -> Result<()> {
+        let path = self.path(kind, key);
+        let dir = path.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+        self.mark()?;
+        // Write then rename, so an interrupted write never leaves a
+        // truncated entry behind
+        let partial = path.with_extension("partial");
+        std::fs::write(&partial, body)
+            .and_then(|()| std::fs::rename(&partial, &path))
+            .with_context(|| format!("Failed to write cache entry {}", path.display()))?;
+
+        let id = format!("{}/{}", kind, key);
+        let mut state = self.lock_index();
+        if let Some(index) = state.index.as_mut() {
+            index.clock += 1;
+            let entry = IndexEntry {
+                written: unix_seconds(SystemTime::now()),
+                used: index.clock,
+                bytes: body.len() as u64,
+                validators: validators.clone(),
+            };
+            index.entries.insert(id.clone(), entry);
+            self.evict(index, &id);
+        }
+        if let Err(e) = state.save() {
+            tracing::warn!("{:#}", e);
+        }
+        Ok(())
+    }
+
+    fn is_fresh(&self, entry: &IndexEntry) -> bool {
+        // A write time in the future counts as just written
+        let age = SystemTime::now()
+            .duration_since(UNIX_EPOCH + Duration::from_secs(entry.written))
+            .unwrap_or_default();
+        age < self.ttl
+    }
+
+    /// Delete the least recently used entries, other than `keep`, until the
+    /// cache is within its limits. Entries that can't be deleted are logged
+    /// and stay indexed, so they're tried again after the next write.
+    fn evict(&self, index: &mut Index, keep: &str) {
+        let mut entries = index.entries.len();
+        let mut bytes: u64 = index.entries.values().map(|entry| entry.bytes).sum();
+        if !self.limits.exceeded(entries, bytes) {
+            return;
+        }
+        let mut by_use: Vec<(u64, String)> = index
+            .entries
+            .iter()
+            .filter(|(id, _)| id.as_str() != keep)
+            .map(|(id, entry)| (entry.used, id.clone()))
+            .collect();
+        by_use.sort();
+        let mut evicted = 0;
+        for (_, id) in by_use {
+            if !self.limits.exceeded(entries, bytes) {
+                break;
+            }
+            let path = self.dir.join(format!("{}.json", id));
+            match std::fs::remove_file(&path) {
+                Ok(()) => evicted += 1,
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => {
+                    tracing::warn!("Failed to evict cache entry {}: {}", path.display(), e);
+                    continue;
+                }
+            }
+            if let Some(entry) = index.entries.remove(&id) {
+                entries -= 1;
+                bytes -= entry.bytes;
+            }
+        }
+        tracing::debug!("Evicted {} cached responses", evicted);
+    }
+
+    /// When the most recently written response was fetched, if any are
+    /// cached. Stale entries count too.
+    pub fn newest_fetch_time(&self) -> Option<SystemTime> {
+        std::fs::read_dir(&self.dir)
+            .ok()?
+            .flatten()
+            .filter_map(|kind| std::fs::read_dir(kind.path()).ok())
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| entry.metadata().and_then(|meta| meta.modified()).ok())
+            .max()
+    }
+
+    /// The cache's root directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Entry counts, sizes, and ages per kind, sorted by kind. Empty if
+    /// nothing has been cached yet.
+    pub fn stats(&self) -> Result<Vec<KindStats>> {
+        let mut stats: Vec<KindStats> = Vec::new();
+        for entry in self.entries()? {
+            let index = match stats.iter().position(|kind| kind.kind == entry.kind) {
+                Some(index) => index,
+                None => {
+                    stats.push(KindStats {
+                        kind: entry.kind.clone(),
+                        ..KindStats::default()
+                    });
+                    stats.len() - 1
+                }
+            };
+            let kind = &mut stats[index];
+            kind.entries += 1;
+            kind.bytes += entry.bytes;
+            let bucket = AGE_BUCKETS
+                .iter()
+                .position(|(_, max)| entry.age < *max)
+                .unwrap_or(AGE_BUCKETS.len());
+            kind.ages[bucket] += 1;
+            if entry.age >= self.ttl {
+                kind.stale += 1;
+            }
+        }
+        stats.sort_by(|a, b| a.kind.cmp(&b.kind));
+        Ok(stats)
+    }
+
+    /// Delete the entries written more than `older_than` ago. Returns how
+    /// many were deleted.
+    pub fn prune(&self, older_than: Duration) -> Result<usize> {
+        self.delete_entries(|entry| entry.age >= older_than)
+    }
+
+    /// Delete every entry. Returns how many were deleted.
+    pub fn clear(&self) -> Result<usize> {
+        self.delete_entries(|_| true)
+    }
+
+    /// Delete the entries `doomed` picks, if the directory is marked as a
+    /// cache, then any kind directories left empty.
+    fn delete_entries(&self, doomed: impl Fn(&Entry) -> bool) -> Result<usize> {
+        if !self.dir.exists() {
+            return Ok(0);
+        }
+        if !self.dir.join(CACHE_MARKER).is_file() {
+            anyhow::bail!(
+                "{} has no {} file, so it isn't treated as a response cache and nothing was \
+                 deleted; a run that caches a response adds the file",
+                self.dir.display(),
+                CACHE_MARKER
+            );
+        }
+        let mut deleted = 0;
+        let mut kinds: Vec<PathBuf> = Vec::new();
+        for entry in self.entries()?.iter().filter(|entry| doomed(entry)) {
+            match std::fs::remove_file(&entry.path) {
+                Ok(()) => deleted += 1,
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("Failed to delete cache entry {}", entry.path.display())
+                    })
+                }
+            }
+            let kind = self.dir.join(&entry.kind);
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+        for kind in kinds {
+            // Fails if anything is left, such as a concurrent run's entry
+            let _ = std::fs::remove_dir(kind);
+        }
+        // Reloading the index drops the deleted entries from it
+        self.index.lock().unwrap_or_else(|e| e.into_inner()).index = None;
+        if let Err(e) = self.lock_index().save() {
+            tracing::warn!("{:#}", e);
+        }
+        Ok(deleted)
+    }
+
+    /// The index, loaded and checked against the entry files if this is
+    /// its first use. A missing or unreadable index is rebuilt from them.
+    fn lock_index(&self) -> std::sync::MutexGuard<'_, IndexState> {
+        let mut state = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        if state.index.is_some() {
+            return state;
+        }
+        let mut index = match std::fs::read_to_string(&state.path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                tracing::debug!("Rebuilding cache index {}: {}", state.path.display(), e);
+                Index::default()
+            }),
+            Err(_) => Index::default(),
+        };
+        match self.entries() {
+            Ok(entries) => {
+                let mut on_disk = BTreeMap::new();
+                for entry in entries {
+                    let key = entry.path.file_stem().and_then(|stem| stem.to_str());
+                    let is_json = entry.path.extension().is_some_and(|ext| ext == "json");
+                    let Some(key) = key.filter(|_| is_json) else {
+                        continue;
+                    };
+                    let id = format!("{}/{}", entry.kind, key);
+                    let indexed = index.entries.remove(&id).filter(|e| e.bytes == entry.bytes);
+                    let indexed = indexed.unwrap_or_else(|| {
+                        state.dirty = true;
+                        IndexEntry {
+                            written: unix_seconds(entry.modified),
+                            used: 0,
+                            bytes: entry.bytes,
+                            validators: Validators::default(),
+                        }
+                    });
+                    on_disk.insert(id, indexed);
+                }
+                // Whatever is left in the index is gone from disk
+                state.dirty |= !index.entries.is_empty();
+                index.entries = on_disk;
+            }
+            Err(e) => tracing::debug!("{:#}", e),
+        }
+        state.index = Some(index);
+        state
+    }
+
+    /// Every entry file: the `.json` and leftover `.partial` files directly
+    /// inside each kind's directory. Anything else is left out.
+    fn entries(&self) -> Result<Vec<Entry>> {
+        let kinds = match std::fs::read_dir(&self.dir) {
+            Ok(kinds) => kinds,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read cache directory {}", self.dir.display())
+                })
+            }
+        };
+        let now = SystemTime::now();
+        let mut entries = Vec::new();
+        for kind in kinds.flatten() {
+            let Ok(files) = std::fs::read_dir(kind.path()) else {
+                continue;
+            };
+            let kind = kind.file_name().to_string_lossy().into_owned();
+            for file in files.flatten() {
+                let path = file.path();
+                let is_entry = path
+                    .extension()
+                    .is_some_and(|ext| ext == "json" || ext == "partial");
+                // Vanished files and anything but plain files are skipped
+                let Ok(meta) = std::fs::symlink_metadata(&path) else {
+                    continue;
+                };
+                if !is_entry || !meta.is_file() {
+                    continue;
+                }
+                let modified = meta.modified().unwrap_or(now);
+                entries.push(Entry {
+                    kind: kind.clone(),
+                    path,
+                    bytes: meta.len(),
+                    modified,
+                    age: now.duration_since(modified).unwrap_or_default(),
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Write the cache marker if it's missing.
+    fn mark(&self) -> Result<()> {
+        let marker = self.dir.join(CACHE_MARKER);
+        if marker.exists() {
+            return Ok(());
+        }
+        std::fs::write(&marker, CACHE_MARKER_CONTENT)
+            .with_context(|| format!("Failed to write {}", marker.display()))
+    }
+
+    fn path(&self, kind: &str, key: u64) -> PathBuf {
+        self.dir.join(kind).join(format!("{}.json", key))
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn temp_cache(name: &str, ttl: Duration) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!(
+            "novel-finder-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        ResponseCache::new(dir, ttl)
+    }
+
+    /// A new cache on the directory of an earlier `temp_cache(name, ..)`.
+    fn temp_cache_at(name: &str, limits: CacheLimits) -> ResponseCache {
+        let dir = std::env::temp_dir().join(format!(
+            "novel-finder-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        ResponseCache::new(dir, DEFAULT_CACHE_TTL).with_limits(limits)
+    }
+
+    #[test]
+    fn test_round_trip_by_kind_and_key() {
+        let cache = temp_cache("round-trip", DEFAULT_CACHE_TTL);
+        assert_eq!(cache.get("similar", 1), None);
+        cache.put("similar", 1, "[1]").unwrap();
+        cache.put("similar", 2, "[2]").unwrap();
+        cache.put("similar", 1, "[1, 3]").unwrap();
+        assert_eq!(cache.get("similar", 1).as_deref(), Some("[1, 3]"));
+        assert_eq!(cache.get("similar", 2).as_deref(), Some("[2]"));
+        assert_eq!(cache.get("other", 1), None);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_stale_entries_are_missing() {
+        let cache = temp_cache("stale", Duration::ZERO);
+        cache.put("similar", 1, "[1]").unwrap();
+        assert_eq!(cache.get("similar", 1), None);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_newest_fetch_time() {
+        let cache = temp_cache("newest", DEFAULT_CACHE_TTL);
+        assert_eq!(cache.newest_fetch_time(), None);
+        cache.put("similar", 1, "[1]").unwrap();
+        cache.put("other", 2, "[2]").unwrap();
+        let newest = UNIX_EPOCH + Duration::from_secs(2_000_000_000);
+        for (kind, key, time) in [("similar", 1, newest), ("other", 2, UNIX_EPOCH)] {
+            let file = std::fs::File::options().write(true).open(cache.path(kind, key));
+            file.unwrap().set_modified(time).unwrap();
+        }
+        assert_eq!(cache.newest_fetch_time(), Some(newest));
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    const DAY: u64 = 24 * 60 * 60;
+
+    /// Cache an entry written `days` ago.
+    fn put_aged(cache: &ResponseCache, kind: &str, key: u64, days: u64) {
+        cache.put(kind, key, "[1, 2, 3]").unwrap();
+        let written = SystemTime::now() - Duration::from_secs(days * DAY);
+        let file = std::fs::File::options().write(true).open(cache.path(kind, key));
+        file.unwrap().set_modified(written).unwrap();
+    }
+
+    /// A cache with entries aged 0, 3, 10, and 40 days, and files that
+    /// aren't entries.
+    fn aged_cache(name: &str) -> ResponseCache {
+        let cache = temp_cache(name, Duration::from_secs(5 * DAY));
+        put_aged(&cache, "similar", 1, 0);
+        put_aged(&cache, "similar", 2, 3);
+        put_aged(&cache, "similar", 3, 40);
+        put_aged(&cache, "author", 4, 10);
+        std::fs::write(cache.dir.join("similar").join("notes.txt"), "keep").unwrap();
+        std::fs::write(cache.dir.join("results.json"), "keep").unwrap();
+        cache
+    }
+
+    #[test]
+    fn test_stats_by_kind_and_age() {
+        let cache = aged_cache("stats");
+        let stats = cache.stats().unwrap();
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+        assert_eq!(
+            stats,
+            vec![
+                KindStats {
+                    kind: "author".to_string(),
+                    entries: 1,
+                    bytes: 9,
+                    ages: [0, 0, 1, 0],
+                    stale: 1,
+                },
+                KindStats {
+                    kind: "similar".to_string(),
+                    entries: 3,
+                    bytes: 27,
+                    ages: [1, 1, 0, 1],
+                    stale: 1,
+                },
+            ]
+        );
+        assert_eq!(temp_cache("stats-empty", DEFAULT_CACHE_TTL).stats().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_prune_deletes_only_old_entries() {
+        let cache = aged_cache("prune");
+        assert_eq!(cache.prune(Duration::from_secs(7 * DAY)).unwrap(), 2);
+        let left: Vec<(String, usize)> = cache
+            .stats()
+            .unwrap()
+            .into_iter()
+            .map(|kind| (kind.kind, kind.entries))
+            .collect();
+        assert_eq!(left, vec![("similar".to_string(), 2)]);
+        // The emptied kind directory goes; other files and the marker stay
+        assert!(!cache.dir.join("author").exists());
+        assert!(cache.dir.join("similar").join("notes.txt").exists());
+        assert!(cache.dir.join("results.json").exists());
+        assert!(cache.dir.join(CACHE_MARKER).exists());
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_clear_deletes_every_entry() {
+        let cache = aged_cache("clear");
+        std::fs::write(cache.dir.join("similar").join("5.partial"), "[").unwrap();
+        assert_eq!(cache.clear().unwrap(), 5);
+        assert_eq!(cache.stats().unwrap(), vec![]);
+        assert!(cache.dir.join("similar").join("notes.txt").exists());
+        assert_eq!(cache.clear().unwrap(), 0);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_stale_entries_with_validators_can_be_refreshed() {
+        let cache = temp_cache("refresh", Duration::from_secs(60));
+        let validators = Validators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        };
+        cache.put_with_validators("similar", 1, "[1]", &validators).unwrap();
+        cache.put("similar", 2, "[2]").unwrap();
+        assert_eq!(cache.stale("similar", 1), None);
+
+        // Once stale, only the entry with validators can be revalidated
+        let stale = ResponseCache::new(&cache.dir, Duration::ZERO);
+        assert_eq!(stale.get("similar", 1), None);
+        let entry = stale.stale("similar", 1).unwrap();
+        assert_eq!((entry.body.as_str(), &entry.validators), ("[1]", &validators));
+        assert_eq!(stale.stale("similar", 2), None);
+
+        let written = SystemTime::now() - Duration::from_secs(DAY);
+        let file = std::fs::File::options().write(true).open(cache.path("similar", 1));
+        file.unwrap().set_modified(written).unwrap();
+        cache.refresh("similar", 1).unwrap();
+        let reloaded = ResponseCache::new(&cache.dir, Duration::from_secs(60));
+        assert_eq!(reloaded.get("similar", 1).as_deref(), Some("[1]"));
+        assert_eq!(reloaded.stats().unwrap()[0].ages[0], 2);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    /// Which of `keys` still have a cached "similar" response.
+    fn cached(cache: &ResponseCache, keys: std::ops::RangeInclusive<u64>) -> Vec<u64> {
+        keys.filter(|&key| cache.path("similar", key).exists()).collect()
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_entries() {
+        let limits = CacheLimits {
+            max_entries: Some(3),
+            ..CacheLimits::default()
+        };
+        let cache = temp_cache("lru", DEFAULT_CACHE_TTL).with_limits(limits);
+        for key in 1..=3 {
+            cache.put("similar", key, "[1, 2, 3]").unwrap();
+        }
+        assert!(cache.get("similar", 1).is_some());
+        cache.put("similar", 4, "[4]").unwrap();
+        assert_eq!(cached(&cache, 1..=4), [1, 3, 4]);
+        assert!(cache.get("similar", 1).is_some());
+        assert!(cache.get("similar", 3).is_some());
+        cache.put("similar", 5, "[5]").unwrap();
+        assert_eq!(cached(&cache, 1..=5), [1, 3, 5]);
+        assert_eq!(cache.get("similar", 4), None);
+
+        // Another cache on the directory picks up the order of use
+        assert!(cache.get("similar", 1).is_some());
+        drop(cache);
+        let cache = temp_cache_at("lru", limits);
+        cache.put("similar", 6, "[6]").unwrap();
+        assert_eq!(cached(&cache, 1..=6), [1, 5, 6]);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_evicts_down_to_the_size_limit() {
+        let limits = CacheLimits {
+            max_bytes: Some(20),
+            ..CacheLimits::default()
+        };
+        let cache = temp_cache("lru-bytes", DEFAULT_CACHE_TTL).with_limits(limits);
+        for key in 1..=3 {
+            cache.put("similar", key, "[1, 2, 3]").unwrap();
+        }
+        assert_eq!(cached(&cache, 1..=3), [2, 3]);
+        // An entry over the limit by itself is kept until the next write
+        cache.put("similar", 4, &"9".repeat(30)).unwrap();
+        assert_eq!(cached(&cache, 1..=4), [4]);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_index_is_rebuilt_from_entries() {
+        let cache = temp_cache("reindex", DEFAULT_CACHE_TTL);
+        cache.put("similar", 1, "[1]").unwrap();
+        cache.put("similar", 2, "[2]").unwrap();
+        drop(cache);
+
+        // Without an index, entries on disk are used first when evicting
+        let limits = CacheLimits {
+            max_entries: Some(2),
+            ..CacheLimits::default()
+        };
+        let cache = temp_cache_at("reindex", limits);
+        std::fs::remove_file(cache.dir.join(CACHE_INDEX)).unwrap();
+        assert_eq!(cache.get("similar", 2).as_deref(), Some("[2]"));
+        cache.put("similar", 3, "[3]").unwrap();
+        assert_eq!(cached(&cache, 1..=3), [2, 3]);
+
+        // Entries deleted behind the index's back are misses
+        std::fs::remove_file(cache.path("similar", 3)).unwrap();
+        assert_eq!(cache.get("similar", 3), None);
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.get("similar", 2), None);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+    }
+
+    #[test]
+    fn test_deleting_needs_the_marker() {
+        let cache = aged_cache("unmarked");
+        std::fs::remove_file(cache.dir.join(CACHE_MARKER)).unwrap();
+        let err = cache.clear().unwrap_err();
+        assert!(err.to_string().contains(CACHE_MARKER), "{}", err);
+        assert!(cache.prune(Duration::ZERO).is_err());
+        assert_eq!(cache.stats().unwrap().len(), 2);
+        std::fs::remove_dir_all(&cache.dir).unwrap();
+
+        // Nothing to delete in a cache that was never written
+        assert_eq!(temp_cache("missing", DEFAULT_CACHE_TTL).clear().unwrap(), 0);
+    }
+}
@@ -0,0 +1,209 @@
+//! Scrape individual chapter pages.
+//!
+//! Used for optional deep-scrape features that need actual chapter text,
+//! such as estimating a novel's word count from a few sample chapters.
+
+use crate::models::{is_stub_notice, Novel};
+use crate::scraper::budget::BudgetGuard;
+use crate::scraper::HttpFetch;
+use crate::site::{royalroad, Site};
+use anyhow::{Context, Result};
+use scraper::{Html, Selector};
+
+/// Scrape the text content of a single chapter.
+///
+/// # Arguments
+/// * `site` - The site the chapter is on, which parses its page.
+/// * `client` - The HTTP client to use for requests.
+/// * `chapter_url` - The chapter URL, absolute or relative to RoyalRoad.
+///
+/// # Returns
+/// The chapter's plain text, without author notes.
+pub fn scrape_chapter(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    chapter_url: &str,
+) -> Result<String> {
+    let url = absolute_chapter_url(chapter_url);
+    let html = client.fetch(&url)?;
+    site.parse_chapter(&html)
+}
+
+/// Parse the chapter text from the raw HTML of a RoyalRoad chapter page.
+///
+/// This is separated from `scrape_chapter` so it can be unit-tested against
+/// an HTML snapshot without making HTTP requests. Author notes live outside
+/// the content element and are not included.
+pub(crate) fn parse_chapter_content(html: &str) -> Result<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("div.chapter-content").expect("valid selector");
+
+    let element = document
+        .select(&selector)
+        .next()
+        .context("no chapter content found in page")?;
+
+    let text = element.text().collect::<String>();
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    Ok(lines.join("\n"))
+}
+
+/// Fetch the first content chapter and return at most `max_chars` of its text.
+///
+/// Stub notices at the start of the chapter list are skipped. Fails with
+/// [`OverBudget`](crate::scraper::budget::OverBudget) if `budget` can't
+/// cover the request.
+#[tracing::instrument(skip_all, fields(novel_id = novel.id, phase = "scrape"))]
+pub fn scrape_first_chapter_excerpt(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    novel: &Novel,
+    max_chars: usize,
+    budget: &BudgetGuard,
+) -> Result<String> {
+    let url = novel
+        .chapter_titles
+        .iter()
+        .zip(&novel.chapter_urls)
+        .find(|(title, url)| !is_stub_notice(title) && !url.is_empty())
+        .map(|(_, url)| url.as_str())
+        .context("novel has no chapters")?;
+
+    budget.check("first chapter", 1)?;
+    let text = scrape_chapter(site, client, url)?;
+    Ok(text.chars().take(max_chars).collect())
+}
+
+/// Estimate a novel's total word count by sampling a few of its chapters.
+///
+/// Fetches the first, middle, and last stored content chapters (skipping
+/// stub notices), counts their words, and extrapolates the average across all
+/// content chapters, including any whose titles weren't stored. Fails with
+/// [`OverBudget`](crate::scraper::budget::OverBudget), before fetching any,
+/// if `budget` can't cover every sample.
+#[tracing::instrument(skip_all, fields(novel_id = novel.id, phase = "scrape"))]
+pub fn estimate_word_count(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    novel: &Novel,
+    budget: &BudgetGuard,
+) -> Result<u64> {
+    let content_urls: Vec<&str> = novel
+        .chapter_titles
+        .iter()
+        .zip(&novel.chapter_urls)
+        .filter(|(title, url)| !is_stub_notice(title) && !url.is_empty())
+        .map(|(_, url)| url.as_str())
+        .collect();
+
+    let indices = sample_indices(content_urls.len());
+    budget.check("word count sampling", indices.len() as u64)?;
+    let mut samples = Vec::new();
+    for index in indices {
+        let text = scrape_chapter(site, client, content_urls[index])?;
+        samples.push(count_words(&text));
+    }
+
+    let chapter_count = (novel.content_chapter_count() as usize).max(content_urls.len());
+    extrapolate_word_count(&samples, chapter_count).context("novel has no chapters to sample")
+}
+
+/// Pick the first, middle, and last index of a list, without duplicates.
+pub(crate) fn sample_indices(len: usize) -> Vec<usize> {
+    let mut indices = match len {
+        0 => Vec::new(),
+        _ => vec![0, len / 2, len - 1],
+    };
+    indices.dedup();
+    indices
+}
+
+/// Extrapolate the average of the sampled chapter word counts to the total.
+pub(crate) fn extrapolate_word_count(samples: &[u64], chapter_count: usize) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let average = samples.iter().sum::<u64>() as f64 / samples.len() as f64;
+    Some((average * chapter_count as f64).round() as u64)
+}
+
+/// Count the words in a chapter's text.
+pub fn count_words(text: &str) -> u64 {
+    text.split_whitespace().count() as u64
+}
+
+/// Turn a chapter path from `window.chapters` into an absolute URL.
+pub fn absolute_chapter_url(chapter_url: &str) -> String {
+    if chapter_url.starts_with("http://") || chapter_url.starts_with("https://") {
+        chapter_url.to_string()
+    } else {
+        format!("{}{}", royalroad::ORIGIN, chapter_url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn testdata_path(filename: &str) -> PathBuf {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src");
+        path.push("scraper");
+        path.push("testdata");
+        path.push(filename);
+        path
+    }
+
+    #[test]
+    fn test_parse_chapter_content() {
+        let html = std::fs::read_to_string(testdata_path("chapter_5000001.html")).unwrap();
+        let text = parse_chapter_content(&html).unwrap();
+
+        assert!(text.starts_with("The kettle whistled"));
+        assert!(text.contains("Something strong."));
+        // Author notes are not part of the chapter content
+        assert!(!text.contains("Thanks for reading"));
+        assert!(!text.contains("<p>"));
+        assert_eq!(count_words(&text), 36);
+    }
+
+    #[test]
+    fn test_parse_chapter_content_missing() {
+        let html = "<html><body><div>No chapter here</div></body></html>";
+        assert!(parse_chapter_content(html).is_err());
+    }
+
+    #[test]
+    fn test_sample_indices() {
+        assert!(sample_indices(0).is_empty());
+        assert_eq!(sample_indices(1), vec![0]);
+        assert_eq!(sample_indices(2), vec![0, 1]);
+        assert_eq!(sample_indices(3), vec![0, 1, 2]);
+        assert_eq!(sample_indices(100), vec![0, 50, 99]);
+    }
+
+    #[test]
+    fn test_extrapolate_word_count() {
+        assert_eq!(extrapolate_word_count(&[], 10), None);
+        assert_eq!(extrapolate_word_count(&[2000, 3000, 4000], 50), Some(150_000));
+        assert_eq!(extrapolate_word_count(&[1500], 1), Some(1500));
+    }
+
+    #[test]
+    fn test_absolute_chapter_url() {
+        assert_eq!(
+            absolute_chapter_url("/fiction/1/a/chapter/2/b"),
+            "https://www.royalroad.com/fiction/1/a/chapter/2/b"
+        );
+        assert_eq!(
+            absolute_chapter_url("https://www.royalroad.com/fiction/1/a/chapter/2/b"),
+            "https://www.royalroad.com/fiction/1/a/chapter/2/b"
+        );
+    }
+}
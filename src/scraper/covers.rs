@@ -0,0 +1,142 @@
+//! Downloading novels' cover images.
+//!
+//! Covers are saved as `{title}-{id}.{ext}` in a directory of the user's
+//! choosing, named by [`safe_filename`] with the extension taken from the
+//! cover URL. Covers already on disk are not fetched again, so the directory
+//! can be reused across runs.
+
+use crate::models::Novel;
+use crate::scraper::HttpFetch;
+use crate::util::safe_filename;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// The extension for cover URLs without a usable one.
+const DEFAULT_EXTENSION: &str = "jpg";
+
+/// Download the covers of `novels` that have one into `dir`, creating it if
+/// needed. A cover that fails to download is logged and skipped. Returns
+/// how many covers were downloaded.
+pub fn download_covers<'a>(
+    client: &dyn HttpFetch,
+    novels: impl IntoIterator<Item = &'a Novel>,
+    dir: &Path,
+) -> Result<usize> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create cover directory {}", dir.display()))?;
+    let mut downloaded = 0;
+    for novel in novels {
+        let Some(url) = &novel.cover_url else {
+            continue;
+        };
+        let path = cover_path(dir, novel, url);
+        if path.exists() {
+            tracing::debug!("Cover for '{}' already at {}", novel.title, path.display());
+            continue;
+        }
+        match save_cover(client, url, &path) {
+            Ok(()) => downloaded += 1,
+            Err(e) => tracing::warn!("Failed to download cover for '{}': {:#}", novel.title, e),
+        }
+    }
+    Ok(downloaded)
+}
+
+/// Fetch one cover and write it to `path`.
+fn save_cover(client: &dyn HttpFetch, url: &str, path: &Path) -> Result<()> {
+    let image = client.fetch_image(url)?;
+    // Write then rename, so an interrupted write never leaves a truncated
+    // cover that later runs would skip
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, image)
+        .and_then(|()| std::fs::rename(&partial, path))
+        .with_context(|| format!("Failed to write cover {}", path.display()))
+}
+
+/// Where the cover of `novel` at `url` is saved.
+fn cover_path(dir: &Path, novel: &Novel, url: &str) -> PathBuf {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .filter(|extension| {
+            (1..=4).contains(&extension.len())
+                && extension.bytes().all(|b| b.is_ascii_alphanumeric())
+        })
+        .unwrap_or_else(|| DEFAULT_EXTENSION.to_string());
+    dir.join(format!("{}.{}", safe_filename(&novel.title, novel.id), extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_novel;
+    use crate::scraper::FakeClient;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "novel-finder-covers-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn novel_with_cover(id: u64, cover_url: Option<&str>) -> Novel {
+        Novel {
+            cover_url: cover_url.map(String::from),
+            ..test_novel(id)
+        }
+    }
+
+    #[test]
+    fn test_cover_path_extension() {
+        let dir = Path::new("covers");
+        let novel = Novel {
+            title: "Bunny Girl Evolution".to_string(),
+            ..test_novel(90435)
+        };
+        let cases = [
+            (
+                "https://www.royalroadcdn.com/public/covers-large/90435-bunny.jpg?time=1764818753",
+                "bunny-girl-evolution-90435.jpg",
+            ),
+            ("https://example.com/cover.PNG", "bunny-girl-evolution-90435.png"),
+            ("https://example.com/cover", "bunny-girl-evolution-90435.jpg"),
+            ("https://example.com/v1.2/cover?format=webp", "bunny-girl-evolution-90435.jpg"),
+            ("https://example.com/cover.not-an-ext", "bunny-girl-evolution-90435.jpg"),
+        ];
+        for (url, file_name) in cases {
+            assert_eq!(cover_path(dir, &novel, url), dir.join(file_name), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_download_covers() {
+        let dir = temp_dir("download");
+        let client = FakeClient::new()
+            .with_page("https://cdn.example/1.jpg", "one")
+            .with_page("https://cdn.example/3.png", "three");
+        let novels = [
+            novel_with_cover(1, Some("https://cdn.example/1.jpg")),
+            novel_with_cover(2, None),
+            novel_with_cover(3, Some("https://cdn.example/3.png")),
+            // Missing covers are skipped without failing the rest
+            novel_with_cover(4, Some("https://cdn.example/4.jpg")),
+        ];
+
+        assert_eq!(download_covers(&client, &novels, &dir).unwrap(), 2);
+        assert_eq!(std::fs::read_to_string(dir.join("novel-1-1.jpg")).unwrap(), "one");
+        assert_eq!(std::fs::read_to_string(dir.join("novel-3-3.png")).unwrap(), "three");
+        assert!(!dir.join("novel-4-4.jpg").exists());
+        assert!(!dir.join("novel-4-4.partial").exists());
+
+        // Covers already on disk aren't fetched again
+        let before = client.requests().len();
+        assert_eq!(download_covers(&client, &novels, &dir).unwrap(), 0);
+        assert_eq!(client.requests()[before..], ["https://cdn.example/4.jpg"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+//! RoyalRoad fiction URLs: reading them in their many shapes and building
+//! the canonical one.
+//!
+//! The same fiction is reachable as `/fiction/123`, `/fiction/123/old-slug`,
+//! `/fiction/123/new-slug`, or through one of its chapters, and RoyalRoad
+//! redirects between them when the title changes. Novels store the canonical
+//! `https://www.royalroad.com/fiction/{id}/{current-slug}` so URLs from
+//! different runs and entry points compare equal.
+
+use crate::site::royalroad;
+
+/// The parts of a fiction URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FictionUrl {
+    /// The fiction ID.
+    pub id: u64,
+    /// The title slug, if the URL has one.
+    pub slug: Option<String>,
+}
+
+/// Read the fiction ID and slug from a fiction or chapter URL, absolute or
+/// relative. Query strings, fragments, and trailing slashes are ignored.
+/// Returns `None` if the URL has no `/fiction/{id}` path.
+pub fn parse(url: &str) -> Option<FictionUrl> {
+    let path = url.trim().split(['?', '#']).next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').collect();
+    let position = segments.iter().position(|segment| *segment == "fiction")?;
+    let id = segments.get(position + 1)?.parse().ok()?;
+    let slug = segments
+        .get(position + 2)
+        .filter(|slug| !slug.is_empty() && **slug != "chapter")
+        .map(|slug| slug.to_string());
+    Some(FictionUrl { id, slug })
+}
+
+/// The canonical URL of a fiction.
+pub fn canonical(id: u64, slug: Option<&str>) -> String {
+    match slug {
+        Some(slug) => format!("{}/{}", royalroad::fiction_page_url(id), slug),
+        None => royalroad::fiction_page_url(id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url_shapes() {
+        let cases = [
+            ("https://www.royalroad.com/fiction/123", Some((123, None))),
+            ("https://www.royalroad.com/fiction/123/", Some((123, None))),
+            ("https://www.royalroad.com/fiction/123/new-slug", Some((123, Some("new-slug")))),
+            ("https://www.royalroad.com/fiction/123/new-slug/", Some((123, Some("new-slug")))),
+            ("http://royalroad.com/fiction/123/slug?page=2", Some((123, Some("slug")))),
+            ("www.royalroad.com/fiction/123#reviews", Some((123, None))),
+            ("https://www.royalroad.com/fiction/123?tab=chapters", Some((123, None))),
+            ("/fiction/123/slug/chapter/456/the-start", Some((123, Some("slug")))),
+            ("https://www.royalroad.com/fiction/123/chapter/456", Some((123, None))),
+            ("  https://www.royalroad.com/fiction/123/slug  ", Some((123, Some("slug")))),
+            ("https://www.royalroad.com/fictions/best-rated", None),
+            ("https://www.royalroad.com/fiction/abc/slug", None),
+            ("https://www.royalroad.com/fiction/", None),
+            ("https://www.royalroad.com/profile/512699", None),
+            ("", None),
+        ];
+        for (url, expected) in cases {
+            let expected = expected.map(|(id, slug)| FictionUrl {
+                id,
+                slug: slug.map(String::from),
+            });
+            assert_eq!(parse(url), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_canonical_round_trips() {
+        let url = canonical(90435, Some("bunny-girl-evolution"));
+        assert_eq!(url, "https://www.royalroad.com/fiction/90435/bunny-girl-evolution");
+        let parts = parse(&url).unwrap();
+        assert_eq!(canonical(parts.id, parts.slug.as_deref()), url);
+        assert_eq!(canonical(7, None), "https://www.royalroad.com/fiction/7");
+    }
+}
@@ -1,44 +1,963 @@
 //! Web scraping module for RoyalRoad.
 //!
 //! Provides a shared HTTP client with rate limiting and submodules
-//! for scraping novel pages, search results, and reviews.
+//! for scraping novel pages, chapters, search results, and reviews.
 
+pub mod author;
+pub mod budget;
+pub mod cache;
+pub mod chapter;
+pub mod covers;
+pub mod fiction_url;
 pub mod novel_page;
+pub mod rate_limit;
 pub mod reviews;
+pub mod robots;
 pub mod search;
 
-use anyhow::Result;
+use crate::proxy::{ProxiedAgent, ProxySetting};
+use anyhow::{Context, Result};
+use rate_limit::{RateClass, RateLimiter, RateLimits};
+use robots::Robots;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// Content encodings the client can decode (via ureq's `gzip` feature).
+const SUPPORTED_ENCODINGS: &str = "gzip";
+
+/// Anything that can fetch the body of a page by URL.
+///
+/// Implemented by `RoyalRoadClient`; tests substitute a fake client that
+/// serves fixtures without touching the network.
+pub trait HttpFetch: Send + Sync {
+    /// Fetch the body of the given URL.
+    fn fetch(&self, url: &str) -> Result<String>;
+
+    /// Fetch a page along with the URL it was finally served from, after
+    /// any redirects. Fetchers that can't tell report the requested URL.
+    fn fetch_page(&self, url: &str) -> Result<Page> {
+        Ok(Page {
+            url: url.to_string(),
+            body: self.fetch(url)?,
+        })
+    }
+
+    /// Fetch a JSON API response. Fetchers that can't check the content
+    /// type return whatever body they get.
+    fn fetch_json(&self, url: &str) -> Result<String> {
+        self.fetch(url)
+    }
+
+    /// Fetch a JSON API response unless it's unchanged since the response
+    /// `validators` came with. Without validators, or from fetchers that
+    /// can't make conditional requests, this is a plain fetch.
+    fn fetch_json_if_modified(&self, url: &str, _validators: &Validators) -> Result<Revalidated> {
+        Ok(Revalidated::Modified {
+            body: self.fetch_json(url)?,
+            validators: Validators::default(),
+        })
+    }
+
+    /// Fetch an image. Fetchers that can't check the content type return
+    /// whatever body they get.
+    fn fetch_image(&self, url: &str) -> Result<Vec<u8>> {
+        self.fetch(url).map(String::into_bytes)
+    }
+
+    /// How many responses in a row, up to the latest, were Cloudflare
+    /// challenges. Fetchers that can't be challenged report 0.
+    fn challenge_streak(&self) -> usize {
+        0
+    }
+
+    /// How many HTTP requests were made so far. Fetchers that don't count
+    /// them report 0.
+    fn request_count(&self) -> u64 {
+        0
+    }
+}
+
+/// A fetched page.
+#[derive(Debug, Clone)]
+pub struct Page {
+    /// The URL the page was served from, after redirects.
+    pub url: String,
+    /// The page body.
+    pub body: String,
+}
+
+/// What a server sent to identify a version of a response, for asking
+/// later whether it has changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Whether there's nothing to make a conditional request with.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    fn from_response(response: &ureq::Response) -> Self {
+        Self {
+            etag: response.header("etag").map(str::to_string),
+            last_modified: response.header("last-modified").map(str::to_string),
+        }
+    }
+}
+
+/// The answer to a conditional request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Revalidated {
+    /// The response is unchanged (HTTP 304), so no body was sent.
+    NotModified,
+    /// A full response, with the validators that came with it.
+    Modified { body: String, validators: Validators },
+}
+
+/// The error for a page that doesn't exist (HTTP 404), such as a deleted
+/// fiction. Callers probing IDs use [`is_not_found`] to skip these.
+#[derive(Debug)]
+pub struct PageNotFound {
+    /// The URL that was requested.
+    pub url: String,
+}
+
+impl fmt::Display for PageNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "page not found: {}", self.url)
+    }
+}
+
+impl std::error::Error for PageNotFound {}
+
+/// Whether an error (or any error it wraps) is a [`PageNotFound`].
+pub fn is_not_found(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<PageNotFound>())
+}
+
+/// A response that arrived but can't be used as the page asked for.
+#[derive(Debug)]
+pub enum ScraperError {
+    /// The response's content type isn't one the request can use, such as
+    /// an image, or an HTML page (often a Cloudflare challenge) where JSON
+    /// was expected.
+    UnexpectedContentType {
+        url: String,
+        content_type: String,
+        expected: &'static str,
+    },
+    /// The response is compressed with an encoding the client can't decode.
+    UnsupportedEncoding { url: String, encoding: String },
+    /// The response body is bigger than the configured limit.
+    ResponseTooLarge { url: String, limit: u64 },
+    /// Cloudflare answered with a "checking your browser" or CAPTCHA page
+    /// instead of the content.
+    Challenged { url: String },
+    /// The site's robots.txt disallows the URL; it was never requested.
+    Disallowed { url: String },
+}
+
+impl fmt::Display for ScraperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScraperError::UnexpectedContentType {
+                url,
+                content_type,
+                expected,
+            } => write!(f, "expected {} from {} but got {}", expected, url, content_type),
+            ScraperError::UnsupportedEncoding { url, encoding } => write!(
+                f,
+                "response from {} uses unsupported content encoding {} (supported: {})",
+                url, encoding, SUPPORTED_ENCODINGS
+            ),
+            ScraperError::ResponseTooLarge { url, limit } => write!(
+                f,
+                "response from {} is larger than {} bytes; raise [scraper] max_response_bytes \
+                 to allow it",
+                url, limit
+            ),
+            ScraperError::Challenged { url } => write!(
+                f,
+                "{} returned a Cloudflare challenge page instead of content; RoyalRoad is \
+                 throttling these requests. Wait before retrying; if it keeps happening, \
+                 requests need a longer delay or cookies from a browser session",
+                url
+            ),
+            ScraperError::Disallowed { url } => write!(
+                f,
+                "robots.txt disallows {}; set [scraper] ignore_robots = true to fetch it anyway",
+                url
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScraperError {}
+
+/// Whether an error (or any error it wraps) is a Cloudflare challenge.
+pub fn is_challenged(err: &anyhow::Error) -> bool {
+    err.chain()
+        .any(|cause| matches!(cause.downcast_ref(), Some(ScraperError::Challenged { .. })))
+}
+
+/// Whether a page is a Cloudflare challenge rather than real content.
+///
+/// Ordinary RoyalRoad pages also load Cloudflare's `challenge-platform`
+/// script, so only markers unique to challenge pages count.
+pub fn is_challenge_page(body: &str) -> bool {
+    const TITLES: [&str; 2] = ["<title>Just a moment...</title>", "<title>Attention Required!"];
+    const MARKERS: [&str; 3] = ["window._cf_chl_opt", "cf-chl-widget", "id=\"challenge-form\""];
+    TITLES.iter().chain(&MARKERS).any(|marker| body.contains(marker))
+}
+
+/// The kind of body a request expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expected {
+    /// A web page: HTML, or JSON from an API.
+    Page,
+    /// JSON only.
+    Json,
+    /// Any image.
+    Image,
+}
+
+impl Expected {
+    /// The `Accept` header for the request.
+    fn accept(self) -> &'static str {
+        match self {
+            Expected::Page => "text/html,application/xhtml+xml,application/json;q=0.9",
+            Expected::Json => "application/json",
+            Expected::Image => "image/*",
+        }
+    }
+
+    /// A description for error messages.
+    fn describe(self) -> &'static str {
+        match self {
+            Expected::Page => "HTML or JSON",
+            Expected::Json => "JSON",
+            Expected::Image => "an image",
+        }
+    }
+
+    /// Whether a response with this MIME type (without parameters) is usable.
+    fn allows(self, mime: &str) -> bool {
+        let mime = mime.trim().to_ascii_lowercase();
+        let json = mime == "application/json" || mime.ends_with("+json");
+        match self {
+            Expected::Page => json || mime == "text/html" || mime == "application/xhtml+xml",
+            Expected::Json => json,
+            Expected::Image => mime.starts_with("image/"),
+        }
+    }
+}
+
 /// A client for making rate-limited HTTP requests to RoyalRoad.
 pub struct RoyalRoadClient {
-    /// The underlying HTTP agent.
-    agent: ureq::Agent,
-    /// Delay between consecutive requests to avoid being rate-limited.
-    request_delay: Duration,
+    /// The underlying HTTP agents, by the proxy each request goes through.
+    agent: ProxiedAgent,
+    /// Minimum interval between requests of each rate class, to avoid
+    /// being rate-limited.
+    rate_limits: RateLimits,
+    /// When each rate class last sent a request.
+    limiter: RateLimiter,
+    /// Largest response body to read, in bytes.
+    max_response_bytes: u64,
+    /// Responses in a row that were Cloudflare challenges.
+    challenge_streak: AtomicUsize,
+    /// HTTP requests made, robots.txt fetches included.
+    request_count: AtomicU64,
+    /// Skip robots.txt: fetch disallowed pages and ignore its crawl delay.
+    ignore_robots: bool,
+    /// robots.txt rules by origin, fetched on the first request to each.
+    robots: Mutex<HashMap<String, Arc<Robots>>>,
 }
 
 impl RoyalRoadClient {
-    /// Create a new client with the specified delay between requests.
-    pub fn new(request_delay: Duration) -> Result<Self> {
-        let agent = ureq::AgentBuilder::new()
-            .timeout_read(Duration::from_secs(30))
-            .timeout_write(Duration::from_secs(30))
-            .user_agent("novel-finder/0.1.0")
-            .build();
+    /// Create a new client spacing out requests by `rate_limits`, giving
+    /// up on requests that take longer than `request_timeout` and
+    /// refusing responses over `max_response_bytes`. Unless `ignore_robots`
+    /// is set, each site's robots.txt is followed. Fails if the proxy, from
+    /// `proxy` or the environment, is invalid.
+    pub fn new(
+        rate_limits: RateLimits,
+        request_timeout: Duration,
+        max_response_bytes: u64,
+        ignore_robots: bool,
+        proxy: &ProxySetting,
+    ) -> Result<Self> {
+        let agent = ProxiedAgent::new(proxy, || {
+            ureq::AgentBuilder::new()
+                .timeout(request_timeout)
+                .user_agent("novel-finder/0.1.0")
+        })
+        .context("Failed to set up the scraper's proxy")?;
 
         Ok(Self {
             agent,
-            request_delay,
+            rate_limits,
+            limiter: RateLimiter::default(),
+            max_response_bytes,
+            challenge_streak: AtomicUsize::new(0),
+            request_count: AtomicU64::new(0),
+            ignore_robots,
+            robots: Mutex::new(HashMap::new()),
         })
     }
 
-    /// Fetch the HTML content of a URL, respecting rate limits.
+    /// The proxy requests for `url` go through, if any.
+    pub fn proxy_for(&self, url: &str) -> Option<&str> {
+        self.agent.proxy_for(url)
+    }
+
+    /// How many responses in a row, up to the latest, were Cloudflare
+    /// challenges.
+    pub fn challenge_streak(&self) -> usize {
+        self.challenge_streak.load(Ordering::Relaxed)
+    }
+
+    /// How many HTTP requests this client has made, robots.txt fetches
+    /// included.
+    pub fn request_count(&self) -> u64 {
+        self.request_count.load(Ordering::Relaxed)
+    }
+
+    /// Fetch the HTML (or API JSON) content of a URL, respecting rate limits.
     pub fn fetch(&self, url: &str) -> Result<String> {
+        Ok(self.fetch_expecting(url, Expected::Page)?.body)
+    }
+
+    /// Fetch a JSON API response, respecting rate limits. An HTML page in
+    /// its place fails with [`ScraperError::UnexpectedContentType`].
+    pub fn fetch_json(&self, url: &str) -> Result<String> {
+        Ok(self.fetch_expecting(url, Expected::Json)?.body)
+    }
+
+    /// Fetch a JSON API response like [`fetch_json`](Self::fetch_json),
+    /// but only if it has changed since the response `validators` came
+    /// with.
+    pub fn fetch_json_if_modified(
+        &self,
+        url: &str,
+        validators: &Validators,
+    ) -> Result<Revalidated> {
+        let fetched = self.fetch_bytes(url, Expected::Json, validators)?;
+        let Some(body) = fetched.body else {
+            return Ok(Revalidated::NotModified);
+        };
+        Ok(Revalidated::Modified {
+            body: utf8_body(url, body)?,
+            validators: fetched.validators,
+        })
+    }
+
+    /// Fetch an image, respecting rate limits. Anything but an image in its
+    /// place fails with [`ScraperError::UnexpectedContentType`].
+    pub fn fetch_image(&self, url: &str) -> Result<Vec<u8>> {
+        let fetched = self.fetch_bytes(url, Expected::Image, &Validators::default())?;
+        fetched.body.with_context(|| not_modified(url))
+    }
+
+    fn fetch_expecting(&self, url: &str, expected: Expected) -> Result<Page> {
+        let fetched = self.fetch_bytes(url, expected, &Validators::default())?;
+        let body = fetched.body.with_context(|| not_modified(url))?;
+        Ok(Page {
+            url: fetched.url,
+            body: utf8_body(url, body)?,
+        })
+    }
+
+    /// Fetch a response, conditionally if there are `validators`, checking
+    /// robots.txt and keeping count of Cloudflare challenges.
+    fn fetch_bytes(
+        &self,
+        url: &str,
+        expected: Expected,
+        validators: &Validators,
+    ) -> Result<Fetched> {
+        let class = RateClass::of(url);
+        let mut delay = self.rate_limits.delay(class);
+        if let Some((origin, path)) = split_url(url).filter(|_| !self.ignore_robots) {
+            let robots = self.robots(origin);
+            if !robots.is_allowed(&path) {
+                return Err(ScraperError::Disallowed {
+                    url: url.to_string(),
+                }
+                .into());
+            }
+            delay = delay.max(robots.crawl_delay().unwrap_or_default());
+        }
+        let result = self.request(url, expected, class, delay, validators);
+        match &result {
+            Err(e) if is_challenged(e) => {
+                self.challenge_streak.fetch_add(1, Ordering::Relaxed);
+            }
+            // Transport errors say nothing about whether we're challenged
+            Err(e) if matches!(e.downcast_ref(), Some(ureq::Error::Transport(_))) => {}
+            _ => self.challenge_streak.store(0, Ordering::Relaxed),
+        }
+        result
+    }
+
+    /// The robots.txt rules for `origin`, fetching them on first use.
+    fn robots(&self, origin: &str) -> Arc<Robots> {
+        // Holding the lock while fetching keeps concurrent first requests
+        // from each fetching robots.txt
+        let mut cache = self.robots.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(robots) = cache.get(origin) {
+            return Arc::clone(robots);
+        }
+        let robots = Arc::new(self.fetch_robots(origin));
+        let page_delay = self.rate_limits.pages;
+        if let Some(crawl_delay) = robots.crawl_delay().filter(|d| *d > page_delay) {
+            tracing::info!(
+                "{} asks for {:.1}s between requests in robots.txt; using that delay",
+                origin,
+                crawl_delay.as_secs_f64()
+            );
+        }
+        cache.insert(origin.to_string(), Arc::clone(&robots));
+        robots
+    }
+
+    /// Fetch and parse an origin's robots.txt. A missing or unreachable
+    /// robots.txt allows everything.
+    fn fetch_robots(&self, origin: &str) -> Robots {
+        let url = format!("{}/robots.txt", origin);
+        tracing::debug!("Fetching URL: {}", url);
+        self.limiter.wait(RateClass::Page, self.rate_limits.pages);
+        self.request_count.fetch_add(1, Ordering::Relaxed);
+        let body = match self.agent.agent(&url).get(&url).call() {
+            Ok(response) => read_body(&url, response, self.max_response_bytes),
+            Err(ureq::Error::Status(status, _)) => {
+                tracing::debug!("{}: status code {}; allowing all paths", url, status);
+                return Robots::allow_all();
+            }
+            Err(e) => Err(e.into()),
+        };
+        match body {
+            Ok(body) => Robots::parse(&String::from_utf8_lossy(&body), robots::USER_AGENT_TOKEN),
+            Err(e) => {
+                tracing::warn!("Failed to fetch {}: {:#}; allowing all paths", url, e);
+                Robots::allow_all()
+            }
+        }
+    }
+
+    fn request(
+        &self,
+        url: &str,
+        expected: Expected,
+        class: RateClass,
+        delay: Duration,
+        validators: &Validators,
+    ) -> Result<Fetched> {
         tracing::debug!("Fetching URL: {}", url);
-        std::thread::sleep(self.request_delay);
-        let response = self.agent.get(url).call()?;
-        let text = response.into_string()?;
-        Ok(text)
+        self.limiter.wait(class, delay);
+        self.request_count.fetch_add(1, Ordering::Relaxed);
+        let mut request = self
+            .agent
+            .agent(url)
+            .get(url)
+            .set("Accept", expected.accept())
+            .set("Accept-Encoding", SUPPORTED_ENCODINGS);
+        if let Some(etag) = &validators.etag {
+            request = request.set("If-None-Match", etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.set("If-Modified-Since", last_modified);
+        }
+        let response = match request.call() {
+            Err(ureq::Error::Status(404, _)) => {
+                return Err(PageNotFound {
+                    url: url.to_string(),
+                }
+                .into())
+            }
+            // Cloudflare serves challenge pages with these statuses
+            Err(ureq::Error::Status(status @ (403 | 429 | 503), response)) => {
+                let body = read_body(url, response, self.max_response_bytes).unwrap_or_default();
+                if is_challenge_page(&String::from_utf8_lossy(&body)) {
+                    return Err(ScraperError::Challenged {
+                        url: url.to_string(),
+                    }
+                    .into());
+                }
+                anyhow::bail!("{}: status code {}", url, status);
+            }
+            result => result?,
+        };
+        let final_url = response.get_url().to_string();
+        if final_url != url {
+            tracing::debug!("{} redirected to {}", url, final_url);
+        }
+        let validators = Validators::from_response(&response);
+        let body = read_revalidation(url, response, expected, self.max_response_bytes)?;
+        Ok(Fetched {
+            url: final_url,
+            body,
+            validators,
+        })
+    }
+}
+
+/// A response as the client received it.
+struct Fetched {
+    /// The URL it was served from, after redirects.
+    url: String,
+    /// The raw body, or `None` if the response was 304 Not Modified.
+    body: Option<Vec<u8>>,
+    validators: Validators,
+}
+
+fn not_modified(url: &str) -> String {
+    format!("{} answered an unconditional request with 304 Not Modified", url)
+}
+
+fn utf8_body(url: &str, body: Vec<u8>) -> Result<String> {
+    String::from_utf8(body).with_context(|| format!("response from {} is not valid UTF-8", url))
+}
+
+/// Split a URL into its origin (scheme and host) and its path with any
+/// query, as robots.txt rules match it.
+fn split_url(url: &str) -> Option<(&str, String)> {
+    let host_start = url.find("://")? + 3;
+    let path_start = url[host_start..]
+        .find(['/', '?', '#'])
+        .map_or(url.len(), |index| host_start + index);
+    let origin = &url[..path_start];
+    let path = url[path_start..].split('#').next().unwrap_or_default();
+    // An empty path, as in a bare query, is the root
+    if path.starts_with('/') {
+        Some((origin, path.to_string()))
+    } else {
+        Some((origin, format!("/{}", path)))
+    }
+}
+
+/// Check a response's encoding and content type, then read its body,
+/// refusing bodies over `max_bytes`.
+fn read_response(
+    url: &str,
+    response: ureq::Response,
+    expected: Expected,
+    max_bytes: u64,
+) -> Result<Vec<u8>> {
+    // ureq decodes gzip and drops the header, so any encoding left is one
+    // it couldn't decode
+    if let Some(encoding) = response.header("content-encoding") {
+        if !encoding.trim().eq_ignore_ascii_case("identity") {
+            return Err(ScraperError::UnsupportedEncoding {
+                url: url.to_string(),
+                encoding: encoding.to_string(),
+            }
+            .into());
+        }
+    }
+    // A missing content type can't be checked, so the body gets the benefit
+    // of the doubt
+    let content_type = response
+        .header("content-type")
+        .map(|_| response.content_type().to_string());
+    let body = read_body(url, response, max_bytes)?;
+
+    // A challenge page is HTML, so check for one before the content type to
+    // report the real problem when JSON was expected
+    if is_challenge_page(&String::from_utf8_lossy(&body)) {
+        return Err(ScraperError::Challenged {
+            url: url.to_string(),
+        }
+        .into());
+    }
+    if let Some(content_type) = content_type.filter(|mime| !expected.allows(mime)) {
+        return Err(ScraperError::UnexpectedContentType {
+            url: url.to_string(),
+            content_type,
+            expected: expected.describe(),
+        }
+        .into());
+    }
+    Ok(body)
+}
+
+/// Read the response to a request that may have been conditional: `None`
+/// if it's 304 Not Modified, otherwise the body as [`read_response`]
+/// checks it.
+fn read_revalidation(
+    url: &str,
+    response: ureq::Response,
+    expected: Expected,
+    max_bytes: u64,
+) -> Result<Option<Vec<u8>>> {
+    if response.status() == 304 {
+        tracing::debug!("{} is unchanged", url);
+        return Ok(None);
+    }
+    read_response(url, response, expected, max_bytes).map(Some)
+}
+
+/// Read a response body, refusing bodies over `max_bytes`.
+fn read_body(url: &str, response: ureq::Response, max_bytes: u64) -> Result<Vec<u8>> {
+    let too_large = || ScraperError::ResponseTooLarge {
+        url: url.to_string(),
+        limit: max_bytes,
+    };
+    let declared = response
+        .header("content-length")
+        .and_then(|length| length.trim().parse::<u64>().ok());
+    if declared.is_some_and(|length| length > max_bytes) {
+        return Err(too_large().into());
+    }
+
+    // Read one byte past the limit to tell a body of exactly the limit from
+    // a bigger one
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(max_bytes.saturating_add(1))
+        .read_to_end(&mut body)
+        .with_context(|| format!("failed to read response from {}", url))?;
+    if body.len() as u64 > max_bytes {
+        return Err(too_large().into());
+    }
+    Ok(body)
+}
+
+impl HttpFetch for RoyalRoadClient {
+    fn fetch(&self, url: &str) -> Result<String> {
+        RoyalRoadClient::fetch(self, url)
+    }
+
+    fn fetch_page(&self, url: &str) -> Result<Page> {
+        self.fetch_expecting(url, Expected::Page)
+    }
+
+    fn fetch_json(&self, url: &str) -> Result<String> {
+        RoyalRoadClient::fetch_json(self, url)
+    }
+
+    fn fetch_json_if_modified(&self, url: &str, validators: &Validators) -> Result<Revalidated> {
+        RoyalRoadClient::fetch_json_if_modified(self, url, validators)
+    }
+
+    fn fetch_image(&self, url: &str) -> Result<Vec<u8>> {
+        RoyalRoadClient::fetch_image(self, url)
+    }
+
+    fn challenge_streak(&self) -> usize {
+        RoyalRoadClient::challenge_streak(self)
+    }
+
+    fn request_count(&self) -> u64 {
+        RoyalRoadClient::request_count(self)
+    }
+}
+
+/// A fake HTTP client for tests that serves canned pages and records every
+/// URL requested. Other URLs fail with [`PageNotFound`].
+#[cfg(test)]
+pub(crate) struct FakeClient {
+    pages: std::collections::HashMap<String, String>,
+    redirects: std::collections::HashMap<String, String>,
+    requests: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl FakeClient {
+    /// Create a fake client that serves no pages.
+    pub(crate) fn new() -> Self {
+        Self {
+            pages: std::collections::HashMap::new(),
+            redirects: std::collections::HashMap::new(),
+            requests: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Redirect requests for `from` to `to`.
+    pub(crate) fn with_redirect(mut self, from: &str, to: &str) -> Self {
+        self.redirects.insert(from.to_string(), to.to_string());
+        self
+    }
+
+    /// Serve `body` for requests to `url`.
+    pub(crate) fn with_page(mut self, url: &str, body: &str) -> Self {
+        self.pages.insert(url.to_string(), body.to_string());
+        self
+    }
+
+    /// The URLs requested so far, in order.
+    pub(crate) fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl HttpFetch for FakeClient {
+    fn fetch(&self, url: &str) -> Result<String> {
+        Ok(self.fetch_page(url)?.body)
+    }
+
+    fn fetch_page(&self, url: &str) -> Result<Page> {
+        self.requests.lock().unwrap().push(url.to_string());
+        let final_url = self.redirects.get(url).map_or(url, String::as_str);
+        let body = self.pages.get(final_url).cloned().ok_or_else(|| PageNotFound {
+            url: url.to_string(),
+        })?;
+        Ok(Page {
+            url: final_url.to_string(),
+            body,
+        })
+    }
+
+    fn request_count(&self) -> u64 {
+        self.requests.lock().unwrap().len() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(headers: &str, body: &str) -> ureq::Response {
+        format!("HTTP/1.1 200 OK\r\n{}\r\n{}", headers, body)
+            .parse()
+            .unwrap()
+    }
+
+    fn scraper_error<T: fmt::Debug>(result: Result<T>) -> ScraperError {
+        result.unwrap_err().downcast::<ScraperError>().unwrap()
+    }
+
+    /// A client without delays, for tests against a local server.
+    fn local_client(ignore_robots: bool, proxy: &ProxySetting) -> Result<RoyalRoadClient> {
+        let limits = RateLimits::uniform(Duration::ZERO);
+        RoyalRoadClient::new(limits, Duration::from_secs(5), 1024, ignore_robots, proxy)
+    }
+
+    /// Serve `robots` as robots.txt and a page at every other path on a
+    /// local port, returning the base URL and the paths requested so far.
+    fn serve(robots: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        use std::io::{BufRead, BufReader, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requested = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&requested);
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(&stream);
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let path = request_line.split(' ').nth(1).unwrap_or_default().to_string();
+                let (content_type, body) = match path.as_str() {
+                    "/robots.txt" => ("text/plain", robots),
+                    _ => ("text/html", "<html>page</html>"),
+                };
+                log.lock().unwrap().push(path);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+                     Connection: close\r\n\r\n{}",
+                    content_type,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (base, requested)
+    }
+
+    #[test]
+    fn test_follows_robots_txt() {
+        let (base, requested) = serve("User-agent: *\nDisallow: /account/\n");
+        let client = local_client(false, &ProxySetting::Direct).unwrap();
+        assert!(client.fetch(&format!("{}/fiction/1", base)).is_ok());
+        let disallowed = client.fetch(&format!("{}/account/settings", base));
+        assert!(matches!(scraper_error(disallowed), ScraperError::Disallowed { .. }));
+        assert!(client.fetch(&format!("{}/fiction/2", base)).is_ok());
+        // robots.txt is fetched once, and disallowed pages are never requested
+        assert_eq!(*requested.lock().unwrap(), ["/robots.txt", "/fiction/1", "/fiction/2"]);
+        assert_eq!(client.challenge_streak(), 0);
+
+        let (base, requested) = serve("User-agent: *\nDisallow: /\n");
+        let client = local_client(true, &ProxySetting::Direct).unwrap();
+        assert!(client.fetch(&format!("{}/account/settings", base)).is_ok());
+        assert_eq!(*requested.lock().unwrap(), ["/account/settings"]);
+    }
+
+    #[test]
+    fn test_sends_requests_through_proxy() {
+        let proxy = ProxySetting::Url("http://proxy.lan:3128".to_string());
+        let client = local_client(false, &proxy).unwrap();
+        let url = "https://www.royalroad.com/fiction/1";
+        assert_eq!(client.proxy_for(url), Some("http://proxy.lan:3128"));
+        let direct = local_client(false, &ProxySetting::Direct);
+        assert_eq!(direct.unwrap().proxy_for(url), None);
+        let socks = ProxySetting::Url("socks5://proxy.lan:1080".to_string());
+        let err = local_client(false, &socks).err().unwrap();
+        assert!(format!("{:#}", err).contains("SOCKS proxies aren't supported"), "{:#}", err);
+
+        // A proxy receives requests for other hosts in absolute form
+        let (proxy, requested) = serve("User-agent: *\nDisallow: /account/\n");
+        let proxy = ProxySetting::Url(proxy);
+        let client = local_client(false, &proxy).unwrap();
+        assert!(client.fetch("http://royalroad.invalid/fiction/1").is_ok());
+        assert_eq!(
+            *requested.lock().unwrap(),
+            ["http://royalroad.invalid/robots.txt", "http://royalroad.invalid/fiction/1"]
+        );
+    }
+
+    #[test]
+    fn test_unreachable_robots_txt_allows_all() {
+        // Nothing listens on the port once the listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let origin = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = local_client(false, &ProxySetting::Direct).unwrap();
+        let robots = client.robots(&origin);
+        assert!(robots.is_allowed("/account/settings"));
+        assert_eq!(robots.crawl_delay(), None);
+    }
+
+    #[test]
+    fn test_split_url() {
+        let cases = [
+            ("https://www.royalroad.com/fiction/1/slug", "/fiction/1/slug"),
+            ("https://www.royalroad.com/fictions/search?page=2#top", "/fictions/search?page=2"),
+            ("https://www.royalroad.com", "/"),
+            ("https://www.royalroad.com?x=1", "/?x=1"),
+        ];
+        for (url, path) in cases {
+            let (origin, split) = split_url(url).unwrap();
+            assert_eq!((origin, split.as_str()), ("https://www.royalroad.com", path));
+        }
+        assert!(split_url("/fiction/1").is_none());
+    }
+
+    #[test]
+    fn test_reads_html_and_json() {
+        let html = response("Content-Type: text/html; charset=utf-8\r\n", "<p>hi</p>");
+        assert_eq!(read_response("u", html, Expected::Page, 100).unwrap(), b"<p>hi</p>");
+        let json = response("Content-Type: application/json\r\n", "[]");
+        assert_eq!(read_response("u", json, Expected::Json, 100).unwrap(), b"[]");
+        let untyped = response("", "plain");
+        assert_eq!(read_response("u", untyped, Expected::Page, 100).unwrap(), b"plain");
+        let image = response("Content-Type: image/jpeg\r\n", "jpg");
+        assert_eq!(read_response("u", image, Expected::Image, 100).unwrap(), b"jpg");
+    }
+
+    #[test]
+    fn test_rejects_unexpected_content_type() {
+        let image = response("Content-Type: image/png\r\n", "png");
+        let err = scraper_error(read_response("u", image, Expected::Page, 100));
+        assert!(matches!(
+            &err,
+            ScraperError::UnexpectedContentType { content_type, .. } if content_type == "image/png"
+        ));
+
+        // An HTML page (such as a Cloudflare challenge) where JSON was expected
+        let html = response("Content-Type: text/html\r\n", "<html></html>");
+        let err = scraper_error(read_response("https://x/api", html, Expected::Json, 100));
+        assert_eq!(err.to_string(), "expected JSON from https://x/api but got text/html");
+        let html = response("Content-Type: text/html\r\n", "<html></html>");
+        let err = scraper_error(read_response("https://x/c.jpg", html, Expected::Image, 100));
+        assert_eq!(
+            err.to_string(),
+            "expected an image from https://x/c.jpg but got text/html"
+        );
+    }
+
+    #[test]
+    fn test_rejects_undecodable_encoding() {
+        let brotli = response("Content-Type: text/html\r\nContent-Encoding: br\r\n", "??");
+        let err = scraper_error(read_response("u", brotli, Expected::Page, 100));
+        assert!(matches!(
+            err,
+            ScraperError::UnsupportedEncoding { encoding, .. } if encoding == "br"
+        ));
+    }
+
+    #[test]
+    fn test_reads_revalidation() {
+        let unchanged: ureq::Response = "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\n\r\n"
+            .parse()
+            .unwrap();
+        assert_eq!(Validators::from_response(&unchanged).etag.as_deref(), Some("\"v1\""));
+        assert_eq!(read_revalidation("u", unchanged, Expected::Json, 100).unwrap(), None);
+
+        let changed = response(
+            "Content-Type: application/json\r\nETag: \"v2\"\r\n\
+             Last-Modified: Wed, 14 Oct 2026 08:00:00 GMT\r\n",
+            "[1]",
+        );
+        let validators = Validators::from_response(&changed);
+        assert_eq!(validators.etag.as_deref(), Some("\"v2\""));
+        assert_eq!(
+            validators.last_modified.as_deref(),
+            Some("Wed, 14 Oct 2026 08:00:00 GMT")
+        );
+        let body = read_revalidation("u", changed, Expected::Json, 100).unwrap();
+        assert_eq!(body.as_deref(), Some(&b"[1]"[..]));
+
+        // A changed response is checked like any other
+        let html = response("Content-Type: text/html\r\n", "<html></html>");
+        assert!(Validators::from_response(&html).is_empty());
+        assert!(read_revalidation("u", html, Expected::Json, 100).is_err());
+    }
+
+    fn testdata(filename: &str) -> String {
+        let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/scraper/testdata");
+        path.push(filename);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_detects_challenge_page() {
+        assert!(is_challenge_page(&testdata("cloudflare_challenge.html")));
+
+        // Real pages carry Cloudflare's challenge-platform script too
+        for page in ["novel_page_90435.html", "chapter_5000001.html"] {
+            assert!(!is_challenge_page(&testdata(page)), "{}", page);
+        }
+    }
+
+    #[test]
+    fn test_challenge_beats_content_type() {
+        let page = testdata("cloudflare_challenge.html");
+        let challenge = response("Content-Type: text/html\r\n", &page);
+        let err = read_response("u", challenge, Expected::Json, 1_000_000).unwrap_err();
+        assert!(is_challenged(&err));
+        assert!(err.to_string().contains("longer delay or cookies"), "{}", err);
+        assert!(!is_challenged(&anyhow::Error::from(PageNotFound { url: "u".into() })));
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let body = "x".repeat(10);
+        let exact = response("Content-Type: text/html\r\n", &body);
+        assert_eq!(read_response("u", exact, Expected::Page, 10).unwrap(), body.as_bytes());
+
+        // Caught while reading, and up front from Content-Length
+        let big = response("Content-Type: text/html\r\n", &body);
+        let err = scraper_error(read_response("u", big, Expected::Page, 9));
+        assert!(matches!(err, ScraperError::ResponseTooLarge { limit: 9, .. }));
+        let declared = response("Content-Type: text/html\r\nContent-Length: 10\r\n", &body);
+        let err = scraper_error(read_response("u", declared, Expected::Page, 9));
+        assert!(matches!(err, ScraperError::ResponseTooLarge { limit: 9, .. }));
     }
 }
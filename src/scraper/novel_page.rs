@@ -1,53 +1,295 @@
 //! Scrape individual novel pages from RoyalRoad.
 //!
-//! Extracts metadata, description, chapter list, and "also liked" novels
-//! from a novel's main page.
+//! Extracts metadata, description, chapter list, reviews, and "also liked"
+//! novels from a novel's main page. The page is fetched and parsed once for
+//! both the metadata and the reviews.
 
-use crate::models::{Novel, NovelStatus};
-use crate::scraper::RoyalRoadClient;
+use crate::models::tags::normalize_tag;
+use crate::models::timestamp::Timestamp;
+use crate::models::{
+    AiContentKind, Badge, ChapterTitleStorage, Novel, NovelStatus, Review, SiteId,
+};
+use crate::scraper::cache::ResponseCache;
+use crate::scraper::reviews::{is_parse_failure, parse_reviews_from_document};
+use crate::scraper::{fiction_url, HttpFetch, Revalidated};
+use crate::site::{RoyalRoad, Site};
 use anyhow::{Context, Result};
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Node, Selector};
+use std::collections::HashMap;
+use std::fmt;
 
 /// Scrape a novel's full details from its RoyalRoad page.
 ///
 /// # Arguments
 /// * `client` - The HTTP client to use for requests.
 /// * `novel_id` - The RoyalRoad fiction ID.
+/// * `chapters` - Which chapter titles to keep.
 ///
 /// # Returns
-/// A fully populated `Novel` struct.
-pub fn scrape_novel(client: &RoyalRoadClient, novel_id: u64) -> Result<Novel> {
-    let url = format!("https://www.royalroad.com/fiction/{}", novel_id);
-    let html = client.fetch(&url)?;
-    parse_novel_from_html(&html, novel_id)
+/// A fully populated `Novel` struct, with up to `MAX_REVIEWS` reviews from
+/// the same page attached so evaluation needn't fetch the page again.
+pub fn scrape_novel(
+    client: &dyn HttpFetch,
+    novel_id: u64,
+    chapters: ChapterTitleStorage,
+) -> Result<Novel> {
+    crate::site::scrape_novel(&RoyalRoad, client, novel_id, chapters)
 }
 
-/// Extract novel IDs from the "Others Also Liked" recommendations via the API.
+/// Scrape a novel's details and up to `max_reviews` of its reviews with a
+/// single fetch of its RoyalRoad page.
+///
+/// The returned novel has no reviews attached; they are returned alongside.
+pub fn scrape_novel_with_reviews(
+    client: &dyn HttpFetch,
+    novel_id: u64,
+    max_reviews: usize,
+    chapters: ChapterTitleStorage,
+) -> Result<(Novel, Vec<Review>)> {
+    crate::site::scrape_novel_with_reviews(&RoyalRoad, client, novel_id, max_reviews, chapters)
+}
+
+/// Where a novel's "Others Also Liked" recommendations came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlsoLikedSource {
+    /// The `/fictions/similar` JSON API.
+    Api,
+    /// The sidebar of the novel's already fetched page.
+    Sidebar,
+}
+
+impl fmt::Display for AlsoLikedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlsoLikedSource::Api => write!(f, "similar fictions API"),
+            AlsoLikedSource::Sidebar => write!(f, "page sidebar"),
+        }
+    }
+}
+
+/// A novel's "Others Also Liked" recommendations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlsoLiked {
+    /// The recommended fictions, in the order shown.
+    pub fictions: Vec<SimilarFiction>,
+    /// Which source produced the recommendations.
+    pub source: AlsoLikedSource,
+}
+
+impl AlsoLiked {
+    /// The recommended fiction IDs, in the order shown.
+    pub fn ids(&self) -> Vec<u64> {
+        self.fictions.iter().map(|fiction| fiction.id).collect()
+    }
+}
+
+/// One recommended fiction, as the similar fictions API describes it.
+/// Recommendations from the page sidebar only have an ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimilarFiction {
+    /// The RoyalRoad fiction ID.
+    pub id: u64,
+    /// The fiction's title.
+    pub title: Option<String>,
+    /// The fiction's URL as given, usually relative to the site.
+    pub url: Option<String>,
+    /// The cover image URL.
+    pub cover: Option<String>,
+    /// The fiction's tags as given; empty when the API leaves them out.
+    pub tags: Vec<String>,
+}
+
+impl SimilarFiction {
+    /// A recommendation known only by its ID.
+    pub fn from_id(id: u64) -> Self {
+        Self {
+            id,
+            title: None,
+            url: None,
+            cover: None,
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// The cache kind similar fictions API responses are stored under.
+const SIMILAR_CACHE_KIND: &str = "similar";
+
+/// Extract the "Others Also Liked" recommendations via the API, falling back
+/// to the sidebar parsed from the novel's page when the API request fails or
+/// its response can't be read.
+///
+/// With a `cache`, a fresh cached API response is used instead of fetching,
+/// and each readable response fetched is cached for later runs. A stale one
+/// is revalidated with a conditional request and reused if it's unchanged.
 ///
 /// # Arguments
 /// * `client` - The HTTP client to use for requests.
-/// * `novel_id` - The RoyalRoad fiction ID whose recommendations to fetch.
+/// * `novel` - The novel whose recommendations to fetch.
+/// * `cache` - Where to keep API responses between runs, if anywhere.
 ///
 /// # Returns
-/// A list of novel IDs found in the recommendations.
-pub fn scrape_also_liked(client: &RoyalRoadClient, novel_id: u64) -> Result<Vec<u64>> {
-    let url = format!(
-        "https://www.royalroad.com/fictions/similar?fictionId={}",
-        novel_id
-    );
-    let json = client.fetch(&url)?;
-    parse_also_liked_from_json(&json)
+/// The recommended fictions, and where they came from.
+pub fn scrape_also_liked(
+    client: &dyn HttpFetch,
+    novel: &Novel,
+    cache: Option<&ResponseCache>,
+) -> Result<AlsoLiked> {
+    scrape_also_liked_from(&RoyalRoad, client, novel, cache)
+}
+
+/// Extract the "Others Also Liked" recommendations of a novel on `site`,
+/// like [`scrape_also_liked`] does on RoyalRoad. On a site without a similar
+/// fictions endpoint, they are the ones from the novel's page, if any.
+#[tracing::instrument(skip_all, fields(phase = "scrape", novel_id = novel.id))]
+pub fn scrape_also_liked_from(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    novel: &Novel,
+    cache: Option<&ResponseCache>,
+) -> Result<AlsoLiked> {
+    if site.similar_url(novel.id).is_none() {
+        let ids = novel.also_liked.iter().flatten().copied();
+        return Ok(AlsoLiked {
+            fictions: ids.map(SimilarFiction::from_id).collect(),
+            source: AlsoLikedSource::Sidebar,
+        });
+    }
+    let cached = cache
+        .and_then(|cache| cache.get(SIMILAR_CACHE_KIND, novel.id))
+        .and_then(|json| match site.parse_similar(&json) {
+            Ok(fictions) => Some(fictions),
+            Err(e) => {
+                tracing::debug!("Ignoring cached similar fictions for {}: {:#}", novel.id, e);
+                None
+            }
+        });
+    let api = match cached {
+        Some(fictions) => {
+            tracing::debug!("Using cached similar fictions for novel {}", novel.id);
+            Ok(fictions)
+        }
+        None => fetch_similar_fictions(site, client, novel.id, cache),
+    };
+    match (api, &novel.also_liked) {
+        (Ok(fictions), _) => Ok(AlsoLiked {
+            fictions,
+            source: AlsoLikedSource::Api,
+        }),
+        (Err(e), Some(sidebar)) => {
+            tracing::warn!(
+                "Similar fictions API failed for novel {}, using its page sidebar: {:#}",
+                novel.id,
+                e
+            );
+            Ok(AlsoLiked {
+                fictions: sidebar.iter().copied().map(SimilarFiction::from_id).collect(),
+                source: AlsoLikedSource::Sidebar,
+            })
+        }
+        (Err(e), None) => Err(e),
+    }
+}
+
+/// Fetch a novel's recommendations from the similar fictions API, caching
+/// the response if it can be read. A stale cached response with validators
+/// is only fetched again if it has changed.
+fn fetch_similar_fictions(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    novel_id: u64,
+    cache: Option<&ResponseCache>,
+) -> Result<Vec<SimilarFiction>> {
+    let url = site
+        .similar_url(novel_id)
+        .with_context(|| format!("{} has no similar fictions endpoint", site.id()))?;
+    let stale = cache.and_then(|cache| cache.stale(SIMILAR_CACHE_KIND, novel_id));
+    let validators = stale
+        .as_ref()
+        .map(|stale| stale.validators.clone())
+        .unwrap_or_default();
+    let (json, validators) = match client.fetch_json_if_modified(&url, &validators)? {
+        Revalidated::Modified { body, validators } => (body, validators),
+        Revalidated::NotModified => {
+            let stale = stale.with_context(|| {
+                format!("{} answered an unconditional request with 304 Not Modified", url)
+            })?;
+            if let Some(cache) = cache {
+                if let Err(e) = cache.refresh(SIMILAR_CACHE_KIND, novel_id) {
+                    tracing::warn!("{:#}", e);
+                }
+            }
+            tracing::debug!("Cached similar fictions for {} are unchanged", novel_id);
+            return site.parse_similar(&stale.body);
+        }
+    };
+    let fictions = site.parse_similar(&json)?;
+    if let Some(cache) = cache {
+        let cached = cache.put_with_validators(SIMILAR_CACHE_KIND, novel_id, &json, &validators);
+        if let Err(e) = cached {
+            tracing::warn!("Failed to cache similar fictions for {}: {:#}", novel_id, e);
+        }
+    }
+    Ok(fictions)
+}
+
+/// A novel page's metadata and reviews, from a single parse.
+#[derive(Debug, Clone)]
+pub struct ParsedPage {
+    /// The novel's metadata, without reviews attached.
+    pub novel: Novel,
+    /// Up to `max_reviews` reviews from the page.
+    pub reviews: Vec<Review>,
+}
+
+/// Parse both the metadata and the reviews from the raw HTML of a novel's
+/// RoyalRoad page, parsing the document only once.
+///
+/// A page showing reviews none of which could be parsed is logged and
+/// marked `reviews_unparsed`, rather than failing the whole page.
+pub fn parse_page(
+    html: &str,
+    novel_id: u64,
+    max_reviews: usize,
+    chapters: ChapterTitleStorage,
+) -> Result<ParsedPage> {
+    let document = Html::parse_document(html);
+    let mut novel = parse_novel_from_document(&document, html, novel_id, chapters)?;
+    let reviews = match parse_reviews_from_document(&document, max_reviews) {
+        Ok(reviews) => reviews,
+        Err(e) if is_parse_failure(&e) => {
+            tracing::warn!("Reviews of novel {}: {}", novel_id, e);
+            novel.reviews_unparsed = true;
+            Vec::new()
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(ParsedPage { novel, reviews })
 }
 
 /// Parse a novel's metadata from the raw HTML of its RoyalRoad page.
 ///
 /// This is separated from `scrape_novel` so it can be unit-tested against
-/// an HTML snapshot without making HTTP requests.
-pub(crate) fn parse_novel_from_html(html: &str, novel_id: u64) -> Result<Novel> {
-    let document = Html::parse_document(html);
+/// an HTML snapshot without making HTTP requests. Only the chapter titles
+/// `chapters` asks for are kept; `chapter_count` always counts them all.
+pub fn parse_novel_from_html(
+    html: &str,
+    novel_id: u64,
+    chapters: ChapterTitleStorage,
+) -> Result<Novel> {
+    parse_novel_from_document(&Html::parse_document(html), html, novel_id, chapters)
+}
 
+/// Parse a novel's metadata from its already parsed page. The raw `html` is
+/// still needed for the chapter list, which lives in a script.
+fn parse_novel_from_document(
+    document: &Html,
+    html: &str,
+    novel_id: u64,
+    storage: ChapterTitleStorage,
+) -> Result<Novel> {
     // --- Extract from JSON-LD ---
-    let ld_json = extract_ld_json(&document)?;
+    let ld_json = extract_ld_json(document)?;
 
     let title = ld_json["name"]
         .as_str()
@@ -58,11 +300,20 @@ pub(crate) fn parse_novel_from_html(html: &str, novel_id: u64) -> Result<Novel>
         .as_str()
         .context("missing 'author.name' in JSON-LD")?
         .to_string();
+    let author_id = extract_author_id(document);
 
     let description_html = ld_json["description"]
         .as_str()
         .context("missing 'description' in JSON-LD")?;
-    let description = strip_html_tags(description_html);
+    let mut description = strip_html_tags(description_html);
+
+    // The JSON-LD description is sometimes truncated relative to the page's
+    // description block (which also holds the "show more" and spoiler content).
+    if let Some(full_description) = extract_full_description(document) {
+        if visible_len(&full_description) > visible_len(&description) {
+            description = full_description;
+        }
+    }
 
     let pages = ld_json["numberOfPages"]
         .as_u64()
@@ -76,49 +327,172 @@ pub(crate) fn parse_novel_from_html(html: &str, novel_id: u64) -> Result<Novel>
         .as_array()
         .context("missing 'genre' in JSON-LD")?
         .iter()
-        .filter_map(|v| v.as_str().map(String::from))
+        .filter_map(|v| v.as_str())
+        .map(|tag| match normalize_tag(tag) {
+            Some(canonical) => canonical.name.to_string(),
+            None => tag.to_string(),
+        })
+        .collect();
+    // Genres are the subset of the JSON-LD "genre" entries that are real genres
+    let genres: Vec<String> = tags
+        .iter()
+        .filter(|tag| normalize_tag(tag).is_some_and(|canonical| canonical.is_genre()))
+        .cloned()
         .collect();
 
-    // --- Extract status from HTML ---
-    let status = extract_status(&document)?;
+    // --- Extract status, fan fiction, and AI content labels from HTML ---
+    let labels = extract_labels(document);
+    let status = extract_status(&labels);
+    let is_fanfiction = extract_is_fanfiction(&labels);
+    let ai_content = extract_ai_content(document, &labels);
+    let badges = extract_badges(document);
 
-    // --- Extract followers and favorites from HTML ---
-    let (followers, favorites) = extract_stats(&document)?;
+    // --- Extract followers, favorites, and views from HTML ---
+    let stats = extract_stats(document);
 
-    // --- Extract chapter titles from window.chapters ---
-    let chapter_titles = extract_chapter_titles(html)?;
-    let chapter_count = chapter_titles.len() as u64;
+    // --- Extract chapter titles, URLs, and dates from window.chapters ---
+    let chapters = extract_chapters(html)?;
+    let chapter_count = chapters.titles.len() as u64;
+    let first_chapter_date = chapters.dates.iter().flatten().min().copied();
+    let last_chapter_date = chapters.dates.iter().flatten().max().copied();
 
-    let url = format!("https://www.royalroad.com/fiction/{}", novel_id);
+    let cover_url = extract_cover_url(&ld_json, document);
+    let slug = extract_canonical_slug(document, novel_id);
+    let url = fiction_url::canonical(novel_id, slug.as_deref());
 
-    Ok(Novel {
+    let mut novel = Novel {
+        site: SiteId::RoyalRoad,
         id: novel_id,
         title,
         author,
+        author_id,
+        author_reputation: None,
         url,
+        slug,
+        cover_url,
         description,
         pages,
         rating,
         status,
+        genres,
         tags,
         chapter_count,
-        chapter_titles,
-        followers,
-        favorites,
-    })
+        chapter_titles: chapters.titles,
+        chapter_urls: chapters.urls,
+        first_chapter_date,
+        last_chapter_date,
+        followers: stats.followers,
+        favorites: stats.favorites,
+        total_views: stats.total_views,
+        average_views: stats.average_views,
+        is_fanfiction,
+        ai_content,
+        badges,
+        word_count_estimate: None,
+        first_chapter_excerpt: None,
+        reviews: None,
+        also_liked: parse_also_liked_from_document(document, novel_id),
+        reviews_unparsed: false,
+    };
+    novel.trim_chapters(storage);
+    Ok(novel)
 }
 
 /// Parse the "also liked" JSON API response into a list of novel IDs.
-pub(crate) fn parse_also_liked_from_json(json: &str) -> Result<Vec<u64>> {
+///
+/// Items without an ID are skipped, but a list where no item has one means
+/// the response changed shape, and is an error.
+pub fn parse_also_liked_from_json(json: &str) -> Result<Vec<u64>> {
+    let fictions = parse_similar_fictions_from_json(json)?;
+    Ok(fictions.into_iter().map(|fiction| fiction.id).collect())
+}
+
+/// Parse the "also liked" JSON API response into its recommended fictions.
+///
+/// Items without an ID are skipped, and missing or malformed fields are
+/// left empty; a list where no item has an ID is an error, as for
+/// `parse_also_liked_from_json`.
+pub fn parse_similar_fictions_from_json(json: &str) -> Result<Vec<SimilarFiction>> {
     let items: Vec<serde_json::Value> =
         serde_json::from_str(json).context("failed to parse similar fictions JSON")?;
 
-    let ids: Vec<u64> = items
-        .iter()
-        .filter_map(|item| item["id"].as_u64())
-        .collect();
+    let fictions: Vec<SimilarFiction> = items.iter().filter_map(parse_similar_fiction).collect();
+    if fictions.is_empty() && !items.is_empty() {
+        anyhow::bail!("similar fictions JSON has {} items but no fiction IDs", items.len());
+    }
+
+    Ok(fictions)
+}
+
+fn parse_similar_fiction(item: &serde_json::Value) -> Option<SimilarFiction> {
+    let text = |key: &str| {
+        let value = item[key].as_str()?.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    };
+    let tags = item["tags"].as_array().into_iter().flatten();
+    Some(SimilarFiction {
+        id: item["id"].as_u64()?,
+        title: text("title"),
+        url: text("url"),
+        cover: text("cover"),
+        tags: tags.filter_map(|tag| tag.as_str()).map(String::from).collect(),
+    })
+}
 
-    Ok(ids)
+/// Parse the fiction IDs linked from the "Others Also Liked" sidebar of a
+/// novel's RoyalRoad page, in order and without repeats. Returns `None` if
+/// the page has no such sidebar.
+pub fn parse_also_liked_from_html(html: &str, novel_id: u64) -> Option<Vec<u64>> {
+    parse_also_liked_from_document(&Html::parse_document(html), novel_id)
+}
+
+fn parse_also_liked_from_document(document: &Html, novel_id: u64) -> Option<Vec<u64>> {
+    let portlet_selector = Selector::parse(".portlet").expect("valid selector");
+    let title_selector = Selector::parse(".portlet-title").expect("valid selector");
+    let link_selector = Selector::parse(".portlet-body a[href]").expect("valid selector");
+
+    let sidebar = document.select(&portlet_selector).find(|portlet| {
+        portlet.select(&title_selector).next().is_some_and(|title| {
+            let title = title.text().collect::<String>().to_lowercase();
+            title.contains("also liked")
+        })
+    })?;
+    let mut ids = Vec::new();
+    for link in sidebar.select(&link_selector) {
+        let href = link.value().attr("href").unwrap_or_default();
+        // Cover images and titles both link to each fiction
+        let id = fiction_url::parse(href).map(|parsed| parsed.id);
+        if let Some(id) = id.filter(|id| *id != novel_id && !ids.contains(id)) {
+            ids.push(id);
+        }
+    }
+    Some(ids)
+}
+
+/// Extract the title slug from the page's canonical link, if it points at
+/// this fiction.
+fn extract_canonical_slug(document: &Html, novel_id: u64) -> Option<String> {
+    let selector = Selector::parse("link[rel='canonical']").expect("valid selector");
+    let href = document.select(&selector).next()?.value().attr("href")?;
+    fiction_url::parse(href)
+        .filter(|parsed| parsed.id == novel_id)
+        .and_then(|parsed| parsed.slug)
+}
+
+/// Extract the cover image URL from the JSON-LD `image`, falling back to the
+/// cover `img` element. RoyalRoad's "no cover" placeholder counts as none.
+fn extract_cover_url(ld_json: &serde_json::Value, document: &Html) -> Option<String> {
+    let selector = Selector::parse("img[data-type='cover']").expect("valid selector");
+    let from_img = || {
+        let src = document.select(&selector).next()?.value().attr("src")?;
+        Some(src.trim().to_string())
+    };
+    let url = ld_json["image"]
+        .as_str()
+        .map(|image| image.trim().to_string())
+        .filter(|image| !image.is_empty())
+        .or_else(from_img)?;
+    (!url.is_empty() && !url.contains("nocover")).then_some(url)
 }
 
 /// Extract the JSON-LD structured data from the page.
@@ -138,68 +512,319 @@ fn extract_ld_json(document: &Html) -> Result<serde_json::Value> {
     Ok(value)
 }
 
-/// Extract the publication status from HTML label spans.
-fn extract_status(document: &Html) -> Result<NovelStatus> {
-    let selector = Selector::parse("span.label").expect("valid selector");
+/// Extract the full description text from the `div.description` element.
+///
+/// Includes text inside the collapsed "show more" and spoiler containers,
+/// keeping one line per text block.
+fn extract_full_description(document: &Html) -> Option<String> {
+    let selector = Selector::parse("div.description").expect("valid selector");
+    let element = document.select(&selector).next()?;
 
-    for element in document.select(&selector) {
-        let text = element.text().collect::<String>();
-        let text = text.trim().to_uppercase();
-
-        match text.as_str() {
-            "ONGOING" => return Ok(NovelStatus::Ongoing),
-            "COMPLETED" => return Ok(NovelStatus::Completed),
-            "HIATUS" => return Ok(NovelStatus::Hiatus),
-            "DROPPED" => return Ok(NovelStatus::Dropped),
-            "STUB" => return Ok(NovelStatus::Stub),
+    let mut text = String::new();
+    push_block_text(element, &mut text);
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// Elements whose text is a block of its own, on separate lines from the
+/// text around it.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "br", "hr", "li", "ul", "ol", "blockquote", "pre", "table", "tr", "h1", "h2",
+    "h3", "h4", "h5", "h6",
+];
+
+/// Append the text within `element` to `text`, with a line break around
+/// each block element so adjacent paragraphs don't run together. Line
+/// breaks in the markup itself are only whitespace, as in a browser.
+fn push_block_text(element: ElementRef, text: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(content) => text.push_str(&content.replace('\n', " ")),
+            Node::Element(el) => {
+                let block = BLOCK_ELEMENTS.contains(&el.name());
+                if block {
+                    text.push('\n');
+                }
+                if let Some(child) = ElementRef::wrap(child) {
+                    push_block_text(child, text);
+                }
+                if block {
+                    text.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Count the non-whitespace characters in a string.
+fn visible_len(s: &str) -> usize {
+    s.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+/// Collect the trimmed, uppercased texts of the fiction's label spans.
+///
+/// Only labels in the fiction header are considered, so unrelated labels
+/// elsewhere on the page (like "37 Chapters" in the table of contents) can't
+/// be mistaken for status or content labels. Falls back to every label on the
+/// page if the header isn't found.
+fn extract_labels(document: &Html) -> Vec<String> {
+    let header_selector = Selector::parse("div.fiction-info span.label").expect("valid selector");
+    let any_selector = Selector::parse("span.label").expect("valid selector");
+
+    let mut elements: Vec<_> = document.select(&header_selector).collect();
+    if elements.is_empty() {
+        elements = document.select(&any_selector).collect();
+    }
+
+    elements
+        .into_iter()
+        .map(|el| el.text().collect::<String>().trim().to_uppercase())
+        .collect()
+}
+
+/// Extract the publication status from the fiction's labels.
+///
+/// Labels that aren't a publication status (Original, Fan Fiction, AI content
+/// labels, ...) are ignored.
+fn extract_status(labels: &[String]) -> NovelStatus {
+    for label in labels {
+        match label.as_str() {
+            "ONGOING" => return NovelStatus::Ongoing,
+            "COMPLETED" => return NovelStatus::Completed,
+            "HIATUS" => return NovelStatus::Hiatus,
+            "DROPPED" => return NovelStatus::Dropped,
+            "STUB" => return NovelStatus::Stub,
             _ => continue,
         }
     }
 
-    anyhow::bail!("could not find novel status in page")
+    // A label RoyalRoad has introduced since shouldn't fail the whole page
+    let label = labels
+        .iter()
+        .find(|label| !is_content_label(label))
+        .cloned()
+        .unwrap_or_default();
+    tracing::warn!("Unrecognized novel status label: {:?}", label);
+    NovelStatus::Unknown(label)
+}
+
+/// Whether a label describes the content (Original, Fan Fiction, AI
+/// content) rather than the publication status.
+fn is_content_label(label: &str) -> bool {
+    matches!(label, "ORIGINAL" | "FAN FICTION" | "FANFICTION")
+        || label.replace('-', " ").starts_with("AI ")
 }
 
-/// Extract followers and favorites counts from the stats section.
-fn extract_stats(document: &Html) -> Result<(u64, u64)> {
+/// Check whether the fiction carries the "Fan Fiction" label.
+///
+/// Original works carry an "Original" label in the same position instead.
+fn extract_is_fanfiction(labels: &[String]) -> bool {
+    labels
+        .iter()
+        .any(|label| matches!(label.as_str(), "FAN FICTION" | "FANFICTION"))
+}
+
+/// Detect RoyalRoad's AI content labels.
+///
+/// The label can appear either among the fiction's label spans or in the
+/// "This fiction contains" warning list. AI-generated wins over AI-assisted
+/// if both are somehow present.
+fn extract_ai_content(document: &Html, labels: &[String]) -> Option<AiContentKind> {
+    let warning_selector =
+        Selector::parse("div.fiction-info ul.list-inline li").expect("valid selector");
+    let warnings = document
+        .select(&warning_selector)
+        .map(|el| el.text().collect::<String>().trim().to_uppercase());
+
+    let mut kind = None;
+    for text in labels.iter().cloned().chain(warnings) {
+        let normalized = text.replace('-', " ");
+        if normalized.contains("AI GENERATED") {
+            return Some(AiContentKind::Generated);
+        }
+        if normalized.contains("AI ASSISTED") {
+            kind = Some(AiContentKind::Assisted);
+        }
+    }
+    kind
+}
+
+/// Extract the "Top X% in <metric>" badges from the fiction's header.
+///
+/// A badge may be shown as text or only in an image's `alt` or a popover's
+/// title, so all three are read. Only whole texts count, so a description
+/// mentioning a ranking in passing isn't taken for a badge. Each metric is
+/// kept once, at its best percentile.
+fn extract_badges(document: &Html) -> Vec<Badge> {
+    let selector = Selector::parse("div.fiction-info *").expect("valid selector");
+    let pattern = regex::Regex::new(r"(?i)^top\s+(\d+(?:\.\d+)?)\s*%\s+in\s+(.+)$")
+        .expect("valid regex");
+
+    let mut badges: Vec<Badge> = Vec::new();
+    for element in document.select(&selector) {
+        let own_text: String = element
+            .children()
+            .filter_map(|child| child.value().as_text().map(|text| text.to_string()))
+            .collect();
+        let attributes = ["alt", "title", "data-original-title"]
+            .into_iter()
+            .filter_map(|name| element.value().attr(name).map(String::from));
+        for text in std::iter::once(own_text).chain(attributes) {
+            let Some(caps) = pattern.captures(text.trim()) else {
+                continue;
+            };
+            let Ok(top_percent) = caps[1].parse::<f64>() else {
+                continue;
+            };
+            let metric = caps[2].trim().to_string();
+            match badges.iter_mut().find(|badge| badge.metric.eq_ignore_ascii_case(&metric)) {
+                Some(badge) => badge.top_percent = badge.top_percent.min(top_percent),
+                None => badges.push(Badge {
+                    metric,
+                    top_percent,
+                }),
+            }
+        }
+    }
+    badges
+}
+
+/// Extract the author's profile ID from the byline link in the page header.
+fn extract_author_id(document: &Html) -> Option<u64> {
     let selector =
-        Selector::parse("div.fiction-stats div.stats-content ul li").expect("valid selector");
+        Selector::parse(r#"div.fic-title h4 a[href^="/profile/"]"#).expect("valid selector");
+    let href = document.select(&selector).next()?.value().attr("href")?;
+    href.trim_start_matches("/profile/")
+        .split('/')
+        .next()?
+        .parse()
+        .ok()
+}
 
-    let items: Vec<String> = document
-        .select(&selector)
-        .map(|el| el.text().collect::<String>().trim().to_string())
-        .collect();
+/// The rows of a novel page's stats section by label ("FOLLOWERS",
+/// "OVERALL SCORE", ...), in whatever order the page lists them.
+///
+/// Each label item is paired with the value item after it; a label without
+/// one is left out rather than taking the next row's value. Score values are
+/// read from their star's `data-content` ("4.4 / 5"), counts from the text.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct StatRows(HashMap<String, String>);
 
-    let mut followers: Option<u64> = None;
-    let mut favorites: Option<u64> = None;
+impl StatRows {
+    /// Read every row of the stats section.
+    pub(crate) fn from_document(document: &Html) -> Self {
+        let item_selector =
+            Selector::parse("div.fiction-stats div.stats-content ul li").expect("valid selector");
+        let score_selector = Selector::parse("span[data-content]").expect("valid selector");
 
-    for (i, item) in items.iter().enumerate() {
-        if item.starts_with("Followers") {
-            if let Some(next) = items.get(i + 1) {
-                followers = Some(parse_stat_number(next)?);
+        let mut rows = HashMap::new();
+        let mut label = None;
+        for item in document.select(&item_selector) {
+            let score = item.select(&score_selector).next();
+            let highlighted = item.value().classes().any(|class| class == "font-red-sunglo");
+            if score.is_none() && !highlighted {
+                label = Some(stat_label(&item.text().collect::<String>()));
+                continue;
             }
-        } else if item.starts_with("Favorites") {
-            if let Some(next) = items.get(i + 1) {
-                favorites = Some(parse_stat_number(next)?);
+            let value = match score.and_then(|span| span.value().attr("data-content")) {
+                Some(content) => content.trim().to_string(),
+                None => item.text().collect::<String>().trim().to_string(),
+            };
+            if let Some(label) = label.take() {
+                rows.insert(label, value);
             }
         }
+        Self(rows)
+    }
+
+    /// The value of the row labelled `label` (uppercase, without the colon).
+    pub(crate) fn get(&self, label: &str) -> Option<&str> {
+        self.0.get(label).map(String::as_str)
     }
 
-    Ok((
-        followers.context("could not find followers count")?,
-        favorites.context("could not find favorites count")?,
-    ))
+    /// The row labelled `label` as a count, if it's there and readable.
+    pub(crate) fn count(&self, label: &str) -> Option<u64> {
+        let value = self.get(label)?;
+        parse_stat_number(value)
+            .map_err(|e| tracing::warn!("Unreadable {} stat: {:#}", label, e))
+            .ok()
+    }
+}
+
+/// A stats label as a [`StatRows`] key: uppercase, whitespace collapsed,
+/// without the trailing colon.
+fn stat_label(text: &str) -> String {
+    let words: Vec<&str> = text.trim().trim_end_matches(':').split_whitespace().collect();
+    words.join(" ").to_uppercase()
+}
+
+/// Counts read from the stats section of a novel page.
+struct FictionStats {
+    followers: u64,
+    favorites: u64,
+    total_views: Option<u64>,
+    average_views: Option<u64>,
+}
+
+/// Extract followers, favorites, and view counts from the stats section.
+///
+/// A missing follower or favorite count is read as zero with a warning, so a
+/// changed layout doesn't fail the whole page; view counts are optional.
+fn extract_stats(document: &Html) -> FictionStats {
+    let rows = StatRows::from_document(document);
+    let required = |label: &str| {
+        rows.count(label).unwrap_or_else(|| {
+            tracing::warn!("Could not find {} stat, reading it as 0", label.to_lowercase());
+            0
+        })
+    };
+    FictionStats {
+        followers: required("FOLLOWERS"),
+        favorites: required("FAVORITES"),
+        total_views: rows.count("TOTAL VIEWS"),
+        average_views: rows.count("AVERAGE VIEWS"),
+    }
 }
 
 /// Parse a stat number that may contain commas (e.g., "6,475").
+///
+/// Only digits and commas are accepted, so unrelated text such as "1 of 2"
+/// is an error rather than being glued into one number.
 fn parse_stat_number(s: &str) -> Result<u64> {
-    let cleaned: String = s.chars().filter(|c| c.is_ascii_digit()).collect();
-    cleaned
+    let trimmed = s.trim();
+    let well_formed = trimmed.starts_with(|c: char| c.is_ascii_digit())
+        && trimmed.ends_with(|c: char| c.is_ascii_digit())
+        && trimmed.chars().all(|c| c.is_ascii_digit() || c == ',');
+    anyhow::ensure!(well_formed, "failed to parse stat number: '{}'", s);
+    trimmed
+        .replace(',', "")
         .parse::<u64>()
         .with_context(|| format!("failed to parse stat number: '{}'", s))
 }
 
-/// Extract chapter titles from the `window.chapters` JavaScript variable.
-fn extract_chapter_titles(html: &str) -> Result<Vec<String>> {
+/// Chapter data parsed from `window.chapters`, as aligned lists.
+#[derive(Debug, Default)]
+struct ChapterList {
+    titles: Vec<String>,
+    urls: Vec<String>,
+    /// Publish times, `None` where a chapter has no readable date.
+    dates: Vec<Option<Timestamp>>,
+}
+
+/// Extract chapter titles and URLs from the `window.chapters` JavaScript variable.
+///
+/// The two lists are aligned; a chapter without a URL gets an empty string.
+fn extract_chapters(html: &str) -> Result<ChapterList> {
     let re = regex::Regex::new(r"window\.chapters\s*=\s*(\[.*?\])\s*;")
         .expect("valid regex");
 
@@ -212,12 +837,31 @@ fn extract_chapter_titles(html: &str) -> Result<Vec<String>> {
     let chapters: Vec<serde_json::Value> =
         serde_json::from_str(json_str).context("failed to parse window.chapters JSON")?;
 
-    let titles: Vec<String> = chapters
-        .iter()
-        .filter_map(|ch| ch["title"].as_str().map(String::from))
-        .collect();
+    let mut list = ChapterList::default();
+    let mut unreadable_dates = Vec::new();
+    for ch in &chapters {
+        let Some(title) = ch["title"].as_str() else {
+            continue;
+        };
+        list.titles.push(title.to_string());
+        list.urls.push(ch["url"].as_str().unwrap_or_default().to_string());
+        let date = ch["date"].as_str();
+        let parsed = date.and_then(Timestamp::parse);
+        if let (Some(date), None) = (date, parsed) {
+            unreadable_dates.push(date);
+        }
+        list.dates.push(parsed);
+    }
+    // One warning per page, since a format change would hit every chapter
+    if let Some(first) = unreadable_dates.first() {
+        tracing::warn!(
+            "Ignoring {} unreadable chapter dates, such as {:?}",
+            unreadable_dates.len(),
+            first
+        );
+    }
 
-    Ok(titles)
+    Ok(list)
 }
 
 /// Strip HTML tags from a string, returning plain text.
@@ -229,7 +873,10 @@ fn strip_html_tags(html: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scraper::reviews::MAX_REVIEWS;
+    use crate::scraper::Validators;
     use std::path::PathBuf;
+    use std::time::Duration;
 
     fn testdata_path(filename: &str) -> PathBuf {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -244,36 +891,266 @@ mod tests {
     fn test_parse_novel_from_html() {
         let html =
             std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
-        let novel = parse_novel_from_html(&html, 90435).unwrap();
+        let novel = parse_novel_from_html(&html, 90435, ChapterTitleStorage::All).unwrap();
 
         assert_eq!(novel.id, 90435);
         assert_eq!(novel.title, "Bunny Girl Evolution");
         assert_eq!(novel.author, "Bedivere the Mad");
-        assert_eq!(novel.url, "https://www.royalroad.com/fiction/90435");
+        assert_eq!(novel.author_id, Some(512699));
+        assert_eq!(novel.url, "https://www.royalroad.com/fiction/90435/bunny-girl-evolution");
+        assert_eq!(novel.slug.as_deref(), Some("bunny-girl-evolution"));
+        assert_eq!(
+            novel.cover_url.as_deref(),
+            Some(
+                "https://www.royalroadcdn.com/public/covers-large/\
+                 90435-bunny-girl-evolution-book-2-stubbed-book.jpg?time=1764818753"
+            )
+        );
         assert_eq!(novel.pages, 391);
         assert!((novel.rating - 4.398).abs() < 0.01);
         assert_eq!(novel.status, NovelStatus::Stub);
         assert_eq!(novel.followers, 6475);
         assert_eq!(novel.favorites, 1808);
+        assert_eq!(novel.total_views, Some(514_501));
+        assert_eq!(novel.average_views, Some(13_905));
         assert_eq!(novel.chapter_count, 37);
+        assert!(!novel.is_fanfiction);
+        assert_eq!(novel.ai_content, None);
 
         // Check some specific tags
         assert!(novel.tags.contains(&"LitRPG".to_string()));
         assert!(novel.tags.contains(&"Fantasy".to_string()));
         assert!(novel.tags.contains(&"Action".to_string()));
 
+        // Genres are split out, while tags keep every entry
+        assert_eq!(novel.genres, vec!["Action", "Adventure", "Fantasy"]);
+        assert_eq!(novel.tags.len(), 19);
+
         // Check chapter titles
         assert!(novel.chapter_titles.contains(&"1 - Rabbit".to_string()));
         assert!(novel
             .chapter_titles
             .contains(&"Stub Announcement".to_string()));
+
+        // Chapter URLs are aligned with titles
+        assert_eq!(novel.chapter_urls.len(), novel.chapter_titles.len());
+        assert_eq!(
+            novel.chapter_urls[0],
+            "/fiction/90435/bunny-girl-evolution/chapter/1741031/1-rabbit"
+        );
+
+        // The first and latest chapters' publish dates
+        assert_eq!(
+            novel.first_chapter_date.map(|date| date.to_string()).as_deref(),
+            Some("2024-08-01T21:03:03Z")
+        );
+        assert_eq!(
+            novel.last_chapter_date.map(|date| date.to_string()).as_deref(),
+            Some("2026-02-07T07:04:08Z")
+        );
+    }
+
+    #[test]
+    fn test_parse_page_matches_separate_parses() {
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
+        let parsed = parse_page(&html, 90435, 5, ChapterTitleStorage::All).unwrap();
+        let novel = parse_novel_from_html(&html, 90435, ChapterTitleStorage::All).unwrap();
+        let reviews = crate::scraper::reviews::parse_reviews_from_html(&html, 5).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed.novel).unwrap(),
+            serde_json::to_value(&novel).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&parsed.reviews).unwrap(),
+            serde_json::to_value(&reviews).unwrap()
+        );
+        assert_eq!(parsed.reviews.len(), 5);
+    }
+
+    #[test]
+    fn test_scrape_novel_attaches_reviews() {
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
+        let client = crate::scraper::FakeClient::new()
+            .with_page("https://www.royalroad.com/fiction/90435", &html);
+        let novel = scrape_novel(&client, 90435, ChapterTitleStorage::All).unwrap();
+        assert_eq!(novel.reviews.map(|reviews| reviews.len()), Some(MAX_REVIEWS));
+        assert_eq!(client.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_also_liked_sidebar() {
+        let html = std::fs::read_to_string(testdata_path("novel_page_also_liked.html")).unwrap();
+        // Repeat links and the novel's own link are dropped; profile links
+        // are ignored
+        let ids = parse_also_liked_from_html(&html, 115399);
+        assert_eq!(ids, Some(vec![90435, 89877, 80744]));
+        let novel = parse_novel_from_html(&html, 115399, ChapterTitleStorage::All).unwrap();
+        assert_eq!(novel.also_liked, ids);
+
+        // Other portlets, such as the author's, aren't the sidebar
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
+        assert_eq!(parse_also_liked_from_html(&html, 90435), None);
+    }
+
+    #[test]
+    fn test_also_liked_falls_back_to_sidebar() {
+        let html = std::fs::read_to_string(testdata_path("novel_page_also_liked.html")).unwrap();
+        let json = std::fs::read_to_string(testdata_path("similar_90435.json")).unwrap();
+        let page = "https://www.royalroad.com/fiction/115399";
+        let similar = "https://www.royalroad.com/fictions/similar?fictionId=115399";
+
+        // The API answers: its IDs win
+        let client = crate::scraper::FakeClient::new()
+            .with_page(page, &html)
+            .with_page(similar, &json);
+        let novel = scrape_novel(&client, 115399, ChapterTitleStorage::All).unwrap();
+        let also_liked = scrape_also_liked(&client, &novel, None).unwrap();
+        assert_eq!(also_liked.source, AlsoLikedSource::Api);
+        assert_eq!(also_liked.fictions.len(), 10);
+
+        // The API fails, or answers with something other than the JSON list
+        for client in [
+            crate::scraper::FakeClient::new().with_page(page, &html),
+            crate::scraper::FakeClient::new()
+                .with_page(page, &html)
+                .with_page(similar, "<html><body>Oops</body></html>"),
+            crate::scraper::FakeClient::new()
+                .with_page(page, &html)
+                .with_page(similar, r#"[{"fictionId": 90435}]"#),
+        ] {
+            let novel = scrape_novel(&client, 115399, ChapterTitleStorage::All).unwrap();
+            let also_liked = scrape_also_liked(&client, &novel, None).unwrap();
+            assert_eq!(also_liked.source, AlsoLikedSource::Sidebar);
+            assert_eq!(also_liked.ids(), vec![90435, 89877, 80744]);
+            assert_eq!(also_liked.fictions[0], SimilarFiction::from_id(90435));
+            // The page was fetched once, for both the novel and the sidebar
+            assert_eq!(client.requests(), vec![page, similar]);
+        }
+
+        // Without a sidebar to fall back on, the API's error stands
+        let mut novel = parse_novel_from_html(&html, 115399, ChapterTitleStorage::All).unwrap();
+        novel.also_liked = None;
+        let client = crate::scraper::FakeClient::new();
+        assert!(scrape_also_liked(&client, &novel, None).is_err());
+    }
+
+    #[test]
+    fn test_also_liked_uses_cached_api_response() {
+        let json = std::fs::read_to_string(testdata_path("similar_90435.json")).unwrap();
+        let similar = "https://www.royalroad.com/fictions/similar?fictionId=90435";
+        let dir = std::env::temp_dir()
+            .join(format!("novel-finder-similar-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = ResponseCache::new(&dir, Duration::from_secs(3600));
+        let mut novel = crate::models::test_novel(90435);
+        novel.also_liked = None;
+
+        // The first lookup fetches and caches; the next reads the cache
+        let client = crate::scraper::FakeClient::new().with_page(similar, &json);
+        let fetched = scrape_also_liked(&client, &novel, Some(&cache)).unwrap();
+        let cached = scrape_also_liked(&client, &novel, Some(&cache)).unwrap();
+        assert_eq!(fetched, cached);
+        assert_eq!(client.requests(), vec![similar]);
+
+        // Unreadable responses aren't cached, and stale entries are refetched
+        let other = "https://www.royalroad.com/fictions/similar?fictionId=1";
+        let client = crate::scraper::FakeClient::new().with_page(other, "<html></html>");
+        let mut unknown = novel.clone();
+        unknown.id = 1;
+        assert!(scrape_also_liked(&client, &unknown, Some(&cache)).is_err());
+        assert_eq!(cache.get("similar", 1), None);
+
+        let stale = ResponseCache::new(&dir, Duration::ZERO);
+        let client = crate::scraper::FakeClient::new().with_page(similar, &json);
+        assert_eq!(scrape_also_liked(&client, &novel, Some(&stale)).unwrap(), fetched);
+        assert_eq!(client.requests(), vec![similar]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Serves one JSON response with an ETag, answering requests that send
+    /// the ETag back with 304 Not Modified.
+    struct RevalidatingClient {
+        body: String,
+        etag: std::sync::Mutex<String>,
+        sent: std::sync::Mutex<Vec<Validators>>,
+    }
+
+    impl HttpFetch for RevalidatingClient {
+        fn fetch(&self, url: &str) -> Result<String> {
+            self.fetch_json(url)
+        }
+
+        fn fetch_json(&self, url: &str) -> Result<String> {
+            match self.fetch_json_if_modified(url, &Validators::default())? {
+                Revalidated::Modified { body, .. } => Ok(body),
+                Revalidated::NotModified => unreachable!(),
+            }
+        }
+
+        fn fetch_json_if_modified(&self, _: &str, validators: &Validators) -> Result<Revalidated> {
+            self.sent.lock().unwrap().push(validators.clone());
+            let etag = self.etag.lock().unwrap().clone();
+            if validators.etag.as_ref() == Some(&etag) {
+                return Ok(Revalidated::NotModified);
+            }
+            Ok(Revalidated::Modified {
+                body: self.body.clone(),
+                validators: Validators {
+                    etag: Some(etag),
+                    last_modified: None,
+                },
+            })
+        }
+    }
+
+    #[test]
+    fn test_stale_cached_response_is_revalidated() {
+        let json = std::fs::read_to_string(testdata_path("similar_90435.json")).unwrap();
+        let dir = std::env::temp_dir()
+            .join(format!("novel-finder-similar-revalidate-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = ResponseCache::new(&dir, Duration::ZERO);
+        let client = RevalidatingClient {
+            body: json,
+            etag: std::sync::Mutex::new("\"v1\"".to_string()),
+            sent: std::sync::Mutex::new(Vec::new()),
+        };
+        let mut novel = crate::models::test_novel(90435);
+        novel.also_liked = None;
+
+        // Every lookup is stale; an unchanged response comes from the cache
+        let fetched = scrape_also_liked(&client, &novel, Some(&cache)).unwrap();
+        let revalidated = scrape_also_liked(&client, &novel, Some(&cache)).unwrap();
+        assert_eq!(fetched, revalidated);
+        *client.etag.lock().unwrap() = "\"v2\"".to_string();
+        assert_eq!(scrape_also_liked(&client, &novel, Some(&cache)).unwrap(), fetched);
+        assert_eq!(scrape_also_liked(&client, &novel, Some(&cache)).unwrap(), fetched);
+
+        let etags: Vec<Option<String>> =
+            client.sent.lock().unwrap().iter().map(|v| v.etag.clone()).collect();
+        let etag = |tag: &str| Some(tag.to_string());
+        assert_eq!(etags, [None, etag("\"v1\""), etag("\"v1\""), etag("\"v2\"")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_scrape_novel_takes_slug_from_redirect() {
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
+        let renamed = "https://www.royalroad.com/fiction/90435/bunny-girl-evolution-rewrite";
+        let client = crate::scraper::FakeClient::new()
+            .with_redirect("https://www.royalroad.com/fiction/90435", renamed)
+            .with_page(renamed, &html);
+
+        let novel = scrape_novel(&client, 90435, ChapterTitleStorage::All).unwrap();
+        assert_eq!(novel.url, renamed);
+        assert_eq!(novel.slug.as_deref(), Some("bunny-girl-evolution-rewrite"));
     }
 
     #[test]
     fn test_parse_novel_description_is_plain_text() {
         let html =
             std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
-        let novel = parse_novel_from_html(&html, 90435).unwrap();
+        let novel = parse_novel_from_html(&html, 90435, ChapterTitleStorage::All).unwrap();
 
         assert!(!novel.description.is_empty());
         assert!(novel.description.contains("bunny"));
@@ -283,6 +1160,147 @@ mod tests {
         assert!(!novel.description.contains("<span"));
     }
 
+    #[test]
+    fn test_parse_novel_cover_falls_back_to_img_and_skips_placeholder() {
+        let html =
+            std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
+        let cover = "https://www.royalroadcdn.com/public/covers-large/\
+                     90435-bunny-girl-evolution-book-2-stubbed-book.jpg?time=1764818753";
+        let parse = |html: &str| {
+            parse_novel_from_html(html, 90435, ChapterTitleStorage::None)
+                .unwrap()
+                .cover_url
+        };
+
+        let ld_image = format!("\"image\":\"{}\"", cover);
+        let without_ld_image = html.replacen(&ld_image, "\"image\":\"\"", 1);
+        assert_ne!(without_ld_image, html);
+        assert_eq!(parse(&without_ld_image).as_deref(), Some(cover));
+
+        let placeholder = html.replace(cover, "/dist/img/nocover-new-min.png");
+        assert_eq!(parse(&placeholder), None);
+    }
+
+    #[test]
+    fn test_parse_novel_prefers_longer_html_description() {
+        let html =
+            std::fs::read_to_string(testdata_path("novel_page_long_description.html")).unwrap();
+        let novel = parse_novel_from_html(&html, 424242, ChapterTitleStorage::All).unwrap();
+
+        assert!(novel.description.contains("tea shop at the edge of the world"));
+        assert!(novel.description.contains("Her old party keeps dropping by"));
+        // Spoiler content is included
+        assert!(novel.description.contains("left behind an egg"));
+        assert!(!novel.description.contains("<p>"));
+        assert!(!novel.description.contains("<div"));
+    }
+
+    #[test]
+    fn test_description_blocks_stay_apart() {
+        let html =
+            std::fs::read_to_string(testdata_path("novel_page_long_description.html")).unwrap();
+        let novel = parse_novel_from_html(&html, 424242, ChapterTitleStorage::All).unwrap();
+        // Paragraphs with no whitespace between them in the markup, and
+        // lines split with <br>, still come out as lines of their own
+        assert!(
+            novel.description.ends_with(
+                "The kettle whistles at dawn.\nThe bell rings at dusk.\n\
+                 First line of the sign\nSecond line of the sign"
+            ),
+            "{}",
+            novel.description
+        );
+        assert!(!novel.description.contains("dawn.The"));
+    }
+
+    #[test]
+    fn test_parse_novel_fanfiction_label() {
+        let html =
+            std::fs::read_to_string(testdata_path("novel_page_fanfiction.html")).unwrap();
+        let novel = parse_novel_from_html(&html, 515151, ChapterTitleStorage::All).unwrap();
+
+        assert_eq!(novel.title, "Hogwarts Tea Shop");
+        assert!(novel.is_fanfiction);
+        assert_eq!(novel.status, NovelStatus::Ongoing);
+    }
+
+    #[test]
+    fn test_parse_novel_ai_assisted_label() {
+        let html =
+            std::fs::read_to_string(testdata_path("novel_page_ai_assisted.html")).unwrap();
+        let novel = parse_novel_from_html(&html, 616161, ChapterTitleStorage::All).unwrap();
+
+        assert_eq!(novel.ai_content, Some(AiContentKind::Assisted));
+        // The AI label must not be confused with the status label
+        assert_eq!(novel.status, NovelStatus::Completed);
+        assert!(!novel.is_fanfiction);
+    }
+
+    #[test]
+    fn test_parse_novel_badges() {
+        let html = std::fs::read_to_string(testdata_path("novel_page_badges.html")).unwrap();
+        let novel = parse_novel_from_html(&html, 616162, ChapterTitleStorage::All).unwrap();
+
+        let badges: Vec<String> = novel.badges.iter().map(|badge| badge.to_string()).collect();
+        // Read from text, popover titles, and image alt text; the description's
+        // "Top 1% in Followers" is prose, not a badge
+        assert_eq!(
+            badges,
+            ["Top 2% in Followers", "Top 12.5% in Views", "Top 30% in Rating"]
+        );
+        assert_eq!(novel.best_badge().map(|badge| badge.top_percent), Some(2.0));
+        assert_eq!(novel.status, NovelStatus::Completed);
+
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
+        let novel = parse_novel_from_html(&html, 90435, ChapterTitleStorage::All).unwrap();
+        assert!(novel.badges.is_empty());
+    }
+
+    #[test]
+    fn test_ai_generated_in_warning_list() {
+        let html = r#"<div class="fiction-info">
+            <span class="label">Original</span>
+            <span class="label">ONGOING</span>
+            <ul class="list-inline"><li>Profanity</li><li>AI-Generated Content</li></ul>
+        </div>"#;
+        let document = Html::parse_document(html);
+        let labels = extract_labels(&document);
+
+        assert_eq!(extract_ai_content(&document, &labels), Some(AiContentKind::Generated));
+        assert_eq!(extract_status(&labels), NovelStatus::Ongoing);
+    }
+
+    #[test]
+    fn test_status_ignores_labels_outside_fiction_header() {
+        let html = r#"<div class="fiction-info"><span class="label">Original</span></div>
+            <span class="label">COMPLETED</span>"#;
+        let document = Html::parse_document(html);
+
+        assert_eq!(
+            extract_status(&extract_labels(&document)),
+            NovelStatus::Unknown(String::new())
+        );
+    }
+
+    #[test]
+    fn test_unknown_status_label_keeps_the_page() {
+        let labels: Vec<String> = ["ORIGINAL", "AI-ASSISTED CONTENT", "ON BREAK"]
+            .map(String::from)
+            .to_vec();
+        assert_eq!(
+            extract_status(&labels),
+            NovelStatus::Unknown("ON BREAK".to_string())
+        );
+
+        // Everything else on the page is still read
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html"))
+            .unwrap()
+            .replacen("STUB", "SEASON BREAK", 1);
+        let novel = parse_novel_from_html(&html, 90435, ChapterTitleStorage::All).unwrap();
+        assert_eq!(novel.status, NovelStatus::Unknown("SEASON BREAK".to_string()));
+        assert_eq!(novel.title, "Bunny Girl Evolution");
+    }
+
     #[test]
     fn test_parse_also_liked_from_json() {
         let json =
@@ -295,5 +1313,203 @@ mod tests {
         assert!(ids.contains(&115399)); // Death Healer
         assert!(ids.contains(&80744)); // Dungeon of Knowledge
         assert!(ids.contains(&129189)); // Chloe the Zombie
+
+        let fictions = parse_similar_fictions_from_json(&json).unwrap();
+        assert_eq!(fictions.iter().map(|f| f.id).collect::<Vec<_>>(), ids);
+        assert_eq!(fictions[0].title.as_deref(), Some("Cursed Explorer of the Arcana"));
+        assert_eq!(
+            fictions[0].url.as_deref(),
+            Some("/fiction/89877/cursed-explorer-of-the-arcana")
+        );
+        assert!(fictions[0].cover.as_deref().unwrap().starts_with("https://www.royalroadcdn.com/"));
+        assert_eq!(fictions[1].title.as_deref(), Some("Death Healer"));
+        // This snapshot's tags are all null
+        assert!(fictions.iter().all(|f| f.title.is_some() && f.tags.is_empty()));
+
+        let json = r#"[
+            {"id": 1, "title": " Tagged ", "tags": ["LitRPG", "Female Lead", 7]},
+            {"id": 2, "title": 3, "url": null, "tags": "LitRPG"}
+        ]"#;
+        let fictions = parse_similar_fictions_from_json(json).unwrap();
+        assert_eq!(fictions[0].title.as_deref(), Some("Tagged"));
+        assert_eq!(fictions[0].tags, vec!["LitRPG", "Female Lead"]);
+        assert_eq!(fictions[1], SimilarFiction::from_id(2));
+    }
+
+    /// Cases per property test; the generator is seeded, so failures repeat.
+    const CASES: usize = 500;
+
+    #[test]
+    fn test_parse_stat_number() {
+        assert_eq!(parse_stat_number(" 6,475\n").unwrap(), 6475);
+        assert_eq!(parse_stat_number("514501").unwrap(), 514_501);
+        for bad in ["1 of 2", "", ",", "12,", ",12", "1.5", "-3", "12k", "99999999999999999999"] {
+            assert!(parse_stat_number(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    /// A stats section with the given `<li>` rows.
+    fn stats_page(rows: &[(&str, &str)]) -> Html {
+        let items: String = rows
+            .iter()
+            .map(|(label, value)| {
+                let value = match value.strip_prefix("score ") {
+                    Some(score) => format!("<li><span data-content=\"{}\"></span></li>", score),
+                    None if value.is_empty() => String::new(),
+                    None => format!("<li class=\"bold uppercase font-red-sunglo\">{}</li>", value),
+                };
+                format!("<li class=\"bold uppercase\">{} :</li>{}", label, value)
+            })
+            .collect();
+        Html::parse_document(&format!(
+            r#"<div class="fiction-stats"><div class="stats-content"><ul>{}</ul></div></div>"#,
+            items
+        ))
+    }
+
+    #[test]
+    fn test_stat_rows_are_read_in_any_order() {
+        let rows = [
+            ("Favorites", "1,808"),
+            ("Overall Score", "score 4.4 / 5"),
+            ("Pages", "391"),
+            ("Total Views", "514,501"),
+            ("Followers", "6,475"),
+            ("Ratings", "1,162"),
+        ];
+        let stat_rows = StatRows::from_document(&stats_page(&rows));
+        assert_eq!(stat_rows.get("OVERALL SCORE"), Some("4.4 / 5"));
+        assert_eq!(stat_rows.count("RATINGS"), Some(1162));
+        assert_eq!(stat_rows.count("PAGES"), Some(391));
+
+        let stats = extract_stats(&stats_page(&rows));
+        assert_eq!((stats.followers, stats.favorites), (6475, 1808));
+        assert_eq!((stats.total_views, stats.average_views), (Some(514_501), None));
+    }
+
+    #[test]
+    fn test_missing_stats_read_as_zero() {
+        // Favorites lost its value, so it mustn't take the next row's
+        let rows = [("Favorites", ""), ("Total Views", "514,501"), ("Average Views", "n/a")];
+        let stat_rows = StatRows::from_document(&stats_page(&rows));
+        assert_eq!(stat_rows.get("FAVORITES"), None);
+        assert_eq!(stat_rows.get("TOTAL VIEWS"), Some("514,501"));
+
+        let stats = extract_stats(&stats_page(&rows));
+        assert_eq!((stats.followers, stats.favorites), (0, 0));
+        assert_eq!((stats.total_views, stats.average_views), (Some(514_501), None));
+        assert_eq!(StatRows::from_document(&stats_page(&[])), StatRows::default());
+    }
+
+    #[test]
+    fn test_page_without_stats_still_parses() {
+        // As if the stats section had moved where the parser doesn't look
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html"))
+            .unwrap()
+            .replace(r#"class="stats-content""#, r#"class="stats-moved""#);
+        let novel = parse_novel_from_html(&html, 90435, ChapterTitleStorage::All).unwrap();
+        assert_eq!(novel.followers, 0);
+        assert_eq!(novel.favorites, 0);
+        assert_eq!(novel.total_views, None);
+        assert_eq!(novel.pages, 391);
+    }
+
+    /// Digits, separators, and lookalikes: a no-break space, an Arabic-Indic
+    /// digit, and letters.
+    const STAT_ALPHABET: [char; 11] =
+        ['0', '7', '9', ',', ' ', '.', 'a', 'K', '\u{a0}', '\u{663}', '\u{e9}'];
+
+    #[test]
+    fn test_parse_stat_number_properties() {
+        use crate::util::{format_thousands, random_string};
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(152);
+        for _ in 0..CASES {
+            // Formatted numbers round-trip, whatever whitespace surrounds them
+            let n: u64 = rng.gen();
+            let padded = format!(" \t{}\n", format_thousands(n));
+            assert_eq!(parse_stat_number(&padded).unwrap(), n, "{:?}", padded);
+
+            // Arbitrary mixes never panic, and only digits and commas parse
+            let input = random_string(&mut rng, &STAT_ALPHABET, 12);
+            if let Ok(parsed) = parse_stat_number(&input) {
+                assert!(input.trim().chars().all(|c| c.is_ascii_digit() || c == ','));
+                let digits: String = input.chars().filter(char::is_ascii_digit).collect();
+                assert_eq!(Some(parsed), digits.parse().ok(), "{:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_strip_html_tags_properties() {
+        use crate::util::random_string;
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(152);
+        let markup = ['<', '>', '/', '&', ';', 'p', 'b', '"', '=', ' ', 'x', '\u{1f407}'];
+        let plain = ['a', 'Z', ' ', '.', '\n', '\u{e9}', '\u{1f407}'];
+        for _ in 0..CASES {
+            // Arbitrary markup never panics
+            strip_html_tags(&random_string(&mut rng, &markup, 40));
+
+            // Plain text survives being wrapped in tags
+            let text = random_string(&mut rng, &plain, 40);
+            assert_eq!(strip_html_tags(&format!("<p><b>{}</b></p>", text)), text);
+        }
+    }
+
+    /// A random JSON value, nested at most `depth` levels, whose objects
+    /// sometimes carry an "id" of any type.
+    fn random_json(rng: &mut impl rand::Rng, depth: usize) -> serde_json::Value {
+        use serde_json::{json, Value};
+        let kind = rng.gen_range(0..if depth == 0 { 4 } else { 6 });
+        match kind {
+            0 => Value::Null,
+            1 => json!(rng.gen::<bool>()),
+            2 => match rng.gen_range(0..3) {
+                0 => json!(rng.gen::<u64>()),
+                1 => json!(rng.gen::<i64>()),
+                _ => json!(rng.gen::<f64>() * 1e6),
+            },
+            3 => json!(crate::util::random_string(rng, &['1', 'a', '"', '\\'], 5)),
+            4 => {
+                let len = rng.gen_range(0..4);
+                Value::Array((0..len).map(|_| random_json(rng, depth - 1)).collect())
+            }
+            _ => {
+                let mut object = serde_json::Map::new();
+                if rng.gen_bool(0.7) {
+                    object.insert("id".to_string(), random_json(rng, depth - 1));
+                }
+                object.insert("title".to_string(), random_json(rng, depth - 1));
+                Value::Object(object)
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_also_liked_tolerates_any_json() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(152);
+        for _ in 0..CASES {
+            let value = random_json(&mut rng, 3);
+            let parsed = parse_also_liked_from_json(&value.to_string());
+            match value.as_array() {
+                // Every item with a non-negative integer ID is kept, in order,
+                // unless no item has one
+                Some(items) => {
+                    let expected: Vec<u64> =
+                        items.iter().filter_map(|item| item.get("id")?.as_u64()).collect();
+                    if expected.is_empty() && !items.is_empty() {
+                        assert!(parsed.is_err(), "{}", value);
+                    } else {
+                        assert_eq!(parsed.unwrap(), expected, "{}", value);
+                    }
+                }
+                None => assert!(parsed.is_err(), "{}", value),
+            }
+        }
     }
 }
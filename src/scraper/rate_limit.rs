@@ -0,0 +1,181 @@
+//! Spacing out requests, with separate limits per kind of route.
+//!
+//! Full fiction pages are heavy to serve, while JSON endpoints like
+//! `/fictions/similar` are light, so each [`RateClass`] keeps its own
+//! minimum interval and its own record of when its last request went out.
+//! A request only waits on earlier requests of its class; the classes are
+//! told apart by URL.
+
+use crate::site::royalroad;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The delay between page requests unless configured otherwise.
+pub const DEFAULT_PAGE_DELAY: Duration = Duration::from_millis(1000);
+
+/// The delay between API requests unless configured otherwise.
+pub const DEFAULT_API_DELAY: Duration = Duration::from_millis(250);
+
+/// A kind of route with its own rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateClass {
+    /// HTML pages, robots.txt, and anything else not classed as an API.
+    Page,
+    /// Lightweight JSON endpoints.
+    Api,
+}
+
+impl RateClass {
+    /// The class a request for `url` falls in.
+    pub fn of(url: &str) -> Self {
+        let path = url
+            .find("://")
+            .and_then(|scheme_end| {
+                let host_start = scheme_end + 3;
+                url[host_start..]
+                    .find('/')
+                    .map(|index| &url[host_start + index..])
+            })
+            .unwrap_or_default();
+        if royalroad::API_PATHS.iter().any(|prefix| path.starts_with(prefix)) {
+            RateClass::Api
+        } else {
+            RateClass::Page
+        }
+    }
+}
+
+/// The minimum interval between requests of each class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimits {
+    pub pages: Duration,
+    pub api: Duration,
+}
+
+impl RateLimits {
+    /// Limits of `delay` for every class.
+    pub fn uniform(delay: Duration) -> Self {
+        Self {
+            pages: delay,
+            api: delay,
+        }
+    }
+
+    /// The minimum interval for `class`.
+    pub fn delay(&self, class: RateClass) -> Duration {
+        match class {
+            RateClass::Page => self.pages,
+            RateClass::Api => self.api,
+        }
+    }
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        Self {
+            pages: DEFAULT_PAGE_DELAY,
+            api: DEFAULT_API_DELAY,
+        }
+    }
+}
+
+/// When each class last sent a request, for spacing out the next.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    last_page: Mutex<Option<Instant>>,
+    last_api: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Wait until at least `interval` has passed since the last request of
+    /// `class`, then record a request as sent now. The first request of a
+    /// class doesn't wait. Concurrent callers of one class take turns.
+    pub fn wait(&self, class: RateClass, interval: Duration) {
+        let last = match class {
+            RateClass::Page => &self.last_page,
+            RateClass::Api => &self.last_api,
+        };
+        // Holding the lock while sleeping keeps the next caller waiting
+        // for this request rather than the one before it
+        let mut last = last.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = *last {
+            let elapsed = previous.elapsed();
+            if elapsed < interval {
+                std::thread::sleep(interval - elapsed);
+            }
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classifies_by_path() {
+        let api = [
+            "https://www.royalroad.com/fictions/similar?fictionId=1",
+            "https://www.royalroad.com/api/fiction/1",
+        ];
+        for url in api {
+            assert_eq!(RateClass::of(url), RateClass::Api, "{}", url);
+        }
+        let pages = [
+            "https://www.royalroad.com/fiction/1/title",
+            "https://www.royalroad.com/fictions/search?title=similar",
+            "https://www.royalroad.com/robots.txt",
+            "https://www.royalroad.com",
+            "not a url",
+        ];
+        for url in pages {
+            assert_eq!(RateClass::of(url), RateClass::Page, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_classes_keep_their_own_intervals() {
+        let limits = RateLimits {
+            pages: Duration::from_millis(150),
+            api: Duration::from_millis(40),
+        };
+        let limiter = RateLimiter::default();
+        let started = Instant::now();
+        let order = [
+            RateClass::Page,
+            RateClass::Api,
+            RateClass::Api,
+            RateClass::Page,
+            RateClass::Api,
+        ];
+        let mut sent: Vec<(RateClass, Duration)> = Vec::new();
+        for class in order {
+            limiter.wait(class, limits.delay(class));
+            sent.push((class, started.elapsed()));
+        }
+
+        // Times are read just after each wait returns, so allow for the
+        // moment between the limiter's clock and ours
+        let slack = Duration::from_millis(1);
+        for class in [RateClass::Page, RateClass::Api] {
+            let times: Vec<Duration> = sent
+                .iter()
+                .filter(|(c, _)| *c == class)
+                .map(|(_, t)| *t)
+                .collect();
+            for pair in times.windows(2) {
+                assert!(
+                    pair[1] - pair[0] + slack >= limits.delay(class),
+                    "{:?}: {:?}",
+                    class,
+                    times
+                );
+            }
+        }
+        // API requests didn't wait behind the slower page requests: the
+        // first goes out right after the first page
+        assert!(sent[1].1 < limits.pages, "{:?}", sent);
+        // The second page waits only for the first
+        assert!(sent[3].1 < limits.pages * 2, "{:?}", sent);
+    }
+}
@@ -1,11 +1,95 @@
 //! Scrape novel reviews from RoyalRoad.
 //!
-//! Fetches user reviews for a given novel to use in evaluation.
+//! Fetches user reviews for a given novel to use in evaluation. Repeat
+//! reviews are dropped so one reader's opinion isn't counted twice: only an
+//! author's latest review is kept, along with no review nearly identical to
+//! one already kept.
+//!
+//! The selectors for each part of a review are kept in one table, each
+//! with fallbacks for when RoyalRoad renames a class. If the page shows
+//! reviews but none could be parsed, that's a [`ParseFailure`] rather than a
+//! novel without reviews.
 
+use crate::models::timestamp::Timestamp;
 use crate::models::Review;
-use crate::scraper::RoyalRoadClient;
+use crate::scraper::HttpFetch;
+use crate::site::RoyalRoad;
 use anyhow::Result;
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
+use std::fmt;
+
+/// How many reviews are kept per novel for evaluation.
+pub const MAX_REVIEWS: usize = 10;
+
+/// Word-level similarity from which a review counts as a copy of another.
+const DUPLICATE_SIMILARITY: f64 = 0.9;
+
+/// CSS selectors for the parts of a review, each a chain: the selector for
+/// the current markup first, then fallbacks tried in order while none has
+/// matched anything.
+struct ReviewSelectors {
+    /// The reviews section of the page, header and all.
+    section: &'static [&'static str],
+    /// Each review in the page.
+    review: &'static [&'static str],
+    /// Within a review, the link to its author's profile.
+    author: &'static [&'static str],
+    /// Within a review, elements whose `aria-label` may give its stars.
+    rating: &'static [&'static str],
+    /// Within a review, its text.
+    text: &'static [&'static str],
+    /// Within a review, the `<time>` it was posted at.
+    date: &'static [&'static str],
+    /// Within a review, the thumbs-up button showing its helpful votes.
+    helpful_votes: &'static [&'static str],
+    /// Within the reviews section, links every review has whatever its
+    /// classes: its permalink and its report link.
+    markers: &'static [&'static str],
+}
+
+const SELECTORS: ReviewSelectors = ReviewSelectors {
+    section: &["div.portlet.reviews", "div.reviews-container"],
+    review: &["div.review", "div.reviews-container > div[id^='review-']"],
+    author: &[
+        "div.review-meta a.small",
+        ".review-meta a[href*='/profile/']",
+        "a[href*='/profile/']",
+    ],
+    rating: &[
+        "div.overall-score-container div[aria-label]",
+        "[aria-label$=' stars'], [aria-label$=' star']",
+    ],
+    text: &["div.review-inner", "div.review-content"],
+    date: &["div.review-meta time", "time[datetime]"],
+    helpful_votes: &[
+        "form.review-vote-form button[value='true']",
+        "button[name='up'][value='true']",
+    ],
+    markers: &["a[href*='?review=']", "a[href^='/report/review/']"],
+};
+
+/// A page whose reviews section shows reviews, none of which could be
+/// parsed: most likely RoyalRoad changed its review markup past what the
+/// selectors' fallbacks cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseFailure;
+
+impl fmt::Display for ParseFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the page shows reviews but none could be parsed; RoyalRoad's review markup may \
+             have changed"
+        )
+    }
+}
+
+impl std::error::Error for ParseFailure {}
+
+/// Whether an error (or any error it wraps) is a [`ParseFailure`].
+pub fn is_parse_failure(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.downcast_ref::<ParseFailure>().is_some())
+}
 
 /// Scrape reviews for a novel from its RoyalRoad page.
 ///
@@ -17,58 +101,162 @@ use scraper::{Html, Selector};
 /// # Returns
 /// A list of reviews for the novel.
 pub fn scrape_reviews(
-    client: &RoyalRoadClient,
+    client: &dyn HttpFetch,
     novel_id: u64,
     max_reviews: usize,
 ) -> Result<Vec<Review>> {
-    let url = format!("https://www.royalroad.com/fiction/{}", novel_id);
-    let html = client.fetch(&url)?;
-    parse_reviews_from_html(&html, max_reviews)
+    crate::site::scrape_reviews(&RoyalRoad, client, novel_id, max_reviews)
 }
 
 /// Parse reviews from the raw HTML of a RoyalRoad novel page.
 ///
 /// This is separated from `scrape_reviews` so it can be unit-tested against
-/// an HTML snapshot without making HTTP requests.
-pub(crate) fn parse_reviews_from_html(html: &str, max_reviews: usize) -> Result<Vec<Review>> {
-    let document = Html::parse_document(html);
-
-    let review_selector = Selector::parse("div.review").expect("valid selector");
+/// an HTML snapshot without making HTTP requests. Fails with a
+/// [`ParseFailure`] if the page shows reviews but none could be parsed.
+pub fn parse_reviews_from_html(html: &str, max_reviews: usize) -> Result<Vec<Review>> {
+    parse_reviews_from_document(&Html::parse_document(html), max_reviews)
+}
 
+/// Parse reviews from an already parsed novel page.
+///
+/// Repeat reviews are dropped before keeping the first `max_reviews`, so
+/// they can't crowd out other readers.
+pub(crate) fn parse_reviews_from_document(
+    document: &Html,
+    max_reviews: usize,
+) -> Result<Vec<Review>> {
     let mut reviews = Vec::new();
 
-    for review_el in document.select(&review_selector) {
-        if reviews.len() >= max_reviews {
-            break;
-        }
-
+    for review_el in select_chain(&document.root_element(), SELECTORS.review) {
         let author = extract_review_author(&review_el);
         let rating = extract_review_rating(&review_el);
         let text = extract_review_text(&review_el);
         let posted_date = extract_review_date(&review_el);
+        let helpful_votes = extract_review_helpful_votes(&review_el);
 
         // Only include reviews where we could extract at minimum the text.
-        if let (Some(author), Some(rating), Some(text), Some(posted_date)) =
-            (author, rating, text, posted_date)
-        {
+        // A missing or unreadable date leaves the review undated.
+        if let (Some(author), Some(rating), Some(text)) = (author, rating, text) {
             reviews.push(Review {
                 author,
                 rating,
                 text,
                 posted_date,
+                helpful_votes,
             });
         }
     }
 
+    if reviews.is_empty() && shows_reviews(document) {
+        return Err(ParseFailure.into());
+    }
+    let mut reviews = dedup_reviews(reviews);
+    reviews.truncate(max_reviews);
     Ok(reviews)
 }
 
+/// Whether the page has a reviews section with reviews in it, going by
+/// links every review has rather than the classes that parse them.
+fn shows_reviews(document: &Html) -> bool {
+    select_chain(&document.root_element(), SELECTORS.section)
+        .iter()
+        .any(|section| !select_chain(section, SELECTORS.markers).is_empty())
+}
+
+/// The elements within `el` matching the first selector in `chain` that
+/// matches any.
+fn select_chain<'a>(el: &ElementRef<'a>, chain: &[&str]) -> Vec<ElementRef<'a>> {
+    for (index, selector) in chain.iter().enumerate() {
+        let parsed = Selector::parse(selector).expect("valid selector");
+        let matched: Vec<ElementRef<'a>> = el.select(&parsed).collect();
+        if !matched.is_empty() {
+            if index > 0 {
+                tracing::debug!(
+                    "Selector {:?} matched nothing; fell back to {:?}",
+                    chain[0],
+                    selector
+                );
+            }
+            return matched;
+        }
+    }
+    Vec::new()
+}
+
+/// Drop repeat reviews, keeping the page order of the rest.
+///
+/// Of several reviews by one author, only the most recently posted is kept
+/// (the first shown, on a tie; undated reviews count as oldest). A review whose text is at least
+/// `DUPLICATE_SIMILARITY` similar to one already kept is dropped too.
+pub(crate) fn dedup_reviews(reviews: Vec<Review>) -> Vec<Review> {
+    let is_latest = |index: usize, review: &Review| {
+        reviews.iter().enumerate().all(|(other_index, other)| {
+            other.author != review.author
+                || other.posted_date < review.posted_date
+                || (other.posted_date == review.posted_date && other_index >= index)
+        })
+    };
+    let latest: Vec<bool> = reviews
+        .iter()
+        .enumerate()
+        .map(|(index, review)| is_latest(index, review))
+        .collect();
+
+    let mut kept: Vec<Review> = Vec::new();
+    for (review, latest) in reviews.into_iter().zip(latest) {
+        if !latest {
+            tracing::debug!("Dropping an earlier review by {}", review.author);
+            continue;
+        }
+        let copy = kept
+            .iter()
+            .find(|other| text_similarity(&other.text, &review.text) >= DUPLICATE_SIMILARITY);
+        if let Some(copy) = copy {
+            tracing::debug!(
+                "Dropping review by {}, a near copy of one by {}",
+                review.author,
+                copy.author
+            );
+            continue;
+        }
+        kept.push(review);
+    }
+    kept
+}
+
+/// How similar two texts are, from 0.0 to 1.0: one minus the word-level
+/// edit distance over the longer text's word count, ignoring case.
+fn text_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<String> = a.split_whitespace().map(str::to_lowercase).collect();
+    let b: Vec<String> = b.split_whitespace().map(str::to_lowercase).collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    // The distance is at least the difference in length, which bounds the
+    // similarity; skip the quadratic comparison when it can't be close
+    let bound = a.len().min(b.len()) as f64 / longest as f64;
+    if bound < DUPLICATE_SIMILARITY {
+        return bound;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, word_a) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, word_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(word_a != word_b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
 /// Extract the review author username from a review element.
-fn extract_review_author(review_el: &scraper::ElementRef) -> Option<String> {
-    let selector = Selector::parse("div.review-meta a.small").expect("valid selector");
-    review_el
-        .select(&selector)
-        .next()
+fn extract_review_author(review_el: &ElementRef) -> Option<String> {
+    select_chain(review_el, SELECTORS.author)
+        .first()
         .map(|el| el.text().collect::<String>().trim().to_string())
 }
 
@@ -76,13 +264,10 @@ fn extract_review_author(review_el: &scraper::ElementRef) -> Option<String> {
 ///
 /// The rating is stored in an `aria-label` attribute like "5 stars" or "4.5 stars"
 /// on a div inside `div.overall-score-container`.
-fn extract_review_rating(review_el: &scraper::ElementRef) -> Option<f64> {
-    let selector =
-        Selector::parse("div.overall-score-container div[aria-label]").expect("valid selector");
-
+fn extract_review_rating(review_el: &ElementRef) -> Option<f64> {
     // The second div[aria-label] inside overall-score-container has the star rating.
     // First is "Overall Score", second is "X stars".
-    for el in review_el.select(&selector) {
+    for el in select_chain(review_el, SELECTORS.rating) {
         let aria_label = el.value().attr("aria-label")?;
         if aria_label.ends_with("stars") || aria_label.ends_with("star") {
             // Parse "5 stars" or "4.5 stars" -> f64
@@ -100,9 +285,8 @@ fn extract_review_rating(review_el: &scraper::ElementRef) -> Option<f64> {
 /// Extract the review text content from a review element.
 ///
 /// Collects plain text from the `div.review-inner` element, stripping HTML tags.
-fn extract_review_text(review_el: &scraper::ElementRef) -> Option<String> {
-    let selector = Selector::parse("div.review-inner").expect("valid selector");
-    review_el.select(&selector).next().map(|el| {
+fn extract_review_text(review_el: &ElementRef) -> Option<String> {
+    select_chain(review_el, SELECTORS.text).first().map(|el| {
         let text = el.text().collect::<String>();
         // Collapse whitespace runs and trim
         let cleaned: String = text
@@ -113,15 +297,32 @@ fn extract_review_text(review_el: &scraper::ElementRef) -> Option<String> {
     })
 }
 
-/// Extract the posted date from a review element.
+/// Extract the posted time from a review element.
 ///
-/// The date is stored in the `datetime` attribute of a `<time>` element.
-fn extract_review_date(review_el: &scraper::ElementRef) -> Option<String> {
-    let selector = Selector::parse("div.review-meta time").expect("valid selector");
-    review_el
-        .select(&selector)
-        .next()
-        .and_then(|el| el.value().attr("datetime").map(String::from))
+/// The time is stored in the `datetime` attribute of a `<time>` element. An
+/// unreadable one is logged and left out rather than dropping the review.
+fn extract_review_date(review_el: &ElementRef) -> Option<Timestamp> {
+    let datetime = select_chain(review_el, SELECTORS.date)
+        .first()?
+        .value()
+        .attr("datetime")?;
+    let parsed = Timestamp::parse(datetime);
+    if parsed.is_none() {
+        tracing::warn!("Ignoring unreadable review date {:?}", datetime);
+    }
+    parsed
+}
+
+/// Extract how many readers found the review helpful.
+///
+/// The count is shown beside the thumbs-up button of the review's vote form;
+/// pages served to logged-out readers leave it out.
+fn extract_review_helpful_votes(review_el: &ElementRef) -> Option<u64> {
+    let text = select_chain(review_el, SELECTORS.helpful_votes)
+        .first()?
+        .text()
+        .collect::<String>();
+    text.trim().replace(',', "").parse().ok()
 }
 
 #[cfg(test)]
@@ -152,7 +353,7 @@ mod tests {
         assert_eq!(first.author, "PhantomBuni");
         assert!((first.rating - 5.0).abs() < 0.01);
         assert!(first.text.contains("I loved this book so much"));
-        assert!(first.posted_date.contains("2025-01-07"));
+        assert_eq!(first.posted_date.unwrap().date(), "2025-01-07");
     }
 
     #[test]
@@ -183,6 +384,195 @@ mod tests {
         assert!((kptn.rating - 3.0).abs() < 0.01);
     }
 
+    /// A page of reviews, each `(author, rating, posted date, text)`.
+    fn reviews_page(reviews: &[(&str, f64, &str, &str)]) -> String {
+        let reviews: Vec<_> = reviews
+            .iter()
+            .map(|&(author, rating, date, text)| (author, rating, date, text, None))
+            .collect();
+        reviews_page_with_votes(&reviews)
+    }
+
+    /// A page of reviews, each `(author, rating, posted date, text, helpful
+    /// votes)`.
+    fn reviews_page_with_votes(reviews: &[(&str, f64, &str, &str, Option<&str>)]) -> String {
+        let mut html = String::from("<html><body>");
+        for (author, rating, date, text, votes) in reviews {
+            html.push_str(&format!(
+                r#"<div class="review">
+                    <div class="review-meta">
+                        <a class="small" href="/profile/1">{}</a>
+                        <time datetime="{}">some time ago</time>
+                    </div>
+                    <div class="overall-score-container">
+                        <div aria-label="Overall Score"></div>
+                        <div aria-label="{} stars"></div>
+                    </div>
+                    <div class="review-inner"><p>{}</p></div>
+                    <form class="inline-block review-vote-form">
+                        <button name="up" value="true"><i class="fa fa-thumbs-up"></i> {}</button>
+                        <button name="up" value="false"><i class="fa fa-thumbs-down"></i></button>
+                    </form>
+                </div>"#,
+                author,
+                date,
+                rating,
+                text,
+                votes.unwrap_or_default()
+            ));
+        }
+        html.push_str("</body></html>");
+        html
+    }
+
+    fn authors(reviews: &[Review]) -> Vec<&str> {
+        reviews.iter().map(|r| r.author.as_str()).collect()
+    }
+
+    const LONG_REVIEW: &str = "The magic system is carefully thought out and the \
+        protagonist grows in believable steps, although the middle arc drags for a \
+        while before the tournament finally picks the pace back up again.";
+
+    #[test]
+    fn test_parse_reviews_keeps_latest_by_author() {
+        let html = reviews_page(&[
+            ("alice", 3.0, "2024-01-05T10:00:00", "Advance review: promising start."),
+            ("bob", 4.0, "2024-02-01T08:00:00", "Solid progression fantasy."),
+            ("alice", 4.5, "2024-06-10T12:00:00", "Updated: it got much better."),
+            ("carol", 2.0, "2024-03-01T09:00:00", "Not for me."),
+            ("alice", 4.0, "2024-06-10T12:00:00", "Same time, shown later."),
+        ]);
+        let reviews = parse_reviews_from_html(&html, 10).unwrap();
+        assert_eq!(authors(&reviews), vec!["bob", "alice", "carol"]);
+        assert!(reviews[1].text.starts_with("Updated"));
+        assert!((reviews[1].rating - 4.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_parse_reviews_drops_near_copies() {
+        let edited = LONG_REVIEW.replace("drags", "DRAGS slightly");
+        let shorter = LONG_REVIEW.replace("for a while ", "");
+        let html = reviews_page(&[
+            ("alice", 4.0, "2024-01-01T00:00:00", LONG_REVIEW),
+            ("bob", 4.0, "2024-01-02T00:00:00", &edited),
+            ("carol", 4.0, "2024-01-03T00:00:00", &shorter),
+            ("dave", 1.0, "2024-01-04T00:00:00", "The magic system makes no sense."),
+        ]);
+        let reviews = parse_reviews_from_html(&html, 10).unwrap();
+        assert_eq!(authors(&reviews), vec!["alice", "dave"]);
+    }
+
+    #[test]
+    fn test_parse_reviews_dedups_before_limiting() {
+        let html = reviews_page(&[
+            ("alice", 5.0, "2024-01-01T00:00:00", "Advance review: great."),
+            ("alice", 5.0, "2024-01-02T00:00:00", LONG_REVIEW),
+            ("bob", 5.0, "2024-01-03T00:00:00", LONG_REVIEW),
+            ("carol", 3.0, "2024-01-04T00:00:00", "Decent."),
+            ("dave", 2.0, "2024-01-05T00:00:00", "Slow."),
+        ]);
+        let reviews = parse_reviews_from_html(&html, 2).unwrap();
+        assert_eq!(authors(&reviews), vec!["alice", "carol"]);
+        let reviews = parse_reviews_from_html(&html, 3).unwrap();
+        assert_eq!(authors(&reviews), vec!["alice", "carol", "dave"]);
+    }
+
+    #[test]
+    fn test_parse_reviews_helpful_votes() {
+        // The snapshot was saved logged out, so it shows no counts
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
+        let reviews = parse_reviews_from_html(&html, 100).unwrap();
+        assert!(reviews.iter().all(|r| r.helpful_votes.is_none()));
+        let counted = html.replacen(
+            r#"<i class="fa fa-thumbs-up"></i>"#,
+            r#"<i class="fa fa-thumbs-up"></i> 1,204"#,
+            1,
+        );
+        let reviews = parse_reviews_from_html(&counted, 100).unwrap();
+        assert_eq!(reviews[0].author, "PhantomBuni");
+        assert_eq!(reviews[0].helpful_votes, Some(1204));
+        assert_eq!(reviews[1].helpful_votes, None);
+
+        let html = reviews_page_with_votes(&[
+            ("alice", 4.0, "2024-01-01T00:00:00", "Fine.", Some("7")),
+            ("bob", 4.0, "2024-01-02T00:00:00", "Okay.", Some("0")),
+            ("carol", 4.0, "2024-01-03T00:00:00", "Sure.", Some("n/a")),
+        ]);
+        let votes: Vec<Option<u64>> = parse_reviews_from_html(&html, 10)
+            .unwrap()
+            .iter()
+            .map(|r| r.helpful_votes)
+            .collect();
+        assert_eq!(votes, vec![Some(7), Some(0), None]);
+    }
+
+    #[test]
+    fn test_text_similarity() {
+        assert_eq!(text_similarity("", ""), 1.0);
+        assert_eq!(text_similarity("Great  story", "great story"), 1.0);
+        assert_eq!(text_similarity("great story", ""), 0.0);
+        assert_eq!(text_similarity("a b c d", "a x c d"), 0.75);
+        assert_eq!(text_similarity("a b c d e f g h i j", "a b c d e f g h i"), 0.9);
+        assert!(text_similarity(LONG_REVIEW, "Short.") < 0.1);
+    }
+
+    #[test]
+    fn test_selectors_are_valid() {
+        let chains = [
+            SELECTORS.section,
+            SELECTORS.review,
+            SELECTORS.author,
+            SELECTORS.rating,
+            SELECTORS.text,
+            SELECTORS.date,
+            SELECTORS.helpful_votes,
+            SELECTORS.markers,
+        ];
+        for selector in chains.iter().flat_map(|chain| chain.iter()) {
+            assert!(Selector::parse(selector).is_ok(), "{}", selector);
+        }
+    }
+
+    #[test]
+    fn test_parse_reviews_falls_back_on_degraded_markup() {
+        let html = std::fs::read_to_string(testdata_path("reviews_degraded.html")).unwrap();
+        let reviews = parse_reviews_from_html(&html, 10).unwrap();
+        assert_eq!(authors(&reviews), vec!["PhantomBuni", "jumpsplat120"]);
+
+        let first = &reviews[0];
+        assert!((first.rating - 5.0).abs() < 0.01);
+        assert_eq!(
+            first.text,
+            "I loved this book so much I caught up to chapter 61 in just two days! \
+             Elise is adorable and I love the way she thinks."
+        );
+        assert_eq!(first.posted_date.unwrap().date(), "2025-01-07");
+        assert_eq!(first.helpful_votes, Some(12));
+        assert!((reviews[1].rating - 4.5).abs() < 0.01);
+        assert_eq!(reviews[1].helpful_votes, None);
+    }
+
+    #[test]
+    fn test_unparsable_reviews_are_a_parse_failure() {
+        // Reviews in markup even the fallbacks don't cover
+        let html = std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
+        let renamed = html
+            .replace(r#"<div class="review""#, r#"<div class="critique""#)
+            .replace("reviews-container", "critiques");
+        let err = parse_reviews_from_html(&renamed, 10).unwrap_err();
+        assert!(is_parse_failure(&err));
+        assert!(err.to_string().contains("review markup may have changed"), "{}", err);
+
+        // Asking for no reviews doesn't hide it
+        assert!(parse_reviews_from_html(&renamed, 0).is_err());
+        // Nor is a reviews section without reviews one
+        let html = r#"<html><body><div class="portlet light reviews">
+            <span class="caption-subject">Reviews</span>
+            <div class="portlet-body reviews-container">No reviews yet.</div>
+            </div></body></html>"#;
+        assert!(parse_reviews_from_html(html, 10).unwrap().is_empty());
+    }
+
     #[test]
     fn test_parse_reviews_empty_html() {
         let html = "<html><body><div>No reviews here</div></body></html>";
@@ -205,15 +595,30 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_reviews_dates_are_iso() {
+    fn test_parse_reviews_dates() {
         let html =
             std::fs::read_to_string(testdata_path("novel_page_90435.html")).unwrap();
         let reviews = parse_reviews_from_html(&html, 100).unwrap();
 
+        // datetime attributes have seven fraction digits and an offset, like
+        // "2025-01-07T10:09:50.0000000+00:00"
         for review in &reviews {
-            // datetime attributes are in ISO format like "2025-01-07T10:09:50.0000000"
-            assert!(review.posted_date.contains("T"), "Date should be ISO format: {}", review.posted_date);
+            assert!(review.posted_date.is_some(), "{} has no date", review.author);
         }
+        let first = reviews[0].posted_date.unwrap();
+        assert_eq!(first, Timestamp::parse("2025-01-07T10:09:50Z").unwrap());
+    }
+
+    #[test]
+    fn test_parse_reviews_keeps_undated_reviews() {
+        let html = reviews_page(&[
+            ("alice", 4.0, "last Tuesday", "No readable date."),
+            ("bob", 4.0, "2024-01-02T00:00:00.0000000", "Dated."),
+        ]);
+        let reviews = parse_reviews_from_html(&html, 10).unwrap();
+        assert_eq!(authors(&reviews), vec!["alice", "bob"]);
+        assert_eq!(reviews[0].posted_date, None);
+        assert_eq!(reviews[1].posted_date.unwrap().to_string(), "2024-01-02T00:00:00Z");
     }
 
     #[test]
@@ -0,0 +1,278 @@
+//! robots.txt rules: which paths a site lets us fetch, and how often.
+//!
+//! Follows RFC 9309: the group naming our product token applies, or else the
+//! `*` group. The longest matching `Allow` or `Disallow` pattern decides,
+//! with `Allow` winning ties; patterns may use `*` and a trailing `$`. The
+//! non-standard `Crawl-delay` is read from the same group, capped at
+//! [`MAX_CRAWL_DELAY`].
+
+use std::time::Duration;
+
+/// The product token robots.txt groups are matched against.
+pub const USER_AGENT_TOKEN: &str = "novel-finder";
+
+/// The longest `Crawl-delay` honoured; a site asking for more gets this,
+/// so one robots.txt can't stall a run indefinitely.
+pub const MAX_CRAWL_DELAY: Duration = Duration::from_secs(60);
+
+/// The rules of one site's robots.txt that apply to us.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Robots {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+/// A group of records under one or more `User-agent` lines.
+#[derive(Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    /// Rules that allow everything, for sites without a usable robots.txt.
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Parse a robots.txt for the crawler named `user_agent`.
+    pub fn parse(text: &str, user_agent: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        // Consecutive User-agent lines share a group; any other record ends
+        // the run, so the next User-agent starts a new group
+        let mut in_agent_lines = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let field = field.trim().to_ascii_lowercase();
+            let value = value.trim();
+            if field == "user-agent" {
+                if !in_agent_lines {
+                    groups.push(Group::default());
+                    in_agent_lines = true;
+                }
+                if let Some(group) = groups.last_mut() {
+                    group.agents.push(value.to_ascii_lowercase());
+                }
+                continue;
+            }
+            in_agent_lines = false;
+            // Records before any User-agent line belong to no group
+            let Some(group) = groups.last_mut() else {
+                continue;
+            };
+            match field.as_str() {
+                "allow" | "disallow" if !value.is_empty() => group.rules.push(Rule {
+                    allow: field == "allow",
+                    pattern: value.to_string(),
+                }),
+                "crawl-delay" => group.crawl_delay = parse_crawl_delay(value),
+                _ => {}
+            }
+        }
+
+        let user_agent = user_agent.to_ascii_lowercase();
+        let named = groups.iter().any(|group| group.agents.contains(&user_agent));
+        let agent = if named { user_agent.as_str() } else { "*" };
+        let mut robots = Self::default();
+        for group in groups.into_iter().filter(|g| g.agents.iter().any(|a| a == agent)) {
+            robots.rules.extend(group.rules);
+            robots.crawl_delay = robots.crawl_delay.max(group.crawl_delay);
+        }
+        robots
+    }
+
+    /// Whether `path` (with any query string) may be fetched.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        // robots.txt itself is always allowed
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    /// The delay the site asks for between requests, if any.
+    pub fn crawl_delay(&self) -> Option<Duration> {
+        self.crawl_delay
+    }
+}
+
+/// A `Crawl-delay` value in seconds, capped at [`MAX_CRAWL_DELAY`]. Values
+/// that aren't a non-negative number are ignored.
+fn parse_crawl_delay(value: &str) -> Option<Duration> {
+    let seconds = value.parse::<f64>().ok()?;
+    // Too large to represent is over the cap too; negative and NaN aren't
+    let delay = match Duration::try_from_secs_f64(seconds) {
+        Ok(delay) => delay,
+        Err(_) if seconds > 0.0 => Duration::MAX,
+        Err(_) => return None,
+    };
+    if delay > MAX_CRAWL_DELAY {
+        tracing::warn!(
+            "robots.txt asks for a crawl delay of {}s; waiting {}s instead",
+            value,
+            MAX_CRAWL_DELAY.as_secs()
+        );
+        return Some(MAX_CRAWL_DELAY);
+    }
+    Some(delay)
+}
+
+/// Whether a robots.txt path pattern matches `path`. Patterns match
+/// prefixes; `*` matches any run of characters and a trailing `$` anchors
+/// the pattern at the end of the path.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROYALROAD_LIKE: &str = "\
+# Comments and blank lines are ignored
+
+User-agent: *
+Disallow: /account/
+Disallow: /fictions/search?*page=   # deep search pages
+Disallow: /*.pdf$
+Allow: /account/login
+Crawl-delay: 2
+
+User-agent: BadBot
+User-agent: OtherBot
+Disallow: /
+";
+
+    #[test]
+    fn test_star_group_rules() {
+        let robots = Robots::parse(ROYALROAD_LIKE, USER_AGENT_TOKEN);
+        assert!(robots.is_allowed("/fiction/90435/bunny-girl-evolution"));
+        assert!(robots.is_allowed("/fictions/similar?fictionId=90435"));
+        assert!(!robots.is_allowed("/account/settings"));
+        assert!(robots.is_allowed("/account/login"));
+        assert!(robots.is_allowed("/fictions/search?title=dungeon"));
+        assert!(!robots.is_allowed("/fictions/search?title=dungeon&page=2"));
+        assert!(!robots.is_allowed("/files/book.pdf"));
+        assert!(robots.is_allowed("/files/book.pdf.html"));
+        assert!(robots.is_allowed("/robots.txt"));
+        assert_eq!(robots.crawl_delay(), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_named_group_replaces_star_group() {
+        let text = "\
+User-agent: *
+Disallow: /
+
+User-agent: Novel-Finder
+Disallow: /profile/
+Crawl-delay: 0.5
+";
+        let robots = Robots::parse(text, USER_AGENT_TOKEN);
+        assert!(robots.is_allowed("/fiction/1"));
+        assert!(!robots.is_allowed("/profile/512699"));
+        assert_eq!(robots.crawl_delay(), Some(Duration::from_millis(500)));
+
+        // Groups listing several agents apply to each of them
+        let robots = Robots::parse(ROYALROAD_LIKE, "otherbot");
+        assert!(!robots.is_allowed("/fiction/1"));
+        assert_eq!(robots.crawl_delay(), None);
+    }
+
+    #[test]
+    fn test_longest_match_wins_and_allow_breaks_ties() {
+        let text = "\
+User-agent: *
+Disallow: /fiction
+Allow: /fiction/
+Disallow: /fiction/1/
+Allow: /fiction/1/
+";
+        let robots = Robots::parse(text, USER_AGENT_TOKEN);
+        assert!(!robots.is_allowed("/fictions/best-rated"));
+        assert!(robots.is_allowed("/fiction/2"));
+        assert!(robots.is_allowed("/fiction/1/slug"));
+    }
+
+    #[test]
+    fn test_empty_and_malformed_files_allow_everything() {
+        for text in ["", "not a robots file", "Disallow: /\n", "User-agent: *\nDisallow:\n"] {
+            let robots = Robots::parse(text, USER_AGENT_TOKEN);
+            assert!(robots.is_allowed("/fiction/1"), "{:?}", text);
+        }
+        for delay in ["soon", "-1", "NaN", "-inf"] {
+            let text = format!("User-agent: *\nCrawl-delay: {}\n", delay);
+            let robots = Robots::parse(&text, USER_AGENT_TOKEN);
+            assert_eq!(robots.crawl_delay(), None, "{:?}", delay);
+        }
+        assert_eq!(Robots::allow_all(), Robots::default());
+    }
+
+    #[test]
+    fn test_crawl_delay_is_capped() {
+        for delay in ["1e30", "inf", "1e9", "61"] {
+            let text = format!("User-agent: *\nCrawl-delay: {}\n", delay);
+            let robots = Robots::parse(&text, USER_AGENT_TOKEN);
+            assert_eq!(robots.crawl_delay(), Some(MAX_CRAWL_DELAY), "{:?}", delay);
+        }
+        let robots = Robots::parse("User-agent: *\nCrawl-delay: 60\n", USER_AGENT_TOKEN);
+        assert_eq!(robots.crawl_delay(), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let cases = [
+            ("/", "/anything", true),
+            ("/fiction", "/fictions", true),
+            ("/fiction$", "/fictions", false),
+            ("/fiction$", "/fiction", true),
+            ("/*/chapter/", "/fiction/1/slug/chapter/2", true),
+            ("/*/chapter/", "/fiction/1", false),
+            ("/*.json$", "/api/data.json", true),
+            ("/*.json$", "/api/data.json?x=1", false),
+            ("*", "/x", true),
+            ("/a*b*c", "/a-b-c-d", true),
+            ("/a*b*c", "/a-c-b", false),
+        ];
+        for (pattern, path, expected) in cases {
+            assert_eq!(pattern_matches(pattern, path), expected, "{} vs {}", pattern, path);
+        }
+    }
+}
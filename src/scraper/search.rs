@@ -1,14 +1,21 @@
-//! Scrape RoyalRoad's advanced search results.
+//! Scrape a site's search results.
 //!
-//! Used to find seed novels when no manual URLs are provided.
+//! Used to find seed novels when no manual URLs are provided, by title or by
+//! the tags every result must have. Results are read a page at a time until
+//! enough are found or the pages run out.
 
-use crate::scraper::RoyalRoadClient;
-use anyhow::Result;
+use crate::models::tags::CanonicalTag;
+use crate::scraper::{fiction_url, HttpFetch};
+use crate::site::Site;
+use anyhow::{Context, Result};
+use scraper::{Html, Selector};
+use std::collections::HashSet;
+use std::fmt;
 
 /// A minimal representation of a novel found in search results.
 #[derive(Debug, Clone)]
 pub struct SearchResult {
-    /// The RoyalRoad fiction ID.
+    /// The site's fiction ID.
     pub id: u64,
     /// Title of the novel.
     pub title: String,
@@ -16,31 +23,275 @@ pub struct SearchResult {
     pub url: String,
 }
 
-/// Search RoyalRoad with the given query and return matching novel IDs.
+/// What a search asks for.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Words the title must contain.
+    pub title: Option<String>,
+    /// Tags every result must have.
+    pub tags: Vec<CanonicalTag>,
+    /// How results are ordered, or the site's default (relevance) if unset.
+    pub order_by: Option<SearchOrder>,
+}
+
+impl fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(title) = &self.title {
+            parts.push(format!("{:?}", title));
+        }
+        if !self.tags.is_empty() {
+            let names: Vec<&str> = self.tags.iter().map(|tag| tag.name).collect();
+            parts.push(format!("tagged {}", names.join(", ")));
+        }
+        if let Some(order_by) = self.order_by {
+            parts.push(format!("by {}", order_by));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// The orders RoyalRoad's search can list results in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchOrder {
+    Popularity,
+    Rating,
+    LastUpdate,
+    ReleaseDate,
+    Followers,
+    Length,
+    Views,
+    Title,
+}
+
+impl SearchOrder {
+    /// Every order, as listed in error messages.
+    pub const ALL: [SearchOrder; 8] = [
+        SearchOrder::Popularity,
+        SearchOrder::Rating,
+        SearchOrder::LastUpdate,
+        SearchOrder::ReleaseDate,
+        SearchOrder::Followers,
+        SearchOrder::Length,
+        SearchOrder::Views,
+        SearchOrder::Title,
+    ];
+
+    /// The order's name in configs and search URLs.
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchOrder::Popularity => "popularity",
+            SearchOrder::Rating => "rating",
+            SearchOrder::LastUpdate => "last_update",
+            SearchOrder::ReleaseDate => "release_date",
+            SearchOrder::Followers => "followers",
+            SearchOrder::Length => "length",
+            SearchOrder::Views => "views",
+            SearchOrder::Title => "title",
+        }
+    }
+
+    /// The order named `name`, if there is one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|order| order.name() == name)
+    }
+}
+
+impl fmt::Display for SearchOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Search `site` and return up to `max_results` matching novels, reading
+/// result pages until enough are found or a page adds none not already seen
+/// (RoyalRoad serves its last page again past the end).
 ///
 /// # Arguments
+/// * `site` - The site to search.
 /// * `client` - The HTTP client to use for requests.
-/// * `query` - The search query string.
+/// * `query` - What to search for.
 /// * `max_results` - Maximum number of results to return.
 ///
 /// # Returns
-/// A list of search results with basic novel info.
+/// A list of search results with basic novel info, in the site's order.
 pub fn search_novels(
-    client: &RoyalRoadClient,
-    query: &str,
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    query: &SearchQuery,
     max_results: usize,
 ) -> Result<Vec<SearchResult>> {
-    let _url = format!(
-        "https://www.royalroad.com/fictions/search?title={}",
-        query
-    );
-    let _ = (client, max_results);
-
-    // TODO: Implement search result scraping
-    // - Build the search URL with proper query parameters
-    // - Fetch and parse search result pages
-    // - Handle pagination if max_results exceeds one page
-    // - Extract fiction IDs, titles, and URLs from result entries
-
-    todo!("Scrape RoyalRoad search results")
+    let mut results = Vec::new();
+    let mut seen = HashSet::new();
+    let mut page = 1;
+    while results.len() < max_results {
+        let url = site
+            .search_url(query, page)
+            .with_context(|| format!("{} can't search for {}", site.id(), query))?;
+        let html = client.fetch(&url)?;
+        let found = results.len();
+        for result in site.parse_search(&html)? {
+            if seen.insert(result.id) {
+                results.push(result);
+            }
+        }
+        tracing::debug!(
+            "Search page {} added {} results",
+            page,
+            results.len() - found
+        );
+        if results.len() == found {
+            break;
+        }
+        page += 1;
+    }
+    results.truncate(max_results);
+    Ok(results)
+}
+
+/// Parse the fictions listed on a RoyalRoad search or tag page.
+pub(crate) fn parse_search_results(html: &str) -> Result<Vec<SearchResult>> {
+    let document = Html::parse_document(html);
+    let item_selector = Selector::parse("div.fiction-list-item").expect("valid selector");
+    let title_selector = Selector::parse("h2.fiction-title a").expect("valid selector");
+
+    let mut results = Vec::new();
+    for item in document.select(&item_selector) {
+        let link = item
+            .select(&title_selector)
+            .next()
+            .context("search result without a title link")?;
+        let href = link.value().attr("href").unwrap_or_default();
+        let Some(parsed) = fiction_url::parse(href) else {
+            tracing::debug!("Skipping search result with unexpected link: {}", href);
+            continue;
+        };
+        results.push(SearchResult {
+            id: parsed.id,
+            title: link.text().collect::<String>().trim().to_string(),
+            url: fiction_url::canonical(parsed.id, parsed.slug.as_deref()),
+        });
+    }
+    Ok(results)
+}
+
+/// Percent-encode a query string value.
+pub(crate) fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::tags::normalize_tag;
+    use crate::scraper::FakeClient;
+    use crate::site::royalroad;
+    use crate::site::RoyalRoad;
+
+    const PAGE_1: &str = include_str!("testdata/search_tags_page1.html");
+    const PAGE_2: &str = include_str!("testdata/search_tags_page2.html");
+
+    fn tag_query(tags: &[&str]) -> SearchQuery {
+        SearchQuery {
+            title: None,
+            tags: tags.iter().map(|tag| normalize_tag(tag).unwrap()).collect(),
+            order_by: Some(SearchOrder::Popularity),
+        }
+    }
+
+    fn page_url(page: usize) -> String {
+        royalroad::search_url(&tag_query(&["litrpg", "kingdom building"]), page)
+    }
+
+    #[test]
+    fn test_parse_search_results() {
+        let results = parse_search_results(PAGE_1).unwrap();
+        let ids: Vec<u64> = results.iter().map(|result| result.id).collect();
+        assert_eq!(ids, [51925, 61302, 48870]);
+        assert_eq!(results[0].title, "The Last Kingdom Builder");
+        assert_eq!(
+            results[0].url,
+            "https://www.royalroad.com/fiction/51925/the-last-kingdom-builder"
+        );
+        assert!(parse_search_results("<html></html>").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tag_search_urls() {
+        assert_eq!(
+            page_url(1),
+            "https://www.royalroad.com/fictions/search\
+             ?tagsAdd=litrpg&tagsAdd=kingdom_building&orderBy=popularity"
+        );
+        assert_eq!(
+            page_url(3),
+            "https://www.royalroad.com/fictions/search\
+             ?tagsAdd=litrpg&tagsAdd=kingdom_building&orderBy=popularity&page=3"
+        );
+        // Aliases search by the canonical slug, genres and warnings included
+        let mut query = tag_query(&["Science Fiction", "Portal Fantasy / Isekai", "gore"]);
+        query.order_by = Some(SearchOrder::LastUpdate);
+        assert_eq!(
+            royalroad::search_url(&query, 1),
+            "https://www.royalroad.com/fictions/search\
+             ?tagsAdd=sci_fi&tagsAdd=summoned_hero&tagsAdd=graphic_violence\
+             &orderBy=last_update"
+        );
+        let query = SearchQuery {
+            title: Some("magic school & more".to_string()),
+            ..SearchQuery::default()
+        };
+        assert_eq!(
+            royalroad::search_url(&query, 1),
+            "https://www.royalroad.com/fictions/search?title=magic+school+%26+more"
+        );
+    }
+
+    #[test]
+    fn test_search_reads_pages_until_enough() {
+        let client = FakeClient::new()
+            .with_page(&page_url(1), PAGE_1)
+            .with_page(&page_url(2), PAGE_2);
+        let query = tag_query(&["litrpg", "kingdom building"]);
+
+        let results = search_novels(&RoyalRoad, &client, &query, 2).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(client.request_count(), 1);
+
+        let results = search_novels(&RoyalRoad, &client, &query, 4).unwrap();
+        let ids: Vec<u64> = results.iter().map(|result| result.id).collect();
+        assert_eq!(ids, [51925, 61302, 48870, 70114]);
+        assert_eq!(client.request_count(), 3);
+    }
+
+    #[test]
+    fn test_search_stops_when_pages_repeat() {
+        // Past the last page RoyalRoad serves the last page again
+        let client = FakeClient::new()
+            .with_page(&page_url(1), PAGE_1)
+            .with_page(&page_url(2), PAGE_2)
+            .with_page(&page_url(3), PAGE_2);
+        let query = tag_query(&["litrpg", "kingdom building"]);
+
+        let results = search_novels(&RoyalRoad, &client, &query, 50).unwrap();
+        assert_eq!(results.len(), 5);
+        assert_eq!(client.request_count(), 3);
+    }
+
+    #[test]
+    fn test_encode_query_value() {
+        assert_eq!(encode_query_value("Lit RPG"), "Lit+RPG");
+        assert_eq!(encode_query_value("a/b?c=d"), "a%2Fb%3Fc%3Dd");
+        assert_eq!(encode_query_value("café"), "caf%C3%A9");
+    }
 }
@@ -0,0 +1,161 @@
+//! The fiction sites novels are found on.
+//!
+//! A [`Site`] knows a site's addresses and how to read its pages: where a
+//! fiction lives, how to parse it and its reviews, and where its
+//! recommendations and search results come from. The pipeline, discovery,
+//! and seed gathering go through the configured site rather than naming one,
+//! so a site only needs an implementation here: [`RoyalRoad`] and
+//! [`ScribbleHub`] so far. Each run scrapes one site, picked by the config's
+//! `site`.
+
+pub mod royalroad;
+pub mod scribblehub;
+
+use crate::models::{ChapterTitleStorage, Novel, Review, SiteId};
+use crate::scraper::novel_page::{ParsedPage, SimilarFiction};
+use crate::scraper::search::{SearchQuery, SearchResult};
+use crate::scraper::HttpFetch;
+use anyhow::Result;
+use std::sync::Arc;
+
+pub use royalroad::RoyalRoad;
+pub use scribblehub::ScribbleHub;
+
+/// A fiction site's addresses and page parsers.
+pub trait Site: Send + Sync {
+    /// Which site this is, as novels scraped from it record.
+    fn id(&self) -> SiteId;
+
+    /// The page of the fiction with `id`.
+    fn fiction_url(&self, id: u64) -> String;
+
+    /// The fiction ID in a link to one of the site's fictions (or its
+    /// chapters), if it is one.
+    fn fiction_id(&self, url: &str) -> Option<u64>;
+
+    /// Parse a fiction's page into the novel and up to `max_reviews` of its
+    /// reviews, keeping the chapter titles `chapters` asks for.
+    fn parse_novel(
+        &self,
+        html: &str,
+        id: u64,
+        max_reviews: usize,
+        chapters: ChapterTitleStorage,
+    ) -> Result<ParsedPage>;
+
+    /// Update a just parsed novel from the URL its page was finally served
+    /// at, after any redirects. Does nothing unless the site needs it.
+    fn served_at(&self, _novel: &mut Novel, _url: &str) {}
+
+    /// Parse up to `max_reviews` reviews from a fiction's page.
+    fn parse_reviews(&self, html: &str, max_reviews: usize) -> Result<Vec<Review>>;
+
+    /// The endpoint listing fictions similar to the one with `id`, if the
+    /// site has one. Without one, recommendations come from the fiction's
+    /// page alone.
+    fn similar_url(&self, id: u64) -> Option<String>;
+
+    /// Parse the response of [`similar_url`](Site::similar_url).
+    fn parse_similar(&self, body: &str) -> Result<Vec<SimilarFiction>>;
+
+    /// Page `page` (from 1) of the search for fictions matching `query`, if
+    /// the site can search for everything it asks.
+    fn search_url(&self, query: &SearchQuery, page: usize) -> Option<String>;
+
+    /// Parse the fictions listed on a page of search results.
+    fn parse_search(&self, html: &str) -> Result<Vec<SearchResult>>;
+
+    /// Parse a chapter's text, without author notes, from its page.
+    fn parse_chapter(&self, html: &str) -> Result<String>;
+}
+
+impl SiteId {
+    /// The site's implementation.
+    pub fn site(self) -> Arc<dyn Site> {
+        match self {
+            SiteId::RoyalRoad => Arc::new(RoyalRoad),
+            SiteId::ScribbleHub => Arc::new(ScribbleHub),
+        }
+    }
+}
+
+/// Scrape a novel's details and up to `max_reviews` of its reviews with a
+/// single fetch of its page on `site`.
+///
+/// The returned novel has no reviews attached; they are returned alongside.
+#[tracing::instrument(skip(site, client), fields(phase = "scrape"))]
+pub fn scrape_novel_with_reviews(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    novel_id: u64,
+    max_reviews: usize,
+    chapters: ChapterTitleStorage,
+) -> Result<(Novel, Vec<Review>)> {
+    let page = client.fetch_page(&site.fiction_url(novel_id))?;
+    let ParsedPage { mut novel, reviews } =
+        site.parse_novel(&page.body, novel_id, max_reviews, chapters)?;
+    site.served_at(&mut novel, &page.url);
+    Ok((novel, reviews))
+}
+
+/// Scrape a novel's full details from its page on `site`, with up to
+/// [`MAX_REVIEWS`](crate::scraper::reviews::MAX_REVIEWS) reviews from the
+/// same page attached so evaluation needn't fetch the page again.
+pub fn scrape_novel(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    novel_id: u64,
+    chapters: ChapterTitleStorage,
+) -> Result<Novel> {
+    let max_reviews = crate::scraper::reviews::MAX_REVIEWS;
+    let (mut novel, reviews) =
+        scrape_novel_with_reviews(site, client, novel_id, max_reviews, chapters)?;
+    novel.reviews = Some(reviews);
+    Ok(novel)
+}
+
+/// Scrape up to `max_reviews` of a novel's reviews from its page on `site`.
+#[tracing::instrument(skip(site, client), fields(phase = "scrape"))]
+pub fn scrape_reviews(
+    site: &dyn Site,
+    client: &dyn HttpFetch,
+    novel_id: u64,
+    max_reviews: usize,
+) -> Result<Vec<Review>> {
+    let html = client.fetch(&site.fiction_url(novel_id))?;
+    site.parse_reviews(&html, max_reviews)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sites_by_name() {
+        assert_eq!(SiteId::from_name("royalroad"), Some(SiteId::RoyalRoad));
+        assert_eq!(SiteId::from_name("scribblehub"), Some(SiteId::ScribbleHub));
+        assert_eq!(SiteId::from_name("wattpad"), None);
+        assert_eq!(SiteId::default().to_string(), "royalroad");
+        for id in SiteId::ALL {
+            assert_eq!(id.site().id(), id);
+        }
+    }
+
+    #[test]
+    fn test_royalroad_addresses() {
+        let site = SiteId::RoyalRoad.site();
+        assert_eq!(site.fiction_url(7), "https://www.royalroad.com/fiction/7");
+        assert_eq!(
+            site.fiction_id("https://www.royalroad.com/fiction/7/slug"),
+            Some(7)
+        );
+        assert_eq!(
+            site.fiction_id("https://www.royalroad.com/fictions/best-rated"),
+            None
+        );
+        assert_eq!(
+            site.similar_url(7).as_deref(),
+            Some("https://www.royalroad.com/fictions/similar?fictionId=7")
+        );
+    }
+}
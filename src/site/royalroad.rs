@@ -0,0 +1,121 @@
+//! RoyalRoad, the site novel-finder was built for.
+//!
+//! Every RoyalRoad address lives here: the origin, the page and endpoint
+//! paths, and the routes limited as lightweight API requests. The parsing
+//! itself is the [`scraper`](crate::scraper) module's, which [`RoyalRoad`]
+//! wraps.
+
+use crate::models::{ChapterTitleStorage, Novel, Review, SiteId};
+use crate::scraper::chapter::parse_chapter_content;
+use crate::scraper::fiction_url;
+use crate::scraper::novel_page::{
+    parse_page, parse_similar_fictions_from_json, ParsedPage, SimilarFiction,
+};
+use crate::scraper::reviews::parse_reviews_from_html;
+use crate::scraper::search::{
+    encode_query_value, parse_search_results, SearchQuery, SearchResult,
+};
+use crate::site::Site;
+use anyhow::Result;
+
+/// The origin every RoyalRoad page is served from, and that relative links
+/// like chapter paths are resolved against.
+pub const ORIGIN: &str = "https://www.royalroad.com";
+
+/// Paths of the JSON endpoints limited as API requests rather than pages.
+pub const API_PATHS: [&str; 2] = ["/fictions/similar", "/api/"];
+
+/// The page of the fiction with `id`, which redirects to its current slug.
+pub fn fiction_page_url(id: u64) -> String {
+    format!("{}/fiction/{}", ORIGIN, id)
+}
+
+/// The similar fictions API endpoint behind "Others Also Liked".
+pub fn similar_url(id: u64) -> String {
+    format!("{}/fictions/similar?fictionId={}", ORIGIN, id)
+}
+
+/// Page `page` (from 1) of the advanced search for `query`. Tags are
+/// searched by their slugs, every one required.
+pub fn search_url(query: &SearchQuery, page: usize) -> String {
+    let mut params = Vec::new();
+    if let Some(title) = &query.title {
+        params.push(format!("title={}", encode_query_value(title)));
+    }
+    for tag in &query.tags {
+        params.push(format!("tagsAdd={}", tag.slug));
+    }
+    if let Some(order_by) = query.order_by {
+        params.push(format!("orderBy={}", order_by.name()));
+    }
+    if page > 1 {
+        params.push(format!("page={}", page));
+    }
+    format!("{}/fictions/search?{}", ORIGIN, params.join("&"))
+}
+
+/// The list of fictions an author has published.
+pub fn author_fictions_url(author_id: u64) -> String {
+    format!("{}/profile/{}/fictions", ORIGIN, author_id)
+}
+
+/// RoyalRoad as a [`Site`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoyalRoad;
+
+impl Site for RoyalRoad {
+    fn id(&self) -> SiteId {
+        SiteId::RoyalRoad
+    }
+
+    fn fiction_url(&self, id: u64) -> String {
+        fiction_page_url(id)
+    }
+
+    fn fiction_id(&self, url: &str) -> Option<u64> {
+        fiction_url::parse(url).map(|parsed| parsed.id)
+    }
+
+    fn parse_novel(
+        &self,
+        html: &str,
+        id: u64,
+        max_reviews: usize,
+        chapters: ChapterTitleStorage,
+    ) -> Result<ParsedPage> {
+        parse_page(html, id, max_reviews, chapters)
+    }
+
+    /// The URL a fiction page redirects to carries its current slug.
+    fn served_at(&self, novel: &mut Novel, url: &str) {
+        let redirected = fiction_url::parse(url).filter(|parsed| parsed.id == novel.id);
+        if let Some(slug) = redirected.and_then(|parsed| parsed.slug) {
+            novel.url = fiction_url::canonical(novel.id, Some(&slug));
+            novel.slug = Some(slug);
+        }
+    }
+
+    fn parse_reviews(&self, html: &str, max_reviews: usize) -> Result<Vec<Review>> {
+        parse_reviews_from_html(html, max_reviews)
+    }
+
+    fn similar_url(&self, id: u64) -> Option<String> {
+        Some(similar_url(id))
+    }
+
+    fn parse_similar(&self, body: &str) -> Result<Vec<SimilarFiction>> {
+        parse_similar_fictions_from_json(body)
+    }
+
+    fn search_url(&self, query: &SearchQuery, page: usize) -> Option<String> {
+        Some(search_url(query, page))
+    }
+
+    fn parse_search(&self, html: &str) -> Result<Vec<SearchResult>> {
+        parse_search_results(html)
+    }
+
+    fn parse_chapter(&self, html: &str) -> Result<String> {
+        parse_chapter_content(html)
+    }
+}
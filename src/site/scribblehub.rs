@@ -0,0 +1,769 @@
+//! ScribbleHub, a site for original web novels and fan fiction.
+//!
+//! Series live at `/series/{id}/{slug}/` and their chapters at
+//! `/read/{id}-{slug}/chapter/{chapter id}/`. A series page has most of what
+//! a novel needs, with a few differences from RoyalRoad:
+//!
+//! - There's no page count, so page limits don't apply to its novels.
+//! - The table of contents shows only a page of the latest chapters, so
+//!   those are the chapter titles kept, while `chapter_count` comes from the
+//!   stats. The first chapter's date is known only when it's listed.
+//! - Readers (who have the series in their reading list) stand in for
+//!   followers.
+//! - Recommendations are the "Similar Series" sidebar; there's no endpoint
+//!   for them.
+//! - Authors have no profile lookup, so their reputation isn't scored.
+//! - Search isn't supported, so search and tag seeds need RoyalRoad.
+
+use crate::models::tags::tag_key;
+use crate::models::timestamp::Timestamp;
+use crate::models::{ChapterTitleStorage, Novel, NovelStatus, Review, SiteId};
+use crate::scraper::novel_page::{ParsedPage, SimilarFiction};
+use crate::scraper::reviews::dedup_reviews;
+use crate::scraper::search::{SearchQuery, SearchResult};
+use crate::site::Site;
+use anyhow::{Context, Result};
+use scraper::{ElementRef, Html, Selector};
+
+/// The origin every ScribbleHub page is served from.
+pub const ORIGIN: &str = "https://www.scribblehub.com";
+
+/// The page of the series with `id`.
+pub fn series_url(id: u64) -> String {
+    format!("{}/series/{}/", ORIGIN, id)
+}
+
+/// Read the series ID from a series or chapter URL, absolute or relative.
+/// Returns `None` if the URL has neither a `/series/{id}` nor a
+/// `/read/{id}-{slug}` path.
+pub fn parse_series_id(url: &str) -> Option<u64> {
+    let path = url.trim().split(['?', '#']).next().unwrap_or_default();
+    let segments: Vec<&str> = path.split('/').collect();
+    let position = segments
+        .iter()
+        .position(|segment| *segment == "series" || *segment == "read")?;
+    let id = match segments[position] {
+        "series" => segments.get(position + 1)?,
+        _ => segments.get(position + 1)?.split('-').next()?,
+    };
+    id.parse().ok()
+}
+
+/// The title slug of a series URL, if it has one.
+fn parse_series_slug(url: &str) -> Option<String> {
+    let path = url.trim().split(['?', '#']).next().unwrap_or_default();
+    let mut segments = path.split('/').skip_while(|segment| *segment != "series");
+    segments
+        .nth(2)
+        .filter(|slug| !slug.is_empty())
+        .map(String::from)
+}
+
+/// ScribbleHub as a [`Site`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScribbleHub;
+
+impl Site for ScribbleHub {
+    fn id(&self) -> SiteId {
+        SiteId::ScribbleHub
+    }
+
+    fn fiction_url(&self, id: u64) -> String {
+        series_url(id)
+    }
+
+    fn fiction_id(&self, url: &str) -> Option<u64> {
+        parse_series_id(url)
+    }
+
+    fn parse_novel(
+        &self,
+        html: &str,
+        id: u64,
+        max_reviews: usize,
+        chapters: ChapterTitleStorage,
+    ) -> Result<ParsedPage> {
+        parse_page(html, id, max_reviews, chapters)
+    }
+
+    fn parse_reviews(&self, html: &str, max_reviews: usize) -> Result<Vec<Review>> {
+        Ok(parse_reviews_from_document(
+            &Html::parse_document(html),
+            max_reviews,
+        ))
+    }
+
+    fn similar_url(&self, _id: u64) -> Option<String> {
+        None
+    }
+
+    fn parse_similar(&self, _body: &str) -> Result<Vec<SimilarFiction>> {
+        anyhow::bail!("ScribbleHub has no similar series endpoint")
+    }
+
+    /// Search results aren't read from ScribbleHub yet.
+    fn search_url(&self, _query: &SearchQuery, _page: usize) -> Option<String> {
+        None
+    }
+
+    fn parse_search(&self, _html: &str) -> Result<Vec<SearchResult>> {
+        anyhow::bail!("ScribbleHub search results can't be read yet")
+    }
+
+    fn parse_chapter(&self, html: &str) -> Result<String> {
+        parse_chapter_content(html)
+    }
+}
+
+/// Parse both the metadata and the reviews from the raw HTML of a series
+/// page, parsing the document only once.
+pub fn parse_page(
+    html: &str,
+    series_id: u64,
+    max_reviews: usize,
+    chapters: ChapterTitleStorage,
+) -> Result<ParsedPage> {
+    let document = Html::parse_document(html);
+    Ok(ParsedPage {
+        novel: parse_novel_from_document(&document, series_id, chapters)?,
+        reviews: parse_reviews_from_document(&document, max_reviews),
+    })
+}
+
+/// Parse a novel's metadata from its already parsed series page. Only the
+/// chapter titles `storage` asks for are kept.
+fn parse_novel_from_document(
+    document: &Html,
+    series_id: u64,
+    storage: ChapterTitleStorage,
+) -> Result<Novel> {
+    let title = select_text(document, "div.fic_title").context("missing series title")?;
+    let author = select_text(document, "span.auth_name_fic").context("missing author name")?;
+    let description = extract_description(document).unwrap_or_default();
+    let rating = select_text(document, "span[property='ratingValue']")
+        .context("missing rating")?
+        .parse::<f64>()
+        .context("unreadable rating")?;
+
+    let genres = select_texts(document, "a.fic_genre");
+    let mut tags = genres.clone();
+    tags.extend(select_texts(document, "a.stag"));
+    let is_fanfiction = genres.iter().any(|genre| tag_key(genre) == "fanfiction");
+
+    let status = extract_status(document)?;
+    let stats = extract_stats(document)?;
+
+    let chapters = extract_chapters(document);
+    let chapter_count = stats.chapters.max(chapters.last_order);
+    let first_chapter_date = chapters.first_chapter_date;
+    let last_chapter_date = chapters.dates.iter().flatten().max().copied();
+
+    let canonical = select_attr(document, "link[rel='canonical']", "href")
+        .filter(|href| parse_series_id(href) == Some(series_id));
+    let slug = canonical.as_deref().and_then(parse_series_slug);
+    let url = match &slug {
+        Some(slug) => format!("{}{}/", series_url(series_id), slug),
+        None => series_url(series_id),
+    };
+
+    let mut novel = Novel {
+        site: SiteId::ScribbleHub,
+        id: series_id,
+        title,
+        author,
+        author_id: None,
+        author_reputation: None,
+        url,
+        slug,
+        cover_url: extract_cover_url(document),
+        description,
+        pages: 0,
+        rating,
+        status,
+        genres,
+        tags,
+        chapter_count,
+        chapter_titles: chapters.titles,
+        chapter_urls: chapters.urls,
+        first_chapter_date,
+        last_chapter_date,
+        followers: stats.readers,
+        favorites: stats.favorites,
+        total_views: stats.views,
+        average_views: None,
+        is_fanfiction,
+        ai_content: None,
+        badges: Vec::new(),
+        word_count_estimate: None,
+        first_chapter_excerpt: None,
+        reviews: None,
+        also_liked: extract_similar_series(document, series_id),
+        reviews_unparsed: false,
+    };
+    novel.trim_chapters(storage);
+    Ok(novel)
+}
+
+/// The trimmed text of the first element matching `selector`, if it has any.
+fn select_text(document: &Html, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).expect("valid selector");
+    let text = document
+        .select(&selector)
+        .next()?
+        .text()
+        .collect::<String>();
+    Some(text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+/// The trimmed, non-empty texts of every element matching `selector`.
+fn select_texts(document: &Html, selector: &str) -> Vec<String> {
+    let selector = Selector::parse(selector).expect("valid selector");
+    document
+        .select(&selector)
+        .map(|el| el.text().collect::<String>().trim().to_string())
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+/// An attribute of the first element matching `selector`.
+fn select_attr(document: &Html, selector: &str, attr: &str) -> Option<String> {
+    let selector = Selector::parse(selector).expect("valid selector");
+    let value = document.select(&selector).next()?.value().attr(attr)?;
+    Some(value.to_string())
+}
+
+/// The description, one paragraph per line group.
+fn extract_description(document: &Html) -> Option<String> {
+    let description = Selector::parse("div.wi_fic_desc").expect("valid selector");
+    let paragraph = Selector::parse("p").expect("valid selector");
+    let element = document.select(&description).next()?;
+    let paragraphs: Vec<String> = element
+        .select(&paragraph)
+        .map(|p| collapse_whitespace(&p.text().collect::<String>()))
+        .filter(|text| !text.is_empty())
+        .collect();
+    if paragraphs.is_empty() {
+        return Some(collapse_whitespace(&element.text().collect::<String>()));
+    }
+    Some(paragraphs.join("\n\n"))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Extract the publication status from the series info sidebar. A status
+/// ScribbleHub has introduced since is kept as [`NovelStatus::Unknown`].
+fn extract_status(document: &Html) -> Result<NovelStatus> {
+    let status = select_text(document, "ul.widget_fic_similar span.rnd_stats")
+        .context("could not find series status")?;
+    match status.to_lowercase().as_str() {
+        "ongoing" => Ok(NovelStatus::Ongoing),
+        "completed" => Ok(NovelStatus::Completed),
+        "hiatus" => Ok(NovelStatus::Hiatus),
+        "dropped" => Ok(NovelStatus::Dropped),
+        _ => {
+            tracing::warn!("Unrecognized series status: {:?}", status);
+            Ok(NovelStatus::Unknown(status))
+        }
+    }
+}
+
+/// The cover image URL, unless it's ScribbleHub's "no image" placeholder.
+fn extract_cover_url(document: &Html) -> Option<String> {
+    let src = select_attr(document, "div.fic_image img", "src")?;
+    Some(src).filter(|src| !src.contains("noimage"))
+}
+
+/// Counts read from the stats row under the series title.
+struct SeriesStats {
+    views: Option<u64>,
+    favorites: u64,
+    readers: u64,
+    chapters: u64,
+}
+
+/// Extract the counts from the stats row, where each item reads like
+/// "4,312 Favorites" or "1.25M Views".
+///
+/// Favorites, readers, and chapters are required; views are optional.
+fn extract_stats(document: &Html) -> Result<SeriesStats> {
+    let mut views = None;
+    let mut favorites = None;
+    let mut readers = None;
+    let mut chapters = None;
+    for item in select_texts(document, "div.fic_stats span.st_item") {
+        let Some((count, label)) = item.split_once(' ') else {
+            continue;
+        };
+        let slot = match label.trim() {
+            "Views" => &mut views,
+            "Favorites" => &mut favorites,
+            "Readers" => &mut readers,
+            "Chapters" => &mut chapters,
+            _ => continue,
+        };
+        *slot = Some(parse_count(count)?);
+    }
+    Ok(SeriesStats {
+        views,
+        favorites: favorites.context("could not find favorites count")?,
+        readers: readers.context("could not find readers count")?,
+        chapters: chapters.context("could not find chapter count")?,
+    })
+}
+
+/// Parse a count that may have thousands separators ("4,312") or be
+/// abbreviated ("9.8k", "1.25M").
+fn parse_count(s: &str) -> Result<u64> {
+    let trimmed = s.trim().replace(',', "");
+    let (number, scale) = match trimmed.char_indices().last() {
+        Some((at, 'k' | 'K')) => (&trimmed[..at], 1e3),
+        Some((at, 'm' | 'M')) => (&trimmed[..at], 1e6),
+        _ => (trimmed.as_str(), 1.0),
+    };
+    let well_formed = number.starts_with(|c: char| c.is_ascii_digit())
+        && number.chars().all(|c| c.is_ascii_digit() || c == '.');
+    anyhow::ensure!(well_formed, "failed to parse count: '{}'", s);
+    let value: f64 = number
+        .parse()
+        .with_context(|| format!("failed to parse count: '{}'", s))?;
+    Ok((value * scale).round() as u64)
+}
+
+/// The chapters listed in the table of contents, in reading order.
+#[derive(Debug, Default)]
+struct ChapterList {
+    titles: Vec<String>,
+    urls: Vec<String>,
+    /// Publish times, `None` where a chapter has no readable date.
+    dates: Vec<Option<Timestamp>>,
+    /// The publish time of chapter 1, if it's listed.
+    first_chapter_date: Option<Timestamp>,
+    /// The highest chapter number listed.
+    last_order: u64,
+}
+
+/// Extract the chapters from the table of contents, which lists the latest
+/// first.
+fn extract_chapters(document: &Html) -> ChapterList {
+    let item_selector = Selector::parse("ol.toc_ol li.toc_w").expect("valid selector");
+    let link_selector = Selector::parse("a.toc_a").expect("valid selector");
+    let date_selector = Selector::parse("span.fic_date_pub").expect("valid selector");
+
+    let mut listed: Vec<(u64, String, String, Option<Timestamp>)> = Vec::new();
+    for item in document.select(&item_selector) {
+        let Some(link) = item.select(&link_selector).next() else {
+            continue;
+        };
+        let order = item
+            .value()
+            .attr("order")
+            .and_then(|order| order.parse().ok());
+        let title = link.text().collect::<String>().trim().to_string();
+        let url = link.value().attr("href").unwrap_or_default().to_string();
+        let date = item
+            .select(&date_selector)
+            .next()
+            .and_then(|span| span.value().attr("title"))
+            .and_then(parse_listed_time);
+        listed.push((order.unwrap_or_default(), title, url, date));
+    }
+    listed.sort_by_key(|(order, ..)| *order);
+
+    let mut chapters = ChapterList {
+        first_chapter_date: listed
+            .iter()
+            .find(|(order, ..)| *order == 1)
+            .and_then(|(.., date)| *date),
+        last_order: listed.last().map_or(0, |(order, ..)| *order),
+        ..ChapterList::default()
+    };
+    for (_, title, url, date) in listed {
+        chapters.titles.push(title);
+        chapters.urls.push(url);
+        chapters.dates.push(date);
+    }
+    chapters
+}
+
+/// Month abbreviations as ScribbleHub writes them.
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Parse a time as ScribbleHub lists it, like "Mar 13, 2024 06:00 PM",
+/// taken as UTC. The time of day may be left out.
+fn parse_listed_time(s: &str) -> Option<Timestamp> {
+    let mut parts = s.split_whitespace();
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|name| *name == month)? + 1;
+    let day: u32 = parts.next()?.strip_suffix(',')?.parse().ok()?;
+    let year: u32 = parts.next()?.parse().ok()?;
+    let (hour, minute) = match parts.next() {
+        Some(clock) => {
+            let (hour, minute) = clock.split_once(':')?;
+            (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?)
+        }
+        None => (0, 0),
+    };
+    let hour = match parts.next() {
+        Some("AM") if (1..=12).contains(&hour) => hour % 12,
+        Some("PM") if (1..=12).contains(&hour) => hour % 12 + 12,
+        None => hour,
+        _ => return None,
+    };
+    Timestamp::parse(&format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:00Z",
+        year, month, day, hour, minute
+    ))
+}
+
+/// The series IDs in the "Similar Series" sidebar, in the order shown and
+/// without this series. `None` if the page has no such sidebar.
+fn extract_similar_series(document: &Html, series_id: u64) -> Option<Vec<u64>> {
+    let box_selector = Selector::parse("div.sb_box").expect("valid selector");
+    let title_selector = Selector::parse("div.sb_box_title").expect("valid selector");
+    let link_selector = Selector::parse("a[href]").expect("valid selector");
+
+    let sidebar = document.select(&box_selector).find(|sb_box| {
+        sb_box.select(&title_selector).next().is_some_and(|title| {
+            let title = title.text().collect::<String>().to_lowercase();
+            title.contains("similar series")
+        })
+    })?;
+    let mut ids = Vec::new();
+    for link in sidebar.select(&link_selector) {
+        // Cover images and titles both link to each series
+        let id = parse_series_id(link.value().attr("href").unwrap_or_default());
+        if let Some(id) = id.filter(|id| *id != series_id && !ids.contains(id)) {
+            ids.push(id);
+        }
+    }
+    Some(ids)
+}
+
+/// Parse up to `max_reviews` reviews from an already parsed series page,
+/// dropping repeats like RoyalRoad's are. Reviews without a rating are
+/// comments and are left out.
+fn parse_reviews_from_document(document: &Html, max_reviews: usize) -> Vec<Review> {
+    let review_selector = Selector::parse("div.w-comments-item.review").expect("valid selector");
+    let reviews = document
+        .select(&review_selector)
+        .filter_map(|review| {
+            Some(Review {
+                author: element_text(&review, "a.w-comments-item-author")?,
+                rating: extract_review_rating(&review)?,
+                text: element_text(&review, "div.w-comments-item-text")?,
+                posted_date: element_attr(&review, "span.w-comments-item-date", "title")
+                    .and_then(parse_listed_time),
+                helpful_votes: extract_review_helpful_votes(&review),
+            })
+        })
+        .collect();
+    let mut reviews = dedup_reviews(reviews);
+    reviews.truncate(max_reviews);
+    reviews
+}
+
+/// The whitespace-collapsed text of the first element under `parent`
+/// matching `selector`, if it has any.
+fn element_text(parent: &ElementRef, selector: &str) -> Option<String> {
+    let selector = Selector::parse(selector).expect("valid selector");
+    let text = collapse_whitespace(&parent.select(&selector).next()?.text().collect::<String>());
+    Some(text).filter(|text| !text.is_empty())
+}
+
+/// An attribute of the first element under `parent` matching `selector`.
+fn element_attr<'a>(parent: &ElementRef<'a>, selector: &str, attr: &str) -> Option<&'a str> {
+    let selector = Selector::parse(selector).expect("valid selector");
+    parent.select(&selector).next()?.value().attr(attr)
+}
+
+/// The star rating, from a title like "4.5 out of 5".
+fn extract_review_rating(review: &ElementRef) -> Option<f64> {
+    let title = element_attr(review, "div.review_stars", "title")?;
+    title.split_whitespace().next()?.parse().ok()
+}
+
+/// How many readers found the review helpful, from "Helpful (12)".
+fn extract_review_helpful_votes(review: &ElementRef) -> Option<u64> {
+    let text = element_text(review, "span.review_helpful")?;
+    let count = text.split_once('(')?.1.strip_suffix(')')?;
+    count.replace(',', "").parse().ok()
+}
+
+/// Parse the chapter text from the raw HTML of a chapter page, leaving out
+/// the author's notes inside it.
+pub(crate) fn parse_chapter_content(html: &str) -> Result<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("div#chp_raw").expect("valid selector");
+    let content = document
+        .select(&selector)
+        .next()
+        .context("no chapter content found in page")?;
+
+    let mut text = String::new();
+    for node in content.descendants() {
+        let Some(fragment) = node.value().as_text() else {
+            continue;
+        };
+        let in_note = node.ancestors().any(|ancestor| {
+            ancestor
+                .value()
+                .as_element()
+                .is_some_and(|el| el.classes().any(|class| class == "wi_authornotes"))
+        });
+        if !in_note {
+            text.push_str(fragment);
+        }
+    }
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::novel_page::scrape_also_liked_from;
+    use crate::scraper::FakeClient;
+    use std::path::PathBuf;
+
+    fn fixture(filename: &str) -> String {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src");
+        path.push("site");
+        path.push("testdata");
+        path.push(filename);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    fn parse(filename: &str, id: u64) -> ParsedPage {
+        parse_page(&fixture(filename), id, 10, ChapterTitleStorage::All).unwrap()
+    }
+
+    #[test]
+    fn test_parse_series_page() {
+        let novel = parse("scribblehub_series_299262.html", 299262).novel;
+
+        assert_eq!(novel.site, SiteId::ScribbleHub);
+        assert_eq!(novel.title, "The Tower Climber's Apprentice");
+        assert_eq!(novel.author, "Ashgrove");
+        assert_eq!(novel.author_id, None);
+        assert_eq!(
+            novel.url,
+            "https://www.scribblehub.com/series/299262/the-tower-climbers-apprentice/"
+        );
+        assert_eq!(novel.slug.as_deref(), Some("the-tower-climbers-apprentice"));
+        assert!(novel.cover_url.as_ref().unwrap().contains("_299262_"));
+        assert!(novel.description.starts_with("Every spire in Halden"));
+        assert!(novel
+            .description
+            .contains("being sold to one.\n\nRelease schedule"));
+        assert_eq!(novel.rating, 4.6);
+        assert_eq!(novel.status, NovelStatus::Ongoing);
+        assert_eq!(novel.genres, ["Action", "Adventure", "Fantasy"]);
+        assert_eq!(
+            novel.tags,
+            [
+                "Action",
+                "Adventure",
+                "Fantasy",
+                "Academy",
+                "Dungeons",
+                "Weak to Strong"
+            ]
+        );
+        assert!(!novel.is_fanfiction);
+        assert_eq!(novel.pages, 0);
+        assert_eq!(novel.pages_per_chapter(), None);
+
+        assert_eq!(novel.total_views, Some(1_250_000));
+        assert_eq!(novel.favorites, 4312);
+        assert_eq!(novel.followers, 9800);
+    }
+
+    #[test]
+    fn test_parse_series_chapters() {
+        let novel = parse("scribblehub_series_299262.html", 299262).novel;
+        // Only the latest chapters are listed; the count comes from the stats
+        assert_eq!(novel.chapter_count, 212);
+        assert_eq!(
+            novel.chapter_titles,
+            [
+                "Chapter 210 - Ledger",
+                "Chapter 211 - Debts",
+                "Chapter 212 - The Thirteenth Floor"
+            ]
+        );
+        assert!(novel.chapter_urls[0].ends_with("/chapter/1157233/"));
+        assert_eq!(novel.first_chapter_date, None);
+        assert_eq!(
+            novel.last_chapter_date,
+            Timestamp::parse("2024-03-13T18:00:00Z")
+        );
+
+        let novel = parse("scribblehub_series_187402.html", 187402).novel;
+        assert_eq!(novel.chapter_count, 3);
+        assert_eq!(
+            novel.chapter_titles,
+            ["Ground", "The Stairwell", "Epilogue"]
+        );
+        assert_eq!(
+            novel.first_chapter_date,
+            Timestamp::parse("2022-07-18T00:15:00Z")
+        );
+
+        let html = fixture("scribblehub_series_187402.html");
+        let none = parse_page(&html, 187402, 10, ChapterTitleStorage::None).unwrap();
+        assert!(none.novel.chapter_titles.is_empty());
+        assert_eq!(none.novel.chapter_count, 3);
+    }
+
+    #[test]
+    fn test_parse_fanfiction_series() {
+        let novel = parse("scribblehub_series_187402.html", 187402).novel;
+        assert_eq!(novel.status, NovelStatus::Completed);
+        assert!(novel.is_fanfiction);
+        assert_eq!(
+            novel.description,
+            "A quiet story about the floor nobody climbs."
+        );
+        // The placeholder isn't a cover
+        assert_eq!(novel.cover_url, None);
+        assert_eq!(novel.followers, 1004);
+        assert_eq!(novel.total_views, Some(48_210));
+        assert_eq!(novel.also_liked, None);
+    }
+
+    #[test]
+    fn test_parse_series_missing_fields() {
+        let html = fixture("scribblehub_series_187402.html");
+        let untitled = html.replace("class=\"fic_title\"", "class=\"other\"");
+        let err = parse_page(&untitled, 187402, 10, ChapterTitleStorage::All).unwrap_err();
+        assert!(err.to_string().contains("title"), "{:#}", err);
+
+        let no_readers = html.replace("Readers", "Lurkers");
+        let err = parse_page(&no_readers, 187402, 10, ChapterTitleStorage::All).unwrap_err();
+        assert!(err.to_string().contains("readers"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_parse_series_reviews() {
+        let reviews = parse("scribblehub_series_299262.html", 299262).reviews;
+        // The unrated comment isn't a review
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews[0].author, "Brindle");
+        assert_eq!(reviews[0].rating, 4.5);
+        assert_eq!(
+            reviews[0].text,
+            "The tower floors are inventive and the debt system gives every climb real \
+             stakes. Pacing drags in the middle arc."
+        );
+        assert_eq!(
+            reviews[0].posted_date,
+            Timestamp::parse("2024-02-20T11:05:00Z")
+        );
+        assert_eq!(reviews[0].helpful_votes, Some(12));
+        assert_eq!(reviews[1].rating, 3.0);
+        assert_eq!(reviews[1].helpful_votes, None);
+
+        let html = fixture("scribblehub_series_299262.html");
+        assert_eq!(ScribbleHub.parse_reviews(&html, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_similar_series_come_from_the_page() {
+        let novel = parse("scribblehub_series_299262.html", 299262).novel;
+        // Without the series itself or the other sidebar's links
+        assert_eq!(novel.also_liked, Some(vec![301877, 187402, 415090]));
+
+        // No endpoint is fetched for them
+        let client = FakeClient::new();
+        let also_liked = scrape_also_liked_from(&ScribbleHub, &client, &novel, None).unwrap();
+        assert_eq!(also_liked.ids(), [301877, 187402, 415090]);
+        assert!(client.requests().is_empty());
+    }
+
+    #[test]
+    fn test_parse_series_urls() {
+        let cases = [
+            (
+                "https://www.scribblehub.com/series/299262/the-tower/",
+                Some(299262),
+            ),
+            ("https://www.scribblehub.com/series/299262/", Some(299262)),
+            ("www.scribblehub.com/series/299262?sort=asc", Some(299262)),
+            ("/series/299262/slug/#reviews", Some(299262)),
+            (
+                "https://www.scribblehub.com/read/299262-the-tower/chapter/1157233/",
+                Some(299262),
+            ),
+            ("https://www.scribblehub.com/series-ranking/", None),
+            ("https://www.scribblehub.com/profile/48213/ashgrove/", None),
+            ("https://www.royalroad.com/fiction/299262", None),
+            ("", None),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(parse_series_id(url), expected, "{}", url);
+        }
+        assert_eq!(
+            parse_series_slug("https://www.scribblehub.com/series/1/a-slug/").as_deref(),
+            Some("a-slug")
+        );
+        assert_eq!(
+            parse_series_slug("https://www.scribblehub.com/series/1/"),
+            None
+        );
+        assert_eq!(
+            ScribbleHub.fiction_url(7),
+            "https://www.scribblehub.com/series/7/"
+        );
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("4,312").unwrap(), 4312);
+        assert_eq!(parse_count("9.8k").unwrap(), 9800);
+        assert_eq!(parse_count("1.25M").unwrap(), 1_250_000);
+        assert_eq!(parse_count("0").unwrap(), 0);
+        assert!(parse_count("").is_err());
+        assert!(parse_count("k").is_err());
+        assert!(parse_count("about 5").is_err());
+    }
+
+    #[test]
+    fn test_parse_listed_time() {
+        assert_eq!(
+            parse_listed_time("Mar 13, 2024 06:00 PM"),
+            Timestamp::parse("2024-03-13T18:00:00Z")
+        );
+        assert_eq!(
+            parse_listed_time("Jan 2, 2024 12:41 AM"),
+            Timestamp::parse("2024-01-02T00:41:00Z")
+        );
+        assert_eq!(
+            parse_listed_time("Aug 1, 2022"),
+            Timestamp::parse("2022-08-01")
+        );
+        assert_eq!(parse_listed_time("2 days ago"), None);
+        assert_eq!(parse_listed_time("Feb 32, 2024"), None);
+        assert_eq!(parse_listed_time("Mar 13, 2024 13:00 PM"), None);
+    }
+
+    #[test]
+    fn test_parse_chapter_content() {
+        let html = fixture("scribblehub_chapter_700001.html");
+        let text = ScribbleHub.parse_chapter(&html).unwrap();
+        assert_eq!(
+            text,
+            "Nobody climbs down.\nThat was the first thing Ilse learned about the tower, \
+             and the last thing she believed.\nThe stairs went on."
+        );
+        assert!(parse_chapter_content("<html><body></body></html>").is_err());
+    }
+}
@@ -0,0 +1,564 @@
+//! Statistics collected over a pipeline run.
+//!
+//! Timings are wall-clock measurements taken around each call site in the
+//! pipeline, so they are available whatever the log level.
+
+use crate::eval::filter::FilterReason;
+use crate::models::timestamp::Timestamp;
+use crate::models::{Criteria, CriteriaProfile, Novel};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// A timed part of the pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Gathering seed novels, including scraping them.
+    SeedGathering,
+    /// Scraping a novel's page and any extra per-novel pages (sample
+    /// chapters, the author's fiction list).
+    NovelScrape,
+    /// Scraping a novel's reviews.
+    ReviewScrape,
+    /// Evaluating a novel (once per matching profile).
+    Evaluate,
+    /// Discovering related novels.
+    Discovery,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Phase::SeedGathering => "Seed gathering",
+            Phase::NovelScrape => "Novel scrape",
+            Phase::ReviewScrape => "Review scrape",
+            Phase::Evaluate => "Evaluate",
+            Phase::Discovery => "Discovery",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// How stratified sampling spread the seeded queue, and how far the run got
+/// into each stratum.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SamplingStats {
+    /// The statistic novels were stratified by, e.g. "followers".
+    pub by: String,
+    /// The strata, from the lowest values up.
+    pub strata: Vec<StratumStats>,
+}
+
+/// One stratum of the seeded queue.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StratumStats {
+    /// The lowest value in the stratum.
+    pub low: f64,
+    /// The highest value in the stratum.
+    pub high: f64,
+    /// Seeds queued in the stratum.
+    pub queued: usize,
+    /// Of those, the novels evaluated before the run stopped.
+    pub evaluated: usize,
+}
+
+/// Accumulated wall-clock time for one phase.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PhaseTiming {
+    /// Number of timed calls.
+    pub count: usize,
+    /// Total time across all calls.
+    #[serde(rename = "total_secs", serialize_with = "as_secs")]
+    pub total: Duration,
+    /// The longest single call.
+    #[serde(rename = "max_secs", serialize_with = "as_secs")]
+    pub max: Duration,
+    /// The novel being processed during the longest call, if any.
+    pub max_novel_id: Option<u64>,
+}
+
+impl PhaseTiming {
+    /// Add one timed call.
+    pub fn record(&mut self, elapsed: Duration, novel_id: Option<u64>) {
+        self.count += 1;
+        self.total += elapsed;
+        if elapsed > self.max || self.count == 1 {
+            self.max = elapsed;
+            self.max_novel_id = novel_id;
+        }
+    }
+
+    /// Mean time per call.
+    pub fn average(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            n => self.total / n as u32,
+        }
+    }
+}
+
+/// Statistics about one pipeline run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RunStats {
+    /// Novels that were evaluated (counted once regardless of profiles).
+    pub novels_evaluated: usize,
+    /// IDs of novels abandoned for taking longer than the per-novel limit.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub novels_timed_out: Vec<u64>,
+    /// IDs of novels that skipped optional enrichments for running out of
+    /// their request budget.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub novels_over_budget: Vec<u64>,
+    /// Novels the hard filters rejected.
+    pub novels_rejected: usize,
+    /// Rejections by the criteria setting that caused them, e.g.
+    /// "min_rating". A novel failing several settings counts for each.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rejections: BTreeMap<&'static str, FilterTally>,
+    /// Novels evaluated only because soft filters let their near misses
+    /// through.
+    pub novels_near_miss: usize,
+    /// IDs of novels whose pages showed reviews none of which could be
+    /// parsed, evaluated without reviews.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub novels_reviews_unparsed: Vec<u64>,
+    /// Novels skipped because their pages came back as Cloudflare
+    /// challenges.
+    pub novels_challenged: usize,
+    /// Novels discovery added to the queue.
+    pub novels_discovered: usize,
+    /// Whether the queue ran out before the stop condition was reached.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub queue_ran_dry: bool,
+    /// Evaluated novels not used for discovery because their best score was
+    /// below `discovery_min_score`.
+    pub discovery_skipped_low_score: usize,
+    /// Discovered novels left unevaluated once `max_discovery_ratio` was
+    /// reached, so a later run can pick them up as seeds.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub found_not_evaluated: Vec<FoundNovel>,
+    /// Whether the run was cancelled before its stop condition, leaving
+    /// novels in the queue.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Wall-clock time of the whole run.
+    #[serde(rename = "total_secs", serialize_with = "as_secs")]
+    pub total: Duration,
+    /// Timing per phase. Phases that never ran are absent.
+    pub phases: BTreeMap<Phase, PhaseTiming>,
+    /// How the result scores spread. Absent when there were no results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_distribution: Option<ScoreDistribution>,
+    /// The strata the seeded queue was sampled across. Absent without
+    /// stratified sampling.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingStats>,
+}
+
+impl RunStats {
+    /// Record a novel the hard filters rejected for `reasons`.
+    pub fn record_rejection(&mut self, reasons: &[FilterReason]) {
+        self.novels_rejected += 1;
+        let mut settings: Vec<&'static str> = Vec::new();
+        for reason in reasons {
+            let setting = reason.setting();
+            let tally = self.rejections.entry(setting).or_default();
+            if let Some((value, limit)) = reason.measurement() {
+                tally.limit = if tally.values.is_empty() {
+                    Some(limit)
+                } else {
+                    // Profiles with different limits have no single one
+                    tally.limit.filter(|&known| known == limit)
+                };
+                tally.values.push(value);
+            }
+            // Profiles failing the same setting count once
+            if !settings.contains(&setting) {
+                settings.push(setting);
+                tally.novels += 1;
+            }
+        }
+        if let [setting] = settings[..] {
+            if let Some(tally) = self.rejections.get_mut(setting) {
+                tally.sole_rejections += 1;
+            }
+        }
+    }
+
+    /// The settings that alone kept novels out, most first: those with
+    /// rejected novels that failed no other setting, so relaxing that one
+    /// setting would have let them through.
+    pub fn most_limiting_filters(&self) -> Vec<(&'static str, &FilterTally)> {
+        let mut limiting: Vec<(&'static str, &FilterTally)> = self
+            .rejections
+            .iter()
+            .filter(|(_, tally)| tally.sole_rejections > 0)
+            .map(|(setting, tally)| (*setting, tally))
+            .collect();
+        limiting.sort_by_key(|(_, tally)| {
+            std::cmp::Reverse((tally.sole_rejections, tally.novels))
+        });
+        limiting
+    }
+
+    /// Record one timed call of a phase.
+    pub fn record(&mut self, phase: Phase, elapsed: Duration, novel_id: Option<u64>) {
+        self.phases.entry(phase).or_default().record(elapsed, novel_id);
+    }
+
+    /// Zero every wall-clock measurement, keeping the counts, so the stats
+    /// depend only on what the run did.
+    pub fn clear_timings(&mut self) {
+        self.total = Duration::ZERO;
+        for timing in self.phases.values_mut() {
+            timing.total = Duration::ZERO;
+            timing.max = Duration::ZERO;
+            timing.max_novel_id = None;
+        }
+    }
+
+    /// Average wall-clock time per evaluated novel over the whole run.
+    pub fn average_per_novel(&self) -> Option<Duration> {
+        (self.novels_evaluated > 0).then(|| self.total / self.novels_evaluated as u32)
+    }
+}
+
+/// The novels one criteria setting rejected.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FilterTally {
+    /// Novels the setting rejected.
+    pub novels: usize,
+    /// Novels the setting rejected that failed no other setting.
+    pub sole_rejections: usize,
+    /// The setting's limit, for numeric settings, unless profiles set it
+    /// differently.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<f64>,
+    /// The rejected novels' values, for numeric settings.
+    #[serde(skip)]
+    pub values: Vec<f64>,
+}
+
+/// A novel found by discovery but never evaluated.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FoundNovel {
+    pub id: u64,
+    pub title: String,
+    pub url: String,
+}
+
+impl From<&Novel> for FoundNovel {
+    fn from(novel: &Novel) -> Self {
+        Self {
+            id: novel.id,
+            title: novel.title.clone(),
+            url: novel.url.clone(),
+        }
+    }
+}
+
+/// What produced a run's results, embedded in the files written from them
+/// so a results file can be traced back to its settings later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// When the run finished.
+    pub finished_at: Timestamp,
+    /// The novel-finder version that ran.
+    pub version: String,
+    /// The criteria novels were scored against, as resolved from the config.
+    pub criteria: Criteria,
+    /// Criteria profiles, when novels were scored once per profile.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<CriteriaProfile>,
+    /// Fingerprint of the scoring settings (see `AppConfig::criteria_hash`),
+    /// which also keys caches and feed entries.
+    pub criteria_hash: String,
+    /// The evaluator, and for LLMs the model.
+    pub eval_mode: String,
+    /// Where the seed novels came from.
+    pub seeds: String,
+    /// When the run was set to stop.
+    pub stop_condition: String,
+    /// HTTP requests made to RoyalRoad.
+    pub total_requests: u64,
+    /// Wall-clock time of the whole run, in seconds.
+    pub duration_secs: f64,
+}
+
+impl RunMetadata {
+    /// The metadata as labelled one-line fields, for file headers.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("novel-finder", self.version.clone()),
+            ("finished_at", self.finished_at.to_string()),
+            ("criteria_hash", self.criteria_hash.clone()),
+            ("eval_mode", self.eval_mode.clone()),
+            ("seeds", self.seeds.clone()),
+            ("stop_condition", self.stop_condition.clone()),
+            ("total_requests", self.total_requests.to_string()),
+            ("duration_secs", format!("{:.1}", self.duration_secs)),
+        ];
+        // Both serialize infallibly: their maps have string keys
+        let criteria = serde_json::to_string(&self.criteria).unwrap_or_default();
+        fields.push(("criteria", criteria));
+        if !self.profiles.is_empty() {
+            let profiles = serde_json::to_string(&self.profiles).unwrap_or_default();
+            fields.push(("profiles", profiles));
+        }
+        fields
+    }
+}
+
+/// Build run metadata for unit tests, for a run finished `seconds` after
+/// the Unix epoch.
+#[cfg(test)]
+pub(crate) fn test_run_metadata(criteria_hash: &str, seconds: u64) -> RunMetadata {
+    RunMetadata {
+        finished_at: Timestamp::from_system_time(
+            std::time::UNIX_EPOCH + Duration::from_secs(seconds),
+        ),
+        version: "0.1.0".to_string(),
+        criteria: Criteria::default(),
+        profiles: Vec::new(),
+        criteria_hash: criteria_hash.to_string(),
+        eval_mode: "local".to_string(),
+        seeds: "manual (URLs: 1)".to_string(),
+        stop_condition: "when the queue is empty".to_string(),
+        total_requests: 12,
+        duration_secs: 3.5,
+    }
+}
+
+/// Number of histogram buckets, each covering a tenth of the score range.
+pub const SCORE_BUCKETS: usize = 10;
+
+/// The spread of result scores: a histogram plus summary statistics.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoreDistribution {
+    /// Result counts per score bucket: 0-10%, 10-20%, ... 90-100%. A score
+    /// on a boundary goes in the higher bucket, except 100%.
+    pub buckets: [usize; SCORE_BUCKETS],
+    pub min: f64,
+    pub median: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+impl ScoreDistribution {
+    /// Summarize scores in the 0.0-1.0 range, ignoring NaN. Returns `None`
+    /// if no scores are left.
+    pub fn from_scores(scores: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let mut scores: Vec<f64> = scores.into_iter().filter(|s| !s.is_nan()).collect();
+        if scores.is_empty() {
+            return None;
+        }
+        scores.sort_by(f64::total_cmp);
+
+        let mut buckets = [0; SCORE_BUCKETS];
+        for score in &scores {
+            buckets[bucket_index(*score)] += 1;
+        }
+        let middle = scores.len() / 2;
+        let median = if scores.len().is_multiple_of(2) {
+            (scores[middle - 1] + scores[middle]) / 2.0
+        } else {
+            scores[middle]
+        };
+        Some(Self {
+            buckets,
+            min: scores[0],
+            median,
+            mean: scores.iter().sum::<f64>() / scores.len() as f64,
+            max: scores[scores.len() - 1],
+        })
+    }
+}
+
+/// The histogram bucket of a score, clamping out-of-range scores.
+fn bucket_index(score: f64) -> usize {
+    ((score * SCORE_BUCKETS as f64).floor().max(0.0) as usize).min(SCORE_BUCKETS - 1)
+}
+
+/// Serialize a duration as fractional seconds.
+fn as_secs<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_novel;
+
+    #[test]
+    fn test_phase_timing_tracks_max_novel() {
+        let mut stats = RunStats::default();
+        stats.record(Phase::NovelScrape, Duration::from_millis(300), Some(1));
+        stats.record(Phase::NovelScrape, Duration::from_millis(900), Some(2));
+        stats.record(Phase::NovelScrape, Duration::from_millis(600), Some(3));
+
+        let timing = &stats.phases[&Phase::NovelScrape];
+        assert_eq!(timing.count, 3);
+        assert_eq!(timing.total, Duration::from_millis(1_800));
+        assert_eq!(timing.average(), Duration::from_millis(600));
+        assert_eq!(timing.max, Duration::from_millis(900));
+        assert_eq!(timing.max_novel_id, Some(2));
+    }
+
+    #[test]
+    fn test_run_stats_json() {
+        let mut stats = RunStats {
+            novels_evaluated: 2,
+            total: Duration::from_secs(10),
+            ..Default::default()
+        };
+        stats.record(Phase::Evaluate, Duration::from_millis(1_500), Some(90435));
+        assert_eq!(stats.average_per_novel(), Some(Duration::from_secs(5)));
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["total_secs"], 10.0);
+        assert_eq!(json["phases"]["evaluate"]["max_secs"], 1.5);
+        assert_eq!(json["phases"]["evaluate"]["max_novel_id"], 90435);
+        assert!(json.get("score_distribution").is_none());
+        assert!(json.get("novels_timed_out").is_none());
+        assert_eq!(json["discovery_skipped_low_score"], 0);
+        assert!(json.get("found_not_evaluated").is_none());
+
+        stats.novels_timed_out.push(89877);
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["novels_timed_out"], serde_json::json!([89877]));
+
+        stats.found_not_evaluated.push(FoundNovel::from(&test_novel(115399)));
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(
+            json["found_not_evaluated"],
+            serde_json::json!([{
+                "id": 115399,
+                "title": "Novel 115399",
+                "url": "https://www.royalroad.com/fiction/115399"
+            }])
+        );
+    }
+
+    #[test]
+    fn test_clear_timings_keeps_counts() {
+        let mut stats = RunStats {
+            total: Duration::from_secs(10),
+            ..Default::default()
+        };
+        stats.record(Phase::Evaluate, Duration::from_millis(1_500), Some(90435));
+        stats.clear_timings();
+        let timing = &stats.phases[&Phase::Evaluate];
+        assert_eq!(stats.total, Duration::ZERO);
+        assert_eq!((timing.total, timing.max), (Duration::ZERO, Duration::ZERO));
+        assert_eq!((timing.count, timing.max_novel_id), (1, None));
+    }
+
+    #[test]
+    fn test_bucket_index_boundaries() {
+        assert_eq!(bucket_index(0.0), 0);
+        assert_eq!(bucket_index(0.099), 0);
+        assert_eq!(bucket_index(0.1), 1);
+        assert_eq!(bucket_index(0.55), 5);
+        assert_eq!(bucket_index(0.9), 9);
+        assert_eq!(bucket_index(1.0), 9);
+        assert_eq!(bucket_index(-0.2), 0);
+        assert_eq!(bucket_index(1.5), 9);
+    }
+
+    #[test]
+    fn test_score_distribution() {
+        let dist = ScoreDistribution::from_scores([0.9, 0.15, 0.5, f64::NAN, 0.95, 0.1]).unwrap();
+        assert_eq!(dist.buckets, [0, 2, 0, 0, 0, 1, 0, 0, 0, 2]);
+        assert_eq!(dist.min, 0.1);
+        assert_eq!(dist.median, 0.5);
+        assert_eq!(dist.max, 0.95);
+        assert!((dist.mean - 0.52).abs() < 1e-9);
+
+        // Even counts take the mean of the middle two
+        let even = ScoreDistribution::from_scores([0.2, 0.4, 0.6, 1.0]).unwrap();
+        assert!((even.median - 0.5).abs() < 1e-9);
+        assert!(ScoreDistribution::from_scores([f64::NAN]).is_none());
+    }
+
+    #[test]
+    fn test_record_rejection_counts_each_setting_once() {
+        let mut stats = RunStats::default();
+        // Two profiles rejecting one novel on rating, with different limits
+        stats.record_rejection(&[
+            FilterReason::RatingTooLow {
+                rating: 4.1,
+                min: 4.5,
+            },
+            FilterReason::FanFiction,
+            FilterReason::RatingTooLow {
+                rating: 4.1,
+                min: 4.2,
+            },
+        ]);
+        stats.record_rejection(&[FilterReason::FanFiction]);
+
+        assert_eq!(stats.novels_rejected, 2);
+        assert_eq!(stats.rejections["allow_fanfiction"].novels, 2);
+        let rating = &stats.rejections["min_rating"];
+        assert_eq!(rating.novels, 1);
+        assert_eq!(rating.limit, None);
+        assert_eq!(rating.values, [4.1, 4.1]);
+        // Only the second novel failed a single setting
+        assert_eq!(stats.rejections["allow_fanfiction"].sole_rejections, 1);
+        assert_eq!(rating.sole_rejections, 0);
+    }
+
+    #[test]
+    fn test_most_limiting_filters() {
+        use crate::eval::filter::hard_filter;
+        use crate::models::{Criteria, NovelStatus};
+
+        let criteria = Criteria {
+            min_pages: Some(300),
+            min_rating: Some(4.0),
+            allowed_statuses: Some(vec![NovelStatus::Completed]),
+            ..Default::default()
+        };
+        let novel = |pages: u64, rating: f64, status: NovelStatus| {
+            let mut novel = test_novel(1);
+            novel.pages = pages;
+            novel.rating = rating;
+            novel.status = status;
+            novel
+        };
+        let novels = [
+            // Rating alone, three times
+            novel(500, 3.5, NovelStatus::Completed),
+            novel(800, 3.9, NovelStatus::Completed),
+            novel(400, 2.0, NovelStatus::Completed),
+            // Pages alone, once
+            novel(100, 4.5, NovelStatus::Completed),
+            // Status and pages: relaxing either alone wouldn't admit them
+            novel(100, 4.5, NovelStatus::Ongoing),
+            novel(50, 4.8, NovelStatus::Ongoing),
+            // Every filter at once
+            novel(10, 1.0, NovelStatus::Hiatus),
+            // Passes
+            novel(500, 4.5, NovelStatus::Completed),
+        ];
+        let mut stats = RunStats::default();
+        for novel in &novels {
+            let reasons = hard_filter(novel, &criteria);
+            if !reasons.is_empty() {
+                stats.record_rejection(&reasons);
+            }
+        }
+
+        assert_eq!(stats.novels_rejected, 7);
+        let limiting: Vec<(&str, usize, usize)> = stats
+            .most_limiting_filters()
+            .into_iter()
+            .map(|(setting, tally)| (setting, tally.sole_rejections, tally.novels))
+            .collect();
+        // allowed_statuses rejected three novels, but none on its own
+        assert_eq!(limiting, [("min_rating", 3, 4), ("min_pages", 1, 4)]);
+        assert_eq!(stats.rejections["allowed_statuses"].novels, 3);
+    }
+}
@@ -0,0 +1,362 @@
+//! Small shared helpers.
+//!
+//! Calendar math for the ISO-8601 dates RoyalRoad embeds in its pages,
+//! counted in whole days since the Unix epoch (UTC), number and date
+//! formatting, and file names built from novel titles.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds in one day.
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Convert a proleptic Gregorian calendar date to days since 1970-01-01.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Convert days since 1970-01-01 back to a `(year, month, day)` date.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Parse the `YYYY-MM-DD` prefix of an ISO-8601 date or timestamp into days
+/// since 1970-01-01. Any time-of-day part is ignored.
+pub fn parse_date_days(s: &str) -> Option<i64> {
+    let date = s.trim().get(..10)?;
+    let mut parts = date.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// Format days since 1970-01-01 as `YYYY-MM-DD`.
+pub fn format_date_days(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Format a number with comma thousands separators (e.g., "6,475").
+pub fn format_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Format a size in bytes for humans: "512 B", "3.4 KB", "12.0 MB".
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Format a duration compactly for humans: "350ms", "4.2s", "3m 05s",
+/// or "1h 02m".
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+    } else if seconds >= 60 {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    } else if seconds >= 1 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+/// Today's date as days since 1970-01-01 (UTC).
+pub fn today_days() -> i64 {
+    (now_seconds() / SECONDS_PER_DAY) as i64
+}
+
+/// Format seconds since the Unix epoch as a compact UTC timestamp,
+/// `YYYYMMDD-HHMMSS`, suitable for file names.
+pub fn format_compact_timestamp(seconds: u64) -> String {
+    let (year, month, day) = civil_from_days((seconds / SECONDS_PER_DAY) as i64);
+    let time = seconds % SECONDS_PER_DAY;
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Format a time as an RFC 3339 UTC timestamp with milliseconds, e.g.
+/// "2024-08-01T21:03:03.250Z".
+pub fn format_rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let time_of_day = seconds % SECONDS_PER_DAY;
+    format!(
+        "{}T{:02}:{:02}:{:02}.{:03}Z",
+        format_date_days((seconds / SECONDS_PER_DAY) as i64),
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Seconds since the Unix epoch, now.
+pub fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The 64-bit FNV-1a hash of some bytes. Unlike `std`'s hashers it is stable
+/// across builds, so it can fingerprint data saved between runs.
+pub fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes
+        .iter()
+        .fold(OFFSET_BASIS, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME))
+}
+
+/// Longest title slug kept in a file name, in bytes, leaving room for the
+/// ID, an extension, and a directory within Windows' 260-character paths.
+const MAX_SLUG_LEN: usize = 80;
+
+/// A file name (without extension) for the novel `id` titled `title`, safe
+/// on every platform: the title as a lowercase ASCII slug, accents
+/// transliterated and everything else (punctuation, path separators, emoji)
+/// turned into dashes, cut to [`MAX_SLUG_LEN`], then the ID. The ID keeps
+/// novels with the same title apart, and means no name is ever one of
+/// Windows' reserved device names like `CON` or `NUL`.
+pub fn safe_filename(title: &str, id: u64) -> String {
+    let mut slug = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        let ascii = if c.is_ascii_alphanumeric() {
+            Some(c.to_string())
+        } else {
+            transliterate(c).map(String::from)
+        };
+        match ascii {
+            Some(text) => slug.push_str(&text),
+            None if !slug.is_empty() && !slug.ends_with('-') => slug.push('-'),
+            None => {}
+        }
+    }
+    if slug.len() > MAX_SLUG_LEN {
+        // Cut at the last word break that fits, if there is one
+        let cut = slug[..=MAX_SLUG_LEN].rfind('-').unwrap_or(MAX_SLUG_LEN);
+        slug.truncate(cut);
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        id.to_string()
+    } else {
+        format!("{}-{}", slug, id)
+    }
+}
+
+/// What [`redact`] puts in place of a secret.
+pub const REDACTED: &str = "[redacted]";
+
+/// `text` with every occurrence of `secret` replaced by [`REDACTED`], for
+/// writing out request details that must never carry an API key. An empty
+/// secret leaves the text as it is.
+pub fn redact(text: &str, secret: &str) -> String {
+    if secret.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(secret, REDACTED)
+    }
+}
+
+/// The ASCII spelling of a lowercase accented Latin letter, if it has one.
+fn transliterate(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'ł' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+/// A random string of up to `max_len` characters drawn from `alphabet`,
+/// for property tests over untrusted input.
+#[cfg(test)]
+pub(crate) fn random_string(rng: &mut impl rand::Rng, alphabet: &[char], max_len: usize) -> String {
+    let len = rng.gen_range(0..=max_len);
+    (0..len)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_from_civil() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(2024, 8, 1), 19_936);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+    }
+
+    #[test]
+    fn test_civil_round_trip() {
+        for days in [-1, 0, 59, 11_016, 11_017, 19_936, 20_500] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+        assert_eq!(format_date_days(19_936), "2024-08-01");
+    }
+
+    #[test]
+    fn test_format_thousands() {
+        assert_eq!(format_thousands(0), "0");
+        assert_eq!(format_thousands(999), "999");
+        assert_eq!(format_thousands(6_475), "6,475");
+        assert_eq!(format_thousands(1_000_000), "1,000,000");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(3_500), "3.4 KB");
+        assert_eq!(format_bytes(12 * 1024 * 1024), "12.0 MB");
+    }
+
+    #[test]
+    fn test_format_compact_timestamp() {
+        // 2024-08-01T21:03:03Z
+        assert_eq!(format_compact_timestamp(1_722_546_183), "20240801-210303");
+        assert_eq!(format_compact_timestamp(0), "19700101-000000");
+    }
+
+    #[test]
+    fn test_format_rfc3339() {
+        let time = UNIX_EPOCH + std::time::Duration::from_millis(1_722_546_183_250);
+        assert_eq!(format_rfc3339(time), "2024-08-01T21:03:03.250Z");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(350)), "350ms");
+        assert_eq!(format_duration(Duration::from_millis(4_210)), "4.2s");
+        assert_eq!(format_duration(Duration::from_secs(185)), "3m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3_720)), "1h 02m");
+    }
+
+    #[test]
+    fn test_parse_date_days() {
+        assert_eq!(parse_date_days("2024-08-01T21:03:03Z"), Some(19_936));
+        assert_eq!(parse_date_days("2024-08-01"), Some(19_936));
+        assert_eq!(parse_date_days("2024-13-01"), None);
+        assert_eq!(parse_date_days("yesterday"), None);
+        assert_eq!(parse_date_days(""), None);
+    }
+
+    #[test]
+    fn test_fnv1a_64() {
+        assert_eq!(fnv1a_64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_safe_filename() {
+        assert_eq!(safe_filename("Bunny Girl Evolution", 90435), "bunny-girl-evolution-90435");
+        // Path separators and characters Windows forbids
+        assert_eq!(
+            safe_filename("Re: Why? / A \\Tale\\ of <Two> \"Worlds\"*|", 7),
+            "re-why-a-tale-of-two-worlds-7"
+        );
+        assert_eq!(safe_filename("../../etc/passwd", 8), "etc-passwd-8");
+        // Accents are transliterated, other scripts and emoji dropped
+        assert_eq!(safe_filename("Café Müller: Straße ✨🐉", 9), "cafe-muller-strasse-9");
+        assert_eq!(safe_filename("転生したらスライム", 10), "10");
+        assert_eq!(safe_filename("", 11), "11");
+        // Reserved device names never stand alone
+        for reserved in ["CON", "nul", "Com1", "LPT9", "aux.txt"] {
+            let name = safe_filename(reserved, 12);
+            assert!(name.ends_with("-12"), "{}", name);
+        }
+        assert_eq!(safe_filename("CON", 12), "con-12");
+        assert_eq!(safe_filename("...", 13), "13");
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            redact("key sk-123 and sk-123 again", "sk-123"),
+            "key [redacted] and [redacted] again"
+        );
+        assert_eq!(redact("nothing secret", "sk-123"), "nothing secret");
+        assert_eq!(redact("left alone", ""), "left alone");
+    }
+
+    #[test]
+    fn test_safe_filename_length_and_collisions() {
+        let long = "The Unbelievably Long Title ".repeat(10);
+        let name = safe_filename(&long, 123_456);
+        assert!(name.len() <= MAX_SLUG_LEN + 1 + "123456".len(), "{}", name);
+        assert!(name.ends_with("-unbelievably-long-123456"), "{}", name);
+        let unbroken = safe_filename(&"a".repeat(300), 5);
+        assert_eq!(unbroken, format!("{}-5", "a".repeat(MAX_SLUG_LEN)));
+
+        // Identical titles, or ones that slug the same, stay apart by ID
+        assert_ne!(safe_filename("Dungeon Life", 1), safe_filename("Dungeon Life", 2));
+        assert_ne!(safe_filename("Dungeon: Life", 1), safe_filename("Dungeon Life?", 2));
+        assert_eq!(safe_filename("Dungeon: Life", 1), safe_filename("Dungeon Life?", 1));
+    }
+}
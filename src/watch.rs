@@ -0,0 +1,212 @@
+//! Watch mode: re-running the pipeline on an interval as a standing search.
+//!
+//! Each iteration runs the whole pipeline and reports only the matches no
+//! earlier iteration reported. The reported novels, by site and ID, can be
+//! kept in a history file so restarts don't repeat old matches either.
+//! Cancelling the loop's token (the CLI does on Ctrl-C) stops it cleanly:
+//! while waiting it stops at once, and during a run the run stops after the
+//! current novel and its results so far are reported.
+
+use crate::cancel::CancellationToken;
+use crate::config::AppConfig;
+use crate::models::{NovelKey, NovelScore};
+use crate::persist::{self, HistoryFile};
+use crate::pipeline::Pipeline;
+use crate::stats::{RunMetadata, RunStats};
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How often the wait between iterations checks for cancellation.
+const SHUTDOWN_POLL: Duration = Duration::from_millis(200);
+
+/// The outcome of one watch iteration.
+pub struct Iteration<'a> {
+    /// 1-based iteration number.
+    pub number: usize,
+    /// Matches not reported by any earlier iteration, ranked best first.
+    pub new_matches: Vec<NovelScore>,
+    /// All results of this iteration's run.
+    pub results: &'a [NovelScore],
+    /// Statistics of this iteration's run.
+    pub stats: &'a RunStats,
+    /// What produced this iteration's results.
+    pub metadata: RunMetadata,
+}
+
+/// Novels already reported, optionally persisted between watch sessions.
+#[derive(Debug, Default)]
+pub struct WatchHistory {
+    reported: BTreeSet<NovelKey>,
+    path: Option<PathBuf>,
+}
+
+impl WatchHistory {
+    /// Load the history at `path`, starting empty if the file doesn't exist.
+    /// Without a path the history lasts only as long as the process.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let reported = match std::fs::read_to_string(path) {
+            Ok(content) => {
+                let file: HistoryFile = persist::from_str(&content).with_context(|| {
+                    format!("Failed to parse watch history {}", path.display())
+                })?;
+                file.reported
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read watch history {}", path.display()))
+            }
+        };
+        Ok(Self {
+            reported,
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Take the results not reported before and scoring at least
+    /// `min_score`, recording them as reported. Each novel is taken once
+    /// even if it matched several profiles.
+    pub fn take_new(&mut self, results: &[NovelScore], min_score: Option<f64>) -> Vec<NovelScore> {
+        results
+            .iter()
+            .filter(|score| min_score.is_none_or(|min| score.overall_score >= min))
+            .filter(|score| self.reported.insert(score.novel.key()))
+            .cloned()
+            .collect()
+    }
+
+    /// Write the history back to its file, if it has one.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let file = HistoryFile {
+            reported: self.reported.clone(),
+        };
+        std::fs::write(path, persist::to_string(&file)?)
+            .with_context(|| format!("Failed to write watch history {}", path.display()))
+    }
+}
+
+/// Run the pipeline every `interval` until `cancel` is cancelled (or once,
+/// with `once`), passing each iteration to `report`.
+///
+/// A failed iteration is logged and retried at the next interval, except
+/// with `once`, where the error is returned.
+pub fn run<F>(
+    config: &AppConfig,
+    interval: Duration,
+    once: bool,
+    history: &mut WatchHistory,
+    cancel: &CancellationToken,
+    mut report: F,
+) -> Result<()>
+where
+    F: FnMut(Iteration) -> Result<()>,
+{
+    for number in 1.. {
+        let started = Instant::now();
+        tracing::info!("Starting watch iteration {}", number);
+        let outcome = run_iteration(config, number, history, cancel, &mut report);
+        match outcome {
+            Ok(()) => {}
+            Err(e) if once => return Err(e),
+            Err(e) => tracing::error!("Watch iteration {} failed: {:#}", number, e),
+        }
+        if once || cancel.is_cancelled() {
+            break;
+        }
+
+        let next = started + interval;
+        tracing::info!(
+            "Next iteration in {}; press Ctrl-C to stop",
+            crate::util::format_duration(next.saturating_duration_since(Instant::now()))
+        );
+        while Instant::now() < next && !cancel.is_cancelled() {
+            std::thread::sleep(SHUTDOWN_POLL.min(next.saturating_duration_since(Instant::now())));
+        }
+        if cancel.is_cancelled() {
+            break;
+        }
+    }
+    tracing::info!("Watch mode stopped");
+    Ok(())
+}
+
+/// Run and report one iteration, then save the history.
+fn run_iteration<F>(
+    config: &AppConfig,
+    number: usize,
+    history: &mut WatchHistory,
+    cancel: &CancellationToken,
+    report: &mut F,
+) -> Result<()>
+where
+    F: FnMut(Iteration) -> Result<()>,
+{
+    let mut pipeline = Pipeline::new(config.clone())?;
+    pipeline.cancel_with(cancel.clone());
+    let results = pipeline.run()?;
+    let new_matches = history.take_new(&results, config.output.min_score);
+    tracing::info!("Watch iteration {}: {} new matches", number, new_matches.len());
+    report(Iteration {
+        number,
+        new_matches,
+        results: &results,
+        stats: pipeline.stats(),
+        metadata: pipeline.run_metadata(),
+    })?;
+    history.save()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_novel, test_score, SiteId};
+
+    fn score(id: u64, overall: f64) -> NovelScore {
+        test_score(test_novel(id), overall)
+    }
+
+    #[test]
+    fn test_take_new_reports_each_novel_once() {
+        let mut history = WatchHistory::default();
+        let first = history.take_new(&[score(1, 0.9), score(2, 0.3)], Some(0.5));
+        assert_eq!(first.iter().map(|s| s.novel.id).collect::<Vec<_>>(), vec![1]);
+
+        // Novel 2 now clears the threshold; novel 1 was already reported
+        let second = history.take_new(&[score(1, 0.9), score(2, 0.6), score(2, 0.8)], Some(0.5));
+        assert_eq!(second.iter().map(|s| s.novel.id).collect::<Vec<_>>(), vec![2]);
+        assert!(history.take_new(&[score(1, 0.9), score(2, 0.6)], None).is_empty());
+    }
+
+    #[test]
+    fn test_same_id_on_another_site_is_new() {
+        let mut history = WatchHistory::default();
+        assert_eq!(history.take_new(&[score(1, 0.9)], None).len(), 1);
+        let mut other = score(1, 0.9);
+        other.novel.site = SiteId::ScribbleHub;
+        assert_eq!(history.take_new(&[other], None).len(), 1);
+    }
+
+    #[test]
+    fn test_history_persists_between_sessions() {
+        let path = std::env::temp_dir()
+            .join(format!("novel-finder-watch-history-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut history = WatchHistory::load(Some(&path)).unwrap();
+        assert_eq!(history.take_new(&[score(7, 0.9)], None).len(), 1);
+        history.save().unwrap();
+
+        let mut reloaded = WatchHistory::load(Some(&path)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(reloaded.take_new(&[score(7, 0.9)], None).is_empty());
+        assert_eq!(reloaded.take_new(&[score(8, 0.9)], None).len(), 1);
+    }
+}
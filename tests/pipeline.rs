@@ -0,0 +1,501 @@
+//! End-to-end pipeline runs against canned RoyalRoad pages, from config text
+//! to ranked results, without touching the network.
+
+use anyhow::Result;
+use novel_finder::cancel::CancellationToken;
+use novel_finder::config::parse_config;
+use novel_finder::eval::filter::FilterReason;
+use novel_finder::models::{Novel, NovelScore, Review};
+use novel_finder::observer::PipelineObserver;
+use novel_finder::output::{self, analysis};
+use novel_finder::pipeline::{Deterministic, Pipeline};
+use novel_finder::rescore;
+use novel_finder::scraper::{HttpFetch, PageNotFound};
+use novel_finder::stats::RunStats;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Serves fixture files by URL and records every URL requested. Other URLs
+/// fail with `PageNotFound`, like deleted fictions.
+struct FakeClient {
+    pages: HashMap<String, String>,
+    requests: Mutex<Vec<String>>,
+    /// Requests for URLs starting with this prefix take this long.
+    slow: Option<(String, Duration)>,
+}
+
+impl FakeClient {
+    fn new(pages: &[(&str, &str)]) -> Self {
+        let pages = pages
+            .iter()
+            .map(|(url, fixture)| (url.to_string(), read_fixture(fixture)))
+            .collect();
+        Self {
+            pages,
+            requests: Mutex::new(Vec::new()),
+            slow: None,
+        }
+    }
+
+    /// Make requests for URLs starting with `prefix` take `delay`.
+    fn with_delay(mut self, prefix: &str, delay: Duration) -> Self {
+        self.slow = Some((prefix.to_string(), delay));
+        self
+    }
+
+    /// How often `url` was requested.
+    fn request_count(&self, url: &str) -> usize {
+        self.requests.lock().unwrap().iter().filter(|r| *r == url).count()
+    }
+
+    /// Assert no URL was requested more than once.
+    fn assert_no_repeat_requests(&self) {
+        let requests = self.requests.lock().unwrap();
+        let unique: HashSet<&String> = requests.iter().collect();
+        assert_eq!(unique.len(), requests.len(), "{:#?}", requests);
+    }
+}
+
+impl HttpFetch for FakeClient {
+    fn fetch(&self, url: &str) -> Result<String> {
+        self.requests.lock().unwrap().push(url.to_string());
+        let slow = self.slow.as_ref().filter(|(prefix, _)| url.starts_with(prefix));
+        if let Some((_, delay)) = slow {
+            std::thread::sleep(*delay);
+        }
+        self.pages.get(url).cloned().ok_or_else(|| {
+            PageNotFound {
+                url: url.to_string(),
+            }
+            .into()
+        })
+    }
+}
+
+/// Fails the test on any request, for runs that must not touch the network.
+struct OfflineClient;
+
+impl HttpFetch for OfflineClient {
+    fn fetch(&self, url: &str) -> Result<String> {
+        panic!("unexpected request for {}", url);
+    }
+}
+
+fn read_fixture(name: &str) -> String {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("src/scraper/testdata");
+    path.push(name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+const CONFIG: &str = r#"
+[eval]
+mode = "local"
+
+[seeds]
+source = "manual"
+urls = ["https://www.royalroad.com/fiction/90435/bunny-girl-evolution"]
+
+[run]
+stop_condition = { type = "max_novels", value = 3 }
+discovery_enabled = true
+"#;
+
+const FICTION: &str = "https://www.royalroad.com/fiction";
+const SIMILAR: &str = "https://www.royalroad.com/fictions/similar?fictionId=";
+
+/// The seed recommends 89877 and 115399 (and eight fictions with no page);
+/// 89877 recommends the seed and 115399 again.
+fn fake_royalroad() -> FakeClient {
+    FakeClient::new(&[
+        (&format!("{}/90435", FICTION), "novel_page_90435.html"),
+        (&format!("{}/89877", FICTION), "novel_page_89877.html"),
+        (&format!("{}/115399", FICTION), "novel_page_115399.html"),
+        (&format!("{}90435", SIMILAR), "similar_90435.json"),
+        (&format!("{}89877", SIMILAR), "similar_89877.json"),
+    ])
+}
+
+#[test]
+fn test_pipeline_runs_seed_and_discovered_novels() {
+    let client = Arc::new(fake_royalroad());
+    let config = parse_config(CONFIG).unwrap();
+    let mut pipeline = Pipeline::with_client(config, client.clone()).unwrap();
+    let results = pipeline.run().unwrap();
+
+    // The seed plus the two discovered novels with pages, each scored once
+    let mut ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![89877, 90435, 115399]);
+    assert_eq!(pipeline.stats().novels_evaluated, 3);
+
+    // Ranked best first
+    assert!(results.windows(2).all(|pair| pair[0].overall_score >= pair[1].overall_score));
+    assert!(results.iter().all(|score| (0.0..=1.0).contains(&score.overall_score)));
+
+    // Each novel's page was fetched once for both its metadata and reviews,
+    // and discovery ran once per novel. 89877 recommended the seed and
+    // 115399 again, but those were already queued so weren't fetched again.
+    client.assert_no_repeat_requests();
+    for id in [90435, 89877, 115399] {
+        assert_eq!(client.request_count(&format!("{}/{}", FICTION, id)), 1, "{}", id);
+        assert_eq!(client.request_count(&format!("{}{}", SIMILAR, id)), 1, "{}", id);
+    }
+}
+
+#[test]
+fn test_pipeline_stops_at_max_novels() {
+    let client = Arc::new(fake_royalroad());
+    let config = parse_config(&CONFIG.replace("value = 3", "value = 2")).unwrap();
+    let mut pipeline = Pipeline::with_client(config, client.clone()).unwrap();
+    let results = pipeline.run().unwrap();
+
+    let mut ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![89877, 90435]);
+    // The queued 115399 was only scraped when first recommended, never used
+    // for discovery
+    client.assert_no_repeat_requests();
+    assert_eq!(client.request_count(&format!("{}/115399", FICTION)), 1);
+    assert_eq!(client.request_count(&format!("{}115399", SIMILAR)), 0);
+}
+
+#[test]
+fn test_exported_queue_seeds_the_next_run() {
+    // Stopping after the seed leaves its two recommendations with pages queued
+    let client = Arc::new(fake_royalroad());
+    let config = parse_config(&CONFIG.replace("value = 3", "value = 1")).unwrap();
+    let mut pipeline = Pipeline::with_client(config, client).unwrap();
+    let results = pipeline.run().unwrap();
+    assert_eq!(results.len(), 1);
+    let path = std::env::temp_dir().join(format!("novel-finder-queue-{}.txt", std::process::id()));
+    assert_eq!(pipeline.export_queue(&results, &path).unwrap(), 2);
+    let exported = std::fs::read_to_string(&path).unwrap();
+
+    // The next run starts from exactly those, without discovering more
+    let manual = "source = \"manual\"\n\
+                  urls = [\"https://www.royalroad.com/fiction/90435/bunny-girl-evolution\"]";
+    let next = CONFIG
+        .replace(manual, &format!("source = \"file\"\nfile = {:?}", path))
+        .replace("discovery_enabled = true", "discovery_enabled = false");
+    let client = Arc::new(fake_royalroad());
+    let config = parse_config(&next).unwrap();
+    let mut pipeline = Pipeline::with_client(config, client.clone()).unwrap();
+    let results = pipeline.run().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![89877, 115399], "{}", exported);
+    assert_eq!(client.request_count(&format!("{}/90435", FICTION)), 0);
+}
+
+#[test]
+fn test_pipeline_discovery_budgets() {
+    // One recommendation per novel: the seed's first (89877) is fetched, and
+    // the rest of its list is never tried
+    let capped = CONFIG.replace(
+        "discovery_enabled = true",
+        "\n[[run.discovery]]\nkind = \"also_liked\"\nmax_per_novel = 1",
+    );
+    let client = Arc::new(fake_royalroad());
+    let config = parse_config(&capped.replace("value = 3", "value = 10")).unwrap();
+    let mut pipeline = Pipeline::with_client(config, client.clone()).unwrap();
+    let results = pipeline.run().unwrap();
+    let mut ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![89877, 90435, 115399]);
+    let fiction_requests = client
+        .requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|url| url.starts_with(&format!("{}/", FICTION)))
+        .count();
+    assert_eq!(fiction_requests, 3);
+
+    // No novel scores a perfect 1.0, so none is used for discovery
+    let strict = capped.replace("max_per_novel = 1", "min_source_score = 1.0");
+    let client = Arc::new(fake_royalroad());
+    let config = parse_config(&strict).unwrap();
+    let mut pipeline = Pipeline::with_client(config, client.clone()).unwrap();
+    let results = pipeline.run().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(client.request_count(&format!("{}90435", SIMILAR)), 0);
+}
+
+#[test]
+fn test_pipeline_records_rejections_with_reasons() {
+    // The seed passes; 89877 is too long, and 115399 is also neither
+    // tagged nor filed under Adventure
+    let strict = format!(
+        "{}\n[criteria]\nmax_pages = 1000\n\
+         required_tags = [\"Adventure\"]\nrequired_genres = [\"Adventure\"]\n",
+        CONFIG.replace("value = 3", "value = 10")
+    );
+    let path =
+        std::env::temp_dir().join(format!("novel-finder-rejects-{}.json", std::process::id()));
+    let config = parse_config(&strict).unwrap();
+    let mut pipeline = Pipeline::with_client(config, Arc::new(fake_royalroad())).unwrap();
+    pipeline.log_rejects_to(&path).unwrap();
+    let results = pipeline.run().unwrap();
+    let written = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+    assert_eq!(ids, vec![90435]);
+    let rejects = pipeline.rejects();
+    assert_eq!(rejects.total(), 2);
+    let reasons: HashMap<u64, String> = rejects
+        .recent()
+        .map(|rejection| (rejection.id, rejection.reasons_text()))
+        .collect();
+    assert_eq!(reasons[&89877], "1520 pages > max 1000");
+    assert_eq!(
+        reasons[&115399],
+        "missing required tag 'Adventure'; missing required genre 'Adventure'"
+    );
+
+    // The file holds the same rejections, each reason tagged by kind
+    let json: serde_json::Value = serde_json::from_str(&written).unwrap();
+    let rejected = json.as_array().unwrap();
+    assert_eq!(rejected.len(), 2);
+    let kinds: Vec<&str> = rejected
+        .iter()
+        .flat_map(|rejection| rejection["reasons"].as_array().unwrap())
+        .map(|reason| reason["reason"].as_str().unwrap())
+        .collect();
+    let mut kinds_sorted = kinds.clone();
+    kinds_sorted.sort_unstable();
+    assert_eq!(kinds_sorted, vec!["missing_genre", "missing_tag", "too_many_pages"]);
+}
+
+#[test]
+fn test_exported_dataset_rescores_like_the_run() {
+    // 89877 is rejected as too long, but still goes into the dataset
+    let strict = format!("{}\n[criteria]\nmax_pages = 1000\n", CONFIG);
+    let path =
+        std::env::temp_dir().join(format!("novel-finder-dataset-{}.jsonl", std::process::id()));
+    let config = parse_config(&strict).unwrap();
+    let mut pipeline = Pipeline::with_client(config, Arc::new(fake_royalroad())).unwrap();
+    pipeline.export_dataset_to(&path).unwrap();
+    let results = pipeline.run().unwrap();
+
+    let dataset = rescore::load_dataset(&path).unwrap();
+    let mut ids: Vec<u64> = dataset.novels.iter().map(|novel| novel.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![89877, 90435, 115399]);
+    assert!(!dataset.reviews[&90435].is_empty());
+
+    // Rescoring the dataset reproduces the run without a request
+    let rescored = rescore::rescore(&parse_config(&strict).unwrap(), &path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let scores = |results: &[NovelScore]| -> Vec<(u64, f64)> {
+        results.iter().map(|score| (score.novel.id, score.overall_score)).collect()
+    };
+    assert_eq!(scores(&rescored.results), scores(&results));
+    assert_eq!(rescored.results.len(), 2);
+}
+
+#[test]
+fn test_dataset_seeds_a_network_free_run() {
+    // Crawl once, rejecting 89877 as too long
+    let strict = format!("{}\n[criteria]\nmax_pages = 1000\n", CONFIG);
+    let path =
+        std::env::temp_dir().join(format!("novel-finder-seeds-{}.json", std::process::id()));
+    let config = parse_config(&strict).unwrap();
+    let mut pipeline = Pipeline::with_client(config, Arc::new(fake_royalroad())).unwrap();
+    pipeline.export_dataset_to(&path).unwrap();
+    let crawled = pipeline.run().unwrap();
+
+    // Evaluate again from the dataset alone: every novel, or only the rejected
+    let from_dataset = |filter: &str| {
+        let manual = "source = \"manual\"\n\
+                      urls = [\"https://www.royalroad.com/fiction/90435/bunny-girl-evolution\"]";
+        let seeds = format!("source = \"dataset\"\npath = {:?}\nfilter = {:?}", path, filter);
+        let config = CONFIG
+            .replace(manual, &seeds)
+            .replace("discovery_enabled = true", "discovery_enabled = false")
+            .replace("value = 3", "value = 10");
+        let mut pipeline =
+            Pipeline::with_client(parse_config(&config).unwrap(), Arc::new(OfflineClient))
+                .unwrap();
+        pipeline.run().unwrap()
+    };
+    let all = from_dataset("all");
+    let unevaluated = from_dataset("unevaluated");
+    std::fs::remove_file(&path).unwrap();
+
+    let scores = |results: &[NovelScore]| -> Vec<(u64, f64)> {
+        results.iter().map(|score| (score.novel.id, score.overall_score)).collect()
+    };
+    assert_eq!(all.len(), 3);
+    let evaluated_before: Vec<(u64, f64)> = scores(&all)
+        .into_iter()
+        .filter(|(id, _)| *id != 89877)
+        .collect();
+    assert_eq!(evaluated_before, scores(&crawled));
+    let ids: Vec<u64> = unevaluated.iter().map(|score| score.novel.id).collect();
+    assert_eq!(ids, vec![89877]);
+}
+
+/// One run of the fake site with `--deterministic`, as `--json` would print
+/// it, with a tag report.
+#[test]
+fn test_cancelled_run_returns_results_so_far() {
+    // Discovery from 89877 is slow; cancel while it's under way
+    let similar = format!("{}89877", SIMILAR);
+    let client = Arc::new(fake_royalroad().with_delay(&similar, Duration::from_millis(500)));
+    let config = CONFIG.replace("value = 3", "value = 10");
+    let mut pipeline = Pipeline::with_client(parse_config(&config).unwrap(), client.clone())
+        .unwrap();
+    let token = CancellationToken::new();
+    pipeline.cancel_with(token.clone());
+    let canceller = std::thread::spawn(move || {
+        while client.request_count(&similar) == 0 {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        token.cancel();
+        Instant::now()
+    });
+    let results = pipeline.run().unwrap();
+    let cancelled_at = canceller.join().unwrap();
+
+    // The novel under way finishes, and 115399 is left in the queue
+    assert!(cancelled_at.elapsed() < Duration::from_secs(2));
+    let ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+    assert_eq!(ids.len(), 2);
+    assert!(!ids.contains(&115399), "{:?}", ids);
+    assert!(pipeline.stats().cancelled);
+    let queue = std::env::temp_dir().join(format!("novel-finder-cancelled-{}", std::process::id()));
+    assert_eq!(pipeline.export_queue(&results, &queue).unwrap(), 1);
+    std::fs::remove_file(&queue).unwrap();
+}
+
+/// Records each event of a run as a line of text.
+struct RecordingObserver(Arc<Mutex<Vec<String>>>);
+
+impl RecordingObserver {
+    fn record(&self, event: String) -> Result<()> {
+        self.0.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+impl PipelineObserver for RecordingObserver {
+    fn on_seeded(&mut self, count: usize) -> Result<()> {
+        self.record(format!("seeded {}", count))
+    }
+
+    fn on_novel_scraped(&mut self, novel: &Novel, reviews: &[Review]) -> Result<()> {
+        self.record(format!("scraped {} with {} reviews", novel.id, reviews.len()))
+    }
+
+    fn on_prefilter_rejected(&mut self, novel: &Novel, reasons: &[FilterReason]) -> Result<()> {
+        let reasons: Vec<String> = reasons.iter().map(ToString::to_string).collect();
+        self.record(format!("rejected {}: {}", novel.id, reasons.join("; ")))
+    }
+
+    fn on_evaluated(&mut self, score: &NovelScore) -> Result<()> {
+        self.record(format!("evaluated {}", score.novel.id))
+    }
+
+    fn on_discovery(&mut self, novel: &Novel, added: usize) -> Result<()> {
+        self.record(format!("discovered {} from {}", added, novel.id))
+    }
+
+    fn on_finished(&mut self, stats: &RunStats) -> Result<()> {
+        self.record(format!("finished with {} evaluated", stats.novels_evaluated))
+    }
+}
+
+#[test]
+fn test_observers_see_each_step_of_the_run() {
+    let strict = format!("{}\n[criteria]\nmax_pages = 1000\n", CONFIG);
+    let config = parse_config(&strict).unwrap();
+    let mut pipeline = Pipeline::with_client(config, Arc::new(fake_royalroad())).unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    pipeline.add_observer(Box::new(RecordingObserver(Arc::clone(&events))));
+    pipeline.run().unwrap();
+
+    // 89877 is too long; 115399 has no recommendations page to discover from
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "seeded 1",
+            "scraped 90435 with 10 reviews",
+            "evaluated 90435",
+            "discovered 2 from 90435",
+            "rejected 89877: 1520 pages > max 1000",
+            "scraped 115399 with 0 reviews",
+            "evaluated 115399",
+            "discovered 0 from 115399",
+            "finished with 2 evaluated",
+        ]
+    );
+}
+
+fn deterministic_json(config: &str) -> String {
+    let config = parse_config(config).unwrap();
+    let tag_settings = config.output.tag_report.clone();
+    let mut pipeline = Pipeline::with_client(config, Arc::new(fake_royalroad())).unwrap();
+    pipeline
+        .make_deterministic(&Deterministic {
+            seed: None,
+            timestamp: "2025-01-07T10:09:50Z".parse().ok(),
+        })
+        .unwrap();
+    let results = pipeline.run().unwrap();
+    let tag_report = analysis::tag_report(&results, &tag_settings);
+    let metadata = pipeline.run_metadata();
+    output::json_document(&results, false, pipeline.stats(), &metadata, None, Some(&tag_report))
+        .unwrap()
+}
+
+#[test]
+fn test_deterministic_runs_print_identical_json() {
+    let first = deterministic_json(CONFIG);
+    assert_eq!(deterministic_json(CONFIG), first);
+    assert!(first.contains("\"finished_at\": \"2025-01-07T10:09:50Z\""), "{}", first);
+    assert!(first.contains("\"total_secs\": 0.0"), "{}", first);
+
+    // Clock-based limits can't be reproduced, so they're refused
+    let timed = CONFIG.replace(
+        "{ type = \"max_novels\", value = 3 }",
+        "{ type = \"max_time\", value = \"1h\" }",
+    );
+    let config = parse_config(&timed).unwrap();
+    let mut pipeline = Pipeline::with_client(config, Arc::new(fake_royalroad())).unwrap();
+    let deterministic = Deterministic {
+        timestamp: "2025-01-07T10:09:50Z".parse().ok(),
+        ..Default::default()
+    };
+    assert!(pipeline.make_deterministic(&deterministic).is_err());
+}
+
+#[test]
+fn test_pipeline_abandons_novels_past_deadline() {
+    // Sampling 89877's chapters for a word count estimate takes longer than
+    // the per-novel limit, so it's abandoned before evaluation
+    let client = Arc::new(fake_royalroad().with_delay(
+        &format!("{}/89877/cursed-explorer-of-the-arcana/chapter/", FICTION),
+        Duration::from_millis(1_100),
+    ));
+    let config = format!(
+        "{}max_seconds_per_novel = 1\n\n[scraper]\nestimate_word_count = true\n",
+        CONFIG
+    );
+    let config = parse_config(&config).unwrap();
+    let mut pipeline = Pipeline::with_client(config, client.clone()).unwrap();
+    let results = pipeline.run().unwrap();
+
+    let mut ids: Vec<u64> = results.iter().map(|score| score.novel.id).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![90435, 115399]);
+    assert_eq!(pipeline.stats().novels_timed_out, vec![89877]);
+    assert_eq!(pipeline.stats().novels_evaluated, 2);
+    // Its recommendations were never fetched
+    assert_eq!(client.request_count(&format!("{}89877", SIMILAR)), 0);
+}